@@ -193,5 +193,6 @@ pub fn build_state_response(
         folders,
         windows,
         hooks,
+        shares: Vec::new(),
     }
 }
@@ -0,0 +1,194 @@
+//! `ExecInTerminal` — run a command in a terminal's context and hand back its
+//! output and exit code.
+//!
+//! Split into prepare / run like content search: [`prepare_exec_in_terminal`]
+//! resolves everything that needs the workspace, then the caller runs the
+//! [`PreparedExec`] off its command loop. The daemon drives [`ExecMode::Shell`]
+//! itself (it needs the PTY output stream to feed a
+//! [`CommandCapture`](okena_terminal::terminal::CommandCapture));
+//! [`run_exec_process`] covers [`ExecMode::Process`].
+
+use super::{ActionResult, ensure_terminal};
+use crate::workspace::persistence::AppSettings;
+use crate::workspace::state::Workspace;
+use okena_core::api::{ApiExecMode, ApiExecResult, CommandResult};
+use okena_core::process::CommandSpec;
+use okena_terminal::TerminalsRegistry;
+use okena_terminal::backend::TerminalBackend;
+use okena_terminal::terminal::{CapturedCommand, Terminal};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Default `ExecInTerminal` timeout, matching `okena run --wait`.
+pub const DEFAULT_EXEC_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Cap on each returned stream in process mode; the tail is kept. Shell mode
+/// is bounded by `CommandCapture` itself.
+const MAX_EXEC_OUTPUT_BYTES: usize = 1024 * 1024;
+
+pub enum ExecMode {
+    /// Type the command into this terminal's shell and read its OSC 133 marks.
+    Shell(Arc<Terminal>),
+    /// Run a hidden one-shot process.
+    Process,
+}
+
+pub struct PreparedExec {
+    pub terminal_id: String,
+    pub command: String,
+    pub timeout: Duration,
+    pub mode: ExecMode,
+    /// The terminal's current working directory (OSC 7, else its spawn cwd).
+    pub cwd: String,
+    pub env: Vec<(String, String)>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn prepare_exec_in_terminal(
+    ws: &Workspace,
+    terminal_id: String,
+    command: String,
+    mirror: bool,
+    timeout_ms: Option<u64>,
+    backend: &dyn TerminalBackend,
+    terminals: &TerminalsRegistry,
+    settings: &AppSettings,
+) -> Result<PreparedExec, String> {
+    if command.trim().is_empty() {
+        return Err("command must not be empty".to_string());
+    }
+    let terminal = ensure_terminal(&terminal_id, terminals, backend, ws, settings)
+        .ok_or_else(|| format!("terminal not found: {terminal_id}"))?;
+    let cwd = terminal.current_cwd();
+    let mode = match (mirror, terminal.at_shell_prompt()) {
+        (true, Some(true)) => ExecMode::Shell(terminal),
+        (true, Some(false)) => {
+            return Err(format!("terminal {terminal_id} is still running a command"));
+        }
+        _ => ExecMode::Process,
+    };
+    Ok(PreparedExec {
+        env: vec![("OKENA_TERMINAL_ID".to_string(), terminal_id.clone())],
+        terminal_id,
+        command,
+        timeout: timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_EXEC_TIMEOUT),
+        mode,
+        cwd,
+    })
+}
+
+impl PreparedExec {
+    /// Type the command into the pane. Shell mode only; subscribe to the PTY
+    /// output before calling this so none of the command's output is missed.
+    pub fn send_to_shell(&self) {
+        if let ExecMode::Shell(terminal) = &self.mode {
+            terminal.send_input(&format!("{}\r", self.command));
+        }
+    }
+}
+
+/// Build the shell-mode reply once the capture saw the command's `D` mark.
+pub fn exec_result_from_capture(captured: CapturedCommand, started: Instant) -> CommandResult {
+    exec_reply(ApiExecResult {
+        stdout: captured.output,
+        stderr: String::new(),
+        exit_code: captured.exit_code,
+        duration_ms: started.elapsed().as_millis() as u64,
+        mode: ApiExecMode::Shell,
+        truncated: captured.truncated,
+    })
+}
+
+/// Run a process-mode exec to completion through the command bus. Blocking.
+pub fn run_exec_process(prepared: &PreparedExec) -> CommandResult {
+    let (program, flag) = if cfg!(windows) {
+        ("cmd".to_string(), "/C")
+    } else {
+        (
+            std::env::var("SHELL")
+                .ok()
+                .filter(|shell| !shell.is_empty())
+                .unwrap_or_else(|| "/bin/sh".to_string()),
+            "-c",
+        )
+    };
+    let mut spec = CommandSpec::new(program)
+        .arg(flag)
+        .arg(prepared.command.clone())
+        .current_dir(prepared.cwd.clone())
+        .timeout(prepared.timeout)
+        .label("exec_in_terminal");
+    for (key, value) in &prepared.env {
+        spec = spec.env(key.clone(), value.clone());
+    }
+
+    let started = Instant::now();
+    let output = match okena_core::process::run(spec) {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+            return CommandResult::Err(format!(
+                "command timed out after {} s",
+                prepared.timeout.as_secs()
+            ));
+        }
+        Err(e) => return CommandResult::Err(format!("failed to run command: {e}")),
+    };
+    let (stdout, stdout_truncated) = tail_lossy(&output.stdout);
+    let (stderr, stderr_truncated) = tail_lossy(&output.stderr);
+    exec_reply(ApiExecResult {
+        stdout,
+        stderr,
+        exit_code: output.status.code(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        mode: ApiExecMode::Process,
+        truncated: stdout_truncated || stderr_truncated,
+    })
+}
+
+/// Synchronous `execute_action` path. There is no PTY stream to watch here,
+/// so it always runs as a hidden process; the daemon intercepts the action
+/// before it gets this far.
+pub(super) fn exec_in_terminal(
+    ws: &Workspace,
+    terminal_id: String,
+    command: String,
+    timeout_ms: Option<u64>,
+    backend: &dyn TerminalBackend,
+    terminals: &TerminalsRegistry,
+    settings: &AppSettings,
+) -> ActionResult {
+    match prepare_exec_in_terminal(
+        ws,
+        terminal_id,
+        command,
+        false,
+        timeout_ms,
+        backend,
+        terminals,
+        settings,
+    ) {
+        Ok(prepared) => match run_exec_process(&prepared) {
+            CommandResult::Err(e) => ActionResult::Err(e),
+            CommandResult::Ok(value) => ActionResult::Ok(value),
+            _ => ActionResult::Ok(None),
+        },
+        Err(e) => ActionResult::Err(e),
+    }
+}
+
+fn exec_reply(result: ApiExecResult) -> CommandResult {
+    match serde_json::to_value(result) {
+        Ok(value) => CommandResult::Ok(Some(value)),
+        Err(e) => CommandResult::Err(format!("failed to encode exec result: {e}")),
+    }
+}
+
+fn tail_lossy(bytes: &[u8]) -> (String, bool) {
+    let start = bytes.len().saturating_sub(MAX_EXEC_OUTPUT_BYTES);
+    (
+        String::from_utf8_lossy(&bytes[start..]).into_owned(),
+        start > 0,
+    )
+}
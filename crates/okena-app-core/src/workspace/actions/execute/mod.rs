@@ -9,6 +9,7 @@
 // unreachable for well-formed types, and callers cannot recover anyway.
 #![allow(clippy::expect_used)]
 
+mod exec;
 mod files;
 mod git;
 mod project;
@@ -27,9 +28,13 @@ use okena_terminal::backend::{TerminalBackend, TerminalLaunchPlan};
 use okena_terminal::shell_config::ShellType;
 use okena_terminal::terminal::{Terminal, TerminalSize};
 use okena_workspace::context::WorkspaceCx;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
+pub use exec::{
+    DEFAULT_EXEC_TIMEOUT, ExecMode, PreparedExec, exec_result_from_capture,
+    prepare_exec_in_terminal, run_exec_process,
+};
 pub use files::{
     PreparedContentSearch, execute_prepared_content_search,
     execute_prepared_content_search_with_cancellation, prepare_content_search,
@@ -84,10 +89,28 @@ pub fn execute_action(
 ) -> ActionResult {
     match action {
         // ── Terminal ops ─────────────────────────────────────────────
-        ActionRequest::CreateTerminal { project_id } => terminal::create(
+        ActionRequest::CreateTerminal {
+            project_id,
+            shell,
+            cwd,
+            command,
+            env,
+            name,
+            minimized,
+            placement,
+        } => terminal::create(
             ws,
             focus_manager,
             project_id,
+            terminal::NewTerminal {
+                shell,
+                cwd,
+                command,
+                env,
+                name,
+                minimized,
+                placement,
+            },
             backend,
             terminals,
             settings,
@@ -224,12 +247,53 @@ pub fn execute_action(
             project_id,
             terminal_id,
         } => project::dismiss_hook(ws, project_id, terminal_id, backend, terminals, cx),
-        ActionRequest::ReadContent { terminal_id } => {
-            terminal::read_content(ws, terminal_id, backend, terminals, settings)
+        ActionRequest::CancelHook { execution_id } => {
+            project::cancel_hook(execution_id, backend, cx)
+        }
+        ActionRequest::ListHookRuns { hook_type, limit } => {
+            project::list_hook_runs(hook_type, limit, cx)
         }
+        ActionRequest::GetHookRun { run_id } => project::get_hook_run(run_id, cx),
+        ActionRequest::DiffHookRuns {
+            base_run_id,
+            run_id,
+        } => project::diff_hook_runs(base_run_id, run_id, cx),
+        ActionRequest::ReadContent {
+            terminal_id,
+            lines,
+            all,
+            ansi,
+            since,
+            strip_prompt,
+        } => terminal::read_content(
+            ws,
+            terminal_id,
+            lines,
+            all,
+            ansi,
+            since,
+            strip_prompt,
+            backend,
+            terminals,
+            settings,
+        ),
         ActionRequest::ExportBuffer { terminal_id } => {
             terminal::export_buffer(terminal_id, backend)
         }
+        ActionRequest::ExecInTerminal {
+            terminal_id,
+            command,
+            mirror: _,
+            timeout_ms,
+        } => exec::exec_in_terminal(
+            ws,
+            terminal_id,
+            command,
+            timeout_ms,
+            backend,
+            terminals,
+            settings,
+        ),
 
         // ── Tab / pane-move ops ──────────────────────────────────────
         ActionRequest::AddTab {
@@ -627,6 +691,9 @@ pub fn execute_action(
         ActionRequest::StartService { .. }
         | ActionRequest::StopService { .. }
         | ActionRequest::RestartService { .. }
+        | ActionRequest::RebuildService { .. }
+        | ActionRequest::PullService { .. }
+        | ActionRequest::ExecServiceShell { .. }
         | ActionRequest::StartAllServices { .. }
         | ActionRequest::StopAllServices { .. }
         | ActionRequest::ReloadServices { .. } => {
@@ -749,11 +816,6 @@ pub fn reserve_uninitialized_terminal_launches(
         }
         let shell_wrapper =
             hooks::resolve_shell_wrapper(&project_hooks, parent_hooks.as_ref(), &settings.hooks);
-        let on_create = hooks::resolve_terminal_on_create_simple(
-            &project_hooks,
-            parent_hooks.as_ref(),
-            &settings.hooks,
-        );
         let folder = ws.folder_for_project_or_parent(project_id);
         let env = hooks::terminal_hook_env(
             project_id,
@@ -763,6 +825,12 @@ pub fn reserve_uninitialized_terminal_launches(
             folder.map(|folder| folder.id.as_str()),
             folder.map(|folder| folder.name.as_str()),
         );
+        let on_create = hooks::resolve_terminal_on_create_simple(
+            &project_hooks,
+            parent_hooks.as_ref(),
+            &settings.hooks,
+            &env,
+        );
         for (path, shell_type) in uninitialized {
             launches.push(PreparedTerminalLaunch::new(
                 project_id.clone(),
@@ -870,10 +938,44 @@ pub fn spawn_uninitialized_terminals(
         return ActionResult::Ok(None);
     }
 
-    let project = match ws.project(project_id) {
-        Some(p) => p,
-        None => return ActionResult::Err(format!("project not found: {}", project_id)),
-    };
+    match spawn_uninitialized_terminals_with_env(
+        ws,
+        project_id,
+        backend,
+        terminals,
+        settings,
+        inherit_cwd,
+        &BTreeMap::new(),
+        cx,
+    ) {
+        // Always return terminal_ids — even when empty — so callers know the action completed
+        Ok(spawned_ids) => {
+            ActionResult::Ok(Some(serde_json::json!({ "terminal_ids": spawned_ids })))
+        }
+        Err(e) => ActionResult::Err(e),
+    }
+}
+
+/// [`spawn_uninitialized_terminals`] with `extra_env` set in the new PTYs
+/// (overriding the hook environment), returning the spawned ids.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn spawn_uninitialized_terminals_with_env(
+    ws: &mut Workspace,
+    project_id: &str,
+    backend: &dyn TerminalBackend,
+    terminals: &TerminalsRegistry,
+    settings: &AppSettings,
+    inherit_cwd: Option<String>,
+    extra_env: &BTreeMap<String, String>,
+    cx: &mut impl WorkspaceCx,
+) -> Result<Vec<String>, String> {
+    if ws.is_creating_project(project_id) {
+        return Ok(Vec::new());
+    }
+
+    let project = ws
+        .project(project_id)
+        .ok_or_else(|| format!("project not found: {}", project_id))?;
 
     let project_path = project.path.clone();
     // The directory new PTYs actually spawn in: the inherited (source-terminal)
@@ -904,11 +1006,6 @@ pub fn spawn_uninitialized_terminals(
     // Resolve shell_wrapper and on_create once for all terminals in this project
     let shell_wrapper =
         hooks::resolve_shell_wrapper(&project_hooks, parent_hooks.as_ref(), &global_hooks);
-    let on_create_cmd = hooks::resolve_terminal_on_create_simple(
-        &project_hooks,
-        parent_hooks.as_ref(),
-        &global_hooks,
-    );
     let folder = ws.folder_for_project_or_parent(project_id);
     let folder_id = folder.map(|f| f.id.as_str());
     let folder_name = folder.map(|f| f.name.as_str());
@@ -920,6 +1017,12 @@ pub fn spawn_uninitialized_terminals(
         folder_id,
         folder_name,
     );
+    let on_create_cmd = hooks::resolve_terminal_on_create_simple(
+        &project_hooks,
+        parent_hooks.as_ref(),
+        &global_hooks,
+        &env,
+    );
 
     let mut spawned_ids = Vec::new();
     for (path, shell_type) in uninitialized {
@@ -931,6 +1034,14 @@ pub fn spawn_uninitialized_terminals(
             on_create_cmd.as_deref(),
             &env,
         );
+        let plan = if extra_env.is_empty() {
+            plan
+        } else {
+            let mut environment = plan.environment.clone();
+            environment.retain(|(key, _)| !extra_env.contains_key(key));
+            environment.extend(extra_env.iter().map(|(k, v)| (k.clone(), v.clone())));
+            plan.with_environment(environment)
+        };
 
         match backend.create_terminal_with_plan(&spawn_cwd, &plan) {
             Ok(terminal_id) => {
@@ -947,13 +1058,12 @@ pub fn spawn_uninitialized_terminals(
             }
             Err(e) => {
                 log::error!("Failed to spawn terminal for project {}: {}", project_id, e);
-                return ActionResult::Err(format!("failed to spawn terminal: {}", e));
+                return Err(format!("failed to spawn terminal: {}", e));
             }
         }
     }
 
-    // Always return terminal_ids — even when empty — so callers know the action completed
-    ActionResult::Ok(Some(serde_json::json!({ "terminal_ids": spawned_ids })))
+    Ok(spawned_ids)
 }
 
 /// Find the first terminal_id in a layout tree (depth-first).
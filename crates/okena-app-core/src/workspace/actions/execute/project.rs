@@ -10,6 +10,7 @@ use crate::workspace::focus::FocusManager;
 use crate::workspace::persistence::AppSettings;
 use crate::workspace::state::{HookTerminalStatus, WindowId, Workspace};
 use okena_core::theme::FolderColor;
+use okena_hooks::HookRunStore;
 use okena_terminal::TerminalsRegistry;
 use okena_terminal::backend::TerminalBackend;
 use okena_terminal::terminal::{Terminal, TerminalSize};
@@ -17,6 +18,9 @@ use okena_workspace::context::WorkspaceCx;
 use okena_workspace::hook_monitor::HookStatus;
 use std::sync::Arc;
 
+/// Runs returned by `ListHookRuns` when the request carries no limit.
+const DEFAULT_HOOK_RUN_LIMIT: usize = 50;
+
 fn project_not_found(project_id: &str) -> ActionResult {
     ActionResult::Err(format!("project not found: {}", project_id))
 }
@@ -662,9 +666,80 @@ pub(super) fn dismiss_hook(
     ActionResult::Ok(None)
 }
 
+/// Cancel a running hook execution by monitor id. Headless hooks are killed by
+/// the monitor; a terminal-backed hook's PTY is killed here and its terminal
+/// stays in the project so the partial output can still be read.
+pub(super) fn cancel_hook(
+    execution_id: u64,
+    backend: &dyn TerminalBackend,
+    cx: &mut impl WorkspaceCx,
+) -> ActionResult {
+    let Some(monitor) = cx.hook_monitor() else {
+        return ActionResult::Err("hook monitor unavailable".to_string());
+    };
+    match monitor.cancel(execution_id) {
+        Ok(terminal_id) => {
+            if let Some(terminal_id) = terminal_id.as_deref() {
+                backend.kill(terminal_id);
+            }
+            ActionResult::Ok(None)
+        }
+        Err(e) => ActionResult::Err(e),
+    }
+}
+
+fn hook_run_store(cx: &impl WorkspaceCx) -> Result<HookRunStore, String> {
+    cx.hook_monitor()
+        .and_then(|monitor| monitor.run_store())
+        .ok_or_else(|| "hook run logs are not enabled".to_string())
+}
+
+fn to_json_result(value: Result<serde_json::Value, String>) -> ActionResult {
+    match value {
+        Ok(json) => ActionResult::Ok(Some(json)),
+        Err(e) => ActionResult::Err(e),
+    }
+}
+
+/// Persisted hook runs, newest first (output stripped).
+pub(super) fn list_hook_runs(
+    hook_type: Option<String>,
+    limit: Option<usize>,
+    cx: &impl WorkspaceCx,
+) -> ActionResult {
+    to_json_result(hook_run_store(cx).map(|store| {
+        serde_json::json!(store.list(
+            hook_type.as_deref(),
+            limit.unwrap_or(DEFAULT_HOOK_RUN_LIMIT),
+        ))
+    }))
+}
+
+/// One persisted hook run including its captured stdout/stderr.
+pub(super) fn get_hook_run(run_id: u64, cx: &impl WorkspaceCx) -> ActionResult {
+    to_json_result(hook_run_store(cx).and_then(|store| {
+        store
+            .get(run_id)
+            .map(|run| serde_json::json!(run))
+            .ok_or_else(|| format!("hook run {} not found", run_id))
+    }))
+}
+
+/// Unified diff of the captured output of two persisted hook runs.
+pub(super) fn diff_hook_runs(base_run_id: u64, run_id: u64, cx: &impl WorkspaceCx) -> ActionResult {
+    to_json_result(
+        hook_run_store(cx)
+            .and_then(|store| store.diff(base_run_id, run_id))
+            .map(|diff| serde_json::json!(diff)),
+    )
+}
+
 #[cfg(test)]
 mod hook_action_tests {
-    use super::{ActionResult, delete_project, dismiss_hook, remove_worktree_project, rerun_hook};
+    use super::{
+        ActionResult, cancel_hook, delete_project, dismiss_hook, remove_worktree_project,
+        rerun_hook,
+    };
     use crate::workspace::focus::FocusManager;
     use crate::workspace::state::{
         HookTerminalEntry, HookTerminalStatus, ProjectData, WindowState, Workspace, WorkspaceData,
@@ -960,6 +1035,42 @@ mod hook_action_tests {
         assert_eq!(backend.killed.lock().unwrap().as_slice(), ["hook-1"]);
     }
 
+    #[test]
+    fn cancel_hook_kills_the_terminal_and_keeps_the_hook_owner() {
+        let workspace = workspace_with_hook("hook-1");
+        let backend = RecordingBackend::default();
+        let monitor = HookMonitor::new();
+        let id = monitor.record_start(
+            "on_project_open",
+            "sleep 600",
+            "Project p1",
+            Some("hook-1".to_string()),
+        );
+        let mut cx = TestCx {
+            monitor: monitor.clone(),
+        };
+
+        let result = cancel_hook(id, &backend, &mut cx);
+
+        assert!(matches!(result, ActionResult::Ok(None)));
+        assert_eq!(backend.killed.lock().unwrap().as_slice(), ["hook-1"]);
+        assert!(matches!(
+            monitor.status(id),
+            Some(HookStatus::Cancelled { .. })
+        ));
+        assert!(
+            workspace
+                .project("p1")
+                .unwrap()
+                .hook_terminals
+                .contains_key("hook-1")
+        );
+        assert!(matches!(
+            cancel_hook(id, &backend, &mut cx),
+            ActionResult::Err(message) if message.contains("not running")
+        ));
+    }
+
     #[test]
     fn rerun_spawn_failure_preserves_the_existing_hook_owner() {
         let mut workspace = workspace_with_hook("old-hook");
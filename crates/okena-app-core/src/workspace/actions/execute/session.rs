@@ -250,11 +250,6 @@ pub fn prepare_workspace_replacement(
             parent_hooks.as_ref(),
             &settings.hooks,
         );
-        let on_create = crate::workspace::hooks::resolve_terminal_on_create_simple(
-            &project.hooks,
-            parent_hooks.as_ref(),
-            &settings.hooks,
-        );
         let folder = lookup.folder_for_project_or_parent(&project.id);
         let env = crate::workspace::hooks::terminal_hook_env(
             &project.id,
@@ -264,6 +259,12 @@ pub fn prepare_workspace_replacement(
             folder.map(|folder| folder.id.as_str()),
             folder.map(|folder| folder.name.as_str()),
         );
+        let on_create = crate::workspace::hooks::resolve_terminal_on_create_simple(
+            &project.hooks,
+            parent_hooks.as_ref(),
+            &settings.hooks,
+            &env,
+        );
         if let Some(layout) = &mut project.layout {
             prepare_layout_terminals(
                 layout,
@@ -429,7 +430,9 @@ pub fn materialize_workspace_replacement(
             .hook_runner
             .as_ref()
             .ok_or_else(|| "hook runner unavailable".to_string())
-            .and_then(|runner| runner.launch_prepared_terminal(prepared_hook));
+            .and_then(|runner| {
+                runner.launch_prepared_terminal(prepared_hook, plan.hook_monitor.as_ref())
+            });
         if let Err(error) = launch {
             prepared_hook.finish_failed_monitor(plan.hook_monitor.as_ref());
             failed_hooks.push(result.terminal_id.clone());
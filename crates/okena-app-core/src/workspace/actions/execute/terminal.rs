@@ -5,12 +5,14 @@
 // more than it clarifies here.
 #![allow(clippy::too_many_arguments)]
 
-use super::{ActionResult, ensure_terminal, find_terminal_path, spawn_uninitialized_terminals};
+use super::{
+    ActionResult, ensure_terminal, find_terminal_path, spawn_uninitialized_terminals,
+    spawn_uninitialized_terminals_with_env,
+};
 use crate::workspace::focus::FocusManager;
 use crate::workspace::persistence::AppSettings;
-use crate::workspace::state::Workspace;
-use alacritty_terminal::grid::Dimensions;
-use alacritty_terminal::index::{Column, Line, Point};
+use crate::workspace::state::{LayoutNode, Workspace};
+use okena_core::api::{ApiReadContent, TerminalPlacement};
 use okena_core::keys::SpecialKey;
 use okena_core::types::SplitDirection;
 use okena_terminal::TerminalsRegistry;
@@ -18,7 +20,9 @@ use okena_terminal::backend::TerminalBackend;
 use okena_terminal::shell_config::ShellType;
 use okena_terminal::terminal::Terminal;
 use okena_terminal::terminal::TerminalSize;
+use okena_terminal::terminal::{ReadOptions, ReadRange};
 use okena_workspace::context::WorkspaceCx;
+use std::collections::BTreeMap;
 
 fn with_ensured_terminal(
     ws: &Workspace,
@@ -34,31 +38,140 @@ fn with_ensured_terminal(
     }
 }
 
+/// `CreateTerminal`'s optional parameters (see the action's docs).
+pub(super) struct NewTerminal {
+    pub shell: Option<ShellType>,
+    pub cwd: Option<String>,
+    pub command: Option<String>,
+    pub env: BTreeMap<String, String>,
+    pub name: Option<String>,
+    pub minimized: bool,
+    pub placement: Option<TerminalPlacement>,
+}
+
 pub(super) fn create(
     ws: &mut Workspace,
     focus_manager: &mut FocusManager,
     project_id: String,
+    new: NewTerminal,
     backend: &dyn TerminalBackend,
     terminals: &TerminalsRegistry,
     settings: &AppSettings,
     cx: &mut impl WorkspaceCx,
 ) -> ActionResult {
-    // Open in the focused terminal's cwd (when one is focused in this project),
-    // else the project path.
-    let inherit_cwd = focus_manager
-        .focused_terminal_state()
-        .filter(|f| f.project_id == project_id)
-        .and_then(|f| super::inherited_cwd(ws, terminals, &project_id, &f.layout_path));
-    ws.add_terminal(focus_manager, &project_id, cx);
-    spawn_uninitialized_terminals(
+    // Panes of a project that is still being created spawn later with their
+    // defaults, which would drop the requested launch parameters.
+    if ws.is_creating_project(&project_id)
+        && (new.cwd.is_some() || new.command.is_some() || !new.env.is_empty())
+    {
+        return ActionResult::Err(format!(
+            "project {} is still being created; retry once it is ready",
+            project_id
+        ));
+    }
+    // Resolve the placement anchor before the layout mutation moves it.
+    let anchor = match &new.placement {
+        Some(
+            TerminalPlacement::Split { terminal_id, .. } | TerminalPlacement::Tab { terminal_id },
+        ) => match find_terminal_path(ws, &project_id, terminal_id) {
+            Some(path) => Some(path),
+            None => return ActionResult::Err(format!("terminal not found: {}", terminal_id)),
+        },
+        Some(TerminalPlacement::Detached) | None => None,
+    };
+    // Spawn slots that were already waiting with their own defaults first, so
+    // the request's cwd and env below reach only the new pane.
+    let mut spawned_ids = match spawn_uninitialized_terminals_with_env(
+        ws,
+        &project_id,
+        backend,
+        terminals,
+        settings,
+        None,
+        &BTreeMap::new(),
+        cx,
+    ) {
+        Ok(ids) => ids,
+        Err(e) => return ActionResult::Err(e),
+    };
+    // An explicit cwd wins; otherwise open in the anchor's live cwd, or the
+    // focused terminal's (when one is focused in this project), else the
+    // project path.
+    let inherit_cwd = match (&new.cwd, &anchor) {
+        (Some(cwd), _) => Some(cwd.clone()),
+        (None, Some(path)) => super::inherited_cwd(ws, terminals, &project_id, path),
+        (None, None) => focus_manager
+            .focused_terminal_state()
+            .filter(|f| f.project_id == project_id)
+            .and_then(|f| super::inherited_cwd(ws, terminals, &project_id, &f.layout_path)),
+    };
+
+    match (&new.placement, anchor) {
+        (Some(TerminalPlacement::Split { direction, .. }), Some(path)) => {
+            ws.split_terminal(focus_manager, &project_id, &path, *direction, cx)
+        }
+        (Some(TerminalPlacement::Tab { .. }), Some(path)) => {
+            ws.add_tab(focus_manager, &project_id, &path, cx)
+        }
+        _ => ws.add_terminal(focus_manager, &project_id, cx),
+    }
+    // Every layout op above leaves the new node as the uninitialized one.
+    let new_path = ws
+        .project(&project_id)
+        .and_then(|p| p.layout.as_ref())
+        .and_then(|l| l.find_uninitialized_terminal_path());
+    if let (Some(shell), Some(path)) = (new.shell, &new_path) {
+        ws.set_terminal_shell(&project_id, path, shell, cx);
+    }
+
+    if ws.is_creating_project(&project_id) {
+        return ActionResult::Ok(None);
+    }
+    match spawn_uninitialized_terminals_with_env(
         ws,
         &project_id,
         backend,
         terminals,
         settings,
         inherit_cwd,
+        &new.env,
         cx,
-    )
+    ) {
+        Ok(ids) => spawned_ids.extend(ids),
+        Err(e) => return ActionResult::Err(e),
+    }
+    let new_node = new_path
+        .as_deref()
+        .and_then(|path| ws.project(&project_id)?.layout.as_ref()?.get_at_path(path));
+    let terminal_id = match new_node {
+        Some(LayoutNode::Terminal { terminal_id, .. }) => terminal_id.clone(),
+        _ => None,
+    };
+
+    if let Some(terminal_id) = &terminal_id {
+        if let Some(name) = new.name {
+            ws.rename_terminal(&project_id, terminal_id, name, cx);
+        }
+        if new.minimized {
+            ws.toggle_terminal_minimized_by_id(&project_id, terminal_id, cx);
+        }
+        if matches!(new.placement, Some(TerminalPlacement::Detached))
+            && let Some(path) = find_terminal_path(ws, &project_id, terminal_id)
+        {
+            ws.detach_terminal(&project_id, &path, cx);
+        }
+        if let Some(command) = new.command {
+            let term = terminals.lock().get(terminal_id).cloned();
+            if let Some(term) = term {
+                term.send_input(&format!("{}\r", command));
+            }
+        }
+    }
+
+    ActionResult::Ok(Some(serde_json::json!({
+        "terminal_id": terminal_id,
+        "terminal_ids": spawned_ids,
+    })))
 }
 
 pub(super) fn split(
@@ -322,34 +435,37 @@ pub(super) fn rename(
 pub(super) fn read_content(
     ws: &mut Workspace,
     terminal_id: String,
+    lines: Option<usize>,
+    all: bool,
+    ansi: bool,
+    since: Option<u64>,
+    strip_prompt: bool,
     backend: &dyn TerminalBackend,
     terminals: &TerminalsRegistry,
     settings: &AppSettings,
 ) -> ActionResult {
+    let range = match (all, lines) {
+        (true, _) => ReadRange::All,
+        (false, Some(n)) => ReadRange::Last(n),
+        (false, None) => ReadRange::Screen,
+    };
+    let options = ReadOptions {
+        range,
+        ansi,
+        since,
+        strip_prompt,
+    };
     with_ensured_terminal(ws, &terminal_id, backend, terminals, settings, |term| {
-        let content = term.with_content(|term| {
-            let grid = term.grid();
-            let screen_lines = grid.screen_lines();
-            let cols = grid.columns();
-            let mut lines = Vec::with_capacity(screen_lines);
-
-            for row in 0..screen_lines as i32 {
-                let mut line = String::with_capacity(cols);
-                for col in 0..cols {
-                    let cell = &grid[Point::new(Line(row), Column(col))];
-                    line.push(cell.c);
-                }
-                let trimmed = line.trim_end().to_string();
-                lines.push(trimmed);
-            }
-
-            while lines.last().is_some_and(|l| l.is_empty()) {
-                lines.pop();
-            }
-
-            lines.join("\n")
-        });
-        ActionResult::Ok(Some(serde_json::json!({"content": content})))
+        let out = term.read_text(&options);
+        let reply = ApiReadContent {
+            content: out.content,
+            sequence: out.sequence,
+            truncated: out.truncated,
+        };
+        match serde_json::to_value(reply) {
+            Ok(value) => ActionResult::Ok(Some(value)),
+            Err(e) => ActionResult::Err(format!("failed to encode read result: {e}")),
+        }
     })
 }
 
@@ -368,3 +484,420 @@ pub(super) fn export_buffer(terminal_id: String, backend: &dyn TerminalBackend)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::execute_action;
+    use super::*;
+    use crate::workspace::settings::HooksConfig;
+    use crate::workspace::state::{ProjectData, WindowId, WindowState, WorkspaceData};
+    use okena_core::api::ActionRequest;
+    use okena_terminal::backend::TerminalLaunchPlan;
+    use okena_terminal::terminal::TerminalTransport;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct RecordingTransport {
+        input: Mutex<Vec<(String, String)>>,
+    }
+
+    impl TerminalTransport for RecordingTransport {
+        fn send_input(&self, terminal_id: &str, data: &[u8]) {
+            self.input.lock().expect("input lock").push((
+                terminal_id.to_string(),
+                String::from_utf8_lossy(data).into_owned(),
+            ));
+        }
+        fn resize(&self, _terminal_id: &str, _cols: u16, _rows: u16) {}
+        fn uses_mouse_backend(&self) -> bool {
+            false
+        }
+    }
+
+    /// Spawns `new-1`, `new-2`, ... and records where and how.
+    #[derive(Default)]
+    struct RecordingBackend {
+        transport: Arc<RecordingTransport>,
+        launches: Mutex<Vec<(String, TerminalLaunchPlan)>>,
+    }
+
+    impl TerminalBackend for RecordingBackend {
+        fn transport(&self) -> Arc<dyn TerminalTransport> {
+            self.transport.clone()
+        }
+
+        fn create_terminal(
+            &self,
+            _cwd: &str,
+            _shell: Option<&ShellType>,
+        ) -> anyhow::Result<String> {
+            unreachable!("create goes through create_terminal_with_plan")
+        }
+
+        fn create_terminal_with_plan(
+            &self,
+            cwd: &str,
+            plan: &TerminalLaunchPlan,
+        ) -> anyhow::Result<String> {
+            let mut launches = self.launches.lock().expect("launch lock");
+            launches.push((cwd.to_string(), plan.clone()));
+            Ok(format!("new-{}", launches.len()))
+        }
+
+        fn reconnect_terminal(
+            &self,
+            terminal_id: &str,
+            _cwd: &str,
+            _shell: Option<&ShellType>,
+        ) -> anyhow::Result<String> {
+            Ok(terminal_id.to_string())
+        }
+
+        fn kill(&self, _terminal_id: &str) {}
+        fn capture_buffer(&self, _terminal_id: &str) -> Option<std::path::PathBuf> {
+            None
+        }
+        fn supports_buffer_capture(&self) -> bool {
+            false
+        }
+        fn is_remote(&self) -> bool {
+            false
+        }
+        fn get_shell_pid(&self, _terminal_id: &str) -> Option<u32> {
+            None
+        }
+        fn get_service_pids(&self, _terminal_id: &str) -> Vec<u32> {
+            Vec::new()
+        }
+    }
+
+    struct TestCx;
+
+    impl WorkspaceCx for TestCx {
+        fn notify(&mut self) {}
+        fn refresh_views(&mut self) {}
+        fn hook_runner(&self) -> Option<crate::workspace::hooks::HookRunner> {
+            None
+        }
+        fn hook_monitor(&self) -> Option<crate::workspace::hook_monitor::HookMonitor> {
+            None
+        }
+    }
+
+    fn terminal(id: &str) -> LayoutNode {
+        LayoutNode::Terminal {
+            terminal_id: Some(id.to_string()),
+            shell_type: ShellType::Default,
+            minimized: false,
+            detached: false,
+            zoom_level: 1.0,
+        }
+    }
+
+    fn workspace(layout: LayoutNode) -> Workspace {
+        let project = ProjectData {
+            id: "p".into(),
+            name: "P".into(),
+            path: "/project".into(),
+            layout: Some(layout),
+            terminal_names: HashMap::new(),
+            hidden_terminals: HashMap::new(),
+            worktree_info: None,
+            worktree_ids: Vec::new(),
+            folder_color: Default::default(),
+            hooks: HooksConfig::default(),
+            is_remote: false,
+            connection_id: None,
+            service_terminals: HashMap::new(),
+            default_shell: None,
+            hook_terminals: HashMap::new(),
+            pinned: false,
+            last_activity_at: None,
+            is_creating: false,
+            is_closing: false,
+            creating_progress: None,
+        };
+        Workspace::new(WorkspaceData {
+            version: 1,
+            projects: vec![project],
+            project_order: vec!["p".into()],
+            folders: Vec::new(),
+            service_panel_heights: HashMap::new(),
+            hook_panel_heights: HashMap::new(),
+            main_window: WindowState::default(),
+            extra_windows: Vec::new(),
+        })
+    }
+
+    fn create_terminal(cwd: Option<&str>, placement: Option<TerminalPlacement>) -> ActionRequest {
+        ActionRequest::CreateTerminal {
+            project_id: "p".into(),
+            shell: None,
+            cwd: cwd.map(str::to_string),
+            command: None,
+            env: BTreeMap::new(),
+            name: None,
+            minimized: false,
+            placement,
+        }
+    }
+
+    /// Runs `action` against `ws`, with `t1` live in `/project/src`, and
+    /// returns the reply's `terminal_id`.
+    fn run(ws: &mut Workspace, backend: &RecordingBackend, action: ActionRequest) -> String {
+        let terminals: TerminalsRegistry = Default::default();
+        terminals.lock().insert(
+            "t1".to_string(),
+            Arc::new(Terminal::new(
+                "t1".to_string(),
+                TerminalSize::default(),
+                backend.transport(),
+                "/project/src".to_string(),
+            )),
+        );
+        let result = execute_action(
+            action,
+            ws,
+            WindowId::Main,
+            &mut FocusManager::default(),
+            backend,
+            &terminals,
+            &AppSettings::default(),
+            &mut TestCx,
+        );
+        let ActionResult::Ok(Some(reply)) = result else {
+            panic!("create_terminal failed");
+        };
+        let id = reply["terminal_id"]
+            .as_str()
+            .expect("terminal_id in reply")
+            .to_string();
+        assert_eq!(reply["terminal_ids"], serde_json::json!([id]));
+        id
+    }
+
+    fn layout(ws: &Workspace) -> &LayoutNode {
+        ws.project("p")
+            .and_then(|project| project.layout.as_ref())
+            .expect("project layout")
+    }
+
+    fn spawn_cwds(backend: &RecordingBackend) -> Vec<String> {
+        let launches = backend.launches.lock().expect("launch lock");
+        launches.iter().map(|(cwd, _)| cwd.clone()).collect()
+    }
+
+    #[test]
+    fn split_placement_splits_the_anchor_in_its_cwd() {
+        let mut ws = workspace(terminal("t1"));
+        let backend = RecordingBackend::default();
+        let action = ActionRequest::CreateTerminal {
+            project_id: "p".into(),
+            shell: None,
+            cwd: None,
+            command: Some("npm run dev".into()),
+            env: BTreeMap::from([("PORT".to_string(), "3000".to_string())]),
+            name: Some("dev".into()),
+            minimized: false,
+            placement: Some(TerminalPlacement::Split {
+                terminal_id: "t1".into(),
+                direction: SplitDirection::Horizontal,
+            }),
+        };
+
+        let id = run(&mut ws, &backend, action);
+
+        assert_eq!(id, "new-1");
+        let LayoutNode::Split {
+            direction: SplitDirection::Horizontal,
+            children,
+            ..
+        } = layout(&ws)
+        else {
+            panic!("expected a horizontal split, got {:?}", layout(&ws));
+        };
+        assert_eq!(children, &[terminal("t1"), terminal("new-1")]);
+        assert_eq!(spawn_cwds(&backend), ["/project/src"]);
+        let launches = backend.launches.lock().expect("launch lock");
+        assert!(
+            launches[0]
+                .1
+                .environment
+                .contains(&("PORT".to_string(), "3000".to_string()))
+        );
+        assert_eq!(
+            ws.project("p").unwrap().terminal_names.get("new-1"),
+            Some(&"dev".to_string())
+        );
+        assert_eq!(
+            backend
+                .transport
+                .input
+                .lock()
+                .expect("input lock")
+                .as_slice(),
+            [("new-1".to_string(), "npm run dev\r".to_string())]
+        );
+    }
+
+    #[test]
+    fn tab_placement_joins_the_anchor_tab_group() {
+        let mut ws = workspace(LayoutNode::Tabs {
+            children: vec![terminal("t1"), terminal("t2")],
+            active_tab: 0,
+        });
+        let backend = RecordingBackend::default();
+        let action = create_terminal(
+            Some("/elsewhere"),
+            Some(TerminalPlacement::Tab {
+                terminal_id: "t1".into(),
+            }),
+        );
+
+        let id = run(&mut ws, &backend, action);
+
+        assert_eq!(id, "new-1");
+        let LayoutNode::Tabs { children, .. } = layout(&ws) else {
+            panic!("expected a tab group, got {:?}", layout(&ws));
+        };
+        assert_eq!(
+            children,
+            &[terminal("t1"), terminal("t2"), terminal("new-1")]
+        );
+        // An explicit cwd wins over the anchor's.
+        assert_eq!(spawn_cwds(&backend), ["/elsewhere"]);
+        assert!(ws.project("p").unwrap().terminal_names.is_empty());
+    }
+
+    #[test]
+    fn detached_placement_appends_a_detached_terminal() {
+        let mut ws = workspace(terminal("t1"));
+        let backend = RecordingBackend::default();
+
+        let id = run(
+            &mut ws,
+            &backend,
+            create_terminal(None, Some(TerminalPlacement::Detached)),
+        );
+
+        assert_eq!(id, "new-1");
+        let LayoutNode::Split { children, .. } = layout(&ws) else {
+            panic!("expected the root to be split, got {:?}", layout(&ws));
+        };
+        assert_eq!(children[0], terminal("t1"));
+        assert!(matches!(
+            &children[1],
+            LayoutNode::Terminal {
+                terminal_id: Some(id),
+                detached: true,
+                ..
+            } if id == "new-1"
+        ));
+        // No anchor and nothing focused: the project path.
+        assert_eq!(spawn_cwds(&backend), ["/project"]);
+        assert!(
+            backend
+                .transport
+                .input
+                .lock()
+                .expect("input lock")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn launch_parameters_reach_only_the_new_pane() {
+        let waiting = LayoutNode::Terminal {
+            terminal_id: None,
+            shell_type: ShellType::Default,
+            minimized: false,
+            detached: false,
+            zoom_level: 1.0,
+        };
+        let mut ws = workspace(LayoutNode::Tabs {
+            children: vec![terminal("t1"), waiting],
+            active_tab: 0,
+        });
+        let backend = RecordingBackend::default();
+        let mut action = create_terminal(
+            Some("/elsewhere"),
+            Some(TerminalPlacement::Tab {
+                terminal_id: "t1".into(),
+            }),
+        );
+        if let ActionRequest::CreateTerminal { env, .. } = &mut action {
+            env.insert("PORT".to_string(), "3000".to_string());
+        }
+
+        let result = execute_action(
+            action,
+            &mut ws,
+            WindowId::Main,
+            &mut FocusManager::default(),
+            &backend,
+            &Default::default(),
+            &AppSettings::default(),
+            &mut TestCx,
+        );
+
+        let ActionResult::Ok(Some(reply)) = result else {
+            panic!("create_terminal failed");
+        };
+        assert_eq!(reply["terminal_id"], "new-2");
+        assert_eq!(reply["terminal_ids"], serde_json::json!(["new-1", "new-2"]));
+        assert_eq!(spawn_cwds(&backend), ["/project", "/elsewhere"]);
+        let launches = backend.launches.lock().expect("launch lock");
+        let port = ("PORT".to_string(), "3000".to_string());
+        assert!(!launches[0].1.environment.contains(&port));
+        assert!(launches[1].1.environment.contains(&port));
+    }
+
+    #[test]
+    fn launch_parameters_for_a_project_being_created_are_refused() {
+        let mut ws = workspace(terminal("t1"));
+        ws.mark_creating_project("p");
+        let backend = RecordingBackend::default();
+        let result = execute_action(
+            create_terminal(Some("/elsewhere"), None),
+            &mut ws,
+            WindowId::Main,
+            &mut FocusManager::default(),
+            &backend,
+            &Default::default(),
+            &AppSettings::default(),
+            &mut TestCx,
+        );
+
+        assert!(
+            matches!(result, ActionResult::Err(message) if message.contains("still being created"))
+        );
+        assert_eq!(layout(&ws), &terminal("t1"));
+        assert!(spawn_cwds(&backend).is_empty());
+    }
+
+    #[test]
+    fn placement_next_to_an_unknown_terminal_is_an_error() {
+        let mut ws = workspace(terminal("t1"));
+        let backend = RecordingBackend::default();
+        let result = execute_action(
+            create_terminal(
+                None,
+                Some(TerminalPlacement::Tab {
+                    terminal_id: "gone".into(),
+                }),
+            ),
+            &mut ws,
+            WindowId::Main,
+            &mut FocusManager::default(),
+            &backend,
+            &Default::default(),
+            &AppSettings::default(),
+            &mut TestCx,
+        );
+
+        assert!(matches!(result, ActionResult::Err(message) if message.contains("gone")));
+        assert_eq!(layout(&ws), &terminal("t1"));
+        assert!(spawn_cwds(&backend).is_empty());
+    }
+}
@@ -8,7 +8,7 @@ use crate::remote_client::manager::RemoteConnectionManager;
 use crate::workspace::focus::FocusManager;
 use crate::workspace::state::{ProjectLayoutMode, WindowId, Workspace};
 
-use okena_core::api::ActionRequest;
+use okena_core::api::{ActionRequest, TerminalPlacement};
 use okena_transport::client::strip_prefix;
 
 use gpui::{AppContext, Entity};
@@ -317,7 +317,8 @@ impl ActionDispatcher {
                 );
                 // Don't return — the daemon still performs the close
             }
-            ActionRequest::CreateTerminal { project_id } => {
+            ActionRequest::CreateTerminal { project_id, .. }
+            | ActionRequest::ExecServiceShell { project_id, .. } => {
                 // Record pending focus — the actual focus will happen when
                 // the next state sync brings the new terminal into the
                 // client's layout (see sync_remote_projects_into_workspace).
@@ -488,6 +489,35 @@ impl ActionDispatcher {
     }
 }
 
+impl ActionDispatcher {
+    /// Forward a service port of a truly remote daemon to localhost and open
+    /// it. The local daemon's ports are already local, so this returns
+    /// `false` for it and the caller opens the port directly.
+    pub fn forward_service_port(
+        &self,
+        project_id: &str,
+        service_name: &str,
+        port: u16,
+        cx: &mut impl AppContext,
+    ) -> bool {
+        if self.shares_local_filesystem() {
+            return false;
+        }
+        let Self::Remote {
+            connection_id,
+            manager,
+            ..
+        } = self;
+        let cid = connection_id.clone();
+        let project_id = strip_prefix(project_id, connection_id);
+        let service_name = service_name.to_string();
+        manager.update(cx, |rm, cx| {
+            rm.forward_and_open(&cid, &project_id, &service_name, port, cx);
+        });
+        true
+    }
+}
+
 impl okena_views_terminal::ActionDispatch for ActionDispatcher {
     fn dispatch(&self, action: ActionRequest, cx: &mut gpui::App) {
         self.dispatch(action, cx);
@@ -530,6 +560,16 @@ impl okena_views_terminal::ActionDispatch for ActionDispatcher {
         self.upload_remote_paste_files(terminal_id, files, cx);
     }
 
+    fn forward_service_port(
+        &self,
+        project_id: &str,
+        service_name: &str,
+        port: u16,
+        cx: &mut gpui::App,
+    ) -> bool {
+        self.forward_service_port(project_id, service_name, port, cx)
+    }
+
     fn export_buffer(&self, terminal_id: &str, cx: &mut gpui::App) -> Option<std::path::PathBuf> {
         let Self::Remote {
             connection_id,
@@ -623,8 +663,20 @@ fn strip_remote_ids(action: ActionRequest, connection_id: &str) -> ActionRequest
                 project_id: s(&project_id),
             }
         }
-        ActionRequest::ReadContent { terminal_id } => ActionRequest::ReadContent {
+        ActionRequest::ReadContent {
+            terminal_id,
+            lines,
+            all,
+            ansi,
+            since,
+            strip_prompt,
+        } => ActionRequest::ReadContent {
             terminal_id: s(&terminal_id),
+            lines,
+            all,
+            ansi,
+            since,
+            strip_prompt,
         },
         ActionRequest::UndoSoftClose { terminal_id } => ActionRequest::UndoSoftClose {
             terminal_id: s(&terminal_id),
@@ -635,6 +687,17 @@ fn strip_remote_ids(action: ActionRequest, connection_id: &str) -> ActionRequest
         ActionRequest::ExportBuffer { terminal_id } => ActionRequest::ExportBuffer {
             terminal_id: s(&terminal_id),
         },
+        ActionRequest::ExecInTerminal {
+            terminal_id,
+            command,
+            mirror,
+            timeout_ms,
+        } => ActionRequest::ExecInTerminal {
+            terminal_id: s(&terminal_id),
+            command,
+            mirror,
+            timeout_ms,
+        },
         ActionRequest::Resize {
             terminal_id,
             cols,
@@ -644,8 +707,36 @@ fn strip_remote_ids(action: ActionRequest, connection_id: &str) -> ActionRequest
             cols,
             rows,
         },
-        ActionRequest::CreateTerminal { project_id } => ActionRequest::CreateTerminal {
+        ActionRequest::CreateTerminal {
+            project_id,
+            shell,
+            cwd,
+            command,
+            env,
+            name,
+            minimized,
+            placement,
+        } => ActionRequest::CreateTerminal {
             project_id: s(&project_id),
+            shell,
+            cwd,
+            command,
+            env,
+            name,
+            minimized,
+            placement: placement.map(|placement| match placement {
+                TerminalPlacement::Split {
+                    terminal_id,
+                    direction,
+                } => TerminalPlacement::Split {
+                    terminal_id: s(&terminal_id),
+                    direction,
+                },
+                TerminalPlacement::Tab { terminal_id } => TerminalPlacement::Tab {
+                    terminal_id: s(&terminal_id),
+                },
+                TerminalPlacement::Detached => TerminalPlacement::Detached,
+            }),
         },
         ActionRequest::UpdateSplitSizes {
             project_id,
@@ -828,6 +919,29 @@ fn strip_remote_ids(action: ActionRequest, connection_id: &str) -> ActionRequest
             project_id: s(&project_id),
             service_name,
         },
+        ActionRequest::RebuildService {
+            project_id,
+            service_name,
+        } => ActionRequest::RebuildService {
+            project_id: s(&project_id),
+            service_name,
+        },
+        ActionRequest::PullService {
+            project_id,
+            service_name,
+        } => ActionRequest::PullService {
+            project_id: s(&project_id),
+            service_name,
+        },
+        ActionRequest::ExecServiceShell {
+            project_id,
+            service_name,
+            shell,
+        } => ActionRequest::ExecServiceShell {
+            project_id: s(&project_id),
+            service_name,
+            shell,
+        },
         ActionRequest::StartAllServices { project_id } => ActionRequest::StartAllServices {
             project_id: s(&project_id),
         },
@@ -1262,7 +1376,11 @@ fn strip_remote_ids(action: ActionRequest, connection_id: &str) -> ActionRequest
         | ActionRequest::SetTheme { .. }
         | ActionRequest::SaveCustomTheme { .. }
         | ActionRequest::ListActions
-        | ActionRequest::InvokeAction { .. }) => a,
+        | ActionRequest::InvokeAction { .. }
+        | ActionRequest::CancelHook { .. }
+        | ActionRequest::ListHookRuns { .. }
+        | ActionRequest::GetHookRun { .. }
+        | ActionRequest::DiffHookRuns { .. }) => a,
     }
 }
 
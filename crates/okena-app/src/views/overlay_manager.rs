@@ -295,6 +295,11 @@ pub enum OverlayManagerEvent {
     TerminalToggleUnread {
         terminal_id: String,
     },
+    /// Terminal context menu: mint a view-only share link
+    ShareView {
+        project_id: String,
+        terminal_id: Option<String>,
+    },
     /// Terminal context menu: select all
     TerminalSelectAll {
         terminal_id: String,
@@ -691,9 +696,16 @@ impl OverlayManager {
         .detach();
     }
 
-    /// Toggle hook log overlay.
-    pub fn toggle_hook_log(&mut self, cx: &mut Context<Self>) {
-        toggle_overlay!(self, cx, HookLog, HookLogEvent, HookLog::new);
+    /// Toggle hook log overlay. Without a daemon client the log is read-only
+    /// (no cancel, no saved-run browser).
+    pub fn toggle_hook_log(
+        &mut self,
+        client: Option<okena_transport::remote_action::RemoteActionClient>,
+        cx: &mut Context<Self>,
+    ) {
+        toggle_overlay!(self, cx, HookLog, HookLogEvent, |cx| HookLog::new(
+            client, cx
+        ));
     }
 
     /// Toggle the log console overlay (live in-app log viewer).
@@ -1369,6 +1381,16 @@ impl OverlayManager {
                         terminal_id: terminal_id.clone(),
                     });
                 }
+                TerminalContextMenuEvent::ShareView {
+                    project_id,
+                    terminal_id,
+                } => {
+                    this.hide_terminal_context_menu(cx);
+                    cx.emit(OverlayManagerEvent::ShareView {
+                        project_id: project_id.clone(),
+                        terminal_id: terminal_id.clone(),
+                    });
+                }
                 TerminalContextMenuEvent::Split {
                     project_id,
                     layout_path,
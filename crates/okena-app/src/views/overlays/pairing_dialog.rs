@@ -4,6 +4,7 @@ use crate::ui::tokens::{ui_text, ui_text_md, ui_text_sm};
 use crate::views::components::{modal_backdrop, modal_content, modal_header};
 use gpui::prelude::*;
 use gpui::*;
+use okena_core::scope::{Scope, TokenGrant};
 use okena_remote_server::local::DaemonEndpoint;
 use okena_transport::client::tls::format_fingerprint;
 use std::time::Instant;
@@ -16,6 +17,8 @@ pub struct PairingDialog {
     remaining_secs: u64,
     expired: bool,
     error: Option<String>,
+    /// What a device paired with the current code may do.
+    grant: TokenGrant,
 }
 
 pub enum PairingDialogEvent {
@@ -55,6 +58,7 @@ impl PairingDialog {
             remaining_secs: 0,
             expired: false,
             error: None,
+            grant: TokenGrant::default(),
         };
 
         dialog.request_new_code(cx);
@@ -76,10 +80,13 @@ impl PairingDialog {
             return;
         };
 
+        let grant = self.grant.clone();
         cx.spawn(async move |this: WeakEntity<PairingDialog>, cx| {
             let outcome = cx
                 .background_executor()
-                .spawn(async move { okena_remote_server::local::request_pair_code(&endpoint) })
+                .spawn(
+                    async move { okena_remote_server::local::request_pair_code(&endpoint, &grant) },
+                )
                 .await;
 
             let _ = this.update(cx, |this, cx| {
@@ -136,6 +143,33 @@ impl PairingDialog {
         self.request_new_code(cx);
         cx.notify();
     }
+
+    /// A code is bound to the grant it was issued with, so changing the
+    /// scopes issues a new one.
+    fn toggle_scope(&mut self, scope: Scope, cx: &mut Context<Self>) {
+        self.grant = toggled(&self.grant, scope);
+        self.generate_new_code(cx);
+    }
+}
+
+/// Admin means full access; `read` is always granted, so picking it alone
+/// makes the code view-only.
+fn toggled(grant: &TokenGrant, scope: Scope) -> TokenGrant {
+    match scope {
+        Scope::Admin => TokenGrant::default(),
+        Scope::Read => TokenGrant::read_only(),
+        _ => {
+            let mut next = if grant.is_full() {
+                TokenGrant::read_only()
+            } else {
+                grant.clone()
+            };
+            if !next.scopes.remove(&scope) {
+                next.scopes.insert(scope);
+            }
+            next
+        }
+    }
 }
 
 fn seconds_remaining(created_at: Instant) -> u64 {
@@ -157,6 +191,7 @@ impl Render for PairingDialog {
         let remaining = self.remaining_secs;
         let error = self.error.clone();
         let loading = code.is_empty() && error.is_none() && !expired;
+        let grant = self.grant.clone();
         // TLS cert fingerprint, shown so the host can read it out for the client
         // to verify during pairing. `None` when the server runs without TLS.
         let fingerprint =
@@ -234,6 +269,58 @@ impl Render for PairingDialog {
                                         .child(format!("Expires in {}s", remaining)),
                                 )
                             })
+                            // Scopes the paired device will get
+                            .child(
+                                div()
+                                    .w_full()
+                                    .flex()
+                                    .flex_col()
+                                    .gap(px(4.0))
+                                    .child(
+                                        div()
+                                            .text_size(ui_text_sm(cx))
+                                            .text_color(rgb(t.text_muted))
+                                            .child(format!("Access: {}", grant.summary())),
+                                    )
+                                    .child(
+                                        div()
+                                            .flex()
+                                            .flex_wrap()
+                                            .gap(px(6.0))
+                                            .children(Scope::ALL.into_iter().map(|scope| {
+                                                let active = if grant.is_full() {
+                                                    scope == Scope::Admin
+                                                } else {
+                                                    grant.scopes.contains(&scope)
+                                                        || scope == Scope::Read
+                                                };
+                                                div()
+                                                    .id(SharedString::from(format!(
+                                                        "pair-scope-{}",
+                                                        scope.as_str()
+                                                    )))
+                                                    .cursor_pointer()
+                                                    .px(px(8.0))
+                                                    .py(px(3.0))
+                                                    .rounded(px(4.0))
+                                                    .text_size(ui_text_sm(cx))
+                                                    .when(active, |d| {
+                                                        d.bg(rgb(t.term_cyan)).text_color(rgb(t.bg_primary))
+                                                    })
+                                                    .when(!active, |d| {
+                                                        d.bg(rgb(t.bg_secondary))
+                                                            .border_1()
+                                                            .border_color(rgb(t.border))
+                                                            .text_color(rgb(t.text_primary))
+                                                            .hover(|s| s.bg(rgb(t.bg_hover)))
+                                                    })
+                                                    .child(scope.as_str())
+                                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                                        this.toggle_scope(scope, cx);
+                                                    }))
+                                            })),
+                                    ),
+                            )
                             // TLS certificate fingerprint (when serving over TLS)
                             .when_some(fingerprint, |d, fp| {
                                 d.child(
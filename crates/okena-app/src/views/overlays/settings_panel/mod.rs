@@ -31,6 +31,7 @@ use crate::views::components::{dropdown_anchored_below, modal_backdrop, modal_co
 use crate::workspace::state::Workspace;
 use gpui::prelude::*;
 use gpui::*;
+use okena_core::audit::{AuditEntry, AuditQuery};
 use okena_extensions::ExtensionRegistry;
 use std::collections::HashMap;
 
@@ -95,6 +96,10 @@ pub struct SettingsPanel {
     // is fetched over its REST API rather than read from an in-process store.
     pub(super) daemon_endpoint: Option<DaemonEndpoint>,
     pub(super) paired_devices: PairedDevices,
+    /// Recent remote API activity from the daemon's audit log, optionally
+    /// narrowed to one device (token id).
+    pub(super) activity: Activity,
+    pub(super) activity_device: Option<String>,
     /// Cached extension settings views (lazily created on first access).
     extension_views: HashMap<String, AnyView>,
 }
@@ -110,6 +115,17 @@ pub(super) enum PairedDevices {
     Failed(String),
 }
 
+/// Load state of the activity list under the paired devices.
+pub(super) enum Activity {
+    Loading,
+    /// Newest last, as the daemon returns them.
+    Loaded(Vec<AuditEntry>),
+    Failed(String),
+}
+
+/// Entries the activity list fetches.
+const ACTIVITY_LIMIT: usize = 100;
+
 impl SettingsPanel {
     pub fn new(
         workspace: Entity<Workspace>,
@@ -913,6 +929,8 @@ impl SettingsPanel {
             file_opener_input,
             listen_address_input,
             paired_devices: PairedDevices::Loading,
+            activity: Activity::Loading,
+            activity_device: None,
             daemon_endpoint,
             extension_views: HashMap::new(),
         };
@@ -929,6 +947,7 @@ impl SettingsPanel {
             return;
         };
 
+        self.load_activity(cx);
         cx.spawn(async move |this: WeakEntity<Self>, cx| {
             let outcome = cx
                 .background_executor()
@@ -946,6 +965,42 @@ impl SettingsPanel {
         .detach();
     }
 
+    /// Fetch recent audit log entries (`GET /v1/audit`) for the selected
+    /// device, or all devices.
+    pub(super) fn load_activity(&mut self, cx: &mut Context<Self>) {
+        let Some(endpoint) = self.daemon_endpoint.clone() else {
+            return;
+        };
+        let query = AuditQuery {
+            token: self.activity_device.clone(),
+            limit: Some(ACTIVITY_LIMIT),
+            ..AuditQuery::default()
+        };
+
+        cx.spawn(async move |this: WeakEntity<Self>, cx| {
+            let outcome = cx
+                .background_executor()
+                .spawn(async move { crate::remote::local::list_audit_entries(&endpoint, &query) })
+                .await;
+
+            let _ = this.update(cx, |this, cx| {
+                this.activity = match outcome {
+                    Ok(entries) => Activity::Loaded(entries),
+                    Err(e) => Activity::Failed(e),
+                };
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    pub(super) fn filter_activity(&mut self, device: Option<String>, cx: &mut Context<Self>) {
+        self.activity_device = device;
+        self.activity = Activity::Loading;
+        self.load_activity(cx);
+        cx.notify();
+    }
+
     /// Revoke one paired device, then reload the list so the panel reflects
     /// what the daemon actually holds.
     pub(super) fn revoke_paired_device(&self, id: String, cx: &mut Context<Self>) {
@@ -1246,6 +1301,14 @@ impl SettingsPanel {
         let term_on_create = opt_string(self.project_hook_terminal_on_create.read(cx).value());
         let term_on_close = opt_string(self.project_hook_terminal_on_close.read(cx).value());
         let shell_wrapper = opt_string(self.project_hook_terminal_shell_wrapper.read(cx).value());
+        // Event hooks and step lists are edited in workspace.json, not here;
+        // carry them over.
+        let current = self
+            .workspace
+            .read(cx)
+            .project(&project_id)
+            .map(|p| p.hooks.to_api())
+            .unwrap_or_default();
 
         let hooks = okena_core::api::ApiHooksConfig {
             project: okena_core::api::ApiProjectHooks { on_open, on_close },
@@ -1253,6 +1316,7 @@ impl SettingsPanel {
                 on_create: term_on_create,
                 on_close: term_on_close,
                 shell_wrapper,
+                ..current.terminal
             },
             worktree: okena_core::api::ApiWorktreeHooks {
                 on_create: wt_create,
@@ -1264,6 +1328,9 @@ impl SettingsPanel {
                 on_rebase_conflict,
                 on_dirty_close,
             },
+            service: current.service,
+            git: current.git,
+            steps: current.steps,
         };
         cx.emit(SettingsPanelEvent::ProjectHooksChanged {
             project_id,
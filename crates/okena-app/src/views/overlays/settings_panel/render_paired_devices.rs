@@ -1,10 +1,11 @@
 use crate::theme::theme;
 use crate::ui::tokens::{ui_text, ui_text_ms, ui_text_sm};
+use gpui::prelude::*;
 use gpui::*;
 use okena_ui::empty_state::empty_state;
 
 use super::components::*;
-use super::{PairedDevices, SettingsPanel};
+use super::{Activity, PairedDevices, SettingsPanel};
 
 impl SettingsPanel {
     pub(super) fn render_paired_devices(&mut self, cx: &mut Context<Self>) -> impl IntoElement {
@@ -43,13 +44,25 @@ impl SettingsPanel {
                 PairedDevices::Loaded(devices) => devices,
             };
 
+        // (token id, label) for the activity filter.
+        let device_labels: Vec<(String, String)> = devices
+            .iter()
+            .map(|info| {
+                (
+                    info.id.clone(),
+                    device_label(&info.id, info.name.as_deref()),
+                )
+            })
+            .collect();
+
         if devices.is_empty() {
             return content
                 .child(section_header("Paired Devices", &t, cx))
                 .child(
                     section_container(&t)
                         .child(empty_state("No devices are currently paired", &t, cx).py(px(16.0))),
-                );
+                )
+                .child(self.render_activity(device_labels, cx));
         }
 
         let now_secs = std::time::SystemTime::now()
@@ -64,10 +77,7 @@ impl SettingsPanel {
             .map(|(i, info)| {
                 let is_last = i == device_count - 1;
                 let id_str = info.id.clone();
-                let display_name = info
-                    .name
-                    .clone()
-                    .unwrap_or_else(|| format!("Device {}", &info.id[..8.min(info.id.len())]));
+                let display_name = device_label(&info.id, info.name.as_deref());
 
                 let created = format_relative_time(now_secs, info.created_at);
                 let last_used = format_relative_time(now_secs, info.last_used_at);
@@ -77,6 +87,9 @@ impl SettingsPanel {
                     "expired".to_string()
                 };
 
+                // Full-access devices (the default) don't get an access line.
+                let access = (!info.grant.is_full()).then(|| info.grant.summary());
+
                 let row = div()
                     .id(ElementId::Name(format!("device-{}", i).into()))
                     .px(px(12.0))
@@ -103,7 +116,15 @@ impl SettingsPanel {
                                         "Created {} \u{2022} Last used {} \u{2022} Expires in {}",
                                         created, last_used, expires,
                                     )),
-                            ),
+                            )
+                            .when_some(access, |d, access| {
+                                d.child(
+                                    div()
+                                        .text_size(ui_text_sm(cx))
+                                        .text_color(rgb(t.text_muted))
+                                        .child(format!("Access: {access}")),
+                                )
+                            }),
                     )
                     .child(
                         div()
@@ -137,7 +158,163 @@ impl SettingsPanel {
         content
             .child(section_header("Paired Devices", &t, cx))
             .child(section_container(&t).children(items))
+            .child(self.render_activity(device_labels, cx))
     }
+
+    /// Recent entries from the daemon's audit log, newest first, with a
+    /// device filter.
+    fn render_activity(
+        &self,
+        devices: Vec<(String, String)>,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let t = theme(cx);
+        let selected = self.activity_device.clone();
+
+        let chip = |id: ElementId, label: String, active: bool| {
+            div()
+                .id(id)
+                .cursor_pointer()
+                .px(px(8.0))
+                .py(px(3.0))
+                .rounded(px(4.0))
+                .text_size(ui_text_sm(cx))
+                .when(active, |d| {
+                    d.bg(rgb(t.term_cyan)).text_color(rgb(t.bg_primary))
+                })
+                .when(!active, |d| {
+                    d.bg(rgb(t.bg_secondary))
+                        .border_1()
+                        .border_color(rgb(t.border))
+                        .text_color(rgb(t.text_primary))
+                        .hover(|s| s.bg(rgb(t.bg_hover)))
+                })
+                .child(label)
+        };
+        let mut filters = vec![
+            chip(
+                ElementId::Name("activity-all".into()),
+                "All devices".to_string(),
+                selected.is_none(),
+            )
+            .on_click(cx.listener(|this, _, _, cx| this.filter_activity(None, cx)))
+            .into_any_element(),
+        ];
+        for (i, (id, label)) in devices.into_iter().enumerate() {
+            let active = selected.as_deref() == Some(id.as_str());
+            filters.push(
+                chip(
+                    ElementId::Name(format!("activity-device-{i}").into()),
+                    label,
+                    active,
+                )
+                .on_click(
+                    cx.listener(move |this, _, _, cx| this.filter_activity(Some(id.clone()), cx)),
+                )
+                .into_any_element(),
+            );
+        }
+
+        let body = match &self.activity {
+            Activity::Loading => empty_state("Loading activity...", &t, cx)
+                .py(px(16.0))
+                .into_any_element(),
+            Activity::Failed(error) => empty_state(error.clone(), &t, cx)
+                .py(px(16.0))
+                .text_color(rgb(t.term_red))
+                .into_any_element(),
+            Activity::Loaded(entries) if entries.is_empty() => {
+                empty_state("No remote activity recorded", &t, cx)
+                    .py(px(16.0))
+                    .into_any_element()
+            }
+            Activity::Loaded(entries) => {
+                let now_secs = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                let count = entries.len();
+                let rows: Vec<_> = entries
+                    .iter()
+                    .rev()
+                    .enumerate()
+                    .map(|(i, entry)| {
+                        let device = device_label(&entry.token_id, entry.token_name.as_deref());
+                        let headline = format!(
+                            "{} \u{2022} {} \u{2022} {}{}",
+                            entry.kind,
+                            device,
+                            entry.client,
+                            if entry.denied { " \u{2022} denied" } else { "" },
+                        );
+                        let row = div()
+                            .px(px(12.0))
+                            .py(px(6.0))
+                            .flex()
+                            .justify_between()
+                            .gap(px(12.0))
+                            .child(
+                                div()
+                                    .flex()
+                                    .flex_col()
+                                    .gap(px(2.0))
+                                    .min_w_0()
+                                    .child(
+                                        div()
+                                            .text_size(ui_text_ms(cx))
+                                            .text_color(rgb(if entry.denied {
+                                                t.term_red
+                                            } else {
+                                                t.text_primary
+                                            }))
+                                            .child(headline),
+                                    )
+                                    .child(
+                                        div()
+                                            .text_size(ui_text_sm(cx))
+                                            .text_color(rgb(t.text_muted))
+                                            .truncate()
+                                            .child(entry.summary.clone()),
+                                    ),
+                            )
+                            .child(
+                                div()
+                                    .flex_shrink_0()
+                                    .text_size(ui_text_sm(cx))
+                                    .text_color(rgb(t.text_muted))
+                                    .child(format_relative_time(now_secs, entry.at)),
+                            );
+                        if i == count - 1 {
+                            row.into_any_element()
+                        } else {
+                            row.border_b_1()
+                                .border_color(rgb(t.border))
+                                .into_any_element()
+                        }
+                    })
+                    .collect();
+                div().children(rows).into_any_element()
+            }
+        };
+
+        div()
+            .child(section_header("Activity", &t, cx))
+            .child(
+                div()
+                    .flex()
+                    .flex_wrap()
+                    .gap(px(6.0))
+                    .pb(px(8.0))
+                    .children(filters),
+            )
+            .child(section_container(&t).child(body))
+    }
+}
+
+/// A device's name, or a short form of its token id.
+fn device_label(id: &str, name: Option<&str>) -> String {
+    name.map(str::to_string)
+        .unwrap_or_else(|| format!("Device {}", &id[..8.min(id.len())]))
 }
 
 fn format_relative_time(now_secs: u64, timestamp: u64) -> String {
@@ -277,6 +277,27 @@ impl HookPanel {
         }
     }
 
+    /// Cancel a running hook: the daemon kills its PTY and records the
+    /// execution as cancelled, leaving the terminal in place for inspection.
+    /// The execution id comes from the mirrored `HookMonitor`.
+    fn cancel_hook(&mut self, terminal_id: &str, cx: &mut Context<Self>) {
+        let Some(execution_id) = cx
+            .try_global::<crate::workspace::hook_monitor::HookMonitor>()
+            .and_then(|monitor| monitor.running_execution_for_terminal(terminal_id))
+        else {
+            log::warn!("Cannot cancel hook: no running execution for {terminal_id}");
+            return;
+        };
+        if let Some(ref dispatcher) = self.action_dispatcher {
+            dispatcher.dispatch(
+                okena_core::api::ActionRequest::CancelHook { execution_id },
+                cx,
+            );
+        } else {
+            log::warn!("Cannot cancel hook: no action dispatcher wired");
+        }
+    }
+
     /// Ask the daemon to stop and remove a hook terminal.
     fn dismiss_hook(&mut self, terminal_id: &str, cx: &mut Context<Self>) {
         let next = self
@@ -595,6 +616,30 @@ impl HookPanel {
                         );
                     }
 
+                    // Cancel button (only while the hook is running)
+                    if is_running {
+                        let entity_cancel = entity.clone();
+                        let tid_cancel = tid.clone();
+                        actions = actions.child(
+                            icon_button_sized(
+                                "hook-panel-cancel",
+                                "icons/close.svg",
+                                22.0,
+                                12.0,
+                                t,
+                            )
+                            .on_click(move |_, _window, cx| {
+                                cx.stop_propagation();
+                                if let Some(e) = entity_cancel.upgrade() {
+                                    e.update(cx, |this, cx| {
+                                        this.cancel_hook(&tid_cancel, cx);
+                                    });
+                                }
+                            })
+                            .tooltip(|_window, cx| Tooltip::new("Cancel Hook").build(_window, cx)),
+                        );
+                    }
+
                     // Rerun button (always visible, dimmed when running)
                     let entity_rerun = entity.clone();
                     let tid_rerun = tid.clone();
@@ -1078,6 +1078,13 @@ impl ProjectColumn {
                                 dispatcher.dispatch(
                                     ActionRequest::CreateTerminal {
                                         project_id: project_id.clone(),
+                                        shell: None,
+                                        cwd: None,
+                                        command: None,
+                                        env: Default::default(),
+                                        name: None,
+                                        minimized: false,
+                                        placement: None,
                                     },
                                     cx,
                                 );
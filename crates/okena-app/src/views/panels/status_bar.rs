@@ -9,7 +9,7 @@ use gpui::*;
 use gpui_component::{h_flex, v_flex};
 use okena_core::api::{ApiLayoutNode, ApiSystemStats};
 use okena_extensions::{ExtensionInstance, ExtensionRegistry};
-use okena_transport::client::{ConnectionStatus, LOCAL_DAEMON_CONNECTION_ID};
+use okena_transport::client::{ConnectionStatus, LOCAL_DAEMON_CONNECTION_ID, StreamThroughput};
 use parking_lot::Mutex;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
@@ -40,6 +40,8 @@ struct RemoteStatusSnapshot {
     terminal_count: usize,
     has_state: bool,
     system_stats: Option<ApiSystemStats>,
+    /// Output stream counters, while connected.
+    throughput: Option<StreamThroughput>,
 }
 
 /// Global system info cache
@@ -245,8 +247,8 @@ impl StatusBar {
             return Vec::new();
         };
 
+        let manager = manager.read(cx);
         manager
-            .read(cx)
             .connections_with_system_stats()
             .into_iter()
             .filter(|(config, _, _, _)| config.id != LOCAL_DAEMON_CONNECTION_ID)
@@ -274,6 +276,9 @@ impl StatusBar {
                     terminal_count,
                     has_state: state.is_some(),
                     system_stats: system_stats.cloned(),
+                    throughput: matches!(status, ConnectionStatus::Connected)
+                        .then(|| manager.throughput(&config.id))
+                        .flatten(),
                 }
             })
             .collect()
@@ -374,6 +379,34 @@ impl StatusBar {
         )
     }
 
+    fn format_rate(bytes_per_sec: u64) -> String {
+        const KIB: f64 = 1024.0;
+        let bytes = bytes_per_sec as f64;
+        if bytes < KIB {
+            format!("{bytes_per_sec} B/s")
+        } else if bytes < KIB * KIB {
+            format!("{:.1} KB/s", bytes / KIB)
+        } else {
+            format!("{:.1} MB/s", bytes / (KIB * KIB))
+        }
+    }
+
+    fn throughput_label(throughput: &StreamThroughput) -> String {
+        let mut label = format!("Output {}", Self::format_rate(throughput.bytes_per_sec));
+        if throughput.compression.is_some() {
+            label.push_str(&format!(
+                " / compressed {:.1}x",
+                throughput.compression_ratio()
+            ));
+        } else {
+            label.push_str(" / uncompressed");
+        }
+        if throughput.dropped > 0 {
+            label.push_str(&format!(" / {} dropped", throughput.dropped));
+        }
+        label
+    }
+
     fn aggregate_remote_color(
         snapshots: &[RemoteStatusSnapshot],
         t: &okena_core::theme::ThemeColors,
@@ -435,6 +468,7 @@ impl StatusBar {
             let security = if snapshot.tls { "TLS" } else { "no TLS" };
             let status_color = Self::status_color(&snapshot.status, t);
             let system_stats = snapshot.system_stats.clone();
+            let throughput = snapshot.throughput.clone();
 
             rows.push(
                 div()
@@ -559,6 +593,21 @@ impl StatusBar {
                                                 ),
                                         ),
                                 )
+                            })
+                            .when_some(throughput, |el, throughput| {
+                                el.child(
+                                    div()
+                                        .min_w_0()
+                                        .overflow_hidden()
+                                        .text_ellipsis()
+                                        .text_size(ui_text_sm(cx))
+                                        .text_color(rgb(if throughput.dropped > 0 {
+                                            t.metric_warning
+                                        } else {
+                                            t.text_muted
+                                        }))
+                                        .child(Self::throughput_label(&throughput)),
+                                )
                             }),
                     )
                     .into_any_element(),
@@ -337,6 +337,83 @@ impl WindowView {
         .detach();
     }
 
+    /// Mint a view-only share link to a project (or one of its terminals) and
+    /// copy its URL. Only projects served by the local daemon can be shared.
+    pub(super) fn share_view(
+        &self,
+        project_id: &str,
+        terminal_id: Option<&str>,
+        cx: &mut Context<Self>,
+    ) {
+        let connection_id = okena_transport::client::LOCAL_DAEMON_CONNECTION_ID;
+        let on_local_daemon = self
+            .workspace
+            .read(cx)
+            .project(project_id)
+            .is_some_and(|project| project.connection_id.as_deref() == Some(connection_id));
+        let endpoint = self.local_daemon_endpoint(cx);
+        let Some(endpoint) = endpoint.filter(|_| on_local_daemon) else {
+            okena_workspace::toast::ToastManager::error(
+                "Only projects on this machine can be shared",
+                cx,
+            );
+            return;
+        };
+        let request = okena_core::api::ApiShareRequest {
+            project_id: okena_transport::client::strip_prefix(project_id, connection_id),
+            terminal_id: terminal_id
+                .map(|id| okena_transport::client::strip_prefix(id, connection_id)),
+            ttl_secs: None,
+        };
+        cx.spawn(async move |_this, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { crate::remote::local::create_share(&endpoint, &request) })
+                .await;
+            cx.update(|cx| match result {
+                Ok(created) => {
+                    cx.write_to_clipboard(gpui::ClipboardItem::new_string(created.url));
+                    okena_workspace::toast::ToastManager::success(
+                        "View-only link copied to clipboard (expires in 1 hour)",
+                        cx,
+                    );
+                }
+                Err(error) => {
+                    okena_workspace::toast::ToastManager::error(
+                        format!("Cannot share: {error}"),
+                        cx,
+                    );
+                }
+            });
+        })
+        .detach();
+    }
+
+    /// End share links and disconnect their viewers.
+    pub(super) fn revoke_shares(&self, share_ids: Vec<String>, cx: &mut Context<Self>) {
+        let Some(endpoint) = self.local_daemon_endpoint(cx) else {
+            return;
+        };
+        cx.spawn(async move |_this, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    share_ids
+                        .iter()
+                        .try_for_each(|id| crate::remote::local::revoke_share(&endpoint, id))
+                })
+                .await;
+            cx.update(|cx| match result {
+                Ok(()) => okena_workspace::toast::ToastManager::info("Sharing stopped", cx),
+                Err(error) => okena_workspace::toast::ToastManager::error(
+                    format!("Cannot stop sharing: {error}"),
+                    cx,
+                ),
+            });
+        })
+        .detach();
+    }
+
     /// Evict cached file viewers for projects that no longer exist.
     ///
     /// Rebuilds the set of `ProjectFs::project_id()` keys from the current
@@ -482,6 +559,13 @@ impl WindowView {
                     dispatcher.dispatch(
                         ActionRequest::CreateTerminal {
                             project_id: project_id.clone(),
+                            shell: None,
+                            cwd: None,
+                            command: None,
+                            env: Default::default(),
+                            name: None,
+                            minimized: false,
+                            placement: None,
                         },
                         cx,
                     );
@@ -804,6 +888,12 @@ impl WindowView {
                     terminal.clear();
                 }
             }
+            OverlayManagerEvent::ShareView {
+                project_id,
+                terminal_id,
+            } => {
+                self.share_view(project_id, terminal_id.as_deref(), cx);
+            }
             OverlayManagerEvent::TerminalToggleUnread { terminal_id } => {
                 {
                     let terminals = self.terminals.lock();
@@ -1304,6 +1394,9 @@ impl WindowView {
                     } => {
                         self.open_terminal_path(&project_id, &terminal_id, path, line, column, cx);
                     }
+                    ProjectOverlayKind::RevokeShares { share_ids } => {
+                        self.revoke_shares(share_ids, cx);
+                    }
                     ProjectOverlayKind::ColorPicker { position } => {
                         self.overlay_manager.update(cx, |om, cx| {
                             om.show_color_picker(
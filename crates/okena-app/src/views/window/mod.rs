@@ -689,6 +689,10 @@ impl WindowView {
         let workspace_conn = self.workspace.clone();
         let focus_manager_conn = self.focus_manager.clone();
         let remote_manager_conn = self.remote_manager.clone();
+        // And for service port forwarding.
+        let workspace_port = self.workspace.clone();
+        let focus_manager_port = self.focus_manager.clone();
+        let remote_manager_port = self.remote_manager.clone();
         self.sidebar.update(cx, |s, _cx| {
             s.set_dispatch_action(Box::new(move |project_id, action, cx| {
                 if let Some(dispatcher) = crate::action_dispatch::dispatcher_for_project(
@@ -716,6 +720,19 @@ impl WindowView {
                     dispatcher.dispatch(action, cx);
                 }
             }));
+            s.set_forward_service_port(Box::new(move |project_id, service_name, port, cx| {
+                crate::action_dispatch::dispatcher_for_project(
+                    project_id,
+                    window_id,
+                    &workspace_port,
+                    &focus_manager_port,
+                    &remote_manager_port,
+                    cx,
+                )
+                .is_some_and(|dispatcher| {
+                    dispatcher.forward_service_port(project_id, service_name, port, cx)
+                })
+            }));
         });
     }
 
@@ -922,7 +922,16 @@ impl Render for WindowView {
                 }
                 if let Some(dispatcher) = this.dispatcher_for_project(&project_id, cx) {
                     dispatcher.dispatch(
-                        okena_core::api::ActionRequest::CreateTerminal { project_id },
+                        okena_core::api::ActionRequest::CreateTerminal {
+                            project_id,
+                            shell: None,
+                            cwd: None,
+                            command: None,
+                            env: Default::default(),
+                            name: None,
+                            minimized: false,
+                            placement: None,
+                        },
                         cx,
                     );
                 }
@@ -1057,8 +1066,9 @@ impl Render for WindowView {
             // Handle show hook log action
             .on_action(cx.listener({
                 let overlay_manager = overlay_manager.clone();
-                move |_this, _: &ShowHookLog, _window, cx| {
-                    overlay_manager.update(cx, |om, cx| om.toggle_hook_log(cx));
+                move |this, _: &ShowHookLog, _window, cx| {
+                    let client = this.local_daemon_action_client(cx).ok();
+                    overlay_manager.update(cx, |om, cx| om.toggle_hook_log(client, cx));
                 }
             }))
             // Handle show log console action
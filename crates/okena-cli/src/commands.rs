@@ -1,16 +1,25 @@
+use crate::parser::{CompletionShell, TermNewArgs};
 use crate::resolve;
 use crate::{api_action, api_get, discover_server, ensure_token};
-use okena_core::api::{ApiProject, StateResponse};
-use okena_remote_server::auth::{generate_pairing_code, pair_code_path};
+use okena_core::api::{
+    ApiGitStatus, ApiHookRun, ApiHookRunDiff, ApiHookStatus, ApiProject, FileUploadRequest,
+    FileUploadResponse, StateResponse, UploadConflict,
+};
+use okena_core::forward::PortForwardQuery;
+use okena_core::scope::{Scope, TokenGrant};
+use okena_remote_server::auth::{generate_pairing_code, pair_code_file_contents, pair_code_path};
 
 /// The agent skill, embedded so `skill show`/`install` always match this build.
 const SKILL_MD: &str = include_str!("skill.md");
 
-/// Prefix of the completion marker `run --wait` appends to detect command end.
-/// Carried in one place so the emit, detect and strip steps can't drift.
-const MARKER_PREFIX: &str = "OKENADONE_";
-
-pub fn cli_pair() -> i32 {
+pub fn cli_pair(scopes: &[Scope], projects: &[String], folders: &[String]) -> i32 {
+    let grant = match pair_grant(scopes, projects, folders) {
+        Ok(grant) => grant,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
     let code = generate_pairing_code();
     let path = pair_code_path();
 
@@ -21,7 +30,7 @@ pub fn cli_pair() -> i32 {
         return 1;
     }
 
-    if let Err(e) = std::fs::write(&path, &code) {
+    if let Err(e) = std::fs::write(&path, pair_code_file_contents(&code, &grant)) {
         eprintln!("Failed to write pairing code: {e}");
         return 1;
     }
@@ -51,10 +60,44 @@ pub fn cli_pair() -> i32 {
             okena_transport::client::tls::format_fingerprint(&fp)
         );
     }
+    if !grant.is_full() {
+        eprintln!("Access: {}", grant.summary());
+    }
     eprintln!("Expires in 60s — run `okena pair` again for a fresh code.");
     0
 }
 
+/// The grant for `okena pair`'s flags. Project and folder names are resolved
+/// against the running server, since the grant stores ids.
+fn pair_grant(
+    scopes: &[Scope],
+    projects: &[String],
+    folders: &[String],
+) -> Result<TokenGrant, String> {
+    let mut grant = if scopes.is_empty() {
+        TokenGrant::default()
+    } else {
+        TokenGrant {
+            scopes: scopes.iter().copied().collect(),
+            ..TokenGrant::default()
+        }
+    };
+    if projects.is_empty() && folders.is_empty() {
+        return Ok(grant);
+    }
+    let token = ensure_token()?;
+    let state = fetch_state(&token)?;
+    grant.projects = projects
+        .iter()
+        .map(|filter| resolve::resolve_project(&state, filter).map(|p| p.id.clone()))
+        .collect::<Result<_, _>>()?;
+    grant.folders = folders
+        .iter()
+        .map(|filter| resolve_folder_id(&state, filter))
+        .collect::<Result<_, _>>()?;
+    Ok(grant)
+}
+
 pub fn cli_health(json_mode: bool) -> i32 {
     let server = match discover_server() {
         Ok(v) => v,
@@ -161,6 +204,94 @@ pub fn cli_action(json: &str) -> i32 {
     }
 }
 
+/// `okena action --batch <file> [--rollback]` — prints the batch result and
+/// exits 1 when a step failed.
+pub fn cli_action_batch(file: &str, rollback: bool) -> i32 {
+    let raw = if file == "-" {
+        let mut s = String::new();
+        if let Err(e) = std::io::Read::read_to_string(&mut std::io::stdin(), &mut s) {
+            eprintln!("Failed to read stdin: {e}");
+            return 1;
+        }
+        s
+    } else {
+        match std::fs::read_to_string(file) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("Failed to read {file}: {e}");
+                return 1;
+            }
+        }
+    };
+    // A bare array is the common case; the object form carries `rollback`.
+    let mut request = match serde_json::from_str::<serde_json::Value>(&raw) {
+        Ok(serde_json::Value::Array(actions)) => okena_core::batch::ActionBatchRequest {
+            actions,
+            rollback: false,
+        },
+        Ok(value) => match serde_json::from_value(value) {
+            Ok(request) => request,
+            Err(e) => {
+                eprintln!("Invalid batch: {e}");
+                return 1;
+            }
+        },
+        Err(e) => {
+            eprintln!("Invalid JSON: {e}");
+            return 1;
+        }
+    };
+    request.rollback |= rollback;
+
+    let token = match ensure_token() {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    let resp = discover_server()
+        .and_then(|server| server.client_and_url("/v1/actions/batch"))
+        .and_then(|(client, url)| {
+            client
+                .post(&url)
+                .header("Authorization", format!("Bearer {token}"))
+                .json(&request)
+                .send()
+                .map_err(|e| format!("Request failed: {e}"))
+        });
+    let resp = match resp {
+        Ok(resp) => resp,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    let status = resp.status();
+    let body = resp.text().unwrap_or_default();
+    // A failed step still answers with the batch result (400).
+    let result: okena_core::batch::ApiBatchResult = match serde_json::from_str(&body) {
+        Ok(result) => result,
+        Err(_) => {
+            let detail = serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|v| v.get("error").and_then(|e| e.as_str()).map(str::to_string))
+                .unwrap_or_else(|| format!("Server returned {status}"));
+            eprintln!("{detail}");
+            return 1;
+        }
+    };
+    println!("{body}");
+    let Some(step) = result.failed_step else {
+        return 0;
+    };
+    eprintln!("Step {step} failed: {}", result.error.unwrap_or_default());
+    if result.rolled_back {
+        eprintln!("Layout changes were rolled back.");
+    }
+    1
+}
+
 /// `okena services [--json] [project]`
 ///
 /// Default: tab-separated lines: project_name \t service_name \t status \t kind \t ports
@@ -249,10 +380,12 @@ pub fn cli_services(project_filter: Option<&str>, json_mode: bool) -> i32 {
     0
 }
 
-/// `okena service <start|stop|restart> <name> [project] [--json]`
+/// `okena service <start|stop|restart|rebuild|pull> <name> [project] [--json]`
 ///
 /// Sends the action and waits for the service to reach the target status.
-/// For start/restart, also waits for port detection to stabilize.
+/// For start/restart/rebuild, also waits for port detection to stabilize.
+/// Pull has no target status (the container keeps running), so it returns as
+/// soon as the daemon accepts the action.
 ///
 /// Default output: name \t status \t ports
 /// --json: object with name, status, kind, ports
@@ -266,12 +399,16 @@ pub fn cli_service(
         "start" => ("start_service", &["running"]),
         "stop" => ("stop_service", &["stopped"]),
         "restart" => ("restart_service", &["running"]),
+        "rebuild" => ("rebuild_service", &["running"]),
+        "pull" => ("pull_service", &[]),
         _ => {
             eprintln!("Unknown service action: {verb}");
-            eprintln!("Use: start, stop, restart");
+            eprintln!("Use: start, stop, restart, rebuild, pull");
             return 1;
         }
     };
+    // Image builds routinely outlast the 30s a plain (re)start gets.
+    let timeout_secs = if verb == "rebuild" { 600 } else { 30 };
 
     let token = match ensure_token() {
         Ok(t) => t,
@@ -327,9 +464,21 @@ pub fn cli_service(
         return 1;
     }
 
+    if target_statuses.is_empty() {
+        if let Some(svc) = state
+            .projects
+            .iter()
+            .find(|p| p.id == project_id)
+            .and_then(|p| p.services.iter().find(|s| s.name == service_name))
+        {
+            print_service_result(svc, json_mode);
+        }
+        return 0;
+    }
+
     // Poll until target status is reached
     let wait_for_ports = target_statuses.contains(&"running");
-    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(30);
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(timeout_secs);
     let mut reached_target = false;
     let mut last_ports: Vec<u16> = Vec::new();
     let mut ports_stable_count = 0u32;
@@ -395,6 +544,48 @@ pub fn cli_service(
     }
 }
 
+/// `okena service exec <name> [project] [--shell <shell>]`
+///
+/// Opens a new terminal with an interactive shell inside a Docker Compose
+/// service's container. Prints the new terminal id.
+pub fn cli_service_exec(
+    service_name: &str,
+    project_filter: Option<&str>,
+    shell: Option<&str>,
+) -> i32 {
+    let token = match ensure_token() {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+
+    let state = match fetch_state(&token) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+
+    let project_id = match resolve_project_id_in_state(&state, project_filter) {
+        Ok(id) => id,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+
+    let body = serde_json::json!({
+        "action": "exec_service_shell",
+        "project_id": project_id,
+        "service_name": service_name,
+        "shell": shell,
+    });
+    post_action_print_ids(&token, &body, &["terminal_ids"])
+}
+
 fn print_service_result(svc: &okena_core::api::ApiServiceInfo, json_mode: bool) {
     if json_mode {
         println!(
@@ -418,127 +609,504 @@ fn print_service_result(svc: &okena_core::api::ApiServiceInfo, json_mode: bool)
     }
 }
 
-/// `okena whoami [--json]`
+// ── Hooks ────────────────────────────────────────────────────────────────────
+
+/// Short status label for a hook execution / run.
+fn hook_status_label(status: &ApiHookStatus) -> String {
+    match status {
+        ApiHookStatus::Running => "running".to_string(),
+        ApiHookStatus::Succeeded { .. } => "ok".to_string(),
+        ApiHookStatus::Failed { exit_code, .. } => format!("exit {exit_code}"),
+        ApiHookStatus::SpawnError { .. } => "spawn-error".to_string(),
+        ApiHookStatus::Cancelled { .. } => "cancelled".to_string(),
+        ApiHookStatus::TimedOut { .. } => "timed-out".to_string(),
+    }
+}
+
+/// Duration of a finished hook in ms, `-` while running / when it never ran.
+fn hook_duration_label(status: &ApiHookStatus) -> String {
+    match status {
+        ApiHookStatus::Succeeded { duration_ms }
+        | ApiHookStatus::Failed { duration_ms, .. }
+        | ApiHookStatus::Cancelled { duration_ms }
+        | ApiHookStatus::TimedOut { duration_ms } => format!("{duration_ms}ms"),
+        ApiHookStatus::Running | ApiHookStatus::SpawnError { .. } => "-".to_string(),
+    }
+}
+
+/// `okena hook ls [--json]`
 ///
-/// Default: tab-separated: terminal_id \t project_id \t project_name \t project_path
-/// --json: object
-pub fn cli_whoami(json_mode: bool) -> i32 {
-    let terminal_id = match std::env::var("OKENA_TERMINAL_ID") {
-        Ok(id) => id,
-        Err(_) => {
-            let msg = "Not running inside an Okena terminal (OKENA_TERMINAL_ID not set).";
-            if json_mode {
-                eprintln!("{}", serde_json::json!({ "error": msg }));
-            } else {
-                eprintln!("{msg}");
-            }
-            return 1;
+/// Default: tab-separated: id \t hook_type \t status \t duration \t project \t command
+pub fn cli_hook_ls(json_mode: bool) -> i32 {
+    with_state_read(|state| {
+        if json_mode {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&state.hooks).map_err(|e| e.to_string())?
+            );
+            return Ok(());
         }
-    };
+        for exec in &state.hooks {
+            println!(
+                "{}\t{}\t{}\t{}\t{}\t{}",
+                exec.id,
+                exec.hook_type,
+                hook_status_label(&exec.status),
+                hook_duration_label(&exec.status),
+                exec.project_name,
+                exec.command
+            );
+        }
+        Ok(())
+    })
+}
 
-    let token = match ensure_token() {
-        Ok(t) => t,
+/// `okena hook cancel <id>`
+pub fn cli_hook_cancel(id: u64) -> i32 {
+    let body = serde_json::json!({ "action": "cancel_hook", "execution_id": id });
+    match post_action_body(&body) {
+        Ok(_) => {
+            println!("ok");
+            0
+        }
         Err(e) => {
-            if json_mode {
-                println!("{}", serde_json::json!({ "terminal_id": terminal_id }));
-            } else {
-                println!("{terminal_id}");
-            }
-            eprintln!("Warning: could not reach Okena server: {e}");
-            return 0;
+            eprintln!("{e}");
+            1
         }
-    };
+    }
+}
 
-    match fetch_state(&token) {
-        Ok(state) => {
-            for project in &state.projects {
-                if has_terminal_id(&project.layout, &terminal_id)
-                    || project.terminal_names.contains_key(&terminal_id)
-                    || project
-                        .services
-                        .iter()
-                        .any(|s| s.terminal_id.as_deref() == Some(&terminal_id))
-                {
-                    if json_mode {
-                        println!(
-                            "{}",
-                            serde_json::json!({
-                                "terminal_id": terminal_id,
-                                "project_id": project.id,
-                                "project_name": project.name,
-                                "project_path": project.path,
-                            })
-                        );
-                    } else {
-                        println!(
-                            "{}\t{}\t{}\t{}",
-                            terminal_id, project.id, project.name, project.path
-                        );
-                    }
-                    return 0;
-                }
-            }
+/// POST a hook-run action and decode its JSON response.
+fn fetch_hook_runs<T: serde::de::DeserializeOwned>(body: serde_json::Value) -> Result<T, String> {
+    let resp = post_action_body(&body)?;
+    serde_json::from_str(&resp).map_err(|e| format!("Invalid hook run response: {e}"))
+}
 
-            // Terminal not found in any project
-            if json_mode {
-                println!("{}", serde_json::json!({ "terminal_id": terminal_id }));
-            } else {
-                println!("{terminal_id}");
+/// `okena hook logs [--type <t>] [--limit <n>] [--json]`
+///
+/// Default: tab-separated: id \t hook_type \t status \t duration \t project \t command
+pub fn cli_hook_logs(hook_type: Option<&str>, limit: usize, json_mode: bool) -> i32 {
+    let mut body = serde_json::json!({ "action": "list_hook_runs", "limit": limit });
+    if let Some(ty) = hook_type {
+        body["hook_type"] = serde_json::Value::String(ty.to_string());
+    }
+    if json_mode {
+        return post_and_print(body);
+    }
+    match fetch_hook_runs::<Vec<ApiHookRun>>(body) {
+        Ok(runs) => {
+            for run in runs {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}\t{}",
+                    run.id,
+                    run.hook_type,
+                    hook_status_label(&run.status),
+                    hook_duration_label(&run.status),
+                    run.project_name,
+                    run.command
+                );
             }
             0
         }
         Err(e) => {
-            if json_mode {
-                println!("{}", serde_json::json!({ "terminal_id": terminal_id }));
-            } else {
-                println!("{terminal_id}");
-            }
-            eprintln!("Warning: could not fetch state: {e}");
-            0
+            eprintln!("{e}");
+            1
         }
     }
 }
 
-/// Check if a layout tree contains a terminal with the given ID.
-fn has_terminal_id(node: &Option<okena_core::api::ApiLayoutNode>, terminal_id: &str) -> bool {
-    use okena_core::api::ApiLayoutNode;
-    match node {
-        None => false,
-        Some(ApiLayoutNode::Terminal {
-            terminal_id: Some(id),
-            ..
-        }) => id == terminal_id,
-        Some(ApiLayoutNode::Terminal { .. }) => false,
-        Some(ApiLayoutNode::Split { children, .. })
-        | Some(ApiLayoutNode::Tabs { children, .. }) => children
-            .iter()
-            .any(|c| has_terminal_id(&Some(c.clone()), terminal_id)),
+/// `okena hook log <id> [--json]`
+///
+/// Default: the run's stdout on stdout and its stderr on stderr, verbatim.
+pub fn cli_hook_log(id: u64, json_mode: bool) -> i32 {
+    let body = serde_json::json!({ "action": "get_hook_run", "run_id": id });
+    if json_mode {
+        return post_and_print(body);
+    }
+    match fetch_hook_runs::<ApiHookRun>(body) {
+        Ok(run) => {
+            print!("{}", run.stdout);
+            eprint!("{}", run.stderr);
+            0
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
     }
 }
 
-pub(crate) fn fetch_state(token: &str) -> Result<StateResponse, String> {
-    let body = api_get("/v1/state", token)?;
-    serde_json::from_str(&body).map_err(|e| format!("Failed to parse state: {e}"))
-}
-
-/// Resolve a project ID from a name/id filter, or pick the only project if
-/// unambiguous. Operates over an already-fetched state (no I/O) so callers that
-/// need the state for follow-up validation don't fetch it twice.
-fn resolve_project_id_in_state(
-    state: &StateResponse,
-    filter: Option<&str>,
-) -> Result<String, String> {
-    match filter {
-        Some(f) => {
-            for p in &state.projects {
-                if p.id == f || p.name.eq_ignore_ascii_case(f) {
-                    return Ok(p.id.clone());
+/// `okena hook diff <base> <run> [--json]`
+///
+/// Default: unified diffs of stdout then stderr; exits 1 when the output differs
+/// (like `diff`), 0 when both streams are identical.
+pub fn cli_hook_diff(base: u64, run: u64, json_mode: bool) -> i32 {
+    let body = serde_json::json!({
+        "action": "diff_hook_runs",
+        "base_run_id": base,
+        "run_id": run,
+    });
+    if json_mode {
+        return post_and_print(body);
+    }
+    match fetch_hook_runs::<ApiHookRunDiff>(body) {
+        Ok(diff) => {
+            if diff.base.hook_type != diff.run.hook_type {
+                eprintln!(
+                    "Warning: comparing runs of different hooks ({} vs {})",
+                    diff.base.hook_type, diff.run.hook_type
+                );
+            }
+            for (stream, text) in [("stdout", &diff.stdout_diff), ("stderr", &diff.stderr_diff)] {
+                if !text.is_empty() {
+                    println!("# {stream}");
+                    print!("{text}");
                 }
             }
-            Err(format!(
-                "Project not found: {f}\nAvailable: {}",
-                state
-                    .projects
+            i32::from(!diff.stdout_diff.is_empty() || !diff.stderr_diff.is_empty())
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            2
+        }
+    }
+}
+
+/// How long `okena events` keeps one stream open. The blocking client applies
+/// its timeout to the whole body read, so a subscription needs a bound far past
+/// any realistic session rather than the usual request timeout.
+const EVENT_STREAM_TIMEOUT: std::time::Duration =
+    std::time::Duration::from_secs(365 * 24 * 60 * 60);
+
+/// `okena events [--type T,...] [--project P,...] [--count N]`
+///
+/// Streams `/v1/events` to stdout as NDJSON, flushing after every line so
+/// pipelines see events as they happen. Project names are resolved to ids up
+/// front; filtering itself happens on the server.
+pub fn cli_events(types: &[String], projects: &[String], count: Option<usize>) -> i32 {
+    let type_filter = (!types.is_empty()).then(|| types.join(","));
+    if let Err(e) = okena_core::events::EventFilter::parse(type_filter.as_deref(), None) {
+        eprintln!("{e}");
+        return 1;
+    }
+    let token = match ensure_token() {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    let project_ids = if projects.is_empty() {
+        Vec::new()
+    } else {
+        let resolved = fetch_state(&token).and_then(|state| {
+            projects
+                .iter()
+                .map(|p| resolve_project_id_in_state(&state, Some(p)))
+                .collect::<Result<Vec<_>, _>>()
+        });
+        match resolved {
+            Ok(ids) => ids,
+            Err(e) => {
+                eprintln!("{e}");
+                return 1;
+            }
+        }
+    };
+
+    let mut query = Vec::new();
+    if let Some(types) = &type_filter {
+        query.push(format!("type={types}"));
+    }
+    if !project_ids.is_empty() {
+        query.push(format!("project={}", project_ids.join(",")));
+    }
+    let path = if query.is_empty() {
+        "/v1/events".to_string()
+    } else {
+        format!("/v1/events?{}", query.join("&"))
+    };
+
+    match stream_events(&token, &path, count) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
+    }
+}
+
+/// `okena schema [TYPE]`: the OpenAPI document, or one type's JSON Schema.
+pub fn cli_schema(type_name: Option<&str>) -> i32 {
+    let document = match type_name {
+        None => okena_core::schema::openapi(),
+        Some(name) => match okena_core::schema::json_schema(name) {
+            Some(schema) => schema,
+            None => {
+                let names: Vec<_> = okena_core::schema::type_names().collect();
+                eprintln!(
+                    "unknown type '{name}'; expected one of: {}",
+                    names.join(", ")
+                );
+                return 1;
+            }
+        },
+    };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&document).unwrap_or_default()
+    );
+    0
+}
+
+/// `okena audit [--device D] [--kind K] [--since AGE] [--limit N] [--json]`
+///
+/// Default: tab-separated, oldest first: at \t device \t client \t kind \t
+/// ok|denied \t summary
+pub fn cli_audit(
+    device: Option<String>,
+    kind: Option<String>,
+    since: Option<u64>,
+    limit: usize,
+    json_mode: bool,
+) -> i32 {
+    let query = okena_core::audit::AuditQuery {
+        token: device,
+        kind,
+        since: since.map(|age| okena_core::audit::now_secs().saturating_sub(age)),
+        limit: Some(limit),
+    };
+    match fetch_audit(&query) {
+        Ok(entries) if json_mode => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&entries).unwrap_or_default()
+            );
+            0
+        }
+        Ok(entries) => {
+            for entry in entries {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}\t{}",
+                    entry.at,
+                    entry.token_name.as_deref().unwrap_or(&entry.token_id),
+                    entry.client,
+                    entry.kind,
+                    if entry.denied { "denied" } else { "ok" },
+                    entry.summary
+                );
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
+    }
+}
+
+fn fetch_audit(
+    query: &okena_core::audit::AuditQuery,
+) -> Result<Vec<okena_core::audit::AuditEntry>, String> {
+    #[derive(serde::Deserialize)]
+    struct AuditResponse {
+        entries: Vec<okena_core::audit::AuditEntry>,
+    }
+
+    let token = ensure_token()?;
+    let server = discover_server()?;
+    let (client, url) = server.client_and_url("/v1/audit")?;
+    let resp = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {token}"))
+        .query(query)
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .map_err(|e| format!("Request failed: {e}"))?;
+    if resp.status() == reqwest::StatusCode::FORBIDDEN {
+        return Err("The audit log needs a token with the 'admin' scope.".into());
+    }
+    if !resp.status().is_success() {
+        return Err(format!("Server returned {}", resp.status()));
+    }
+    resp.json::<AuditResponse>()
+        .map(|body| body.entries)
+        .map_err(|e| format!("Invalid audit response: {e}"))
+}
+
+fn stream_events(token: &str, path: &str, count: Option<usize>) -> Result<(), String> {
+    use std::io::{BufRead as _, Write as _};
+
+    if count == Some(0) {
+        return Ok(());
+    }
+    let server = discover_server()?;
+    let (client, url) = server.client_and_url(path)?;
+    let resp = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {token}"))
+        .timeout(EVENT_STREAM_TIMEOUT)
+        .send()
+        .map_err(|e| format!("Request failed: {e}"))?;
+    if resp.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err("Token expired or revoked. Delete ~/.config/okena/cli.json and retry.".into());
+    }
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().unwrap_or_default();
+        let detail = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|v| v.get("error").and_then(|e| e.as_str()).map(str::to_string));
+        return Err(match detail {
+            Some(detail) => detail,
+            None => format!("Server returned {status}"),
+        });
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    let mut printed = 0usize;
+    for line in std::io::BufReader::new(resp).lines() {
+        let line = line.map_err(|e| format!("Event stream failed: {e}"))?;
+        // Blank lines are server heartbeats.
+        if line.trim().is_empty() {
+            continue;
+        }
+        if writeln!(stdout, "{line}")
+            .and_then(|()| stdout.flush())
+            .is_err()
+        {
+            // Reader went away (e.g. `okena events | head -1`).
+            return Ok(());
+        }
+        printed += 1;
+        if count.is_some_and(|n| printed >= n) {
+            return Ok(());
+        }
+    }
+    Err("Event stream closed by the server.".to_string())
+}
+
+/// `okena whoami [--json]`
+///
+/// Default: tab-separated: terminal_id \t project_id \t project_name \t project_path
+/// --json: object
+pub fn cli_whoami(json_mode: bool) -> i32 {
+    let terminal_id = match std::env::var("OKENA_TERMINAL_ID") {
+        Ok(id) => id,
+        Err(_) => {
+            let msg = "Not running inside an Okena terminal (OKENA_TERMINAL_ID not set).";
+            if json_mode {
+                eprintln!("{}", serde_json::json!({ "error": msg }));
+            } else {
+                eprintln!("{msg}");
+            }
+            return 1;
+        }
+    };
+
+    let token = match ensure_token() {
+        Ok(t) => t,
+        Err(e) => {
+            if json_mode {
+                println!("{}", serde_json::json!({ "terminal_id": terminal_id }));
+            } else {
+                println!("{terminal_id}");
+            }
+            eprintln!("Warning: could not reach Okena server: {e}");
+            return 0;
+        }
+    };
+
+    match fetch_state(&token) {
+        Ok(state) => {
+            for project in &state.projects {
+                if has_terminal_id(&project.layout, &terminal_id)
+                    || project.terminal_names.contains_key(&terminal_id)
+                    || project
+                        .services
+                        .iter()
+                        .any(|s| s.terminal_id.as_deref() == Some(&terminal_id))
+                {
+                    if json_mode {
+                        println!(
+                            "{}",
+                            serde_json::json!({
+                                "terminal_id": terminal_id,
+                                "project_id": project.id,
+                                "project_name": project.name,
+                                "project_path": project.path,
+                            })
+                        );
+                    } else {
+                        println!(
+                            "{}\t{}\t{}\t{}",
+                            terminal_id, project.id, project.name, project.path
+                        );
+                    }
+                    return 0;
+                }
+            }
+
+            // Terminal not found in any project
+            if json_mode {
+                println!("{}", serde_json::json!({ "terminal_id": terminal_id }));
+            } else {
+                println!("{terminal_id}");
+            }
+            0
+        }
+        Err(e) => {
+            if json_mode {
+                println!("{}", serde_json::json!({ "terminal_id": terminal_id }));
+            } else {
+                println!("{terminal_id}");
+            }
+            eprintln!("Warning: could not fetch state: {e}");
+            0
+        }
+    }
+}
+
+/// Check if a layout tree contains a terminal with the given ID.
+fn has_terminal_id(node: &Option<okena_core::api::ApiLayoutNode>, terminal_id: &str) -> bool {
+    use okena_core::api::ApiLayoutNode;
+    match node {
+        None => false,
+        Some(ApiLayoutNode::Terminal {
+            terminal_id: Some(id),
+            ..
+        }) => id == terminal_id,
+        Some(ApiLayoutNode::Terminal { .. }) => false,
+        Some(ApiLayoutNode::Split { children, .. })
+        | Some(ApiLayoutNode::Tabs { children, .. }) => children
+            .iter()
+            .any(|c| has_terminal_id(&Some(c.clone()), terminal_id)),
+    }
+}
+
+pub(crate) fn fetch_state(token: &str) -> Result<StateResponse, String> {
+    let body = api_get("/v1/state", token)?;
+    serde_json::from_str(&body).map_err(|e| format!("Failed to parse state: {e}"))
+}
+
+/// Resolve a project ID from a name/id filter, or pick the only project if
+/// unambiguous. Operates over an already-fetched state (no I/O) so callers that
+/// need the state for follow-up validation don't fetch it twice.
+fn resolve_project_id_in_state(
+    state: &StateResponse,
+    filter: Option<&str>,
+) -> Result<String, String> {
+    match filter {
+        Some(f) => {
+            for p in &state.projects {
+                if p.id == f || p.name.eq_ignore_ascii_case(f) {
+                    return Ok(p.id.clone());
+                }
+            }
+            Err(format!(
+                "Project not found: {f}\nAvailable: {}",
+                state
+                    .projects
                     .iter()
                     .map(|p| p.name.as_str())
                     .collect::<Vec<_>>()
@@ -701,7 +1269,7 @@ fn validate_color(color: &str) -> Result<String, String> {
 /// carries). Named keys serialize to a bare string (e.g. `"Enter"`); a generic
 /// `ctrl-<letter>` chord serializes to `{"Ctrl":"l"}`. Supports the canonical
 /// names plus friendly aliases.
-fn map_special_key(key: &str) -> Result<serde_json::Value, String> {
+pub(crate) fn map_special_key(key: &str) -> Result<serde_json::Value, String> {
     let k = key.to_ascii_lowercase().replace(['-', '_', ' '], "");
     let named = match k.as_str() {
         "enter" | "return" | "cr" => Some("Enter"),
@@ -1456,6 +2024,367 @@ pub fn cli_worktree_rm(worktree: &str, force: bool) -> i32 {
     })
 }
 
+// ── Git ──────────────────────────────────────────────────────────────────────
+
+/// Resolve `project` and run `f` with the token and the resolved project.
+fn with_git_project<F>(project: &str, f: F) -> i32
+where
+    F: FnOnce(&str, &ApiProject) -> Result<(), String>,
+{
+    let result = ensure_token().and_then(|token| {
+        let state = fetch_state(&token)?;
+        let project = resolve::resolve_project(&state, project)?;
+        f(&token, project)
+    });
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
+    }
+}
+
+/// POST a git action and decode its response into the `okena_git` type it
+/// serializes.
+fn git_query<T: serde::de::DeserializeOwned>(
+    token: &str,
+    body: &serde_json::Value,
+) -> Result<T, String> {
+    let resp = api_action(token, &body.to_string())?;
+    serde_json::from_str(&resp).map_err(|e| format!("Invalid git response: {e}"))
+}
+
+/// `okena git status <project> [--json]`
+///
+/// Default: one `key \t value…` line per known field — branch, changes,
+/// ahead, behind, unpushed, review_base, pr, ci. Falls back to the status in
+/// the state snapshot while the daemon's status cache is still cold.
+pub fn cli_git_status(project: &str, json_mode: bool) -> i32 {
+    with_git_project(project, |token, project| {
+        let body = serde_json::json!({ "action": "git_status", "project_id": project.id });
+        let status =
+            git_query::<Option<ApiGitStatus>>(token, &body)?.or_else(|| project.git_status.clone());
+        if json_mode {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&status).map_err(|e| e.to_string())?
+            );
+            return Ok(());
+        }
+        let Some(status) = status else {
+            return Err(format!(
+                "No git status for {} (not a repository?)",
+                project.name
+            ));
+        };
+        println!(
+            "branch\t{}",
+            status.branch.as_deref().unwrap_or("(detached)")
+        );
+        println!(
+            "changes\t+{}\t-{}",
+            status.lines_added, status.lines_removed
+        );
+        for (key, value) in [
+            ("ahead", status.ahead),
+            ("behind", status.behind),
+            ("unpushed", status.unpushed),
+        ] {
+            if let Some(value) = value {
+                println!("{key}\t{value}");
+            }
+        }
+        if let Some(base) = &status.review_base {
+            println!("review_base\t{base}");
+        }
+        if let Some(pr) = &status.pr_info {
+            println!("pr\t#{}\t{}\t{}", pr.number, pr.state.label(), pr.url);
+        }
+        if let Some(ci) = &status.ci_checks {
+            println!(
+                "ci\t{}\tpassed={}\tfailed={}\tpending={}",
+                format!("{:?}", ci.status).to_lowercase(),
+                ci.passed,
+                ci.failed,
+                ci.pending
+            );
+        }
+        Ok(())
+    })
+}
+
+/// `okena git diff <project> [--staged|--commit <ref>|--base <b> --head <h>] [--stat] [--json]`
+///
+/// Default: a unified diff. `--stat`: path \t +added \t -removed per file.
+pub fn cli_git_diff(
+    project: &str,
+    mode: okena_core::types::DiffMode,
+    ignore_whitespace: bool,
+    stat: bool,
+    json_mode: bool,
+) -> i32 {
+    with_git_project(project, |token, project| {
+        let body = serde_json::json!({
+            "action": "git_diff",
+            "project_id": project.id,
+            "mode": mode,
+            "ignore_whitespace": ignore_whitespace,
+        });
+        if json_mode {
+            print_json_pretty(&api_action(token, &body.to_string())?);
+            return Ok(());
+        }
+        let diff: okena_git::DiffResult = git_query(token, &body)?;
+        if stat {
+            for file in &diff.files {
+                println!(
+                    "{}\t+{}\t-{}",
+                    file.display_name(),
+                    file.lines_added,
+                    file.lines_removed
+                );
+            }
+        } else {
+            print!("{}", render_unified_diff(&diff));
+        }
+        Ok(())
+    })
+}
+
+/// Turn a parsed diff back into `git diff`-style text. Each hunk's first line
+/// is its own `@@` header, so hunks need no separate header.
+pub(crate) fn render_unified_diff(diff: &okena_git::DiffResult) -> String {
+    use okena_git::DiffLineType;
+    let mut out = String::new();
+    for file in &diff.files {
+        let header_old = file.old_path.as_deref().unwrap_or(file.display_name());
+        out.push_str(&format!(
+            "diff --git a/{header_old} b/{}\n",
+            file.display_name()
+        ));
+        let old = file
+            .old_path
+            .as_ref()
+            .map_or_else(|| "/dev/null".to_string(), |p| format!("a/{p}"));
+        let new = file
+            .new_path
+            .as_ref()
+            .map_or_else(|| "/dev/null".to_string(), |p| format!("b/{p}"));
+        if file.is_binary {
+            out.push_str(&format!("Binary files {old} and {new} differ\n"));
+            continue;
+        }
+        out.push_str(&format!("--- {old}\n+++ {new}\n"));
+        for line in file.hunks.iter().flat_map(|h| &h.lines) {
+            let prefix = match line.line_type {
+                DiffLineType::Header => "",
+                DiffLineType::Added => "+",
+                DiffLineType::Removed => "-",
+                DiffLineType::Context => " ",
+            };
+            out.push_str(prefix);
+            out.push_str(&line.content);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// `okena git branches <project> [--json]`
+///
+/// Default: name \t local|remote \t * (current) \t upstream \t worktree
+pub fn cli_git_branches(project: &str, json_mode: bool) -> i32 {
+    with_git_project(project, |token, project| {
+        let body = serde_json::json!({
+            "action": "git_list_branches_classified",
+            "project_id": project.id,
+        });
+        if json_mode {
+            print_json_pretty(&api_action(token, &body.to_string())?);
+            return Ok(());
+        }
+        let list: okena_git::BranchList = git_query(token, &body)?;
+        let rows = list
+            .local
+            .iter()
+            .map(|name| (name, "local"))
+            .chain(list.remote.iter().map(|name| (name, "remote")));
+        for (name, kind) in rows {
+            let detail = list.details.get(name).cloned().unwrap_or_default();
+            let current = if list.current.as_ref() == Some(name) {
+                "*"
+            } else {
+                ""
+            };
+            let upstream = match detail.upstream {
+                okena_git::UpstreamState::Untracked => String::new(),
+                okena_git::UpstreamState::Gone => "gone".to_string(),
+                okena_git::UpstreamState::Tracked {
+                    name,
+                    ahead,
+                    behind,
+                } => format!("{name} +{ahead} -{behind}"),
+            };
+            println!(
+                "{name}\t{kind}\t{current}\t{upstream}\t{}",
+                detail.worktree.unwrap_or_default()
+            );
+        }
+        Ok(())
+    })
+}
+
+/// `okena git checkout <project> <branch> [-b [--start-point <ref>]]`
+pub fn cli_git_checkout(
+    project: &str,
+    branch: &str,
+    create: bool,
+    start_point: Option<&str>,
+) -> i32 {
+    with_git_project(project, |token, project| {
+        let body = if create {
+            serde_json::json!({
+                "action": "git_create_and_checkout_branch",
+                "project_id": project.id,
+                "new_name": branch,
+                "start_point": start_point,
+            })
+        } else {
+            let list: okena_git::BranchList = git_query(
+                token,
+                &serde_json::json!({
+                    "action": "git_list_branches_classified",
+                    "project_id": project.id,
+                }),
+            )?;
+            checkout_action(&list, &project.id, branch)?
+        };
+        api_action(token, &body.to_string())?;
+        Ok(())
+    })
+}
+
+/// Pick the checkout action for `branch`: a local branch as-is, a remote one
+/// (`origin/x`, or bare `x` when only `origin/x` exists) via a new tracking
+/// branch.
+fn checkout_action(
+    list: &okena_git::BranchList,
+    project_id: &str,
+    branch: &str,
+) -> Result<serde_json::Value, String> {
+    if list.local.iter().any(|b| b == branch) {
+        return Ok(serde_json::json!({
+            "action": "git_checkout_local_branch",
+            "project_id": project_id,
+            "branch": branch,
+        }));
+    }
+    let remote = list.remote.iter().find(|b| *b == branch).or_else(|| {
+        list.remote
+            .iter()
+            .find(|b| b.split_once('/').map(|(_, rest)| rest) == Some(branch))
+    });
+    match remote {
+        Some(remote_branch) => Ok(serde_json::json!({
+            "action": "git_checkout_remote_branch",
+            "project_id": project_id,
+            "remote_branch": remote_branch,
+        })),
+        None => Err(format!(
+            "Branch not found: {branch} (use -b to create it)\nAvailable: {}",
+            list.local
+                .iter()
+                .chain(&list.remote)
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// `okena git stage|unstage|discard <project> <files…>`
+///
+/// One action per file, stopping at the first failure.
+pub fn cli_git_files(project: &str, files: &[String], action: &str) -> i32 {
+    with_git_project(project, |token, project| {
+        for file in files {
+            let body = serde_json::json!({
+                "action": action,
+                "project_id": project.id,
+                "file_path": file,
+            });
+            api_action(token, &body.to_string()).map_err(|e| format!("{file}: {e}"))?;
+        }
+        Ok(())
+    })
+}
+
+/// `okena git log <project> [-n <count>] [--branch <b>] [--json]`
+///
+/// Default: hash \t age \t author \t refs (comma-separated) \t subject
+pub fn cli_git_log(project: &str, count: usize, branch: Option<&str>, json_mode: bool) -> i32 {
+    with_git_project(project, |token, project| {
+        let body = serde_json::json!({
+            "action": "git_commit_graph",
+            "project_id": project.id,
+            "count": count,
+            "branch": branch,
+        });
+        if json_mode {
+            print_json_pretty(&api_action(token, &body.to_string())?);
+            return Ok(());
+        }
+        let entries: Vec<okena_git::CommitLogEntry> = git_query(token, &body)?;
+        for entry in entries {
+            println!(
+                "{}\t{}\t{}\t{}\t{}",
+                entry.hash,
+                okena_git::format_relative_time(entry.timestamp),
+                entry.author,
+                entry.refs.join(", "),
+                entry.message
+            );
+        }
+        Ok(())
+    })
+}
+
+/// `okena git blame <project> <file> [--json]`
+///
+/// Default: line \t short hash (or `uncommitted`) \t author \t age \t summary
+pub fn cli_git_blame(project: &str, file: &str, json_mode: bool) -> i32 {
+    with_git_project(project, |token, project| {
+        let body = serde_json::json!({
+            "action": "git_blame",
+            "project_id": project.id,
+            "relative_path": file,
+        });
+        if json_mode {
+            print_json_pretty(&api_action(token, &body.to_string())?);
+            return Ok(());
+        }
+        let lines: Vec<serde_json::Value> = git_query(token, &body)?;
+        for line in &lines {
+            let commit = &line["commit"];
+            let text = |v: &serde_json::Value| v.as_str().unwrap_or_default().to_string();
+            let hash = if line["kind"] == "Uncommitted" {
+                "uncommitted".to_string()
+            } else {
+                text(&commit["short_hash"])
+            };
+            println!(
+                "{}\t{hash}\t{}\t{}\t{}",
+                line["line_number"],
+                text(&commit["author"]),
+                okena_git::format_relative_time(commit["timestamp"].as_i64().unwrap_or_default()),
+                text(&commit["summary"])
+            );
+        }
+        Ok(())
+    })
+}
+
 // ── Folders ──────────────────────────────────────────────────────────────────
 
 /// `okena folder add <name>`
@@ -1494,7 +2423,7 @@ pub fn cli_folder_rename(folder: &str, name: &str) -> i32 {
 // ── Terminals & layout ───────────────────────────────────────────────────────
 
 /// `okena term new <project>`
-pub fn cli_term_new(project: &str) -> i32 {
+pub fn cli_term_new(args: &TermNewArgs) -> i32 {
     let token = match ensure_token() {
         Ok(t) => t,
         Err(e) => {
@@ -1509,15 +2438,69 @@ pub fn cli_term_new(project: &str) -> i32 {
             return 1;
         }
     };
-    let p = match resolve::resolve_project(&state, project) {
-        Ok(p) => p,
+    match term_new_body(&state, args) {
+        Ok(body) => post_action_print_ids(&token, &body, &["terminal_id"]),
         Err(e) => {
             eprintln!("{e}");
-            return 1;
+            1
         }
+    }
+}
+
+fn term_new_body(state: &StateResponse, args: &TermNewArgs) -> Result<serde_json::Value, String> {
+    let p = resolve::resolve_project(state, &args.project)?;
+    let mut body = serde_json::json!({ "action": "create_terminal", "project_id": p.id });
+
+    // --split / --tab name a terminal, which must live in the same project.
+    let anchor = |terminal: &str| -> Result<String, String> {
+        let (project_id, terminal_id) = resolve::resolve_terminal(state, terminal)?;
+        if project_id != p.id {
+            return Err(format!(
+                "Terminal {terminal} is not in project '{}'",
+                p.name
+            ));
+        }
+        Ok(terminal_id)
     };
-    let body = serde_json::json!({ "action": "create_terminal", "project_id": p.id });
-    post_action_print_ids(&token, &body, &["terminal_ids"])
+    if let Some(terminal) = &args.split {
+        body["placement"] = serde_json::json!({
+            "kind": "split",
+            "terminal_id": anchor(terminal)?,
+            "direction": parse_direction(&args.direction)?,
+        });
+    } else if let Some(terminal) = &args.tab {
+        body["placement"] = serde_json::json!({ "kind": "tab", "terminal_id": anchor(terminal)? });
+    } else if args.detached {
+        body["placement"] = serde_json::json!({ "kind": "detached" });
+    }
+
+    if !args.env.is_empty() {
+        let mut env = serde_json::Map::new();
+        for pair in &args.env {
+            let (key, value) = pair
+                .split_once('=')
+                .filter(|(key, _)| !key.is_empty())
+                .ok_or_else(|| format!("Invalid --env {pair} (use KEY=VALUE)"))?;
+            env.insert(key.to_string(), value.into());
+        }
+        body["env"] = env.into();
+    }
+    if let Some(shell) = &args.shell {
+        body["shell"] = serde_json::json!({ "type": "Custom", "path": shell });
+    }
+    for (field, value) in [
+        ("cwd", &args.cwd),
+        ("command", &args.command),
+        ("name", &args.name),
+    ] {
+        if let Some(value) = value {
+            body[field] = value.as_str().into();
+        }
+    }
+    if args.minimized {
+        body["minimized"] = true.into();
+    }
+    Ok(body)
 }
 
 /// `okena term close <terminal>`
@@ -1626,71 +2609,257 @@ pub fn cli_term_tab(terminal: &str) -> i32 {
     let state = match fetch_state(&token) {
         Ok(s) => s,
         Err(e) => {
-            eprintln!("{e}");
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    let (project_id, terminal_id) = match resolve::resolve_terminal(&state, terminal) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    let p = match state.projects.iter().find(|p| p.id == project_id) {
+        Some(p) => p,
+        None => {
+            eprintln!("Internal error: resolved project {project_id} missing from state");
+            return 1;
+        }
+    };
+    let path = match resolve::resolve_terminal_path(p, &terminal_id) {
+        Some(path) => path,
+        None => {
+            eprintln!("Could not resolve layout path for terminal {terminal_id}");
+            return 1;
+        }
+    };
+    // `in_group: false` mirrors the UI's add-tab button: if the terminal's
+    // parent is already a Tabs container the new tab joins it, otherwise the
+    // terminal is wrapped in a fresh Tabs group. `in_group: true` only works
+    // when `path` points *at* an existing Tabs node, which we never produce
+    // here (we always resolve to a terminal leaf) — so it was a silent no-op.
+    let body = serde_json::json!({
+        "action": "add_tab",
+        "project_id": project_id,
+        "path": path,
+        "in_group": false,
+    });
+    post_action_print_ids(&token, &body, &["terminal_ids"])
+}
+
+/// `okena term minimize <terminal>`
+pub fn cli_term_minimize(terminal: &str) -> i32 {
+    with_state_post(|state| {
+        let (project_id, terminal_id) = resolve::resolve_terminal(state, terminal)?;
+        Ok(serde_json::json!({
+            "action": "toggle_minimized",
+            "project_id": project_id,
+            "terminal_id": terminal_id,
+        }))
+    })
+}
+
+/// `okena term fullscreen <terminal> [--off] [--window]`
+pub fn cli_term_fullscreen(terminal: &str, off: bool, window: Option<&str>) -> i32 {
+    with_state_post(|state| {
+        let (project_id, terminal_id) = resolve::resolve_terminal(state, terminal)?;
+        let mut body = serde_json::json!({
+            "action": "set_fullscreen",
+            "project_id": project_id,
+            "terminal_id": if off { serde_json::Value::Null } else { serde_json::Value::String(terminal_id) },
+        });
+        if let Some(w) = window {
+            body["window"] = serde_json::Value::String(resolve::resolve_window(state, w)?);
+        }
+        Ok(body)
+    })
+}
+
+// ── Sessions & workspace files ───────────────────────────────────────────────
+
+/// POST a session / workspace action that replies with no body. Prints `done`
+/// on success, or `{"ok": true}` with --json.
+fn post_session_action(body: serde_json::Value, json_mode: bool, done: &str) -> i32 {
+    match post_action_body(&body) {
+        Ok(_) => {
+            if json_mode {
+                println!("{}", serde_json::json!({ "ok": true }));
+            } else {
+                println!("{done}");
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
+    }
+}
+
+/// Ask before replacing the daemon's workspace, which closes every terminal.
+/// `--yes` skips the prompt; without a terminal on stdin to ask on it is
+/// required. Returns the exit code to stop with, or `None` to go ahead.
+fn confirm_workspace_replacement(what: &str, yes: bool) -> Option<i32> {
+    use std::io::IsTerminal as _;
+    confirm_replacement(
+        what,
+        yes,
+        std::io::stdin().is_terminal(),
+        || {
+            ensure_token()
+                .and_then(|token| fetch_state(&token))
+                .map(|state| {
+                    state
+                        .projects
+                        .iter()
+                        .map(|p| resolve::project_terminals(p).len())
+                        .sum::<usize>()
+                })
+                .unwrap_or(0)
+        },
+        || {
+            let mut answer = String::new();
+            std::io::stdin().read_line(&mut answer).ok()?;
+            Some(answer)
+        },
+    )
+}
+
+/// [`confirm_workspace_replacement`] with stdin abstracted: `interactive` is
+/// whether stdin is a terminal, `read_answer` reads one line from it.
+fn confirm_replacement(
+    what: &str,
+    yes: bool,
+    interactive: bool,
+    terminal_count: impl FnOnce() -> usize,
+    read_answer: impl FnOnce() -> Option<String>,
+) -> Option<i32> {
+    if yes {
+        return None;
+    }
+    let terminal_count = terminal_count();
+    eprintln!("{what} replaces the current workspace and closes its {terminal_count} terminal(s).");
+    if !interactive {
+        eprintln!("Re-run with --yes to confirm.");
+        return Some(2);
+    }
+    eprint!("Continue? [y/N] ");
+    if !read_answer().is_some_and(|answer| matches!(answer.trim(), "y" | "Y" | "yes")) {
+        eprintln!("Aborted.");
+        return Some(1);
+    }
+    None
+}
+
+/// `okena session ls [--json]`
+///
+/// Default: tab-separated: name \t project_count \t modified_at \t created_at
+pub fn cli_session_ls(json_mode: bool) -> i32 {
+    let body = serde_json::json!({ "action": "list_sessions" });
+    if json_mode {
+        return post_and_print(body);
+    }
+    let sessions = post_action_body(&body).and_then(|resp| {
+        serde_json::from_str::<Vec<okena_workspace::sessions::SessionInfo>>(&resp)
+            .map_err(|e| format!("Invalid session list: {e}"))
+    });
+    match sessions {
+        Ok(sessions) => {
+            for session in sessions {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    session.name, session.project_count, session.modified_at, session.created_at
+                );
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
+    }
+}
+
+/// `okena session save <name> [--json]`
+pub fn cli_session_save(name: &str, json_mode: bool) -> i32 {
+    post_session_action(
+        serde_json::json!({ "action": "save_session", "name": name }),
+        json_mode,
+        name,
+    )
+}
+
+/// `okena session load <name> [--yes] [--json]`
+pub fn cli_session_load(name: &str, yes: bool, json_mode: bool) -> i32 {
+    if let Some(code) = confirm_workspace_replacement(&format!("Loading '{name}'"), yes) {
+        return code;
+    }
+    post_session_action(
+        serde_json::json!({ "action": "load_session", "name": name }),
+        json_mode,
+        name,
+    )
+}
+
+/// `okena session rm <name> [--json]`
+pub fn cli_session_rm(name: &str, json_mode: bool) -> i32 {
+    post_session_action(
+        serde_json::json!({ "action": "delete_session", "name": name }),
+        json_mode,
+        "ok",
+    )
+}
+
+/// `okena session rename <name> <new-name> [--json]`
+pub fn cli_session_rename(name: &str, new_name: &str, json_mode: bool) -> i32 {
+    post_session_action(
+        serde_json::json!({
+            "action": "rename_session",
+            "old_name": name,
+            "new_name": new_name,
+        }),
+        json_mode,
+        new_name,
+    )
+}
+
+/// `okena workspace export <file> [--json]` — prints the absolute path written.
+///
+/// The daemon writes the file, so a relative path is made absolute against the
+/// CLI's CWD first.
+pub fn cli_workspace_export(file: &str, json_mode: bool) -> i32 {
+    let abs = match std::path::absolute(file) {
+        Ok(p) => p.to_string_lossy().into_owned(),
+        Err(e) => {
+            eprintln!("Cannot resolve path '{file}': {e}");
             return 1;
         }
     };
-    let (project_id, terminal_id) = match resolve::resolve_terminal(&state, terminal) {
-        Ok(v) => v,
+    post_session_action(
+        serde_json::json!({ "action": "export_workspace", "path": abs }),
+        json_mode,
+        &abs,
+    )
+}
+
+/// `okena workspace import <file> [--yes] [--json]`
+pub fn cli_workspace_import(file: &str, yes: bool, json_mode: bool) -> i32 {
+    let abs = match std::fs::canonicalize(file) {
+        Ok(p) => p.to_string_lossy().into_owned(),
         Err(e) => {
-            eprintln!("{e}");
-            return 1;
-        }
-    };
-    let p = match state.projects.iter().find(|p| p.id == project_id) {
-        Some(p) => p,
-        None => {
-            eprintln!("Internal error: resolved project {project_id} missing from state");
+            eprintln!("Cannot resolve path '{file}': {e}");
             return 1;
         }
     };
-    let path = match resolve::resolve_terminal_path(p, &terminal_id) {
-        Some(path) => path,
-        None => {
-            eprintln!("Could not resolve layout path for terminal {terminal_id}");
-            return 1;
-        }
-    };
-    // `in_group: false` mirrors the UI's add-tab button: if the terminal's
-    // parent is already a Tabs container the new tab joins it, otherwise the
-    // terminal is wrapped in a fresh Tabs group. `in_group: true` only works
-    // when `path` points *at* an existing Tabs node, which we never produce
-    // here (we always resolve to a terminal leaf) — so it was a silent no-op.
-    let body = serde_json::json!({
-        "action": "add_tab",
-        "project_id": project_id,
-        "path": path,
-        "in_group": false,
-    });
-    post_action_print_ids(&token, &body, &["terminal_ids"])
-}
-
-/// `okena term minimize <terminal>`
-pub fn cli_term_minimize(terminal: &str) -> i32 {
-    with_state_post(|state| {
-        let (project_id, terminal_id) = resolve::resolve_terminal(state, terminal)?;
-        Ok(serde_json::json!({
-            "action": "toggle_minimized",
-            "project_id": project_id,
-            "terminal_id": terminal_id,
-        }))
-    })
-}
-
-/// `okena term fullscreen <terminal> [--off] [--window]`
-pub fn cli_term_fullscreen(terminal: &str, off: bool, window: Option<&str>) -> i32 {
-    with_state_post(|state| {
-        let (project_id, terminal_id) = resolve::resolve_terminal(state, terminal)?;
-        let mut body = serde_json::json!({
-            "action": "set_fullscreen",
-            "project_id": project_id,
-            "terminal_id": if off { serde_json::Value::Null } else { serde_json::Value::String(terminal_id) },
-        });
-        if let Some(w) = window {
-            body["window"] = serde_json::Value::String(resolve::resolve_window(state, w)?);
-        }
-        Ok(body)
-    })
+    if let Some(code) = confirm_workspace_replacement(&format!("Importing '{abs}'"), yes) {
+        return code;
+    }
+    post_session_action(
+        serde_json::json!({ "action": "import_workspace", "path": abs }),
+        json_mode,
+        &abs,
+    )
 }
 
 // ── I/O (the agent loop) ─────────────────────────────────────────────────────
@@ -1722,10 +2891,9 @@ pub fn cli_run(terminal: &str, command: &[String], wait: bool, timeout_secs: u64
         });
     }
 
-    // --wait: append a completion marker carrying the exit code, then poll the
-    // terminal until it appears. The echoed command line shows the literal `%s`
-    // (not digits), so matching `TAG:<digits>:END` only fires on the real printf
-    // output, never on the echo. Assumes a POSIX-ish shell (bash/zsh/sh).
+    // --wait: the daemon types the command into the pane and cuts its output
+    // and exit code out of the OSC 133 marks. Without shell integration it
+    // runs as a hidden process in the terminal's cwd instead.
     let token = match ensure_token() {
         Ok(t) => t,
         Err(e) => {
@@ -1733,87 +2901,65 @@ pub fn cli_run(terminal: &str, command: &[String], wait: bool, timeout_secs: u64
             return 1;
         }
     };
-    let state = match fetch_state(&token) {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!("{e}");
-            return 1;
-        }
-    };
-    let terminal_id = match resolve::resolve_terminal(&state, terminal) {
-        Ok((_project_id, tid)) => tid,
+    let terminal_id =
+        match fetch_state(&token).and_then(|state| resolve::resolve_terminal(&state, terminal)) {
+            Ok((_project_id, tid)) => tid,
+            Err(e) => {
+                eprintln!("{e}");
+                return 1;
+            }
+        };
+    let action = okena_core::api::ActionRequest::ExecInTerminal {
+        terminal_id,
+        command,
+        mirror: true,
+        timeout_ms: Some(timeout_secs.saturating_mul(1000)),
+    };
+    let resp = discover_server()
+        .and_then(|server| server.client_and_url("/v1/actions"))
+        .and_then(|(client, url)| {
+            client
+                .post(&url)
+                .header("Authorization", format!("Bearer {token}"))
+                .json(&action)
+                // The daemon enforces the timeout; leave headroom for the reply.
+                .timeout(std::time::Duration::from_secs(
+                    timeout_secs.saturating_add(10),
+                ))
+                .send()
+                .map_err(|e| format!("Request failed: {e}"))
+        });
+    let resp = match resp {
+        Ok(resp) => resp,
         Err(e) => {
             eprintln!("{e}");
             return 1;
         }
     };
-
-    let tag = format!("{MARKER_PREFIX}{}", std::process::id());
-    let full = format!("{command} ; printf '\\n{tag}:%s:END\\n' \"$?\"");
-    let body = serde_json::json!({
-        "action": "run_command",
-        "terminal_id": terminal_id,
-        "command": full,
-    });
-    if let Err(e) = api_action(&token, &body.to_string()) {
-        eprintln!("{e}");
+    let status = resp.status();
+    let body = resp.text().unwrap_or_default();
+    if !status.is_success() {
+        let detail = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|v| v.get("error").and_then(|e| e.as_str()).map(str::to_string))
+            .unwrap_or_else(|| format!("Server returned {status}"));
+        eprintln!("{detail}");
         return 1;
     }
-
-    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(timeout_secs);
-    loop {
-        if std::time::Instant::now() > deadline {
-            eprintln!("Timeout after {timeout_secs}s waiting for the command to finish.");
+    let result: okena_core::api::ApiExecResult = match serde_json::from_str(&body) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Invalid exec response: {e}");
             return 1;
         }
-        std::thread::sleep(std::time::Duration::from_millis(250));
-        let content = match fetch_terminal_content(&token, &terminal_id) {
-            Ok(c) => c,
-            Err(_) => continue,
-        };
-        if let Some(code) = parse_done_marker(&content, &tag) {
-            // Echo the visible output, dropping every marker/echo line (ours and
-            // any left on screen by previous `run --wait` calls).
-            for line in content.lines() {
-                if !line.contains(MARKER_PREFIX) {
-                    println!("{line}");
-                }
-            }
-            if code != 0 {
-                eprintln!("Command exited with status {code}.");
-            }
-            return code;
-        }
-    }
-}
-
-/// Post a `read_content` action and return the terminal's visible content.
-fn fetch_terminal_content(token: &str, terminal_id: &str) -> Result<String, String> {
-    let body = serde_json::json!({ "action": "read_content", "terminal_id": terminal_id });
-    let resp = api_action(token, &body.to_string())?;
-    let v: serde_json::Value =
-        serde_json::from_str(&resp).map_err(|e| format!("bad read response: {e}"))?;
-    Ok(v.get("content")
-        .and_then(|c| c.as_str())
-        .unwrap_or("")
-        .to_string())
-}
-
-/// Find a completion marker `TAG:<digits>:END` and return the exit code. Only
-/// matches when digits are present, so the echoed command line (which carries
-/// the literal `%s`) never yields a false positive.
-fn parse_done_marker(content: &str, tag: &str) -> Option<i32> {
-    let needle = format!("{tag}:");
-    let mut rest = content;
-    while let Some(idx) = rest.find(&needle) {
-        let after = &rest[idx + needle.len()..];
-        let digits: String = after.chars().take_while(|c| c.is_ascii_digit()).collect();
-        if !digits.is_empty() && after[digits.len()..].starts_with(":END") {
-            return digits.parse().ok();
-        }
-        rest = &rest[idx + needle.len()..];
+    };
+    print!("{}", result.stdout);
+    eprint!("{}", result.stderr);
+    let code = result.exit_code.unwrap_or(1);
+    if code != 0 {
+        eprintln!("Command exited with status {code}.");
     }
-    None
+    code
 }
 
 /// `okena skill show` — print the embedded skill markdown to stdout.
@@ -1849,6 +2995,37 @@ pub fn cli_skill_install(_user: bool, project: bool) -> i32 {
     0
 }
 
+/// `okena completions <shell>` — print the registration script. Candidates are
+/// produced at <Tab> time by re-running this binary (see [`crate::complete`]),
+/// so they always match the installed version and the live workspace.
+pub fn cli_completions(shell: CompletionShell) -> i32 {
+    use clap_complete::env::EnvCompleter;
+
+    let completer: &dyn EnvCompleter = match shell {
+        CompletionShell::Bash => &clap_complete::env::Bash,
+        CompletionShell::Zsh => &clap_complete::env::Zsh,
+        CompletionShell::Fish => &clap_complete::env::Fish,
+    };
+    let exe = std::env::current_exe()
+        .ok()
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "okena".to_string());
+    let mut stdout = std::io::stdout();
+    match completer.write_registration(
+        crate::complete::ENV_VAR,
+        "okena",
+        "okena",
+        &exe,
+        &mut stdout,
+    ) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Failed to write completion script: {e}");
+            1
+        }
+    }
+}
+
 // ── Version updates ─────────────────────────────────────────────────────────
 
 pub fn cli_update_status(json_mode: bool) -> i32 {
@@ -2394,8 +3571,19 @@ pub fn cli_key(terminal: &str, key: &str) -> i32 {
     })
 }
 
-/// `okena read <terminal> [--json]`
-pub fn cli_read(terminal: &str, json_mode: bool) -> i32 {
+/// `okena read <terminal> [--lines N | --all] [--ansi] [--since <seq>] [--strip-prompt] [--json]`
+///
+/// Default: the content on stdout; with `--since`, the sequence to pass next
+/// time on stderr.
+pub fn cli_read(
+    terminal: &str,
+    lines: Option<usize>,
+    all: bool,
+    ansi: bool,
+    since: Option<u64>,
+    strip_prompt: bool,
+    json_mode: bool,
+) -> i32 {
     let token = match ensure_token() {
         Ok(t) => t,
         Err(e) => {
@@ -2417,18 +3605,159 @@ pub fn cli_read(terminal: &str, json_mode: bool) -> i32 {
             return 1;
         }
     };
-    let body = serde_json::json!({ "action": "read_content", "terminal_id": terminal_id });
-    match api_action(&token, &body.to_string()) {
+    let action = okena_core::api::ActionRequest::ReadContent {
+        terminal_id,
+        lines,
+        all,
+        ansi,
+        since,
+        strip_prompt,
+    };
+    let body = match serde_json::to_string(&action) {
+        Ok(body) => body,
+        Err(e) => {
+            eprintln!("Failed to encode action: {e}");
+            return 1;
+        }
+    };
+    match api_action(&token, &body) {
         Ok(resp) => {
             if json_mode {
                 println!("{}", resp.trim());
-            } else {
-                let v: serde_json::Value =
-                    serde_json::from_str(&resp).unwrap_or(serde_json::Value::Null);
-                match v.get("content").and_then(|c| c.as_str()) {
-                    Some(content) => println!("{content}"),
-                    None => println!("{}", resp.trim()),
+                return 0;
+            }
+            match serde_json::from_str::<okena_core::api::ApiReadContent>(&resp) {
+                Ok(read) => {
+                    println!("{}", read.content);
+                    // The sequence goes to stderr so stdout stays pure content.
+                    if since.is_some() {
+                        eprintln!("sequence: {}", read.sequence);
+                    }
+                    if read.truncated {
+                        eprintln!("(earlier output is no longer available)");
+                    }
                 }
+                Err(_) => println!("{}", resp.trim()),
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
+    }
+}
+
+/// Exit status for `wait-for` timing out, matching coreutils `timeout(1)` so
+/// scripts can tell "no match" apart from a failed request.
+const WAIT_FOR_TIMEOUT_EXIT: i32 = 124;
+
+/// `okena wait-for <terminal> --regex <re> [--timeout <s>] [--since-now] [--json]`
+///
+/// Default: the match on the first line, then one line per capture group
+/// (empty for a group that did not participate).
+/// --json: `{matched, groups, named}`
+pub fn cli_wait_for(
+    terminal: &str,
+    regex: &str,
+    timeout_secs: u64,
+    since_now: bool,
+    json_mode: bool,
+) -> i32 {
+    let token = match ensure_token() {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    let terminal_id =
+        match fetch_state(&token).and_then(|state| resolve::resolve_terminal(&state, terminal)) {
+            Ok((_project_id, tid)) => tid,
+            Err(e) => {
+                eprintln!("{e}");
+                return 1;
+            }
+        };
+    let request = okena_core::api::ApiWaitForRequest {
+        regex: regex.to_string(),
+        timeout_ms: Some(timeout_secs.saturating_mul(1000)),
+        since_now,
+    };
+    let resp = discover_server()
+        .and_then(|server| server.client_and_url(&format!("/v1/terminals/{terminal_id}/wait-for")))
+        .and_then(|(client, url)| {
+            client
+                .post(&url)
+                .header("Authorization", format!("Bearer {token}"))
+                .json(&request)
+                // The server enforces the wait; leave headroom for the reply.
+                .timeout(std::time::Duration::from_secs(
+                    timeout_secs.saturating_add(10),
+                ))
+                .send()
+                .map_err(|e| format!("Request failed: {e}"))
+        });
+    let resp = match resp {
+        Ok(resp) => resp,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+
+    let status = resp.status();
+    let body = resp.text().unwrap_or_default();
+    if !status.is_success() {
+        let detail = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|v| v.get("error").and_then(|e| e.as_str()).map(str::to_string))
+            .unwrap_or_else(|| format!("Server returned {status}"));
+        if status == reqwest::StatusCode::REQUEST_TIMEOUT {
+            eprintln!("Timeout after {timeout_secs}s: {detail}");
+            return WAIT_FOR_TIMEOUT_EXIT;
+        }
+        eprintln!("{detail}");
+        return 1;
+    }
+    let found: okena_core::api::ApiOutputMatch = match serde_json::from_str(&body) {
+        Ok(found) => found,
+        Err(e) => {
+            eprintln!("Invalid wait-for response: {e}");
+            return 1;
+        }
+    };
+    if json_mode {
+        print_json_pretty(&body);
+    } else {
+        println!("{}", found.matched);
+        for group in &found.groups {
+            println!("{}", group.as_deref().unwrap_or(""));
+        }
+    }
+    0
+}
+
+/// `okena upload <file> [project] [--to <path>] [--rename|--overwrite] [--json]`
+///
+/// Default: the daemon-side path the file was written to.
+/// --json: `{path, name, size}`
+pub fn cli_upload(
+    file: &str,
+    project: Option<&str>,
+    to: Option<&str>,
+    conflict: UploadConflict,
+    json_mode: bool,
+) -> i32 {
+    match upload(file, project, to, conflict) {
+        Ok(written) => {
+            if json_mode {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&written).unwrap_or_default()
+                );
+            } else {
+                println!("{}", written.path);
             }
             0
         }
@@ -2439,27 +3768,235 @@ pub fn cli_read(terminal: &str, json_mode: bool) -> i32 {
     }
 }
 
+fn upload(
+    file: &str,
+    project: Option<&str>,
+    to: Option<&str>,
+    conflict: UploadConflict,
+) -> Result<FileUploadResponse, String> {
+    let source = std::fs::File::open(file).map_err(|e| format!("Cannot open {file}: {e}"))?;
+    let length = source
+        .metadata()
+        .map_err(|e| format!("Cannot read {file}: {e}"))?
+        .len();
+    let relative_path = upload_destination(std::path::Path::new(file), to)?;
+    let token = ensure_token()?;
+    let state = fetch_state(&token)?;
+    let project_id = match project {
+        Some(filter) => resolve::resolve_project(&state, filter)?.id.clone(),
+        None => resolve_project_id_in_state(&state, None)?,
+    };
+    let request = FileUploadRequest {
+        project_id: Some(project_id),
+        root: None,
+        relative_path,
+        conflict,
+    };
+    let config = discover_server()?.connection_config(
+        okena_transport::client::LOCAL_DAEMON_CONNECTION_ID,
+        "okena upload",
+        &token,
+    );
+    okena_transport::remote_action::RemoteActionClient::new(config, token)
+        .upload_file(&request, source, length)
+}
+
+/// Project-relative target for `okena upload`: the file's own name, `--to`
+/// as given, or the file's name inside a `--to` that ends in `/`.
+fn upload_destination(file: &std::path::Path, to: Option<&str>) -> Result<String, String> {
+    let name = file
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| format!("Not a file: {}", file.display()))?;
+    Ok(match to {
+        None | Some("") => name,
+        Some(dir) if dir.ends_with('/') => format!("{dir}{name}"),
+        Some(path) => path.to_string(),
+    })
+}
+
+/// `okena forward <service> [project] [--port <n>] [--local-port <n>]`
+///
+/// Prints the local URL once it listens, then relays until interrupted.
+pub fn cli_forward(
+    service: &str,
+    project: Option<&str>,
+    port: Option<u16>,
+    local_port: Option<u16>,
+) -> i32 {
+    let started = ensure_token().and_then(|token| {
+        let state = fetch_state(&token)?;
+        let project_id = resolve_project_id_in_state(&state, project)?;
+        let target = PortForwardQuery {
+            project_id,
+            service: service.to_string(),
+            port,
+        };
+        // Check here for a clearer error than the daemon's.
+        okena_core::forward::resolve_target(&state, &target)?;
+        let config = discover_server()?.connection_config(
+            okena_transport::client::LOCAL_DAEMON_CONNECTION_ID,
+            "okena forward",
+            &token,
+        );
+        Ok((config, target))
+    });
+    let (config, target) = match started {
+        Ok(started) => started,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+
+    let forwarded = okena_tui::forward::run_forward(config, target, local_port, |forward| {
+        eprintln!(
+            "Forwarding {} :{} to {} (Ctrl-C to stop)",
+            forward.target().service,
+            forward.remote_port(),
+            forward.local_addr()
+        );
+        println!("{}", forward.url());
+    });
+    match forwarded {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{e:#}");
+            1
+        }
+    }
+}
+
+pub fn cli_attach(terminal: &str, detach_key: okena_tui::attach::DetachKey) -> i32 {
+    use std::io::IsTerminal as _;
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        eprintln!("okena attach needs an interactive terminal.");
+        return 2;
+    }
+
+    let token = match ensure_token() {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    let terminal_id =
+        match fetch_state(&token).and_then(|state| resolve::resolve_terminal(&state, terminal)) {
+            Ok((_project_id, tid)) => tid,
+            Err(e) => {
+                eprintln!("{e}");
+                return 1;
+            }
+        };
+    let config = match discover_server() {
+        Ok(server) => server.connection_config(
+            okena_transport::client::LOCAL_DAEMON_CONNECTION_ID,
+            "okena attach",
+            &token,
+        ),
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+
+    match okena_tui::attach::attach(config, &terminal_id, detach_key) {
+        Ok(okena_tui::attach::AttachEnd::Detached) => {
+            eprintln!("[detached from {terminal_id}]");
+            0
+        }
+        Ok(okena_tui::attach::AttachEnd::TerminalClosed) => {
+            eprintln!("[terminal {terminal_id} closed]");
+            0
+        }
+        Err(e) => {
+            eprintln!("{e:#}");
+            1
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::parse_done_marker;
+    use super::{checkout_action, confirm_replacement, render_unified_diff, upload_destination};
+    use std::path::Path;
+
+    #[test]
+    fn unified_diff_round_trips_through_the_parser() {
+        let patch = "diff --git a/src/lib.rs b/src/lib.rs\n\
+                     --- a/src/lib.rs\n\
+                     +++ b/src/lib.rs\n\
+                     @@ -1,3 +1,3 @@ fn main()\n \
+                     keep\n\
+                     -old\n\
+                     +new\n\
+                     diff --git a/notes.md b/notes.md\n\
+                     --- /dev/null\n\
+                     +++ b/notes.md\n\
+                     @@ -0,0 +1 @@\n\
+                     +hello\n";
+        let parsed = okena_git::diff::parse_unified_diff(patch);
+        assert_eq!(render_unified_diff(&parsed), patch);
+    }
+
+    #[test]
+    fn checkout_prefers_local_then_tracks_remote() {
+        let list = okena_git::BranchList {
+            local: vec!["main".to_string()],
+            remote: vec!["origin/feature".to_string()],
+            ..Default::default()
+        };
+        let local = checkout_action(&list, "p", "main").unwrap();
+        assert_eq!(local["action"], "git_checkout_local_branch");
+        // A bare name resolves to its only remote counterpart.
+        let remote = checkout_action(&list, "p", "feature").unwrap();
+        assert_eq!(remote["action"], "git_checkout_remote_branch");
+        assert_eq!(remote["remote_branch"], "origin/feature");
+        assert!(checkout_action(&list, "p", "nope").is_err());
+    }
 
     #[test]
-    fn done_marker_matches_only_real_output() {
-        let tag = "OKENADONE_42";
-        // The echoed command line carries the literal `%s` — must NOT match.
-        let echo = "okena $ make ; printf '\\nOKENADONE_42:%s:END\\n' \"$?\"";
-        assert_eq!(parse_done_marker(echo, tag), None);
-        // The actual printf output carries digits — matches.
-        assert_eq!(parse_done_marker("OKENADONE_42:0:END", tag), Some(0));
+    fn upload_destination_keeps_the_name_for_directories() {
+        let file = Path::new("/tmp/crash.log");
+        assert_eq!(upload_destination(file, None).unwrap(), "crash.log");
+        assert_eq!(
+            upload_destination(file, Some("logs/")).unwrap(),
+            "logs/crash.log"
+        );
+        assert_eq!(
+            upload_destination(file, Some("logs/today.log")).unwrap(),
+            "logs/today.log"
+        );
+        assert!(upload_destination(Path::new("/"), None).is_err());
+    }
+
+    #[test]
+    fn workspace_replacement_without_a_terminal_needs_yes() {
+        let never_asked = || -> Option<String> { panic!("prompted without a terminal") };
+        assert_eq!(
+            confirm_replacement("Loading 'demo'", false, false, || 3, never_asked),
+            Some(2)
+        );
+        assert_eq!(
+            confirm_replacement("Loading 'demo'", true, false, || 3, never_asked),
+            None
+        );
+        let answer = |text: &str| {
+            let text = text.to_string();
+            move || Some(text)
+        };
+        assert_eq!(
+            confirm_replacement("Loading 'demo'", false, true, || 3, answer("y\n")),
+            None
+        );
+        assert_eq!(
+            confirm_replacement("Loading 'demo'", false, true, || 3, answer("\n")),
+            Some(1)
+        );
         assert_eq!(
-            parse_done_marker("noise\nOKENADONE_42:130:END\n$", tag),
-            Some(130)
+            confirm_replacement("Loading 'demo'", false, true, || 3, || None),
+            Some(1)
         );
-        // Echo line followed by the real result line: still resolves the result.
-        let both = format!("{echo}\nOKENADONE_42:7:END\nokena $ ");
-        assert_eq!(parse_done_marker(&both, tag), Some(7));
-        // Absent / wrong tag.
-        assert_eq!(parse_done_marker("nothing here", tag), None);
-        assert_eq!(parse_done_marker("OKENADONE_99:0:END", tag), None);
     }
 }
@@ -0,0 +1,154 @@
+//! Dynamic shell-completion candidates, fetched from the running Okena.
+//!
+//! The scripts printed by `okena completions` re-invoke the binary with
+//! [`ENV_VAR`] set on every <Tab>. The candidate functions here run in that
+//! process, so they only use the saved CLI token (never register), give up
+//! after a short timeout, and return nothing when no daemon answers — a shell
+//! prompt must never hang or print errors because Okena isn't running.
+
+use std::time::Duration;
+
+use clap_complete::CompletionCandidate;
+use okena_core::api::StateResponse;
+
+use crate::{discover_server, load_cli_config, resolve};
+
+/// Environment variable that switches the binary into completion mode. Not
+/// clap's default `COMPLETE`, which is too generic for a binary that also
+/// launches the GUI.
+pub const ENV_VAR: &str = "OKENA_COMPLETE";
+
+const TIMEOUT: Duration = Duration::from_millis(1500);
+
+fn get(path: &str) -> Option<String> {
+    let token = load_cli_config()?.token;
+    let (client, url) = discover_server().ok()?.client_and_url(path).ok()?;
+    client
+        .get(&url)
+        .header("Authorization", format!("Bearer {token}"))
+        .timeout(TIMEOUT)
+        .send()
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .text()
+        .ok()
+}
+
+fn action(body: serde_json::Value) -> Option<serde_json::Value> {
+    let token = load_cli_config()?.token;
+    let (client, url) = discover_server().ok()?.client_and_url("/v1/actions").ok()?;
+    client
+        .post(&url)
+        .header("Authorization", format!("Bearer {token}"))
+        .json(&body)
+        .timeout(TIMEOUT)
+        .send()
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .json()
+        .ok()
+}
+
+fn state() -> Option<StateResponse> {
+    serde_json::from_str(&get("/v1/state")?).ok()
+}
+
+/// Objects in `reply[key]`, as `(value, help)` from the given fields.
+fn listed(
+    reply: Option<serde_json::Value>,
+    key: &str,
+    value: &str,
+    help: &str,
+) -> Vec<CompletionCandidate> {
+    let Some(items) = reply.as_ref().and_then(|r| r.get(key)?.as_array().cloned()) else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            let value = item.get(value)?.as_str()?;
+            let help = item.get(help).and_then(|h| h.as_str()).unwrap_or_default();
+            Some(CompletionCandidate::new(value).help(Some(help.to_string().into())))
+        })
+        .collect()
+}
+
+pub fn projects() -> Vec<CompletionCandidate> {
+    let Some(state) = state() else {
+        return Vec::new();
+    };
+    state
+        .projects
+        .iter()
+        .map(|p| CompletionCandidate::new(&p.name).help(Some(p.path.clone().into())))
+        .collect()
+}
+
+pub fn terminals() -> Vec<CompletionCandidate> {
+    let Some(state) = state() else {
+        return Vec::new();
+    };
+    resolve::terminal_addresses(&state)
+        .into_iter()
+        .map(|(address, name)| CompletionCandidate::new(address).help(Some(name.into())))
+        .collect()
+}
+
+pub fn services() -> Vec<CompletionCandidate> {
+    let Some(state) = state() else {
+        return Vec::new();
+    };
+    let mut seen = std::collections::HashSet::new();
+    state
+        .projects
+        .iter()
+        .flat_map(|p| p.services.iter().map(move |s| (p, s)))
+        .filter(|(_, s)| seen.insert(s.name.clone()))
+        .map(|(p, s)| {
+            CompletionCandidate::new(&s.name)
+                .help(Some(format!("{} ({})", p.name, s.status).into()))
+        })
+        .collect()
+}
+
+pub fn sessions() -> Vec<CompletionCandidate> {
+    let reply = action(serde_json::json!({ "action": "list_sessions" }));
+    let Some(sessions) = reply.and_then(|r| {
+        serde_json::from_value::<Vec<okena_workspace::sessions::SessionInfo>>(r).ok()
+    }) else {
+        return Vec::new();
+    };
+    sessions
+        .into_iter()
+        .map(|s| {
+            let help = format!("{} project(s)", s.project_count);
+            CompletionCandidate::new(s.name).help(Some(help.into()))
+        })
+        .collect()
+}
+
+pub fn schema_types() -> Vec<CompletionCandidate> {
+    okena_core::schema::type_names()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+pub fn themes() -> Vec<CompletionCandidate> {
+    listed(
+        action(serde_json::json!({ "action": "get_themes" })),
+        "themes",
+        "id",
+        "name",
+    )
+}
+
+pub fn palette_commands() -> Vec<CompletionCandidate> {
+    listed(
+        action(serde_json::json!({ "action": "list_actions" })),
+        "actions",
+        "name",
+        "description",
+    )
+}
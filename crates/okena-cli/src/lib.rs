@@ -1,15 +1,18 @@
 pub mod commands;
+pub mod complete;
+pub mod mcp;
 pub mod parser;
 pub mod register;
 pub mod resolve;
 
-use clap::Parser as _;
+use clap::{CommandFactory as _, Parser as _};
+use okena_core::api::UploadConflict;
 use okena_core::process::is_process_alive;
 use okena_transport::client::{LocalEndpoint, RemoteConnectionConfig};
 use okena_workspace::persistence::config_dir;
 use parser::{
-    Cli, Command, FolderCmd, PaletteCmd, ProjectCmd, ServiceCmd, SettingsCmd, SkillCmd, TermCmd,
-    ThemeCmd, UpdateCmd, WorktreeCmd,
+    Cli, Command, FolderCmd, GitCmd, HookCmd, PaletteCmd, ProjectCmd, ServiceCmd, SessionCmd,
+    SettingsCmd, SkillCmd, TermCmd, ThemeCmd, UpdateCmd, WorkspaceCmd, WorktreeCmd,
 };
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -30,6 +33,12 @@ pub struct CliConfig {
 /// `--list-profiles`, `--new-profile`, or any other GUI flag — returns `None`
 /// so GUI launch and profile handling in `main.rs` stay untouched.
 pub fn try_handle_cli() -> Option<i32> {
+    // A completion request from the script `okena completions` installed:
+    // prints the candidates and exits, before any GUI gating.
+    clap_complete::CompleteEnv::with_factory(Cli::command)
+        .var(complete::ENV_VAR)
+        .complete();
+
     let args: Vec<String> = std::env::args().collect();
     let first = args.get(1)?.as_str();
 
@@ -87,10 +96,21 @@ fn dispatch(cli: Cli) -> i32 {
         );
     }
     match cli.command {
-        Command::Pair => commands::cli_pair(),
+        Command::Pair {
+            scopes,
+            projects,
+            folders,
+        } => commands::cli_pair(&scopes, &projects, &folders),
         Command::Health { json } => commands::cli_health(json),
         Command::State => commands::cli_state(),
-        Command::Action { json } => commands::cli_action(&json),
+        Command::Action {
+            json,
+            batch,
+            rollback,
+        } => match batch {
+            Some(file) => commands::cli_action_batch(&file, rollback),
+            None => commands::cli_action(json.as_deref().unwrap_or_default()),
+        },
         Command::Services { project, json } => commands::cli_services(project.as_deref(), json),
         Command::Service { cmd } => match cmd {
             ServiceCmd::Start {
@@ -108,6 +128,21 @@ fn dispatch(cli: Cli) -> i32 {
                 project,
                 json,
             } => commands::cli_service("restart", &name, project.as_deref(), json),
+            ServiceCmd::Rebuild {
+                name,
+                project,
+                json,
+            } => commands::cli_service("rebuild", &name, project.as_deref(), json),
+            ServiceCmd::Pull {
+                name,
+                project,
+                json,
+            } => commands::cli_service("pull", &name, project.as_deref(), json),
+            ServiceCmd::Exec {
+                name,
+                project,
+                shell,
+            } => commands::cli_service_exec(&name, project.as_deref(), shell.as_deref()),
         },
         Command::Whoami { json } => commands::cli_whoami(json),
         Command::Ls { json } => commands::cli_ls(json),
@@ -153,6 +188,56 @@ fn dispatch(cli: Cli) -> i32 {
             } => commands::cli_worktree_add(&project, &branch, new_branch),
             WorktreeCmd::Rm { worktree, force } => commands::cli_worktree_rm(&worktree, force),
         },
+        Command::Git { cmd } => match cmd {
+            GitCmd::Status { project, json } => commands::cli_git_status(&project, json),
+            GitCmd::Diff {
+                project,
+                staged,
+                commit,
+                base,
+                head,
+                ignore_whitespace,
+                stat,
+                json,
+            } => {
+                let mode = match (commit, base, head) {
+                    (Some(commit), _, _) => okena_core::types::DiffMode::Commit(commit),
+                    (None, Some(base), Some(head)) => {
+                        okena_core::types::DiffMode::BranchCompare { base, head }
+                    }
+                    _ if staged => okena_core::types::DiffMode::Staged,
+                    _ => okena_core::types::DiffMode::WorkingTree,
+                };
+                commands::cli_git_diff(&project, mode, ignore_whitespace, stat, json)
+            }
+            GitCmd::Branches { project, json } => commands::cli_git_branches(&project, json),
+            GitCmd::Checkout {
+                project,
+                branch,
+                create,
+                start_point,
+            } => commands::cli_git_checkout(&project, &branch, create, start_point.as_deref()),
+            GitCmd::Stage { project, files } => {
+                commands::cli_git_files(&project, &files, "git_stage_file")
+            }
+            GitCmd::Unstage { project, files } => {
+                commands::cli_git_files(&project, &files, "git_unstage_file")
+            }
+            GitCmd::Discard { project, files } => {
+                commands::cli_git_files(&project, &files, "git_discard_file")
+            }
+            GitCmd::Log {
+                project,
+                count,
+                branch,
+                json,
+            } => commands::cli_git_log(&project, count, branch.as_deref(), json),
+            GitCmd::Blame {
+                project,
+                file,
+                json,
+            } => commands::cli_git_blame(&project, &file, json),
+        },
 
         Command::Folder { cmd } => match cmd {
             FolderCmd::Add { name } => commands::cli_folder_add(&name),
@@ -162,7 +247,7 @@ fn dispatch(cli: Cli) -> i32 {
 
         Command::Term { cmd } => match cmd {
             TermCmd::Ls { project, json } => commands::cli_term_ls(project.as_deref(), json),
-            TermCmd::New { project } => commands::cli_term_new(&project),
+            TermCmd::New(args) => commands::cli_term_new(&args),
             TermCmd::Close { terminal } => commands::cli_term_close(&terminal),
             TermCmd::Focus { terminal } => commands::cli_term_focus(&terminal, window),
             TermCmd::Rename { terminal, name } => commands::cli_term_rename(&terminal, &name),
@@ -177,6 +262,24 @@ fn dispatch(cli: Cli) -> i32 {
             }
         },
 
+        Command::Session { cmd } => match cmd {
+            SessionCmd::Ls { json } => commands::cli_session_ls(json),
+            SessionCmd::Save { name, json } => commands::cli_session_save(&name, json),
+            SessionCmd::Load { name, yes, json } => commands::cli_session_load(&name, yes, json),
+            SessionCmd::Rm { name, json } => commands::cli_session_rm(&name, json),
+            SessionCmd::Rename {
+                name,
+                new_name,
+                json,
+            } => commands::cli_session_rename(&name, &new_name, json),
+        },
+        Command::Workspace { cmd } => match cmd {
+            WorkspaceCmd::Export { file, json } => commands::cli_workspace_export(&file, json),
+            WorkspaceCmd::Import { file, yes, json } => {
+                commands::cli_workspace_import(&file, yes, json)
+            }
+        },
+
         Command::Send { terminal, text } => commands::cli_send(&terminal, &text),
         Command::Run {
             wait,
@@ -185,13 +288,57 @@ fn dispatch(cli: Cli) -> i32 {
             command,
         } => commands::cli_run(&terminal, &command, wait, timeout),
         Command::Key { terminal, key } => commands::cli_key(&terminal, &key),
-        Command::Read { terminal, json } => commands::cli_read(&terminal, json),
+        Command::Read {
+            terminal,
+            lines,
+            all,
+            ansi,
+            since,
+            strip_prompt,
+            json,
+        } => commands::cli_read(&terminal, lines, all, ansi, since, strip_prompt, json),
+        Command::WaitFor {
+            terminal,
+            regex,
+            timeout,
+            since_now,
+            json,
+        } => commands::cli_wait_for(&terminal, &regex, timeout, since_now, json),
+        Command::Attach {
+            terminal,
+            detach_key,
+        } => commands::cli_attach(&terminal, detach_key),
+        Command::Upload {
+            file,
+            project,
+            to,
+            rename,
+            overwrite,
+            json,
+        } => {
+            let conflict = if overwrite {
+                UploadConflict::Overwrite
+            } else if rename {
+                UploadConflict::Rename
+            } else {
+                UploadConflict::Fail
+            };
+            commands::cli_upload(&file, project.as_deref(), to.as_deref(), conflict, json)
+        }
+        Command::Forward {
+            service,
+            project,
+            port,
+            local_port,
+        } => commands::cli_forward(&service, project.as_deref(), port, local_port),
 
         Command::Skill { cmd } => match cmd {
             SkillCmd::Show => commands::cli_skill_show(),
             SkillCmd::Install { user, project } => commands::cli_skill_install(user, project),
         },
 
+        Command::Completions { shell } => commands::cli_completions(shell),
+
         Command::Settings { cmd } => match cmd {
             SettingsCmd::Show { key } => commands::cli_settings_show(key.as_deref()),
             SettingsCmd::Schema => commands::cli_settings_schema(),
@@ -211,6 +358,31 @@ fn dispatch(cli: Cli) -> i32 {
             PaletteCmd::List { json } => commands::cli_command_list(json),
             PaletteCmd::Run { name } => commands::cli_command_run(&name, window),
         },
+        Command::Hook { cmd } => match cmd {
+            HookCmd::Ls { json } => commands::cli_hook_ls(json),
+            HookCmd::Cancel { id } => commands::cli_hook_cancel(id),
+            HookCmd::Logs {
+                hook_type,
+                limit,
+                json,
+            } => commands::cli_hook_logs(hook_type.as_deref(), limit, json),
+            HookCmd::Log { id, json } => commands::cli_hook_log(id, json),
+            HookCmd::Diff { base, run, json } => commands::cli_hook_diff(base, run, json),
+        },
+        Command::Events {
+            types,
+            projects,
+            count,
+        } => commands::cli_events(&types, &projects, count),
+        Command::Audit {
+            device,
+            kind,
+            since,
+            limit,
+            json,
+        } => commands::cli_audit(device, kind, since, limit, json),
+        Command::Schema { type_name } => commands::cli_schema(type_name.as_deref()),
+        Command::Mcp => mcp::cli_mcp(),
         Command::Update { cmd } => match cmd {
             UpdateCmd::Status { json } => commands::cli_update_status(json),
             UpdateCmd::List { json, quiet } => commands::cli_update_list(json, quiet),
@@ -290,6 +462,22 @@ impl DiscoveredServer {
             format!("{scheme}://{}:{}{path}", self.host, self.port),
         ))
     }
+
+    /// Connection settings for a `RemoteClient` that authenticates with `token`.
+    pub fn connection_config(self, id: &str, name: &str, token: &str) -> RemoteConnectionConfig {
+        RemoteConnectionConfig {
+            id: id.to_string(),
+            name: name.to_string(),
+            host: self.host,
+            port: self.port,
+            saved_token: Some(token.to_string()),
+            token_obtained_at: None,
+            tls: self.tls,
+            pinned_cert_sha256: None,
+            local_endpoint: self.local_endpoint,
+            proxy_command: None,
+        }
+    }
 }
 
 /// Discover a running Okena instance by reading `remote.json`.
@@ -333,8 +521,15 @@ fn discover_server() -> Result<DiscoveredServer, String> {
 }
 
 /// Ensure we have a valid token, auto-registering if needed.
-/// Returns the bearer token string.
+/// Returns the bearer token string. `$OKENA_TOKEN` wins when set, so an agent
+/// can run under a restricted token minted for it; the server validates it.
 fn ensure_token() -> Result<String, String> {
+    if let Ok(token) = std::env::var("OKENA_TOKEN")
+        && !token.is_empty()
+    {
+        return Ok(token);
+    }
+
     // Try existing token
     if let Some(config) = load_cli_config() {
         // Quick validation: try an authenticated request
@@ -379,17 +574,11 @@ fn api_action(token: &str, body: &str) -> Result<String, String> {
     let server = discover_server()?;
     let action: okena_core::api::ActionRequest =
         serde_json::from_str(body).map_err(|e| format!("Invalid action: {e}"))?;
-    let config = RemoteConnectionConfig {
-        id: okena_transport::client::LOCAL_DAEMON_CONNECTION_ID.to_string(),
-        name: "Local daemon".to_string(),
-        host: server.host,
-        port: server.port,
-        saved_token: Some(token.to_string()),
-        token_obtained_at: None,
-        tls: server.tls,
-        pinned_cert_sha256: None,
-        local_endpoint: server.local_endpoint,
-    };
+    let config = server.connection_config(
+        okena_transport::client::LOCAL_DAEMON_CONNECTION_ID,
+        "Local daemon",
+        token,
+    );
     match okena_transport::remote_action::RemoteActionClient::new(config, token.to_string())
         .post_action(action)?
     {
@@ -0,0 +1,1020 @@
+//! `okena mcp`: a Model Context Protocol server on stdin/stdout.
+//!
+//! Speaks newline-delimited JSON-RPC 2.0. Every tool maps onto one or more
+//! [`ActionRequest`]s sent to the running daemon with the CLI's token, so the
+//! daemon enforces scopes and project limits exactly as it does for the CLI.
+//! Tools the token's grant cannot use are left out of `tools/list`.
+//!
+//! Resources expose terminal buffers (`okena://terminal/<id>`) and working
+//! tree diffs (`okena://diff/<project_id>`).
+//!
+//! Nothing but protocol messages may reach stdout; diagnostics go to stderr.
+
+use crate::commands::{map_special_key, render_unified_diff};
+use crate::resolve;
+use okena_core::api::{ActionRequest, ApiExecResult, ApiGitStatus, ApiReadContent, StateResponse};
+use okena_core::scope::{Scope, TokenGrant};
+use okena_core::types::DiffMode;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use std::io::{BufRead, Write};
+
+/// Protocol revisions this server speaks, newest first.
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Lines of scrollback a terminal resource returns.
+const RESOURCE_LINES: usize = 1000;
+
+const TERMINAL_URI: &str = "okena://terminal/";
+const DIFF_URI: &str = "okena://diff/";
+
+// JSON-RPC and MCP error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const RESOURCE_NOT_FOUND: i64 = -32002;
+
+/// What the server needs from the daemon. The CLI's connection implements
+/// it; tests use a canned one.
+pub(crate) trait Daemon {
+    fn state(&self) -> Result<StateResponse, String>;
+    fn action(&self, action: ActionRequest) -> Result<Option<Value>, String>;
+}
+
+/// The running daemon, reached the way every other CLI command reaches it.
+struct LocalDaemon {
+    token: String,
+    client: okena_transport::remote_action::RemoteActionClient,
+}
+
+impl LocalDaemon {
+    fn connect() -> Result<Self, String> {
+        let token = crate::ensure_token()?;
+        let config = crate::discover_server()?.connection_config(
+            okena_transport::client::LOCAL_DAEMON_CONNECTION_ID,
+            "Local daemon",
+            &token,
+        );
+        let client = okena_transport::remote_action::RemoteActionClient::new(config, token.clone());
+        Ok(Self { token, client })
+    }
+
+    /// The token's grant. Daemons that predate `/v1/tokens/self` get the
+    /// full tool list; they still refuse what the token may not do.
+    fn grant(&self) -> TokenGrant {
+        #[derive(Deserialize)]
+        struct OwnGrant {
+            grant: TokenGrant,
+        }
+        crate::api_get("/v1/tokens/self", &self.token)
+            .ok()
+            .and_then(|body| serde_json::from_str::<OwnGrant>(&body).ok())
+            .map(|own| own.grant)
+            .unwrap_or_default()
+    }
+}
+
+impl Daemon for LocalDaemon {
+    fn state(&self) -> Result<StateResponse, String> {
+        crate::commands::fetch_state(&self.token)
+    }
+
+    fn action(&self, action: ActionRequest) -> Result<Option<Value>, String> {
+        self.client.post_action(action)
+    }
+}
+
+/// `okena mcp`
+pub fn cli_mcp() -> i32 {
+    let daemon = match LocalDaemon::connect() {
+        Ok(daemon) => daemon,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    let grant = daemon.grant();
+    if !grant.is_full() {
+        eprintln!("okena mcp: restricted token ({})", grant.summary());
+    }
+    let server = Server { daemon, grant };
+
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(reply) = server.handle_line(&line)
+            && (writeln!(stdout, "{reply}").is_err() || stdout.flush().is_err())
+        {
+            break;
+        }
+    }
+    0
+}
+
+pub(crate) struct Server<D> {
+    daemon: D,
+    grant: TokenGrant,
+}
+
+/// A JSON-RPC error.
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl<D: Daemon> Server<D> {
+    /// Answer one line of input. Notifications get no reply.
+    pub(crate) fn handle_line(&self, line: &str) -> Option<Value> {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(e) => {
+                return Some(error_reply(
+                    Value::Null,
+                    RpcError::new(PARSE_ERROR, e.to_string()),
+                ));
+            }
+        };
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // A response to a request we never send, or garbage.
+            return message.get("id").map(|id| {
+                error_reply(
+                    id.clone(),
+                    RpcError::new(INVALID_REQUEST, "expected a request"),
+                )
+            });
+        };
+        let id = message.get("id").cloned()?;
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        Some(match self.handle(method, params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => error_reply(id, error),
+        })
+    }
+
+    fn handle(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(initialize(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": self.tools() })),
+            "tools/call" => {
+                let call: ToolCall = parse_params(params)?;
+                Ok(self.call_tool(&call.name, call.arguments))
+            }
+            "resources/list" => self.resources(),
+            "resources/templates/list" => Ok(json!({ "resourceTemplates": self.templates() })),
+            "resources/read" => {
+                let read: ResourceRead = parse_params(params)?;
+                self.read_resource(&read.uri)
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method '{method}'"),
+            )),
+        }
+    }
+
+    fn tools(&self) -> Vec<Value> {
+        TOOLS
+            .iter()
+            .filter(|tool| self.grant.allows(tool.scope))
+            .map(|tool| {
+                json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "inputSchema": (tool.input_schema)(),
+                })
+            })
+            .collect()
+    }
+
+    /// Run a tool. Failures are tool results with `isError`, so the model
+    /// sees them, rather than protocol errors.
+    fn call_tool(&self, name: &str, arguments: Value) -> Value {
+        let result = match TOOLS.iter().find(|tool| tool.name == name) {
+            None => Err(format!("unknown tool '{name}'")),
+            Some(tool) if !self.grant.allows(tool.scope) => {
+                Err(format!("token lacks the '{}' scope", tool.scope))
+            }
+            Some(_) => self.run_tool(name, arguments),
+        };
+        let (text, is_error) = match result {
+            Ok(text) => (text, false),
+            Err(text) => (text, true),
+        };
+        json!({
+            "content": [{ "type": "text", "text": text }],
+            "isError": is_error,
+        })
+    }
+
+    fn run_tool(&self, name: &str, arguments: Value) -> Result<String, String> {
+        match name {
+            "list_projects" => {
+                let ListProjectsArgs {} = args(arguments)?;
+                let state = self.daemon.state()?;
+                pretty(&project_summaries(&state))
+            }
+            "read_terminal" => {
+                let a: ReadTerminalArgs = args(arguments)?;
+                let terminal_id = self.terminal_id(&a.terminal)?;
+                let read: ApiReadContent = self.query(ActionRequest::ReadContent {
+                    terminal_id,
+                    lines: a.lines,
+                    all: a.all,
+                    ansi: false,
+                    since: a.since,
+                    strip_prompt: false,
+                })?;
+                let mut text = read.content;
+                if read.truncated {
+                    text.insert_str(0, "[earlier output was dropped]\n");
+                }
+                text.push_str(&format!(
+                    "\n[sequence {}: pass as `since` to read only newer output]",
+                    read.sequence
+                ));
+                Ok(text)
+            }
+            "send_text" => {
+                let a: SendTextArgs = args(arguments)?;
+                let terminal_id = self.terminal_id(&a.terminal)?;
+                let action = if a.submit {
+                    ActionRequest::RunCommand {
+                        terminal_id,
+                        command: a.text,
+                    }
+                } else {
+                    ActionRequest::SendText {
+                        terminal_id,
+                        text: a.text,
+                    }
+                };
+                self.daemon.action(action)?;
+                Ok("Sent.".to_string())
+            }
+            "send_keys" => {
+                let a: SendKeysArgs = args(arguments)?;
+                let terminal_id = self.terminal_id(&a.terminal)?;
+                let keys = a
+                    .keys
+                    .iter()
+                    .map(|key| {
+                        map_special_key(key)
+                            .and_then(|key| serde_json::from_value(key).map_err(|e| e.to_string()))
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+                for key in keys {
+                    self.daemon.action(ActionRequest::SendSpecialKey {
+                        terminal_id: terminal_id.clone(),
+                        key,
+                    })?;
+                }
+                Ok("Sent.".to_string())
+            }
+            "run_command" => {
+                let a: RunCommandArgs = args(arguments)?;
+                let terminal_id = self.terminal_id(&a.terminal)?;
+                let result: ApiExecResult = self.query(ActionRequest::ExecInTerminal {
+                    terminal_id,
+                    command: a.command,
+                    mirror: true,
+                    timeout_ms: a.timeout_secs.map(|secs| secs.saturating_mul(1000)),
+                })?;
+                Ok(exec_report(&result))
+            }
+            "git_status" => {
+                let a: ProjectArgs = args(arguments)?;
+                let project_id = self.project_id(&a.project)?;
+                let status: Option<ApiGitStatus> =
+                    self.query(ActionRequest::GitStatus { project_id })?;
+                match status {
+                    Some(status) => pretty(&status),
+                    None => Err(format!(
+                        "No git status for {} (not a repository?)",
+                        a.project
+                    )),
+                }
+            }
+            "git_diff" => {
+                let a: GitDiffArgs = args(arguments)?;
+                let project_id = self.project_id(&a.project)?;
+                let mode = match (a.commit, a.base, a.head) {
+                    (Some(commit), _, _) => DiffMode::Commit(commit),
+                    (None, Some(base), Some(head)) => DiffMode::BranchCompare { base, head },
+                    (None, None, None) if a.staged => DiffMode::Staged,
+                    (None, None, None) => DiffMode::WorkingTree,
+                    _ => return Err("`base` and `head` go together".to_string()),
+                };
+                self.diff(project_id, mode, a.stat)
+            }
+            "start_service" | "stop_service" | "restart_service" => {
+                let a: ServiceArgs = args(arguments)?;
+                let project_id = self.project_id(&a.project)?;
+                let service_name = a.service;
+                let action = match name {
+                    "start_service" => ActionRequest::StartService {
+                        project_id,
+                        service_name,
+                    },
+                    "stop_service" => ActionRequest::StopService {
+                        project_id,
+                        service_name,
+                    },
+                    _ => ActionRequest::RestartService {
+                        project_id,
+                        service_name,
+                    },
+                };
+                self.daemon.action(action)?;
+                Ok("Requested; `list_projects` shows the service's status.".to_string())
+            }
+            "create_worktree" => {
+                let a: CreateWorktreeArgs = args(arguments)?;
+                let project_id = self.project_id(&a.project)?;
+                let reply = self.daemon.action(ActionRequest::CreateWorktree {
+                    project_id,
+                    branch: a.branch,
+                    create_branch: a.create_branch,
+                })?;
+                pretty(&reply.unwrap_or_else(|| json!({ "ok": true })))
+            }
+            _ => Err(format!("unknown tool '{name}'")),
+        }
+    }
+
+    fn resources(&self) -> Result<Value, RpcError> {
+        let state = self
+            .daemon
+            .state()
+            .map_err(|e| RpcError::new(INVALID_REQUEST, e))?;
+        let mut resources = Vec::new();
+        for project in &state.projects {
+            for entry in resolve::project_terminals(project) {
+                resources.push(json!({
+                    "uri": format!("{TERMINAL_URI}{}", entry.terminal_id),
+                    "name": format!("{}/{}", project.name, entry.name),
+                    "description": format!("Last {RESOURCE_LINES} lines of the terminal"),
+                    "mimeType": "text/plain",
+                }));
+            }
+            if self.grant.allows(Scope::Git) && project.git_status.is_some() {
+                resources.push(json!({
+                    "uri": format!("{DIFF_URI}{}", project.id),
+                    "name": format!("{} diff", project.name),
+                    "description": "Uncommitted changes in the working tree",
+                    "mimeType": "text/x-diff",
+                }));
+            }
+        }
+        Ok(json!({ "resources": resources }))
+    }
+
+    fn templates(&self) -> Vec<Value> {
+        let mut templates = vec![json!({
+            "uriTemplate": format!("{TERMINAL_URI}{{terminal_id}}"),
+            "name": "Terminal buffer",
+            "mimeType": "text/plain",
+        })];
+        if self.grant.allows(Scope::Git) {
+            templates.push(json!({
+                "uriTemplate": format!("{DIFF_URI}{{project_id}}"),
+                "name": "Working tree diff",
+                "mimeType": "text/x-diff",
+            }));
+        }
+        templates
+    }
+
+    fn read_resource(&self, uri: &str) -> Result<Value, RpcError> {
+        let (text, mime_type) = if let Some(terminal_id) = uri.strip_prefix(TERMINAL_URI) {
+            let read: ApiReadContent = self
+                .query(ActionRequest::ReadContent {
+                    terminal_id: terminal_id.to_string(),
+                    lines: Some(RESOURCE_LINES),
+                    all: false,
+                    ansi: false,
+                    since: None,
+                    strip_prompt: false,
+                })
+                .map_err(|e| RpcError::new(RESOURCE_NOT_FOUND, e))?;
+            (read.content, "text/plain")
+        } else if let Some(project_id) = uri.strip_prefix(DIFF_URI) {
+            let diff = self
+                .diff(project_id.to_string(), DiffMode::WorkingTree, false)
+                .map_err(|e| RpcError::new(RESOURCE_NOT_FOUND, e))?;
+            (diff, "text/x-diff")
+        } else {
+            return Err(RpcError::new(
+                RESOURCE_NOT_FOUND,
+                format!("unknown resource '{uri}'"),
+            ));
+        };
+        Ok(json!({
+            "contents": [{ "uri": uri, "mimeType": mime_type, "text": text }],
+        }))
+    }
+
+    fn diff(&self, project_id: String, mode: DiffMode, stat: bool) -> Result<String, String> {
+        let diff: okena_git::DiffResult = self.query(ActionRequest::GitDiff {
+            project_id,
+            mode,
+            ignore_whitespace: false,
+        })?;
+        if diff.files.is_empty() {
+            return Ok("No changes.".to_string());
+        }
+        if !stat {
+            return Ok(render_unified_diff(&diff));
+        }
+        Ok(diff
+            .files
+            .iter()
+            .map(|file| {
+                format!(
+                    "{}\t+{}\t-{}\n",
+                    file.display_name(),
+                    file.lines_added,
+                    file.lines_removed
+                )
+            })
+            .collect())
+    }
+
+    /// Post `action` and decode its reply.
+    fn query<T: DeserializeOwned>(&self, action: ActionRequest) -> Result<T, String> {
+        let reply = self.daemon.action(action)?.unwrap_or(Value::Null);
+        serde_json::from_value(reply).map_err(|e| format!("Invalid response: {e}"))
+    }
+
+    fn project_id(&self, filter: &str) -> Result<String, String> {
+        let state = self.daemon.state()?;
+        resolve::resolve_project(&state, filter).map(|project| project.id.clone())
+    }
+
+    fn terminal_id(&self, filter: &str) -> Result<String, String> {
+        let state = self.daemon.state()?;
+        resolve::resolve_terminal(&state, filter).map(|(_, terminal_id)| terminal_id)
+    }
+}
+
+fn initialize(params: &Value) -> Value {
+    let requested = params.get("protocolVersion").and_then(Value::as_str);
+    let version = requested
+        .and_then(|requested| PROTOCOL_VERSIONS.iter().find(|v| **v == requested))
+        .unwrap_or(&PROTOCOL_VERSIONS[0]);
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": {}, "resources": {} },
+        "serverInfo": { "name": "okena", "version": env!("CARGO_PKG_VERSION") },
+        "instructions": "Okena runs the user's terminals, grouped into projects. \
+            Call list_projects first; terminals are addressed by id, \
+            `<project>/<name>` or `<project>:<index>`.",
+    })
+}
+
+fn error_reply(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn args<T: DeserializeOwned>(arguments: Value) -> Result<T, String> {
+    let arguments = if arguments.is_null() {
+        json!({})
+    } else {
+        arguments
+    };
+    serde_json::from_value(arguments).map_err(|e| format!("Invalid arguments: {e}"))
+}
+
+fn pretty<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| e.to_string())
+}
+
+/// Projects with their terminals (and the addresses tools accept) and
+/// services.
+fn project_summaries(state: &StateResponse) -> Value {
+    state
+        .projects
+        .iter()
+        .map(|project| {
+            let terminals: Vec<Value> = resolve::project_terminals(project)
+                .into_iter()
+                .enumerate()
+                .map(|(index, entry)| {
+                    json!({
+                        "id": entry.terminal_id,
+                        "name": entry.name,
+                        "address": format!("{}:{index}", project.name),
+                    })
+                })
+                .collect();
+            let services: Vec<Value> = project
+                .services
+                .iter()
+                .map(|service| {
+                    json!({
+                        "name": service.name,
+                        "status": service.status,
+                        "ports": service.ports,
+                    })
+                })
+                .collect();
+            json!({
+                "id": project.id,
+                "name": project.name,
+                "path": project.path,
+                "branch": project.git_status.as_ref().and_then(|git| git.branch.clone()),
+                "terminals": terminals,
+                "services": services,
+            })
+        })
+        .collect()
+}
+
+fn exec_report(result: &ApiExecResult) -> String {
+    let mut text = result.stdout.clone();
+    if !result.stderr.is_empty() {
+        text.push_str("\n[stderr]\n");
+        text.push_str(&result.stderr);
+    }
+    if result.truncated {
+        text.insert_str(0, "[earlier output was dropped]\n");
+    }
+    match result.exit_code {
+        Some(code) => text.push_str(&format!("\n[exit code {code}]")),
+        None => text.push_str("\n[no exit code]"),
+    }
+    text
+}
+
+#[derive(Deserialize)]
+struct ToolCall {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+#[derive(Deserialize)]
+struct ResourceRead {
+    uri: String,
+}
+
+/// One MCP tool and the scope its actions need.
+struct Tool {
+    name: &'static str,
+    scope: Scope,
+    description: &'static str,
+    input_schema: fn() -> Value,
+}
+
+fn input_schema<T: JsonSchema>() -> Value {
+    schemars::SchemaGenerator::default()
+        .into_root_schema_for::<T>()
+        .to_value()
+}
+
+const TOOLS: &[Tool] = &[
+    Tool {
+        name: "list_projects",
+        scope: Scope::Read,
+        description: "List projects with their terminals, services and git branch.",
+        input_schema: input_schema::<ListProjectsArgs>,
+    },
+    Tool {
+        name: "read_terminal",
+        scope: Scope::Read,
+        description: "Read a terminal's screen, or its last lines of scrollback.",
+        input_schema: input_schema::<ReadTerminalArgs>,
+    },
+    Tool {
+        name: "send_text",
+        scope: Scope::TerminalInput,
+        description: "Type text into a terminal, optionally pressing Enter after it.",
+        input_schema: input_schema::<SendTextArgs>,
+    },
+    Tool {
+        name: "send_keys",
+        scope: Scope::TerminalInput,
+        description: "Press keys in a terminal: enter, esc, tab, arrows, ctrl-<letter>, ...",
+        input_schema: input_schema::<SendKeysArgs>,
+    },
+    Tool {
+        name: "run_command",
+        scope: Scope::TerminalInput,
+        description: "Run a command in a terminal and wait for its output and exit code.",
+        input_schema: input_schema::<RunCommandArgs>,
+    },
+    Tool {
+        name: "git_status",
+        scope: Scope::Git,
+        description: "Branch, changes, ahead/behind, pull request and CI for a project.",
+        input_schema: input_schema::<ProjectArgs>,
+    },
+    Tool {
+        name: "git_diff",
+        scope: Scope::Git,
+        description: "Unified diff of a project's working tree, index, a commit or two branches.",
+        input_schema: input_schema::<GitDiffArgs>,
+    },
+    Tool {
+        name: "start_service",
+        scope: Scope::Services,
+        description: "Start a project service.",
+        input_schema: input_schema::<ServiceArgs>,
+    },
+    Tool {
+        name: "stop_service",
+        scope: Scope::Services,
+        description: "Stop a project service.",
+        input_schema: input_schema::<ServiceArgs>,
+    },
+    Tool {
+        name: "restart_service",
+        scope: Scope::Services,
+        description: "Restart a project service.",
+        input_schema: input_schema::<ServiceArgs>,
+    },
+    Tool {
+        name: "create_worktree",
+        scope: Scope::Git,
+        description: "Check out a branch in a new git worktree, added as its own project.",
+        input_schema: input_schema::<CreateWorktreeArgs>,
+    },
+];
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct ListProjectsArgs {}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct ReadTerminalArgs {
+    /// Terminal id, `<project>/<name>` or `<project>:<index>`.
+    terminal: String,
+    /// The last N lines, reaching back into scrollback.
+    #[serde(default)]
+    lines: Option<usize>,
+    /// All of scrollback plus the screen.
+    #[serde(default)]
+    all: bool,
+    /// Only output after the read that returned this sequence number.
+    #[serde(default)]
+    since: Option<u64>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct SendTextArgs {
+    /// Terminal id, `<project>/<name>` or `<project>:<index>`.
+    terminal: String,
+    text: String,
+    /// Press Enter after the text.
+    #[serde(default)]
+    submit: bool,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct SendKeysArgs {
+    /// Terminal id, `<project>/<name>` or `<project>:<index>`.
+    terminal: String,
+    /// Pressed in order, e.g. `["ctrl-c", "up", "enter"]`.
+    keys: Vec<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct RunCommandArgs {
+    /// Terminal id, `<project>/<name>` or `<project>:<index>`.
+    terminal: String,
+    command: String,
+    /// Give up after this many seconds. Defaults to 5 minutes.
+    #[serde(default)]
+    timeout_secs: Option<u64>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct ProjectArgs {
+    /// Project id, name or path.
+    project: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct GitDiffArgs {
+    /// Project id, name or path.
+    project: String,
+    /// Staged changes instead of the working tree.
+    #[serde(default)]
+    staged: bool,
+    /// The changes a commit introduced (hash or ref).
+    #[serde(default)]
+    commit: Option<String>,
+    /// Compare branches: base...
+    #[serde(default)]
+    base: Option<String>,
+    /// ...and head.
+    #[serde(default)]
+    head: Option<String>,
+    /// One `path +added -removed` line per file instead of the diff.
+    #[serde(default)]
+    stat: bool,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct ServiceArgs {
+    /// Project id, name or path.
+    project: String,
+    /// Service name, as listed by `list_projects`.
+    service: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct CreateWorktreeArgs {
+    /// Project id, name or path of the repository.
+    project: String,
+    branch: String,
+    /// Create `branch` from the current HEAD instead of checking out an
+    /// existing one.
+    #[serde(default)]
+    create_branch: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use okena_core::api::ApiProject;
+    use okena_core::scope::classify;
+    use std::cell::RefCell;
+    use std::collections::BTreeSet;
+
+    struct FakeDaemon {
+        state: StateResponse,
+        sent: RefCell<Vec<ActionRequest>>,
+    }
+
+    impl Daemon for FakeDaemon {
+        fn state(&self) -> Result<StateResponse, String> {
+            Ok(self.state.clone())
+        }
+
+        fn action(&self, action: ActionRequest) -> Result<Option<Value>, String> {
+            let reply = match &action {
+                ActionRequest::ReadContent { .. } => Some(json!({
+                    "content": "$ ls\nsrc\n",
+                    "sequence": 7,
+                })),
+                _ => None,
+            };
+            self.sent.borrow_mut().push(action);
+            Ok(reply)
+        }
+    }
+
+    fn server(grant: TokenGrant) -> Server<FakeDaemon> {
+        let project: ApiProject = serde_json::from_value(json!({
+            "id": "p1",
+            "name": "web",
+            "path": "/src/web",
+            "is_visible": true,
+            "layout": {
+                "type": "terminal",
+                "terminal_id": "t1",
+                "minimized": false,
+                "detached": false,
+            },
+            "terminal_names": { "t1": "shell" },
+        }))
+        .unwrap();
+        let state: StateResponse = serde_json::from_value(json!({
+            "state_version": 1,
+            "projects": [project],
+            "focused_project_id": null,
+            "fullscreen_terminal": null,
+        }))
+        .unwrap();
+        Server {
+            daemon: FakeDaemon {
+                state,
+                sent: RefCell::new(Vec::new()),
+            },
+            grant,
+        }
+    }
+
+    fn request(server: &Server<FakeDaemon>, method: &str, params: Value) -> Value {
+        let line = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        server.handle_line(&line.to_string()).unwrap()
+    }
+
+    fn call(server: &Server<FakeDaemon>, name: &str, arguments: Value) -> Value {
+        request(
+            server,
+            "tools/call",
+            json!({ "name": name, "arguments": arguments }),
+        )["result"]
+            .clone()
+    }
+
+    #[test]
+    fn tool_scopes_match_the_daemon() {
+        let scope_of = |action: ActionRequest| classify(&action).0;
+        let terminal_id = || "t1".to_string();
+        let project_id = || "p1".to_string();
+        let expected = [
+            (
+                "read_terminal",
+                scope_of(ActionRequest::ReadContent {
+                    terminal_id: terminal_id(),
+                    lines: None,
+                    all: false,
+                    ansi: false,
+                    since: None,
+                    strip_prompt: false,
+                }),
+            ),
+            (
+                "send_keys",
+                scope_of(ActionRequest::SendSpecialKey {
+                    terminal_id: terminal_id(),
+                    key: okena_core::keys::SpecialKey::Enter,
+                }),
+            ),
+            (
+                "run_command",
+                scope_of(ActionRequest::ExecInTerminal {
+                    terminal_id: terminal_id(),
+                    command: String::new(),
+                    mirror: true,
+                    timeout_ms: None,
+                }),
+            ),
+            (
+                "git_diff",
+                scope_of(ActionRequest::GitDiff {
+                    project_id: project_id(),
+                    mode: DiffMode::WorkingTree,
+                    ignore_whitespace: false,
+                }),
+            ),
+            (
+                "stop_service",
+                scope_of(ActionRequest::StopService {
+                    project_id: project_id(),
+                    service_name: String::new(),
+                }),
+            ),
+            (
+                "create_worktree",
+                scope_of(ActionRequest::CreateWorktree {
+                    project_id: project_id(),
+                    branch: String::new(),
+                    create_branch: false,
+                }),
+            ),
+        ];
+        for (name, scope) in expected {
+            let tool = TOOLS.iter().find(|tool| tool.name == name).unwrap();
+            assert_eq!(tool.scope, scope, "{name}");
+        }
+    }
+
+    #[test]
+    fn restricted_token_only_lists_its_tools() {
+        let grant = TokenGrant {
+            scopes: BTreeSet::from([Scope::Read]),
+            ..TokenGrant::default()
+        };
+        let server = server(grant);
+        let tools = request(&server, "tools/list", Value::Null)["result"]["tools"].clone();
+        let names: Vec<&str> = tools
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|tool| tool["name"].as_str())
+            .collect();
+        assert_eq!(names, ["list_projects", "read_terminal"]);
+
+        let refused = call(
+            &server,
+            "send_text",
+            json!({ "terminal": "t1", "text": "x" }),
+        );
+        assert_eq!(refused["isError"], true);
+        assert!(server.daemon.sent.borrow().is_empty());
+    }
+
+    #[test]
+    fn tools_resolve_terminal_addresses() {
+        let server = server(TokenGrant::default());
+        let result = call(
+            &server,
+            "send_text",
+            json!({ "terminal": "web/shell", "text": "make", "submit": true }),
+        );
+        assert_eq!(result["isError"], false);
+        assert!(matches!(
+            server.daemon.sent.borrow().as_slice(),
+            [ActionRequest::RunCommand { terminal_id, command }]
+                if terminal_id == "t1" && command == "make"
+        ));
+
+        let read = call(&server, "read_terminal", json!({ "terminal": "web:0" }));
+        let text = read["content"][0]["text"].as_str().unwrap();
+        assert!(text.starts_with("$ ls\nsrc\n"));
+        assert!(text.contains("sequence 7"));
+    }
+
+    #[test]
+    fn bad_arguments_are_tool_errors() {
+        let server = server(TokenGrant::default());
+        let result = call(&server, "read_terminal", json!({ "terminl": "t1" }));
+        assert_eq!(result["isError"], true);
+        let result = call(
+            &server,
+            "send_keys",
+            json!({ "terminal": "t1", "keys": ["hyper"] }),
+        );
+        assert_eq!(result["isError"], true);
+    }
+
+    #[test]
+    fn protocol_basics() {
+        let server = server(TokenGrant::default());
+        let init = request(
+            &server,
+            "initialize",
+            json!({ "protocolVersion": "2024-11-05", "capabilities": {} }),
+        );
+        assert_eq!(init["result"]["protocolVersion"], "2024-11-05");
+        let init = request(
+            &server,
+            "initialize",
+            json!({ "protocolVersion": "1999-01-01" }),
+        );
+        assert_eq!(init["result"]["protocolVersion"], PROTOCOL_VERSIONS[0]);
+
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert!(server.handle_line(&notification.to_string()).is_none());
+
+        assert_eq!(
+            request(&server, "no/such", Value::Null)["error"]["code"],
+            METHOD_NOT_FOUND
+        );
+        assert_eq!(
+            server.handle_line("{not json").unwrap()["error"]["code"],
+            PARSE_ERROR
+        );
+    }
+
+    #[test]
+    fn resources_cover_terminals() {
+        let grant = TokenGrant {
+            scopes: BTreeSet::from([Scope::Read]),
+            ..TokenGrant::default()
+        };
+        let server = server(grant);
+        let list = request(&server, "resources/list", Value::Null);
+        let resources = list["result"]["resources"].as_array().unwrap();
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0]["uri"], "okena://terminal/t1");
+        assert_eq!(resources[0]["name"], "web/shell");
+
+        let read = request(
+            &server,
+            "resources/read",
+            json!({ "uri": "okena://terminal/t1" }),
+        );
+        assert_eq!(read["result"]["contents"][0]["text"], "$ ls\nsrc\n");
+        let missing = request(&server, "resources/read", json!({ "uri": "okena://nope" }));
+        assert_eq!(missing["error"]["code"], RESOURCE_NOT_FOUND);
+    }
+}
@@ -4,7 +4,10 @@
 //! [`subcommand_names`] feeds the gate in `try_handle_cli` so the GUI / profile
 //! launch path stays untouched for anything that isn't one of our commands.
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap_complete::ArgValueCandidates;
+
+use crate::complete;
 
 /// Okena CLI — control a running Okena instance over its remote HTTP API.
 #[derive(Parser)]
@@ -29,7 +32,21 @@ pub struct Cli {
 pub enum Command {
     // ── KEPT (reimplemented under clap) ──────────────────────────────────────
     /// Generate a pairing code for remote clients
-    Pair,
+    ///
+    /// Without --scope or --project/--folder the paired device gets full
+    /// access. Scopes: read, terminal:input, git, files, services, admin;
+    /// `read` alone makes it view-only.
+    Pair {
+        /// Scopes the device gets (repeatable or comma-separated)
+        #[arg(long = "scope", value_delimiter = ',')]
+        scopes: Vec<okena_core::scope::Scope>,
+        /// Only these projects (id / name; repeatable or comma-separated)
+        #[arg(long = "project", value_delimiter = ',', add = ArgValueCandidates::new(complete::projects))]
+        projects: Vec<String>,
+        /// Only the projects in these folders (id / name; repeatable or comma-separated)
+        #[arg(long = "folder", value_delimiter = ',')]
+        folders: Vec<String>,
+    },
     /// Server health check
     Health {
         /// Output JSON instead of the default plain text
@@ -43,13 +60,25 @@ pub enum Command {
     /// Escape hatch for actions without a dedicated subcommand. The body is a
     /// snake_case-tagged object like `{"action":"focus_terminal", ...}`. See
     /// `okena skill show` for the action surface and `okena state` for the ids.
+    ///
+    /// `--batch FILE` applies a list of actions in one daemon turn instead;
+    /// a field set to `{"$ref": "0.terminal_id"}` uses an earlier result.
     Action {
         /// The JSON ActionRequest body
-        json: String,
+        #[arg(required_unless_present = "batch", conflicts_with = "batch")]
+        json: Option<String>,
+        /// Run a JSON array of actions (or `{"actions": [...]}`) from FILE,
+        /// `-` for stdin
+        #[arg(long, value_name = "FILE")]
+        batch: Option<String>,
+        /// On failure, put the layouts back as they were before the batch
+        #[arg(long, conflicts_with = "json")]
+        rollback: bool,
     },
     /// List services and their status
     Services {
         /// Optional project filter (id / name)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
         project: Option<String>,
         /// Output JSON instead of the default plain text
         #[arg(long)]
@@ -60,6 +89,65 @@ pub enum Command {
         #[command(subcommand)]
         cmd: ServiceCmd,
     },
+    /// Inspect, cancel and diff lifecycle hook runs
+    Hook {
+        #[command(subcommand)]
+        cmd: HookCmd,
+    },
+    /// Stream workspace events as NDJSON, one object per line
+    ///
+    /// Blocks until interrupted (or until --count events arrived). Each line has
+    /// a `type` field: state_changed, project_added, project_removed,
+    /// terminal_created, terminal_closed, command_finished, bell, notification,
+    /// service_status_changed, hook_finished, git_status_changed or toast.
+    Events {
+        /// Only these event types (repeatable or comma-separated)
+        #[arg(long = "type", value_delimiter = ',')]
+        types: Vec<String>,
+        /// Only events for these projects (id / name; repeatable or comma-separated)
+        #[arg(long = "project", value_delimiter = ',', add = ArgValueCandidates::new(complete::projects))]
+        projects: Vec<String>,
+        /// Exit after printing this many events
+        #[arg(long)]
+        count: Option<usize>,
+    },
+    /// Show what paired remote devices did (the server's audit log)
+    ///
+    /// Default: tab-separated, oldest first: unix time \t device \t client
+    /// \t kind \t ok|denied \t summary. Needs a token with the admin scope.
+    Audit {
+        /// Only this device (token id or device name)
+        #[arg(long)]
+        device: Option<String>,
+        /// Only this kind (an action such as send_text, or ws_input, batch,
+        /// paste_image, paste_file, pair, revoke_token)
+        #[arg(long)]
+        kind: Option<String>,
+        /// Only entries newer than this age (e.g. 90s, 30m, 12h, 7d)
+        #[arg(long, value_parser = parse_age)]
+        since: Option<u64>,
+        /// Show at most the newest N entries
+        #[arg(long, default_value_t = okena_core::audit::DEFAULT_QUERY_LIMIT)]
+        limit: usize,
+        /// Output JSON instead of the default tab-separated text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the remote API's OpenAPI document, or one type's JSON Schema
+    ///
+    /// Generated from the wire types this binary was built with; a running
+    /// server serves the same document at GET /v1/schema. Works offline.
+    Schema {
+        /// A request, response or WebSocket frame type, e.g. ActionRequest
+        #[arg(add = ArgValueCandidates::new(complete::schema_types))]
+        type_name: Option<String>,
+    },
+    /// Serve Okena's tools to an agent over the Model Context Protocol
+    ///
+    /// Speaks JSON-RPC on stdin/stdout; point an MCP client's stdio server
+    /// entry at `okena mcp`. Uses the CLI's token, or $OKENA_TOKEN if set, and
+    /// only offers the tools that token's scopes allow.
+    Mcp,
     /// Identify the current terminal and project (uses $OKENA_TERMINAL_ID)
     Whoami {
         /// Output JSON instead of the default plain text
@@ -86,6 +174,11 @@ pub enum Command {
         #[command(subcommand)]
         cmd: WorktreeCmd,
     },
+    /// Git status, diffs, branches and staging for a project
+    Git {
+        #[command(subcommand)]
+        cmd: GitCmd,
+    },
     /// Folder operations
     Folder {
         #[command(subcommand)]
@@ -97,10 +190,23 @@ pub enum Command {
         cmd: TermCmd,
     },
 
+    // ── Sessions ─────────────────────────────────────────────────────────────
+    /// Saved sessions (named workspace snapshots in the profile directory)
+    Session {
+        #[command(subcommand)]
+        cmd: SessionCmd,
+    },
+    /// Export / import the whole workspace as a file
+    Workspace {
+        #[command(subcommand)]
+        cmd: WorkspaceCmd,
+    },
+
     // ── I/O (the agent loop) ─────────────────────────────────────────────────
     /// Send raw text to a terminal (no trailing newline)
     Send {
         /// Terminal address (id, project/name, or project:index)
+        #[arg(add = ArgValueCandidates::new(complete::terminals))]
         terminal: String,
         /// Text to send (joined with spaces)
         #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
@@ -112,14 +218,16 @@ pub enum Command {
     /// its status. Because the command is a trailing arg, --wait/--timeout must
     /// come *before* the terminal: `okena run --wait <term> <cmd>`.
     Run {
-        /// Block until the command finishes, then exit with its status.
-        /// Non-interactive commands only (appends a completion marker).
+        /// Block until the command finishes, print its output and exit with its
+        /// status. Uses shell integration (OSC 133) when the terminal has it,
+        /// otherwise runs the command as a hidden process in the terminal's cwd.
         #[arg(long)]
         wait: bool,
         /// With --wait: seconds to wait for completion before giving up.
         #[arg(long, default_value_t = 300)]
         timeout: u64,
         /// Terminal address (id, project/name, or project:index)
+        #[arg(add = ArgValueCandidates::new(complete::terminals))]
         terminal: String,
         /// Command to run (joined with spaces)
         #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
@@ -128,25 +236,131 @@ pub enum Command {
     /// Send a special key to a terminal
     Key {
         /// Terminal address (id, project/name, or project:index)
+        #[arg(add = ArgValueCandidates::new(complete::terminals))]
         terminal: String,
         /// Key name: enter, esc, tab, up/down/left/right, home, end, pageup,
         /// pagedown, backspace, delete, or a ctrl-<a-z> chord (e.g. ctrl-c, ctrl-l)
         key: String,
     },
-    /// Read the visible content of a terminal
+    /// Read the content of a terminal (the visible screen by default)
+    ///
+    /// For polling, start with `--since 0` and pass the sequence printed on
+    /// stderr (or in `--json`) to the next read to get only newer output.
     Read {
         /// Terminal address (id, project/name, or project:index)
+        #[arg(add = ArgValueCandidates::new(complete::terminals))]
         terminal: String,
+        /// The last N lines, reaching back into scrollback
+        #[arg(long, value_name = "N")]
+        lines: Option<usize>,
+        /// All of scrollback plus the screen
+        #[arg(long, conflicts_with = "lines")]
+        all: bool,
+        /// Keep colors and attributes as ANSI escapes
+        #[arg(long)]
+        ansi: bool,
+        /// Only output after the read that returned this sequence
+        #[arg(long, value_name = "SEQUENCE")]
+        since: Option<u64>,
+        /// Leave out the shell prompt the cursor sits on
+        #[arg(long)]
+        strip_prompt: bool,
         /// Output JSON instead of the default plain text
         #[arg(long)]
         json: bool,
     },
+    /// Block until a terminal's output matches a regex
+    ///
+    /// Matched server-side against the live output stream (ANSI stripped,
+    /// multi-line), so lines that scroll past are still caught. Prints the
+    /// match, then each capture group on its own line. Exits 124 on timeout.
+    WaitFor {
+        /// Terminal address (id, project/name, or project:index)
+        #[arg(add = ArgValueCandidates::new(complete::terminals))]
+        terminal: String,
+        /// Pattern to wait for (Rust regex syntax; `^`/`$` match per line)
+        #[arg(long)]
+        regex: String,
+        /// Seconds to wait before giving up
+        #[arg(long, default_value_t = 30)]
+        timeout: u64,
+        /// Ignore what is already on screen; only match new output
+        #[arg(long)]
+        since_now: bool,
+        /// Output JSON instead of the default plain text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Take over a terminal from the current tty, like `tmux attach`
+    ///
+    /// Renders the pane full-screen and forwards keys to it; this tty's size
+    /// becomes the terminal's size while attached. The detach key returns to
+    /// the shell and leaves the terminal running. Needs an interactive tty.
+    Attach {
+        /// Terminal address (id, project/name, or project:index)
+        #[arg(add = ArgValueCandidates::new(complete::terminals))]
+        terminal: String,
+        /// Key chord that detaches (ctrl-<char>)
+        #[arg(long, default_value = "ctrl-]")]
+        detach_key: okena_tui::attach::DetachKey,
+    },
+    /// Upload a local file into a project
+    ///
+    /// Streams the file to the daemon's host, so it also works against a
+    /// remote Okena. It lands at the project root under its own name unless
+    /// --to gives a path; a --to ending in `/` keeps the name inside that
+    /// directory, which must exist. Prints the path it was written to.
+    Upload {
+        /// Local file to upload
+        file: String,
+        /// Project (id, name or path); defaults to the only or focused project
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
+        project: Option<String>,
+        /// Destination relative to the project root
+        #[arg(long, value_name = "PATH")]
+        to: Option<String>,
+        /// If the destination exists, write `name (1).ext` instead
+        #[arg(long, conflicts_with = "overwrite")]
+        rename: bool,
+        /// If the destination exists, replace it
+        #[arg(long)]
+        overwrite: bool,
+        /// Output JSON instead of the default plain text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Forward a service's port to localhost until interrupted
+    ///
+    /// Relays over the daemon connection, so a service on a remote Okena
+    /// opens at http://localhost:<port> without an SSH tunnel. Only ports the
+    /// daemon detected for the service can be forwarded. Prints the local URL.
+    Forward {
+        /// Service name (see `okena services`)
+        #[arg(add = ArgValueCandidates::new(complete::services))]
+        service: String,
+        /// Project (id / name); omit to use the only / focused project
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
+        project: Option<String>,
+        /// Remote port, if the service listens on several (default: the first)
+        #[arg(long)]
+        port: Option<u16>,
+        /// Local port (default: the remote port if free, else any)
+        #[arg(long)]
+        local_port: Option<u16>,
+    },
 
     /// Print or install the agent skill (a concise CLI reference for agents)
     Skill {
         #[command(subcommand)]
         cmd: SkillCmd,
     },
+    /// Print the shell completion script
+    ///
+    /// Completes subcommands and flags, plus project names, terminal
+    /// addresses, services, sessions, themes and palette commands from the
+    /// running Okena. Source it from your shell's rc file, e.g.
+    /// `source <(okena completions bash)`.
+    Completions { shell: CompletionShell },
 
     /// Inspect or change app settings
     Settings {
@@ -239,11 +453,13 @@ pub enum ThemeCmd {
     /// Print a theme as an editable JSON blob (the active theme if id omitted)
     Show {
         /// Theme id: a built-in mode (dark/light/…) or a custom id
+        #[arg(add = ArgValueCandidates::new(complete::themes))]
         id: Option<String>,
     },
     /// Activate a theme (built-in mode or custom id)
     Set {
         /// Theme id: auto/dark/light/pastel-dark/high-contrast or a custom id
+        #[arg(add = ArgValueCandidates::new(complete::themes))]
         id: String,
     },
     /// Write a custom theme from a full JSON blob and (by default) activate it
@@ -269,10 +485,18 @@ pub enum PaletteCmd {
     /// Invoke a command by name (e.g. ToggleSidebar, NewWindow, ZoomIn)
     Run {
         /// Action name from `okena command list`
+        #[arg(add = ArgValueCandidates::new(complete::palette_commands))]
         name: String,
     },
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
 #[derive(Subcommand)]
 pub enum SkillCmd {
     /// Print the skill markdown to stdout
@@ -293,8 +517,10 @@ pub enum ServiceCmd {
     /// Start a service
     Start {
         /// Service name (see `okena services`)
+        #[arg(add = ArgValueCandidates::new(complete::services))]
         name: String,
         /// Project (id / name); omit to use the only / focused project
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
         project: Option<String>,
         /// Output JSON instead of the default plain text
         #[arg(long)]
@@ -303,8 +529,10 @@ pub enum ServiceCmd {
     /// Stop a service
     Stop {
         /// Service name (see `okena services`)
+        #[arg(add = ArgValueCandidates::new(complete::services))]
         name: String,
         /// Project (id / name); omit to use the only / focused project
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
         project: Option<String>,
         /// Output JSON instead of the default plain text
         #[arg(long)]
@@ -313,13 +541,96 @@ pub enum ServiceCmd {
     /// Restart a service
     Restart {
         /// Service name (see `okena services`)
+        #[arg(add = ArgValueCandidates::new(complete::services))]
         name: String,
         /// Project (id / name); omit to use the only / focused project
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
         project: Option<String>,
         /// Output JSON instead of the default plain text
         #[arg(long)]
         json: bool,
     },
+    /// Rebuild a Docker Compose service's image and recreate its container
+    Rebuild {
+        /// Service name (see `okena services`)
+        #[arg(add = ArgValueCandidates::new(complete::services))]
+        name: String,
+        /// Project (id / name); omit to use the only / focused project
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
+        project: Option<String>,
+        /// Output JSON instead of the default plain text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Pull the latest image for a Docker Compose service
+    Pull {
+        /// Service name (see `okena services`)
+        #[arg(add = ArgValueCandidates::new(complete::services))]
+        name: String,
+        /// Project (id / name); omit to use the only / focused project
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
+        project: Option<String>,
+        /// Output JSON instead of the default plain text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Open a terminal with a shell inside a Docker Compose service's container
+    Exec {
+        /// Service name (see `okena services`)
+        #[arg(add = ArgValueCandidates::new(complete::services))]
+        name: String,
+        /// Project (id / name); omit to use the only / focused project
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
+        project: Option<String>,
+        /// Shell to run in the container (default: bash, falling back to sh)
+        #[arg(long)]
+        shell: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum HookCmd {
+    /// List recent hook executions (in memory; ids for `hook cancel`)
+    Ls {
+        /// Output JSON instead of tab-separated text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Cancel a running hook execution
+    Cancel {
+        /// Execution id (see `okena hook ls`)
+        id: u64,
+    },
+    /// List saved hook runs, newest first (persisted across daemon restarts)
+    Logs {
+        /// Only runs of this hook type (e.g. on_project_open)
+        #[arg(long = "type")]
+        hook_type: Option<String>,
+        /// Maximum number of runs to list
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+        /// Output JSON instead of tab-separated text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the captured stdout/stderr of a saved hook run
+    Log {
+        /// Run id (see `okena hook logs`)
+        id: u64,
+        /// Output JSON instead of the raw output
+        #[arg(long)]
+        json: bool,
+    },
+    /// Diff the output of two saved runs of the same hook
+    Diff {
+        /// Base run id
+        base: u64,
+        /// Run id to compare against the base
+        run: u64,
+        /// Output JSON instead of a unified diff
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -361,21 +672,25 @@ pub enum ProjectCmd {
     /// Remove a project (unlinks it from Okena; the folder on disk is kept)
     Rm {
         /// Project (id / name / path)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
         project: String,
     },
     /// Show a project in the overview
     Show {
         /// Project (id / name / path)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
         project: String,
     },
     /// Hide a project from the overview
     Hide {
         /// Project (id / name / path)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
         project: String,
     },
     /// Rename a project
     Rename {
         /// Project (id / name / path)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
         project: String,
         /// New display name
         name: String,
@@ -383,6 +698,7 @@ pub enum ProjectCmd {
     /// Set a project's color
     Color {
         /// Project (id / name / path)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
         project: String,
         /// Color (default, red, orange, yellow, lime, green, teal, cyan, blue, indigo, purple, pink)
         color: String,
@@ -390,6 +706,7 @@ pub enum ProjectCmd {
     /// Focus a project's first terminal
     Focus {
         /// Project (id / name / path)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
         project: String,
     },
 }
@@ -416,6 +733,195 @@ pub enum WorktreeCmd {
     },
 }
 
+#[derive(Subcommand)]
+pub enum GitCmd {
+    /// Branch, line counts, ahead/behind and PR/CI status
+    Status {
+        /// Project (id / name / path)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
+        project: String,
+        /// Output JSON instead of tab-separated text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show changes (working tree by default)
+    Diff {
+        /// Project (id / name / path)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
+        project: String,
+        /// Staged changes instead of the working tree
+        #[arg(long, conflicts_with_all = ["commit", "base"])]
+        staged: bool,
+        /// Changes introduced by this commit (hash or ref)
+        #[arg(long, conflicts_with = "base")]
+        commit: Option<String>,
+        /// Compare branches: base ref (use with --head)
+        #[arg(long, requires = "head")]
+        base: Option<String>,
+        /// Compare branches: head ref (use with --base)
+        #[arg(long, requires = "base")]
+        head: Option<String>,
+        /// Ignore whitespace-only changes
+        #[arg(long)]
+        ignore_whitespace: bool,
+        /// Per-file line counts instead of the patch
+        #[arg(long)]
+        stat: bool,
+        /// Output JSON instead of a unified diff
+        #[arg(long)]
+        json: bool,
+    },
+    /// List local and remote branches
+    Branches {
+        /// Project (id / name / path)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
+        project: String,
+        /// Output JSON instead of tab-separated text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check out a branch (remote-only branches get a tracking local branch)
+    Checkout {
+        /// Project (id / name / path)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
+        project: String,
+        /// Branch name (e.g. `feature` or `origin/feature`)
+        branch: String,
+        /// Create the branch first
+        #[arg(short = 'b', long)]
+        create: bool,
+        /// With --create: start the new branch here instead of at HEAD
+        #[arg(long, requires = "create")]
+        start_point: Option<String>,
+    },
+    /// Stage files
+    Stage {
+        /// Project (id / name / path)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
+        project: String,
+        /// File paths, relative to the repository root
+        #[arg(required = true)]
+        files: Vec<String>,
+    },
+    /// Unstage files
+    Unstage {
+        /// Project (id / name / path)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
+        project: String,
+        /// File paths, relative to the repository root
+        #[arg(required = true)]
+        files: Vec<String>,
+    },
+    /// Discard working-tree changes to files (cannot be undone)
+    Discard {
+        /// Project (id / name / path)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
+        project: String,
+        /// File paths, relative to the repository root
+        #[arg(required = true)]
+        files: Vec<String>,
+    },
+    /// Recent commits
+    Log {
+        /// Project (id / name / path)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
+        project: String,
+        /// Number of commits to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        count: usize,
+        /// Branch to walk instead of HEAD
+        #[arg(long)]
+        branch: Option<String>,
+        /// Output JSON instead of tab-separated text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Line-by-line authorship of a file
+    Blame {
+        /// Project (id / name / path)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
+        project: String,
+        /// File path, relative to the repository root
+        file: String,
+        /// Output JSON instead of tab-separated text
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SessionCmd {
+    /// List saved sessions
+    Ls {
+        /// Output JSON instead of tab-separated text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Save the current workspace as a new session
+    Save {
+        /// Session name (must not exist yet)
+        name: String,
+        /// Output JSON instead of plain text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Replace the current workspace with a saved session (closes all terminals)
+    Load {
+        /// Session name
+        #[arg(add = ArgValueCandidates::new(complete::sessions))]
+        name: String,
+        /// Skip the confirmation prompt (required when stdin is not a terminal)
+        #[arg(long)]
+        yes: bool,
+        /// Output JSON instead of plain text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Delete a saved session
+    Rm {
+        /// Session name
+        #[arg(add = ArgValueCandidates::new(complete::sessions))]
+        name: String,
+        /// Output JSON instead of plain text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Rename a saved session
+    Rename {
+        /// Current session name
+        #[arg(add = ArgValueCandidates::new(complete::sessions))]
+        name: String,
+        /// New session name
+        new_name: String,
+        /// Output JSON instead of plain text
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum WorkspaceCmd {
+    /// Write the current workspace to a file
+    Export {
+        /// Destination file (relative paths resolve against CWD)
+        file: String,
+        /// Output JSON instead of plain text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Replace the current workspace with one from a file (closes all terminals)
+    Import {
+        /// Exported workspace file (relative paths resolve against CWD)
+        file: String,
+        /// Skip the confirmation prompt (required when stdin is not a terminal)
+        #[arg(long)]
+        yes: bool,
+        /// Output JSON instead of plain text
+        #[arg(long)]
+        json: bool,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum FolderCmd {
     /// Create a folder
@@ -442,29 +948,30 @@ pub enum TermCmd {
     /// List terminals
     Ls {
         /// Optional project filter (id / name / path)
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
         project: Option<String>,
         /// Output JSON instead of the default plain text
         #[arg(long)]
         json: bool,
     },
     /// Create a new terminal in a project
-    New {
-        /// Project (id / name / path)
-        project: String,
-    },
+    New(TermNewArgs),
     /// Close a terminal
     Close {
         /// Terminal address (id, project/name, or project:index)
+        #[arg(add = ArgValueCandidates::new(complete::terminals))]
         terminal: String,
     },
     /// Focus a terminal
     Focus {
         /// Terminal address (id, project/name, or project:index)
+        #[arg(add = ArgValueCandidates::new(complete::terminals))]
         terminal: String,
     },
     /// Rename a terminal
     Rename {
         /// Terminal address (id, project/name, or project:index)
+        #[arg(add = ArgValueCandidates::new(complete::terminals))]
         terminal: String,
         /// New terminal name
         name: String,
@@ -472,6 +979,7 @@ pub enum TermCmd {
     /// Split a terminal: h = stacked top/bottom, v = side by side left/right
     Split {
         /// Terminal address (id, project/name, or project:index)
+        #[arg(add = ArgValueCandidates::new(complete::terminals))]
         terminal: String,
         /// h = horizontal split (new pane below, panes stacked top/bottom);
         /// v = vertical split (new pane to the right, panes side by side)
@@ -480,16 +988,19 @@ pub enum TermCmd {
     /// Add a tab next to a terminal
     Tab {
         /// Terminal address (id, project/name, or project:index)
+        #[arg(add = ArgValueCandidates::new(complete::terminals))]
         terminal: String,
     },
     /// Toggle a terminal's minimized state
     Minimize {
         /// Terminal address (id, project/name, or project:index)
+        #[arg(add = ArgValueCandidates::new(complete::terminals))]
         terminal: String,
     },
     /// Fullscreen a terminal (or exit fullscreen with --off)
     Fullscreen {
         /// Terminal address (id, project/name, or project:index)
+        #[arg(add = ArgValueCandidates::new(complete::terminals))]
         terminal: String,
         /// Exit fullscreen instead
         #[arg(long)]
@@ -497,14 +1008,114 @@ pub enum TermCmd {
     },
 }
 
+/// `okena term new`: everything `create_terminal` accepts, so a multi-pane
+/// setup needs no follow-up rename / split / send calls.
+#[derive(Args)]
+pub struct TermNewArgs {
+    /// Project (id / name / path)
+    #[arg(add = ArgValueCandidates::new(complete::projects))]
+    pub project: String,
+    /// Working directory (default: the cwd of the --split/--tab terminal,
+    /// else the project root)
+    #[arg(long)]
+    pub cwd: Option<String>,
+    /// Command typed into the new shell once it starts
+    #[arg(long)]
+    pub command: Option<String>,
+    /// Shell program to run instead of the project/global default
+    #[arg(long)]
+    pub shell: Option<String>,
+    /// Extra environment variable for the shell (repeatable)
+    #[arg(long = "env", value_name = "KEY=VALUE")]
+    pub env: Vec<String>,
+    /// Terminal name
+    #[arg(long)]
+    pub name: Option<String>,
+    /// Start minimized
+    #[arg(long)]
+    pub minimized: bool,
+    /// Split this terminal instead of appending to the project layout
+    #[arg(
+        long,
+        value_name = "TERMINAL",
+        conflicts_with_all = ["tab", "detached"],
+        add = ArgValueCandidates::new(complete::terminals)
+    )]
+    pub split: Option<String>,
+    /// Direction for --split: h (new pane below) or v (new pane to the right)
+    #[arg(long, requires = "split", default_value = "v")]
+    pub direction: String,
+    /// Open as a tab next to this terminal
+    #[arg(
+        long,
+        value_name = "TERMINAL",
+        conflicts_with = "detached",
+        add = ArgValueCandidates::new(complete::terminals)
+    )]
+    pub tab: Option<String>,
+    /// Open in its own window
+    #[arg(long)]
+    pub detached: bool,
+}
+
+/// `30m` → 1800. A bare number is seconds.
+fn parse_age(s: &str) -> Result<u64, String> {
+    let (digits, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let n: u64 = digits
+        .parse()
+        .map_err(|_| format!("invalid age '{s}' (expected e.g. 90s, 30m, 12h, 7d)"))?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return Err(format!("invalid age unit in '{s}' (use s, m, h or d)")),
+    };
+    Ok(n.saturating_mul(unit_secs))
+}
+
 /// The set of top-level subcommand names the CLI claims. Used by the gate in
 /// `try_handle_cli`: if `args[1]` isn't one of these (or a help flag), we hand
 /// control back to GUI/profile launch.
 pub fn subcommand_names() -> &'static [&'static str] {
     &[
-        "pair", "health", "state", "action", "services", "service", "whoami", "ls", "project",
-        "worktree", "folder", "term", "send", "run", "key", "read", "skill", "settings", "theme",
-        "command", "update",
+        "pair",
+        "health",
+        "state",
+        "action",
+        "services",
+        "service",
+        "hook",
+        "events",
+        "audit",
+        "schema",
+        "mcp",
+        "whoami",
+        "ls",
+        "project",
+        "worktree",
+        "git",
+        "folder",
+        "term",
+        "session",
+        "workspace",
+        "send",
+        "run",
+        "key",
+        "read",
+        "wait-for",
+        "attach",
+        "upload",
+        "forward",
+        "skill",
+        "completions",
+        "settings",
+        "theme",
+        "command",
+        "update",
     ]
 }
 
@@ -540,7 +1151,93 @@ mod tests {
     fn parses_representative_commands() {
         // A spread of forms, including the global --window flag and trailing args.
         assert!(Cli::try_parse_from(["okena", "ls", "--json"]).is_ok());
+        assert!(
+            Cli::try_parse_from([
+                "okena",
+                "pair",
+                "--scope",
+                "read,terminal:input",
+                "--project",
+                "web"
+            ])
+            .is_ok()
+        );
+        assert!(Cli::try_parse_from(["okena", "pair", "--scope", "root"]).is_err());
+        let audit = Cli::try_parse_from(["okena", "audit", "--device", "phone", "--since", "2h"]);
+        assert!(matches!(
+            audit.map(|cli| cli.command),
+            Ok(Command::Audit {
+                since: Some(7200),
+                ..
+            })
+        ));
+        assert!(Cli::try_parse_from(["okena", "audit", "--since", "2w"]).is_err());
+        assert!(Cli::try_parse_from(["okena", "schema", "ActionRequest"]).is_ok());
+        assert!(Cli::try_parse_from(["okena", "mcp"]).is_ok());
         assert!(Cli::try_parse_from(["okena", "term", "split", "p/sh", "h"]).is_ok());
+        assert!(
+            Cli::try_parse_from(["okena", "action", "--batch", "b.json", "--rollback"]).is_ok()
+        );
+        assert!(Cli::try_parse_from(["okena", "action", "{}", "--batch", "b.json"]).is_err());
+        assert!(Cli::try_parse_from(["okena", "action", "{}", "--rollback"]).is_err());
+        let term_new = Cli::try_parse_from([
+            "okena",
+            "term",
+            "new",
+            "web",
+            "--split",
+            "web:0",
+            "--direction",
+            "h",
+            "--cwd",
+            "src",
+            "--shell",
+            "/bin/zsh",
+            "--env",
+            "PORT=3000",
+            "--env",
+            "HOST=0.0.0.0",
+            "--command",
+            "npm run dev",
+            "--name",
+            "dev",
+            "--minimized",
+        ]);
+        let Ok(Command::Term {
+            cmd: TermCmd::New(args),
+        }) = term_new.map(|cli| cli.command)
+        else {
+            panic!("term new with every flag should parse");
+        };
+        assert_eq!(args.project, "web");
+        assert_eq!(args.split.as_deref(), Some("web:0"));
+        assert_eq!(args.direction, "h");
+        assert_eq!(args.cwd.as_deref(), Some("src"));
+        assert_eq!(args.shell.as_deref(), Some("/bin/zsh"));
+        assert_eq!(args.env, ["PORT=3000", "HOST=0.0.0.0"]);
+        assert_eq!(args.command.as_deref(), Some("npm run dev"));
+        assert_eq!(args.name.as_deref(), Some("dev"));
+        assert!(args.minimized);
+        assert!(args.tab.is_none());
+        assert!(!args.detached);
+        for placement in [&["--tab", "web:0"][..], &["--detached"]] {
+            let argv = ["okena", "term", "new", "web"].iter().chain(placement);
+            assert!(Cli::try_parse_from(argv).is_ok(), "{placement:?}");
+        }
+        // One placement at a time; --direction only goes with --split.
+        assert!(
+            Cli::try_parse_from([
+                "okena",
+                "term",
+                "new",
+                "web",
+                "--tab",
+                "web:0",
+                "--detached"
+            ])
+            .is_err()
+        );
+        assert!(Cli::try_parse_from(["okena", "term", "new", "web", "--direction", "h"]).is_err());
         assert!(
             Cli::try_parse_from(["okena", "project", "focus", "Proj", "--window", "main"]).is_ok()
         );
@@ -567,6 +1264,134 @@ mod tests {
             Cli::try_parse_from(["okena", "run", "--wait", "--timeout", "60", "t1", "make"])
                 .is_ok()
         );
+        assert!(Cli::try_parse_from(["okena", "service", "rebuild", "web", "--json"]).is_ok());
+        assert!(Cli::try_parse_from(["okena", "service", "exec", "web", "--shell", "zsh"]).is_ok());
+        assert!(Cli::try_parse_from(["okena", "hook", "cancel", "12"]).is_ok());
+        assert!(
+            Cli::try_parse_from(["okena", "hook", "logs", "--type", "on_project_open"]).is_ok()
+        );
+        assert!(Cli::try_parse_from(["okena", "hook", "diff", "3", "7", "--json"]).is_ok());
+        assert!(Cli::try_parse_from(["okena", "hook", "cancel", "abc"]).is_err());
+        assert!(
+            Cli::try_parse_from([
+                "okena",
+                "wait-for",
+                "t1",
+                "--regex",
+                r"^>>> ",
+                "--timeout",
+                "5",
+                "--since-now",
+            ])
+            .is_ok()
+        );
+        assert!(Cli::try_parse_from(["okena", "wait-for", "t1"]).is_err());
+        assert!(
+            Cli::try_parse_from([
+                "okena",
+                "read",
+                "t1",
+                "--lines",
+                "200",
+                "--since",
+                "41",
+                "--strip-prompt",
+            ])
+            .is_ok()
+        );
+        assert!(Cli::try_parse_from(["okena", "read", "t1", "--all", "--lines", "5"]).is_err());
+        assert!(Cli::try_parse_from(["okena", "completions", "zsh"]).is_ok());
+        assert!(Cli::try_parse_from(["okena", "completions", "tcsh"]).is_err());
+        assert!(Cli::try_parse_from(["okena", "attach", "web:0"]).is_ok());
+        assert!(
+            Cli::try_parse_from(["okena", "attach", "web:0", "--detach-key", "ctrl-b"]).is_ok()
+        );
+        assert!(Cli::try_parse_from(["okena", "attach", "web:0", "--detach-key", "q"]).is_err());
+        assert!(
+            Cli::try_parse_from(["okena", "upload", "crash.log", "web", "--to", "logs/"]).is_ok()
+        );
+        assert!(
+            Cli::try_parse_from(["okena", "upload", "a.png", "--rename", "--overwrite"]).is_err()
+        );
+        assert!(
+            Cli::try_parse_from(["okena", "forward", "web", "site", "--local-port", "8080"])
+                .is_ok()
+        );
+        assert!(Cli::try_parse_from(["okena", "forward", "web", "--port", "http"]).is_err());
+        assert!(Cli::try_parse_from(["okena", "git", "status", "web", "--json"]).is_ok());
+        assert!(
+            Cli::try_parse_from([
+                "okena", "git", "diff", "web", "--base", "main", "--head", "x"
+            ])
+            .is_ok()
+        );
+        assert!(Cli::try_parse_from(["okena", "git", "diff", "web", "--base", "main"]).is_err());
+        assert!(
+            Cli::try_parse_from([
+                "okena", "git", "diff", "web", "--staged", "--commit", "HEAD"
+            ])
+            .is_err()
+        );
+        assert!(Cli::try_parse_from(["okena", "git", "checkout", "web", "-b", "feat"]).is_ok());
+        assert!(Cli::try_parse_from(["okena", "git", "stage", "web", "a.rs", "b.rs"]).is_ok());
+        assert!(Cli::try_parse_from(["okena", "git", "discard", "web"]).is_err());
+        assert!(Cli::try_parse_from(["okena", "git", "log", "web", "-n", "5"]).is_ok());
+        assert!(Cli::try_parse_from(["okena", "session", "ls", "--json"]).is_ok());
+        assert!(Cli::try_parse_from(["okena", "session", "save", "demo", "--json"]).is_ok());
+        assert!(Cli::try_parse_from(["okena", "session", "save"]).is_err());
+        let load = Cli::try_parse_from(["okena", "session", "load", "demo", "--yes", "--json"]);
+        assert!(matches!(
+            load.map(|cli| cli.command),
+            Ok(Command::Session {
+                cmd: SessionCmd::Load {
+                    yes: true,
+                    json: true,
+                    ..
+                }
+            })
+        ));
+        assert!(matches!(
+            Cli::try_parse_from(["okena", "session", "load", "demo"]).map(|cli| cli.command),
+            Ok(Command::Session {
+                cmd: SessionCmd::Load { yes: false, .. }
+            })
+        ));
+        assert!(Cli::try_parse_from(["okena", "session", "rm", "demo", "--json"]).is_ok());
+        assert!(Cli::try_parse_from(["okena", "session", "rm", "demo", "--yes"]).is_err());
+        assert!(Cli::try_parse_from(["okena", "session", "rename", "a", "b", "--json"]).is_ok());
+        assert!(Cli::try_parse_from(["okena", "session", "rename", "a"]).is_err());
+        assert!(Cli::try_parse_from(["okena", "workspace", "export", "ws.json", "--json"]).is_ok());
+        assert!(Cli::try_parse_from(["okena", "workspace", "export", "ws.json", "--yes"]).is_err());
+        assert!(Cli::try_parse_from(["okena", "workspace", "export"]).is_err());
+        let import =
+            Cli::try_parse_from(["okena", "workspace", "import", "ws.json", "--yes", "--json"]);
+        assert!(matches!(
+            import.map(|cli| cli.command),
+            Ok(Command::Workspace {
+                cmd: WorkspaceCmd::Import {
+                    yes: true,
+                    json: true,
+                    ..
+                }
+            })
+        ));
+        assert!(Cli::try_parse_from(["okena", "workspace", "import", "ws.json"]).is_ok());
+        assert!(Cli::try_parse_from(["okena", "events"]).is_ok());
+        assert!(
+            Cli::try_parse_from([
+                "okena",
+                "events",
+                "--type",
+                "bell,command_finished",
+                "--type",
+                "hook_finished",
+                "--project",
+                "web",
+                "--count",
+                "1",
+            ])
+            .is_ok()
+        );
         assert!(Cli::try_parse_from(["okena", "skill", "show"]).is_ok());
         assert!(Cli::try_parse_from(["okena", "skill", "install", "--project"]).is_ok());
         assert!(Cli::try_parse_from(["okena", "settings", "show"]).is_ok());
@@ -173,6 +173,22 @@ pub fn resolve_terminal(state: &StateResponse, filter: &str) -> Result<(String,
     Err(format!("Terminal not found: {filter}"))
 }
 
+/// Every address [`resolve_terminal`] accepts for a named or indexed terminal,
+/// as `(address, terminal name)`: `<project>:<index>` for each terminal plus
+/// `<project>/<name>` for named ones. Used for shell completion.
+pub fn terminal_addresses(state: &StateResponse) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    for project in &state.projects {
+        for (index, entry) in project_terminals(project).into_iter().enumerate() {
+            out.push((format!("{}:{index}", project.name), entry.name.clone()));
+            if project.terminal_names.contains_key(&entry.terminal_id) {
+                out.push((format!("{}/{}", project.name, entry.name), entry.name));
+            }
+        }
+    }
+    out
+}
+
 fn terminal_names_list(project: &ApiProject) -> String {
     let mut names: Vec<&str> = project
         .terminal_names
@@ -361,6 +377,7 @@ mod tests {
             folders: vec![],
             windows,
             hooks: Vec::new(),
+            shares: Vec::new(),
         }
     }
 
@@ -505,4 +522,18 @@ mod tests {
         assert!(project_terminals(&empty).is_empty());
         assert_eq!(first_terminal_id(&empty), None);
     }
+
+    #[test]
+    fn terminal_addresses_resolve_back() {
+        let p = project("p1", "Proj", "/tmp/p1", Some(nested_layout()));
+        let state = state_with(vec![p], vec![]);
+        let addresses = terminal_addresses(&state);
+        // Four indexed addresses, plus names for t1..t3 (t4 is unnamed).
+        assert_eq!(addresses.len(), 7);
+        assert!(addresses.contains(&("Proj:3".to_string(), "t4".to_string())));
+        assert!(addresses.contains(&("Proj/logs".to_string(), "logs".to_string())));
+        for (address, _) in &addresses {
+            assert!(resolve_terminal(&state, address).is_ok(), "{address}");
+        }
+    }
 }
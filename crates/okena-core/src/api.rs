@@ -1,13 +1,16 @@
+use crate::hooks::HookSteps;
 use crate::keys::SpecialKey;
 use crate::shell::ShellType;
 use crate::theme::FolderColor;
 use crate::types::{DiffMode, SplitDirection};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 // ── API request/response types ──────────────────────────────────────────────
 
 /// GET /health response
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct HealthResponse {
     pub status: String,
     pub version: String,
@@ -15,7 +18,7 @@ pub struct HealthResponse {
 }
 
 /// Lightweight system metrics for remote status surfaces.
-#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ApiSystemStats {
     pub cpu_usage: f32,
     pub memory_used_bytes: u64,
@@ -23,7 +26,7 @@ pub struct ApiSystemStats {
 }
 
 /// GET /v1/state response
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
 pub struct StateResponse {
     pub state_version: u64,
     pub projects: Vec<ApiProject>,
@@ -41,10 +44,53 @@ pub struct StateResponse {
     /// older servers (which omit the field) deserializable.
     #[serde(default)]
     pub hooks: Vec<ApiHookExecution>,
+    /// Live view-only share links. Only full-access tokens see them; the
+    /// server fills them in on top of the daemon's snapshot.
+    #[serde(default)]
+    pub shares: Vec<ApiShare>,
+}
+
+/// A view-only share link into one project or one terminal
+/// (`POST /v1/shares`).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ApiShare {
+    pub id: String,
+    pub project_id: String,
+    /// The one terminal shared; `None` shares the whole project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminal_id: Option<String>,
+    /// Unix seconds after which the link stops working.
+    pub expires_at: u64,
+    /// Spectators connected right now.
+    #[serde(default)]
+    pub viewers: u32,
+}
+
+/// `POST /v1/shares` body.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ApiShareRequest {
+    pub project_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminal_id: Option<String>,
+    /// Lifetime in seconds; the server's default when omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_secs: Option<u64>,
+}
+
+/// `POST /v1/shares` response.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ApiShareCreated {
+    pub share: ApiShare,
+    /// The spectator's credential. Shown once, like a pairing token.
+    pub token: String,
+    /// Web client path that opens the share, token included.
+    pub path: String,
+    /// `path` on the address viewers on the LAN can reach.
+    pub url: String,
 }
 
 /// OS window bounds in screen pixels.
-#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ApiWindowBounds {
     pub x: f32,
     pub y: f32,
@@ -54,7 +100,7 @@ pub struct ApiWindowBounds {
 
 /// One open OS window onto the shared workspace. Multi-window state is
 /// exposed so remote/CLI clients can see what the user actually sees.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ApiWindow {
     /// "main" for the main window, or the extra window's UUID string.
     pub id: String,
@@ -86,7 +132,7 @@ pub struct ApiWindow {
 }
 
 /// PR state from GitHub
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum PrState {
     Open,
     Merged,
@@ -107,7 +153,7 @@ impl PrState {
 }
 
 /// Overall CI check rollup status
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum CiStatus {
     Success,
     Failure,
@@ -129,7 +175,7 @@ impl CiStatus {
 }
 
 /// A single CI check / status entry as returned by `gh pr checks`.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct CiCheck {
     /// Display name (e.g. "Lint", "Test (ubuntu-latest)").
     pub name: String,
@@ -171,7 +217,7 @@ impl CiCheck {
 }
 
 /// Summary of CI check results
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct CiCheckSummary {
     pub status: CiStatus,
     pub passed: usize,
@@ -201,7 +247,7 @@ impl CiCheckSummary {
 }
 
 /// Pull request info
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct PrInfo {
     pub url: String,
     pub state: PrState,
@@ -214,7 +260,7 @@ pub struct PrInfo {
 }
 
 /// Open pull request offered as a worktree source.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct WorktreePullRequest {
     pub number: u32,
     pub title: String,
@@ -222,7 +268,7 @@ pub struct WorktreePullRequest {
 }
 
 /// Daemon-resolved worktree paths for the worktree management popover.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct ApiWorktreeEntry {
     pub worktree_path: String,
     pub project_path: String,
@@ -230,7 +276,7 @@ pub struct ApiWorktreeEntry {
     pub is_main: bool,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ApiGitStatus {
     pub branch: Option<String>,
     pub lines_added: usize,
@@ -275,7 +321,7 @@ pub struct ApiGitStatus {
 /// Deliberately omits the local-only `Toast` fields: `created: Instant` and
 /// `ttl: Duration` are not serde-serializable as-is, so the TTL travels as
 /// `ttl_ms` and the client stamps a fresh `created` on receipt.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct ApiToast {
     pub id: String,
     /// One of "success" | "error" | "warning" | "info".
@@ -293,7 +339,7 @@ pub struct ApiToast {
 /// A clickable button on a wire toast. `id` is opaque (the client decodes it,
 /// e.g. `soft_close_undo:<project>:<terminal>`); `style` is one of
 /// "default" | "primary" | "danger".
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct ApiToastAction {
     pub id: String,
     pub label: String,
@@ -304,7 +350,7 @@ pub struct ApiToastAction {
 /// `FocusTerminal` action succeeds on the daemon. Unlike workspace state, this
 /// is intentionally transient: connected desktop clients focus and raise the
 /// requested pane once without repeatedly stealing focus on later state syncs.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct ApiTerminalFocusRequest {
     pub project_id: String,
     pub terminal_id: String,
@@ -312,7 +358,76 @@ pub struct ApiTerminalFocusRequest {
     pub window: Option<String>,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+/// `POST /v1/terminals/{terminal_id}/wait-for` request body.
+///
+/// The regex runs over the terminal's ANSI-stripped output as the server sees
+/// it, multi-line (`^`/`$` anchor at line breaks). Unless `since_now` is set,
+/// the visible screen at request time is searched first, so a prompt that is
+/// already showing matches immediately.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ApiWaitForRequest {
+    pub regex: String,
+    /// How long to wait before answering `408`. Defaults to 30 s server-side.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Only match output produced after the request arrived.
+    #[serde(default)]
+    pub since_now: bool,
+}
+
+/// A `wait-for` match. `groups` holds the numbered capture groups (group 0,
+/// the whole match, is `matched`); unmatched optional groups are `null`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ApiOutputMatch {
+    pub matched: String,
+    #[serde(default)]
+    pub groups: Vec<Option<String>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub named: BTreeMap<String, String>,
+}
+
+/// Result of [`ActionRequest::ReadContent`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ApiReadContent {
+    pub content: String,
+    /// Output position of this read; pass it back as `since` to continue.
+    #[serde(default)]
+    pub sequence: u64,
+    /// `since` could not be honored in full, so some earlier output is
+    /// missing from `content`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+/// How an [`ActionRequest::ExecInTerminal`] ran.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiExecMode {
+    /// Typed into the pane's shell; output cut from its OSC 133 marks.
+    Shell,
+    /// Hidden one-shot process in the terminal's cwd and environment.
+    Process,
+}
+
+/// Result of [`ActionRequest::ExecInTerminal`].
+///
+/// In `shell` mode the PTY merges both streams into `stdout` and `stderr` is
+/// empty. `exit_code` is `None` when the shell's `D` mark carried no code or
+/// the process was killed by a signal.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ApiExecResult {
+    pub stdout: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub stderr: String,
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    pub mode: ApiExecMode,
+    /// Older output was dropped to keep the reply bounded.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ApiProject {
     pub id: String,
     pub name: String,
@@ -381,7 +496,7 @@ pub struct ApiProject {
 /// Wire mirror of `okena_state::HookTerminalStatus` (which can't be referenced
 /// from `okena-core` without a dependency cycle). Converted via
 /// `HookTerminalStatus::{to_api,from_api}` in `okena-state`.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "state", rename_all = "snake_case")]
 pub enum ApiHookTerminalStatus {
     Running,
@@ -392,7 +507,7 @@ pub enum ApiHookTerminalStatus {
 /// Wire mirror of a hook terminal entry shown in the service panel. The
 /// terminal id (the map key in the domain type) is inlined here as `terminal_id`
 /// so the wire form is a flat list.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ApiHookTerminalEntry {
     pub terminal_id: String,
     pub label: String,
@@ -404,7 +519,7 @@ pub struct ApiHookTerminalEntry {
 
 /// Wire mirror of `okena_hooks::HookStatus`. Durations are carried as whole
 /// milliseconds (the domain `Duration`/`Instant` types don't cross the wire).
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "state", rename_all = "snake_case")]
 pub enum ApiHookStatus {
     Running,
@@ -419,6 +534,12 @@ pub enum ApiHookStatus {
     SpawnError {
         message: String,
     },
+    Cancelled {
+        duration_ms: u64,
+    },
+    TimedOut {
+        duration_ms: u64,
+    },
 }
 
 /// Wire mirror of `okena_hooks::HookExecution` — one row in the hook log.
@@ -427,7 +548,7 @@ pub enum ApiHookStatus {
 /// cannot be serialized; the client reconstructs a fresh `Instant` on ingest
 /// (only the still-`Running` elapsed readout depends on it, and that
 /// self-corrects once the hook finishes and carries a concrete duration).
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ApiHookExecution {
     pub id: u64,
     pub hook_type: String,
@@ -438,8 +559,37 @@ pub struct ApiHookExecution {
     pub terminal_id: Option<String>,
 }
 
+/// One persisted run of a headless hook, as kept in the daemon's hook log
+/// directory. Unlike [`ApiHookExecution`] it survives daemon restarts and
+/// carries the captured output. Listings leave `stdout`/`stderr` empty.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ApiHookRun {
+    pub id: u64,
+    pub hook_type: String,
+    pub command: String,
+    pub project_name: String,
+    /// Wall-clock start, in milliseconds since the Unix epoch.
+    pub started_at_ms: u64,
+    pub status: ApiHookStatus,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub stdout: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub stderr: String,
+}
+
+/// Line diff between the captured output of two hook runs. Each diff is in
+/// unified format (`@@` hunks, ` `/`-`/`+` line prefixes) and empty when the
+/// stream is identical in both runs.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ApiHookRunDiff {
+    pub base: ApiHookRun,
+    pub run: ApiHookRun,
+    pub stdout_diff: String,
+    pub stderr_diff: String,
+}
+
 /// Wire mirror of `okena_state::ProjectHooks`.
-#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ApiProjectHooks {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub on_open: Option<String>,
@@ -448,7 +598,7 @@ pub struct ApiProjectHooks {
 }
 
 /// Wire mirror of `okena_state::TerminalHooks`.
-#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ApiTerminalHooks {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub on_create: Option<String>,
@@ -456,10 +606,36 @@ pub struct ApiTerminalHooks {
     pub on_close: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub shell_wrapper: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_command_finished: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_bell: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_notification: Option<String>,
+}
+
+/// Wire mirror of `okena_state::ServiceHooks`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ApiServiceHooks {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_crash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_ready: Option<String>,
+}
+
+/// Wire mirror of `okena_state::GitHooks`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ApiGitHooks {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_branch_change: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_ci_status_change: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_pr_state_change: Option<String>,
 }
 
 /// Wire mirror of `okena_state::WorktreeHooks`.
-#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ApiWorktreeHooks {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub on_create: Option<String>,
@@ -483,7 +659,7 @@ pub struct ApiWorktreeHooks {
 /// overrides. Carried so daemon-client clients show and edit the *real*
 /// per-project hooks (the daemon applies them when it spawns PTYs). Converted
 /// via `HooksConfig::{to_api,from_api}` in `okena-state`.
-#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ApiHooksConfig {
     #[serde(default, skip_serializing_if = "is_default")]
     pub project: ApiProjectHooks,
@@ -491,6 +667,13 @@ pub struct ApiHooksConfig {
     pub terminal: ApiTerminalHooks,
     #[serde(default, skip_serializing_if = "is_default")]
     pub worktree: ApiWorktreeHooks,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub service: ApiServiceHooks,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub git: ApiGitHooks,
+    /// Step-list hooks keyed by `group.hook`; see `okena_state::HooksConfig::steps`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub steps: BTreeMap<String, HookSteps>,
 }
 
 impl ApiHooksConfig {
@@ -503,14 +686,14 @@ fn is_default<T: Default + PartialEq>(v: &T) -> bool {
     *v == T::default()
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ApiWorktreeMetadata {
     pub parent_project_id: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub color_override: Option<FolderColor>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ApiServiceInfo {
     pub name: String,
     pub status: String, // "running", "stopped", "crashed", "starting", "restarting"
@@ -526,13 +709,18 @@ pub struct ApiServiceInfo {
     /// Docker service not listed in okena.yaml filter
     #[serde(default)]
     pub is_extra: bool,
+    /// Path (relative to the service cwd) whose change triggered the last
+    /// automatic restart: the last file changed in the burst, with a count of
+    /// the others (`src/a.rs and 12 more`). Cleared by a manual stop/restart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart_trigger: Option<String>,
 }
 
 fn default_service_kind() -> String {
     "okena".to_string()
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ApiFolder {
     pub id: String,
     pub name: String,
@@ -541,7 +729,7 @@ pub struct ApiFolder {
     pub folder_color: FolderColor,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum ApiLayoutNode {
     Terminal {
@@ -566,14 +754,14 @@ pub enum ApiLayoutNode {
     },
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ApiFullscreen {
     pub project_id: String,
     pub terminal_id: String,
 }
 
 /// The kind of a path resolved on the daemon filesystem.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ResolvedPathKind {
     File,
@@ -581,14 +769,14 @@ pub enum ResolvedPathKind {
 }
 
 /// One daemon-native ancestor used by the file browser breadcrumb.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct PathBreadcrumb {
     pub canonical_path: String,
     pub label: String,
 }
 
 /// A path resolved on the daemon that owns the filesystem.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct ResolvedPath {
     pub canonical_path: String,
     pub name: String,
@@ -603,7 +791,7 @@ pub struct ResolvedPath {
 }
 
 /// Identifies a daemon-side file for streaming downloads.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "source", rename_all = "snake_case", deny_unknown_fields)]
 pub enum FileDownloadRequest {
     Project {
@@ -620,8 +808,63 @@ pub enum FileDownloadRequest {
     },
 }
 
+/// Query string of `POST /v1/upload`: where the request body is written.
+///
+/// `relative_path` names the new file, relative to project `project_id` or to
+/// the scoped directory `root`; exactly one of the two must be set. The
+/// parent directory must already exist.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FileUploadRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    pub relative_path: String,
+    #[serde(default)]
+    pub conflict: UploadConflict,
+}
+
+/// What an upload does when its target file already exists.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadConflict {
+    /// Refuse with 409 Conflict.
+    #[default]
+    Fail,
+    /// Write `name (1).ext`, `name (2).ext`, ... instead.
+    Rename,
+    /// Replace the existing file once the upload completes.
+    Overwrite,
+}
+
+/// `POST /v1/upload` response: where the file landed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FileUploadResponse {
+    /// Daemon-native path of the written file.
+    pub path: String,
+    /// Final name, which differs from the requested one after a rename.
+    pub name: String,
+    pub size: u64,
+}
+
+/// Where `CreateTerminal` puts the new terminal.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum TerminalPlacement {
+    /// Split `terminal_id`'s pane (its whole tab group when it is a tab).
+    Split {
+        terminal_id: String,
+        direction: SplitDirection,
+    },
+    /// A new tab next to `terminal_id`: in its tab group, or a new group.
+    Tab { terminal_id: String },
+    /// Appended to the project root and opened in its own window.
+    Detached,
+}
+
 /// POST /v1/actions request body (tagged enum)
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "action", rename_all = "snake_case", deny_unknown_fields)]
 pub enum ActionRequest {
     SendText {
@@ -674,8 +917,25 @@ pub enum ActionRequest {
     RecordProjectActivity {
         project_id: String,
     },
+    /// Read a terminal's text as [`ApiReadContent`]. With no options: the
+    /// visible screen as plain text.
     ReadContent {
         terminal_id: String,
+        /// The last `n` lines, reaching back into scrollback.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        lines: Option<usize>,
+        /// All of scrollback plus the screen. Wins over `lines`.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        all: bool,
+        /// Keep colors and attributes as SGR escapes.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        ansi: bool,
+        /// Only output after the read that returned this `sequence`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        since: Option<u64>,
+        /// Leave out the shell prompt the cursor sits on.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        strip_prompt: bool,
     },
     /// Capture a terminal's full scrollback buffer (tmux `capture-pane`). The
     /// daemon writes it to a temp file, reads it back, and returns the content
@@ -684,13 +944,51 @@ pub enum ActionRequest {
     ExportBuffer {
         terminal_id: String,
     },
+    /// Run a command in a terminal's context and return [`ApiExecResult`].
+    ///
+    /// With `mirror`, a pane whose shell reports OSC 133 marks and sits at a
+    /// prompt runs the command visibly; the output and exit code come from the
+    /// `C`..`D` marks. Otherwise (and always without `mirror`) it runs as a
+    /// hidden one-shot process in the terminal's cwd and environment.
+    ExecInTerminal {
+        terminal_id: String,
+        command: String,
+        #[serde(default)]
+        mirror: bool,
+        /// Give up after this long. Defaults to 5 minutes.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout_ms: Option<u64>,
+    },
     Resize {
         terminal_id: String,
         cols: u16,
         rows: u16,
     },
+    /// Create a terminal. Every field but `project_id` is optional; the reply
+    /// is `{"terminal_id": ..., "terminal_ids": [...]}`.
     CreateTerminal {
         project_id: String,
+        /// Shell to launch instead of the project/global default.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        shell: Option<ShellType>,
+        /// Working directory. Defaults to the cwd of the terminal it is placed
+        /// next to (or the focused one in this project), else the project path.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cwd: Option<String>,
+        /// Typed into the shell once it starts, like `RunCommand`, so the
+        /// pane stays usable after the command exits.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        command: Option<String>,
+        /// Extra environment for the PTY, on top of the hook environment.
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        env: BTreeMap<String, String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        minimized: bool,
+        /// Where the terminal goes. None appends it to the project root.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        placement: Option<TerminalPlacement>,
     },
     UpdateSplitSizes {
         project_id: String,
@@ -870,6 +1168,26 @@ pub enum ActionRequest {
         project_id: String,
         service_name: String,
     },
+    /// Rebuild a Docker Compose service's image and recreate its container
+    /// (`docker compose up -d --build`). Errors for non-Docker services.
+    RebuildService {
+        project_id: String,
+        service_name: String,
+    },
+    /// Pull the latest image for a Docker Compose service without restarting it.
+    PullService {
+        project_id: String,
+        service_name: String,
+    },
+    /// Open a new terminal with an interactive shell inside a Docker Compose
+    /// service's container. `shell` defaults to bash, falling back to sh.
+    /// Returns `{"terminal_ids": [...]}` like `CreateTerminal`.
+    ExecServiceShell {
+        project_id: String,
+        service_name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        shell: Option<String>,
+    },
     StartAllServices {
         project_id: String,
     },
@@ -905,6 +1223,27 @@ pub enum ActionRequest {
         project_id: String,
         terminal_id: String,
     },
+    /// Kill a running hook execution (headless process or hook terminal) by
+    /// its hook-monitor id. The execution is recorded as cancelled.
+    CancelHook {
+        execution_id: u64,
+    },
+    /// List persisted headless hook runs, newest first (output omitted).
+    ListHookRuns {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hook_type: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<usize>,
+    },
+    /// Fetch one persisted hook run including its captured output.
+    GetHookRun {
+        run_id: u64,
+    },
+    /// Diff the captured output of two persisted runs.
+    DiffHookRuns {
+        base_run_id: u64,
+        run_id: u64,
+    },
     ListFiles {
         project_id: String,
         #[serde(default)]
@@ -1263,21 +1602,21 @@ fn default_pull_request_limit() -> usize {
 }
 
 /// POST /v1/pair request
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct PairRequest {
     pub code: String,
 }
 
 /// POST /v1/pair response
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct PairResponse {
     pub token: String,
     pub expires_in: u64,
 }
 
 /// Generic error response
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct ErrorResponse {
     pub error: String,
 }
@@ -1311,6 +1650,7 @@ impl ApiLayoutNode {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hooks::{HookMergeMode, HookStep};
 
     #[test]
     fn state_response_round_trip() {
@@ -1373,6 +1713,19 @@ mod tests {
                         ..Default::default()
                     },
                     worktree: ApiWorktreeHooks::default(),
+                    service: ApiServiceHooks {
+                        on_crash: Some("notify-send crashed".into()),
+                        on_ready: None,
+                    },
+                    git: ApiGitHooks::default(),
+                    steps: BTreeMap::from([(
+                        "worktree.on_create".into(),
+                        HookSteps {
+                            merge: HookMergeMode::Append,
+                            timeout: None,
+                            steps: vec![HookStep::command("npm ci")],
+                        },
+                    )]),
                 },
                 is_creating: false,
                 is_closing: false,
@@ -1408,6 +1761,7 @@ mod tests {
                 sidebar_open: Some(true),
             }],
             hooks: Vec::new(),
+            shares: Vec::new(),
         };
         let json = serde_json::to_string(&resp).unwrap();
         let parsed: StateResponse = serde_json::from_str(&json).unwrap();
@@ -1437,6 +1791,11 @@ mod tests {
             parsed.projects[0].hooks.worktree,
             ApiWorktreeHooks::default()
         );
+        assert_eq!(
+            parsed.projects[0].hooks.service.on_crash.as_deref(),
+            Some("notify-send crashed")
+        );
+        assert_eq!(parsed.projects[0].hooks.git, ApiGitHooks::default());
         assert!(parsed.fullscreen_terminal.is_none());
         assert_eq!(parsed.project_order, vec!["folder1", "p1"]);
         assert_eq!(parsed.folders.len(), 1);
@@ -1581,6 +1940,25 @@ mod tests {
             },
             ActionRequest::ReadContent {
                 terminal_id: "t1".into(),
+                lines: None,
+                all: false,
+                ansi: false,
+                since: None,
+                strip_prompt: false,
+            },
+            ActionRequest::ReadContent {
+                terminal_id: "t1".into(),
+                lines: Some(200),
+                all: false,
+                ansi: true,
+                since: Some(42),
+                strip_prompt: true,
+            },
+            ActionRequest::ExecInTerminal {
+                terminal_id: "t1".into(),
+                command: "cargo test".into(),
+                mirror: true,
+                timeout_ms: Some(60_000),
             },
             ActionRequest::Resize {
                 terminal_id: "t1".into(),
@@ -1589,6 +1967,52 @@ mod tests {
             },
             ActionRequest::CreateTerminal {
                 project_id: "p1".into(),
+                shell: None,
+                cwd: None,
+                command: None,
+                env: BTreeMap::new(),
+                name: None,
+                minimized: false,
+                placement: None,
+            },
+            ActionRequest::CreateTerminal {
+                project_id: "p1".into(),
+                shell: Some(ShellType::DockerExec {
+                    compose_file: "/p/docker-compose.yml".into(),
+                    service: "web".into(),
+                    shell: None,
+                }),
+                cwd: None,
+                command: None,
+                env: BTreeMap::new(),
+                name: None,
+                minimized: false,
+                placement: Some(TerminalPlacement::Detached),
+            },
+            ActionRequest::CreateTerminal {
+                project_id: "p1".into(),
+                shell: None,
+                cwd: Some("/p/src".into()),
+                command: Some("npm run dev".into()),
+                env: BTreeMap::from([("PORT".to_string(), "3000".to_string())]),
+                name: Some("dev".into()),
+                minimized: true,
+                placement: Some(TerminalPlacement::Split {
+                    terminal_id: "t1".into(),
+                    direction: SplitDirection::Horizontal,
+                }),
+            },
+            ActionRequest::CreateTerminal {
+                project_id: "p1".into(),
+                shell: None,
+                cwd: None,
+                command: None,
+                env: BTreeMap::new(),
+                name: None,
+                minimized: false,
+                placement: Some(TerminalPlacement::Tab {
+                    terminal_id: "t1".into(),
+                }),
             },
             ActionRequest::UpdateSplitSizes {
                 project_id: "p1".into(),
@@ -1723,6 +2147,19 @@ mod tests {
                 project_id: "p1".into(),
                 service_name: "vite".into(),
             },
+            ActionRequest::RebuildService {
+                project_id: "p1".into(),
+                service_name: "web".into(),
+            },
+            ActionRequest::PullService {
+                project_id: "p1".into(),
+                service_name: "web".into(),
+            },
+            ActionRequest::ExecServiceShell {
+                project_id: "p1".into(),
+                service_name: "web".into(),
+                shell: Some("zsh".into()),
+            },
             ActionRequest::StartAllServices {
                 project_id: "p1".into(),
             },
@@ -1806,6 +2243,16 @@ mod tests {
                 project_id: "p1".into(),
                 terminal_id: "h1".into(),
             },
+            ActionRequest::CancelHook { execution_id: 7 },
+            ActionRequest::ListHookRuns {
+                hook_type: Some("pre_merge".into()),
+                limit: Some(20),
+            },
+            ActionRequest::GetHookRun { run_id: 7 },
+            ActionRequest::DiffHookRuns {
+                base_run_id: 6,
+                run_id: 7,
+            },
             ActionRequest::CreateFolder {
                 name: "My Folder".into(),
             },
@@ -1941,6 +2388,7 @@ mod tests {
             exit_code: None,
             kind: "okena".into(),
             is_extra: false,
+            restart_trigger: Some("main.go".into()),
         };
         let json = serde_json::to_string(&svc).unwrap();
         let parsed: ApiServiceInfo = serde_json::from_str(&json).unwrap();
@@ -2014,6 +2462,13 @@ mod tests {
 
         let create = ActionRequest::CreateTerminal {
             project_id: "p1".into(),
+            shell: None,
+            cwd: None,
+            command: None,
+            env: BTreeMap::new(),
+            name: None,
+            minimized: false,
+            placement: None,
         };
         assert_eq!(create.target_window(), None);
 
@@ -0,0 +1,436 @@
+//! Append-only log of what paired remote tokens did.
+//!
+//! The server writes one JSON line per mutating action, stream input burst
+//! and token change to [`AUDIT_FILE`] in the profile's config dir. Entries
+//! name the token and client but never carry typed text, commands or file
+//! contents: [`describe_action`] keeps ids and names and reduces everything
+//! else to a length.
+
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::api::ActionRequest;
+use crate::scope::{Scope, classify};
+
+/// File name of the audit log inside the profile's config dir.
+pub const AUDIT_FILE: &str = "remote_audit.jsonl";
+
+/// Past this size the log is moved to `<file>.1` and a new file is started.
+pub const ROTATE_AT_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Rotated files kept next to the live one (`<file>.1` is the newest). The
+/// oldest is deleted when another rotation needs its place.
+pub const ROTATED_FILES: usize = 4;
+
+/// Entries returned when a query sets no limit.
+pub const DEFAULT_QUERY_LIMIT: usize = 200;
+
+/// Fields whose values never reach the log, only their size.
+const REDACTED_FIELDS: &[&str] = &["text", "data", "command", "env", "url"];
+
+/// Longest string value kept verbatim in a summary.
+const MAX_VALUE_CHARS: usize = 80;
+
+/// One audited request.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AuditEntry {
+    /// Unix seconds.
+    pub at: u64,
+    pub token_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_name: Option<String>,
+    /// Client IP address.
+    pub client: String,
+    /// The action tag (`send_text`, `create_terminal`, …) or a route event:
+    /// `batch`, `ws_input`, `paste_image`, `paste_file`, `pair` or
+    /// `revoke_token`.
+    pub kind: String,
+    /// Redacted description of the request.
+    pub summary: String,
+    /// The token's grant refused the request.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub denied: bool,
+}
+
+/// `GET /v1/audit` query. Every set field must match.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct AuditQuery {
+    /// Token id, or device name (case-insensitive).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Unix seconds; older entries are skipped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<u64>,
+    /// Keep the newest N matches (default [`DEFAULT_QUERY_LIMIT`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        let token_matches = self.token.as_deref().is_none_or(|token| {
+            entry.token_id == token
+                || entry
+                    .token_name
+                    .as_deref()
+                    .is_some_and(|name| name.eq_ignore_ascii_case(token))
+        });
+        token_matches
+            && self.kind.as_deref().is_none_or(|kind| entry.kind == kind)
+            && self.since.is_none_or(|since| entry.at >= since)
+    }
+}
+
+/// The log file. Appends are serialized so concurrent requests never
+/// interleave lines.
+pub struct AuditLog {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, entry: &AuditEntry) -> io::Result<()> {
+        let line = serde_json::to_string(entry).map_err(io::Error::other)?;
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        if std::fs::metadata(&self.path).is_ok_and(|m| m.len() >= ROTATE_AT_BYTES) {
+            self.rotate()?;
+        }
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&self.path)?;
+        writeln!(file, "{line}")
+    }
+
+    /// Matching entries, oldest first, limited to the newest
+    /// `query.limit`. Lines that don't parse are skipped. The files are read
+    /// line by line and only the newest matches are held.
+    pub fn query(&self, query: &AuditQuery) -> Vec<AuditEntry> {
+        let limit = query.limit.unwrap_or(DEFAULT_QUERY_LIMIT);
+        if limit == 0 {
+            return Vec::new();
+        }
+        let mut entries = VecDeque::with_capacity(limit.min(DEFAULT_QUERY_LIMIT));
+        let oldest_first = (1..=ROTATED_FILES)
+            .rev()
+            .map(|generation| self.rotated_path(generation))
+            .chain([self.path.clone()]);
+        for path in oldest_first {
+            let Ok(file) = std::fs::File::open(&path) else {
+                continue;
+            };
+            let matches = BufReader::new(file)
+                .lines()
+                .map_while(Result::ok)
+                .filter_map(|line| serde_json::from_str::<AuditEntry>(&line).ok())
+                .filter(|entry| query.matches(entry));
+            for entry in matches {
+                if entries.len() == limit {
+                    entries.pop_front();
+                }
+                entries.push_back(entry);
+            }
+        }
+        entries.into()
+    }
+
+    /// Shift `<file>.N` to `<file>.N+1`, dropping the last one, and move the
+    /// live file to `<file>.1`.
+    fn rotate(&self) -> io::Result<()> {
+        for generation in (1..ROTATED_FILES).rev() {
+            let from = self.rotated_path(generation);
+            if from.exists() {
+                std::fs::rename(&from, self.rotated_path(generation + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.rotated_path(1))
+    }
+
+    fn rotated_path(&self, generation: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{generation}"));
+        PathBuf::from(path)
+    }
+}
+
+/// Unix seconds now.
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Whether `action` belongs in the log: everything beyond the `read` scope.
+pub fn is_audited(action: &ActionRequest) -> bool {
+    classify(action).0 != Scope::Read
+}
+
+/// `(kind, summary)` for an action: its tag and its fields as `key=value`,
+/// with [`REDACTED_FIELDS`] reduced to their size.
+pub fn describe_action(action: &ActionRequest) -> (String, String) {
+    let Ok(Value::Object(fields)) = serde_json::to_value(action) else {
+        return ("unknown".to_string(), String::new());
+    };
+    let kind = fields
+        .get("action")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown")
+        .to_string();
+    let summary = fields
+        .iter()
+        .filter(|(key, value)| *key != "action" && !value.is_null())
+        .map(|(key, value)| format!("{key}={}", describe_value(key, value)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    (kind, summary)
+}
+
+fn describe_value(key: &str, value: &Value) -> String {
+    let redacted = REDACTED_FIELDS.contains(&key);
+    match value {
+        Value::String(s) if redacted => format!("<{} chars>", s.chars().count()),
+        Value::Array(items) if redacted => format!("<{} values>", items.len()),
+        Value::Object(fields) if redacted => format!("<{} entries>", fields.len()),
+        _ if redacted => "<redacted>".to_string(),
+        Value::String(s) if s.chars().count() > MAX_VALUE_CHARS => {
+            let kept: String = s.chars().take(MAX_VALUE_CHARS).collect();
+            format!("{kept}…")
+        }
+        Value::String(s) => s.clone(),
+        Value::Array(items) => format!("[{} values]", items.len()),
+        Value::Object(_) => "{…}".to_string(),
+        Value::Null | Value::Bool(_) | Value::Number(_) => value.to_string(),
+    }
+}
+
+/// A quiet gap this long ends an input burst.
+pub const INPUT_BURST_GAP: Duration = Duration::from_secs(5);
+
+/// Keystrokes sent to one terminal without a [`INPUT_BURST_GAP`] pause.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputBurst {
+    pub terminal_id: String,
+    pub bytes: usize,
+    pub messages: usize,
+    started: Instant,
+    last: Instant,
+}
+
+impl InputBurst {
+    pub fn summary(&self) -> String {
+        let secs = self.last.duration_since(self.started).as_secs();
+        format!(
+            "terminal_id={} {} bytes in {} messages over {secs}s",
+            self.terminal_id, self.bytes, self.messages
+        )
+    }
+}
+
+/// Coalesces a stream connection's input so the log gets one entry per burst
+/// instead of one per keystroke.
+#[derive(Debug, Default)]
+pub struct InputBursts {
+    current: Option<InputBurst>,
+}
+
+impl InputBursts {
+    /// Count `bytes` of input to `terminal_id`. Returns the previous burst
+    /// when this input starts a new one (other terminal, or after a gap).
+    pub fn push(&mut self, terminal_id: &str, bytes: usize, now: Instant) -> Option<InputBurst> {
+        if let Some(burst) = &mut self.current
+            && burst.terminal_id == terminal_id
+            && now.duration_since(burst.last) < INPUT_BURST_GAP
+        {
+            burst.bytes += bytes;
+            burst.messages += 1;
+            burst.last = now;
+            return None;
+        }
+        self.current.replace(InputBurst {
+            terminal_id: terminal_id.to_string(),
+            bytes,
+            messages: 1,
+            started: now,
+            last: now,
+        })
+    }
+
+    /// The current burst, once it has been quiet for [`INPUT_BURST_GAP`].
+    pub fn take_idle(&mut self, now: Instant) -> Option<InputBurst> {
+        self.current
+            .take_if(|burst| now.duration_since(burst.last) >= INPUT_BURST_GAP)
+    }
+
+    /// The current burst, e.g. when the connection closes.
+    pub fn finish(&mut self) -> Option<InputBurst> {
+        self.current.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn entry(at: u64, token_id: &str, kind: &str) -> AuditEntry {
+        AuditEntry {
+            at,
+            token_id: token_id.to_string(),
+            token_name: Some(format!("{token_id}-phone")),
+            client: "10.0.0.2".to_string(),
+            kind: kind.to_string(),
+            summary: String::new(),
+            denied: false,
+        }
+    }
+
+    #[test]
+    fn summaries_keep_ids_and_redact_input() {
+        let (kind, summary) = describe_action(&ActionRequest::SendText {
+            terminal_id: "t1".into(),
+            text: "hunter2\n".into(),
+        });
+        assert_eq!(kind, "send_text");
+        assert_eq!(summary, "terminal_id=t1 text=<8 chars>");
+
+        let (kind, summary) = describe_action(&ActionRequest::CreateTerminal {
+            project_id: "p1".into(),
+            shell: None,
+            cwd: None,
+            command: Some("psql postgres://user:secret@db".into()),
+            env: BTreeMap::from([("TOKEN".into(), "abc".into())]),
+            name: Some("db".into()),
+            minimized: false,
+            placement: None,
+        });
+        assert_eq!(kind, "create_terminal");
+        assert!(summary.contains("project_id=p1"));
+        assert!(summary.contains("name=db"));
+        assert!(summary.contains("command=<30 chars>"));
+        assert!(summary.contains("env=<1 entries>"));
+        assert!(!summary.contains("secret") && !summary.contains("abc"));
+    }
+
+    #[test]
+    fn only_actions_beyond_read_are_audited() {
+        assert!(is_audited(&ActionRequest::SendText {
+            terminal_id: "t1".into(),
+            text: "ls".into(),
+        }));
+        assert!(!is_audited(&ActionRequest::GetThemes));
+    }
+
+    #[test]
+    fn log_appends_and_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join(AUDIT_FILE));
+        log.append(&entry(10, "a", "send_text")).unwrap();
+        log.append(&entry(20, "b", "git_commit")).unwrap();
+        log.append(&entry(30, "a", "ws_input")).unwrap();
+
+        assert_eq!(log.query(&AuditQuery::default()).len(), 3);
+        let by_name = log.query(&AuditQuery {
+            token: Some("A-PHONE".into()),
+            ..AuditQuery::default()
+        });
+        assert_eq!(by_name.iter().map(|e| e.at).collect::<Vec<_>>(), [10, 30]);
+        let newest = log.query(&AuditQuery {
+            since: Some(15),
+            limit: Some(1),
+            ..AuditQuery::default()
+        });
+        assert_eq!(newest, [entry(30, "a", "ws_input")]);
+        let by_kind = log.query(&AuditQuery {
+            kind: Some("git_commit".into()),
+            ..AuditQuery::default()
+        });
+        assert_eq!(by_kind, [entry(20, "b", "git_commit")]);
+    }
+
+    #[test]
+    fn rotation_keeps_numbered_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join(AUDIT_FILE));
+        let newest = ROTATED_FILES as u64 + 1;
+        for at in 0..=newest {
+            log.append(&entry(at, "a", "send_text")).unwrap();
+            // Pad the live file to the limit so the next append rotates.
+            let file = OpenOptions::new().write(true).open(log.path()).unwrap();
+            file.set_len(ROTATE_AT_BYTES).unwrap();
+        }
+
+        assert!(log.rotated_path(ROTATED_FILES).exists());
+        assert!(!log.rotated_path(ROTATED_FILES + 1).exists());
+        // The first entry went out with the oldest file.
+        let kept = log.query(&AuditQuery::default());
+        assert_eq!(
+            kept.iter().map(|e| e.at).collect::<Vec<_>>(),
+            (1..=newest).collect::<Vec<_>>()
+        );
+        let last_two = log.query(&AuditQuery {
+            limit: Some(2),
+            ..AuditQuery::default()
+        });
+        assert_eq!(
+            last_two.iter().map(|e| e.at).collect::<Vec<_>>(),
+            [newest - 1, newest]
+        );
+    }
+
+    #[test]
+    fn input_coalesces_into_bursts() {
+        let start = Instant::now();
+        let mut bursts = InputBursts::default();
+        assert!(bursts.push("t1", 1, start).is_none());
+        assert!(
+            bursts
+                .push("t1", 2, start + Duration::from_secs(1))
+                .is_none()
+        );
+        assert!(bursts.take_idle(start + Duration::from_secs(2)).is_none());
+
+        // Another terminal ends the burst.
+        let done = bursts
+            .push("t2", 4, start + Duration::from_secs(3))
+            .unwrap();
+        assert_eq!(
+            (done.terminal_id.as_str(), done.bytes, done.messages),
+            ("t1", 3, 2)
+        );
+        assert_eq!(
+            done.summary(),
+            "terminal_id=t1 3 bytes in 2 messages over 1s"
+        );
+
+        // So does going quiet.
+        let idle = bursts.take_idle(start + Duration::from_secs(9)).unwrap();
+        assert_eq!(idle.terminal_id, "t2");
+        assert!(bursts.finish().is_none());
+    }
+}
@@ -0,0 +1,199 @@
+//! `POST /v1/actions/batch`: a list of actions applied in one daemon turn.
+//!
+//! Steps are raw JSON rather than [`ActionRequest`](crate::api::ActionRequest)s
+//! so a field can name an earlier step's result before it exists. A field set
+//! to `{"$ref": "N"}` or `{"$ref": "N.path"}` is replaced by step `N`'s result
+//! (0-based) or the value at the dot-separated `path` inside it, e.g.
+//! `{"$ref": "1.terminal_id"}` or `{"$ref": "0.terminal_ids.0"}`. Strings are
+//! never rewritten.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// POST /v1/actions/batch request body.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ActionBatchRequest {
+    pub actions: Vec<Value>,
+    /// On failure, restore the project layouts as they were before the batch
+    /// and close the terminals it created. Only layouts are restored, so steps
+    /// outside the `read` and `terminal:input` scopes are refused.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rollback: bool,
+}
+
+/// POST /v1/actions/batch response body.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct ApiBatchResult {
+    /// One entry per step that succeeded; `null` when a step has no result.
+    pub results: Vec<Value>,
+    /// The step that failed. Later steps did not run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failed_step: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rolled_back: bool,
+}
+
+/// Replace references in `step` with values from `results`, the results of
+/// the steps before it.
+pub fn resolve_references(step: &mut Value, results: &[Value]) -> Result<(), String> {
+    if let Some(reference) = as_reference(step) {
+        let (index, path) = parse_reference(reference)?;
+        *step = lookup(results, index, path)?;
+        return Ok(());
+    }
+    match step {
+        Value::Array(items) => items
+            .iter_mut()
+            .try_for_each(|item| resolve_references(item, results)),
+        Value::Object(fields) => fields
+            .values_mut()
+            .try_for_each(|value| resolve_references(value, results)),
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => Ok(()),
+    }
+}
+
+/// `step` with every reference replaced by its text, `"$ref:N.path"`, so it
+/// parses as an `ActionRequest` before the batch runs (scope checks, audit).
+/// An id field holding a reference then names no real id.
+pub fn references_as_text(step: &Value) -> Value {
+    if let Some(reference) = as_reference(step) {
+        return Value::String(format!("$ref:{reference}"));
+    }
+    match step {
+        Value::Array(items) => Value::Array(items.iter().map(references_as_text).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), references_as_text(value)))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+/// `{"$ref": "N.path"}` → `"N.path"`.
+fn as_reference(value: &Value) -> Option<&str> {
+    match value {
+        Value::Object(fields) if fields.len() == 1 => fields.get("$ref")?.as_str(),
+        _ => None,
+    }
+}
+
+/// `"N"` / `"N.path"` → `(N, path)`.
+fn parse_reference(reference: &str) -> Result<(usize, Option<&str>), String> {
+    let (index, path) = match reference.split_once('.') {
+        Some((index, path)) => (index, Some(path)),
+        None => (reference, None),
+    };
+    if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!(
+            "invalid $ref \"{reference}\" (expected a step number, e.g. \"0.terminal_id\")"
+        ));
+    }
+    let index = index
+        .parse()
+        .map_err(|_| format!("invalid $ref \"{reference}\""))?;
+    Ok((index, path))
+}
+
+fn lookup(results: &[Value], index: usize, path: Option<&str>) -> Result<Value, String> {
+    let mut value = results
+        .get(index)
+        .ok_or_else(|| format!("$ref {index} refers to a step that has not run"))?;
+    for segment in path.into_iter().flat_map(|p| p.split('.')) {
+        let next = match value {
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => value.get(segment),
+        };
+        value = next.ok_or_else(|| {
+            format!(
+                "$ref {index}.{} not found in step {index}'s result",
+                path.unwrap_or_default()
+            )
+        })?;
+    }
+    Ok(value.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn references_resolve_against_earlier_results() {
+        let results = vec![
+            json!({ "project_id": "p1" }),
+            json!({ "terminal_id": "t2", "terminal_ids": ["t2", "t3"] }),
+        ];
+        let mut step = json!({
+            "action": "split_terminal",
+            "project_id": { "$ref": "0.project_id" },
+            "placement": { "terminal_id": { "$ref": "1.terminal_ids.1" } },
+            "whole": { "$ref": "1" },
+        });
+        resolve_references(&mut step, &results).unwrap();
+        assert_eq!(step["project_id"], "p1");
+        assert_eq!(step["placement"]["terminal_id"], "t3");
+        assert_eq!(step["whole"], results[1]);
+    }
+
+    #[test]
+    fn strings_are_never_references() {
+        let results = vec![json!({ "terminal_id": "t1" })];
+        let mut step = json!({
+            "action": "send_text",
+            "terminal_id": "t1",
+            "text": "echo $1 $0.terminal_id $$1.50 $HOME",
+            "args": ["$1", "$0.terminal_id"],
+        });
+        let written = step.clone();
+        resolve_references(&mut step, &results).unwrap();
+        assert_eq!(step, written);
+    }
+
+    #[test]
+    fn bad_references_are_errors() {
+        let results = vec![json!({ "terminal_id": "t1" })];
+        assert!(resolve_references(&mut json!({ "$ref": "1.terminal_id" }), &results).is_err());
+        assert!(resolve_references(&mut json!({ "$ref": "0.missing" }), &results).is_err());
+        assert!(resolve_references(&mut json!({ "$ref": "terminal_id" }), &results).is_err());
+        // An object with other keys is not a reference.
+        let mut other = json!({ "$ref": "0", "kind": "tab" });
+        resolve_references(&mut other, &results).unwrap();
+        assert_eq!(other, json!({ "$ref": "0", "kind": "tab" }));
+    }
+
+    #[test]
+    fn references_as_text_keeps_the_step_parseable() {
+        let step = json!({
+            "action": "send_text",
+            "terminal_id": { "$ref": "0.terminal_id" },
+            "text": "ls\n",
+        });
+        assert_eq!(
+            references_as_text(&step),
+            json!({
+                "action": "send_text",
+                "terminal_id": "$ref:0.terminal_id",
+                "text": "ls\n",
+            })
+        );
+    }
+
+    #[test]
+    fn batch_result_omits_empty_failure_fields() {
+        let ok = ApiBatchResult {
+            results: vec![Value::Null],
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&ok).unwrap(),
+            json!({ "results": [null] })
+        );
+    }
+}
@@ -0,0 +1,544 @@
+//! Semantic daemon events, streamed to scripts as NDJSON by `GET /v1/events`.
+//!
+//! Most events are derived by diffing two consecutive [`StateResponse`]
+//! snapshots ([`diff_states`]) — terminals, projects, services and hook runs all
+//! live in the state. The PTY-level edges that leave no trace in the state
+//! (command finished, bell, desktop notification) are pushed by the daemon's
+//! PTY loop instead, and git status / toasts come from their own channels.
+
+use std::collections::{HashMap, HashSet};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::api::{ApiGitStatus, ApiHookExecution, ApiHookStatus, ApiToast, StateResponse};
+
+/// One semantic event. Serialized internally tagged on `type`, e.g.
+/// `{"type":"terminal_created","project_id":"…","terminal_id":"…"}`.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ApiEvent {
+    /// The workspace state changed; refetch `/v1/state` for details.
+    StateChanged {
+        state_version: u64,
+    },
+    ProjectAdded {
+        project_id: String,
+        name: String,
+    },
+    ProjectRemoved {
+        project_id: String,
+    },
+    TerminalCreated {
+        project_id: String,
+        terminal_id: String,
+    },
+    TerminalClosed {
+        project_id: String,
+        terminal_id: String,
+    },
+    /// A shell-integrated command finished (OSC 133 ;D).
+    CommandFinished {
+        project_id: String,
+        terminal_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        exit_code: Option<i32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        duration_ms: Option<u64>,
+    },
+    Bell {
+        project_id: String,
+        terminal_id: String,
+    },
+    /// A desktop notification (OSC 9/777/99) raised by a terminal.
+    Notification {
+        project_id: String,
+        terminal_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        body: String,
+    },
+    ServiceStatusChanged {
+        project_id: String,
+        service_name: String,
+        status: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        previous_status: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        exit_code: Option<u32>,
+    },
+    /// A hook execution left the `running` state.
+    HookFinished {
+        /// Resolved from the execution's project name; `None` when it no
+        /// longer matches exactly one project.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        project_id: Option<String>,
+        execution: ApiHookExecution,
+    },
+    GitStatusChanged {
+        project_id: String,
+        /// `None` when the project is no longer a git repository.
+        status: Option<ApiGitStatus>,
+    },
+    Toast {
+        toast: ApiToast,
+    },
+}
+
+/// Every `type` tag [`ApiEvent`] can carry, for validating `--type` filters.
+pub const EVENT_TYPES: &[&str] = &[
+    "state_changed",
+    "project_added",
+    "project_removed",
+    "terminal_created",
+    "terminal_closed",
+    "command_finished",
+    "bell",
+    "notification",
+    "service_status_changed",
+    "hook_finished",
+    "git_status_changed",
+    "toast",
+];
+
+impl ApiEvent {
+    /// The event's `type` tag.
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            Self::StateChanged { .. } => "state_changed",
+            Self::ProjectAdded { .. } => "project_added",
+            Self::ProjectRemoved { .. } => "project_removed",
+            Self::TerminalCreated { .. } => "terminal_created",
+            Self::TerminalClosed { .. } => "terminal_closed",
+            Self::CommandFinished { .. } => "command_finished",
+            Self::Bell { .. } => "bell",
+            Self::Notification { .. } => "notification",
+            Self::ServiceStatusChanged { .. } => "service_status_changed",
+            Self::HookFinished { .. } => "hook_finished",
+            Self::GitStatusChanged { .. } => "git_status_changed",
+            Self::Toast { .. } => "toast",
+        }
+    }
+
+    /// The project the event belongs to, if any.
+    pub fn project_id(&self) -> Option<&str> {
+        match self {
+            Self::ProjectAdded { project_id, .. }
+            | Self::ProjectRemoved { project_id }
+            | Self::TerminalCreated { project_id, .. }
+            | Self::TerminalClosed { project_id, .. }
+            | Self::CommandFinished { project_id, .. }
+            | Self::Bell { project_id, .. }
+            | Self::Notification { project_id, .. }
+            | Self::ServiceStatusChanged { project_id, .. }
+            | Self::GitStatusChanged { project_id, .. } => Some(project_id),
+            Self::HookFinished { project_id, .. } => project_id.as_deref(),
+            Self::StateChanged { .. } | Self::Toast { .. } => None,
+        }
+    }
+}
+
+/// Subscriber-side filter: empty sets match everything.
+#[derive(Clone, Debug, Default)]
+pub struct EventFilter {
+    pub types: HashSet<String>,
+    pub project_ids: HashSet<String>,
+}
+
+impl EventFilter {
+    /// Parse comma-separated `type` / `project` query values. Unknown types
+    /// are rejected so a typo doesn't silently match nothing.
+    pub fn parse(types: Option<&str>, project_ids: Option<&str>) -> Result<Self, String> {
+        let split = |s: Option<&str>| -> HashSet<String> {
+            s.unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|part| !part.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        let types = split(types);
+        if let Some(unknown) = types.iter().find(|t| !EVENT_TYPES.contains(&t.as_str())) {
+            return Err(format!(
+                "unknown event type '{unknown}' (expected one of: {})",
+                EVENT_TYPES.join(", ")
+            ));
+        }
+        Ok(Self {
+            types,
+            project_ids: split(project_ids),
+        })
+    }
+
+    /// Whether `event` passes the filter. A project filter drops events that
+    /// carry no project (state changes, toasts).
+    pub fn matches(&self, event: &ApiEvent) -> bool {
+        if !self.types.is_empty() && !self.types.contains(event.event_type()) {
+            return false;
+        }
+        if self.project_ids.is_empty() {
+            return true;
+        }
+        event
+            .project_id()
+            .is_some_and(|id| self.project_ids.contains(id))
+    }
+}
+
+/// Semantic events between two state snapshots, in a stable order: projects,
+/// terminals and services per project (in `next`'s project order), then
+/// finished hooks.
+pub fn diff_states(prev: &StateResponse, next: &StateResponse) -> Vec<ApiEvent> {
+    let mut events = Vec::new();
+    let prev_projects: HashMap<&str, _> =
+        prev.projects.iter().map(|p| (p.id.as_str(), p)).collect();
+    let next_ids: HashSet<&str> = next.projects.iter().map(|p| p.id.as_str()).collect();
+
+    for project in &next.projects {
+        let old = prev_projects.get(project.id.as_str());
+        if old.is_none() {
+            events.push(ApiEvent::ProjectAdded {
+                project_id: project.id.clone(),
+                name: project.name.clone(),
+            });
+        }
+
+        let old_terminals = old.copied().map(layout_terminal_ids).unwrap_or_default();
+        let new_terminals = layout_terminal_ids(project);
+        events.extend(
+            new_terminals
+                .iter()
+                .filter(|id| !old_terminals.contains(*id))
+                .map(|id| ApiEvent::TerminalCreated {
+                    project_id: project.id.clone(),
+                    terminal_id: id.clone(),
+                }),
+        );
+        events.extend(
+            old_terminals
+                .iter()
+                .filter(|id| !new_terminals.contains(*id))
+                .map(|id| ApiEvent::TerminalClosed {
+                    project_id: project.id.clone(),
+                    terminal_id: id.clone(),
+                }),
+        );
+
+        for service in &project.services {
+            let previous = old.and_then(|p| p.services.iter().find(|s| s.name == service.name));
+            if previous.is_some_and(|s| s.status == service.status) {
+                continue;
+            }
+            events.push(ApiEvent::ServiceStatusChanged {
+                project_id: project.id.clone(),
+                service_name: service.name.clone(),
+                status: service.status.clone(),
+                previous_status: previous.map(|s| s.status.clone()),
+                exit_code: service.exit_code,
+            });
+        }
+    }
+
+    for project in &prev.projects {
+        if next_ids.contains(project.id.as_str()) {
+            continue;
+        }
+        events.extend(layout_terminal_ids(project).into_iter().map(|id| {
+            ApiEvent::TerminalClosed {
+                project_id: project.id.clone(),
+                terminal_id: id,
+            }
+        }));
+        events.push(ApiEvent::ProjectRemoved {
+            project_id: project.id.clone(),
+        });
+    }
+
+    let prev_hooks: HashMap<u64, &ApiHookExecution> =
+        prev.hooks.iter().map(|h| (h.id, h)).collect();
+    let newest_prev_hook = prev.hooks.iter().map(|h| h.id).max();
+    for execution in &next.hooks {
+        if matches!(execution.status, ApiHookStatus::Running) {
+            continue;
+        }
+        let finished_now = match prev_hooks.get(&execution.id) {
+            Some(old) => matches!(old.status, ApiHookStatus::Running),
+            // Started and finished between two snapshots. Older ids missing
+            // from `prev` fell off its bounded history; they aren't new.
+            None => newest_prev_hook.is_none_or(|newest| execution.id > newest),
+        };
+        if finished_now {
+            events.push(ApiEvent::HookFinished {
+                project_id: project_id_for_name(next, &execution.project_name),
+                execution: execution.clone(),
+            });
+        }
+    }
+
+    events
+}
+
+/// Per-project git status changes between two git-status maps.
+pub fn diff_git_statuses(
+    prev: &HashMap<String, ApiGitStatus>,
+    next: &HashMap<String, ApiGitStatus>,
+) -> Vec<ApiEvent> {
+    // `ApiGitStatus` isn't `PartialEq`; compare the wire form instead.
+    let same = |a: &ApiGitStatus, b: &ApiGitStatus| {
+        serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+    };
+    let mut project_ids: Vec<&String> = next.keys().chain(prev.keys()).collect();
+    project_ids.sort();
+    project_ids.dedup();
+    project_ids
+        .into_iter()
+        .filter_map(|id| match (prev.get(id), next.get(id)) {
+            (Some(a), Some(b)) if same(a, b) => None,
+            (None, None) => None,
+            (_, status) => Some(ApiEvent::GitStatusChanged {
+                project_id: id.clone(),
+                status: status.cloned(),
+            }),
+        })
+        .collect()
+}
+
+fn layout_terminal_ids(project: &crate::api::ApiProject) -> Vec<String> {
+    project
+        .layout
+        .as_ref()
+        .map(|layout| layout.collect_terminal_ids())
+        .unwrap_or_default()
+}
+
+fn project_id_for_name(state: &StateResponse, name: &str) -> Option<String> {
+    let mut matching = state.projects.iter().filter(|p| p.name == name);
+    match (matching.next(), matching.next()) {
+        (Some(project), None) => Some(project.id.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{ApiLayoutNode, ApiProject, ApiServiceInfo};
+
+    fn state(projects: Vec<ApiProject>, hooks: Vec<ApiHookExecution>) -> StateResponse {
+        serde_json::from_value(serde_json::json!({
+            "state_version": 1,
+            "projects": projects,
+            "focused_project_id": null,
+            "fullscreen_terminal": null,
+            "hooks": hooks,
+        }))
+        .unwrap()
+    }
+
+    fn project(id: &str, terminals: &[&str], services: &[(&str, &str)]) -> ApiProject {
+        let layout = ApiLayoutNode::Tabs {
+            active_tab: 0,
+            children: terminals
+                .iter()
+                .map(|tid| ApiLayoutNode::Terminal {
+                    terminal_id: Some(tid.to_string()),
+                    minimized: false,
+                    detached: false,
+                    shell_type: Default::default(),
+                    cols: None,
+                    rows: None,
+                })
+                .collect(),
+        };
+        let services: Vec<ApiServiceInfo> = services
+            .iter()
+            .map(|(name, status)| {
+                serde_json::from_value(serde_json::json!({
+                    "name": name,
+                    "status": status,
+                    "terminal_id": null,
+                }))
+                .unwrap()
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": format!("Project {id}"),
+            "path": format!("/tmp/{id}"),
+            "show_in_overview": true,
+            "layout": layout,
+            "terminal_names": {},
+            "git_status": null,
+            "services": services,
+        }))
+        .unwrap()
+    }
+
+    fn hook(id: u64, project: &str, status: ApiHookStatus) -> ApiHookExecution {
+        ApiHookExecution {
+            id,
+            hook_type: "on_project_open".to_string(),
+            command: "make".to_string(),
+            project_name: format!("Project {project}"),
+            status,
+            terminal_id: None,
+        }
+    }
+
+    #[test]
+    fn diff_reports_terminal_project_and_service_changes() {
+        let prev = state(
+            vec![
+                project("a", &["t1", "t2"], &[("web", "starting")]),
+                project("gone", &["t9"], &[]),
+            ],
+            Vec::new(),
+        );
+        let next = state(
+            vec![
+                project("a", &["t2", "t3"], &[("web", "running"), ("db", "stopped")]),
+                project("new", &[], &[]),
+            ],
+            Vec::new(),
+        );
+
+        let events = diff_states(&prev, &next);
+        let types: Vec<(&str, Option<&str>)> = events
+            .iter()
+            .map(|e| (e.event_type(), e.project_id()))
+            .collect();
+
+        assert_eq!(
+            types,
+            vec![
+                ("terminal_created", Some("a")),
+                ("terminal_closed", Some("a")),
+                ("service_status_changed", Some("a")),
+                ("service_status_changed", Some("a")),
+                ("project_added", Some("new")),
+                ("terminal_closed", Some("gone")),
+                ("project_removed", Some("gone")),
+            ]
+        );
+        let service_change = events
+            .into_iter()
+            .find(|e| matches!(e, ApiEvent::ServiceStatusChanged { service_name, .. } if service_name == "web"))
+            .unwrap();
+        assert!(matches!(
+            service_change,
+            ApiEvent::ServiceStatusChanged { status, previous_status: Some(prev), .. }
+                if status == "running" && prev == "starting"
+        ));
+    }
+
+    #[test]
+    fn diff_reports_each_finished_hook_once() {
+        let projects = vec![project("a", &[], &[])];
+        let prev = state(
+            projects.clone(),
+            vec![
+                hook(1, "a", ApiHookStatus::Succeeded { duration_ms: 5 }),
+                hook(2, "a", ApiHookStatus::Running),
+            ],
+        );
+        let next = state(
+            projects.clone(),
+            vec![
+                hook(1, "a", ApiHookStatus::Succeeded { duration_ms: 5 }),
+                hook(2, "a", ApiHookStatus::Cancelled { duration_ms: 9 }),
+                hook(3, "a", ApiHookStatus::Succeeded { duration_ms: 1 }),
+                hook(4, "a", ApiHookStatus::Running),
+            ],
+        );
+
+        let finished: Vec<(u64, Option<String>)> = diff_states(&prev, &next)
+            .into_iter()
+            .filter_map(|e| match e {
+                ApiEvent::HookFinished {
+                    project_id,
+                    execution,
+                } => Some((execution.id, project_id)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            finished,
+            vec![(2, Some("a".to_string())), (3, Some("a".to_string()))]
+        );
+        assert!(diff_states(&next, &next).is_empty());
+    }
+
+    #[test]
+    fn git_status_diff_reports_changed_and_removed_projects() {
+        let status = |branch: &str| ApiGitStatus {
+            branch: Some(branch.to_string()),
+            ..Default::default()
+        };
+        let prev = HashMap::from([
+            ("a".to_string(), status("main")),
+            ("b".to_string(), status("main")),
+            ("c".to_string(), status("dev")),
+        ]);
+        let next = HashMap::from([
+            ("a".to_string(), status("main")),
+            ("b".to_string(), status("feature")),
+        ]);
+
+        let events = diff_git_statuses(&prev, &next);
+
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[0],
+            ApiEvent::GitStatusChanged { project_id, status: Some(s) }
+                if project_id == "b" && s.branch.as_deref() == Some("feature")
+        ));
+        assert!(matches!(
+            &events[1],
+            ApiEvent::GitStatusChanged { project_id, status: None } if project_id == "c"
+        ));
+    }
+
+    #[test]
+    fn filter_matches_types_and_projects() {
+        let bell = ApiEvent::Bell {
+            project_id: "a".to_string(),
+            terminal_id: "t1".to_string(),
+        };
+        let state_changed = ApiEvent::StateChanged { state_version: 3 };
+
+        let any = EventFilter::parse(None, None).unwrap();
+        assert!(any.matches(&bell) && any.matches(&state_changed));
+
+        let bells_in_a = EventFilter::parse(Some("bell, command_finished"), Some("a")).unwrap();
+        assert!(bells_in_a.matches(&bell));
+        assert!(!bells_in_a.matches(&state_changed));
+        assert!(!EventFilter::parse(None, Some("b")).unwrap().matches(&bell));
+
+        assert!(EventFilter::parse(Some("bel"), None).is_err());
+    }
+
+    #[test]
+    fn events_serialize_with_a_type_tag() {
+        let event = ApiEvent::CommandFinished {
+            project_id: "a".to_string(),
+            terminal_id: "t1".to_string(),
+            exit_code: Some(2),
+            duration_ms: None,
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "command_finished",
+                "project_id": "a",
+                "terminal_id": "t1",
+                "exit_code": 2,
+            })
+        );
+        let parsed = serde_json::from_value::<ApiEvent>(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), json);
+        for ty in EVENT_TYPES {
+            assert!(EventFilter::parse(Some(ty), None).is_ok());
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Port forwarding over the remote API.
+//!
+//! A client opens one `GET /v1/forward` WebSocket per forwarded TCP
+//! connection. It authenticates with a [`WsInbound::Auth`] message, as on
+//! `/v1/stream`, and the server answers with a [`ForwardReply`]. After
+//! `ready` binary frames carry raw bytes of the TCP stream in either
+//! direction. WebSocket has no half-close, so an empty binary frame marks
+//! EOF of the sender's direction; a close frame tears down both.
+//!
+//! Only ports the daemon detected for the named service can be reached, so a
+//! token with the `services` scope can't use the daemon as a general proxy.
+//!
+//! [`WsInbound::Auth`]: crate::ws::WsInbound::Auth
+
+use crate::api::{ApiServiceInfo, StateResponse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Path of the forwarding WebSocket.
+pub const FORWARD_PATH: &str = "/v1/forward";
+
+/// `GET /v1/forward` query.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PortForwardQuery {
+    pub project_id: String,
+    /// Service name, as in `ApiProject.services`.
+    pub service: String,
+    /// One of the service's detected ports. Defaults to the first one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+}
+
+impl PortForwardQuery {
+    /// The query string, percent-encoded.
+    pub fn to_query_string(&self) -> String {
+        let mut query = format!(
+            "project_id={}&service={}",
+            encode(&self.project_id),
+            encode(&self.service)
+        );
+        if let Some(port) = self.port {
+            query.push_str(&format!("&port={port}"));
+        }
+        query
+    }
+}
+
+/// First server message on `/v1/forward`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ForwardReply {
+    /// Connected to the service; binary frames follow.
+    Ready { port: u16 },
+    /// Auth, scope or target check failed, or the port refused the
+    /// connection. The server closes the socket after sending it.
+    Error { error: String },
+}
+
+impl ApiServiceInfo {
+    /// The port a forward to this service should use: `requested` when the
+    /// service listens on it, else its first detected port.
+    pub fn forward_port(&self, requested: Option<u16>) -> Result<u16, String> {
+        match requested {
+            Some(port) if self.ports.contains(&port) => Ok(port),
+            Some(port) => Err(format!(
+                "service '{}' has no detected port {port} (detected: {})",
+                self.name,
+                describe_ports(&self.ports)
+            )),
+            None => self.ports.first().copied().ok_or_else(|| {
+                format!(
+                    "service '{}' has no detected ports (status: {})",
+                    self.name, self.status
+                )
+            }),
+        }
+    }
+}
+
+/// Resolve `query` against `state` to the port to connect to.
+pub fn resolve_target(state: &StateResponse, query: &PortForwardQuery) -> Result<u16, String> {
+    let project = state
+        .projects
+        .iter()
+        .find(|project| project.id == query.project_id)
+        .ok_or_else(|| format!("project '{}' not found", query.project_id))?;
+    let service = project
+        .services
+        .iter()
+        .find(|service| service.name == query.service)
+        .ok_or_else(|| {
+            format!(
+                "service '{}' not found in project '{}'",
+                query.service, project.name
+            )
+        })?;
+    service.forward_port(query.port)
+}
+
+fn describe_ports(ports: &[u16]) -> String {
+    if ports.is_empty() {
+        return "none".to_string();
+    }
+    ports
+        .iter()
+        .map(u16::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Percent-encode everything outside RFC 3986's unreserved set.
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(ports: &[u16]) -> ApiServiceInfo {
+        ApiServiceInfo {
+            name: "web".to_string(),
+            status: "running".to_string(),
+            terminal_id: None,
+            ports: ports.to_vec(),
+            exit_code: None,
+            kind: "okena".to_string(),
+            is_extra: false,
+            restart_trigger: None,
+        }
+    }
+
+    #[test]
+    fn forward_port_only_allows_detected_ports() {
+        assert_eq!(service(&[5173, 24678]).forward_port(None), Ok(5173));
+        assert_eq!(service(&[5173, 24678]).forward_port(Some(24678)), Ok(24678));
+        assert!(
+            service(&[5173])
+                .forward_port(Some(22))
+                .unwrap_err()
+                .contains("no detected port 22")
+        );
+        assert!(service(&[]).forward_port(None).is_err());
+    }
+
+    #[test]
+    fn query_string_is_encoded() {
+        let query = PortForwardQuery {
+            project_id: "p 1".to_string(),
+            service: "api&web".to_string(),
+            port: Some(8080),
+        };
+        assert_eq!(
+            query.to_query_string(),
+            "project_id=p%201&service=api%26web&port=8080"
+        );
+    }
+
+    #[test]
+    fn reply_wire_format() {
+        let ready = serde_json::to_value(ForwardReply::Ready { port: 3000 }).unwrap();
+        assert_eq!(ready, serde_json::json!({"type": "ready", "port": 3000}));
+    }
+}
@@ -0,0 +1,365 @@
+//! Multi-step hook definitions shared by the domain config and the wire API.
+//!
+//! A hook is either a plain command string or an ordered list of steps. Each
+//! step may be gated by `when:` conditions and carry its own failure and
+//! timeout policy. Plain strings stay plain strings on disk.
+
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::path::Path;
+
+/// How a hook level combines with the levels below it
+/// (global → parent project → project).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HookMergeMode {
+    /// Discard inherited steps (the behavior of plain string hooks).
+    #[default]
+    Replace,
+    /// Run inherited steps first, then this level's steps.
+    Append,
+}
+
+/// Conditions that must all hold for a step to run. Empty lists match anything.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct HookCondition {
+    /// Branch globs (`*`, `?`); any match passes. Never matches without a branch.
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(with = "OneOrMany")]
+    pub branch: Vec<String>,
+    /// Operating systems as reported by Rust (`linux`, `macos`, `windows`).
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(with = "OneOrMany")]
+    pub os: Vec<String>,
+    /// Path, relative to the project, that must exist.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exists: Option<String>,
+}
+
+impl HookCondition {
+    pub fn is_empty(&self) -> bool {
+        *self == HookCondition::default()
+    }
+
+    /// Evaluate against the current OS, the hook's branch and project path.
+    pub fn matches(&self, branch: Option<&str>, project_path: &Path) -> bool {
+        self.matches_os(std::env::consts::OS, branch, project_path)
+    }
+
+    fn matches_os(&self, os: &str, branch: Option<&str>, project_path: &Path) -> bool {
+        if !self.os.is_empty() && !self.os.iter().any(|o| o.eq_ignore_ascii_case(os)) {
+            return false;
+        }
+        if !self.branch.is_empty() {
+            let Some(branch) = branch else {
+                return false;
+            };
+            if !self.branch.iter().any(|glob| glob_matches(glob, branch)) {
+                return false;
+            }
+        }
+        match &self.exists {
+            Some(path) => project_path.join(path).exists(),
+            None => true,
+        }
+    }
+}
+
+/// One command in a multi-step hook.
+///
+/// The schema describes the object form; plain commands are strings (see
+/// `StepRepr`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "HookStepObject")]
+pub struct HookStep {
+    pub run: String,
+    #[serde(default)]
+    pub when: HookCondition,
+    /// Keep going with the next step when this one exits non-zero.
+    #[serde(default)]
+    pub continue_on_error: bool,
+    /// Kill the step after this many seconds.
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// Open the command in a new terminal pane instead of running it inline
+    /// (only for hooks that support terminal actions).
+    #[serde(default)]
+    pub terminal: bool,
+}
+
+impl HookStep {
+    pub fn command(run: impl Into<String>) -> Self {
+        Self {
+            run: run.into(),
+            ..Default::default()
+        }
+    }
+
+    /// A step with no options round-trips as a bare command string.
+    pub fn is_plain(&self) -> bool {
+        self.when.is_empty() && !self.continue_on_error && self.timeout.is_none() && !self.terminal
+    }
+}
+
+impl Serialize for HookStep {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.is_plain() {
+            return serializer.serialize_str(&self.run);
+        }
+        #[derive(Serialize)]
+        struct Full<'a> {
+            run: &'a str,
+            #[serde(skip_serializing_if = "HookCondition::is_empty")]
+            when: &'a HookCondition,
+            #[serde(skip_serializing_if = "std::ops::Not::not")]
+            continue_on_error: bool,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            timeout: Option<u64>,
+            #[serde(skip_serializing_if = "std::ops::Not::not")]
+            terminal: bool,
+        }
+        Full {
+            run: &self.run,
+            when: &self.when,
+            continue_on_error: self.continue_on_error,
+            timeout: self.timeout,
+            terminal: self.terminal,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// An ordered step list for one hook at one config level.
+///
+/// Deserializes from a list of steps (replace) or from
+/// `{ "merge": "append", "timeout": 600, "steps": [...] }`. Steps are strings
+/// or objects.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HookSteps {
+    pub merge: HookMergeMode,
+    /// Seconds the whole hook may run before it is killed; the hook runner's
+    /// default applies when no level sets one.
+    pub timeout: Option<u64>,
+    pub steps: Vec<HookStep>,
+}
+
+/// A step as written: a bare command or the full object.
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+#[schemars(rename = "HookStep")]
+enum StepRepr {
+    Command(String),
+    Full(HookStep),
+}
+
+impl From<StepRepr> for HookStep {
+    fn from(repr: StepRepr) -> Self {
+        match repr {
+            StepRepr::Command(run) => HookStep::command(run),
+            StepRepr::Full(step) => step,
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum StepsRepr {
+    List(Vec<StepRepr>),
+    Object {
+        #[serde(default)]
+        merge: HookMergeMode,
+        #[serde(default)]
+        timeout: Option<u64>,
+        steps: Vec<StepRepr>,
+    },
+}
+
+impl<'de> Deserialize<'de> for HookSteps {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (merge, timeout, steps) = match StepsRepr::deserialize(deserializer)? {
+            StepsRepr::List(steps) => (HookMergeMode::Replace, None, steps),
+            StepsRepr::Object {
+                merge,
+                timeout,
+                steps,
+            } => (merge, timeout, steps),
+        };
+        Ok(HookSteps {
+            merge,
+            timeout,
+            steps: steps.into_iter().map(HookStep::from).collect(),
+        })
+    }
+}
+
+impl JsonSchema for HookSteps {
+    fn schema_name() -> Cow<'static, str> {
+        "HookSteps".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        StepsRepr::json_schema(generator)
+    }
+}
+
+impl Serialize for HookSteps {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Object<'a> {
+            merge: HookMergeMode,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            timeout: Option<u64>,
+            steps: &'a [HookStep],
+        }
+        match (self.merge, self.timeout) {
+            (HookMergeMode::Replace, None) => self.steps.serialize(serializer),
+            _ => Object {
+                merge: self.merge,
+                timeout: self.timeout,
+                steps: &self.steps,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+/// A single value or a list of them.
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
+}
+
+/// Minimal glob: `*` matches any run of characters (including `/`), `?` one.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_deserialize_from_list_and_object() {
+        let steps: HookSteps = serde_json::from_str(
+            r#"["npm ci", {"run": "cargo build", "when": {"os": "linux"}, "timeout": 60}]"#,
+        )
+        .unwrap();
+        assert_eq!(steps.merge, HookMergeMode::Replace);
+        assert_eq!(steps.steps[0], HookStep::command("npm ci"));
+        assert_eq!(steps.steps[1].when.os, vec!["linux".to_string()]);
+        assert_eq!(steps.steps[1].timeout, Some(60));
+
+        let steps: HookSteps = serde_json::from_str(
+            r#"{"merge": "append", "steps": [{"run": "make", "continue_on_error": true}]}"#,
+        )
+        .unwrap();
+        assert_eq!(steps.merge, HookMergeMode::Append);
+        assert!(steps.steps[0].continue_on_error);
+
+        let steps: HookSteps =
+            serde_json::from_str(r#"{"timeout": 600, "steps": ["cargo test"]}"#).unwrap();
+        assert_eq!(steps.merge, HookMergeMode::Replace);
+        assert_eq!(steps.timeout, Some(600));
+    }
+
+    #[test]
+    fn steps_round_trip_compactly() {
+        let json = r#"{"merge":"append","steps":["echo a",{"run":"echo b","when":{"branch":["feature/*"]},"terminal":true}]}"#;
+        let steps: HookSteps = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::to_string(&steps).unwrap(), json);
+
+        let replace = HookSteps {
+            merge: HookMergeMode::Replace,
+            timeout: None,
+            steps: vec![HookStep::command("echo a")],
+        };
+        assert_eq!(serde_json::to_string(&replace).unwrap(), r#"["echo a"]"#);
+
+        let timed = HookSteps {
+            timeout: Some(30),
+            ..replace
+        };
+        assert_eq!(
+            serde_json::to_string(&timed).unwrap(),
+            r#"{"merge":"replace","timeout":30,"steps":["echo a"]}"#
+        );
+    }
+
+    #[test]
+    fn step_rejects_unknown_keys() {
+        assert!(serde_json::from_str::<HookSteps>(r#"[{"run": "x", "retries": 3}]"#).is_err());
+    }
+
+    #[test]
+    fn condition_matches_os_branch_and_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+
+        let cond = HookCondition {
+            branch: vec!["feature/*".into(), "main".into()],
+            os: vec!["linux".into(), "macos".into()],
+            exists: Some("package.json".into()),
+        };
+        assert!(cond.matches_os("linux", Some("feature/login"), dir.path()));
+        assert!(cond.matches_os("macos", Some("main"), dir.path()));
+        assert!(!cond.matches_os("windows", Some("main"), dir.path()));
+        assert!(!cond.matches_os("linux", Some("release/1.0"), dir.path()));
+        assert!(!cond.matches_os("linux", None, dir.path()));
+
+        let missing = HookCondition {
+            exists: Some("Cargo.toml".into()),
+            ..Default::default()
+        };
+        assert!(!missing.matches_os("linux", None, dir.path()));
+        assert!(HookCondition::default().matches_os("linux", None, dir.path()));
+    }
+
+    #[test]
+    fn glob_handles_stars_and_question_marks() {
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("feat*", "feature/x"));
+        assert!(glob_matches("*/fix-??", "team/fix-42"));
+        assert!(glob_matches("a*b*c", "aXXbYYc"));
+        assert!(!glob_matches("a*b*c", "aXXbYY"));
+        assert!(!glob_matches("main", "mainline"));
+    }
+}
@@ -1,16 +1,24 @@
 #![cfg_attr(not(test), warn(clippy::unwrap_used, clippy::expect_used))]
 
 pub mod api;
+pub mod audit;
+pub mod batch;
+pub mod events;
+pub mod forward;
 pub mod git_poll;
+pub mod hooks;
 pub mod keys;
 pub mod latency_probe;
 pub mod process;
 pub mod profiles;
 pub mod render_probe;
+pub mod schema;
+pub mod scope;
 pub mod selection;
 pub mod send_payload;
 pub mod shell;
 pub mod soft_close;
+pub mod state_delta;
 pub mod theme;
 pub mod timing;
 pub mod types;
@@ -953,7 +953,7 @@ impl ProcessTree {
 }
 
 #[cfg(windows)]
-fn resume_suspended_process(process_id: u32) -> std::io::Result<()> {
+pub(super) fn resume_suspended_process(process_id: u32) -> std::io::Result<()> {
     use std::os::windows::io::{AsRawHandle, FromRawHandle, OwnedHandle};
     use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
     use windows_sys::Win32::System::Diagnostics::ToolHelp::{
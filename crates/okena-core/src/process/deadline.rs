@@ -0,0 +1,236 @@
+//! Run a child with inherited stdio under a deadline, killing its whole
+//! process tree when the deadline passes.
+//!
+//! Unlike the [`bus`](super::bus), nothing is captured: the child keeps the
+//! caller's stdin/stdout/stderr, so it can run inside a terminal. It gets its
+//! own process group (a job object on Windows), so a timeout also reaches the
+//! grandchildren a wrapper like `npm run` leaves behind.
+
+use std::process::{Child, Command, ExitStatus};
+use std::time::{Duration, Instant};
+
+/// Exit code for a child stopped by [`run_with_deadline`]: 128 + SIGTERM, as
+/// a shell reports a terminated job.
+pub const DEADLINE_EXIT_CODE: i32 = 143;
+
+const POLL: Duration = Duration::from_millis(25);
+
+/// How long a timed-out group gets between SIGTERM and SIGKILL.
+#[cfg(unix)]
+const KILL_GRACE: Duration = Duration::from_secs(2);
+
+/// Run `cmd` until it exits or `timeout` elapses. On timeout the child's
+/// whole process tree is killed and `Ok(None)` returned.
+///
+/// On Unix, when stdin is the terminal this process runs in the foreground
+/// of, the child's group is made the foreground group for the duration, so
+/// it can read the terminal and gets Ctrl-C. That swaps the process-wide
+/// SIGTTOU disposition for a moment; call this from a single-threaded helper.
+pub fn run_with_deadline(
+    cmd: &mut Command,
+    timeout: Duration,
+) -> std::io::Result<Option<ExitStatus>> {
+    let (mut child, tree) = Tree::spawn(cmd)?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            tree.kill(&mut child);
+            return Ok(None);
+        }
+        std::thread::sleep(POLL);
+    }
+}
+
+#[cfg(unix)]
+struct Tree {
+    process_group: libc::pid_t,
+    foreground: Option<Foreground>,
+}
+
+#[cfg(unix)]
+impl Tree {
+    fn spawn(cmd: &mut Command) -> std::io::Result<(Child, Self)> {
+        use std::os::unix::process::CommandExt;
+
+        cmd.process_group(0);
+        let mut child = cmd.spawn()?;
+        let Ok(process_group) = libc::pid_t::try_from(child.id()) else {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(std::io::Error::other(
+                "spawned process has an invalid process group",
+            ));
+        };
+        let foreground = Foreground::hand_to(process_group);
+        Ok((
+            child,
+            Self {
+                process_group,
+                foreground,
+            },
+        ))
+    }
+
+    fn kill(mut self, child: &mut Child) {
+        // SAFETY: process_group is the pid of our unreaped child, which
+        // `process_group(0)` made a group leader before exec.
+        let _ = unsafe { libc::kill(-self.process_group, libc::SIGTERM) };
+        let grace = Instant::now() + KILL_GRACE;
+        while matches!(child.try_wait(), Ok(None)) && Instant::now() < grace {
+            std::thread::sleep(POLL);
+        }
+        // Members that ignored SIGTERM keep the group id alive even once the
+        // leader is gone, so this can't reach an unrelated process.
+        // SAFETY: as above.
+        let _ = unsafe { libc::kill(-self.process_group, libc::SIGKILL) };
+        let _ = child.wait();
+        self.foreground.take();
+    }
+}
+
+/// The terminal's foreground group handed to a child, given back on drop.
+#[cfg(unix)]
+struct Foreground {
+    previous: libc::pid_t,
+}
+
+#[cfg(unix)]
+impl Foreground {
+    fn hand_to(process_group: libc::pid_t) -> Option<Self> {
+        // SAFETY: plain queries and updates of fd 0's terminal state.
+        unsafe {
+            if libc::isatty(libc::STDIN_FILENO) != 1 {
+                return None;
+            }
+            let previous = libc::tcgetpgrp(libc::STDIN_FILENO);
+            if previous != libc::getpgrp()
+                || libc::tcsetpgrp(libc::STDIN_FILENO, process_group) != 0
+            {
+                return None;
+            }
+            // A read before the handover stopped the child with SIGTTIN.
+            libc::kill(-process_group, libc::SIGCONT);
+        }
+        Some(Self { previous })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Foreground {
+    fn drop(&mut self) {
+        // SAFETY: we are a background group now, so tcsetpgrp would stop us
+        // with SIGTTOU unless it is ignored for the call.
+        unsafe {
+            let old = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+            libc::tcsetpgrp(libc::STDIN_FILENO, self.previous);
+            libc::signal(libc::SIGTTOU, old);
+        }
+    }
+}
+
+#[cfg(windows)]
+struct Tree {
+    job: std::os::windows::io::OwnedHandle,
+}
+
+#[cfg(windows)]
+impl Tree {
+    fn spawn(cmd: &mut Command) -> std::io::Result<(Child, Self)> {
+        use std::os::windows::io::{AsRawHandle, FromRawHandle, OwnedHandle};
+        use std::os::windows::process::CommandExt;
+        use windows_sys::Win32::System::JobObjects::{
+            AssignProcessToJobObject, CreateJobObjectW, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+            JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JobObjectExtendedLimitInformation,
+            SetInformationJobObject,
+        };
+
+        // Suspended so it joins the job before it can start descendants. No
+        // CREATE_NO_WINDOW: the child shares our console.
+        const CREATE_SUSPENDED: u32 = 0x0000_0004;
+        cmd.creation_flags(CREATE_SUSPENDED);
+
+        // SAFETY: null security/name pointers request a private unnamed job.
+        let raw_job = unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) };
+        if raw_job.is_null() {
+            return Err(std::io::Error::last_os_error());
+        }
+        // SAFETY: CreateJobObjectW returned a unique owned handle on success.
+        let job = unsafe { OwnedHandle::from_raw_handle(raw_job) };
+        let mut limits = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+        limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+        let limits_size = u32::try_from(std::mem::size_of_val(&limits))
+            .map_err(|_| std::io::Error::other("job limits structure is too large"))?;
+        // SAFETY: limits points to the structure required by this information
+        // class and remains valid for the duration of the call.
+        if unsafe {
+            SetInformationJobObject(
+                job.as_raw_handle(),
+                JobObjectExtendedLimitInformation,
+                std::ptr::from_ref(&limits).cast(),
+                limits_size,
+            )
+        } == 0
+        {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        let mut child = cmd.spawn()?;
+        // SAFETY: both handles are live and owned for the duration of the call.
+        if unsafe { AssignProcessToJobObject(job.as_raw_handle(), child.as_raw_handle()) } == 0 {
+            let error = std::io::Error::last_os_error();
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(error);
+        }
+        if let Err(error) = super::bus::resume_suspended_process(child.id()) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(error);
+        }
+        Ok((child, Self { job }))
+    }
+
+    fn kill(self, child: &mut Child) {
+        use std::os::windows::io::AsRawHandle;
+        use windows_sys::Win32::System::JobObjects::TerminateJobObject;
+
+        // SAFETY: the owned job handle remains live for this call.
+        let _ = unsafe { TerminateJobObject(self.job.as_raw_handle(), DEADLINE_EXIT_CODE as u32) };
+        let _ = child.wait();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadline_kills_grandchildren() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("survived");
+        let script = format!("sh -c 'sleep 2; touch {}' & wait", marker.display());
+        let started = Instant::now();
+        let status = run_with_deadline(
+            Command::new("sh").arg("-c").arg(script),
+            Duration::from_millis(300),
+        )
+        .unwrap();
+        assert!(status.is_none());
+        assert!(started.elapsed() < Duration::from_secs(2));
+        std::thread::sleep(Duration::from_millis(2500));
+        assert!(!marker.exists(), "the grandchild outlived the timeout");
+    }
+
+    #[test]
+    fn finished_children_report_their_status() {
+        let status = run_with_deadline(
+            Command::new("sh").arg("-c").arg("exit 7"),
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        assert_eq!(status.and_then(|s| s.code()), Some(7));
+    }
+}
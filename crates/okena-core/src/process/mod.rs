@@ -13,11 +13,13 @@
 //! into [`Lane::Poll`] with [`with_lane`]).
 
 mod bus;
+mod deadline;
 
 pub use bus::{
     CommandBus, CommandCancellation, CommandHandle, CommandSpec, Lane, StderrSink, current_lane,
     with_lane,
 };
+pub use deadline::{DEADLINE_EXIT_CODE, run_with_deadline};
 
 /// Create a [`std::process::Command`] that does **not** flash a console
 /// window on Windows.  On other platforms this is identical to
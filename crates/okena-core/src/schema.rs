@@ -0,0 +1,663 @@
+//! Machine-readable description of the remote API, generated from the wire
+//! types in this crate.
+//!
+//! [`openapi`] builds an OpenAPI 3.1 document for the bearer-token routes and
+//! the public `/health`, `/v1/pair` and `/v1/schema`. The loopback lifecycle
+//! routes (`/v1/restart`, `/v1/shutdown`, `/v1/auth/reload`, `/v1/update/*`)
+//! belong to the desktop app and the CLI and are left out. WebSocket frames
+//! have no OpenAPI form; `/v1/stream` names them in an `x-websocket`
+//! extension and their schemas sit in `components.schemas` with the rest.
+//!
+//! Schemas describe what the server accepts, so fields with a default are
+//! optional. The generated document is checked in at
+//! `docs/reference/openapi.json`; run the tests with `OKENA_UPDATE_SCHEMA=1`
+//! to rewrite it after changing a wire type.
+
+use crate::api::{
+    ActionRequest, ApiExecResult, ApiOutputMatch, ApiReadContent, ApiShare, ApiShareCreated,
+    ApiShareRequest, ApiWaitForRequest, ErrorResponse, FileDownloadRequest, FileUploadRequest,
+    FileUploadResponse, HealthResponse, PairRequest, PairResponse, StateResponse,
+};
+use crate::audit::{AuditEntry, AuditQuery};
+use crate::batch::{ActionBatchRequest, ApiBatchResult};
+use crate::events::ApiEvent;
+use crate::forward::{ForwardReply, PortForwardQuery};
+use crate::scope::TokenGrant;
+use crate::ws::{WsInbound, WsOutbound};
+use schemars::generate::{SchemaGenerator, SchemaSettings};
+use schemars::{JsonSchema, Schema};
+use serde_json::{Map, Value, json};
+use std::cell::RefCell;
+
+/// Version of the API the document describes (the `/v1` prefix).
+const API_VERSION: &str = "1";
+
+type RootFn = fn() -> Schema;
+
+/// Types with a standalone schema, for `okena schema <TYPE>`.
+const ROOTS: &[(&str, RootFn)] = &[
+    ("ActionRequest", root::<ActionRequest>),
+    ("ActionBatchRequest", root::<ActionBatchRequest>),
+    ("ApiBatchResult", root::<ApiBatchResult>),
+    ("ApiEvent", root::<ApiEvent>),
+    ("ApiExecResult", root::<ApiExecResult>),
+    ("ApiOutputMatch", root::<ApiOutputMatch>),
+    ("ApiReadContent", root::<ApiReadContent>),
+    ("ApiShare", root::<ApiShare>),
+    ("ApiShareCreated", root::<ApiShareCreated>),
+    ("ApiShareRequest", root::<ApiShareRequest>),
+    ("ApiWaitForRequest", root::<ApiWaitForRequest>),
+    ("AuditEntry", root::<AuditEntry>),
+    ("AuditQuery", root::<AuditQuery>),
+    ("ErrorResponse", root::<ErrorResponse>),
+    ("FileDownloadRequest", root::<FileDownloadRequest>),
+    ("FileUploadRequest", root::<FileUploadRequest>),
+    ("FileUploadResponse", root::<FileUploadResponse>),
+    ("ForwardReply", root::<ForwardReply>),
+    ("HealthResponse", root::<HealthResponse>),
+    ("PairRequest", root::<PairRequest>),
+    ("PairResponse", root::<PairResponse>),
+    ("PortForwardQuery", root::<PortForwardQuery>),
+    ("StateResponse", root::<StateResponse>),
+    ("TokenGrant", root::<TokenGrant>),
+    ("WsInbound", root::<WsInbound>),
+    ("WsOutbound", root::<WsOutbound>),
+];
+
+fn root<T: JsonSchema>() -> Schema {
+    SchemaSettings::draft2020_12()
+        .into_generator()
+        .into_root_schema_for::<T>()
+}
+
+/// Names accepted by [`json_schema`].
+pub fn type_names() -> impl Iterator<Item = &'static str> {
+    ROOTS.iter().map(|(name, _)| *name)
+}
+
+/// Standalone JSON Schema (draft 2020-12) for one wire type, by name.
+pub fn json_schema(name: &str) -> Option<Value> {
+    ROOTS
+        .iter()
+        .find(|(root_name, _)| root_name.eq_ignore_ascii_case(name))
+        .map(|(_, root)| root().to_value())
+}
+
+/// The OpenAPI 3.1 document served at `GET /v1/schema`.
+pub fn openapi() -> Value {
+    let spec = Spec::new();
+
+    spec.route(
+        "get",
+        "/health",
+        json!({
+            "summary": "Server health",
+            "security": [],
+            "responses": { "200": spec.json_response::<HealthResponse>("Server is up") },
+        }),
+    );
+    spec.route(
+        "post",
+        "/v1/pair",
+        json!({
+            "summary": "Exchange a pairing code for a token",
+            "security": [],
+            "requestBody": spec.json_body::<PairRequest>(),
+            "responses": {
+                "200": spec.json_response::<PairResponse>("Paired"),
+                "401": spec.error_response("Invalid or expired code"),
+                "429": spec.error_response("Too many attempts"),
+            },
+        }),
+    );
+    spec.route(
+        "get",
+        "/v1/schema",
+        json!({
+            "summary": "This document",
+            "security": [],
+            "responses": { "200": { "description": "OpenAPI document", "content": { "application/json": {} } } },
+        }),
+    );
+    spec.route(
+        "get",
+        "/v1/state",
+        json!({
+            "summary": "Workspace state",
+            "x-scope": "read",
+            "responses": { "200": spec.json_response::<StateResponse>("Current state") },
+        }),
+    );
+    let action_result = json!({
+        "description": "Action-specific result; `{\"ok\": true}` when the action has none",
+        "content": { "application/json": { "schema": { "anyOf": [
+            spec.schema::<ApiReadContent>(),
+            spec.schema::<ApiExecResult>(),
+            { "type": "object" },
+        ] } } },
+    });
+    spec.route(
+        "post",
+        "/v1/actions",
+        json!({
+            "summary": "Run one action",
+            "description": "The scope an action needs depends on the action; see the token scopes table.",
+            "requestBody": spec.json_body::<ActionRequest>(),
+            "responses": {
+                "200": action_result,
+                "400": spec.error_response("The action failed"),
+            },
+        }),
+    );
+    spec.route(
+        "post",
+        "/v1/actions/batch",
+        json!({
+            "summary": "Run a list of actions in one daemon turn",
+            "description": "Each step is an action; any field may be set to `{\"$ref\": \"N.path\"}` to use a value from step N's result.",
+            "requestBody": spec.json_body::<ActionBatchRequest>(),
+            "responses": {
+                "200": spec.json_response::<ApiBatchResult>("Every step succeeded"),
+                "400": spec.json_response::<ApiBatchResult>("A step failed"),
+            },
+        }),
+    );
+    spec.route(
+        "post",
+        "/v1/files/download",
+        json!({
+            "summary": "Stream a file from the daemon's host",
+            "x-scope": "read",
+            "requestBody": spec.json_body::<FileDownloadRequest>(),
+            "responses": {
+                "200": { "description": "File contents", "content": { "application/octet-stream": {} } },
+            },
+        }),
+    );
+    spec.route(
+        "post",
+        "/v1/upload",
+        json!({
+            "summary": "Stream a file into a project or scoped directory",
+            "x-scope": "files",
+            "parameters": spec.query_params::<FileUploadRequest>(),
+            "requestBody": {
+                "required": true,
+                "content": { "application/octet-stream": {} },
+            },
+            "responses": {
+                "200": spec.json_response::<FileUploadResponse>("Written"),
+                "409": spec.error_response("The file exists and `conflict` is `fail`"),
+                "413": spec.error_response("Larger than the upload limit"),
+            },
+        }),
+    );
+    for (kind, summary) in [
+        ("paste-image", "Save an image and type its path"),
+        ("paste-file", "Save a file and type its path"),
+    ] {
+        let mut parameters = vec![terminal_id_param()];
+        if kind == "paste-file" {
+            parameters.push(json!({
+                "name": "x-okena-file-extension",
+                "in": "header",
+                "description": "Extension for the saved file",
+                "schema": { "type": "string" },
+            }));
+        }
+        let op = json!({
+            "summary": summary,
+            "x-scope": "input",
+            "parameters": parameters,
+            "requestBody": {
+                "required": true,
+                "content": { "application/octet-stream": {} },
+            },
+            "responses": {
+                "200": json_content("Path the file was written to", path_result()),
+            },
+        });
+        spec.route("post", &format!("/v1/terminals/{{terminal_id}}/{kind}"), op);
+    }
+    spec.route(
+        "post",
+        "/v1/terminals/{terminal_id}/wait-for",
+        json!({
+            "summary": "Wait for terminal output to match a regex",
+            "x-scope": "read",
+            "parameters": [terminal_id_param()],
+            "requestBody": spec.json_body::<ApiWaitForRequest>(),
+            "responses": {
+                "200": spec.json_response::<ApiOutputMatch>("Matched"),
+                "408": spec.error_response("Timed out"),
+            },
+        }),
+    );
+    spec.route(
+        "get",
+        "/v1/stream",
+        json!({
+            "summary": "WebSocket: PTY output and state notifications",
+            "description": "Binary frames are `[u8 proto_version=1][u8 frame_type][u32 stream_id BE][payload]`: \
+                            1 = PTY output and 2 = screen snapshot (server to client), 3 = input (client to server).",
+            "x-scope": "read",
+            "parameters": [
+                { "name": "token", "in": "query", "schema": { "type": "string" },
+                  "description": "Bearer token; otherwise send an `auth` message first" },
+                { "name": "state_deltas", "in": "query", "schema": { "type": "string", "enum": ["1"] },
+                  "description": "Receive `state_delta` in place of `state_changed`" },
+            ],
+            "x-websocket": {
+                "inbound": spec.schema::<WsInbound>(),
+                "outbound": spec.schema::<WsOutbound>(),
+            },
+            "responses": { "101": { "description": "Switching protocols" } },
+        }),
+    );
+    spec.route(
+        "get",
+        "/v1/forward",
+        json!({
+            "summary": "WebSocket: relay a TCP connection to a service's detected port",
+            "description": "Send an `auth` message first. After a `ready` reply, binary frames carry the \
+                            raw TCP stream both ways; an `error` reply is followed by a close.",
+            "x-scope": "services",
+            "parameters": spec.query_params::<PortForwardQuery>(),
+            "x-websocket": {
+                "inbound": spec.schema::<WsInbound>(),
+                "outbound": spec.schema::<ForwardReply>(),
+            },
+            "responses": { "101": { "description": "Switching protocols" } },
+        }),
+    );
+    spec.route(
+        "get",
+        "/v1/events",
+        json!({
+            "summary": "NDJSON stream of workspace events",
+            "description": "One event per line; a blank line every 15 seconds is a heartbeat.",
+            "x-scope": "read",
+            "parameters": [
+                { "name": "type", "in": "query", "schema": { "type": "string" },
+                  "description": "Comma-separated event types" },
+                { "name": "project", "in": "query", "schema": { "type": "string" },
+                  "description": "Comma-separated project ids" },
+            ],
+            "responses": {
+                "200": {
+                    "description": "Event stream",
+                    "content": { "application/x-ndjson": { "schema": spec.schema::<ApiEvent>() } },
+                },
+            },
+        }),
+    );
+    spec.route(
+        "post",
+        "/v1/refresh",
+        json!({
+            "summary": "Swap the caller's token for a fresh one",
+            "responses": {
+                "200": spec.json_response::<PairResponse>("New token"),
+                "401": spec.error_response("Token cannot be refreshed"),
+            },
+        }),
+    );
+    let token_info = json!({
+        "type": "object",
+        "required": ["id", "created_at", "last_used_at", "expires_at"],
+        "properties": {
+            "id": { "type": "string" },
+            "created_at": { "type": "integer", "description": "Unix seconds" },
+            "last_used_at": { "type": "integer", "description": "Unix seconds" },
+            "expires_at": { "type": "integer", "description": "Unix seconds" },
+            "name": { "type": ["string", "null"] },
+            "grant": spec.schema::<TokenGrant>(),
+        },
+    });
+    spec.route(
+        "get",
+        "/v1/tokens",
+        json!({
+            "summary": "Paired devices",
+            "x-scope": "admin",
+            "responses": {
+                "200": json_content("Stored tokens", wrapped("tokens", json!({ "type": "array", "items": token_info }))),
+            },
+        }),
+    );
+    spec.route(
+        "get",
+        "/v1/tokens/self",
+        json!({
+            "summary": "The calling token's grant",
+            "x-scope": "read",
+            "responses": {
+                "200": json_content("Scopes and project limits", wrapped("grant", spec.schema::<TokenGrant>())),
+            },
+        }),
+    );
+    spec.route(
+        "delete",
+        "/v1/tokens/{id}",
+        json!({
+            "summary": "Revoke a token",
+            "x-scope": "admin",
+            "parameters": [path_param("id")],
+            "responses": {
+                "200": json_content("Revoked", wrapped("revoked", json!({ "type": "boolean" }))),
+            },
+        }),
+    );
+    let audit_entries = json!({ "type": "array", "items": spec.schema::<AuditEntry>() });
+    spec.route(
+        "get",
+        "/v1/audit",
+        json!({
+            "summary": "Audit log entries, oldest first",
+            "x-scope": "admin",
+            "parameters": spec.query_params::<AuditQuery>(),
+            "responses": {
+                "200": json_content("Matching entries", wrapped("entries", audit_entries)),
+            },
+        }),
+    );
+    let shares = json!({ "type": "array", "items": spec.schema::<ApiShare>() });
+    spec.route(
+        "get",
+        "/v1/shares",
+        json!({
+            "summary": "Live share links",
+            "x-scope": "admin",
+            "responses": { "200": json_content("Shares with viewer counts", wrapped("shares", shares)) },
+        }),
+    );
+    spec.route(
+        "post",
+        "/v1/shares",
+        json!({
+            "summary": "Create a view-only share link",
+            "x-scope": "admin",
+            "requestBody": spec.json_body::<ApiShareRequest>(),
+            "responses": {
+                "201": spec.json_response::<ApiShareCreated>("Share created"),
+                "404": spec.error_response("No such project or terminal"),
+            },
+        }),
+    );
+    spec.route(
+        "delete",
+        "/v1/shares/{id}",
+        json!({
+            "summary": "Revoke a share link and disconnect its viewers",
+            "x-scope": "admin",
+            "parameters": [path_param("id")],
+            "responses": {
+                "200": json_content("Revoked", wrapped("revoked", json!({ "type": "boolean" }))),
+                "404": spec.error_response("No such share"),
+            },
+        }),
+    );
+    let pair_code = json!({
+        "type": "object",
+        "required": ["code", "expires_in"],
+        "properties": {
+            "code": { "type": "string" },
+            "expires_in": { "type": "integer", "description": "Seconds" },
+        },
+    });
+    spec.route(
+        "post",
+        "/v1/pair-code",
+        json!({
+            "summary": "Show a pairing code for a new device",
+            "description": "Same-host callers only. The body is the grant the paired token receives; omit it for full access.",
+            "x-scope": "admin",
+            "requestBody": { "content": { "application/json": { "schema": spec.schema::<TokenGrant>() } } },
+            "responses": { "200": json_content("Pairing code", pair_code) },
+        }),
+    );
+    spec.route(
+        "delete",
+        "/v1/pair-code",
+        json!({
+            "summary": "Withdraw the pairing code",
+            "description": "Same-host callers only.",
+            "x-scope": "admin",
+            "responses": { "204": { "description": "Withdrawn" } },
+        }),
+    );
+
+    spec.finish()
+}
+
+/// An OpenAPI document under construction. Schemas referenced while adding
+/// routes are collected into `components.schemas`.
+///
+/// Interior mutability lets an operation's `json!` body call back into the
+/// spec that [`Spec::route`] is adding it to.
+struct Spec {
+    generator: RefCell<SchemaGenerator>,
+    paths: RefCell<Map<String, Value>>,
+}
+
+impl Spec {
+    fn new() -> Self {
+        let generator = SchemaSettings::draft2020_12()
+            .with(|settings| {
+                settings.definitions_path = "/components/schemas".into();
+                settings.meta_schema = None;
+            })
+            .into_generator();
+        Self {
+            generator: RefCell::new(generator),
+            paths: RefCell::new(Map::new()),
+        }
+    }
+
+    fn schema<T: JsonSchema>(&self) -> Value {
+        self.generator.borrow_mut().subschema_for::<T>().to_value()
+    }
+
+    fn json_body<T: JsonSchema>(&self) -> Value {
+        json!({
+            "required": true,
+            "content": { "application/json": { "schema": self.schema::<T>() } },
+        })
+    }
+
+    fn json_response<T: JsonSchema>(&self, description: &str) -> Value {
+        json_content(description, self.schema::<T>())
+    }
+
+    fn error_response(&self, description: &str) -> Value {
+        self.json_response::<ErrorResponse>(description)
+    }
+
+    /// One query parameter per property of `T`, which must be a flat struct.
+    fn query_params<T: JsonSchema>(&self) -> Value {
+        let schema = SchemaSettings::draft2020_12()
+            .with(|settings| settings.inline_subschemas = true)
+            .into_generator()
+            .into_root_schema_for::<T>()
+            .to_value();
+        let required = schema.get("required").cloned().unwrap_or_default();
+        let properties = schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default();
+        properties
+            .into_iter()
+            .map(|(name, mut property)| {
+                let description = property
+                    .as_object_mut()
+                    .and_then(|property| property.remove("description"));
+                let mut param = json!({
+                    "name": name,
+                    "in": "query",
+                    "required": required.as_array().is_some_and(|r| r.contains(&json!(name))),
+                    "schema": property,
+                });
+                if let Some(description) = description {
+                    param["description"] = description;
+                }
+                param
+            })
+            .collect()
+    }
+
+    /// Add an operation. Every operation can fail with an [`ErrorResponse`].
+    fn route(&self, method: &str, path: &str, mut operation: Value) {
+        operation["responses"]["default"] = self.error_response("Error");
+        let mut paths = self.paths.borrow_mut();
+        let item = paths
+            .entry(path.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+        item[method] = operation;
+    }
+
+    fn finish(self) -> Value {
+        json!({
+            "openapi": "3.1.0",
+            "info": {
+                "title": "Okena remote API",
+                "version": API_VERSION,
+                "description": "Control a running Okena daemon: workspace state, terminal input and output, and events.",
+            },
+            "security": [{ "bearer": [] }],
+            "paths": self.paths.into_inner(),
+            "components": {
+                "securitySchemes": { "bearer": { "type": "http", "scheme": "bearer" } },
+                "schemas": self.generator.into_inner().take_definitions(true),
+            },
+        })
+    }
+}
+
+fn json_content(description: &str, schema: Value) -> Value {
+    json!({
+        "description": description,
+        "content": { "application/json": { "schema": schema } },
+    })
+}
+
+/// `{ "<key>": <schema> }`, the shape of the list and flag replies.
+fn wrapped(key: &str, schema: Value) -> Value {
+    json!({
+        "type": "object",
+        "required": [key],
+        "properties": { key: schema },
+    })
+}
+
+fn path_result() -> Value {
+    wrapped("path", json!({ "type": "string" }))
+}
+
+fn path_param(name: &str) -> Value {
+    json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } })
+}
+
+fn terminal_id_param() -> Value {
+    path_param("terminal_id")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn checked_in_path() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../docs/reference/openapi.json")
+    }
+
+    #[test]
+    fn checked_in_openapi_is_current() {
+        let generated = format!(
+            "{}\n",
+            serde_json::to_string_pretty(&openapi()).expect("document serializes")
+        );
+        let path = checked_in_path();
+        if std::env::var_os("OKENA_UPDATE_SCHEMA").is_some() {
+            std::fs::write(&path, &generated).expect("write openapi.json");
+            return;
+        }
+        let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            checked_in == generated,
+            "docs/reference/openapi.json is out of date with the wire types; \
+             regenerate it with `OKENA_UPDATE_SCHEMA=1 cargo test -p okena-core schema`"
+        );
+    }
+
+    /// Tags of every `ActionRequest` variant, read from its declaration so a
+    /// new variant is covered without touching this test.
+    fn declared_actions() -> Vec<String> {
+        let source = include_str!("api.rs");
+        let start = source
+            .find("pub enum ActionRequest {")
+            .expect("ActionRequest is declared in api.rs");
+        let body = &source[start..];
+        let body = &body[..body.find("\n}").expect("enum closes")];
+        body.lines()
+            .filter_map(|line| line.strip_prefix("    "))
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_uppercase()))
+            .map(|line| {
+                let name = line
+                    .split(|c: char| !c.is_ascii_alphanumeric())
+                    .next()
+                    .unwrap_or_default();
+                let mut tag = String::new();
+                for (i, c) in name.chars().enumerate() {
+                    if c.is_ascii_uppercase() && i > 0 {
+                        tag.push('_');
+                    }
+                    tag.push(c.to_ascii_lowercase());
+                }
+                tag
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_action_is_in_the_document() {
+        let doc = openapi();
+        let variants = doc["components"]["schemas"]["ActionRequest"]["oneOf"]
+            .as_array()
+            .expect("ActionRequest is a tagged enum");
+        let mut documented: Vec<String> = variants
+            .iter()
+            .filter_map(|v| v["properties"]["action"]["const"].as_str())
+            .map(str::to_string)
+            .collect();
+        let mut declared = declared_actions();
+        assert!(declared.len() > 50, "scan found {declared:?}");
+        documented.sort();
+        declared.sort();
+        assert_eq!(documented, declared);
+    }
+
+    #[test]
+    fn every_root_has_a_standalone_schema() {
+        for name in type_names() {
+            let schema = json_schema(name).expect("root resolves");
+            assert_eq!(
+                schema["$schema"],
+                "https://json-schema.org/draft/2020-12/schema"
+            );
+            assert_eq!(schema["title"], name);
+        }
+        assert!(json_schema("actionrequest").is_some());
+        assert!(json_schema("NoSuchType").is_none());
+    }
+
+    #[test]
+    fn hook_steps_accept_strings_and_objects() {
+        let schema = json_schema("StateResponse").expect("root resolves");
+        let defs = &schema["$defs"];
+        assert!(defs.get("HookSteps").is_some());
+        let step = defs["HookStep"]["anyOf"].as_array().expect("untagged");
+        assert_eq!(step[0]["type"], "string");
+        assert_eq!(step[1]["$ref"], "#/$defs/HookStepObject");
+    }
+}
@@ -0,0 +1,767 @@
+//! What a paired remote token may do.
+//!
+//! A [`TokenGrant`] is chosen when the pairing code is issued (pair dialog or
+//! `okena pair --scope`) and travels with the token for its whole life,
+//! refreshes included. The server classifies every request with [`classify`]
+//! and checks it with [`TokenGrant::authorize`].
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::api::{ActionRequest, StateResponse, TerminalPlacement};
+use crate::shell::ShellType;
+
+/// One capability a token can carry. `admin` implies every other scope and
+/// `read` is implied by any scope.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub enum Scope {
+    /// Workspace state, terminal output and events.
+    #[serde(rename = "read")]
+    Read,
+    /// Keystrokes, commands and the terminal layout.
+    #[serde(rename = "terminal:input")]
+    TerminalInput,
+    /// Git status, diffs, branches and worktrees.
+    #[serde(rename = "git")]
+    Git,
+    /// Project files: browse, read, download, edit.
+    #[serde(rename = "files")]
+    Files,
+    /// Start, stop and rebuild services.
+    #[serde(rename = "services")]
+    Services,
+    /// Projects, folders, sessions, settings, themes and paired devices.
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 6] = [
+        Scope::Read,
+        Scope::TerminalInput,
+        Scope::Git,
+        Scope::Files,
+        Scope::Services,
+        Scope::Admin,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::TerminalInput => "terminal:input",
+            Scope::Git => "git",
+            Scope::Files => "files",
+            Scope::Services => "services",
+            Scope::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Scope::ALL
+            .into_iter()
+            .find(|scope| scope.as_str() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Scope::ALL.iter().map(|scope| scope.as_str()).collect();
+                format!(
+                    "unknown scope '{s}' (expected one of: {})",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// The scopes and projects a token is limited to. The default grant is full
+/// access, which is what tokens paired before scopes existed get.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TokenGrant {
+    pub scopes: BTreeSet<Scope>,
+    /// Project ids the token may touch. Empty together with `folders` means
+    /// every project.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+    /// Folder ids whose projects the token may touch.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub folders: Vec<String>,
+    /// Terminal ids the token may reach within those projects. Empty means
+    /// all of them; set for share links to a single terminal.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub terminals: Vec<String>,
+}
+
+impl Default for TokenGrant {
+    fn default() -> Self {
+        Self {
+            scopes: BTreeSet::from([Scope::Admin]),
+            projects: Vec::new(),
+            folders: Vec::new(),
+            terminals: Vec::new(),
+        }
+    }
+}
+
+impl TokenGrant {
+    /// View-only: state, terminal output and events.
+    pub fn read_only() -> Self {
+        Self {
+            scopes: BTreeSet::from([Scope::Read]),
+            ..Self::default()
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn is_project_restricted(&self) -> bool {
+        !self.projects.is_empty() || !self.folders.is_empty() || !self.terminals.is_empty()
+    }
+
+    pub fn allows(&self, scope: Scope) -> bool {
+        scope == Scope::Read || self.scopes.contains(&Scope::Admin) || self.scopes.contains(&scope)
+    }
+
+    /// Whether `other` grants nothing beyond this grant, so a token holding
+    /// this one may hand `other` out.
+    pub fn covers(&self, other: &TokenGrant) -> bool {
+        if !other.scopes.iter().all(|scope| self.allows(*scope)) {
+            return false;
+        }
+        if !self.is_project_restricted() {
+            return true;
+        }
+        let within = |mine: &[String], theirs: &[String]| theirs.iter().all(|id| mine.contains(id));
+        other.is_project_restricted()
+            && within(&self.projects, &other.projects)
+            && within(&self.folders, &other.folders)
+            && (self.terminals.is_empty()
+                || (!other.terminals.is_empty() && within(&self.terminals, &other.terminals)))
+    }
+
+    /// `"read, git (2 projects)"` for device lists.
+    pub fn summary(&self) -> String {
+        let scopes: Vec<&str> = self.scopes.iter().map(|scope| scope.as_str()).collect();
+        let mut summary = scopes.join(", ");
+        let limits = self.projects.len() + self.folders.len();
+        if limits > 0 {
+            let noun = if limits == 1 { "project" } else { "projects" };
+            summary.push_str(&format!(" ({limits} {noun})"));
+        }
+        summary
+    }
+
+    /// Check one request. `projects` must be given for a project-restricted
+    /// grant; without it such a grant only passes actions that touch no
+    /// project.
+    pub fn authorize(
+        &self,
+        action: &ActionRequest,
+        projects: Option<&ProjectAccess>,
+    ) -> Result<(), String> {
+        let (scope, target) = classify(action);
+        if !self.allows(scope) {
+            return Err(format!("token lacks the '{scope}' scope"));
+        }
+        if !self.is_project_restricted() {
+            return Ok(());
+        }
+        let allowed = match (target, projects) {
+            (Target::None, _) => true,
+            (Target::Workspace, _) | (Target::Ids { .. }, None) => false,
+            (
+                Target::Ids {
+                    projects,
+                    terminals,
+                },
+                Some(access),
+            ) => {
+                projects.iter().all(|id| access.allows_project(id))
+                    && terminals.iter().all(|id| access.allows_terminal(id))
+            }
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err("token is not allowed to access this project".to_string())
+        }
+    }
+}
+
+/// What an action touches, for project-restricted tokens.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Target<'a> {
+    /// No project data: themes, the settings schema, the action list.
+    None,
+    /// Every project at once, or none in particular: the project list, paths,
+    /// hook runs, sessions, settings. Refused for restricted tokens.
+    Workspace,
+    /// These projects and the projects of these terminals.
+    Ids {
+        projects: Vec<&'a str>,
+        terminals: Vec<&'a str>,
+    },
+}
+
+/// The scope an action needs and what it touches.
+pub fn classify(action: &ActionRequest) -> (Scope, Target<'_>) {
+    use ActionRequest as A;
+
+    fn project(id: &str) -> Target<'_> {
+        Target::Ids {
+            projects: vec![id],
+            terminals: Vec::new(),
+        }
+    }
+    fn terminal(id: &str) -> Target<'_> {
+        Target::Ids {
+            projects: Vec::new(),
+            terminals: vec![id],
+        }
+    }
+    match action {
+        A::ReadContent { terminal_id, .. } | A::ExportBuffer { terminal_id } => {
+            (Scope::Read, terminal(terminal_id))
+        }
+        A::RecordProjectActivity { project_id } => (Scope::Read, project(project_id)),
+        A::GetSettingsSchema | A::GetThemes | A::GetTheme { .. } | A::ListActions => {
+            (Scope::Read, Target::None)
+        }
+        A::ListHookRuns { .. }
+        | A::GetHookRun { .. }
+        | A::DiffHookRuns { .. }
+        | A::ListSessions => (Scope::Read, Target::Workspace),
+
+        A::SendText { terminal_id, .. }
+        | A::SendBytes { terminal_id, .. }
+        | A::RunCommand { terminal_id, .. }
+        | A::SendSpecialKey { terminal_id, .. }
+        | A::UndoSoftClose { terminal_id }
+        | A::CloseTerminalNow { terminal_id }
+        | A::ExecInTerminal { terminal_id, .. }
+        | A::Resize { terminal_id, .. } => (Scope::TerminalInput, terminal(terminal_id)),
+        // A split / tab anchor must belong to a reachable project too. A custom
+        // shell names any host executable, so only admin tokens pick one, here
+        // and when switching a terminal's shell below.
+        A::CreateTerminal {
+            project_id,
+            shell,
+            placement,
+            ..
+        } => {
+            let terminals = match placement {
+                Some(
+                    TerminalPlacement::Split { terminal_id, .. }
+                    | TerminalPlacement::Tab { terminal_id },
+                ) => vec![terminal_id.as_str()],
+                Some(TerminalPlacement::Detached) | None => Vec::new(),
+            };
+            let scope = match shell {
+                Some(ShellType::Custom { .. }) => Scope::Admin,
+                _ => Scope::TerminalInput,
+            };
+            (
+                scope,
+                Target::Ids {
+                    projects: vec![project_id],
+                    terminals,
+                },
+            )
+        }
+        A::SwitchTerminalShell {
+            project_id, shell, ..
+        } => {
+            let scope = match shell {
+                ShellType::Custom { .. } => Scope::Admin,
+                _ => Scope::TerminalInput,
+            };
+            (scope, project(project_id))
+        }
+        A::SplitTerminal { project_id, .. }
+        | A::CloseTerminal { project_id, .. }
+        | A::CloseTerminals { project_id, .. }
+        | A::FocusTerminal { project_id, .. }
+        | A::UpdateSplitSizes { project_id, .. }
+        | A::ToggleMinimized { project_id, .. }
+        | A::SetFullscreen { project_id, .. }
+        | A::RenameTerminal { project_id, .. }
+        | A::AddTab { project_id, .. }
+        | A::SetActiveTab { project_id, .. }
+        | A::MoveTab { project_id, .. }
+        | A::RerunHook { project_id, .. }
+        | A::DismissHook { project_id, .. }
+        // An interactive shell inside a container runs arbitrary code, so it
+        // needs the same scope as typing into a terminal.
+        | A::ExecServiceShell { project_id, .. } => (Scope::TerminalInput, project(project_id)),
+        A::MoveTerminalToTabGroup {
+            project_id,
+            target_project_id,
+            ..
+        } => {
+            let mut projects = vec![project_id.as_str()];
+            projects.extend(target_project_id.as_deref());
+            (
+                Scope::TerminalInput,
+                Target::Ids {
+                    projects,
+                    terminals: Vec::new(),
+                },
+            )
+        }
+        A::MovePaneTo {
+            project_id,
+            target_project_id,
+            ..
+        } => (
+            Scope::TerminalInput,
+            Target::Ids {
+                projects: vec![project_id, target_project_id],
+                terminals: Vec::new(),
+            },
+        ),
+        A::CancelHook { .. } => (Scope::TerminalInput, Target::Workspace),
+
+        A::GitStatus { project_id }
+        | A::GitDiffSummary { project_id }
+        | A::GitDiff { project_id, .. }
+        | A::GitBranches { project_id }
+        | A::GitListPullRequests { project_id, .. }
+        | A::GitFileContents { project_id, .. }
+        | A::GitCommitGraph { project_id, .. }
+        | A::GitListBranches { project_id }
+        | A::GitListWorktrees { project_id }
+        | A::WorktreeCloseInfo { project_id }
+        | A::GenerateWorktreeBranchName { project_id }
+        | A::GitListBranchesClassified { project_id }
+        | A::GitCheckoutLocalBranch { project_id, .. }
+        | A::GitCheckoutRemoteBranch { project_id, .. }
+        | A::GitCreateAndCheckoutBranch { project_id, .. }
+        | A::GitStageFile { project_id, .. }
+        | A::GitUnstageFile { project_id, .. }
+        | A::GitDiscardFile { project_id, .. }
+        | A::GitBlame { project_id, .. }
+        | A::CreateWorktree { project_id, .. }
+        | A::RemoveWorktreeProject { project_id, .. }
+        | A::ForceRemoveWorktreeProject { project_id }
+        | A::CloseWorktree { project_id, .. } => (Scope::Git, project(project_id)),
+        A::AddDiscoveredWorktree {
+            parent_project_id, ..
+        } => (Scope::Git, project(parent_project_id)),
+
+        A::ListFiles { project_id, .. }
+        | A::ListDirectory { project_id, .. }
+        | A::ReadFile { project_id, .. }
+        | A::ReadFileBytes { project_id, .. }
+        | A::ResolveProjectPath { project_id, .. }
+        | A::FileSize { project_id, .. }
+        | A::SearchContent { project_id, .. }
+        | A::RenameFile { project_id, .. }
+        | A::DeleteFile { project_id, .. }
+        | A::CreateFile { project_id, .. }
+        | A::CreateDirectory { project_id, .. } => (Scope::Files, project(project_id)),
+        A::ResolveTerminalPath { terminal_id, .. }
+        | A::ReadTerminalFile { terminal_id, .. }
+        | A::ReadTerminalFileBytes { terminal_id, .. }
+        | A::TerminalFileSize { terminal_id, .. } => (Scope::Files, terminal(terminal_id)),
+        A::ResolvePath { .. }
+        | A::ResolvePathInScope { .. }
+        | A::ListPathFiles { .. }
+        | A::ListPathDirectory { .. }
+        | A::ReadPathFile { .. }
+        | A::ReadPathFileBytes { .. }
+        | A::PathFileSize { .. }
+        | A::SearchPathContent { .. }
+        | A::RenamePath { .. }
+        | A::DeletePath { .. } => (Scope::Files, Target::Workspace),
+
+        A::StartService { project_id, .. }
+        | A::StopService { project_id, .. }
+        | A::RestartService { project_id, .. }
+        | A::RebuildService { project_id, .. }
+        | A::PullService { project_id, .. }
+        | A::StartAllServices { project_id }
+        | A::StopAllServices { project_id }
+        | A::ReloadServices { project_id } => (Scope::Services, project(project_id)),
+
+        A::SetProjectColor { project_id, .. }
+        | A::RenameProject { project_id, .. }
+        | A::UpdateProjectHooks { project_id, .. }
+        | A::RenameProjectDirectory { project_id, .. }
+        | A::DeleteProject { project_id }
+        | A::SetProjectShowInOverview { project_id, .. }
+        | A::ToggleProjectPinned { project_id }
+        | A::SetWorktreeColorOverride { project_id, .. } => (Scope::Admin, project(project_id)),
+        A::AddProject { .. }
+        | A::CloneProject { .. }
+        | A::ReorderProjectInFolder { .. }
+        | A::SetFolderColor { .. }
+        | A::CreateFolder { .. }
+        | A::DeleteFolder { .. }
+        | A::RenameFolder { .. }
+        | A::MoveProjectToFolder { .. }
+        | A::MoveProjectOutOfFolder { .. }
+        | A::MoveProject { .. }
+        | A::MoveItemInOrder { .. }
+        | A::ReorderWorktree { .. }
+        | A::LoadSession { .. }
+        | A::SaveSession { .. }
+        | A::RenameSession { .. }
+        | A::DeleteSession { .. }
+        | A::ImportWorkspace { .. }
+        | A::ExportWorkspace { .. }
+        | A::GetSettings
+        | A::SetSettings { .. }
+        | A::SetTheme { .. }
+        | A::SaveCustomTheme { .. }
+        | A::InvokeAction { .. } => (Scope::Admin, Target::Workspace),
+    }
+}
+
+/// The projects and terminals a restricted grant reaches in one state
+/// snapshot. Worktrees of an allowed project are allowed too.
+#[derive(Debug, Default)]
+pub struct ProjectAccess {
+    projects: HashSet<String>,
+    terminal_projects: HashMap<String, String>,
+    /// The grant's terminal allow-list, if it has one.
+    terminals: Option<HashSet<String>>,
+}
+
+impl ProjectAccess {
+    pub fn new(grant: &TokenGrant, state: &StateResponse) -> Self {
+        let mut projects: HashSet<String> = grant.projects.iter().cloned().collect();
+        for folder in &state.folders {
+            if grant.folders.contains(&folder.id) {
+                projects.extend(folder.project_ids.iter().cloned());
+            }
+        }
+        for project in &state.projects {
+            if projects.contains(&project.id) {
+                projects.extend(project.worktree_ids.iter().cloned());
+            }
+        }
+        let terminal_projects = state
+            .projects
+            .iter()
+            .flat_map(|project| {
+                let layout_ids = project
+                    .layout
+                    .as_ref()
+                    .map(|layout| layout.collect_terminal_ids())
+                    .unwrap_or_default();
+                layout_ids
+                    .into_iter()
+                    .chain(project.terminal_names.keys().cloned())
+                    .map(|terminal_id| (terminal_id, project.id.clone()))
+            })
+            .collect();
+        Self {
+            projects,
+            terminal_projects,
+            terminals: (!grant.terminals.is_empty())
+                .then(|| grant.terminals.iter().cloned().collect()),
+        }
+    }
+
+    pub fn allows_project(&self, project_id: &str) -> bool {
+        self.projects.contains(project_id)
+    }
+
+    pub fn project_ids(&self) -> impl Iterator<Item = &String> {
+        self.projects.iter()
+    }
+
+    pub fn allows_terminal(&self, terminal_id: &str) -> bool {
+        self.terminals
+            .as_ref()
+            .is_none_or(|terminals| terminals.contains(terminal_id))
+            && self
+                .terminal_projects
+                .get(terminal_id)
+                .is_some_and(|project_id| self.allows_project(project_id))
+    }
+
+    /// Drop every project, folder entry and window focus the grant can't see.
+    pub fn filter_state(&self, state: &mut StateResponse) {
+        state
+            .projects
+            .retain(|project| self.allows_project(&project.id));
+        state.project_order.retain(|id| self.allows_project(id));
+        for folder in &mut state.folders {
+            folder.project_ids.retain(|id| self.allows_project(id));
+        }
+        state
+            .folders
+            .retain(|folder| !folder.project_ids.is_empty());
+        if state
+            .focused_project_id
+            .as_deref()
+            .is_some_and(|id| !self.allows_project(id))
+        {
+            state.focused_project_id = None;
+        }
+        if state
+            .fullscreen_terminal
+            .as_ref()
+            .is_some_and(|fullscreen| !self.allows_project(&fullscreen.project_id))
+        {
+            state.fullscreen_terminal = None;
+        }
+        state.hooks.retain(|hook| {
+            hook.terminal_id
+                .as_deref()
+                .is_some_and(|id| self.allows_terminal(id))
+        });
+        if self.terminals.is_some() {
+            for project in &mut state.projects {
+                project
+                    .terminal_names
+                    .retain(|id, _| self.allows_terminal(id));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_round_trip_through_their_names() {
+        for scope in Scope::ALL {
+            assert_eq!(scope.as_str().parse::<Scope>(), Ok(scope));
+            assert_eq!(
+                serde_json::to_value(scope).unwrap(),
+                serde_json::json!(scope.as_str())
+            );
+        }
+        assert!("write".parse::<Scope>().is_err());
+    }
+
+    #[test]
+    fn grants_without_admin_only_pass_their_scopes() {
+        let grant = TokenGrant::read_only();
+        let input = ActionRequest::SendText {
+            terminal_id: "t1".into(),
+            text: "ls".into(),
+        };
+        let read = ActionRequest::ReadContent {
+            terminal_id: "t1".into(),
+            lines: None,
+            all: false,
+            ansi: false,
+            since: None,
+            strip_prompt: false,
+        };
+        assert!(grant.authorize(&input, None).is_err());
+        assert!(grant.authorize(&read, None).is_ok());
+        assert!(TokenGrant::default().authorize(&input, None).is_ok());
+    }
+
+    #[test]
+    fn service_shells_need_terminal_input() {
+        let shell = ActionRequest::ExecServiceShell {
+            project_id: "p1".into(),
+            service_name: "web".into(),
+            shell: None,
+        };
+        assert_eq!(classify(&shell).0, Scope::TerminalInput);
+        let services_only = TokenGrant {
+            scopes: BTreeSet::from([Scope::Services]),
+            ..TokenGrant::default()
+        };
+        assert!(services_only.authorize(&shell, None).is_err());
+    }
+
+    #[test]
+    fn custom_shells_need_admin() {
+        let create = |shell| ActionRequest::CreateTerminal {
+            project_id: "p1".into(),
+            shell,
+            cwd: None,
+            command: None,
+            env: Default::default(),
+            name: None,
+            minimized: false,
+            placement: None,
+        };
+        let input = TokenGrant {
+            scopes: BTreeSet::from([Scope::TerminalInput]),
+            ..TokenGrant::default()
+        };
+        assert!(input.authorize(&create(None), None).is_ok());
+        let custom = create(Some(ShellType::Custom {
+            path: "/tmp/payload".into(),
+            args: Vec::new(),
+        }));
+        assert_eq!(classify(&custom).0, Scope::Admin);
+        assert!(input.authorize(&custom, None).is_err());
+        assert!(TokenGrant::default().authorize(&custom, None).is_ok());
+
+        let switch = |shell| ActionRequest::SwitchTerminalShell {
+            project_id: "p1".into(),
+            terminal_id: "t1".into(),
+            shell,
+        };
+        assert!(input.authorize(&switch(ShellType::Default), None).is_ok());
+        let custom = switch(ShellType::Custom {
+            path: "/tmp/payload".into(),
+            args: Vec::new(),
+        });
+        assert_eq!(classify(&custom).0, Scope::Admin);
+        assert!(input.authorize(&custom, None).is_err());
+    }
+
+    #[test]
+    fn restricted_grants_reach_only_their_projects() {
+        let project = |id: &str, terminal: &str| {
+            serde_json::json!({
+                "id": id,
+                "name": id,
+                "path": format!("/tmp/{id}"),
+                "show_in_overview": true,
+                "layout": null,
+                "terminal_names": { terminal: "shell" },
+                "git_status": null,
+            })
+        };
+        let mut state: StateResponse = serde_json::from_value(serde_json::json!({
+            "state_version": 1,
+            "projects": [project("web", "t-web"), project("api", "t-api")],
+            "focused_project_id": "api",
+            "fullscreen_terminal": null,
+            "project_order": ["web", "api"],
+            "folders": [{ "id": "f1", "name": "Work", "project_ids": ["api"] }],
+        }))
+        .unwrap();
+        let grant = TokenGrant {
+            scopes: BTreeSet::from([Scope::TerminalInput]),
+            projects: vec!["web".into()],
+            folders: Vec::new(),
+            terminals: Vec::new(),
+        };
+        let access = ProjectAccess::new(&grant, &state);
+        let send = |terminal_id: &str| ActionRequest::SendText {
+            terminal_id: terminal_id.into(),
+            text: "ls".into(),
+        };
+
+        assert!(grant.authorize(&send("t-web"), Some(&access)).is_ok());
+        assert!(grant.authorize(&send("t-api"), Some(&access)).is_err());
+        assert!(grant.authorize(&send("t-web"), None).is_err());
+        assert!(
+            grant
+                .authorize(&ActionRequest::ListSessions, Some(&access))
+                .is_err()
+        );
+        assert!(
+            grant
+                .authorize(&ActionRequest::GetThemes, Some(&access))
+                .is_ok()
+        );
+
+        access.filter_state(&mut state);
+        assert_eq!(state.projects.len(), 1);
+        assert_eq!(state.project_order, vec!["web".to_string()]);
+        assert!(state.folders.is_empty());
+        assert_eq!(state.focused_project_id, None);
+
+        let by_folder = TokenGrant {
+            folders: vec!["f1".into()],
+            ..grant
+        };
+        let state: StateResponse = serde_json::from_value(serde_json::json!({
+            "state_version": 1,
+            "projects": [project("web", "t-web"), project("api", "t-api")],
+            "focused_project_id": null,
+            "fullscreen_terminal": null,
+            "folders": [{ "id": "f1", "name": "Work", "project_ids": ["api"] }],
+        }))
+        .unwrap();
+        let access = ProjectAccess::new(&by_folder, &state);
+        assert!(access.allows_terminal("t-api"));
+    }
+
+    #[test]
+    fn terminal_grants_reach_only_their_terminals() {
+        let mut state: StateResponse = serde_json::from_value(serde_json::json!({
+            "state_version": 1,
+            "projects": [{
+                "id": "web",
+                "name": "web",
+                "path": "/tmp/web",
+                "show_in_overview": true,
+                "layout": null,
+                "terminal_names": { "t-agent": "agent", "t-shell": "shell" },
+            }],
+            "focused_project_id": null,
+            "fullscreen_terminal": null,
+        }))
+        .unwrap();
+        let grant = TokenGrant {
+            projects: vec!["web".into()],
+            terminals: vec!["t-agent".into()],
+            ..TokenGrant::read_only()
+        };
+        let access = ProjectAccess::new(&grant, &state);
+        assert!(grant.is_project_restricted());
+        assert!(access.allows_project("web"));
+        assert!(access.allows_terminal("t-agent"));
+        assert!(!access.allows_terminal("t-shell"));
+
+        access.filter_state(&mut state);
+        assert_eq!(
+            state.projects[0].terminal_names.keys().collect::<Vec<_>>(),
+            ["t-agent"]
+        );
+    }
+
+    #[test]
+    fn grants_cover_only_narrower_grants() {
+        let input = TokenGrant {
+            scopes: BTreeSet::from([Scope::TerminalInput]),
+            projects: vec!["web".into()],
+            ..TokenGrant::default()
+        };
+        assert!(TokenGrant::default().covers(&input));
+        assert!(input.covers(&TokenGrant {
+            scopes: BTreeSet::from([Scope::Read]),
+            ..input.clone()
+        }));
+        assert!(!input.covers(&TokenGrant::default()));
+        assert!(!input.covers(&TokenGrant::read_only()));
+        assert!(!input.covers(&TokenGrant {
+            projects: vec!["web".into(), "api".into()],
+            ..input.clone()
+        }));
+        assert!(!input.covers(&TokenGrant {
+            scopes: BTreeSet::from([Scope::Git]),
+            ..input.clone()
+        }));
+    }
+
+    #[test]
+    fn legacy_tokens_deserialize_as_full_access() {
+        #[derive(Deserialize)]
+        struct Persisted {
+            #[serde(default)]
+            grant: TokenGrant,
+        }
+        let persisted: Persisted = serde_json::from_str("{}").unwrap();
+        assert!(persisted.grant.is_full());
+    }
+}
@@ -6,10 +6,11 @@
 //! into a spawnable `portable_pty::CommandBuilder` — lives in `okena-terminal`
 //! (see `ShellCommandExt::build_command`).
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Shell type for terminal creation
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(tag = "type")]
 #[derive(Default)]
 pub enum ShellType {
@@ -43,6 +44,18 @@ pub enum ShellType {
         #[serde(default)]
         args: Vec<String>,
     },
+
+    /// Interactive shell inside a Docker Compose service container. Persisted as
+    /// its own variant (rather than a `Custom` command line) so a restored
+    /// terminal re-enters the container instead of opening a host shell.
+    DockerExec {
+        /// Absolute path of the compose file the service is defined in.
+        compose_file: String,
+        service: String,
+        /// Shell to exec in the container; `None` prefers bash, falling back to sh.
+        #[serde(default)]
+        shell: Option<String>,
+    },
 }
 
 impl ShellType {
@@ -64,6 +77,34 @@ impl ShellType {
         }
     }
 
+    /// Program and argv for a `DockerExec` marker; `None` for every other variant.
+    pub fn docker_exec_command(&self) -> Option<(String, Vec<String>)> {
+        let ShellType::DockerExec {
+            compose_file,
+            service,
+            shell,
+        } = self
+        else {
+            return None;
+        };
+        let mut args = vec![
+            "compose".to_string(),
+            "-f".to_string(),
+            compose_file.clone(),
+            "exec".to_string(),
+            service.clone(),
+        ];
+        match shell {
+            Some(shell) => args.push(shell.clone()),
+            None => args.extend([
+                "sh".to_string(),
+                "-c".to_string(),
+                "command -v bash >/dev/null 2>&1 && exec bash || exec sh".to_string(),
+            ]),
+        }
+        Some(("docker".to_string(), args))
+    }
+
     /// Resolve `ShellType::Default` into a concrete shell by checking
     /// the project's default shell first, then the global setting.
     /// Non-Default variants are returned unchanged.
@@ -103,6 +144,7 @@ impl ShellType {
                     .unwrap_or(path)
                     .to_string()
             }
+            ShellType::DockerExec { service, .. } => format!("Docker ({})", service),
         }
     }
 
@@ -123,6 +165,7 @@ impl ShellType {
             #[cfg(windows)]
             ShellType::Wsl { .. } => "WSL",
             ShellType::Custom { .. } => "Custom",
+            ShellType::DockerExec { .. } => "Docker",
         }
     }
 
@@ -153,6 +196,11 @@ impl ShellType {
                     format!("{} {}", shell_quote(path), quoted_args.join(" "))
                 }
             }
+            ShellType::DockerExec { .. } => {
+                let (program, args) = self.docker_exec_command().unwrap_or_default();
+                let quoted_args: Vec<String> = args.iter().map(|a| shell_quote(a)).collect();
+                format!("{} {}", program, quoted_args.join(" "))
+            }
         }
     }
 }
@@ -0,0 +1,261 @@
+//! Incremental `StateResponse` updates pushed over the WebSocket.
+//!
+//! A client that opens the stream with [`crate::ws::STATE_DELTAS_QUERY`] gets
+//! [`crate::ws::WsOutbound::StateDelta`] instead of a bare `state_changed` for
+//! every change: the projects that changed (whole, keyed by id), the ids of
+//! those that went away, and any other top-level field that changed. A delta
+//! only applies to the exact `base_version` it was computed from; on any gap
+//! the client refetches `GET /v1/state` as before.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::api::{ApiProject, StateResponse};
+
+/// Changes between two `StateResponse`s, minus `state_version` (carried next
+/// to it in the message).
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct StateDelta {
+    /// New projects and projects whose content changed, in their new order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub upserted_projects: Vec<ApiProject>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_projects: Vec<String>,
+    /// Full id order of `projects`, sent only when applying the upserts and
+    /// removals in place would not reproduce it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_ids: Option<Vec<String>>,
+    /// Other top-level fields that changed, by name, with their new values.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub fields: Map<String, Value>,
+}
+
+impl StateDelta {
+    pub fn is_empty(&self) -> bool {
+        self.upserted_projects.is_empty()
+            && self.removed_projects.is_empty()
+            && self.project_ids.is_none()
+            && self.fields.is_empty()
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+/// The delta that turns `old` into `new`.
+pub fn diff(old: &StateResponse, new: &StateResponse) -> StateDelta {
+    let old_projects: std::collections::HashMap<&str, Value> = old
+        .projects
+        .iter()
+        .map(|project| (project.id.as_str(), to_value(project)))
+        .collect();
+    let new_ids: std::collections::HashSet<&str> = new
+        .projects
+        .iter()
+        .map(|project| project.id.as_str())
+        .collect();
+
+    let mut delta = StateDelta {
+        upserted_projects: new
+            .projects
+            .iter()
+            .filter(|project| old_projects.get(project.id.as_str()) != Some(&to_value(*project)))
+            .cloned()
+            .collect(),
+        removed_projects: old
+            .projects
+            .iter()
+            .filter(|project| !new_ids.contains(project.id.as_str()))
+            .map(|project| project.id.clone())
+            .collect(),
+        ..StateDelta::default()
+    };
+
+    let mut patched = old.projects.clone();
+    apply_projects(&mut patched, &delta);
+    let new_order: Vec<String> = new.projects.iter().map(|p| p.id.clone()).collect();
+    if patched.iter().map(|p| &p.id).ne(new_order.iter()) {
+        delta.project_ids = Some(new_order);
+    }
+
+    if let (Value::Object(old_fields), Value::Object(new_fields)) = (to_value(old), to_value(new)) {
+        for (name, value) in new_fields {
+            if name != "projects"
+                && name != "state_version"
+                && old_fields.get(&name) != Some(&value)
+            {
+                delta.fields.insert(name, value);
+            }
+        }
+    }
+    delta
+}
+
+/// Apply `delta` to `state` (which must be at the delta's base version) and
+/// stamp it with `state_version`. On error `state` may be half-updated, so
+/// the caller should refetch.
+pub fn apply(
+    state: &mut StateResponse,
+    delta: StateDelta,
+    state_version: u64,
+) -> Result<(), String> {
+    apply_projects(&mut state.projects, &delta);
+    if let Some(order) = &delta.project_ids {
+        let mut by_id: std::collections::HashMap<String, ApiProject> = state
+            .projects
+            .drain(..)
+            .map(|project| (project.id.clone(), project))
+            .collect();
+        for id in order {
+            let project = by_id
+                .remove(id)
+                .ok_or_else(|| format!("state delta orders unknown project {id}"))?;
+            state.projects.push(project);
+        }
+    }
+    // Merge through the serialized form so every `StateResponse` field is
+    // covered; a field this build doesn't know is dropped, as in a full fetch.
+    if !delta.fields.is_empty() {
+        let Value::Object(mut fields) = to_value(&*state) else {
+            return Err("state does not serialize to an object".to_string());
+        };
+        fields.extend(delta.fields);
+        *state = serde_json::from_value(Value::Object(fields))
+            .map_err(|e| format!("state delta fields: {e}"))?;
+    }
+    state.state_version = state_version;
+    Ok(())
+}
+
+/// Drop removed projects, replace changed ones where they stand and append
+/// new ones.
+fn apply_projects(projects: &mut Vec<ApiProject>, delta: &StateDelta) {
+    projects.retain(|project| !delta.removed_projects.contains(&project.id));
+    for upserted in &delta.upserted_projects {
+        match projects
+            .iter_mut()
+            .find(|project| project.id == upserted.id)
+        {
+            Some(project) => *project = upserted.clone(),
+            None => projects.push(upserted.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(id: &str, name: &str) -> ApiProject {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": name,
+            "path": format!("/src/{id}"),
+            "show_in_overview": true,
+            "layout": null,
+            "terminal_names": {},
+        }))
+        .unwrap()
+    }
+
+    fn state(version: u64, projects: Vec<ApiProject>) -> StateResponse {
+        StateResponse {
+            state_version: version,
+            project_order: projects.iter().map(|p| p.id.clone()).collect(),
+            projects,
+            focused_project_id: None,
+            fullscreen_terminal: None,
+            folders: Vec::new(),
+            windows: Vec::new(),
+            hooks: Vec::new(),
+            shares: Vec::new(),
+        }
+    }
+
+    fn round_trip(old: &StateResponse, new: &StateResponse) -> StateDelta {
+        let delta = diff(old, new);
+        let wire: StateDelta =
+            serde_json::from_str(&serde_json::to_string(&delta).unwrap()).unwrap();
+        let mut patched = old.clone();
+        apply(&mut patched, wire, new.state_version).unwrap();
+        assert_eq!(to_value(&patched), to_value(new));
+        delta
+    }
+
+    #[test]
+    fn rename_sends_only_that_project() {
+        let old = state(1, vec![project("a", "A"), project("b", "B")]);
+        let new = state(2, vec![project("a", "A"), project("b", "Renamed")]);
+        let delta = round_trip(&old, &new);
+        assert_eq!(delta.upserted_projects.len(), 1);
+        assert_eq!(delta.upserted_projects[0].id, "b");
+        assert!(delta.removed_projects.is_empty());
+        assert!(delta.project_ids.is_none());
+        assert!(delta.fields.is_empty());
+    }
+
+    #[test]
+    fn focus_change_sends_only_the_field() {
+        let old = state(1, vec![project("a", "A")]);
+        let mut new = old.clone();
+        new.state_version = 2;
+        new.focused_project_id = Some("a".into());
+        let delta = round_trip(&old, &new);
+        assert!(delta.upserted_projects.is_empty());
+        assert_eq!(
+            delta.fields.keys().collect::<Vec<_>>(),
+            ["focused_project_id"]
+        );
+    }
+
+    #[test]
+    fn adds_removes_and_reorders() {
+        let old = state(
+            1,
+            vec![project("a", "A"), project("b", "B"), project("c", "C")],
+        );
+        let new = state(
+            5,
+            vec![project("d", "D"), project("c", "C"), project("a", "A")],
+        );
+        let delta = round_trip(&old, &new);
+        assert_eq!(delta.removed_projects, ["b"]);
+        assert_eq!(delta.upserted_projects.len(), 1);
+        assert_eq!(
+            delta.project_ids.as_deref(),
+            Some(&["d".to_string(), "c".into(), "a".into()][..])
+        );
+    }
+
+    #[test]
+    fn unchanged_state_gives_an_empty_delta() {
+        let old = state(1, vec![project("a", "A")]);
+        assert!(diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn unknown_fields_are_ignored() {
+        let mut patched = state(1, vec![project("a", "A")]);
+        let mut delta = StateDelta::default();
+        delta
+            .fields
+            .insert("from_the_future".into(), Value::Bool(true));
+        delta
+            .fields
+            .insert("focused_project_id".into(), Value::from("a"));
+        apply(&mut patched, delta, 2).unwrap();
+        assert_eq!(patched.state_version, 2);
+        assert_eq!(patched.focused_project_id.as_deref(), Some("a"));
+        assert_eq!(patched.projects.len(), 1);
+    }
+
+    #[test]
+    fn invalid_field_is_an_error() {
+        let mut patched = state(1, Vec::new());
+        let mut delta = StateDelta::default();
+        delta.fields.insert("folders".into(), Value::Bool(true));
+        assert!(apply(&mut patched, delta, 2).is_err());
+    }
+}
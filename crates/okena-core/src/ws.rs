@@ -1,13 +1,20 @@
 use crate::api::{ApiGitStatus, ApiSystemStats, ApiTerminalFocusRequest, ApiToast};
 use crate::keys::SpecialKey;
+use crate::state_delta::StateDelta;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Inbound WebSocket messages (from client)
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
 pub enum WsInbound {
     Auth {
         token: String,
+        /// Codecs the client can decode, in order of preference. The server
+        /// picks one and names it in [`WsOutbound::AuthOk`]. Only honored
+        /// when authenticating with this message.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        compression: Vec<WsCompression>,
     },
     Subscribe {
         terminal_ids: Vec<String>,
@@ -43,14 +50,60 @@ pub enum WsInbound {
         cols: u16,
         rows: u16,
     },
+    /// Deliver the output of `terminal_ids` at most once per `interval_ms`,
+    /// coalesced, instead of as it is produced. For terminals the client is
+    /// not showing. A complete replacement set: terminals left out go back to
+    /// live output, and an empty list turns pacing off.
+    SetOutputRate {
+        terminal_ids: Vec<String>,
+        interval_ms: u32,
+    },
     Ping,
 }
 
+/// Per-message compression of binary frames, negotiated in
+/// [`WsInbound::Auth`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WsCompression {
+    /// Raw DEFLATE (RFC 1951) with one window for the whole connection, so
+    /// each frame can refer back to earlier output. See [`FrameDeflater`].
+    Deflate,
+    /// A codec this build doesn't know, offered by a newer client.
+    #[serde(other)]
+    #[schemars(skip)]
+    Unknown,
+}
+
+impl WsCompression {
+    /// The first of the client's `offered` codecs this build supports.
+    pub fn negotiate(offered: &[WsCompression]) -> Option<WsCompression> {
+        offered
+            .iter()
+            .copied()
+            .find(|codec| *codec != WsCompression::Unknown)
+    }
+}
+
+/// Query string a client adds to `/v1/stream` to receive
+/// [`WsOutbound::StateDelta`] in place of [`WsOutbound::StateChanged`].
+/// Servers that predate deltas ignore it and keep sending `state_changed`.
+pub const STATE_DELTAS_QUERY: &str = "state_deltas=1";
+
 /// Outbound WebSocket JSON messages (to client)
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum WsOutbound {
-    AuthOk,
+    AuthOk {
+        /// The codec compressed binary frames use on this connection. `None`
+        /// (and servers that predate compression) sends every frame raw.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        compression: Option<WsCompression>,
+        /// The server understands [`WsInbound::SetOutputRate`]. Older
+        /// servers reject it as an invalid message.
+        #[serde(default)]
+        output_rate: bool,
+    },
     AuthFailed {
         error: String,
     },
@@ -64,6 +117,13 @@ pub enum WsOutbound {
     StateChanged {
         state_version: u64,
     },
+    /// What changed since `base_version`, for clients that asked for deltas.
+    /// A client not holding exactly `base_version` refetches `GET /v1/state`.
+    StateDelta {
+        base_version: u64,
+        state_version: u64,
+        delta: StateDelta,
+    },
     Dropped {
         count: u64,
     },
@@ -104,6 +164,9 @@ pub const PROTO_VERSION: u8 = 1;
 pub const FRAME_TYPE_PTY: u8 = 1; // server → client: live PTY output
 pub const FRAME_TYPE_SNAPSHOT: u8 = 2; // server → client: full screen redraw
 pub const FRAME_TYPE_INPUT: u8 = 3; // client → server: terminal input
+/// Set on a frame type when the payload is compressed with the connection's
+/// negotiated [`WsCompression`]; see [`FrameInflater`].
+pub const FRAME_FLAG_COMPRESSED: u8 = 0x80;
 
 /// Parse a generic binary frame.
 /// Format: [proto_version=1][frame_type][stream_id:u32 BE][payload...]
@@ -142,6 +205,115 @@ pub fn build_pty_frame(stream_id: u32, data: &[u8]) -> Vec<u8> {
     build_binary_frame(FRAME_TYPE_PTY, stream_id, data)
 }
 
+// ── Compression ────────────────────────────────────────────────────────────
+
+/// Payloads shorter than this go out raw: keystroke echoes and prompts
+/// don't shrink enough to pay for the flush marker.
+const MIN_COMPRESSED_PAYLOAD: usize = 64;
+
+/// Largest payload [`FrameInflater`] will produce from one frame.
+const MAX_INFLATED_PAYLOAD: usize = 64 * 1024 * 1024;
+
+/// Compresses one connection's outbound PTY and snapshot frames.
+///
+/// Every frame is sync-flushed, so the client can decode it on arrival, but
+/// the window carries over: repeated prompts and build-log lines compress
+/// against earlier frames. Frames must reach the client in the order they
+/// were compressed, and a frame this returns must not be dropped.
+pub struct FrameDeflater {
+    compress: flate2::Compress,
+}
+
+impl Default for FrameDeflater {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameDeflater {
+    pub fn new() -> Self {
+        Self {
+            compress: flate2::Compress::new(flate2::Compression::fast(), false),
+        }
+    }
+
+    /// `frame` with its payload compressed and [`FRAME_FLAG_COMPRESSED`] set,
+    /// or `None` to send it as it is (input frames, short payloads).
+    pub fn compress_frame(&mut self, frame: &[u8]) -> Option<Vec<u8>> {
+        let (frame_type, stream_id, payload) = parse_binary_frame(frame)?;
+        if !matches!(frame_type, FRAME_TYPE_PTY | FRAME_TYPE_SNAPSHOT)
+            || payload.len() < MIN_COMPRESSED_PAYLOAD
+        {
+            return None;
+        }
+        let mut out = build_binary_frame(frame_type | FRAME_FLAG_COMPRESSED, stream_id, &[]);
+        out.reserve(payload.len() / 2 + 64);
+        let start = self.compress.total_in();
+        loop {
+            let consumed = (self.compress.total_in() - start) as usize;
+            if out.capacity() - out.len() < 64 {
+                out.reserve(out.capacity());
+            }
+            // Sync flush never fails on a valid stream; a failure would leave
+            // the window out of step with the client, so there is no retry.
+            self.compress
+                .compress_vec(&payload[consumed..], &mut out, flate2::FlushCompress::Sync)
+                .ok()?;
+            let consumed = (self.compress.total_in() - start) as usize;
+            if consumed == payload.len() && out.len() < out.capacity() {
+                return Some(out);
+            }
+        }
+    }
+}
+
+/// Decodes the frames a [`FrameDeflater`] produced, in order.
+pub struct FrameInflater {
+    decompress: flate2::Decompress,
+}
+
+impl Default for FrameInflater {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameInflater {
+    pub fn new() -> Self {
+        Self {
+            decompress: flate2::Decompress::new(false),
+        }
+    }
+
+    /// The payload of a frame whose type carries [`FRAME_FLAG_COMPRESSED`].
+    /// An error means the stream is corrupt and the connection must be
+    /// reopened.
+    pub fn inflate(&mut self, payload: &[u8]) -> Result<Vec<u8>, String> {
+        let mut out = Vec::with_capacity(payload.len() * 4 + 64);
+        let start = self.decompress.total_in();
+        loop {
+            let consumed = (self.decompress.total_in() - start) as usize;
+            if out.capacity() - out.len() < 64 {
+                if out.len() >= MAX_INFLATED_PAYLOAD {
+                    return Err("compressed frame is too large".to_string());
+                }
+                out.reserve(out.capacity());
+            }
+            self.decompress
+                .decompress_vec(
+                    &payload[consumed..],
+                    &mut out,
+                    flate2::FlushDecompress::Sync,
+                )
+                .map_err(|e| format!("corrupt compressed frame: {e}"))?;
+            let consumed = (self.decompress.total_in() - start) as usize;
+            if consumed == payload.len() && out.len() < out.capacity() {
+                return Ok(out);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,6 +323,7 @@ mod tests {
         let messages = vec![
             WsInbound::Auth {
                 token: "tok123".into(),
+                compression: vec![WsCompression::Deflate],
             },
             WsInbound::Subscribe {
                 terminal_ids: vec!["t1".into(), "t2".into()],
@@ -178,6 +351,10 @@ mod tests {
                 cols: 80,
                 rows: 24,
             },
+            WsInbound::SetOutputRate {
+                terminal_ids: vec!["t2".into()],
+                interval_ms: 500,
+            },
             WsInbound::Ping,
         ];
         for msg in messages {
@@ -189,7 +366,10 @@ mod tests {
     #[test]
     fn ws_outbound_round_trip() {
         let messages = vec![
-            WsOutbound::AuthOk,
+            WsOutbound::AuthOk {
+                compression: Some(WsCompression::Deflate),
+                output_rate: true,
+            },
             WsOutbound::AuthFailed {
                 error: "bad token".into(),
             },
@@ -198,6 +378,14 @@ mod tests {
                 sizes: [("t1".into(), (120, 40))].into_iter().collect(),
             },
             WsOutbound::StateChanged { state_version: 5 },
+            WsOutbound::StateDelta {
+                base_version: 5,
+                state_version: 7,
+                delta: StateDelta {
+                    removed_projects: vec!["p1".into()],
+                    ..Default::default()
+                },
+            },
             WsOutbound::Dropped { count: 3 },
             WsOutbound::Pong,
             WsOutbound::Error {
@@ -269,6 +457,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn auth_compression_is_optional_and_tolerates_new_codecs() {
+        let plain: WsInbound = serde_json::from_str(r#"{"type":"auth","token":"t"}"#).unwrap();
+        assert!(matches!(plain, WsInbound::Auth { compression, .. } if compression.is_empty()));
+
+        let json = r#"{"type":"auth","token":"t","compression":["zstd","deflate"]}"#;
+        let WsInbound::Auth { compression, .. } = serde_json::from_str(json).unwrap() else {
+            panic!("expected Auth");
+        };
+        assert_eq!(
+            WsCompression::negotiate(&compression),
+            Some(WsCompression::Deflate)
+        );
+        assert_eq!(WsCompression::negotiate(&[WsCompression::Unknown]), None);
+
+        // Older servers send a bare `auth_ok`.
+        let ok: WsOutbound = serde_json::from_str(r#"{"type":"auth_ok"}"#).unwrap();
+        assert!(matches!(
+            ok,
+            WsOutbound::AuthOk {
+                compression: None,
+                output_rate: false
+            }
+        ));
+    }
+
     // ── Generic binary frame tests ─────────────────────────────────────
 
     #[test]
@@ -362,4 +576,50 @@ mod tests {
             assert_eq!(parsed_data, payload.as_bytes());
         }
     }
+
+    // ── Compression tests ──────────────────────────────────────────────
+
+    #[test]
+    fn compressed_frames_round_trip_in_order() {
+        let mut deflater = FrameDeflater::new();
+        let mut inflater = FrameInflater::new();
+        let line = "   Compiling okena-core v0.1.0 (/src/okena/crates/okena-core)\r\n";
+        let mut wire = 0;
+        let mut raw = 0;
+        for (i, frame_type) in [FRAME_TYPE_PTY, FRAME_TYPE_SNAPSHOT, FRAME_TYPE_PTY]
+            .into_iter()
+            .enumerate()
+        {
+            let payload = line.repeat(20 + i);
+            let frame = build_binary_frame(frame_type, 9, payload.as_bytes());
+            let compressed = deflater.compress_frame(&frame).unwrap();
+            wire += compressed.len();
+            raw += frame.len();
+
+            let (ft, stream_id, body) = parse_binary_frame(&compressed).unwrap();
+            assert_eq!(ft, frame_type | FRAME_FLAG_COMPRESSED);
+            assert_eq!(stream_id, 9);
+            assert_eq!(inflater.inflate(body).unwrap(), payload.as_bytes());
+        }
+        // Later frames compress against the first one's window.
+        assert!(wire * 10 < raw, "{wire} vs {raw}");
+    }
+
+    #[test]
+    fn short_and_input_frames_stay_raw() {
+        let mut deflater = FrameDeflater::new();
+        assert!(
+            deflater
+                .compress_frame(&build_pty_frame(1, b"ls\r\n"))
+                .is_none()
+        );
+        let input = build_binary_frame(FRAME_TYPE_INPUT, 1, &[b'x'; 512]);
+        assert!(deflater.compress_frame(&input).is_none());
+    }
+
+    #[test]
+    fn inflating_garbage_fails() {
+        let mut inflater = FrameInflater::new();
+        assert!(inflater.inflate(&[0xff; 32]).is_err());
+    }
 }
@@ -50,18 +50,23 @@ use okena_app_core::remote_snapshot::build_state_response;
 #[cfg(test)]
 use okena_app_core::workspace::actions::execute::apply_loaded_session;
 use okena_app_core::workspace::actions::execute::{
-    PreparedContentSearch, begin_workspace_replacement, cleanup_stale_prepared_terminal_launches,
-    cleanup_stale_workspace_replacement, clear_failed_terminal_launch_reservations,
-    ensure_terminal, ensure_workspace_replacement_allowed, execute_action,
+    ExecMode, PreparedContentSearch, PreparedExec, begin_workspace_replacement,
+    cleanup_stale_prepared_terminal_launches, cleanup_stale_workspace_replacement,
+    clear_failed_terminal_launch_reservations, ensure_terminal,
+    ensure_workspace_replacement_allowed, exec_result_from_capture, execute_action,
     execute_prepared_content_search_with_cancellation, fail_workspace_replacement,
     finish_workspace_replacement, import_workspace_data, load_session_data_for_shell,
     materialize_prepared_terminal_launches, materialize_workspace_replacement,
-    prepare_content_search, prepare_workspace_replacement, publish_prepared_terminal_launches,
-    reserve_uninitialized_terminal_launches, spawn_uninitialized_terminals,
+    prepare_content_search, prepare_exec_in_terminal, prepare_workspace_replacement,
+    publish_prepared_terminal_launches, reserve_uninitialized_terminal_launches, run_exec_process,
+    spawn_uninitialized_terminals,
 };
 use okena_core::api::{ActionRequest, ApiGitStatus, ApiServiceInfo, ApiWindow, CommandResult};
+use okena_core::batch::{ActionBatchRequest, ApiBatchResult, resolve_references};
 use okena_core::git_poll::{GitPollTrigger, git_poll_trigger_for_action};
+use okena_core::scope::{Scope, classify};
 use okena_remote_server::bridge::{BridgeMessage, BridgeReceiver, RemoteCommand};
+use okena_remote_server::pty_broadcaster::{PtyBroadcastEvent, PtyBroadcaster};
 use okena_services::config::{PreparedProjectConfig, prepare_project_config};
 use okena_services::manager::{
     ComposeProjectIdentity, ServiceKind, ServiceLoadStatus, ServiceManager,
@@ -69,6 +74,7 @@ use okena_services::manager::{
 };
 use okena_terminal::TerminalsRegistry;
 use okena_terminal::backend::{TerminalBackend, TerminalSessionTeardown};
+use okena_terminal::terminal::CommandCapture;
 use okena_workspace::actions::project::ProjectDirectoryRenamePlan;
 use okena_workspace::actions::soft_close::{
     begin_soft_close_flow, close_now_flow, probe_busy, undo_soft_close_flow,
@@ -81,7 +87,7 @@ use okena_workspace::state::{
     ProjectRuntimeQuiesce, TerminalBackendMigration, WindowId, Workspace,
 };
 use parking_lot::Mutex;
-use tokio::sync::{Semaphore, oneshot, watch};
+use tokio::sync::{Semaphore, broadcast, oneshot, watch};
 
 use crate::daemon_config::{DaemonConfig, get_settings_schema};
 use crate::service_cx::ServiceReactorRef;
@@ -266,6 +272,94 @@ fn spawn_content_search(
     });
 }
 
+/// Run a prepared `ExecInTerminal` off the loop and reply when it is done.
+///
+/// Shell mode subscribes to the PTY output before typing the command, then
+/// feeds the terminal's chunks into a [`CommandCapture`] until the `OSC 133 ; D`
+/// mark arrives. On timeout the command keeps running in the pane; only the
+/// caller stops waiting. If the subscription lags past the `D` mark, the
+/// terminal's prompt tracker tells us and the exec fails instead of hanging.
+/// Process mode runs on the blocking pool.
+fn spawn_exec_in_terminal(
+    prepared: PreparedExec,
+    reply: Option<oneshot::Sender<CommandResult>>,
+    runtime: &tokio::runtime::Handle,
+    broadcaster: &PtyBroadcaster,
+) {
+    let started = Instant::now();
+    match prepared.mode {
+        ExecMode::Shell(ref terminal) => {
+            let terminal = terminal.clone();
+            let mut output_rx = broadcaster.subscribe();
+            prepared.send_to_shell();
+            let _task = runtime.spawn(async move {
+                let deadline = tokio::time::Instant::now() + prepared.timeout;
+                let mut capture = CommandCapture::new();
+                let mut lagged = false;
+                let result = loop {
+                    let event = match tokio::time::timeout_at(deadline, output_rx.recv()).await {
+                        Ok(event) => event,
+                        Err(_) => {
+                            break CommandResult::Err(format!(
+                                "command did not finish within {} s",
+                                prepared.timeout.as_secs()
+                            ));
+                        }
+                    };
+                    match event {
+                        Ok(PtyBroadcastEvent::Output {
+                            terminal_id, data, ..
+                        }) if terminal_id == prepared.terminal_id => {
+                            capture.feed(&data);
+                            if let Some(mut captured) = capture.finished() {
+                                // Skipped chunks are a hole in the output.
+                                captured.truncated |= lagged;
+                                break exec_result_from_capture(captured, started);
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            log::warn!(
+                                "exec in {}: lagged, {n} output events skipped",
+                                prepared.terminal_id
+                            );
+                            // The `D` mark may have been in the skipped range;
+                            // the prompt tracker saw it even if we did not.
+                            if terminal.at_shell_prompt() != Some(false) {
+                                break CommandResult::Err(format!(
+                                    "lost command completion: {n} output events were skipped \
+                                     and the shell is back at its prompt"
+                                ));
+                            }
+                            lagged = true;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => {
+                            break CommandResult::Err("output stream closed".to_string());
+                        }
+                    }
+                };
+                if let Some(reply) = reply {
+                    let _ = reply.send(result);
+                }
+            });
+        }
+        ExecMode::Process => {
+            let worker_runtime = runtime.clone();
+            let _task = runtime.spawn(async move {
+                let result = worker_runtime
+                    .spawn_blocking(move || run_exec_process(&prepared))
+                    .await
+                    .unwrap_or_else(|error| {
+                        CommandResult::Err(format!("exec worker failed: {error}"))
+                    });
+                if let Some(reply) = reply {
+                    let _ = reply.send(result);
+                }
+            });
+        }
+    }
+}
+
 /// Run destructive cleanup only while no current project occupies the physical
 /// worktree root or one of its subdirectories. The workspace guard fences
 /// replacement registration against the check-to-delete window.
@@ -2542,6 +2636,7 @@ pub async fn daemon_command_loop(
     mut daemon_config: DaemonConfig,
     deadlines: SoftCloseDeadlines,
     git_poll_trigger_tx: tokio::sync::mpsc::UnboundedSender<GitPollTrigger>,
+    broadcaster: Arc<PtyBroadcaster>,
 ) {
     // Single dormant "main" FocusManager. The loop is single-threaded, so it
     // owns the FM directly instead of resolving a per-window entity like the
@@ -2623,6 +2718,36 @@ pub async fn daemon_command_loop(
                 }
                 continue;
             }
+            RemoteCommand::Action(ActionRequest::ExecInTerminal {
+                terminal_id,
+                command,
+                mirror,
+                timeout_ms,
+            }) => {
+                let prepared = {
+                    let workspace = workspace.lock();
+                    let settings = settings.lock();
+                    prepare_exec_in_terminal(
+                        &workspace,
+                        terminal_id,
+                        command,
+                        mirror,
+                        timeout_ms,
+                        &*backend,
+                        &terminals,
+                        &settings,
+                    )
+                };
+                match prepared {
+                    Ok(prepared) => spawn_exec_in_terminal(prepared, reply, &runtime, &broadcaster),
+                    Err(error) => {
+                        if let Some(reply) = reply {
+                            let _ = reply.send(CommandResult::Err(error));
+                        }
+                    }
+                }
+                continue;
+            }
             command => command,
         };
 
@@ -2654,6 +2779,58 @@ pub async fn daemon_command_loop(
                         let mut cx = service_reactor.cx();
                         sm.restart_service_action(&project_id, &service_name, &mut cx)
                     }
+                    ActionRequest::RebuildService {
+                        project_id,
+                        service_name,
+                    } => {
+                        let mut sm = service_manager.lock();
+                        let mut cx = service_reactor.cx();
+                        sm.rebuild_service_action(&project_id, &service_name, &mut cx)
+                    }
+                    ActionRequest::PullService {
+                        project_id,
+                        service_name,
+                    } => {
+                        let mut sm = service_manager.lock();
+                        let mut cx = service_reactor.cx();
+                        sm.pull_service_action(&project_id, &service_name, &mut cx)
+                    }
+                    ActionRequest::ExecServiceShell {
+                        project_id,
+                        service_name,
+                        shell,
+                    } => {
+                        let shell =
+                            service_manager
+                                .lock()
+                                .service_shell(&project_id, &service_name, shell);
+                        match shell {
+                            Ok(shell) => {
+                                let app_settings = settings.lock().clone();
+                                run_main_workspace_action(
+                                    ActionRequest::CreateTerminal {
+                                        project_id,
+                                        shell: Some(shell),
+                                        cwd: None,
+                                        command: None,
+                                        env: Default::default(),
+                                        name: None,
+                                        minimized: false,
+                                        placement: None,
+                                    },
+                                    &workspace,
+                                    &mut focus_manager,
+                                    &backend,
+                                    &terminals,
+                                    &app_settings,
+                                    &workspace_tick,
+                                    &hook_runner,
+                                    &hook_monitor,
+                                )
+                            }
+                            Err(e) => CommandResult::Err(e),
+                        }
+                    }
                     ActionRequest::StartAllServices { project_id } => {
                         let mut sm = service_manager.lock();
                         let mut cx = service_reactor.cx();
@@ -3747,6 +3924,21 @@ pub async fn daemon_command_loop(
                     )
                 }
             }
+            RemoteCommand::Batch(batch) => {
+                let app_settings = settings.lock().clone();
+                run_action_batch(
+                    batch,
+                    &workspace,
+                    &mut focus_manager,
+                    &backend,
+                    &terminals,
+                    &app_settings,
+                    &workspace_tick,
+                    &hook_runner,
+                    &hook_monitor,
+                    &git_poll_trigger_tx,
+                )
+            }
             RemoteCommand::ActionFromConnection { .. } => {
                 CommandResult::Err("internal action normalization error".to_string())
             }
@@ -4338,6 +4530,187 @@ fn run_main_workspace_action(
     result
 }
 
+/// Actions a batch refuses, by their `action` tag: the loop runs them outside
+/// `execute_action` (services, settings, sessions, soft close, off-reactor
+/// work), so they can't join a batch's single turn. Mirrors the special arms
+/// in `daemon_command_loop`, except `close_terminal`: in a batch it skips the
+/// soft close and closes at once through `execute_action`, so a rollback can
+/// reopen the pane.
+const REFUSED_IN_BATCH: &[&str] = &[
+    "search_content",
+    "exec_in_terminal",
+    "start_service",
+    "stop_service",
+    "restart_service",
+    "rebuild_service",
+    "pull_service",
+    "exec_service_shell",
+    "start_all_services",
+    "stop_all_services",
+    "reload_services",
+    "get_settings",
+    "get_settings_schema",
+    "set_settings",
+    "get_themes",
+    "get_theme",
+    "set_theme",
+    "save_custom_theme",
+    "list_actions",
+    "invoke_action",
+    "load_session",
+    "import_workspace",
+    "undo_soft_close",
+    "close_terminal_now",
+    "clone_project",
+    "create_worktree",
+    "close_worktree",
+    "remove_worktree_project",
+    "force_remove_worktree_project",
+    "rename_project_directory",
+];
+
+/// One batch step: resolve its references to earlier results, then run it
+/// like the default workspace arm.
+#[allow(clippy::too_many_arguments)]
+fn run_batch_step(
+    mut step: serde_json::Value,
+    results: &[serde_json::Value],
+    rollback: bool,
+    workspace: &Arc<Mutex<Workspace>>,
+    focus_manager: &mut FocusManager,
+    backend: &Arc<dyn TerminalBackend>,
+    terminals: &TerminalsRegistry,
+    app_settings: &AppSettings,
+    workspace_tick: &watch::Sender<u64>,
+    hook_runner: &Option<okena_hooks::HookRunner>,
+    hook_monitor: &Option<okena_hooks::HookMonitor>,
+    git_poll_trigger_tx: &tokio::sync::mpsc::UnboundedSender<GitPollTrigger>,
+) -> Result<serde_json::Value, String> {
+    resolve_references(&mut step, results)?;
+    let name = step
+        .get("action")
+        .and_then(|a| a.as_str())
+        .unwrap_or_default()
+        .to_string();
+    if REFUSED_IN_BATCH.contains(&name.as_str()) {
+        return Err(format!("{name} can't run in a batch"));
+    }
+    let action: ActionRequest =
+        serde_json::from_value(step).map_err(|e| format!("invalid action: {e}"))?;
+    // A rollback only puts layouts back, so it can't undo git, file, service
+    // or project changes.
+    if rollback && !matches!(classify(&action).0, Scope::Read | Scope::TerminalInput) {
+        return Err(format!("{name} can't be rolled back; run it outside a rollback batch"));
+    }
+    // Same window rules as the default action arm.
+    if let Some(window) = action.target_window() {
+        match parse_window_id(window) {
+            None => return Err(format!("invalid window id: {window}")),
+            Some(WindowId::Extra(uuid))
+                if !matches!(&action, ActionRequest::FocusTerminal { .. }) =>
+            {
+                return Err(format!("window not found: {uuid}"));
+            }
+            Some(_) => {}
+        }
+    }
+
+    let git_poll_trigger = git_poll_trigger_for_action(&action);
+    let result = run_main_workspace_action(
+        action,
+        workspace,
+        focus_manager,
+        backend,
+        terminals,
+        app_settings,
+        workspace_tick,
+        hook_runner,
+        hook_monitor,
+    );
+    send_git_poll_trigger_after_success(&result, git_poll_trigger, git_poll_trigger_tx);
+    match result {
+        CommandResult::Ok(value) => Ok(value.unwrap_or_default()),
+        CommandResult::Err(e) => Err(e),
+        CommandResult::OkBytes(_) | CommandResult::OkSnapshot { .. } => Ok(serde_json::Value::Null),
+    }
+}
+
+/// `POST /v1/actions/batch`: run the steps back to back with no await in
+/// between, so the observers on this `LocalSet` see one workspace change and
+/// clients get one `StateChanged`. Stops at the first failure; with
+/// `rollback` the layouts are put back and terminals the batch opened closed,
+/// and steps outside the `read` and `terminal:input` scopes are refused.
+#[allow(clippy::too_many_arguments)]
+fn run_action_batch(
+    batch: ActionBatchRequest,
+    workspace: &Arc<Mutex<Workspace>>,
+    focus_manager: &mut FocusManager,
+    backend: &Arc<dyn TerminalBackend>,
+    terminals: &TerminalsRegistry,
+    app_settings: &AppSettings,
+    workspace_tick: &watch::Sender<u64>,
+    hook_runner: &Option<okena_hooks::HookRunner>,
+    hook_monitor: &Option<okena_hooks::HookMonitor>,
+    git_poll_trigger_tx: &tokio::sync::mpsc::UnboundedSender<GitPollTrigger>,
+) -> CommandResult {
+    let snapshot = batch.rollback.then(|| workspace.lock().layout_snapshot());
+    let mut outcome = ApiBatchResult::default();
+    for (index, step) in batch.actions.into_iter().enumerate() {
+        match run_batch_step(
+            step,
+            &outcome.results,
+            batch.rollback,
+            workspace,
+            focus_manager,
+            backend,
+            terminals,
+            app_settings,
+            workspace_tick,
+            hook_runner,
+            hook_monitor,
+            git_poll_trigger_tx,
+        ) {
+            Ok(value) => outcome.results.push(value),
+            Err(error) => {
+                outcome.failed_step = Some(index);
+                outcome.error = Some(error);
+                break;
+            }
+        }
+    }
+
+    if outcome.failed_step.is_some()
+        && let Some(snapshot) = snapshot
+    {
+        let mut cx = DaemonWorkspaceCx::new(workspace_tick, hook_runner, hook_monitor);
+        let mut ws = workspace.lock();
+        let restore = ws.restore_layouts(snapshot, |id| terminals.lock().contains_key(id), &mut cx);
+        for terminal_id in restore.dropped {
+            backend.kill(&terminal_id);
+            terminals.lock().remove(&terminal_id);
+        }
+        // Slots whose PTY a step closed come back as fresh shells. Projects
+        // the batch never touched keep whatever state they are in.
+        for project_id in restore.projects {
+            let _ = spawn_uninitialized_terminals(
+                &mut ws,
+                &project_id,
+                &**backend,
+                terminals,
+                app_settings,
+                None,
+                &mut cx,
+            );
+        }
+        outcome.rolled_back = true;
+    }
+
+    match serde_json::to_value(outcome) {
+        Ok(v) => CommandResult::Ok(Some(v)),
+        Err(e) => CommandResult::Err(format!("failed to serialize batch result: {e}")),
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn finalize_soft_close_now(
     workspace: &Arc<Mutex<Workspace>>,
@@ -4412,6 +4785,7 @@ mod tests {
                 self.daemon_config,
                 Arc::new(Mutex::new(HashMap::new())),
                 tokio::sync::mpsc::unbounded_channel().0,
+                Arc::new(PtyBroadcaster::new()),
             ))
         }
     }
@@ -4886,6 +5260,81 @@ mod tests {
         assert_eq!(parse_window_id("550e8400-e29b-41d4-a716-44665544000"), None);
     }
 
+    /// The `ActionRequest` arms `daemon_command_loop` handles itself, read
+    /// from this file: every arm ahead of its default workspace arm. A pattern
+    /// is told apart from an `ActionRequest` built inside an arm by the `=>`
+    /// on the line that closes it at its own indentation.
+    fn special_arms() -> Vec<String> {
+        let source = include_str!("command_loop.rs");
+        let start = source
+            .find("pub async fn daemon_command_loop(")
+            .expect("daemon_command_loop");
+        let end = start
+            + source[start..]
+                .find("// ── Default: workspace-scoped action")
+                .expect("default arm marker");
+        let lines: Vec<&str> = source[start..end].lines().collect();
+        let indent = |line: &str| line.len() - line.trim_start().len();
+        let mut arms: Vec<String> = lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let trimmed = line.trim_start();
+                let trimmed = trimmed
+                    .strip_prefix("RemoteCommand::Action(")
+                    .unwrap_or(trimmed);
+                let name = trimmed.strip_prefix("ActionRequest::")?;
+                let closing = if line.contains("=>") {
+                    line
+                } else {
+                    lines[i + 1..].iter().find(|l| indent(l) == indent(line))?
+                };
+                if !closing.contains("=>") {
+                    return None;
+                }
+                let end = name
+                    .find(|c: char| !c.is_ascii_alphanumeric())
+                    .unwrap_or(name.len());
+                Some(name[..end].to_string())
+            })
+            .collect();
+        arms.sort();
+        arms.dedup();
+        arms
+    }
+
+    fn snake_case(name: &str) -> String {
+        let mut out = String::new();
+        for c in name.chars() {
+            if c.is_ascii_uppercase() && !out.is_empty() {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        }
+        out
+    }
+
+    #[test]
+    fn batch_refuses_exactly_the_actions_the_loop_handles_itself() {
+        let arms = special_arms();
+        assert!(arms.len() > 20, "arm scan found only {arms:?}");
+        let mut special: Vec<String> = arms
+            .iter()
+            .map(|arm| snake_case(arm))
+            .filter(|arm| arm != "close_terminal")
+            .collect();
+        special.sort();
+        let mut refused: Vec<String> = REFUSED_IN_BATCH.iter().map(|s| s.to_string()).collect();
+        refused.sort();
+        assert_eq!(
+            special, refused,
+            "REFUSED_IN_BATCH must list every special arm of daemon_command_loop"
+        );
+        // Batched closes go through `execute_action` instead.
+        assert!(arms.iter().any(|arm| arm == "CloseTerminal"));
+        assert!(!REFUSED_IN_BATCH.contains(&"close_terminal"));
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn failed_close_service_recovery_rearms_current_writeback_owner() {
         let project_dir =
@@ -7268,6 +7717,100 @@ mod tests {
         );
     }
 
+    /// A batched `close_terminal` closes at once, and a rollback reopens the
+    /// pane with a fresh shell.
+    #[test]
+    fn batched_close_terminal_is_reopened_by_rollback() {
+        let backend: Arc<dyn TerminalBackend> = Arc::new(RestoringBackend);
+        let terminals: TerminalsRegistry = Arc::new(Mutex::new(Default::default()));
+        let workspace = Arc::new(Mutex::new(Workspace::new(
+            workspace_with_initialized_terminal("t1"),
+        )));
+        let (workspace_tick, _wtrx) = watch::channel(0u64);
+        let (git_poll_trigger_tx, _git_poll_trigger_rx) = tokio::sync::mpsc::unbounded_channel();
+        let batch = ActionBatchRequest {
+            actions: vec![
+                serde_json::json!({
+                    "action": "close_terminal",
+                    "project_id": "p1",
+                    "terminal_id": "t1",
+                }),
+                serde_json::json!({ "action": "no_such_action" }),
+            ],
+            rollback: true,
+        };
+
+        let result = run_action_batch(
+            batch,
+            &workspace,
+            &mut FocusManager::new(),
+            &backend,
+            &terminals,
+            &default_settings(),
+            &workspace_tick,
+            &None,
+            &None,
+            &git_poll_trigger_tx,
+        );
+
+        let CommandResult::Ok(Some(outcome)) = result else {
+            panic!("batch should report its outcome: {result:?}");
+        };
+        let outcome: ApiBatchResult = serde_json::from_value(outcome).unwrap();
+        assert_eq!(outcome.results.len(), 1, "close_terminal ran");
+        assert_eq!(outcome.failed_step, Some(1));
+        assert!(outcome.rolled_back);
+        let ws = workspace.lock();
+        assert!(matches!(
+            ws.project("p1").and_then(|p| p.layout.as_ref()),
+            Some(okena_state::LayoutNode::Terminal {
+                terminal_id: Some(id),
+                ..
+            }) if id == "restored-terminal"
+        ));
+    }
+
+    /// A rollback can't undo a deleted project, so such a step fails the
+    /// batch instead of running.
+    #[test]
+    fn rollback_batch_refuses_steps_it_cannot_undo() {
+        let backend: Arc<dyn TerminalBackend> = Arc::new(RestoringBackend);
+        let terminals: TerminalsRegistry = Arc::new(Mutex::new(Default::default()));
+        let workspace = Arc::new(Mutex::new(Workspace::new(
+            workspace_with_initialized_terminal("t1"),
+        )));
+        let (workspace_tick, _wtrx) = watch::channel(0u64);
+        let (git_poll_trigger_tx, _git_poll_trigger_rx) = tokio::sync::mpsc::unbounded_channel();
+        let batch = ActionBatchRequest {
+            actions: vec![serde_json::json!({
+                "action": "delete_project",
+                "project_id": "p1",
+            })],
+            rollback: true,
+        };
+
+        let result = run_action_batch(
+            batch,
+            &workspace,
+            &mut FocusManager::new(),
+            &backend,
+            &terminals,
+            &default_settings(),
+            &workspace_tick,
+            &None,
+            &None,
+            &git_poll_trigger_tx,
+        );
+
+        let CommandResult::Ok(Some(outcome)) = result else {
+            panic!("batch should report its outcome: {result:?}");
+        };
+        let outcome: ApiBatchResult = serde_json::from_value(outcome).unwrap();
+        assert_eq!(outcome.failed_step, Some(0));
+        assert!(outcome.error.unwrap().contains("can't be rolled back"));
+        assert!(workspace.lock().project("p1").is_some());
+    }
+
     #[test]
     fn generic_terminal_teardown_releases_workspace_before_kill() {
         let cases = [
@@ -58,8 +58,9 @@ use std::sync::atomic::AtomicU64;
 
 use async_channel::Receiver;
 use okena_core::api::{ApiGitStatus, ApiTerminalFocusRequest, ApiToast};
+use okena_core::events::ApiEvent;
 use okena_core::git_poll::GitPollTrigger;
-use okena_hooks::{HookMonitor, HookRunner};
+use okena_hooks::{HookMonitor, HookRunStore, HookRunner};
 use okena_remote_server::auth::AuthStore;
 use okena_remote_server::bridge::{self, BridgeReceiver};
 use okena_remote_server::pty_broadcaster::PtyBroadcaster;
@@ -239,6 +240,12 @@ pub struct DaemonCore {
     /// server fans them out to clients. The daemon has no surface of its own, so
     /// this is how hook-failure notifications reach the GUI.
     toast_tx: Arc<tokio::sync::broadcast::Sender<ApiToast>>,
+    /// Semantic-event broadcast for `/v1/events`: the PTY loop publishes
+    /// command-finished / bell / notification edges here.
+    events_tx: Arc<tokio::sync::broadcast::Sender<ApiEvent>>,
+    /// PTY output fan-out, shared with the remote server. The command loop
+    /// subscribes to it to capture `ExecInTerminal` output in shell mode.
+    broadcaster: Arc<PtyBroadcaster>,
     /// Client terminal subscriptions (connection id -> subscribed terminal ids),
     /// shared with the remote server. The git poll reads it to fan out the
     /// expensive `gh` PR/CI lookups only for projects a client is viewing.
@@ -355,8 +362,12 @@ impl DaemonCore {
         // `terminals` registry + broadcast over the same `PtyBroadcaster`, so
         // hook terminals reach clients via the normal remote terminal path. Both
         // ctors are gpui-free (okena-hooks built without the gpui feature here).
+        // Headless hook runs are persisted under `<profile>/hook-logs` so the
+        // hook log browser survives daemon restarts.
         let hook_runner = HookRunner::new(backend.clone(), terminals.clone());
-        let hook_monitor = HookMonitor::new();
+        let hook_monitor = HookMonitor::new().with_run_store(HookRunStore::new(
+            persistence::get_config_dir().join("hook-logs"),
+        ));
         let reactor = Arc::new(DaemonReactor::new(
             workspace,
             backend.clone(),
@@ -396,6 +407,9 @@ impl DaemonCore {
         let toast_tx = Arc::new(tokio::sync::broadcast::channel::<ApiToast>(64).0);
         let terminal_focus_tx =
             Arc::new(tokio::sync::broadcast::channel::<ApiTerminalFocusRequest>(64).0);
+        // Activity events are bursty (one per finished command), so give
+        // `/v1/events` subscribers more slack than toasts get.
+        let events_tx = Arc::new(tokio::sync::broadcast::channel::<ApiEvent>(256).0);
         let auth_store = Arc::new(AuthStore::new());
         let remote_subscribed_terminals = Arc::new(std::sync::RwLock::new(HashMap::new()));
         let remote_visible_projects = Arc::new(std::sync::RwLock::new(HashMap::new()));
@@ -418,6 +432,7 @@ impl DaemonCore {
             git_status_tx.clone(),
             toast_tx.clone(),
             terminal_focus_tx,
+            events_tx.clone(),
             remote_subscribed_terminals.clone(),
             remote_visible_projects.clone(),
             Some(git_poll_trigger_tx.clone()),
@@ -443,8 +458,7 @@ impl DaemonCore {
         println!("Run `okena pair` for a fresh code.");
 
         // ── 8. Store exactly what `run()` needs ──────────────────────────────
-        // `broadcaster` and `auth_store` are now owned by the server; no
-        // duplicates are kept here.
+        // `auth_store` is now owned by the server; no duplicate is kept here.
         Ok(Self {
             runtime,
             reactor,
@@ -457,6 +471,8 @@ impl DaemonCore {
             state_version,
             git_status_tx,
             toast_tx,
+            events_tx,
+            broadcaster,
             remote_subscribed_terminals,
             remote_visible_projects,
             git_poll_trigger_tx,
@@ -488,6 +504,8 @@ impl DaemonCore {
             state_version,
             git_status_tx,
             toast_tx,
+            events_tx,
+            broadcaster,
             remote_subscribed_terminals,
             remote_visible_projects,
             git_poll_trigger_tx,
@@ -508,7 +526,7 @@ impl DaemonCore {
         let shutdown_autosaves = reactor.autosave_tracker.clone();
         local.block_on(&runtime, async move {
             // Observers MUST be spawned inside the LocalSet (they `spawn_local`).
-            reactor.spawn_observers();
+            reactor.spawn_observers(settings.clone());
             tokio::task::spawn_local(crate::pty_loop::run_pty_loop(
                 pty_events,
                 terminals.clone(),
@@ -526,6 +544,7 @@ impl DaemonCore {
                     hook_monitor: reactor.hook_monitor.clone(),
                     workspace_tick: reactor.workspace_tick.clone(),
                     settings: settings.clone(),
+                    events_tx: (*events_tx).clone(),
                 },
                 reactor.state_version.clone(),
             ));
@@ -536,12 +555,16 @@ impl DaemonCore {
                 remote_subscribed_terminals.clone(),
                 remote_visible_projects.clone(),
                 git_poll_trigger_rx,
+                settings.clone(),
+                reactor.hook_monitor.clone(),
             ));
             tokio::task::spawn_local(crate::git_poll::run_git_head_poll(
                 reactor.workspace.clone(),
                 remote_subscribed_terminals,
                 remote_visible_projects,
                 git_poll_trigger_tx.clone(),
+                settings.clone(),
+                reactor.hook_monitor.clone(),
             ));
             // Forward the daemon's HookMonitor toasts to clients. The daemon has
             // no surface; this drains its pending toasts and broadcasts them over
@@ -619,6 +642,7 @@ impl DaemonCore {
                 daemon_config,
                 soft_close_deadlines,
                 git_poll_trigger_tx,
+                broadcaster,
             );
             tokio::pin!(cmd);
             let interrupted = tokio::select! {
@@ -0,0 +1,126 @@
+//! Event hooks (`terminal.on_command_finished`, `service.on_crash`,
+//! `git.on_branch_change`, ...) fired from the daemon's loops.
+//!
+//! The PTY loop, the service observer and the git poller each notice their own
+//! events; this module resolves the owning project's hook context once, under a
+//! short workspace lock, so the (subprocess-spawning) hook runs outside it.
+
+use okena_hooks::{
+    GitHookEvent, HookMonitor, ServiceHookEvent, TerminalHookEvent, hook_configured,
+};
+use okena_state::HooksConfig;
+use okena_workspace::state::Workspace;
+
+/// Hook config + project metadata for one project, snapshotted from the
+/// workspace. Parent hooks are set for worktree projects.
+pub(crate) struct ProjectHookContext {
+    project_hooks: HooksConfig,
+    parent_hooks: Option<HooksConfig>,
+    project_id: String,
+    project_name: String,
+    project_path: String,
+    folder_id: Option<String>,
+    folder_name: Option<String>,
+}
+
+impl ProjectHookContext {
+    /// Snapshot `project_id`'s hook context, or `None` when the project is gone
+    /// or `hook` isn't configured at any level (the common case, checked
+    /// before cloning anything).
+    pub(crate) fn resolve(
+        ws: &Workspace,
+        project_id: &str,
+        global_hooks: &HooksConfig,
+        hook: &str,
+    ) -> Option<Self> {
+        let project = ws.project(project_id)?;
+        let parent = project
+            .worktree_info
+            .as_ref()
+            .and_then(|wt| ws.project(&wt.parent_project_id));
+        if !hook_configured(
+            &project.hooks,
+            parent.map(|pp| &pp.hooks),
+            global_hooks,
+            hook,
+        ) {
+            return None;
+        }
+        let folder = ws.folder_for_project_or_parent(&project.id);
+        Some(Self {
+            project_hooks: project.hooks.clone(),
+            parent_hooks: parent.map(|pp| pp.hooks.clone()),
+            project_id: project.id.clone(),
+            project_name: project.name.clone(),
+            project_path: project.path.clone(),
+            folder_id: folder.map(|f| f.id.clone()),
+            folder_name: folder.map(|f| f.name.clone()),
+        })
+    }
+
+    pub(crate) fn fire_terminal_event(
+        &self,
+        terminal_id: &str,
+        terminal_name: Option<&str>,
+        global_hooks: &HooksConfig,
+        event: &TerminalHookEvent,
+        monitor: Option<&HookMonitor>,
+    ) {
+        okena_hooks::fire_terminal_event(
+            &self.project_hooks,
+            self.parent_hooks.as_ref(),
+            &self.project_id,
+            &self.project_name,
+            &self.project_path,
+            terminal_id,
+            terminal_name,
+            self.folder_id.as_deref(),
+            self.folder_name.as_deref(),
+            global_hooks,
+            event,
+            monitor,
+        );
+    }
+
+    pub(crate) fn fire_service_event(
+        &self,
+        service_name: &str,
+        global_hooks: &HooksConfig,
+        event: &ServiceHookEvent,
+        monitor: Option<&HookMonitor>,
+    ) {
+        okena_hooks::fire_service_event(
+            &self.project_hooks,
+            self.parent_hooks.as_ref(),
+            &self.project_id,
+            &self.project_name,
+            &self.project_path,
+            service_name,
+            self.folder_id.as_deref(),
+            self.folder_name.as_deref(),
+            global_hooks,
+            event,
+            monitor,
+        );
+    }
+
+    pub(crate) fn fire_git_event(
+        &self,
+        global_hooks: &HooksConfig,
+        event: &GitHookEvent,
+        monitor: Option<&HookMonitor>,
+    ) {
+        okena_hooks::fire_git_event(
+            &self.project_hooks,
+            self.parent_hooks.as_ref(),
+            &self.project_id,
+            &self.project_name,
+            &self.project_path,
+            self.folder_id.as_deref(),
+            self.folder_name.as_deref(),
+            global_hooks,
+            event,
+            monitor,
+        );
+    }
+}
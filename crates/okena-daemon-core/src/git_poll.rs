@@ -23,10 +23,14 @@ use okena_core::git_poll::{GitPollTrigger, GithubPollSchedule};
 use okena_core::process::{Lane, with_lane};
 use okena_git::repository::{CiFetch, PrFetch};
 use okena_git::{self as git, GitStatus, HeadSnapshot};
+use okena_hooks::{GitHookEvent, HookMonitor};
+use okena_workspace::persistence::AppSettings;
 use okena_workspace::state::Workspace;
 use parking_lot::Mutex;
 use tokio::sync::{Semaphore, mpsc, watch};
 
+use crate::event_hooks::ProjectHookContext;
+
 /// Responsive full-status cadence for visible or remotely subscribed projects.
 const GIT_POLL_INTERVAL: Duration = Duration::from_secs(5);
 /// Hidden projects receive a full fallback scan every 6 responsive cycles (30s).
@@ -221,11 +225,14 @@ fn merge_status_results(
 
 /// Poll only each repository's symbolic HEAD and commit id, waking the full
 /// status loop when either changes. This never reads the index or worktree.
+/// A switched reference also fires `git.on_branch_change`.
 pub async fn run_git_head_poll(
     workspace: Arc<Mutex<Workspace>>,
     remote_subscribed_terminals: Arc<RwLock<HashMap<u64, HashSet<String>>>>,
     remote_visible_projects: Arc<RwLock<HashMap<u64, HashSet<String>>>>,
     trigger_tx: mpsc::UnboundedSender<GitPollTrigger>,
+    settings: Arc<Mutex<AppSettings>>,
+    hook_monitor: Option<HookMonitor>,
 ) {
     let mut previous = HashMap::<String, HeadSnapshot>::new();
     let mut tick = 0u64;
@@ -277,27 +284,61 @@ pub async fn run_git_head_poll(
 
         // `active_ids` deliberately includes unsampled hidden projects so their
         // prior snapshots survive fast-tier ticks and later changes are detected.
-        for id in update_head_snapshots(&mut previous, &active_ids, snapshots) {
+        // A switched reference also invalidates the branch's PR/CI.
+        let changed = update_head_snapshots(
+            &mut previous,
+            &active_ids,
+            snapshots,
+            HeadSnapshot::reference_changed,
+        );
+        let mut events = Vec::new();
+        for (id, switched_from) in changed {
+            if let Some(old) = switched_from {
+                events.push((
+                    id.clone(),
+                    GitHookEvent::BranchChanged {
+                        previous: old.branch().map(str::to_string),
+                        branch: previous
+                            .get(&id)
+                            .and_then(HeadSnapshot::branch)
+                            .map(str::to_string),
+                    },
+                ));
+                if trigger_tx
+                    .send(GitPollTrigger::branch_change(id.clone()))
+                    .is_err()
+                {
+                    return;
+                }
+            }
             if trigger_tx.send(GitPollTrigger::head_change(id)).is_err() {
                 return;
             }
         }
+        fire_git_event_hooks(&workspace, &settings, hook_monitor.as_ref(), events);
     }
 }
 
+/// Store this tick's snapshots and return `(id, switched_from)` for every
+/// project whose snapshot differs from the previous one. `switched_from` is
+/// the replaced snapshot when the reference changed too.
 fn update_head_snapshots<T: PartialEq>(
     previous: &mut HashMap<String, T>,
     active_ids: &HashSet<String>,
     snapshots: HashMap<String, T>,
-) -> Vec<String> {
+    reference_changed: impl Fn(&T, &T) -> bool,
+) -> Vec<(String, Option<T>)> {
     previous.retain(|id, _| active_ids.contains(id));
     snapshots
         .into_iter()
         .filter_map(|(id, snapshot)| {
             let changed = previous.get(&id).is_some_and(|old| old != &snapshot);
             if changed {
-                previous.insert(id.clone(), snapshot);
-                Some(id)
+                let reference_changed = previous
+                    .get(&id)
+                    .is_some_and(|old| reference_changed(&snapshot, old));
+                let old = previous.insert(id.clone(), snapshot);
+                Some((id, old.filter(|_| reference_changed)))
             } else {
                 previous.insert(id, snapshot);
                 None
@@ -450,6 +491,10 @@ async fn poll_github(
     }
 }
 
+/// Merge one `gh` pass into the caches and publish. Returns the
+/// `git.on_ci_status_change` / `git.on_pr_state_change` events it produced: a
+/// change is only reported against a cached result for the same branch, so the
+/// first fetch after startup or a branch switch stays quiet.
 #[allow(clippy::too_many_arguments)]
 fn apply_github_result(
     result: GithubPollResult,
@@ -461,7 +506,7 @@ fn apply_github_result(
     last: &mut HashMap<String, GitStatus>,
     git_status_tx: &watch::Sender<HashMap<String, ApiGitStatus>>,
     state_version: &watch::Sender<u64>,
-) {
+) -> Vec<(String, GitHookEvent)> {
     let GithubPollResult {
         head_generations,
         branches,
@@ -492,9 +537,26 @@ fn apply_github_result(
         expected_generation == current_generation && expected_branch == current_branch
     };
 
+    let mut events = Vec::new();
+    let branch_of = |id: &str| branches.get(id).cloned().flatten();
     for (id, pr_info) in fetched_pr_infos {
         if is_current(&id) {
             schedule.record_pr(&id, cycle);
+            if let Some(previous) = pr_infos.get(&id) {
+                let previous_state = previous.as_ref().map(|pr| &pr.state);
+                if previous_state != pr_info.as_ref().map(|pr| &pr.state) {
+                    events.push((
+                        id.clone(),
+                        GitHookEvent::PrStateChanged {
+                            branch: branch_of(&id),
+                            number: pr_info.as_ref().map(|pr| pr.number),
+                            url: pr_info.as_ref().map(|pr| pr.url.clone()),
+                            previous: previous_state.map(|state| pr_state_name(state).into()),
+                            state: pr_info.as_ref().map(|pr| pr_state_name(&pr.state).into()),
+                        },
+                    ));
+                }
+            }
             pr_infos.insert(id, pr_info);
         }
     }
@@ -509,6 +571,22 @@ fn apply_github_result(
                     .as_ref()
                     .is_some_and(|summary| summary.status.is_pending());
                 schedule.record_ci(&id, cycle, pending, sha);
+                if let Some(previous) = ci_checks.get(&id) {
+                    let previous_status = previous.as_ref().map(|summary| &summary.status);
+                    if previous_status != summary.as_ref().map(|summary| &summary.status) {
+                        events.push((
+                            id.clone(),
+                            GitHookEvent::CiStatusChanged {
+                                branch: branch_of(&id),
+                                previous: previous_status
+                                    .map(|status| ci_status_name(status).into()),
+                                status: summary
+                                    .as_ref()
+                                    .map(|summary| ci_status_name(&summary.status).into()),
+                            },
+                        ));
+                    }
+                }
                 ci_checks.insert(id, summary);
             }
             // Refusals never make it this far — they set `rate_limited` instead.
@@ -522,6 +600,51 @@ fn apply_github_result(
         status.ci_checks = ci_checks.get(id).cloned().flatten();
     }
     publish(last, &enriched, git_status_tx, state_version);
+    events
+}
+
+fn pr_state_name(state: &git::PrState) -> &'static str {
+    match state {
+        git::PrState::Open => "open",
+        git::PrState::Draft => "draft",
+        git::PrState::Merged => "merged",
+        git::PrState::Closed => "closed",
+    }
+}
+
+fn ci_status_name(status: &git::CiStatus) -> &'static str {
+    match status {
+        git::CiStatus::Success => "success",
+        git::CiStatus::Failure => "failure",
+        git::CiStatus::Pending => "pending",
+    }
+}
+
+/// Fire the `git.*` hook for each `(project_id, event)`. Hook contexts are
+/// resolved under the workspace lock; the hooks run after it's released.
+fn fire_git_event_hooks(
+    workspace: &Mutex<Workspace>,
+    settings: &Mutex<AppSettings>,
+    hook_monitor: Option<&HookMonitor>,
+    events: Vec<(String, GitHookEvent)>,
+) {
+    if events.is_empty() {
+        return;
+    }
+    let global_hooks = settings.lock().hooks.clone();
+    let firings: Vec<_> = {
+        let ws = workspace.lock();
+        events
+            .into_iter()
+            .filter_map(|(project_id, event)| {
+                ProjectHookContext::resolve(&ws, &project_id, &global_hooks, event.hook_type())
+                    .map(|context| (context, event))
+            })
+            .collect()
+    };
+    for (context, event) in firings {
+        context.fire_git_event(&global_hooks, &event, hook_monitor);
+    }
 }
 
 /// Run the daemon git-status poll loop until the `watch` channel is closed (all
@@ -535,7 +658,10 @@ fn apply_github_result(
 /// PR/CI lookups retain their existing visible-project adaptive cadence.
 ///
 /// Bumps `state_version` on a real change so a snapshot/broadcast observer can
-/// react; the *primary* output is the `git_status_tx` watch.
+/// react; the *primary* output is the `git_status_tx` watch. CI and PR changes
+/// also fire the `git.*` hooks (global ones come from `settings`); branch
+/// changes fire from [`run_git_head_poll`].
+#[allow(clippy::too_many_arguments)]
 pub async fn run_git_poll(
     workspace: Arc<Mutex<Workspace>>,
     git_status_tx: Arc<watch::Sender<HashMap<String, ApiGitStatus>>>,
@@ -543,6 +669,8 @@ pub async fn run_git_poll(
     remote_subscribed_terminals: Arc<RwLock<HashMap<u64, HashSet<String>>>>,
     remote_visible_projects: Arc<RwLock<HashMap<u64, HashSet<String>>>>,
     mut trigger_rx: mpsc::UnboundedReceiver<GitPollTrigger>,
+    settings: Arc<Mutex<AppSettings>>,
+    hook_monitor: Option<HookMonitor>,
 ) {
     // Last-published per-project statuses, kept across cycles so we only
     // re-broadcast + bump on real change. Keyed by the richer `GitStatus`
@@ -789,7 +917,7 @@ pub async fn run_git_poll(
                 }
                 Some(result) = github_result_rx.recv() => {
                     github_in_flight = false;
-                    apply_github_result(
+                    let events = apply_github_result(
                         result,
                         cycle,
                         &head_generations,
@@ -800,6 +928,7 @@ pub async fn run_git_poll(
                         &git_status_tx,
                         &state_version,
                     );
+                    fire_git_event_hooks(&workspace, &settings, hook_monitor.as_ref(), events);
                 }
             }
         }
@@ -904,6 +1033,8 @@ mod tests {
             subscribed,
             client_visible,
             trigger_rx,
+            Arc::new(Mutex::new(AppSettings::default())),
+            None,
         )
         .await;
 
@@ -1087,7 +1218,11 @@ mod tests {
         ]);
         let active = HashSet::from(["hidden".to_string()]);
 
-        assert!(update_head_snapshots(&mut previous, &active, HashMap::new()).is_empty());
+        let same_reference = |_: &String, _: &String| false;
+        assert!(
+            update_head_snapshots(&mut previous, &active, HashMap::new(), same_reference)
+                .is_empty()
+        );
         assert_eq!(previous.get("hidden").map(String::as_str), Some("old"));
         assert!(!previous.contains_key("deleted"));
 
@@ -1095,8 +1230,20 @@ mod tests {
             &mut previous,
             &active,
             HashMap::from([("hidden".to_string(), "new".to_string())]),
+            same_reference,
+        );
+        assert_eq!(changed, vec![("hidden".to_string(), None)]);
+
+        let changed = update_head_snapshots(
+            &mut previous,
+            &active,
+            HashMap::from([("hidden".to_string(), "switched".to_string())]),
+            |new: &String, old: &String| new.len() != old.len(),
+        );
+        assert_eq!(
+            changed,
+            vec![("hidden".to_string(), Some("new".to_string()))]
         );
-        assert_eq!(changed, vec!["hidden".to_string()]);
     }
 
     fn workspace_with_hidden_project(id: &str) -> Workspace {
@@ -1214,7 +1361,7 @@ mod tests {
             result: GithubPollResult,
             cycle: u64,
             generations: &HashMap<String, u64>,
-        ) {
+        ) -> Vec<(String, GitHookEvent)> {
             apply_github_result(
                 result,
                 cycle,
@@ -1225,7 +1372,7 @@ mod tests {
                 &mut self.last,
                 &self.git_status_tx,
                 &self.state_version,
-            );
+            )
         }
     }
 
@@ -1300,6 +1447,60 @@ mod tests {
         );
     }
 
+    fn fetched_with(status: git::CiStatus) -> CiFetch {
+        CiFetch::Fetched {
+            sha: Some("abc".to_string()),
+            summary: Some(git::CiCheckSummary {
+                status,
+                passed: 0,
+                failed: 0,
+                pending: 0,
+                total: 1,
+                checks: Vec::new(),
+            }),
+        }
+    }
+
+    #[test]
+    fn ci_changes_are_reported_only_against_a_cached_result() {
+        let mut harness = ApplyHarness::new();
+        let generations = HashMap::from([("p1".to_string(), 0)]);
+
+        let first = harness.apply(
+            github_result(0, "main", fetched_with(git::CiStatus::Pending)),
+            5,
+            &generations,
+        );
+        assert!(
+            first.is_empty(),
+            "the first fetch has nothing to compare to"
+        );
+
+        let same = harness.apply(
+            github_result(0, "main", fetched_with(git::CiStatus::Pending)),
+            6,
+            &generations,
+        );
+        assert!(same.is_empty());
+
+        let changed = harness.apply(
+            github_result(0, "main", fetched_with(git::CiStatus::Failure)),
+            7,
+            &generations,
+        );
+        assert_eq!(
+            changed,
+            vec![(
+                "p1".to_string(),
+                GitHookEvent::CiStatusChanged {
+                    branch: Some("main".to_string()),
+                    previous: Some("pending".to_string()),
+                    status: Some("failure".to_string()),
+                },
+            )]
+        );
+    }
+
     fn projects() -> Vec<(String, String)> {
         vec![
             ("visible".to_string(), "/tmp/visible".to_string()),
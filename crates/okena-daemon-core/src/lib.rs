@@ -32,6 +32,7 @@
 pub mod command_loop;
 pub mod daemon;
 pub mod daemon_config;
+mod event_hooks;
 pub mod git_poll;
 pub mod observers;
 pub mod pty_loop;
@@ -46,3 +47,5 @@ pub mod worktree_close_watchdog;
 mod test_support;
 
 pub use daemon::{DaemonCore, DaemonParams};
+/// Entry point of the timed hook step helper, for the binaries that run hooks.
+pub use okena_hooks::hook_step;
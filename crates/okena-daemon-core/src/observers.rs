@@ -34,12 +34,14 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 
+use okena_hooks::ServiceHookEvent;
 use okena_services::config::{PreparedProjectConfig, prepare_project_config};
 use okena_services::manager::{
-    ServiceCx, ServiceLoadStatus, ServiceManager, ServiceTerminalWriteback,
+    ServiceCx, ServiceEvent, ServiceLoadStatus, ServiceManager, ServiceTerminalWriteback,
 };
-use okena_workspace::persistence;
+use okena_workspace::persistence::{self, AppSettings};
 
+use crate::event_hooks::ProjectHookContext;
 use crate::reactor::DaemonReactor;
 use crate::service_cx::ServiceReactorRef;
 use crate::workspace_cx::DaemonWorkspaceCx;
@@ -190,7 +192,9 @@ impl DaemonReactor {
     ///
     /// `spawn_local` does not require the futures to be `Send`, which matches the
     /// GUI's single-threaded main executor and lets the service tasks stay `!Send`.
-    pub fn spawn_observers(&self) {
+    ///
+    /// `settings` supplies the global hooks for `service.*` event hooks.
+    pub fn spawn_observers(&self, settings: Arc<parking_lot::Mutex<AppSettings>>) {
         // Subscribe to each tick *here*, synchronously, before spawning. A
         // `watch::Receiver` created now treats any bump made after this call as
         // "changed" — so a tick fired between `spawn_observers()` returning and
@@ -230,6 +234,7 @@ impl DaemonReactor {
             self.workspace_tick.clone(),
             self.hook_runner.clone(),
             self.hook_monitor.clone(),
+            settings,
         ));
     }
 }
@@ -308,9 +313,11 @@ async fn wait_for_service_sync_trigger(
     }
 }
 
-/// The service-tick observer task: bump `state_version` and write the per-project
-/// service terminal-id maps back into the workspace on every `service_tick`
-/// change.
+/// The service-tick observer task: bump `state_version`, write the per-project
+/// service terminal-id maps back into the workspace, and fire `service.on_crash`
+/// / `service.on_ready` hooks for the manager's queued events on every
+/// `service_tick` change.
+#[allow(clippy::too_many_arguments)]
 async fn service_tick_task(
     mut tick_rx: tokio::sync::watch::Receiver<u64>,
     workspace: SharedWorkspace,
@@ -319,6 +326,7 @@ async fn service_tick_task(
     workspace_tick: tokio::sync::watch::Sender<u64>,
     hook_runner: Option<okena_hooks::HookRunner>,
     hook_monitor: Option<okena_hooks::HookMonitor>,
+    settings: Arc<parking_lot::Mutex<AppSettings>>,
 ) {
     loop {
         if tick_rx.changed().await.is_err() {
@@ -331,7 +339,10 @@ async fn service_tick_task(
         //
         // Lock scope 1: snapshot the per-project terminal-id maps under the
         // service-manager lock, then DROP it.
-        let writebacks = service_manager.lock().service_terminal_writebacks();
+        let (writebacks, events) = {
+            let mut sm = service_manager.lock();
+            (sm.service_terminal_writebacks(), sm.take_service_events())
+        };
 
         // Lock scope 2: write the maps back under the workspace lock.
         // `sync_service_terminals` only notifies when a map actually changes, so
@@ -344,6 +355,58 @@ async fn service_tick_task(
             &hook_monitor,
             writebacks,
         );
+
+        if !events.is_empty() {
+            fire_service_event_hooks(&workspace, &settings, hook_monitor.as_ref(), events);
+        }
+    }
+}
+
+/// Fire the `service.*` hook for each queued service event. Hook contexts are
+/// resolved under the workspace lock; the hooks run after it's released.
+fn fire_service_event_hooks(
+    workspace: &SharedWorkspace,
+    settings: &Arc<parking_lot::Mutex<AppSettings>>,
+    hook_monitor: Option<&okena_hooks::HookMonitor>,
+    events: Vec<ServiceEvent>,
+) {
+    let global_hooks = settings.lock().hooks.clone();
+    let firings: Vec<_> = {
+        let ws = workspace.lock();
+        events
+            .into_iter()
+            .filter_map(|event| {
+                let (project_id, service_name, event) = match event {
+                    ServiceEvent::Crashed {
+                        project_id,
+                        service_name,
+                        exit_code,
+                        restarting,
+                    } => (
+                        project_id,
+                        service_name,
+                        ServiceHookEvent::Crashed {
+                            exit_code,
+                            restarting,
+                        },
+                    ),
+                    ServiceEvent::Ready {
+                        project_id,
+                        service_name,
+                    } => (project_id, service_name, ServiceHookEvent::Ready),
+                };
+                let context = ProjectHookContext::resolve(
+                    &ws,
+                    &project_id,
+                    &global_hooks,
+                    event.hook_type(),
+                )?;
+                Some((context, service_name, event))
+            })
+            .collect()
+    };
+    for (context, service_name, event) in firings {
+        context.fire_service_event(&service_name, &global_hooks, &event, hook_monitor);
     }
 }
 
@@ -2316,7 +2379,7 @@ mod tests {
         let local = tokio::task::LocalSet::new();
         local
             .run_until(async move {
-                reactor.spawn_observers();
+                reactor.spawn_observers(Arc::new(parking_lot::Mutex::new(AppSettings::default())));
 
                 let mut sv_rx = reactor.state_version.subscribe();
                 let before = *sv_rx.borrow_and_update();
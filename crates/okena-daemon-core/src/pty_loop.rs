@@ -36,7 +36,8 @@ use std::collections::HashSet;
 use std::sync::Arc;
 
 use async_channel::Receiver;
-use okena_hooks::{HookMonitor, HookRunner};
+use okena_core::events::ApiEvent;
+use okena_hooks::{HookMonitor, HookRunner, TerminalHookEvent};
 use okena_services::manager::ServiceManager;
 use okena_terminal::TerminalsRegistry;
 use okena_terminal::backend::TerminalBackend;
@@ -48,6 +49,7 @@ use parking_lot::Mutex;
 use tokio::runtime::Handle;
 use tokio::sync::watch;
 
+use crate::event_hooks::ProjectHookContext;
 use crate::service_cx::ServiceReactorRef;
 use crate::workspace_cx::DaemonWorkspaceCx;
 
@@ -84,6 +86,9 @@ pub struct PtyLoopReactor {
     /// App settings (read for the global `terminal.on_close` hook + the
     /// global-hooks arg passed into project deletion / hook firing).
     pub settings: Arc<Mutex<AppSettings>>,
+    /// `/v1/events` broadcast; activity edges are published here as they're
+    /// drained. Sends with no subscribers are simply dropped.
+    pub events_tx: tokio::sync::broadcast::Sender<ApiEvent>,
 }
 
 impl PtyLoopReactor {
@@ -408,9 +413,11 @@ fn resolve_osc_worktree_closes(
 
 /// Drain the one-shot activity edges — command-finished (OSC 133 ;D), bell, and
 /// desktop-notification (OSC 9/777) — for each terminal that produced output this
-/// batch and stamp `last_activity_at` on the owning project (drives the
-/// activity-sorted sidebar). Returns `true` if any activity was stamped, so the
-/// caller can bump `state_version` for clients to resync.
+/// batch, stamp `last_activity_at` on the owning project (drives the
+/// activity-sorted sidebar), and fire the matching `terminal.on_command_finished`
+/// / `terminal.on_bell` / `terminal.on_notification` hooks. Returns `true` if any
+/// activity was stamped, so the caller can bump `state_version` for clients to
+/// resync.
 ///
 /// Mirrors the GUI's activity bump: drain the cheap atomic edges first (almost
 /// every batch drains nothing), resolve the active terminals to their owning
@@ -423,25 +430,37 @@ fn process_activity_edges(
     terminals: &TerminalsRegistry,
     reactor: &PtyLoopReactor,
 ) -> bool {
-    // Drain edges first (cheap atomic swaps); collect the terminals that saw any
-    // meaningful activity. The lock is dropped before touching the workspace.
+    // Drain edges first (cheap swaps); collect each active terminal's events.
+    // The lock is dropped before touching the workspace.
     //
-    // Drain ALL THREE edges with `|=` (never `||`): each is a one-shot that must
-    // be consumed to clear it, so short-circuiting would leak the notification
+    // Drain ALL THREE edges unconditionally: each is a one-shot that must be
+    // consumed to clear it, so short-circuiting would leak the notification
     // queue (and miss a bell that follows a command-finish in the same batch).
-    let active: Vec<String> = {
+    let active: Vec<(String, Vec<TerminalHookEvent>)> = {
         let reg = terminals.lock();
         dirty_terminal_ids
             .iter()
-            .filter(|tid| {
-                reg.get(*tid).is_some_and(|t| {
-                    let mut a = t.take_pending_command_finished();
-                    a |= t.take_pending_bell();
-                    a |= !t.take_pending_notifications().is_empty();
-                    a
-                })
+            .filter_map(|tid| {
+                let t = reg.get(tid)?;
+                let mut events: Vec<TerminalHookEvent> = t
+                    .take_finished_commands()
+                    .into_iter()
+                    .map(|finished| TerminalHookEvent::CommandFinished {
+                        exit_code: finished.exit_code,
+                        duration: finished.duration,
+                    })
+                    .collect();
+                if t.take_pending_bell() {
+                    events.push(TerminalHookEvent::Bell);
+                }
+                events.extend(t.take_pending_notifications().into_iter().map(|n| {
+                    TerminalHookEvent::Notification {
+                        title: n.title,
+                        body: n.body,
+                    }
+                }));
+                (!events.is_empty()).then(|| (tid.clone(), events))
             })
-            .cloned()
             .collect()
     };
     if active.is_empty() {
@@ -449,14 +468,39 @@ fn process_activity_edges(
     }
 
     // Resolve each active terminal to its owning project, deduplicating so a
-    // batch touching several terminals of the same project bumps it once.
-    let project_ids: HashSet<String> = {
+    // batch touching several terminals of the same project bumps it once. Hook
+    // contexts are resolved under the same lock and fired after it's released.
+    let global_hooks = reactor.settings.lock().hooks.clone();
+    let mut project_ids: HashSet<String> = HashSet::new();
+    let mut hook_firings = Vec::new();
+    {
         let ws = reactor.workspace.lock();
-        active
-            .iter()
-            .filter_map(|tid| ws.find_project_for_terminal(tid).map(|p| p.id.clone()))
-            .collect()
-    };
+        for (tid, events) in active {
+            let Some(p) = ws.find_project_for_terminal(&tid) else {
+                continue;
+            };
+            project_ids.insert(p.id.clone());
+            let terminal_name = p.terminal_names.get(&tid).cloned();
+            for event in events {
+                let _ = reactor.events_tx.send(activity_event(&p.id, &tid, &event));
+                let hook = event.hook_type();
+                if let Some(context) = ProjectHookContext::resolve(&ws, &p.id, &global_hooks, hook)
+                {
+                    hook_firings.push((context, tid.clone(), terminal_name.clone(), event));
+                }
+            }
+        }
+    }
+    let monitor = reactor.hook_monitor.as_ref();
+    for (context, tid, terminal_name, event) in hook_firings {
+        context.fire_terminal_event(
+            &tid,
+            terminal_name.as_deref(),
+            &global_hooks,
+            &event,
+            monitor,
+        );
+    }
     if project_ids.is_empty() {
         return false;
     }
@@ -469,6 +513,33 @@ fn process_activity_edges(
     true
 }
 
+/// The `/v1/events` form of a drained activity edge.
+fn activity_event(project_id: &str, terminal_id: &str, event: &TerminalHookEvent) -> ApiEvent {
+    let project_id = project_id.to_string();
+    let terminal_id = terminal_id.to_string();
+    match event {
+        TerminalHookEvent::CommandFinished {
+            exit_code,
+            duration,
+        } => ApiEvent::CommandFinished {
+            project_id,
+            terminal_id,
+            exit_code: *exit_code,
+            duration_ms: duration.map(|d| d.as_millis() as u64),
+        },
+        TerminalHookEvent::Bell => ApiEvent::Bell {
+            project_id,
+            terminal_id,
+        },
+        TerminalHookEvent::Notification { title, body } => ApiEvent::Notification {
+            project_id,
+            terminal_id,
+            title: title.clone(),
+            body: body.clone(),
+        },
+    }
+}
+
 struct ExitHandlingContext<'a> {
     terminals: &'a TerminalsRegistry,
     pty_manager: &'a PtyManager,
@@ -813,7 +884,6 @@ fn collect_terminal_close_infos(
     reactor: &PtyLoopReactor,
     global_hooks: &okena_state::HooksConfig,
 ) -> Vec<TerminalCloseInfo> {
-    let global_on_close = global_hooks.terminal.on_close.is_some();
     let mut ws = reactor.workspace.lock();
     exit_events
         .iter()
@@ -827,20 +897,19 @@ fn collect_terminal_close_infos(
                 (None, None) => return None,
             };
             let p = ws.project(&project_id)?;
-            let parent_on_close = p
-                .worktree_info
-                .as_ref()
-                .and_then(|wt| ws.project(&wt.parent_project_id))
-                .and_then(|pp| pp.hooks.terminal.on_close.as_ref())
-                .is_some();
-            if !(global_on_close || p.hooks.terminal.on_close.is_some() || parent_on_close) {
-                return None;
-            }
             let parent_hooks = p
                 .worktree_info
                 .as_ref()
                 .and_then(|wt| ws.project(&wt.parent_project_id))
                 .map(|pp| pp.hooks.clone());
+            if !okena_hooks::hook_configured(
+                &p.hooks,
+                parent_hooks.as_ref(),
+                global_hooks,
+                "terminal.on_close",
+            ) {
+                return None;
+            }
             let terminal_name = p
                 .terminal_names
                 .get(tid)
@@ -911,6 +980,7 @@ mod tests {
             hook_monitor: Some(HookMonitor::new()),
             workspace_tick,
             settings: Arc::new(Mutex::new(settings)),
+            events_tx: tokio::sync::broadcast::channel(16).0,
         }
     }
 
@@ -1072,6 +1142,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn activity_events_carry_project_terminal_and_millis() {
+        let event = activity_event(
+            "project-1",
+            "terminal-1",
+            &TerminalHookEvent::CommandFinished {
+                exit_code: Some(2),
+                duration: Some(std::time::Duration::from_millis(1500)),
+            },
+        );
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({
+                "type": "command_finished",
+                "project_id": "project-1",
+                "terminal_id": "terminal-1",
+                "exit_code": 2,
+                "duration_ms": 1500,
+            })
+        );
+    }
+
     #[test]
     fn terminal_close_uses_retained_owner_after_layout_removal_once() {
         let mut project = plain_project("terminal-1");
@@ -40,6 +40,11 @@ impl Write for TeeWriter {
 }
 
 fn main() -> anyhow::Result<()> {
+    // A timed hook step calls back into this binary; run it and leave.
+    if let Some(code) = okena_daemon_core::hook_step::run_from_args() {
+        std::process::exit(code);
+    }
+
     okena_remote_server::local::remember_current_executable()
         .context("failed to remember daemon executable path")?;
 
@@ -71,6 +76,18 @@ fn main() -> anyhow::Result<()> {
         eprintln!("Warning: profile migration failed: {e}");
     }
 
+    // `--stdio` is the far end of a client's proxy command (`ssh host
+    // okena-daemon --stdio`): relay to the already-running daemon and exit.
+    // Before logging, which would truncate the running daemon's log, and with
+    // nothing written to stdout but the relayed bytes.
+    if std::env::args().any(|a| a == "--stdio") {
+        if let Err(e) = okena_remote_server::local::relay_stdio_to_daemon() {
+            eprintln!("okena-daemon --stdio: {e}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // Snapshot the existing config BEFORE load_settings()/load_workspace() so an
     // upgrade can be reverted to an old-format config the previous binary reads.
     // Shares the marker + config-backups dir with the GUI (first-wins, idempotent).
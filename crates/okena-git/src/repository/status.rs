@@ -20,6 +20,12 @@ impl HeadSnapshot {
     pub fn reference_changed(&self, previous: &Self) -> bool {
         self.reference != previous.reference
     }
+
+    /// Short name of the checked-out branch, `None` while HEAD is detached.
+    pub fn branch(&self) -> Option<&str> {
+        let reference = self.reference.as_deref()?;
+        Some(reference.strip_prefix("refs/heads/").unwrap_or(reference))
+    }
 }
 
 /// Three-state result of a fresh git status fetch.
@@ -691,6 +697,7 @@ mod tests {
         let switched = get_head_snapshot(&repo).expect("read switched HEAD");
         assert_ne!(switched, committed);
         assert!(switched.reference_changed(&committed));
+        assert_eq!(switched.branch(), Some("feature"));
     }
 
     #[test]
@@ -1,10 +1,12 @@
-use okena_core::api::{ApiHookExecution, ApiHookStatus};
+use crate::hook_runs::HookRunStore;
+use okena_core::api::{ApiHookExecution, ApiHookRun, ApiHookStatus};
+use okena_core::process::CommandCancellation;
 use okena_state::Toast;
 use parking_lot::Mutex;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::mpsc;
 use std::sync::{Arc, Weak};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// Maximum number of hook executions to keep in history.
 const MAX_HISTORY: usize = 50;
@@ -28,6 +30,14 @@ pub enum HookStatus {
     SpawnError {
         message: String,
     },
+    /// Killed on request (`CancelHook`).
+    Cancelled {
+        duration: Duration,
+    },
+    /// Killed after running past its timeout.
+    TimedOut {
+        duration: Duration,
+    },
 }
 
 /// A single hook execution record.
@@ -58,6 +68,14 @@ fn intern_hook_type(s: &str) -> &'static str {
         "on_rebase_conflict" => "on_rebase_conflict",
         "on_dirty_worktree_close" => "on_dirty_worktree_close",
         "terminal.on_close" => "terminal.on_close",
+        "terminal.on_command_finished" => "terminal.on_command_finished",
+        "terminal.on_bell" => "terminal.on_bell",
+        "terminal.on_notification" => "terminal.on_notification",
+        "service.on_crash" => "service.on_crash",
+        "service.on_ready" => "service.on_ready",
+        "git.on_branch_change" => "git.on_branch_change",
+        "git.on_ci_status_change" => "git.on_ci_status_change",
+        "git.on_pr_state_change" => "git.on_pr_state_change",
         _ => "unknown",
     }
 }
@@ -92,6 +110,12 @@ impl HookExecution {
             HookStatus::SpawnError { message } => ApiHookStatus::SpawnError {
                 message: message.clone(),
             },
+            HookStatus::Cancelled { duration } => ApiHookStatus::Cancelled {
+                duration_ms: duration.as_millis() as u64,
+            },
+            HookStatus::TimedOut { duration } => ApiHookStatus::TimedOut {
+                duration_ms: duration.as_millis() as u64,
+            },
         };
         ApiHookExecution {
             id: self.id,
@@ -123,6 +147,12 @@ impl HookExecution {
             ApiHookStatus::SpawnError { message } => HookStatus::SpawnError {
                 message: message.clone(),
             },
+            ApiHookStatus::Cancelled { duration_ms } => HookStatus::Cancelled {
+                duration: Duration::from_millis(*duration_ms),
+            },
+            ApiHookStatus::TimedOut { duration_ms } => HookStatus::TimedOut {
+                duration: Duration::from_millis(*duration_ms),
+            },
         };
         HookExecution {
             id: api.id,
@@ -134,6 +164,26 @@ impl HookExecution {
             terminal_id: api.terminal_id.clone(),
         }
     }
+
+    /// Persisted form of a finished headless run (see [`HookRunStore`]).
+    fn to_run(&self, stdout: String, stderr: String) -> ApiHookRun {
+        let started = SystemTime::now()
+            .checked_sub(self.started_at.elapsed())
+            .unwrap_or(UNIX_EPOCH);
+        ApiHookRun {
+            id: self.id,
+            hook_type: self.hook_type.to_string(),
+            command: self.command.clone(),
+            project_name: self.project_name.clone(),
+            started_at_ms: started
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            status: self.to_api().status,
+            stdout,
+            stderr,
+        }
+    }
 }
 
 /// Internal mutable state behind the Arc<Mutex<...>>.
@@ -146,6 +196,10 @@ struct HookMonitorInner {
     early_exits: VecDeque<(String, Option<u32>, Instant)>,
     next_exit_reservation_id: u64,
     unbound_exit_reservations: HashSet<u64>,
+    /// Kill switches for running headless hooks, keyed by execution id.
+    cancellations: HashMap<u64, CommandCancellation>,
+    /// Where finished headless runs are persisted (daemon only).
+    run_store: Option<HookRunStore>,
     /// Monotonic counter incremented on every mutation. Allows cheap
     /// "has anything changed?" checks without cloning the full history.
     version: u64,
@@ -232,10 +286,30 @@ impl HookMonitor {
             early_exits: VecDeque::new(),
             next_exit_reservation_id: 1,
             unbound_exit_reservations: HashSet::new(),
+            cancellations: HashMap::new(),
+            run_store: None,
             version: 0,
         })))
     }
 
+    /// Persist finished headless runs to `store`. Execution ids continue after
+    /// the highest stored run so they stay unique across restarts.
+    pub fn with_run_store(self, store: HookRunStore) -> Self {
+        {
+            let mut inner = self.0.lock();
+            if let Some(last_id) = store.last_id() {
+                inner.next_id = inner.next_id.max(last_id + 1);
+            }
+            inner.run_store = Some(store);
+        }
+        self
+    }
+
+    /// The persisted-run store, if this monitor has one.
+    pub fn run_store(&self) -> Option<HookRunStore> {
+        self.0.lock().run_store.clone()
+    }
+
     /// Record a hook execution start. Returns an ID to use with `record_finish`.
     pub fn record_start(
         &self,
@@ -268,6 +342,7 @@ impl HookMonitor {
                 && matches!(entry.status, HookStatus::Running)
             {
                 inner.running_count = inner.running_count.saturating_sub(1);
+                inner.cancellations.remove(&entry.id);
             }
         }
 
@@ -292,12 +367,32 @@ impl HookMonitor {
 
     /// Record hook completion (success, failure, or spawn error).
     pub fn record_finish(&self, id: u64, status: HookStatus) {
-        let mut inner = self.0.lock();
-        inner.running_count = inner.running_count.saturating_sub(1);
-        inner.version += 1;
+        self.record_finish_with_output(id, status, String::new(), String::new());
+    }
 
-        // Single-pass: find by position, then use index for both toast and status update.
-        if let Some(idx) = inner.history.iter().position(|e| e.id == id) {
+    /// Record completion of a headless hook along with its captured output,
+    /// which is persisted when a run store is configured. A no-op once the
+    /// execution was cancelled or timed out — the late result of a killed
+    /// process doesn't overwrite that.
+    pub fn record_finish_with_output(
+        &self,
+        id: u64,
+        status: HookStatus,
+        stdout: String,
+        stderr: String,
+    ) {
+        let persisted = {
+            let mut inner = self.0.lock();
+            let Some(idx) = inner
+                .history
+                .iter()
+                .position(|e| e.id == id && matches!(e.status, HookStatus::Running))
+            else {
+                return;
+            };
+            inner.running_count = inner.running_count.saturating_sub(1);
+            inner.version += 1;
+            inner.cancellations.remove(&id);
             let hook_type = inner.history[idx].hook_type;
 
             // Queue a toast on failure
@@ -316,11 +411,107 @@ impl HookMonitor {
                     );
                     inner.pending_toasts.push(Toast::error(msg));
                 }
+                HookStatus::TimedOut { duration } => {
+                    inner
+                        .pending_toasts
+                        .push(timed_out_toast(hook_type, *duration));
+                }
                 _ => {}
             }
 
             inner.history[idx].status = status;
+            persistable_run(&inner, idx, stdout, stderr)
+        };
+        persist_run(persisted);
+    }
+
+    /// Register the kill switch of a running headless hook for [`cancel`](Self::cancel).
+    pub fn register_cancellation(&self, id: u64, cancellation: CommandCancellation) {
+        let mut inner = self.0.lock();
+        let running = inner
+            .history
+            .iter()
+            .any(|e| e.id == id && matches!(e.status, HookStatus::Running));
+        if running {
+            inner.cancellations.insert(id, cancellation);
+        }
+    }
+
+    /// Cancel a running hook execution. Headless hooks are killed here; for a
+    /// terminal-backed hook the terminal id is returned and the caller kills
+    /// its PTY (the monitor has no terminal backend).
+    pub fn cancel(&self, id: u64) -> Result<Option<String>, String> {
+        let (terminal_id, cancellation, persisted) = {
+            let mut inner = self.0.lock();
+            let Some(idx) = inner.history.iter().position(|e| e.id == id) else {
+                return Err(format!("No hook execution with id {}", id));
+            };
+            if !matches!(inner.history[idx].status, HookStatus::Running) {
+                return Err(format!("Hook execution {} is not running", id));
+            }
+            let cancellation = inner.cancellations.remove(&id);
+            let terminal_id = inner.history[idx].terminal_id.clone();
+            if cancellation.is_none() && terminal_id.is_none() {
+                return Err(format!("Hook execution {} cannot be cancelled", id));
+            }
+            let duration = inner.history[idx].started_at.elapsed();
+            inner.history[idx].status = HookStatus::Cancelled { duration };
+            inner.running_count = inner.running_count.saturating_sub(1);
+            inner.version += 1;
+            (
+                terminal_id,
+                cancellation,
+                persistable_run(&inner, idx, String::new(), String::new()),
+            )
+        };
+        if let Some(cancellation) = cancellation {
+            cancellation.cancel();
         }
+        persist_run(persisted);
+        Ok(terminal_id)
+    }
+
+    /// Mark the running hook in `terminal_id` as timed out after `limit`.
+    /// Returns `true` when it was still running, i.e. the caller should kill
+    /// the terminal.
+    pub fn time_out_by_terminal_id(&self, terminal_id: &str, limit: Duration) -> bool {
+        let mut inner = self.0.lock();
+        let Some(entry) = inner.history.iter_mut().find(|e| {
+            e.terminal_id.as_deref() == Some(terminal_id) && matches!(e.status, HookStatus::Running)
+        }) else {
+            return false;
+        };
+        entry.status = HookStatus::TimedOut {
+            duration: entry.started_at.elapsed(),
+        };
+        let toast = timed_out_toast(entry.hook_type, limit);
+        inner.pending_toasts.push(toast);
+        inner.running_count = inner.running_count.saturating_sub(1);
+        inner.version += 1;
+        true
+    }
+
+    /// Current status of an execution still in the history.
+    pub fn status(&self, id: u64) -> Option<HookStatus> {
+        let inner = self.0.lock();
+        inner
+            .history
+            .iter()
+            .find(|e| e.id == id)
+            .map(|e| e.status.clone())
+    }
+
+    /// Id of the running execution attached to a hook terminal, if any.
+    pub fn running_execution_for_terminal(&self, terminal_id: &str) -> Option<u64> {
+        let inner = self.0.lock();
+        inner
+            .history
+            .iter()
+            .find(|e| {
+                e.terminal_id.as_deref() == Some(terminal_id)
+                    && matches!(e.status, HookStatus::Running)
+            })
+            .map(|e| e.id)
     }
 
     /// Drain pending toast notifications (called by UI thread).
@@ -517,6 +708,38 @@ fn prune_early_exits(early_exits: &mut VecDeque<(String, Option<u32>, Instant)>)
     }
 }
 
+fn timed_out_toast(hook_type: &str, limit: Duration) -> Toast {
+    Toast::error(format!(
+        "Hook `{}` timed out after {}s",
+        hook_type,
+        limit.as_secs()
+    ))
+}
+
+/// The store + run to write for a just-finished execution, when it is a
+/// headless hook and the monitor persists runs. Written after the lock drops.
+fn persistable_run(
+    inner: &HookMonitorInner,
+    idx: usize,
+    stdout: String,
+    stderr: String,
+) -> Option<(HookRunStore, ApiHookRun)> {
+    let store = inner.run_store.as_ref()?;
+    let execution = &inner.history[idx];
+    if execution.terminal_id.is_some() {
+        return None;
+    }
+    Some((store.clone(), execution.to_run(stdout, stderr)))
+}
+
+fn persist_run(persisted: Option<(HookRunStore, ApiHookRun)>) {
+    if let Some((store, run)) = persisted
+        && let Err(e) = store.save(&run)
+    {
+        log::warn!("Failed to persist hook run {}: {}", run.id, e);
+    }
+}
+
 fn truncate(s: &str, max: usize) -> String {
     if s.len() <= max {
         s.to_string()
@@ -796,6 +1019,119 @@ mod tests {
             }
             _ => panic!("status kind not preserved"),
         }
+
+        for status in [
+            HookStatus::Cancelled {
+                duration: Duration::from_millis(40),
+            },
+            HookStatus::TimedOut {
+                duration: Duration::from_millis(50),
+            },
+        ] {
+            let exec = HookExecution {
+                status: status.clone(),
+                ..exec.clone()
+            };
+            assert_eq!(HookExecution::from_api(&exec.to_api()).status, status);
+        }
+    }
+
+    #[test]
+    fn cancel_marks_terminal_hook_and_ignores_late_exit() {
+        let monitor = HookMonitor::new();
+        let id = monitor.record_start("on_project_open", "npm run dev", "p", Some("t1".into()));
+        assert_eq!(monitor.running_execution_for_terminal("t1"), Some(id));
+
+        assert_eq!(monitor.cancel(id), Ok(Some("t1".to_string())));
+        assert!(matches!(
+            monitor.status(id),
+            Some(HookStatus::Cancelled { .. })
+        ));
+        assert_eq!(monitor.running_count(), 0);
+        assert!(monitor.cancel(id).is_err());
+
+        // The killed PTY's exit doesn't turn the cancellation into a failure.
+        assert!(!monitor.finish_by_terminal_id("t1", Some(137)));
+        assert!(matches!(
+            monitor.status(id),
+            Some(HookStatus::Cancelled { .. })
+        ));
+        assert!(monitor.drain_pending_toasts().is_empty());
+    }
+
+    #[test]
+    fn cancel_kills_registered_headless_command() {
+        let monitor = HookMonitor::new();
+        let id = monitor.record_start("post_merge", "sleep 30", "p", None);
+        // Without a kill switch there is nothing to cancel yet.
+        assert!(monitor.cancel(id).is_err());
+
+        let handle = okena_core::process::CommandBus::global()
+            .submit(okena_core::process::CommandSpec::new("sleep").arg("30"));
+        monitor.register_cancellation(id, handle.cancellation());
+        assert_eq!(monitor.cancel(id), Ok(None));
+        let err = handle.wait().expect_err("cancelled");
+        assert_eq!(err.kind(), std::io::ErrorKind::Interrupted);
+
+        // The worker's late spawn-error report is dropped.
+        monitor.record_finish(
+            id,
+            HookStatus::SpawnError {
+                message: err.to_string(),
+            },
+        );
+        assert!(matches!(
+            monitor.status(id),
+            Some(HookStatus::Cancelled { .. })
+        ));
+        assert!(monitor.drain_pending_toasts().is_empty());
+    }
+
+    #[test]
+    fn terminal_timeout_marks_running_hook_once() {
+        let monitor = HookMonitor::new();
+        let id = monitor.record_start("post_merge", "make", "p", Some("t1".into()));
+        assert!(monitor.time_out_by_terminal_id("t1", Duration::from_secs(60)));
+        assert!(!monitor.time_out_by_terminal_id("t1", Duration::from_secs(60)));
+        assert!(matches!(
+            monitor.status(id),
+            Some(HookStatus::TimedOut { .. })
+        ));
+        let toasts = monitor.drain_pending_toasts();
+        assert_eq!(toasts.len(), 1);
+        assert!(toasts[0].message.contains("timed out after 60s"));
+    }
+
+    #[test]
+    fn run_store_persists_headless_runs_and_seeds_ids() {
+        let dir = std::env::temp_dir().join(format!("okena-monitor-runs-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = HookRunStore::new(&dir);
+
+        let monitor = HookMonitor::new().with_run_store(store.clone());
+        let headless = monitor.record_start("pre_merge", "make lint", "p", None);
+        let terminal = monitor.record_start("pre_merge", "make lint", "p", Some("t1".into()));
+        monitor.record_finish_with_output(
+            headless,
+            HookStatus::Succeeded {
+                duration: Duration::from_millis(5),
+            },
+            "all good\n".into(),
+            String::new(),
+        );
+        monitor.finish_by_terminal_id("t1", Some(0));
+
+        let run = store.get(headless).expect("headless run persisted");
+        assert_eq!(run.stdout, "all good\n");
+        assert_eq!(run.command, "make lint");
+        assert!(store.get(terminal).is_none());
+
+        // A fresh monitor (daemon restart) continues after the stored ids.
+        let restarted = HookMonitor::new().with_run_store(store);
+        let next = restarted.record_start("pre_merge", "make lint", "p", None);
+        assert!(next > headless);
+
+        let _ = std::fs::remove_dir_all(&dir);
     }
 
     #[test]
@@ -816,6 +1152,14 @@ mod tests {
             "on_rebase_conflict",
             "on_dirty_worktree_close",
             "terminal.on_close",
+            "terminal.on_command_finished",
+            "terminal.on_bell",
+            "terminal.on_notification",
+            "service.on_crash",
+            "service.on_ready",
+            "git.on_branch_change",
+            "git.on_ci_status_change",
+            "git.on_pr_state_change",
         ] {
             let api = ApiHookExecution {
                 id: 1,
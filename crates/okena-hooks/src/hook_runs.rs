@@ -0,0 +1,370 @@
+//! On-disk log of headless hook runs.
+//!
+//! The [`HookMonitor`](crate::HookMonitor) history is in-memory and capped; a
+//! [`HookRunStore`] additionally keeps each finished non-terminal run (status
+//! plus captured stdout/stderr) as `<id>.json` in a directory under the config
+//! dir, so runs can be browsed and compared after a daemon restart. Terminal
+//! hooks are not stored: their output lives in the hook terminal itself.
+
+use okena_core::api::{ApiHookRun, ApiHookRunDiff};
+use std::path::{Path, PathBuf};
+
+/// Oldest runs beyond this count are deleted on every save.
+const MAX_PERSISTED_RUNS: usize = 500;
+
+/// Per-stream cap on captured output. The tail is kept — that is where
+/// failures usually are.
+const MAX_CAPTURED_BYTES: usize = 256 * 1024;
+
+/// Beyond this many changed-line pairs the diff stops aligning lines and
+/// reports the whole changed region as removed + added.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Lines of unchanged context around each diff hunk.
+const DIFF_CONTEXT: usize = 3;
+
+/// Directory-backed store of persisted hook runs. Cheap to clone.
+#[derive(Clone, Debug)]
+pub struct HookRunStore {
+    dir: PathBuf,
+}
+
+impl HookRunStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Highest stored run id, used to keep monitor ids unique across restarts.
+    pub fn last_id(&self) -> Option<u64> {
+        self.run_ids().last().copied()
+    }
+
+    /// Write `run` (output truncated to its tail) and prune the oldest runs.
+    pub fn save(&self, run: &ApiHookRun) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let mut run = run.clone();
+        run.stdout = capture_tail(&run.stdout);
+        run.stderr = capture_tail(&run.stderr);
+        let json = serde_json::to_string(&run).map_err(std::io::Error::other)?;
+        let path = self.run_path(run.id);
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, json)?;
+        std::fs::rename(&tmp_path, &path)?;
+
+        let ids = self.run_ids();
+        if ids.len() > MAX_PERSISTED_RUNS {
+            for id in &ids[..ids.len() - MAX_PERSISTED_RUNS] {
+                let _ = std::fs::remove_file(self.run_path(*id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Stored runs, newest first, optionally filtered by hook type. Output is
+    /// stripped; fetch a single run with [`get`](Self::get) to read it.
+    pub fn list(&self, hook_type: Option<&str>, limit: usize) -> Vec<ApiHookRun> {
+        self.run_ids()
+            .into_iter()
+            .rev()
+            .filter_map(|id| self.get(id))
+            .filter(|run| hook_type.is_none_or(|ty| run.hook_type == ty))
+            .take(limit)
+            .map(|mut run| {
+                run.stdout.clear();
+                run.stderr.clear();
+                run
+            })
+            .collect()
+    }
+
+    /// One stored run including its captured output.
+    pub fn get(&self, id: u64) -> Option<ApiHookRun> {
+        let json = std::fs::read_to_string(self.run_path(id)).ok()?;
+        match serde_json::from_str(&json) {
+            Ok(run) => Some(run),
+            Err(e) => {
+                log::warn!("Ignoring unreadable hook run {}: {}", id, e);
+                None
+            }
+        }
+    }
+
+    /// Diff the output of two stored runs (`base` → `run`).
+    pub fn diff(&self, base_id: u64, run_id: u64) -> Result<ApiHookRunDiff, String> {
+        let base = self
+            .get(base_id)
+            .ok_or_else(|| format!("hook run {} not found", base_id))?;
+        let run = self
+            .get(run_id)
+            .ok_or_else(|| format!("hook run {} not found", run_id))?;
+        let base_label = format!("run {} ({})", base.id, base.hook_type);
+        let run_label = format!("run {} ({})", run.id, run.hook_type);
+        let stdout_diff = unified_diff(&base.stdout, &run.stdout, &base_label, &run_label);
+        let stderr_diff = unified_diff(&base.stderr, &run.stderr, &base_label, &run_label);
+        Ok(ApiHookRunDiff {
+            base,
+            run,
+            stdout_diff,
+            stderr_diff,
+        })
+    }
+
+    fn run_path(&self, id: u64) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    /// Ids of all stored runs, ascending.
+    fn run_ids(&self) -> Vec<u64> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut ids: Vec<u64> = entries
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name();
+                name.to_str()?.strip_suffix(".json")?.parse().ok()
+            })
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+fn capture_tail(output: &str) -> String {
+    if output.len() <= MAX_CAPTURED_BYTES {
+        return output.to_string();
+    }
+    let start = output.ceil_char_boundary(output.len() - MAX_CAPTURED_BYTES);
+    format!("[... output truncated ...]\n{}", &output[start..])
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DiffOp {
+    Same,
+    Removed,
+    Added,
+}
+
+/// Line-level unified diff of `old` → `new`; empty when they are identical.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_ops(&old_lines, &new_lines);
+    if ops.iter().all(|op| *op == DiffOp::Same) {
+        return String::new();
+    }
+
+    // Line index (old, new) at each op, to number the hunks.
+    let mut positions = Vec::with_capacity(ops.len());
+    let (mut o, mut n) = (0, 0);
+    for op in &ops {
+        positions.push((o, n));
+        match op {
+            DiffOp::Same => {
+                o += 1;
+                n += 1;
+            }
+            DiffOp::Removed => o += 1,
+            DiffOp::Added => n += 1,
+        }
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    let mut i = 0;
+    while i < ops.len() {
+        if ops[i] == DiffOp::Same {
+            i += 1;
+            continue;
+        }
+        // Extend the hunk while the next change is within 2 * context lines.
+        let start = i.saturating_sub(DIFF_CONTEXT);
+        let mut last_change = i;
+        while let Some(next) = ops[last_change + 1..]
+            .iter()
+            .position(|op| *op != DiffOp::Same)
+            .map(|offset| last_change + 1 + offset)
+            .filter(|next| next - last_change - 1 <= 2 * DIFF_CONTEXT)
+        {
+            last_change = next;
+        }
+        let end = (last_change + 1 + DIFF_CONTEXT).min(ops.len());
+
+        let (old_start, new_start) = positions[start];
+        let old_count = ops[start..end]
+            .iter()
+            .filter(|op| **op != DiffOp::Added)
+            .count();
+        let new_count = ops[start..end]
+            .iter()
+            .filter(|op| **op != DiffOp::Removed)
+            .count();
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start + 1,
+            old_count,
+            new_start + 1,
+            new_count
+        ));
+        for (op, &(o, n)) in ops[start..end].iter().zip(&positions[start..end]) {
+            match op {
+                DiffOp::Same => out.push_str(&format!(" {}\n", old_lines[o])),
+                DiffOp::Removed => out.push_str(&format!("-{}\n", old_lines[o])),
+                DiffOp::Added => out.push_str(&format!("+{}\n", new_lines[n])),
+            }
+        }
+        i = end;
+    }
+    out
+}
+
+/// Longest-common-subsequence edit script. The common prefix and suffix are
+/// trimmed first, so typical run-to-run diffs only align a small middle.
+fn diff_ops(old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut ops = vec![DiffOp::Same; prefix];
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_DIFF_CELLS {
+        ops.extend(std::iter::repeat_n(DiffOp::Removed, old_mid.len()));
+        ops.extend(std::iter::repeat_n(DiffOp::Added, new_mid.len()));
+    } else {
+        // lcs[i][j] = LCS length of old_mid[i..] and new_mid[j..].
+        let width = new_mid.len() + 1;
+        let mut lcs = vec![0u32; (old_mid.len() + 1) * width];
+        for i in (0..old_mid.len()).rev() {
+            for j in (0..new_mid.len()).rev() {
+                lcs[i * width + j] = if old_mid[i] == new_mid[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < old_mid.len() && j < new_mid.len() {
+            if old_mid[i] == new_mid[j] {
+                ops.push(DiffOp::Same);
+                i += 1;
+                j += 1;
+            } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+                ops.push(DiffOp::Removed);
+                i += 1;
+            } else {
+                ops.push(DiffOp::Added);
+                j += 1;
+            }
+        }
+        ops.extend(std::iter::repeat_n(DiffOp::Removed, old_mid.len() - i));
+        ops.extend(std::iter::repeat_n(DiffOp::Added, new_mid.len() - j));
+    }
+    ops.extend(std::iter::repeat_n(DiffOp::Same, suffix));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use okena_core::api::ApiHookStatus;
+
+    fn temp_store(name: &str) -> HookRunStore {
+        let dir =
+            std::env::temp_dir().join(format!("okena-hook-runs-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        HookRunStore::new(dir)
+    }
+
+    fn run(id: u64, hook_type: &str, stdout: &str) -> ApiHookRun {
+        ApiHookRun {
+            id,
+            hook_type: hook_type.into(),
+            command: "make".into(),
+            project_name: "proj".into(),
+            started_at_ms: 1_700_000_000_000 + id,
+            status: ApiHookStatus::Succeeded { duration_ms: 10 },
+            stdout: stdout.into(),
+            stderr: String::new(),
+        }
+    }
+
+    #[test]
+    fn store_lists_newest_first_without_output() {
+        let store = temp_store("list");
+        store.save(&run(1, "pre_merge", "a\n")).unwrap();
+        store.save(&run(2, "post_merge", "b\n")).unwrap();
+        store.save(&run(3, "pre_merge", "c\n")).unwrap();
+
+        assert_eq!(store.last_id(), Some(3));
+        let ids: Vec<u64> = store.list(None, 10).iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![3, 2, 1]);
+        let pre: Vec<u64> = store
+            .list(Some("pre_merge"), 10)
+            .iter()
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(pre, vec![3, 1]);
+        assert!(store.list(None, 1)[0].stdout.is_empty());
+        assert_eq!(store.get(3).unwrap().stdout, "c\n");
+
+        let _ = std::fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn store_prunes_oldest_runs() {
+        let store = temp_store("prune");
+        for id in 1..=(MAX_PERSISTED_RUNS as u64 + 2) {
+            store.save(&run(id, "pre_merge", "")).unwrap();
+        }
+        assert!(store.get(1).is_none());
+        assert!(store.get(2).is_none());
+        assert!(store.get(3).is_some());
+
+        let _ = std::fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn unified_diff_reports_changed_lines_with_context() {
+        let old: String = (1..=20).map(|n| format!("{n}\n")).collect();
+        let new = old
+            .replace("\n5\n", "\nfive\n")
+            .replace("\n18\n", "\neighteen\n");
+        let diff = unified_diff(&old, &new, "a", "b");
+        assert_eq!(
+            diff,
+            "--- a\n+++ b\n\
+             @@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n\
+             @@ -15,6 +15,6 @@\n 15\n 16\n 17\n-18\n+eighteen\n 19\n 20\n"
+        );
+        // Changes closer than two context windows share one hunk.
+        let near = old.replace("\n9\n", "\nnine\n");
+        let near = near.replace("\n5\n", "\nfive\n");
+        assert_eq!(
+            unified_diff(&old, &near, "a", "b").matches("@@ -").count(),
+            1
+        );
+        assert!(unified_diff(&old, &old, "a", "b").is_empty());
+    }
+
+    #[test]
+    fn diff_compares_stored_runs() {
+        let store = temp_store("diff");
+        store.save(&run(1, "pre_merge", "ok\nwarning\n")).unwrap();
+        store.save(&run(2, "pre_merge", "ok\n")).unwrap();
+
+        let diff = store.diff(1, 2).unwrap();
+        assert!(diff.stdout_diff.contains("\n-warning\n"));
+        assert!(diff.stderr_diff.is_empty());
+        assert!(store.diff(1, 9).is_err());
+
+        let _ = std::fs::remove_dir_all(store.dir());
+    }
+}
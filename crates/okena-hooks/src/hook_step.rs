@@ -0,0 +1,112 @@
+//! Per-step hook timeouts.
+//!
+//! A step with a `timeout` is rendered as a call back into the running
+//! binary, `<exe> --hook-step <secs> <command>`, with the command base64
+//! encoded so no shell quoting is involved. The helper runs the command
+//! through the shell in its own process group (a job object on Windows) and
+//! kills that whole group when the timeout passes, so grandchildren such as
+//! the node process under `npm run` go down with it. Its stdio is the step's
+//! own, so the step keeps reading the hook terminal.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use std::path::Path;
+use std::time::Duration;
+
+/// First argument that makes a binary act as the hook step helper.
+pub const HOOK_STEP_FLAG: &str = "--hook-step";
+
+/// Run as the hook step helper when this process was started as one.
+///
+/// Returns the exit code to leave with, or `None` for any other invocation.
+/// Binaries that run hooks call this first thing in `main`.
+pub fn run_from_args() -> Option<i32> {
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() != Some(HOOK_STEP_FLAG) {
+        return None;
+    }
+    let secs = args.next().and_then(|secs| secs.parse::<u64>().ok());
+    let command = args
+        .next()
+        .and_then(|encoded| URL_SAFE_NO_PAD.decode(encoded).ok())
+        .and_then(|bytes| String::from_utf8(bytes).ok());
+    let (Some(secs), Some(command)) = (secs, command) else {
+        eprintln!("usage: {HOOK_STEP_FLAG} <seconds> <base64 command>");
+        return Some(2);
+    };
+    Some(match run(&command, Duration::from_secs(secs)) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("okena: failed to run hook step: {e}");
+            127
+        }
+    })
+}
+
+/// The shell expression that runs `command` through the helper in `exe`.
+pub(crate) fn render(exe: &Path, secs: u64, command: &str, windows: bool) -> String {
+    let encoded = URL_SAFE_NO_PAD.encode(command.as_bytes());
+    let exe = exe.to_string_lossy();
+    if windows {
+        format!("\"{exe}\" {HOOK_STEP_FLAG} {secs} {encoded}")
+    } else {
+        format!(
+            "'{}' {HOOK_STEP_FLAG} {secs} {encoded}",
+            exe.replace('\'', "'\\''")
+        )
+    }
+}
+
+/// Run `command` through the shell until it exits or `timeout` passes, and
+/// return its exit code (143 after a timeout, as the shell reports SIGTERM).
+fn run(command: &str, timeout: Duration) -> std::io::Result<i32> {
+    #[cfg(unix)]
+    let mut cmd = std::process::Command::new("sh");
+    #[cfg(unix)]
+    cmd.arg("-c").arg(command);
+
+    #[cfg(windows)]
+    let mut cmd = std::process::Command::new("cmd");
+    #[cfg(windows)]
+    cmd.arg("/C").arg(command);
+
+    let Some(status) = okena_core::process::run_with_deadline(&mut cmd, timeout)? else {
+        eprintln!("okena: hook step timed out after {} s", timeout.as_secs());
+        return Ok(okena_core::process::DEADLINE_EXIT_CODE);
+    };
+    #[cfg(unix)]
+    if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+        return Ok(128 + signal);
+    }
+    Ok(status.code().unwrap_or(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rendered_steps_carry_the_command_without_quoting() {
+        let command = "echo 'it''s' \"$HOME\" && exit 3";
+        let rendered = render(Path::new("/opt/o'kena/okena"), 30, command, false);
+        let encoded = URL_SAFE_NO_PAD.encode(command);
+        assert_eq!(
+            rendered,
+            format!("'/opt/o'\\''kena/okena' --hook-step 30 {encoded}")
+        );
+        assert!(
+            encoded
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timed_out_steps_exit_143() {
+        let started = std::time::Instant::now();
+        assert_eq!(run("sleep 30", Duration::from_secs(1)).unwrap(), 143);
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(run("exit 9", Duration::from_secs(10)).unwrap(), 9);
+    }
+}
@@ -4,16 +4,18 @@
 #![allow(clippy::too_many_arguments)]
 
 use crate::hook_monitor::{HookMonitor, HookStatus};
+use crate::hook_step;
 #[cfg(feature = "gpui")]
 use gpui::App;
-use okena_state::HooksConfig;
+use okena_state::{HookMergeMode, HookStep, HooksConfig};
 use okena_terminal::TerminalsRegistry;
 use okena_terminal::backend::{TerminalBackend, TerminalLaunchCommand, TerminalLaunchPlan};
 use okena_terminal::shell_config::ShellType;
 use okena_terminal::terminal::{Terminal, TerminalSize};
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 /// Bundles the dependencies needed to run hooks through PTY terminals.
 /// Stored as a GPUI Global. All fields are Clone + Send + Sync.
@@ -49,6 +51,7 @@ pub struct HookActionPlan {
     project_name: String,
     project_id: String,
     keep_alive: bool,
+    timeout: Option<Duration>,
 }
 
 /// Execute a previously resolved hook plan with the caller's PTY services.
@@ -66,6 +69,7 @@ pub fn execute_hook_action_plan(
         runner,
         &plan.project_id,
         plan.keep_alive,
+        plan.timeout,
     )
 }
 
@@ -89,6 +93,7 @@ pub struct PreparedHookTerminal {
     launch_plan: TerminalLaunchPlan,
     monitor_command: String,
     project_name: String,
+    timeout: Option<Duration>,
 }
 
 impl PreparedHookTerminal {
@@ -177,8 +182,13 @@ impl HookRunner {
             .insert(prepared.result.terminal_id.clone(), terminal);
     }
 
-    /// Launch a prepared hook using its already-published logical id.
-    pub fn launch_prepared_terminal(&self, prepared: &PreparedHookTerminal) -> Result<(), String> {
+    /// Launch a prepared hook using its already-published logical id, arming
+    /// its timeout (if configured) against `monitor`.
+    pub fn launch_prepared_terminal(
+        &self,
+        prepared: &PreparedHookTerminal,
+        monitor: Option<&HookMonitor>,
+    ) -> Result<(), String> {
         let terminal_id = self
             .backend
             .reconnect_terminal_with_plan(
@@ -193,12 +203,34 @@ impl HookRunner {
                 "hook backend returned unexpected terminal id {terminal_id}"
             ));
         }
+        if let (Some(monitor), Some(timeout)) = (monitor, prepared.timeout) {
+            self.watch_timeout(monitor, &terminal_id, timeout);
+        }
         Ok(())
     }
 
     pub fn remove_prepared_terminal(&self, prepared: &PreparedHookTerminal) {
         self.terminals.lock().remove(&prepared.result.terminal_id);
     }
+
+    /// Kill the hook terminal once `timeout` elapses if its hook is still
+    /// running; the monitor records the execution as timed out.
+    pub fn watch_timeout(&self, monitor: &HookMonitor, terminal_id: &str, timeout: Duration) {
+        let monitor = monitor.clone();
+        let backend = self.backend.clone();
+        let terminal_id = terminal_id.to_string();
+        std::thread::spawn(move || {
+            std::thread::sleep(timeout);
+            if monitor.time_out_by_terminal_id(&terminal_id, timeout) {
+                log::warn!(
+                    "Hook in terminal {} timed out after {}s",
+                    terminal_id,
+                    timeout.as_secs()
+                );
+                backend.kill(&terminal_id);
+            }
+        });
+    }
 }
 
 /// Resolve one project-open hook without launching its PTY.
@@ -212,7 +244,6 @@ pub fn prepare_project_open_hook(
     folder_name: Option<&str>,
     global_hooks: &HooksConfig,
 ) -> Option<PreparedHookTerminal> {
-    let command = resolve_hook(project_hooks, global_hooks, |hooks| &hooks.project.on_open)?;
     let env_vars = project_env(
         project_id,
         project_name,
@@ -220,6 +251,7 @@ pub fn prepare_project_open_hook(
         folder_id,
         folder_name,
     );
+    let command = resolve_hook(project_hooks, global_hooks, "project.on_open", &env_vars)?;
     let full_command = rerunnable_hook_command(&command, &env_vars);
     let cwd = if project_path.is_empty() {
         ".".to_string()
@@ -240,6 +272,7 @@ pub fn prepare_project_open_hook(
         launch_plan,
         monitor_command: command,
         project_name: project_name.to_string(),
+        timeout: hook_timeout(project_hooks, None, global_hooks, "project.on_open"),
     })
 }
 
@@ -482,6 +515,7 @@ fn run_hook_actions(
     runner: Option<&HookRunner>,
     project_id: &str,
     keep_alive: bool,
+    timeout: Option<Duration>,
 ) -> HookActionOutcome {
     let actions = parse_hook_actions(command);
     let mut terminal_actions = Vec::new();
@@ -499,6 +533,7 @@ fn run_hook_actions(
                     runner,
                     project_id,
                     keep_alive,
+                    timeout,
                 ) {
                     hook_results.push(result);
                 }
@@ -512,29 +547,152 @@ fn run_hook_actions(
     (terminal_actions, hook_results)
 }
 
-/// Resolve a hook command: project → parent project (if worktree) → global.
+/// Hooks whose `terminal: true` steps open a pane instead of running inline.
+const TERMINAL_ACTION_HOOKS: &[&str] = &["worktree.on_rebase_conflict", "worktree.on_dirty_close"];
+
+/// Resolve a hook (`group.hook` key) into the command to run: project → global.
 fn resolve_hook(
     project_hooks: &HooksConfig,
     global_hooks: &HooksConfig,
-    get_field: fn(&HooksConfig) -> &Option<String>,
+    hook: &str,
+    env_vars: &HashMap<String, String>,
 ) -> Option<String> {
-    get_field(project_hooks)
-        .clone()
-        .or_else(|| get_field(global_hooks).clone())
+    resolve_hook_with_parent(project_hooks, None, global_hooks, hook, env_vars)
 }
 
-/// Resolve a hook command with parent project fallback for worktrees:
+/// Resolve a hook with parent project fallback for worktrees:
 /// project → parent project → global.
+///
+/// Levels are combined from global upwards: a level in `replace` mode (every
+/// plain string hook) discards what it inherits, one in `append` mode runs its
+/// steps after the inherited ones. Steps whose `when:` does not hold for the
+/// hook's branch / project path are dropped before rendering.
 fn resolve_hook_with_parent(
     project_hooks: &HooksConfig,
     parent_hooks: Option<&HooksConfig>,
     global_hooks: &HooksConfig,
-    get_field: fn(&HooksConfig) -> &Option<String>,
+    hook: &str,
+    env_vars: &HashMap<String, String>,
 ) -> Option<String> {
-    get_field(project_hooks)
-        .clone()
-        .or_else(|| parent_hooks.and_then(|h| get_field(h).clone()))
-        .or_else(|| get_field(global_hooks).clone())
+    let mut steps: Option<Vec<HookStep>> = None;
+    for level in [Some(global_hooks), parent_hooks, Some(project_hooks)]
+        .into_iter()
+        .flatten()
+    {
+        let Some(level_steps) = level.hook_steps(hook) else {
+            continue;
+        };
+        match (level_steps.merge, steps.as_mut()) {
+            (HookMergeMode::Append, Some(inherited)) => inherited.extend(level_steps.steps),
+            _ => steps = Some(level_steps.steps),
+        }
+    }
+
+    let steps = steps?;
+    let project_path = Path::new(
+        env_vars
+            .get("OKENA_PROJECT_PATH")
+            .map(String::as_str)
+            .unwrap_or("."),
+    );
+    // Only worktree hooks carry OKENA_BRANCH; ask git when a step needs it.
+    let branch = match env_vars.get("OKENA_BRANCH") {
+        Some(branch) => Some(branch.clone()),
+        None if steps.iter().any(|step| !step.when.branch.is_empty()) => {
+            okena_git::get_current_branch(project_path)
+        }
+        None => None,
+    };
+    let active: Vec<HookStep> = steps
+        .into_iter()
+        .filter(|step| step.when.matches(branch.as_deref(), project_path))
+        .collect();
+    render_hook_steps(
+        &active,
+        TERMINAL_ACTION_HOOKS.contains(&hook),
+        cfg!(windows),
+    )
+}
+
+/// Limit for headless and blocking hooks that set no `timeout`. Terminal
+/// hooks without one run unbounded (they may start long-lived processes).
+const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// The hook-level `timeout` for `hook`: the most specific level that sets
+/// one wins (project → parent project → global).
+fn hook_timeout(
+    project_hooks: &HooksConfig,
+    parent_hooks: Option<&HooksConfig>,
+    global_hooks: &HooksConfig,
+    hook: &str,
+) -> Option<Duration> {
+    [Some(project_hooks), parent_hooks, Some(global_hooks)]
+        .into_iter()
+        .flatten()
+        .find_map(|level| level.steps.get(hook).and_then(|steps| steps.timeout))
+        .map(Duration::from_secs)
+}
+
+/// Render resolved steps into one hook command string.
+///
+/// A lone step without options is passed through verbatim, so plain string
+/// hooks behave exactly as before. Otherwise inline steps are chained with
+/// `&&` (stopping at the first failure unless `continue_on_error`) and, for
+/// hooks that support it, `terminal: true` steps become `terminal:` lines.
+fn render_hook_steps(steps: &[HookStep], terminal_actions: bool, windows: bool) -> Option<String> {
+    let is_terminal = |step: &HookStep| terminal_actions && step.terminal;
+    match steps {
+        [] => return None,
+        [step] if !is_terminal(step) && !step.continue_on_error && step.timeout.is_none() => {
+            return Some(step.run.clone());
+        }
+        _ => {}
+    }
+
+    let mut lines = Vec::new();
+    let mut chain = Vec::new();
+    for step in steps {
+        if is_terminal(step) {
+            if !chain.is_empty() {
+                lines.push(chain.join(" && "));
+                chain.clear();
+            }
+            lines.push(format!("terminal: {}", step.run));
+        } else {
+            chain.push(render_hook_step(step, windows));
+        }
+    }
+    if !chain.is_empty() {
+        lines.push(chain.join(" && "));
+    }
+    Some(lines.join("\n"))
+}
+
+/// Render one inline step as a self-contained shell expression.
+fn render_hook_step(step: &HookStep, windows: bool) -> String {
+    let mut rendered = format!("({})", step.run);
+    if let Some(secs) = step.timeout {
+        // The helper kills the step's whole process group on timeout; the
+        // step then fails with 143.
+        match std::env::current_exe() {
+            Ok(exe) => {
+                rendered = format!("({})", hook_step::render(&exe, secs, &step.run, windows))
+            }
+            Err(e) => log::warn!(
+                "Hook step timeout ignored, no executable path ({e}): {}",
+                step.run
+            ),
+        }
+    }
+    if step.continue_on_error {
+        rendered = if windows {
+            format!("{rendered} || (call )")
+        } else {
+            format!("{rendered} || true")
+        };
+        rendered = format!("({rendered})");
+    }
+    rendered
 }
 
 /// Try to get the global HookMonitor from GPUI context.
@@ -556,6 +714,9 @@ pub fn try_runner(cx: &App) -> Option<HookRunner> {
 /// When `keep_alive` is true, the terminal stays interactive after the command finishes.
 /// When false, the PTY exits when the command completes (needed for hooks that gate
 /// operations like worktree removal).
+///
+/// Headless runs are killed after `timeout` (default [`DEFAULT_HOOK_TIMEOUT`]);
+/// terminal runs only when a timeout is configured.
 fn run_hook(
     command: String,
     env_vars: HashMap<String, String>,
@@ -565,6 +726,7 @@ fn run_hook(
     runner: Option<&HookRunner>,
     project_id: &str,
     keep_alive: bool,
+    timeout: Option<Duration>,
 ) -> Option<HookTerminalResult> {
     // PTY path: create a real terminal so output is visible in the service panel
     if let Some(runner) = runner {
@@ -582,9 +744,17 @@ fn run_hook(
         match runner.create_hook_terminal(&command, &env_vars, &project_path, keep_alive) {
             Ok((terminal_id, full_cmd)) => {
                 // exec_id not needed — PTY hooks are finished via finish_by_terminal_id
-                let _ = monitor.map(|m| {
-                    m.record_start(hook_type, &command, project_name, Some(terminal_id.clone()))
-                });
+                if let Some(monitor) = monitor {
+                    monitor.record_start(
+                        hook_type,
+                        &command,
+                        project_name,
+                        Some(terminal_id.clone()),
+                    );
+                    if let Some(timeout) = timeout {
+                        runner.watch_timeout(monitor, &terminal_id, timeout);
+                    }
+                }
                 log::info!(
                     "Hook '{}' started in terminal {} (label: {})",
                     hook_type,
@@ -617,59 +787,96 @@ fn run_hook(
 
     std::thread::spawn(move || {
         let start = Instant::now();
-
-        let cmd = build_headless_command(&command, &env_vars);
-        // Long lane: a hook can run for minutes, so it must not contend for the
-        // bus permits the git/services pollers need.
-        let spec = okena_core::process::CommandSpec::from_command(&cmd)
-            .lane(okena_core::process::Lane::Long)
-            .label("hook")
-            .timeout(std::time::Duration::from_secs(300));
-
-        match okena_core::process::run(spec) {
-            Ok(output) => {
-                let duration = start.elapsed();
-                if output.status.success() {
-                    if let (Some(monitor), Some(id)) = (&monitor_clone, exec_id) {
-                        monitor.record_finish(id, HookStatus::Succeeded { duration });
-                    }
-                } else {
-                    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-                    let exit_code = output.status.code().unwrap_or(-1);
-                    log::warn!("Hook command failed (exit {}): {}", exit_code, stderr,);
-                    if let (Some(monitor), Some(id)) = (&monitor_clone, exec_id) {
-                        monitor.record_finish(
-                            id,
-                            HookStatus::Failed {
-                                duration,
-                                exit_code,
-                                stderr,
-                            },
-                        );
-                    }
-                }
-            }
-            Err(e) => {
-                log::error!("Failed to execute hook command '{}': {}", command, e);
-                if let (Some(monitor), Some(id)) = (&monitor_clone, exec_id) {
-                    monitor.record_finish(
-                        id,
-                        HookStatus::SpawnError {
-                            message: e.to_string(),
-                        },
-                    );
-                }
-            }
+        let timeout = timeout.unwrap_or(DEFAULT_HOOK_TIMEOUT);
+        let result = run_headless(
+            &command,
+            &env_vars,
+            timeout,
+            monitor_clone.as_ref(),
+            exec_id,
+        );
+        let duration = start.elapsed();
+        match &result {
+            Ok(output) if !output.status.success() => log::warn!(
+                "Hook command failed (exit {}): {}",
+                output.status.code().unwrap_or(-1),
+                String::from_utf8_lossy(&output.stderr).trim(),
+            ),
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to execute hook command '{}': {}", command, e),
+        }
+        if let (Some(monitor), Some(id)) = (&monitor_clone, exec_id) {
+            record_headless_result(monitor, id, result, duration, timeout);
         }
     });
 
     None
 }
 
+/// Run a headless hook command on the command bus' long lane, registering its
+/// kill switch with the monitor so `CancelHook` can stop it.
+fn run_headless(
+    command: &str,
+    env_vars: &HashMap<String, String>,
+    timeout: Duration,
+    monitor: Option<&HookMonitor>,
+    exec_id: Option<u64>,
+) -> std::io::Result<std::process::Output> {
+    let cmd = build_headless_command(command, env_vars);
+    // Long lane: a hook can run for minutes, so it must not contend for the
+    // bus permits the git/services pollers need.
+    let spec = okena_core::process::CommandSpec::from_command(&cmd)
+        .lane(okena_core::process::Lane::Long)
+        .label("hook")
+        .timeout(timeout);
+    let handle = okena_core::process::CommandBus::global().submit(spec);
+    if let (Some(monitor), Some(id)) = (monitor, exec_id) {
+        monitor.register_cancellation(id, handle.cancellation());
+    }
+    handle.wait()
+}
+
+/// Record a headless run's outcome, with its captured output, in the monitor.
+fn record_headless_result(
+    monitor: &HookMonitor,
+    id: u64,
+    result: std::io::Result<std::process::Output>,
+    duration: Duration,
+    timeout: Duration,
+) {
+    match result {
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+            let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+            let status = if output.status.success() {
+                HookStatus::Succeeded { duration }
+            } else {
+                HookStatus::Failed {
+                    duration,
+                    exit_code: output.status.code().unwrap_or(-1),
+                    stderr: stderr.trim().to_string(),
+                }
+            };
+            monitor.record_finish_with_output(id, status, stdout, stderr);
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+            monitor.record_finish(id, HookStatus::TimedOut { duration: timeout });
+        }
+        // Cancellation already recorded the outcome; this late report is a no-op.
+        Err(e) => monitor.record_finish(
+            id,
+            HookStatus::SpawnError {
+                message: e.to_string(),
+            },
+        ),
+    }
+}
+
 /// Run a hook command synchronously, blocking until completion.
 /// When a HookRunner is available, creates a PTY terminal and waits for exit via the monitor's
 /// exit waiter channel. Otherwise falls back to headless execution.
 /// Returns Ok(Some(result)) on PTY success, Ok(None) on headless success, Err on failure.
+/// Gives up (and kills the hook) after `timeout`, default [`DEFAULT_HOOK_TIMEOUT`].
 fn run_hook_sync(
     command: &str,
     env_vars: HashMap<String, String>,
@@ -678,7 +885,9 @@ fn run_hook_sync(
     project_name: &str,
     runner: Option<&HookRunner>,
     project_id: &str,
+    timeout: Option<Duration>,
 ) -> Result<Option<HookTerminalResult>, String> {
+    let timeout = timeout.unwrap_or(DEFAULT_HOOK_TIMEOUT);
     // PTY path: requires both runner and monitor (monitor provides the exit waiter channel).
     // If runner exists but monitor is missing, fall through to headless execution.
     if let (Some(runner), Some(monitor)) = (runner, monitor) {
@@ -697,24 +906,31 @@ fn run_hook_sync(
         let (terminal_id, full_cmd) =
             runner.create_hook_terminal(command, &env_vars, &project_path, false)?;
 
-        // exec_id not needed — PTY hooks are finished via finish_by_terminal_id
-        let _ = monitor.record_start(hook_type, command, project_name, Some(terminal_id.clone()));
+        // The PTY loop finishes the execution via finish_by_terminal_id; the id
+        // is only kept to tell a cancellation apart from an ordinary failure.
+        let exec_id =
+            monitor.record_start(hook_type, command, project_name, Some(terminal_id.clone()));
 
-        // Register exit waiter and block until the PTY process exits (5 min timeout)
+        // Register exit waiter and block until the PTY process exits
         let rx = exit_reservation.bind(&terminal_id);
 
-        let exit_code = match rx.recv_timeout(std::time::Duration::from_secs(300)) {
+        let exit_code = match rx.recv_timeout(timeout) {
             Ok(exit_code) => exit_code,
             Err(error) => {
                 monitor.cancel_exit_waiter(&terminal_id);
                 runner.backend.kill(&terminal_id);
                 runner.terminals.lock().remove(&terminal_id);
-                monitor.finish_by_terminal_id(&terminal_id, None);
                 return Err(match error {
                     std::sync::mpsc::RecvTimeoutError::Timeout => {
-                        format!("Hook '{}' timed out after 5 minutes", hook_type)
+                        monitor.time_out_by_terminal_id(&terminal_id, timeout);
+                        format!(
+                            "Hook '{}' timed out after {}s",
+                            hook_type,
+                            timeout.as_secs()
+                        )
                     }
                     std::sync::mpsc::RecvTimeoutError::Disconnected => {
+                        monitor.finish_by_terminal_id(&terminal_id, None);
                         "Hook terminal exit channel closed unexpectedly".to_string()
                     }
                 });
@@ -739,6 +955,9 @@ fn run_hook_sync(
             let code = exit_code.map(|c| c as i32).unwrap_or(-1);
             runner.backend.kill(&terminal_id);
             runner.terminals.lock().remove(&terminal_id);
+            if matches!(monitor.status(exec_id), Some(HookStatus::Cancelled { .. })) {
+                return Err(format!("Hook '{}' was cancelled", hook_type));
+            }
             return Err(format!("Hook failed (exit {})", code));
         }
     } else if runner.is_some() {
@@ -752,45 +971,29 @@ fn run_hook_sync(
     let exec_id = monitor.map(|m| m.record_start(hook_type, command, project_name, None));
     let start = Instant::now();
 
-    let cmd = build_headless_command(command, &env_vars);
-    let spec = okena_core::process::CommandSpec::from_command(&cmd)
-        .lane(okena_core::process::Lane::Long)
-        .label("hook")
-        .timeout(std::time::Duration::from_secs(300));
-    let output = okena_core::process::run(spec).map_err(|e| {
-        let msg = format!("Failed to execute hook '{}': {}", command, e);
-        if let (Some(monitor), Some(id)) = (monitor, exec_id) {
-            monitor.record_finish(
-                id,
-                HookStatus::SpawnError {
-                    message: e.to_string(),
-                },
-            );
-        }
-        msg
-    })?;
-
+    let result = run_headless(command, &env_vars, timeout, monitor, exec_id);
     let duration = start.elapsed();
-    if output.status.success() {
-        if let (Some(monitor), Some(id)) = (monitor, exec_id) {
-            monitor.record_finish(id, HookStatus::Succeeded { duration });
-        }
-        Ok(None)
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-        let exit_code = output.status.code().unwrap_or(-1);
-        if let (Some(monitor), Some(id)) = (monitor, exec_id) {
-            monitor.record_finish(
-                id,
-                HookStatus::Failed {
-                    duration,
-                    exit_code,
-                    stderr: stderr.clone(),
-                },
-            );
+    let outcome = match &result {
+        Ok(output) if output.status.success() => Ok(None),
+        Ok(output) => Err(format!(
+            "Hook failed (exit {}): {}",
+            output.status.code().unwrap_or(-1),
+            String::from_utf8_lossy(&output.stderr).trim(),
+        )),
+        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => Err(format!(
+            "Hook '{}' timed out after {}s",
+            hook_type,
+            timeout.as_secs()
+        )),
+        Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {
+            Err(format!("Hook '{}' was cancelled", hook_type))
         }
-        Err(format!("Hook failed (exit {}): {}", exit_code, stderr,))
+        Err(e) => Err(format!("Failed to execute hook '{}': {}", command, e)),
+    };
+    if let (Some(monitor), Some(id)) = (monitor, exec_id) {
+        record_headless_result(monitor, id, result, duration, timeout);
     }
+    outcome
 }
 
 /// Build standard environment variables for a project hook.
@@ -830,14 +1033,14 @@ pub fn fire_on_project_open(
     runner: Option<&HookRunner>,
     monitor: Option<&HookMonitor>,
 ) -> Vec<HookTerminalResult> {
-    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, |h| &h.project.on_open) {
-        let env = project_env(
-            project_id,
-            project_name,
-            project_path,
-            folder_id,
-            folder_name,
-        );
+    let env = project_env(
+        project_id,
+        project_name,
+        project_path,
+        folder_id,
+        folder_name,
+    );
+    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, "project.on_open", &env) {
         log::info!(
             "Running on_project_open hook for project '{}'",
             project_name
@@ -851,6 +1054,7 @@ pub fn fire_on_project_open(
             runner,
             project_id,
             true,
+            hook_timeout(project_hooks, None, global_hooks, "project.on_open"),
         ) {
             return vec![result];
         }
@@ -873,14 +1077,14 @@ pub fn fire_on_project_close(
     global_hooks: &HooksConfig,
     monitor: Option<&HookMonitor>,
 ) {
-    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, |h| &h.project.on_close) {
-        let env = project_env(
-            project_id,
-            project_name,
-            project_path,
-            folder_id,
-            folder_name,
-        );
+    let env = project_env(
+        project_id,
+        project_name,
+        project_path,
+        folder_id,
+        folder_name,
+    );
+    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, "project.on_close", &env) {
         log::info!(
             "Running on_project_close hook for project '{}'",
             project_name
@@ -894,6 +1098,7 @@ pub fn fire_on_project_close(
             None,
             project_id,
             true,
+            hook_timeout(project_hooks, None, global_hooks, "project.on_close"),
         );
     }
 }
@@ -909,9 +1114,6 @@ pub fn fire_on_project_close_headless_sync(
     global_hooks: &HooksConfig,
     monitor: Option<&HookMonitor>,
 ) -> Result<(), String> {
-    let Some(command) = resolve_hook(project_hooks, global_hooks, |h| &h.project.on_close) else {
-        return Ok(());
-    };
     let env = project_env(
         project_id,
         project_name,
@@ -919,6 +1121,9 @@ pub fn fire_on_project_close_headless_sync(
         folder_id,
         folder_name,
     );
+    let Some(command) = resolve_hook(project_hooks, global_hooks, "project.on_close", &env) else {
+        return Ok(());
+    };
     log::info!(
         "Running on_project_close hook for project '{}'",
         project_name
@@ -931,6 +1136,7 @@ pub fn fire_on_project_close_headless_sync(
         project_name,
         None,
         project_id,
+        hook_timeout(project_hooks, None, global_hooks, "project.on_close"),
     )?;
     Ok(())
 }
@@ -950,15 +1156,15 @@ pub fn fire_on_worktree_create(
     runner: Option<&HookRunner>,
     monitor: Option<&HookMonitor>,
 ) -> Vec<HookTerminalResult> {
-    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, |h| &h.worktree.on_create) {
-        let mut env = project_env(
-            project_id,
-            project_name,
-            project_path,
-            folder_id,
-            folder_name,
-        );
-        env.insert("OKENA_BRANCH".into(), branch.into());
+    let mut env = project_env(
+        project_id,
+        project_name,
+        project_path,
+        folder_id,
+        folder_name,
+    );
+    env.insert("OKENA_BRANCH".into(), branch.into());
+    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, "worktree.on_create", &env) {
         log::info!("Running on_worktree_create hook for branch '{}'", branch);
         if let Some(result) = run_hook(
             cmd,
@@ -969,6 +1175,7 @@ pub fn fire_on_worktree_create(
             runner,
             project_id,
             true,
+            hook_timeout(project_hooks, None, global_hooks, "worktree.on_create"),
         ) {
             return vec![result];
         }
@@ -992,15 +1199,15 @@ pub fn fire_on_worktree_close_with_services(
     global_hooks: &HooksConfig,
     monitor: Option<&HookMonitor>,
 ) {
-    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, |h| &h.worktree.on_close) {
-        let mut env = project_env(
-            project_id,
-            project_name,
-            project_path,
-            folder_id,
-            folder_name,
-        );
-        env.insert("OKENA_BRANCH".into(), branch.into());
+    let mut env = project_env(
+        project_id,
+        project_name,
+        project_path,
+        folder_id,
+        folder_name,
+    );
+    env.insert("OKENA_BRANCH".into(), branch.into());
+    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, "worktree.on_close", &env) {
         log::info!(
             "Running on_worktree_close hook for project '{}' (branch: {})",
             project_name,
@@ -1015,6 +1222,7 @@ pub fn fire_on_worktree_close_with_services(
             None,
             project_id,
             true,
+            hook_timeout(project_hooks, None, global_hooks, "worktree.on_close"),
         );
     }
 }
@@ -1032,9 +1240,6 @@ pub fn fire_on_worktree_close_headless_sync(
     global_hooks: &HooksConfig,
     monitor: Option<&HookMonitor>,
 ) -> Result<(), String> {
-    let Some(command) = resolve_hook(project_hooks, global_hooks, |h| &h.worktree.on_close) else {
-        return Ok(());
-    };
     let mut env = project_env(
         project_id,
         project_name,
@@ -1043,6 +1248,9 @@ pub fn fire_on_worktree_close_headless_sync(
         folder_name,
     );
     env.insert("OKENA_BRANCH".into(), branch.into());
+    let Some(command) = resolve_hook(project_hooks, global_hooks, "worktree.on_close", &env) else {
+        return Ok(());
+    };
     log::info!(
         "Running on_worktree_close hook for project '{}' (branch: {})",
         project_name,
@@ -1056,6 +1264,7 @@ pub fn fire_on_worktree_close_headless_sync(
         project_name,
         None,
         project_id,
+        hook_timeout(project_hooks, None, global_hooks, "worktree.on_close"),
     )?;
     Ok(())
 }
@@ -1095,7 +1304,7 @@ fn run_hook_sync_bare(
     command: &str,
     env_vars: HashMap<String, String>,
 ) -> Result<Option<HookTerminalResult>, String> {
-    run_hook_sync(command, env_vars, None, "", "", None, "")
+    run_hook_sync(command, env_vars, None, "", "", None, "", None)
 }
 
 /// Build extended environment for merge/worktree-remove hooks.
@@ -1137,17 +1346,17 @@ pub fn fire_pre_merge(
     monitor: Option<&HookMonitor>,
     runner: Option<&HookRunner>,
 ) -> Result<Option<HookTerminalResult>, String> {
-    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, |h| &h.worktree.pre_merge) {
-        let env = merge_env(
-            project_id,
-            project_name,
-            project_path,
-            branch,
-            target_branch,
-            main_repo_path,
-            folder_id,
-            folder_name,
-        );
+    let env = merge_env(
+        project_id,
+        project_name,
+        project_path,
+        branch,
+        target_branch,
+        main_repo_path,
+        folder_id,
+        folder_name,
+    );
+    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, "worktree.pre_merge", &env) {
         log::info!("Running pre_merge hook for project '{}'", project_name);
         return run_hook_sync(
             &cmd,
@@ -1157,6 +1366,7 @@ pub fn fire_pre_merge(
             project_name,
             runner,
             project_id,
+            hook_timeout(project_hooks, None, global_hooks, "worktree.pre_merge"),
         );
     }
     Ok(None)
@@ -1177,17 +1387,17 @@ pub fn fire_post_merge(
     monitor: Option<&HookMonitor>,
     runner: Option<&HookRunner>,
 ) -> Vec<HookTerminalResult> {
-    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, |h| &h.worktree.post_merge) {
-        let env = merge_env(
-            project_id,
-            project_name,
-            project_path,
-            branch,
-            target_branch,
-            main_repo_path,
-            folder_id,
-            folder_name,
-        );
+    let env = merge_env(
+        project_id,
+        project_name,
+        project_path,
+        branch,
+        target_branch,
+        main_repo_path,
+        folder_id,
+        folder_name,
+    );
+    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, "worktree.post_merge", &env) {
         log::info!("Running post_merge hook for project '{}'", project_name);
         if let Some(result) = run_hook(
             cmd,
@@ -1198,6 +1408,7 @@ pub fn fire_post_merge(
             runner,
             project_id,
             true,
+            hook_timeout(project_hooks, None, global_hooks, "worktree.post_merge"),
         ) {
             return vec![result];
         }
@@ -1219,10 +1430,6 @@ pub fn fire_post_merge_headless_sync(
     folder_name: Option<&str>,
     monitor: Option<&HookMonitor>,
 ) -> Result<(), String> {
-    let Some(command) = resolve_hook(project_hooks, global_hooks, |h| &h.worktree.post_merge)
-    else {
-        return Ok(());
-    };
     let env = merge_env(
         project_id,
         project_name,
@@ -1233,6 +1440,10 @@ pub fn fire_post_merge_headless_sync(
         folder_id,
         folder_name,
     );
+    let Some(command) = resolve_hook(project_hooks, global_hooks, "worktree.post_merge", &env)
+    else {
+        return Ok(());
+    };
     log::info!("Running post_merge hook synchronously for project '{project_name}'");
     run_hook_sync(
         &command,
@@ -1242,6 +1453,7 @@ pub fn fire_post_merge_headless_sync(
         project_name,
         None,
         project_id,
+        hook_timeout(project_hooks, None, global_hooks, "worktree.post_merge"),
     )?;
     Ok(())
 }
@@ -1260,16 +1472,16 @@ pub fn fire_before_worktree_remove(
     monitor: Option<&HookMonitor>,
     runner: Option<&HookRunner>,
 ) -> Result<Option<HookTerminalResult>, String> {
-    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, |h| &h.worktree.before_remove) {
-        let mut env = project_env(
-            project_id,
-            project_name,
-            project_path,
-            folder_id,
-            folder_name,
-        );
-        env.insert("OKENA_BRANCH".into(), branch.into());
-        env.insert("OKENA_MAIN_REPO_PATH".into(), main_repo_path.into());
+    let mut env = project_env(
+        project_id,
+        project_name,
+        project_path,
+        folder_id,
+        folder_name,
+    );
+    env.insert("OKENA_BRANCH".into(), branch.into());
+    env.insert("OKENA_MAIN_REPO_PATH".into(), main_repo_path.into());
+    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, "worktree.before_remove", &env) {
         log::info!(
             "Running before_worktree_remove hook for project '{}'",
             project_name
@@ -1282,6 +1494,7 @@ pub fn fire_before_worktree_remove(
             project_name,
             runner,
             project_id,
+            hook_timeout(project_hooks, None, global_hooks, "worktree.before_remove"),
         );
     }
     Ok(None)
@@ -1303,16 +1516,16 @@ pub fn fire_before_worktree_remove_async(
     monitor: Option<&HookMonitor>,
     runner: Option<&HookRunner>,
 ) -> Vec<HookTerminalResult> {
-    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, |h| &h.worktree.before_remove) {
-        let mut env = project_env(
-            project_id,
-            project_name,
-            project_path,
-            folder_id,
-            folder_name,
-        );
-        env.insert("OKENA_BRANCH".into(), branch.into());
-        env.insert("OKENA_MAIN_REPO_PATH".into(), main_repo_path.into());
+    let mut env = project_env(
+        project_id,
+        project_name,
+        project_path,
+        folder_id,
+        folder_name,
+    );
+    env.insert("OKENA_BRANCH".into(), branch.into());
+    env.insert("OKENA_MAIN_REPO_PATH".into(), main_repo_path.into());
+    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, "worktree.before_remove", &env) {
         log::info!(
             "Running before_worktree_remove hook (async) for project '{}'",
             project_name
@@ -1326,6 +1539,7 @@ pub fn fire_before_worktree_remove_async(
             runner,
             project_id,
             false,
+            hook_timeout(project_hooks, None, global_hooks, "worktree.before_remove"),
         ) {
             return vec![result];
         }
@@ -1348,20 +1562,23 @@ pub fn plan_on_rebase_conflict(
     folder_id: Option<&str>,
     folder_name: Option<&str>,
 ) -> Option<HookActionPlan> {
-    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, |h| {
-        &h.worktree.on_rebase_conflict
-    }) {
-        let mut env = merge_env(
-            project_id,
-            project_name,
-            project_path,
-            branch,
-            target_branch,
-            main_repo_path,
-            folder_id,
-            folder_name,
-        );
-        env.insert("OKENA_REBASE_ERROR".into(), rebase_error.into());
+    let mut env = merge_env(
+        project_id,
+        project_name,
+        project_path,
+        branch,
+        target_branch,
+        main_repo_path,
+        folder_id,
+        folder_name,
+    );
+    env.insert("OKENA_REBASE_ERROR".into(), rebase_error.into());
+    if let Some(cmd) = resolve_hook(
+        project_hooks,
+        global_hooks,
+        "worktree.on_rebase_conflict",
+        &env,
+    ) {
         log::info!(
             "Running on_rebase_conflict hook for project '{}'",
             project_name
@@ -1373,6 +1590,12 @@ pub fn plan_on_rebase_conflict(
             project_name: project_name.to_string(),
             project_id: project_id.to_string(),
             keep_alive: true,
+            timeout: hook_timeout(
+                project_hooks,
+                None,
+                global_hooks,
+                "worktree.on_rebase_conflict",
+            ),
         });
     }
     None
@@ -1428,15 +1651,15 @@ pub fn fire_on_dirty_worktree_close(
     monitor: Option<&HookMonitor>,
     runner: Option<&HookRunner>,
 ) -> HookActionOutcome {
-    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, |h| &h.worktree.on_dirty_close) {
-        let mut env = project_env(
-            project_id,
-            project_name,
-            project_path,
-            folder_id,
-            folder_name,
-        );
-        env.insert("OKENA_BRANCH".into(), branch.into());
+    let mut env = project_env(
+        project_id,
+        project_name,
+        project_path,
+        folder_id,
+        folder_name,
+    );
+    env.insert("OKENA_BRANCH".into(), branch.into());
+    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, "worktree.on_dirty_close", &env) {
         log::info!(
             "Running on_dirty_worktree_close hook for project '{}'",
             project_name
@@ -1450,6 +1673,7 @@ pub fn fire_on_dirty_worktree_close(
             runner,
             project_id,
             true,
+            hook_timeout(project_hooks, None, global_hooks, "worktree.on_dirty_close"),
         );
     }
     (Vec::new(), Vec::new())
@@ -1467,11 +1691,6 @@ pub fn fire_on_dirty_worktree_close_headless(
     folder_name: Option<&str>,
     monitor: Option<&HookMonitor>,
 ) -> Result<(), String> {
-    let Some(command) = resolve_hook(project_hooks, global_hooks, |h| &h.worktree.on_dirty_close)
-    else {
-        return Ok(());
-    };
-
     let mut env = project_env(
         project_id,
         project_name,
@@ -1480,6 +1699,10 @@ pub fn fire_on_dirty_worktree_close_headless(
         folder_name,
     );
     env.insert("OKENA_BRANCH".into(), branch.into());
+    let Some(command) = resolve_hook(project_hooks, global_hooks, "worktree.on_dirty_close", &env)
+    else {
+        return Ok(());
+    };
     log::info!(
         "Running on_dirty_worktree_close hook for project '{}'",
         project_name
@@ -1497,6 +1720,7 @@ pub fn fire_on_dirty_worktree_close_headless(
             project_name,
             None,
             project_id,
+            hook_timeout(project_hooks, None, global_hooks, "worktree.on_dirty_close"),
         )?;
     }
     Ok(())
@@ -1516,16 +1740,16 @@ pub fn fire_worktree_removed(
     monitor: Option<&HookMonitor>,
     runner: Option<&HookRunner>,
 ) -> Vec<HookTerminalResult> {
-    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, |h| &h.worktree.after_remove) {
-        let mut env = project_env(
-            project_id,
-            project_name,
-            project_path,
-            folder_id,
-            folder_name,
-        );
-        env.insert("OKENA_BRANCH".into(), branch.into());
-        env.insert("OKENA_MAIN_REPO_PATH".into(), main_repo_path.into());
+    let mut env = project_env(
+        project_id,
+        project_name,
+        project_path,
+        folder_id,
+        folder_name,
+    );
+    env.insert("OKENA_BRANCH".into(), branch.into());
+    env.insert("OKENA_MAIN_REPO_PATH".into(), main_repo_path.into());
+    if let Some(cmd) = resolve_hook(project_hooks, global_hooks, "worktree.after_remove", &env) {
         log::info!(
             "Running worktree_removed hook for project '{}'",
             project_name
@@ -1539,6 +1763,7 @@ pub fn fire_worktree_removed(
             runner,
             project_id,
             true,
+            hook_timeout(project_hooks, None, global_hooks, "worktree.after_remove"),
         ) {
             return vec![result];
         }
@@ -1553,23 +1778,35 @@ pub fn resolve_terminal_on_create(
     project_hooks: &HooksConfig,
     parent_hooks: Option<&HooksConfig>,
     global_hooks: &HooksConfig,
+    env_vars: &HashMap<String, String>,
     _cx: &App,
 ) -> Option<String> {
-    resolve_hook_with_parent(project_hooks, parent_hooks, global_hooks, |h| {
-        &h.terminal.on_create
-    })
+    resolve_hook_with_parent(
+        project_hooks,
+        parent_hooks,
+        global_hooks,
+        "terminal.on_create",
+        env_vars,
+    )
 }
 
 /// Resolve the `terminal.on_create` hook command (without GPUI context).
 /// Returns the command string if configured at any level (project/parent/global).
+/// `env_vars` are the terminal's hook env (see [`terminal_hook_env`]), used to
+/// evaluate step conditions.
 pub fn resolve_terminal_on_create_simple(
     project_hooks: &HooksConfig,
     parent_hooks: Option<&HooksConfig>,
     global_hooks: &HooksConfig,
+    env_vars: &HashMap<String, String>,
 ) -> Option<String> {
-    resolve_hook_with_parent(project_hooks, parent_hooks, global_hooks, |h| {
-        &h.terminal.on_create
-    })
+    resolve_hook_with_parent(
+        project_hooks,
+        parent_hooks,
+        global_hooks,
+        "terminal.on_create",
+        env_vars,
+    )
 }
 
 /// Apply the `terminal.on_create` command by wrapping the shell to run
@@ -1595,7 +1832,10 @@ pub fn terminal_launch_plan(
     on_create: Option<&str>,
     env_vars: &HashMap<String, String>,
 ) -> TerminalLaunchPlan {
-    if shell_wrapper.is_none() && on_create.is_none() {
+    // Hooks describe host-shell setup; a container shell launches untouched.
+    if (shell_wrapper.is_none() && on_create.is_none())
+        || matches!(shell, ShellType::DockerExec { .. })
+    {
         return TerminalLaunchPlan::for_shell(shell);
     }
 
@@ -1668,6 +1908,7 @@ fn terminal_launch_parts(shell: &ShellType) -> (String, Vec<String>, String, &'s
                 true,
             );
         }
+        ShellType::DockerExec { .. } => unreachable!("container shells launch without hooks"),
     }
 
     #[cfg(not(windows))]
@@ -1678,6 +1919,7 @@ fn terminal_launch_parts(shell: &ShellType) -> (String, Vec<String>, String, &'s
                 std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string()),
                 Vec::new(),
             ),
+            ShellType::DockerExec { .. } => unreachable!("container shells launch without hooks"),
         };
         args.push("-ic".to_string());
         (
@@ -1712,32 +1954,45 @@ pub fn fire_terminal_on_close_with_services(
     global_hooks: &HooksConfig,
     monitor: Option<&HookMonitor>,
 ) {
-    if let Some(cmd) = resolve_hook_with_parent(project_hooks, parent_hooks, global_hooks, |h| {
-        &h.terminal.on_close
-    }) {
-        let mut env = project_env(
-            project_id,
-            project_name,
-            project_path,
-            folder_id,
-            folder_name,
-        );
-        env.insert("OKENA_TERMINAL_ID".into(), terminal_id.into());
-        if let Some(name) = terminal_name {
-            env.insert("OKENA_TERMINAL_NAME".into(), name.into());
-        }
-        if let Some(code) = exit_code {
-            env.insert("OKENA_EXIT_CODE".into(), code.to_string());
-        }
-        if is_worktree {
-            let path = std::path::Path::new(project_path);
-            let branch = okena_git::get_git_status(path)
-                .and_then(|s| s.branch)
-                .or_else(|| okena_git::get_current_branch(path));
-            if let Some(branch) = branch {
-                env.insert("OKENA_BRANCH".into(), branch);
-            }
+    // Skip the git lookup below for the common no-hook case.
+    if !hook_configured(
+        project_hooks,
+        parent_hooks,
+        global_hooks,
+        "terminal.on_close",
+    ) {
+        return;
+    }
+    let mut env = project_env(
+        project_id,
+        project_name,
+        project_path,
+        folder_id,
+        folder_name,
+    );
+    env.insert("OKENA_TERMINAL_ID".into(), terminal_id.into());
+    if let Some(name) = terminal_name {
+        env.insert("OKENA_TERMINAL_NAME".into(), name.into());
+    }
+    if let Some(code) = exit_code {
+        env.insert("OKENA_EXIT_CODE".into(), code.to_string());
+    }
+    if is_worktree {
+        let path = Path::new(project_path);
+        let branch = okena_git::get_git_status(path)
+            .and_then(|s| s.branch)
+            .or_else(|| okena_git::get_current_branch(path));
+        if let Some(branch) = branch {
+            env.insert("OKENA_BRANCH".into(), branch);
         }
+    }
+    if let Some(cmd) = resolve_hook_with_parent(
+        project_hooks,
+        parent_hooks,
+        global_hooks,
+        "terminal.on_close",
+        &env,
+    ) {
         log::info!(
             "Running terminal.on_close hook for terminal '{}'",
             terminal_id
@@ -1751,6 +2006,12 @@ pub fn fire_terminal_on_close_with_services(
             None,
             project_id,
             true,
+            hook_timeout(
+                project_hooks,
+                parent_hooks,
+                global_hooks,
+                "terminal.on_close",
+            ),
         );
     }
 }
@@ -1793,6 +2054,328 @@ pub fn fire_terminal_on_close(
     );
 }
 
+/// A terminal event that fires a `terminal.*` hook.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TerminalHookEvent {
+    /// A shell-integrated command finished (OSC 133 ;D).
+    CommandFinished {
+        exit_code: Option<i32>,
+        duration: Option<Duration>,
+    },
+    Bell,
+    /// A desktop notification (OSC 9/777/99).
+    Notification {
+        title: Option<String>,
+        body: String,
+    },
+}
+
+impl TerminalHookEvent {
+    /// The `group.hook` key this event fires.
+    pub fn hook_type(&self) -> &'static str {
+        match self {
+            Self::CommandFinished { .. } => "terminal.on_command_finished",
+            Self::Bell => "terminal.on_bell",
+            Self::Notification { .. } => "terminal.on_notification",
+        }
+    }
+
+    fn add_env(&self, env: &mut HashMap<String, String>) {
+        match self {
+            Self::CommandFinished {
+                exit_code,
+                duration,
+            } => {
+                if let Some(code) = exit_code {
+                    env.insert("OKENA_EXIT_CODE".into(), code.to_string());
+                }
+                if let Some(duration) = duration {
+                    env.insert(
+                        "OKENA_COMMAND_DURATION_MS".into(),
+                        duration.as_millis().to_string(),
+                    );
+                }
+            }
+            Self::Bell => {}
+            Self::Notification { title, body } => {
+                if let Some(title) = title {
+                    env.insert("OKENA_NOTIFICATION_TITLE".into(), title.clone());
+                }
+                env.insert("OKENA_NOTIFICATION_BODY".into(), body.clone());
+            }
+        }
+    }
+}
+
+/// A service event that fires a `service.*` hook.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ServiceHookEvent {
+    Crashed {
+        exit_code: Option<u32>,
+        restarting: bool,
+    },
+    Ready,
+}
+
+impl ServiceHookEvent {
+    /// The `group.hook` key this event fires.
+    pub fn hook_type(&self) -> &'static str {
+        match self {
+            Self::Crashed { .. } => "service.on_crash",
+            Self::Ready => "service.on_ready",
+        }
+    }
+
+    fn add_env(&self, env: &mut HashMap<String, String>) {
+        if let Self::Crashed {
+            exit_code,
+            restarting,
+        } = self
+        {
+            if let Some(code) = exit_code {
+                env.insert("OKENA_EXIT_CODE".into(), code.to_string());
+            }
+            env.insert("OKENA_SERVICE_RESTARTING".into(), restarting.to_string());
+        }
+    }
+}
+
+/// A git or forge change that fires a `git.*` hook. States are the lowercase
+/// wire names (`success`, `open`, ...); `None` means "none known".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GitHookEvent {
+    BranchChanged {
+        previous: Option<String>,
+        branch: Option<String>,
+    },
+    CiStatusChanged {
+        branch: Option<String>,
+        previous: Option<String>,
+        status: Option<String>,
+    },
+    PrStateChanged {
+        branch: Option<String>,
+        number: Option<u32>,
+        url: Option<String>,
+        previous: Option<String>,
+        state: Option<String>,
+    },
+}
+
+impl GitHookEvent {
+    /// The `group.hook` key this event fires.
+    pub fn hook_type(&self) -> &'static str {
+        match self {
+            Self::BranchChanged { .. } => "git.on_branch_change",
+            Self::CiStatusChanged { .. } => "git.on_ci_status_change",
+            Self::PrStateChanged { .. } => "git.on_pr_state_change",
+        }
+    }
+
+    fn add_env(&self, env: &mut HashMap<String, String>) {
+        let mut set = |key: &str, value: Option<String>| {
+            if let Some(value) = value {
+                env.insert(key.into(), value);
+            }
+        };
+        match self {
+            Self::BranchChanged { previous, branch } => {
+                set("OKENA_BRANCH", branch.clone());
+                set("OKENA_PREVIOUS_BRANCH", previous.clone());
+            }
+            Self::CiStatusChanged {
+                branch,
+                previous,
+                status,
+            } => {
+                set("OKENA_BRANCH", branch.clone());
+                set("OKENA_CI_STATUS", status.clone());
+                set("OKENA_PREVIOUS_CI_STATUS", previous.clone());
+            }
+            Self::PrStateChanged {
+                branch,
+                number,
+                url,
+                previous,
+                state,
+            } => {
+                set("OKENA_BRANCH", branch.clone());
+                set("OKENA_PR_NUMBER", number.map(|n| n.to_string()));
+                set("OKENA_PR_URL", url.clone());
+                set("OKENA_PR_STATE", state.clone());
+                set("OKENA_PREVIOUS_PR_STATE", previous.clone());
+            }
+        }
+    }
+}
+
+/// Whether `hook` is set at any level. Event hooks fire often (every finished
+/// command), so callers check this before building env or asking git.
+pub fn hook_configured(
+    project_hooks: &HooksConfig,
+    parent_hooks: Option<&HooksConfig>,
+    global_hooks: &HooksConfig,
+    hook: &str,
+) -> bool {
+    [Some(project_hooks), parent_hooks, Some(global_hooks)]
+        .into_iter()
+        .flatten()
+        .any(|hooks| hooks.has_hook(hook))
+}
+
+/// Resolve and run an event hook headlessly (no PTY runner: a hook terminal
+/// of its own could ring the bell or notify and re-trigger the hook).
+fn fire_event_hook(
+    project_hooks: &HooksConfig,
+    parent_hooks: Option<&HooksConfig>,
+    global_hooks: &HooksConfig,
+    hook: &'static str,
+    env: HashMap<String, String>,
+    project_id: &str,
+    project_name: &str,
+    monitor: Option<&HookMonitor>,
+) {
+    if let Some(cmd) =
+        resolve_hook_with_parent(project_hooks, parent_hooks, global_hooks, hook, &env)
+    {
+        log::info!("Running {} hook for project '{}'", hook, project_name);
+        run_hook(
+            cmd,
+            env,
+            monitor,
+            hook,
+            project_name,
+            None,
+            project_id,
+            true,
+            hook_timeout(project_hooks, parent_hooks, global_hooks, hook),
+        );
+    }
+}
+
+/// Fire the `terminal.*` hook for `event`. Env: the project vars plus
+/// `OKENA_TERMINAL_ID` / `OKENA_TERMINAL_NAME` and the event's own vars.
+pub fn fire_terminal_event(
+    project_hooks: &HooksConfig,
+    parent_hooks: Option<&HooksConfig>,
+    project_id: &str,
+    project_name: &str,
+    project_path: &str,
+    terminal_id: &str,
+    terminal_name: Option<&str>,
+    folder_id: Option<&str>,
+    folder_name: Option<&str>,
+    global_hooks: &HooksConfig,
+    event: &TerminalHookEvent,
+    monitor: Option<&HookMonitor>,
+) {
+    let hook = event.hook_type();
+    if !hook_configured(project_hooks, parent_hooks, global_hooks, hook) {
+        return;
+    }
+    let mut env = project_env(
+        project_id,
+        project_name,
+        project_path,
+        folder_id,
+        folder_name,
+    );
+    env.insert("OKENA_TERMINAL_ID".into(), terminal_id.into());
+    if let Some(name) = terminal_name {
+        env.insert("OKENA_TERMINAL_NAME".into(), name.into());
+    }
+    event.add_env(&mut env);
+    fire_event_hook(
+        project_hooks,
+        parent_hooks,
+        global_hooks,
+        hook,
+        env,
+        project_id,
+        project_name,
+        monitor,
+    );
+}
+
+/// Fire the `service.*` hook for `event`. Env: the project vars plus
+/// `OKENA_SERVICE_NAME` and, for crashes, `OKENA_EXIT_CODE` /
+/// `OKENA_SERVICE_RESTARTING`.
+pub fn fire_service_event(
+    project_hooks: &HooksConfig,
+    parent_hooks: Option<&HooksConfig>,
+    project_id: &str,
+    project_name: &str,
+    project_path: &str,
+    service_name: &str,
+    folder_id: Option<&str>,
+    folder_name: Option<&str>,
+    global_hooks: &HooksConfig,
+    event: &ServiceHookEvent,
+    monitor: Option<&HookMonitor>,
+) {
+    let hook = event.hook_type();
+    if !hook_configured(project_hooks, parent_hooks, global_hooks, hook) {
+        return;
+    }
+    let mut env = project_env(
+        project_id,
+        project_name,
+        project_path,
+        folder_id,
+        folder_name,
+    );
+    env.insert("OKENA_SERVICE_NAME".into(), service_name.into());
+    event.add_env(&mut env);
+    fire_event_hook(
+        project_hooks,
+        parent_hooks,
+        global_hooks,
+        hook,
+        env,
+        project_id,
+        project_name,
+        monitor,
+    );
+}
+
+/// Fire the `git.*` hook for `event`. Env: the project vars plus
+/// `OKENA_BRANCH` and the event's previous/current values.
+pub fn fire_git_event(
+    project_hooks: &HooksConfig,
+    parent_hooks: Option<&HooksConfig>,
+    project_id: &str,
+    project_name: &str,
+    project_path: &str,
+    folder_id: Option<&str>,
+    folder_name: Option<&str>,
+    global_hooks: &HooksConfig,
+    event: &GitHookEvent,
+    monitor: Option<&HookMonitor>,
+) {
+    let hook = event.hook_type();
+    if !hook_configured(project_hooks, parent_hooks, global_hooks, hook) {
+        return;
+    }
+    let mut env = project_env(
+        project_id,
+        project_name,
+        project_path,
+        folder_id,
+        folder_name,
+    );
+    event.add_env(&mut env);
+    fire_event_hook(
+        project_hooks,
+        parent_hooks,
+        global_hooks,
+        hook,
+        env,
+        project_id,
+        project_name,
+        monitor,
+    );
+}
+
 /// Resolve the shell_wrapper for terminal creation.
 /// Returns the wrapper command template if configured (project or global level).
 pub fn resolve_shell_wrapper(
@@ -1800,9 +2383,11 @@ pub fn resolve_shell_wrapper(
     parent_hooks: Option<&HooksConfig>,
     global_hooks: &HooksConfig,
 ) -> Option<String> {
-    resolve_hook_with_parent(project_hooks, parent_hooks, global_hooks, |h| {
-        &h.terminal.shell_wrapper
-    })
+    // A wrapper is a template, not a command: no steps, no merging.
+    [Some(project_hooks), parent_hooks, Some(global_hooks)]
+        .into_iter()
+        .flatten()
+        .find_map(|hooks| hooks.terminal.shell_wrapper.clone())
 }
 
 /// Apply shell_wrapper to a ShellType, producing a new ShellType.
@@ -1882,6 +2467,7 @@ mod tests {
             "Project",
             Some(&runner),
             "p1",
+            None,
         );
         let terminal_id = event_thread.join().expect("event thread joins");
 
@@ -1895,6 +2481,61 @@ mod tests {
         pty_manager.flush_teardown();
     }
 
+    #[test]
+    fn headless_sync_hook_times_out_and_keeps_output_of_finished_runs() {
+        let monitor = HookMonitor::new();
+        let result = run_hook_sync(
+            "sleep 5",
+            HashMap::new(),
+            Some(&monitor),
+            "pre_merge",
+            "Project",
+            None,
+            "p1",
+            Some(Duration::from_secs(1)),
+        );
+        assert_eq!(
+            result.err().as_deref(),
+            Some("Hook 'pre_merge' timed out after 1s")
+        );
+        assert!(matches!(
+            monitor.history()[0].status,
+            HookStatus::TimedOut { .. }
+        ));
+        assert_eq!(monitor.drain_pending_toasts().len(), 1);
+    }
+
+    #[test]
+    fn hook_timeout_prefers_the_most_specific_level() {
+        let timed = |timeout| {
+            let mut config = HooksConfig::default();
+            config.steps.insert(
+                "worktree.pre_merge".into(),
+                okena_state::HookSteps {
+                    merge: HookMergeMode::Replace,
+                    timeout,
+                    steps: vec![HookStep::command("lint")],
+                },
+            );
+            config
+        };
+        let global = timed(Some(60));
+        let parent = timed(Some(120));
+        let project = timed(None);
+        assert_eq!(
+            hook_timeout(&project, Some(&parent), &global, "worktree.pre_merge"),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            hook_timeout(&project, None, &global, "worktree.pre_merge"),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            hook_timeout(&project, None, &global, "worktree.post_merge"),
+            None
+        );
+    }
+
     #[test]
     fn resolve_hook_prefers_project_over_global() {
         let project = HooksConfig {
@@ -1911,7 +2552,7 @@ mod tests {
             },
             ..Default::default()
         };
-        let resolved = resolve_hook(&project, &global, |h| &h.worktree.pre_merge);
+        let resolved = resolve_hook(&project, &global, "worktree.pre_merge", &HashMap::new());
         assert_eq!(resolved, Some("project-cmd".into()));
     }
 
@@ -1925,7 +2566,7 @@ mod tests {
             },
             ..Default::default()
         };
-        let resolved = resolve_hook(&project, &global, |h| &h.worktree.pre_merge);
+        let resolved = resolve_hook(&project, &global, "worktree.pre_merge", &HashMap::new());
         assert_eq!(resolved, Some("global-cmd".into()));
     }
 
@@ -1933,7 +2574,7 @@ mod tests {
     fn resolve_hook_returns_none_when_both_empty() {
         let project = HooksConfig::default();
         let global = HooksConfig::default();
-        let resolved = resolve_hook(&project, &global, |h| &h.worktree.before_remove);
+        let resolved = resolve_hook(&project, &global, "worktree.before_remove", &HashMap::new());
         assert_eq!(resolved, None);
     }
 
@@ -1988,6 +2629,7 @@ mod tests {
             None,
             "proj-id",
             true,
+            None,
         );
         assert_eq!(terminal_actions.len(), 1);
         assert_eq!(terminal_actions[0].0, "my-cmd");
@@ -2127,12 +2769,23 @@ mod tests {
         };
 
         // Project empty → falls through to parent
-        let resolved =
-            resolve_hook_with_parent(&project, Some(&parent), &global, |h| &h.terminal.on_create);
+        let resolved = resolve_hook_with_parent(
+            &project,
+            Some(&parent),
+            &global,
+            "terminal.on_create",
+            &HashMap::new(),
+        );
         assert_eq!(resolved, Some("parent-cmd".into()));
 
         // Project empty, no parent → falls through to global
-        let resolved = resolve_hook_with_parent(&project, None, &global, |h| &h.terminal.on_create);
+        let resolved = resolve_hook_with_parent(
+            &project,
+            None,
+            &global,
+            "terminal.on_create",
+            &HashMap::new(),
+        );
         assert_eq!(resolved, Some("global-cmd".into()));
 
         // Project set → wins over parent and global
@@ -2143,12 +2796,172 @@ mod tests {
             },
             ..Default::default()
         };
-        let resolved = resolve_hook_with_parent(&project_with_hook, Some(&parent), &global, |h| {
-            &h.terminal.on_create
-        });
+        let resolved = resolve_hook_with_parent(
+            &project_with_hook,
+            Some(&parent),
+            &global,
+            "terminal.on_create",
+            &HashMap::new(),
+        );
         assert_eq!(resolved, Some("project-cmd".into()));
     }
 
+    fn steps(merge: HookMergeMode, steps: Vec<HookStep>) -> okena_state::HookSteps {
+        okena_state::HookSteps {
+            merge,
+            timeout: None,
+            steps,
+        }
+    }
+
+    #[test]
+    fn resolve_hook_with_parent_appends_and_replaces_by_merge_mode() {
+        let global = HooksConfig {
+            worktree: WorktreeHooks {
+                on_create: Some("npm ci".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut parent = HooksConfig::default();
+        parent.steps.insert(
+            "worktree.on_create".into(),
+            steps(HookMergeMode::Append, vec![HookStep::command("make")]),
+        );
+        let mut project = HooksConfig::default();
+        project.steps.insert(
+            "worktree.on_create".into(),
+            steps(HookMergeMode::Append, vec![HookStep::command("make test")]),
+        );
+        let env = HashMap::new();
+
+        let resolved =
+            resolve_hook_with_parent(&project, Some(&parent), &global, "worktree.on_create", &env);
+        assert_eq!(
+            resolved.as_deref(),
+            Some("(npm ci) && (make) && (make test)")
+        );
+
+        // A replacing level drops everything it inherits.
+        project.steps.insert(
+            "worktree.on_create".into(),
+            steps(HookMergeMode::Replace, vec![HookStep::command("just")]),
+        );
+        let resolved =
+            resolve_hook_with_parent(&project, Some(&parent), &global, "worktree.on_create", &env);
+        assert_eq!(resolved.as_deref(), Some("just"));
+    }
+
+    #[test]
+    fn resolve_hook_drops_steps_whose_conditions_fail() {
+        let dir = std::env::temp_dir().join(format!("okena-hook-steps-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("package.json"), "{}").unwrap();
+        let mut project = HooksConfig::default();
+        project.steps.insert(
+            "worktree.on_create".into(),
+            steps(
+                HookMergeMode::Replace,
+                vec![
+                    HookStep {
+                        when: okena_state::HookCondition {
+                            exists: Some("package.json".into()),
+                            ..Default::default()
+                        },
+                        ..HookStep::command("npm ci")
+                    },
+                    HookStep {
+                        when: okena_state::HookCondition {
+                            branch: vec!["release/*".into()],
+                            ..Default::default()
+                        },
+                        ..HookStep::command("./publish.sh")
+                    },
+                ],
+            ),
+        );
+        let mut env = HashMap::new();
+        env.insert(
+            "OKENA_PROJECT_PATH".to_string(),
+            dir.to_string_lossy().into_owned(),
+        );
+        env.insert("OKENA_BRANCH".to_string(), "feature/x".to_string());
+
+        let resolved = resolve_hook(
+            &project,
+            &HooksConfig::default(),
+            "worktree.on_create",
+            &env,
+        );
+        assert_eq!(resolved.as_deref(), Some("npm ci"));
+
+        env.insert("OKENA_BRANCH".to_string(), "main".to_string());
+        std::fs::remove_dir_all(&dir).unwrap();
+        let resolved = resolve_hook(
+            &project,
+            &HooksConfig::default(),
+            "worktree.on_create",
+            &env,
+        );
+        assert_eq!(resolved, None);
+    }
+
+    #[test]
+    fn render_hook_steps_applies_step_options() {
+        let lenient = HookStep {
+            continue_on_error: true,
+            ..HookStep::command("lint")
+        };
+        let pane = HookStep {
+            terminal: true,
+            ..HookStep::command("claude")
+        };
+        let steps = [lenient, HookStep::command("test"), pane];
+
+        assert_eq!(
+            render_hook_steps(&steps, true, false).as_deref(),
+            Some("((lint) || true) && (test)\nterminal: claude")
+        );
+        // Hooks without terminal actions run `terminal: true` steps inline.
+        assert_eq!(
+            render_hook_steps(&steps, false, false).as_deref(),
+            Some("((lint) || true) && (test) && (claude)")
+        );
+        assert_eq!(
+            render_hook_steps(&steps[..1], false, true).as_deref(),
+            Some("((lint) || (call ))")
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn rendered_steps_stop_on_failure() {
+        let render = |steps: &[HookStep]| render_hook_steps(steps, false, false).unwrap();
+
+        let failing = [HookStep::command("false"), HookStep::command("exit 9")];
+        assert!(run_hook_sync_bare(&render(&failing), HashMap::new()).is_err());
+
+        let tolerated = [
+            HookStep {
+                continue_on_error: true,
+                ..HookStep::command("false")
+            },
+            HookStep::command("true"),
+        ];
+        assert!(run_hook_sync_bare(&render(&tolerated), HashMap::new()).is_ok());
+    }
+
+    #[test]
+    fn timed_steps_run_through_the_step_helper() {
+        let slow = [HookStep {
+            timeout: Some(1),
+            ..HookStep::command("sleep 30")
+        }];
+        let rendered = render_hook_steps(&slow, false, false).unwrap();
+        assert!(rendered.contains(" --hook-step 1 "), "got: {rendered}");
+        assert!(!rendered.contains("sleep 30"), "got: {rendered}");
+    }
+
     #[test]
     fn valid_env_keys() {
         assert!(is_valid_env_key("OKENA_PROJECT_PATH"));
@@ -2416,6 +3229,24 @@ mod tests {
         assert!(!command.args[1].contains("cmd.exe"));
     }
 
+    #[test]
+    fn docker_exec_route_skips_host_shell_hooks() {
+        let route = ShellType::DockerExec {
+            compose_file: "/srv/app/compose.yml".to_string(),
+            service: "web".to_string(),
+            shell: None,
+        };
+        let plan = terminal_launch_plan(
+            route.clone(),
+            Some("envbox -- {shell}"),
+            Some("echo ready"),
+            &HashMap::new(),
+        );
+
+        assert_eq!(plan.route, route);
+        assert!(plan.initial_command.is_none());
+    }
+
     #[cfg(not(windows))]
     #[test]
     fn apply_shell_wrapper_with_env_vars() {
@@ -2435,4 +3266,66 @@ mod tests {
             other => panic!("Expected ShellType::Custom, got: {:?}", other),
         }
     }
+
+    #[test]
+    fn event_env_carries_command_result() {
+        let mut env = HashMap::new();
+        TerminalHookEvent::CommandFinished {
+            exit_code: Some(2),
+            duration: Some(Duration::from_millis(1500)),
+        }
+        .add_env(&mut env);
+        assert_eq!(env.get("OKENA_EXIT_CODE").map(String::as_str), Some("2"));
+        assert_eq!(
+            env.get("OKENA_COMMAND_DURATION_MS").map(String::as_str),
+            Some("1500")
+        );
+
+        let mut env = HashMap::new();
+        GitHookEvent::PrStateChanged {
+            branch: Some("feature".into()),
+            number: Some(42),
+            url: None,
+            previous: Some("open".into()),
+            state: Some("merged".into()),
+        }
+        .add_env(&mut env);
+        assert_eq!(env.get("OKENA_PR_NUMBER").map(String::as_str), Some("42"));
+        assert_eq!(
+            env.get("OKENA_PR_STATE").map(String::as_str),
+            Some("merged")
+        );
+        assert_eq!(
+            env.get("OKENA_PREVIOUS_PR_STATE").map(String::as_str),
+            Some("open")
+        );
+        assert!(!env.contains_key("OKENA_PR_URL"));
+    }
+
+    #[test]
+    fn hook_configured_checks_every_level() {
+        let empty = HooksConfig::default();
+        let mut with_bell = HooksConfig::default();
+        with_bell.terminal.on_bell = Some("echo bell".into());
+
+        assert!(!hook_configured(&empty, None, &empty, "terminal.on_bell"));
+        assert!(hook_configured(
+            &empty,
+            Some(&with_bell),
+            &empty,
+            "terminal.on_bell"
+        ));
+        assert!(hook_configured(
+            &empty,
+            None,
+            &with_bell,
+            "terminal.on_bell"
+        ));
+        assert!(!hook_configured(
+            &with_bell,
+            None,
+            &empty,
+            "service.on_crash"
+        ));
+    }
 }
@@ -5,7 +5,8 @@
 //! Runs project/terminal/worktree lifecycle hooks as shell commands, either
 //! through a PTY-backed terminal (when a `HookRunner` is registered as a
 //! GPUI Global) or headlessly via `sh -c` / `cmd /C`. The `HookMonitor`
-//! tracks in-flight and completed executions for the UI.
+//! tracks in-flight and completed executions for the UI; a `HookRunStore`
+//! keeps headless runs and their output on disk.
 //!
 //! This crate intentionally does not depend on `okena-workspace`: hook
 //! callers pass project metadata in, and the result (`HookTerminalResult`)
@@ -13,18 +14,23 @@
 //! entity state.
 
 pub mod hook_monitor;
+pub mod hook_runs;
+pub mod hook_step;
 pub mod hooks;
 
 pub use hook_monitor::{HookExecution, HookMonitor, HookStatus};
+pub use hook_runs::HookRunStore;
 pub use hooks::{
-    HookActionOutcome, HookActionPlan, HookRunner, HookTerminalResult, PreparedHookTerminal,
-    apply_shell_wrapper, execute_hook_action_plan, fire_before_worktree_remove,
-    fire_before_worktree_remove_async, fire_on_dirty_worktree_close,
-    fire_on_dirty_worktree_close_headless, fire_on_project_close,
-    fire_on_project_close_headless_sync, fire_on_project_open, fire_on_rebase_conflict,
-    fire_on_worktree_close_headless_sync, fire_on_worktree_close_with_services,
-    fire_on_worktree_create, fire_post_merge, fire_pre_merge, fire_terminal_on_close_with_services,
-    fire_worktree_removed, plan_on_rebase_conflict, prepare_project_open_hook, terminal_hook_env,
+    GitHookEvent, HookActionOutcome, HookActionPlan, HookRunner, HookTerminalResult,
+    PreparedHookTerminal, ServiceHookEvent, TerminalHookEvent, apply_shell_wrapper,
+    execute_hook_action_plan, fire_before_worktree_remove, fire_before_worktree_remove_async,
+    fire_git_event, fire_on_dirty_worktree_close, fire_on_dirty_worktree_close_headless,
+    fire_on_project_close, fire_on_project_close_headless_sync, fire_on_project_open,
+    fire_on_rebase_conflict, fire_on_worktree_close_headless_sync,
+    fire_on_worktree_close_with_services, fire_on_worktree_create, fire_post_merge, fire_pre_merge,
+    fire_service_event, fire_terminal_event, fire_terminal_on_close_with_services,
+    fire_worktree_removed, hook_configured, plan_on_rebase_conflict, prepare_project_open_hook,
+    terminal_hook_env,
 };
 #[cfg(feature = "gpui")]
 pub use hooks::{
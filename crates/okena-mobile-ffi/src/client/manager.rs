@@ -1,13 +1,17 @@
 use crate::client::handler::MobileConnectionHandler;
 use crate::client::terminal_holder::TerminalHolder;
 
-use okena_core::api::{ActionRequest, ApiFullscreen, ApiLayoutNode, StateResponse};
+use okena_core::api::{
+    ActionRequest, ApiFullscreen, ApiLayoutNode, FileUploadRequest, FileUploadResponse,
+    StateResponse,
+};
 use okena_transport::client::{
     ConnectionEvent, ConnectionStatus, RemoteClient, RemoteConnectionConfig, WsClientMessage,
     make_prefixed_id,
 };
 use parking_lot::RwLock;
 use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::sync::{Arc, OnceLock};
 
 static MANAGER: OnceLock<ConnectionManager> = OnceLock::new();
@@ -411,6 +415,7 @@ impl ConnectionManager {
             tls,
             pinned_cert_sha256,
             local_endpoint: None,
+            proxy_command: None,
         };
         let conn_id = config.id.clone();
 
@@ -674,18 +679,7 @@ impl ConnectionManager {
         conn_id: &str,
         action: ActionRequest,
     ) -> anyhow::Result<String> {
-        let (config, token) = {
-            let connections = self.connections.read();
-            let conn = connections
-                .get(conn_id)
-                .ok_or_else(|| anyhow::anyhow!("Connection not found: {}", conn_id))?;
-            let config = conn.client.read().config().clone();
-            let token = config
-                .effective_auth_token()
-                .ok_or_else(|| anyhow::anyhow!("No auth token for connection: {}", conn_id))?;
-            (config, token)
-        };
-
+        let (config, token) = self.config_and_token(conn_id)?;
         let response = okena_transport::remote_action::post_action_async(&config, &token, action)
             .await
             .map_err(anyhow::Error::msg)?;
@@ -695,6 +689,31 @@ impl ConnectionManager {
         }
     }
 
+    /// Stream a local file into a project on the remote server.
+    pub async fn upload_file(
+        &self,
+        conn_id: &str,
+        request: FileUploadRequest,
+        local_path: &Path,
+    ) -> anyhow::Result<FileUploadResponse> {
+        let (config, token) = self.config_and_token(conn_id)?;
+        okena_transport::remote_action::upload_file_async(&config, &token, &request, local_path)
+            .await
+            .map_err(anyhow::Error::msg)
+    }
+
+    fn config_and_token(&self, conn_id: &str) -> anyhow::Result<(RemoteConnectionConfig, String)> {
+        let connections = self.connections.read();
+        let conn = connections
+            .get(conn_id)
+            .ok_or_else(|| anyhow::anyhow!("Connection not found: {}", conn_id))?;
+        let config = conn.client.read().config().clone();
+        let token = config
+            .effective_auth_token()
+            .ok_or_else(|| anyhow::anyhow!("No auth token for connection: {}", conn_id))?;
+        Ok((config, token))
+    }
+
     /// Background task that drains the event channel and updates connection state.
     async fn process_events(conn_id: String, event_rx: async_channel::Receiver<ConnectionEvent>) {
         while let Ok(event) = event_rx.recv().await {
@@ -835,6 +854,7 @@ mod tests {
             folders: Vec::new(),
             windows: Vec::new(),
             hooks: Vec::new(),
+            shares: Vec::new(),
         }
     }
 
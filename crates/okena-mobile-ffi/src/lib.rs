@@ -29,7 +29,7 @@ mod api;
 mod client;
 mod types;
 
-use okena_core::api::ActionRequest;
+use okena_core::api::{ActionRequest, FileUploadRequest, UploadConflict};
 use okena_core::keys::SpecialKey;
 use okena_core::theme::DARK_THEME;
 use okena_core::types::{DiffMode, SplitDirection};
@@ -459,7 +459,20 @@ async fn send_mobile_action(conn_id: &str, action: ActionRequest) -> Result<(),
 /// Create a new terminal in the given project.
 #[uniffi::export(async_runtime = "tokio")]
 pub async fn create_terminal(conn_id: String, project_id: String) -> Result<(), MobileFfiError> {
-    send_mobile_action(&conn_id, ActionRequest::CreateTerminal { project_id }).await
+    send_mobile_action(
+        &conn_id,
+        ActionRequest::CreateTerminal {
+            project_id,
+            shell: None,
+            cwd: None,
+            command: None,
+            env: Default::default(),
+            name: None,
+            minimized: false,
+            placement: None,
+        },
+    )
+    .await
 }
 
 /// Close a terminal in the given project.
@@ -598,7 +611,17 @@ pub async fn run_command(
 #[uniffi::export(async_runtime = "tokio")]
 pub async fn read_content(conn_id: String, terminal_id: String) -> Result<String, MobileFfiError> {
     Ok(ConnectionManager::get()
-        .send_action_with_response(&conn_id, ActionRequest::ReadContent { terminal_id })
+        .send_action_with_response(
+            &conn_id,
+            ActionRequest::ReadContent {
+                terminal_id,
+                lines: None,
+                all: false,
+                ansi: false,
+                since: None,
+                strip_prompt: false,
+            },
+        )
         .await?)
 }
 
@@ -818,6 +841,39 @@ pub async fn reorder_project_in_folder(
     .await
 }
 
+// ── Files (async) ───────────────────────────────────────────────────
+
+/// Upload a local file (e.g. a picked photo or shared log) into a project.
+/// `relative_path` is the destination under the project root; its directory
+/// must exist. `conflict` is "rename" or "overwrite"; anything else fails if
+/// the file exists. Returns the JSON `{path, name, size}` of the written file.
+#[uniffi::export(async_runtime = "tokio")]
+pub async fn upload_file(
+    conn_id: String,
+    project_id: String,
+    local_path: String,
+    relative_path: String,
+    conflict: String,
+) -> Result<String, MobileFfiError> {
+    let conflict = match conflict.as_str() {
+        "rename" => UploadConflict::Rename,
+        "overwrite" => UploadConflict::Overwrite,
+        _ => UploadConflict::Fail,
+    };
+    let request = FileUploadRequest {
+        project_id: Some(project_id),
+        root: None,
+        relative_path,
+        conflict,
+    };
+    let written = ConnectionManager::get()
+        .upload_file(&conn_id, request, std::path::Path::new(&local_path))
+        .await?;
+    serde_json::to_string(&written).map_err(|e| MobileFfiError::Action {
+        message: e.to_string(),
+    })
+}
+
 // ── Layout actions (async) ──────────────────────────────────────────
 
 /// Update split sizes for a split pane.
@@ -6,7 +6,7 @@ use okena_terminal::terminal::{Terminal, TerminalSize};
 use okena_core::api::{ApiSystemStats, StateResponse};
 use okena_transport::client::{
     ConnectionEvent, ConnectionHandler, ConnectionStatus, RemoteClient, RemoteConnectionConfig,
-    WsClientMessage, is_remote_terminal,
+    StreamThroughput, WsClientMessage, is_remote_terminal,
 };
 
 use std::collections::HashMap;
@@ -226,6 +226,10 @@ impl RemoteConnection {
         self.client.set_system_stats(stats);
     }
 
+    pub fn throughput(&self) -> StreamThroughput {
+        self.client.throughput()
+    }
+
     pub fn update_stream_mappings(&mut self, mappings: HashMap<String, u32>) {
         self.client.update_stream_mappings(mappings);
     }
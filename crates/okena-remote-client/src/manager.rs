@@ -6,13 +6,14 @@ use okena_workspace::settings::{AppSettings, load_settings, update_remote_connec
 use okena_workspace::toast::{Toast, ToastManager};
 
 use okena_core::api::{ActionRequest, ApiSystemStats, StateResponse};
+use okena_core::forward::PortForwardQuery;
 use okena_core::soft_close::{
     SOFT_CLOSE_KILL_PREFIX, SOFT_CLOSE_UNDO_PREFIX, decode_action, encode_action,
 };
 use okena_transport::client::connection::try_refresh_token;
 use okena_transport::client::{
-    ConnectionEvent, ConnectionStatus, LOCAL_DAEMON_CONNECTION_ID, RemoteConnectionConfig,
-    is_remote_terminal, make_prefixed_id, strip_prefix,
+    ConnectionEvent, ConnectionStatus, LOCAL_DAEMON_CONNECTION_ID, PortForward,
+    RemoteConnectionConfig, StreamThroughput, is_remote_terminal, make_prefixed_id, strip_prefix,
 };
 
 use gpui::*;
@@ -25,6 +26,16 @@ struct QueuedAction {
     action: ActionRequest,
 }
 
+/// A forwarded service port: connection, server-side project id, service
+/// name and remote port.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct ForwardKey {
+    connection_id: String,
+    project_id: String,
+    service: String,
+    port: u16,
+}
+
 struct PasteUpload {
     endpoint: &'static str,
     content_type: String,
@@ -193,6 +204,13 @@ pub struct RemoteConnectionManager {
     /// output until the GPUI side drains, so output bursts collapse into a
     /// single repaint pass. Handed to every connection's `ConnectionHandler`.
     activity_tx: async_channel::Sender<()>,
+
+    /// Remote service ports forwarded to localhost. Dropping a forward closes
+    /// its listener and connections.
+    port_forwards: HashMap<ForwardKey, PortForward>,
+    /// Forwards whose daemon check is still in flight, so a double click
+    /// doesn't start two.
+    port_forwards_starting: HashSet<ForwardKey>,
 }
 
 impl RemoteConnectionManager {
@@ -241,6 +259,8 @@ impl RemoteConnectionManager {
             event_tx,
             action_queues,
             activity_tx,
+            port_forwards: HashMap::new(),
+            port_forwards_starting: HashSet::new(),
         };
         manager.start_terminal_activity_pump(activity_rx, cx);
         manager
@@ -405,6 +425,8 @@ impl RemoteConnectionManager {
         if let Some(mut conn) = self.connections.remove(connection_id) {
             conn.disconnect();
         }
+        self.port_forwards
+            .retain(|key, _| key.connection_id != connection_id);
         // Remove from saved settings (off GPUI thread)
         let id = connection_id.to_string();
         cx.background_executor()
@@ -485,6 +507,13 @@ impl RemoteConnectionManager {
             .collect()
     }
 
+    /// Terminal output throughput of a connection's current session.
+    pub fn throughput(&self, connection_id: &str) -> Option<StreamThroughput> {
+        self.connections
+            .get(connection_id)
+            .map(|conn| conn.throughput())
+    }
+
     /// Get the backend for a specific connection.
     pub fn backend_for(&self, connection_id: &str) -> Option<Arc<dyn TerminalBackend>> {
         self.connections
@@ -574,6 +603,81 @@ impl RemoteConnectionManager {
         );
     }
 
+    /// Forward a remote service port to localhost and open it in the browser.
+    ///
+    /// The forward relays over the connection's own transport and token (see
+    /// [`PortForward`]) and runs until the connection is removed; clicking the
+    /// same port again just reopens it. `project_id` is the server-local id.
+    pub fn forward_and_open(
+        &mut self,
+        connection_id: &str,
+        project_id: &str,
+        service: &str,
+        port: u16,
+        cx: &mut Context<Self>,
+    ) {
+        let key = ForwardKey {
+            connection_id: connection_id.to_string(),
+            project_id: project_id.to_string(),
+            service: service.to_string(),
+            port,
+        };
+        if let Some(forward) = self.port_forwards.get(&key) {
+            okena_core::process::open_url(&forward.url());
+            return;
+        }
+        if self.port_forwards_starting.contains(&key) {
+            return;
+        }
+        let config = match self.connections.get(connection_id) {
+            Some(conn) => conn.config().clone(),
+            None => {
+                log::error!("port forward: connection {} not found", connection_id);
+                return;
+            }
+        };
+        let Some(token) = config.effective_auth_token() else {
+            ToastManager::error("No auth token for remote connection".to_string(), cx);
+            return;
+        };
+
+        let target = PortForwardQuery {
+            project_id: project_id.to_string(),
+            service: service.to_string(),
+            port: Some(port),
+        };
+        self.port_forwards_starting.insert(key.clone());
+        let started = self
+            .runtime
+            .spawn(PortForward::start(config, token, target, None));
+        cx.spawn(async move |this: WeakEntity<Self>, cx| {
+            let started = started
+                .await
+                .unwrap_or_else(|e| Err(format!("forward task failed: {e}")));
+            let _ = this.update(cx, |this, cx| {
+                this.port_forwards_starting.remove(&key);
+                match started {
+                    Ok(forward) => {
+                        let url = forward.url();
+                        ToastManager::info(
+                            format!("Forwarding {} :{} to {}", key.service, key.port, url),
+                            cx,
+                        );
+                        okena_core::process::open_url(&url);
+                        this.port_forwards.insert(key, forward);
+                    }
+                    Err(error) => {
+                        ToastManager::error(
+                            format!("Cannot forward {} :{}: {}", key.service, key.port, error),
+                            cx,
+                        );
+                    }
+                }
+            });
+        })
+        .detach();
+    }
+
     /// Upload a pasted clipboard image to the remote server, which writes it to
     /// a temp file on its own filesystem and bracketed-pastes that path into the
     /// terminal (so a server-side TUI like Claude Code can read it).
@@ -1206,6 +1310,7 @@ mod tests {
             tls: false,
             pinned_cert_sha256: None,
             local_endpoint: None,
+            proxy_command: None,
         }
     }
 
@@ -1,6 +1,8 @@
 use base64::Engine as _;
 use fs2::FileExt;
 use hmac::{Hmac, Mac};
+use okena_core::api::ApiShare;
+use okena_core::scope::TokenGrant;
 use parking_lot::Mutex;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
@@ -15,6 +17,12 @@ type HmacSha256 = Hmac<Sha256>;
 /// Token time-to-live in seconds (14 days).
 pub const TOKEN_TTL_SECS: u64 = 14 * 24 * 3600;
 
+/// Share link lifetime when the request names none (1 hour).
+pub const SHARE_TTL_SECS: u64 = 3600;
+
+/// Longest a share link may live (24 hours).
+pub const MAX_SHARE_TTL_SECS: u64 = 24 * 3600;
+
 /// A stored token record.
 #[allow(dead_code)]
 pub struct TokenRecord {
@@ -23,6 +31,43 @@ pub struct TokenRecord {
     pub created_at: SystemTime,
     pub last_used_at: Mutex<SystemTime>,
     pub name: Option<String>,
+    /// What the token may do, fixed when its pairing code was issued.
+    pub grant: TokenGrant,
+}
+
+/// A view-only share link. Kept in memory only: restarting the daemon ends
+/// every share, and a reload from `remote_tokens.json` never drops one.
+struct ShareRecord {
+    id: String,
+    token_hmac: Vec<u8>,
+    expires_at: SystemTime,
+    project_id: String,
+    terminal_id: Option<String>,
+}
+
+impl ShareRecord {
+    fn is_live(&self, now: SystemTime) -> bool {
+        now < self.expires_at
+    }
+
+    /// Read-only, limited to the shared project or terminal.
+    fn grant(&self) -> TokenGrant {
+        TokenGrant {
+            projects: vec![self.project_id.clone()],
+            terminals: self.terminal_id.iter().cloned().collect(),
+            ..TokenGrant::read_only()
+        }
+    }
+
+    fn info(&self) -> ApiShare {
+        ApiShare {
+            id: self.id.clone(),
+            project_id: self.project_id.clone(),
+            terminal_id: self.terminal_id.clone(),
+            expires_at: unix_secs(self.expires_at),
+            viewers: 0,
+        }
+    }
 }
 
 /// Rate limiter state for pairing attempts.
@@ -105,8 +150,12 @@ struct AuthStoreInner {
     current_code: Option<String>,
     /// When the current code was generated
     code_created_at: Instant,
+    /// Grant handed to the token paired with the current code
+    code_grant: TokenGrant,
     /// Stored token records (HMAC digests only)
     tokens: Vec<TokenRecord>,
+    /// Live share links (HMAC digests only)
+    shares: Vec<ShareRecord>,
     /// Rate limiter for pairing
     rate_limiter: RateLimiter,
 }
@@ -137,7 +186,9 @@ impl AuthStore {
                 app_secret,
                 current_code: None,
                 code_created_at: Instant::now(),
+                code_grant: TokenGrant::default(),
                 tokens,
+                shares: Vec::new(),
                 rate_limiter: RateLimiter::new(),
             }),
             tokens_path: t_path,
@@ -147,7 +198,7 @@ impl AuthStore {
 
     /// Create an AuthStore with a given secret and isolated temp directory (for testing).
     #[cfg(test)]
-    fn with_secret(secret: Vec<u8>) -> Self {
+    pub(crate) fn with_secret(secret: Vec<u8>) -> Self {
         let test_dir = std::env::temp_dir().join(format!(
             "okena-auth-test-{:?}-{}",
             std::thread::current().id(),
@@ -162,7 +213,9 @@ impl AuthStore {
                 app_secret: secret,
                 current_code: None,
                 code_created_at: Instant::now(),
+                code_grant: TokenGrant::default(),
                 tokens: Vec::new(),
+                shares: Vec::new(),
                 rate_limiter: RateLimiter::new(),
             }),
             tokens_path: test_dir.join("remote_tokens.json"),
@@ -187,15 +240,22 @@ impl AuthStore {
         let code = generate_pairing_code();
         inner.current_code = Some(code.clone());
         inner.code_created_at = now;
+        inner.code_grant = TokenGrant::default();
         code
     }
 
     /// Generate a fresh pairing code unconditionally (for explicit pairing dialog).
     pub fn generate_fresh_code(&self) -> String {
+        self.generate_fresh_code_with_grant(TokenGrant::default())
+    }
+
+    /// Like [`Self::generate_fresh_code`], but the paired token gets `grant`.
+    pub fn generate_fresh_code_with_grant(&self, grant: TokenGrant) -> String {
         let mut inner = self.inner.lock();
         let code = generate_pairing_code();
         inner.current_code = Some(code.clone());
         inner.code_created_at = Instant::now();
+        inner.code_grant = grant;
         code
     }
 
@@ -239,23 +299,20 @@ impl AuthStore {
             None => false,
         };
 
-        let file_valid = if !in_memory_valid {
+        let file_grant = if !in_memory_valid {
             check_file_pair_code(code, &self.pair_code_path)
         } else {
-            false
+            None
         };
+        let file_valid = file_grant.is_some();
 
-        if !in_memory_valid && !file_valid {
-            return Err(PairError::InvalidCode);
-        }
+        let grant = match file_grant {
+            Some(grant) => grant,
+            None if in_memory_valid => inner.code_grant.clone(),
+            None => return Err(PairError::InvalidCode),
+        };
 
-        // Generate token (32 random bytes, base64url encoded)
-        let mut token_bytes = [0u8; 32];
-        rand::thread_rng().fill(&mut token_bytes);
-        let token = base64::Engine::encode(
-            &base64::engine::general_purpose::URL_SAFE_NO_PAD,
-            token_bytes,
-        );
+        let token = generate_token();
 
         // Store HMAC of the token
         let token_hmac = compute_hmac(&inner.app_secret, token.as_bytes());
@@ -266,6 +323,7 @@ impl AuthStore {
             created_at: now,
             last_used_at: Mutex::new(now),
             name: None,
+            grant,
         };
         inner.tokens.push(record);
 
@@ -291,6 +349,16 @@ impl AuthStore {
 
     /// Validate a bearer token. Returns true if valid and not expired.
     pub fn validate_token(&self, token: &str) -> bool {
+        self.authorize_token(token).is_some()
+    }
+
+    /// Validate a bearer token and return what it may do.
+    pub fn authorize_token(&self, token: &str) -> Option<TokenGrant> {
+        self.authenticate(token).map(|token| token.grant)
+    }
+
+    /// Validate a bearer token and return which token it is.
+    pub fn authenticate(&self, token: &str) -> Option<AuthenticatedToken> {
         let inner = self.inner.lock();
         let candidate_hmac = compute_hmac(&inner.app_secret, token.as_bytes());
         let now = SystemTime::now();
@@ -302,13 +370,83 @@ impl AuthStore {
                     .duration_since(record.created_at)
                     .unwrap_or(Duration::MAX);
                 if age >= Duration::from_secs(TOKEN_TTL_SECS) {
-                    return false;
+                    return None;
                 }
                 *record.last_used_at.lock() = now;
-                return true;
+                return Some(AuthenticatedToken {
+                    id: record.id.clone(),
+                    name: record.name.clone(),
+                    grant: record.grant.clone(),
+                    share_expires_at: None,
+                });
             }
         }
-        false
+        inner
+            .shares
+            .iter()
+            .find(|share| {
+                share.is_live(now) && constant_time_eq(&share.token_hmac, &candidate_hmac)
+            })
+            .map(|share| AuthenticatedToken {
+                id: share.id.clone(),
+                name: Some("view-only share".to_string()),
+                grant: share.grant(),
+                share_expires_at: Some(share.expires_at),
+            })
+    }
+
+    /// Mint a view-only share link to a project, or to one of its terminals.
+    /// Returns the share and its bearer token.
+    pub fn create_share(
+        &self,
+        project_id: &str,
+        terminal_id: Option<&str>,
+        ttl: Duration,
+    ) -> (ApiShare, String) {
+        let mut inner = self.inner.lock();
+        let token = generate_token();
+        let now = SystemTime::now();
+        let record = ShareRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            token_hmac: compute_hmac(&inner.app_secret, token.as_bytes()),
+            expires_at: now + ttl,
+            project_id: project_id.to_string(),
+            terminal_id: terminal_id.map(str::to_string),
+        };
+        let info = record.info();
+        inner.shares.retain(|share| share.is_live(now));
+        inner.shares.push(record);
+        (info, token)
+    }
+
+    /// Share links that haven't expired, with no viewer counts.
+    pub fn list_shares(&self) -> Vec<ApiShare> {
+        let now = SystemTime::now();
+        let inner = self.inner.lock();
+        inner
+            .shares
+            .iter()
+            .filter(|share| share.is_live(now))
+            .map(ShareRecord::info)
+            .collect()
+    }
+
+    /// Whether share `id` still works.
+    pub fn share_is_live(&self, id: &str) -> bool {
+        let now = SystemTime::now();
+        self.inner
+            .lock()
+            .shares
+            .iter()
+            .any(|share| share.id == id && share.is_live(now))
+    }
+
+    /// End a share link. Returns true if it existed.
+    pub fn revoke_share(&self, id: &str) -> bool {
+        let mut inner = self.inner.lock();
+        let before = inner.shares.len();
+        inner.shares.retain(|share| share.id != id);
+        inner.shares.len() < before
     }
 
     /// List all non-expired tokens with metadata.
@@ -343,6 +481,7 @@ impl AuthStore {
                     last_used_at: last_used_unix,
                     expires_at,
                     name: record.name.clone(),
+                    grant: record.grant.clone(),
                 })
             })
             .collect()
@@ -376,31 +515,30 @@ impl AuthStore {
     }
 
     /// Refresh a valid token: validate the current token, generate a new one,
-    /// and keep both valid until their respective expiry times.
+    /// and keep both valid until their respective expiry times. The new token
+    /// keeps the old one's grant.
     pub fn refresh_token(&self, current_token: &str) -> Result<String, &'static str> {
         let mut inner = self.inner.lock();
         let candidate_hmac = compute_hmac(&inner.app_secret, current_token.as_bytes());
         let now = SystemTime::now();
 
         // Validate the current token
-        let valid = inner.tokens.iter().any(|record| {
-            constant_time_eq(&record.token_hmac, &candidate_hmac)
-                && now
-                    .duration_since(record.created_at)
-                    .unwrap_or(Duration::MAX)
-                    < Duration::from_secs(TOKEN_TTL_SECS)
-        });
-        if !valid {
+        let grant = inner
+            .tokens
+            .iter()
+            .find(|record| {
+                constant_time_eq(&record.token_hmac, &candidate_hmac)
+                    && now
+                        .duration_since(record.created_at)
+                        .unwrap_or(Duration::MAX)
+                        < Duration::from_secs(TOKEN_TTL_SECS)
+            })
+            .map(|record| record.grant.clone());
+        let Some(grant) = grant else {
             return Err("invalid or expired token");
-        }
+        };
 
-        // Generate a new token
-        let mut token_bytes = [0u8; 32];
-        rand::thread_rng().fill(&mut token_bytes);
-        let new_token = base64::Engine::encode(
-            &base64::engine::general_purpose::URL_SAFE_NO_PAD,
-            token_bytes,
-        );
+        let new_token = generate_token();
 
         // Store HMAC of the new token (old token remains valid until its own expiry)
         let new_hmac = compute_hmac(&inner.app_secret, new_token.as_bytes());
@@ -410,6 +548,7 @@ impl AuthStore {
             created_at: now,
             last_used_at: Mutex::new(now),
             name: None,
+            grant,
         });
 
         // Evict oldest tokens if we exceed the limit
@@ -425,6 +564,16 @@ impl AuthStore {
     }
 }
 
+/// A token that passed [`AuthStore::authenticate`].
+#[derive(Clone, Debug)]
+pub struct AuthenticatedToken {
+    pub id: String,
+    pub name: Option<String>,
+    pub grant: TokenGrant,
+    /// Set for a share link: when it stops working. `id` is the share's id.
+    pub share_expires_at: Option<SystemTime>,
+}
+
 /// Information about a stored token, safe to expose to clients.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TokenInfo {
@@ -433,6 +582,8 @@ pub struct TokenInfo {
     pub last_used_at: u64,
     pub expires_at: u64,
     pub name: Option<String>,
+    #[serde(default)]
+    pub grant: TokenGrant,
 }
 
 /// Pairing errors.
@@ -443,31 +594,47 @@ pub enum PairError {
 }
 
 /// Check a pairing code against the file-based code written by `okena pair` CLI.
-/// Returns true if the file exists, was modified within 60s, and the code matches.
-fn check_file_pair_code(code: &str, path: &std::path::Path) -> bool {
-    let metadata = match std::fs::metadata(path) {
-        Ok(m) => m,
-        Err(_) => return false,
-    };
+/// Returns the grant for the new token if the file exists, was modified within
+/// 60s, and the code matches.
+fn check_file_pair_code(code: &str, path: &std::path::Path) -> Option<TokenGrant> {
+    let metadata = std::fs::metadata(path).ok()?;
 
     // Check mtime is within 60s
-    let modified = match metadata.modified() {
-        Ok(t) => t,
-        Err(_) => return false,
-    };
+    let modified = metadata.modified().ok()?;
     let age = std::time::SystemTime::now()
         .duration_since(modified)
         .unwrap_or(Duration::from_secs(u64::MAX));
     if age > Duration::from_secs(60) {
-        return false;
+        return None;
     }
 
-    let file_code = match std::fs::read_to_string(path) {
-        Ok(c) => c,
-        Err(_) => return false,
-    };
+    let contents = std::fs::read_to_string(path).ok()?;
+    let (file_code, grant) = parse_pair_code_file(&contents)?;
+    constant_time_eq(file_code.as_bytes(), code.as_bytes()).then_some(grant)
+}
 
-    constant_time_eq(file_code.trim().as_bytes(), code.as_bytes())
+/// Contents of the `okena pair` code file: the code, then an optional JSON
+/// [`TokenGrant`] on the next line.
+pub fn pair_code_file_contents(code: &str, grant: &TokenGrant) -> String {
+    if grant.is_full() {
+        return code.to_string();
+    }
+    match serde_json::to_string(grant) {
+        Ok(json) => format!("{code}\n{json}"),
+        Err(_) => code.to_string(),
+    }
+}
+
+/// Inverse of [`pair_code_file_contents`]. A grant line that doesn't parse
+/// voids the code rather than falling back to full access.
+fn parse_pair_code_file(contents: &str) -> Option<(&str, TokenGrant)> {
+    let mut lines = contents.trim().lines();
+    let code = lines.next()?.trim();
+    let grant = match lines.next() {
+        Some(line) => serde_json::from_str(line).ok()?,
+        None => TokenGrant::default(),
+    };
+    Some((code, grant))
 }
 
 /// Compute HMAC-SHA256.
@@ -586,6 +753,25 @@ pub struct PersistedToken {
     pub token_hmac: String,
     /// Unix timestamp (seconds since epoch).
     pub created_at: u64,
+    /// Full access when absent, which covers tokens written before scopes.
+    #[serde(default, skip_serializing_if = "TokenGrant::is_full")]
+    pub grant: TokenGrant,
+}
+
+/// A fresh bearer token: 32 random bytes, base64url encoded.
+fn generate_token() -> String {
+    let mut token_bytes = [0u8; 32];
+    rand::thread_rng().fill(&mut token_bytes);
+    base64::Engine::encode(
+        &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+        token_bytes,
+    )
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
 }
 
 /// Path to the persisted tokens file.
@@ -612,6 +798,7 @@ fn persisted_tokens(tokens: &[TokenRecord]) -> Vec<PersistedToken> {
                 id: t.id.clone(),
                 token_hmac: base64::engine::general_purpose::STANDARD.encode(&t.token_hmac),
                 created_at: unix,
+                grant: t.grant.clone(),
             }
         })
         .collect()
@@ -772,6 +959,7 @@ fn load_tokens_from(path: &std::path::Path) -> Result<Vec<TokenRecord>, TokenLoa
                 created_at,
                 last_used_at: Mutex::new(now),
                 name: None,
+                grant: p.grant,
             })
         })
         .collect())
@@ -780,6 +968,7 @@ fn load_tokens_from(path: &std::path::Path) -> Result<Vec<TokenRecord>, TokenLoa
 #[cfg(test)]
 mod tests {
     use super::*;
+    use okena_core::scope::Scope;
     use std::net::{IpAddr, Ipv4Addr};
 
     fn test_store() -> AuthStore {
@@ -854,6 +1043,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn paired_token_carries_the_code_grant_through_refresh() {
+        let store = test_store();
+        let code = store.generate_fresh_code_with_grant(TokenGrant::read_only());
+        let token = store.try_pair(&code, test_ip()).unwrap();
+        assert_eq!(store.authorize_token(&token), Some(TokenGrant::read_only()));
+
+        let refreshed = store.refresh_token(&token).unwrap();
+        assert_eq!(
+            store.authorize_token(&refreshed),
+            Some(TokenGrant::read_only())
+        );
+    }
+
+    #[test]
+    fn file_based_pair_reads_the_grant_line() {
+        let store = test_store();
+        let contents = pair_code_file_contents("ABCD-EFGH", &TokenGrant::read_only());
+        std::fs::write(&store.pair_code_path, contents).unwrap();
+
+        let token = store.try_pair("ABCD-EFGH", test_ip()).unwrap();
+        assert_eq!(store.authorize_token(&token), Some(TokenGrant::read_only()));
+
+        // A grant line that doesn't parse voids the code.
+        std::fs::write(&store.pair_code_path, "ABCD-EFGH\n{oops").unwrap();
+        assert!(store.try_pair("ABCD-EFGH", test_ip()).is_err());
+    }
+
     #[test]
     fn no_in_memory_code_and_no_file_returns_invalid() {
         let store = test_store();
@@ -880,7 +1097,9 @@ mod tests {
                 app_secret: secret,
                 current_code: None,
                 code_created_at: Instant::now(),
+                code_grant: TokenGrant::default(),
                 tokens: loaded_tokens,
+                shares: Vec::new(),
                 rate_limiter: RateLimiter::new(),
             }),
             tokens_path: store.tokens_path.clone(),
@@ -1002,6 +1221,7 @@ mod tests {
                 created_at: expired_time,
                 last_used_at: Mutex::new(now),
                 name: None,
+                grant: TokenGrant::default(),
             },
             TokenRecord {
                 id: "valid".to_string(),
@@ -1009,6 +1229,7 @@ mod tests {
                 created_at: valid_time,
                 last_used_at: Mutex::new(now),
                 name: None,
+                grant: TokenGrant::default(),
             },
         ];
 
@@ -1042,11 +1263,13 @@ mod tests {
                 token_hmac: base64::engine::general_purpose::STANDARD
                     .encode(&store.inner.lock().tokens[0].token_hmac),
                 created_at: now_unix,
+                grant: TokenGrant::default(),
             },
             PersistedToken {
                 id: "external".into(),
                 token_hmac: base64::engine::general_purpose::STANDARD.encode(&external_hmac),
                 created_at: now_unix,
+                grant: TokenGrant::default(),
             },
         ];
         let json = serde_json::to_string(&persisted).unwrap();
@@ -1082,6 +1305,7 @@ mod tests {
                     .duration_since(SystemTime::UNIX_EPOCH)
                     .unwrap()
                     .as_secs(),
+                grant: TokenGrant::default(),
             },
         )
         .unwrap();
@@ -1106,4 +1330,59 @@ mod tests {
             "failed reload should not clear in-memory tokens"
         );
     }
+
+    #[test]
+    fn share_token_grants_read_only_view_of_its_terminal() {
+        let store = test_store();
+        let (share, token) =
+            store.create_share("p1", Some("t1"), Duration::from_secs(SHARE_TTL_SECS));
+
+        let auth = store
+            .authenticate(&token)
+            .expect("share should authenticate");
+        assert_eq!(auth.id, share.id);
+        assert!(auth.share_expires_at.is_some());
+        assert!(!auth.grant.allows(Scope::TerminalInput));
+        assert_eq!(auth.grant.projects, vec!["p1".to_string()]);
+        assert_eq!(auth.grant.terminals, vec!["t1".to_string()]);
+        assert_eq!(store.list_shares().len(), 1);
+    }
+
+    #[test]
+    fn expired_share_is_rejected() {
+        let store = test_store();
+        let (share, token) = store.create_share("p1", None, Duration::ZERO);
+
+        assert!(store.authenticate(&token).is_none());
+        assert!(!store.share_is_live(&share.id));
+        assert!(store.list_shares().is_empty());
+    }
+
+    #[test]
+    fn revoked_share_stops_working() {
+        let store = test_store();
+        let (share, token) = store.create_share("p1", None, Duration::from_secs(60));
+
+        assert!(store.revoke_share(&share.id));
+        assert!(!store.revoke_share(&share.id));
+        assert!(store.authenticate(&token).is_none());
+    }
+
+    #[test]
+    fn share_token_cannot_be_refreshed() {
+        let store = test_store();
+        let (_, token) = store.create_share("p1", None, Duration::from_secs(60));
+
+        assert!(store.refresh_token(&token).is_err());
+    }
+
+    #[test]
+    fn shares_survive_token_reload() {
+        let store = test_store();
+        let (_, token) = store.create_share("p1", None, Duration::from_secs(60));
+        pair_token(&store);
+
+        assert!(store.reload_tokens());
+        assert!(store.authenticate(&token).is_some());
+    }
 }
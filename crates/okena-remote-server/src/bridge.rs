@@ -1,4 +1,5 @@
 use crate::types::ActionRequest;
+use okena_core::batch::ActionBatchRequest;
 use tokio::sync::oneshot;
 
 /// Result of processing a `RemoteCommand`. Defined in `okena-core` and
@@ -23,6 +24,8 @@ pub enum RemoteCommand {
         action: ActionRequest,
         connection_id: String,
     },
+    /// `POST /v1/actions/batch`: actions applied in order in one turn.
+    Batch(ActionBatchRequest),
     /// Resize from a WebSocket client. Only the current owner may resize.
     ResizeFromConnection {
         terminal_id: String,
@@ -14,6 +14,8 @@
 
 use crate::auth::{self, PersistedToken, TokenInfo};
 use base64::Engine as _;
+use okena_core::api::{ApiShareCreated, ApiShareRequest};
+use okena_core::audit::{AuditEntry, AuditQuery};
 pub use okena_core::process::is_process_alive;
 use okena_transport::client::{LOCAL_DAEMON_CONNECTION_ID, LocalEndpoint, RemoteConnectionConfig};
 use okena_workspace::persistence::config_dir;
@@ -65,6 +67,7 @@ impl LocalDaemon {
             tls: self.tls,
             pinned_cert_sha256: None,
             local_endpoint: self.local_endpoint.clone(),
+            proxy_command: None,
         }
     }
 }
@@ -240,6 +243,7 @@ pub fn mint_local_token_in(dir: &Path) -> Result<MintedToken, String> {
         id: token_id.clone(),
         token_hmac: token_hmac_b64,
         created_at,
+        grant: okena_core::scope::TokenGrant::default(),
     };
     auth::append_persisted_token(&dir.join("remote_tokens.json"), persisted)
         .map_err(|e| format!("Failed to write remote_tokens.json: {e}"))?;
@@ -1009,6 +1013,11 @@ struct TokenListResponse {
     tokens: Vec<TokenInfo>,
 }
 
+#[derive(Deserialize)]
+struct AuditResponse {
+    entries: Vec<AuditEntry>,
+}
+
 /// Everything a same-host caller needs to reach the local daemon's *protected*
 /// HTTP API: where to dial plus the bearer token to present.
 ///
@@ -1043,11 +1052,15 @@ fn error_for_status(
     Err(format!("{what} returned {status}: {body}"))
 }
 
-pub fn request_pair_code(endpoint: &DaemonEndpoint) -> Result<LocalPairCode, String> {
+pub fn request_pair_code(
+    endpoint: &DaemonEndpoint,
+    grant: &okena_core::scope::TokenGrant,
+) -> Result<LocalPairCode, String> {
     let (client, url) = endpoint.client_and_url("/v1/pair-code");
     let resp = client
         .post(&url)
         .bearer_auth(&endpoint.token)
+        .json(grant)
         .timeout(Duration::from_secs(5))
         .send()
         .map_err(|e| format!("Failed to request pairing code: {e}"))?;
@@ -1086,6 +1099,26 @@ pub fn list_paired_devices(endpoint: &DaemonEndpoint) -> Result<Vec<TokenInfo>,
     Ok(body.tokens)
 }
 
+/// Audit log entries matching `query`, oldest first (`GET /v1/audit`).
+pub fn list_audit_entries(
+    endpoint: &DaemonEndpoint,
+    query: &AuditQuery,
+) -> Result<Vec<AuditEntry>, String> {
+    let (client, url) = endpoint.client_and_url("/v1/audit");
+    let resp = client
+        .get(&url)
+        .bearer_auth(&endpoint.token)
+        .query(query)
+        .timeout(Duration::from_secs(5))
+        .send()
+        .map_err(|e| format!("Failed to read audit log: {e}"))?;
+
+    let body = error_for_status(resp, "Audit log")?
+        .json::<AuditResponse>()
+        .map_err(|e| format!("Failed to parse audit log: {e}"))?;
+    Ok(body.entries)
+}
+
 /// Revoke one paired device by token id (`DELETE /v1/tokens/{id}`).
 pub fn revoke_paired_device(endpoint: &DaemonEndpoint, id: &str) -> Result<(), String> {
     let (client, url) = endpoint.client_and_url(&format!("/v1/tokens/{id}"));
@@ -1099,6 +1132,70 @@ pub fn revoke_paired_device(endpoint: &DaemonEndpoint, id: &str) -> Result<(), S
     error_for_status(resp, "Device revocation").map(|_| ())
 }
 
+/// Mint a view-only share link (`POST /v1/shares`).
+pub fn create_share(
+    endpoint: &DaemonEndpoint,
+    request: &ApiShareRequest,
+) -> Result<ApiShareCreated, String> {
+    let (client, url) = endpoint.client_and_url("/v1/shares");
+    let resp = client
+        .post(&url)
+        .bearer_auth(&endpoint.token)
+        .json(request)
+        .timeout(Duration::from_secs(5))
+        .send()
+        .map_err(|e| format!("Failed to create share link: {e}"))?;
+
+    error_for_status(resp, "Share link")?
+        .json::<ApiShareCreated>()
+        .map_err(|e| format!("Failed to parse share link: {e}"))
+}
+
+/// End a share link and disconnect its viewers (`DELETE /v1/shares/{id}`).
+pub fn revoke_share(endpoint: &DaemonEndpoint, id: &str) -> Result<(), String> {
+    let (client, url) = endpoint.client_and_url(&format!("/v1/shares/{id}"));
+    let resp = client
+        .delete(&url)
+        .bearer_auth(&endpoint.token)
+        .timeout(Duration::from_secs(5))
+        .send()
+        .map_err(|e| format!("Failed to revoke share link: {e}"))?;
+
+    error_for_status(resp, "Share revocation").map(|_| ())
+}
+
+/// `okena-daemon --stdio`: carry stdin/stdout to the running daemon's Unix
+/// socket, so a client with a proxy command like `ssh host okena-daemon
+/// --stdio` reaches it without the daemon listening on any network port. The
+/// daemon sees a same-user socket peer; ssh did the authenticating. One
+/// invocation carries one HTTP or WebSocket connection.
+#[cfg(unix)]
+pub fn relay_stdio_to_daemon() -> Result<(), String> {
+    use std::os::unix::net::UnixStream;
+
+    let daemon = running_daemon().ok_or("no Okena daemon is running for this profile")?;
+    let Some(LocalEndpoint::UnixSocket { path }) = daemon.local_endpoint else {
+        return Err("the running daemon has no Unix socket endpoint".to_string());
+    };
+    let stream =
+        UnixStream::connect(&path).map_err(|e| format!("cannot connect to {path}: {e}"))?;
+    let mut to_daemon = stream.try_clone().map_err(|e| e.to_string())?;
+    std::thread::spawn(move || {
+        let _ = okena_transport::proxy::pump(&mut std::io::stdin().lock(), &mut to_daemon);
+        let _ = to_daemon.shutdown(std::net::Shutdown::Write);
+    });
+    // Once the daemon hangs up we are done; a stdin still open is not ours to
+    // wait for.
+    let mut from_daemon = stream;
+    okena_transport::proxy::pump(&mut from_daemon, &mut std::io::stdout().lock())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(unix))]
+pub fn relay_stdio_to_daemon() -> Result<(), String> {
+    Err("--stdio needs Unix sockets, which this platform lacks".to_string())
+}
+
 fn blocking_client_and_url(
     host: &str,
     port: u16,
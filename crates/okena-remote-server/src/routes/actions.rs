@@ -1,11 +1,82 @@
 use crate::bridge::{BridgeMessage, CommandResult, RemoteCommand};
-use crate::routes::AppState;
+use crate::routes::audit::{self, Caller};
+use crate::routes::{AppState, events};
 use crate::types::ActionRequest;
 use axum::Json;
-use axum::extract::State;
+use axum::extract::{Extension, State};
 use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::response::{IntoResponse, Response};
 use okena_core::api::ApiTerminalFocusRequest;
+use okena_core::batch::{ActionBatchRequest, references_as_text};
+use okena_core::scope::{ProjectAccess, Scope, TokenGrant};
+
+fn forbidden(error: String) -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(serde_json::json!({"error": error})),
+    )
+        .into_response()
+}
+
+/// The projects a restricted grant reaches right now.
+pub(crate) async fn project_access(
+    state: &AppState,
+    grant: &TokenGrant,
+) -> Result<ProjectAccess, Response> {
+    match events::fetch_state(&state.bridge_tx).await {
+        Ok(snapshot) => Ok(ProjectAccess::new(grant, &snapshot)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": e})),
+        )
+            .into_response()),
+    }
+}
+
+/// Check `actions` against the caller's grant. Only project-restricted grants
+/// pay for the state snapshot that maps terminals to projects.
+pub(crate) async fn authorize(
+    state: &AppState,
+    grant: &TokenGrant,
+    actions: &[ActionRequest],
+) -> Result<(), Response> {
+    if grant.is_full() {
+        return Ok(());
+    }
+    let access = if grant.is_project_restricted() {
+        Some(project_access(state, grant).await?)
+    } else {
+        None
+    };
+    for action in actions {
+        grant
+            .authorize(action, access.as_ref())
+            .map_err(forbidden)?;
+    }
+    Ok(())
+}
+
+/// [`authorize`] for routes outside `/v1/actions` that act on one terminal.
+pub(crate) async fn authorize_terminal(
+    state: &AppState,
+    grant: &TokenGrant,
+    scope: Scope,
+    terminal_id: &str,
+) -> Result<(), Response> {
+    if !grant.allows(scope) {
+        return Err(forbidden(format!("token lacks the '{scope}' scope")));
+    }
+    if grant.is_project_restricted()
+        && !project_access(state, grant)
+            .await?
+            .allows_terminal(terminal_id)
+    {
+        return Err(forbidden(
+            "token is not allowed to access this project".to_string(),
+        ));
+    }
+    Ok(())
+}
 
 fn terminal_focus_request(action: &ActionRequest) -> Option<ApiTerminalFocusRequest> {
     match action {
@@ -24,8 +95,17 @@ fn terminal_focus_request(action: &ActionRequest) -> Option<ApiTerminalFocusRequ
 
 pub async fn post_actions(
     State(state): State<AppState>,
+    Extension(grant): Extension<TokenGrant>,
+    Extension(caller): Extension<Option<Caller>>,
     Json(action): Json<ActionRequest>,
 ) -> impl IntoResponse {
+    if let Err(response) = authorize(&state, &grant, std::slice::from_ref(&action)).await {
+        if response.status() == StatusCode::FORBIDDEN {
+            audit::record_action(&state, caller.as_ref(), &action, true);
+        }
+        return response;
+    }
+    audit::record_action(&state, caller.as_ref(), &action, false);
     let terminal_focus = terminal_focus_request(&action);
     let command = RemoteCommand::Action(action);
 
@@ -67,6 +147,77 @@ pub async fn post_actions(
     }
 }
 
+/// `POST /v1/actions/batch`. Replies 200 when every step succeeded and 400
+/// with the same body (naming the failed step) otherwise.
+///
+/// Scoped tokens have every step checked up front, so a step must parse on
+/// its own. A `$ref` in an id field is checked as its text, so restricted
+/// tokens can't point a step at a terminal an earlier step created.
+pub async fn post_actions_batch(
+    State(state): State<AppState>,
+    Extension(grant): Extension<TokenGrant>,
+    Extension(caller): Extension<Option<Caller>>,
+    Json(batch): Json<ActionBatchRequest>,
+) -> impl IntoResponse {
+    if !grant.is_full() {
+        let mut steps = Vec::with_capacity(batch.actions.len());
+        for (index, step) in batch.actions.iter().enumerate() {
+            match serde_json::from_value::<ActionRequest>(references_as_text(step)) {
+                Ok(action) => steps.push(action),
+                Err(e) => {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        Json(serde_json::json!({"error": format!("step {index}: {e}")})),
+                    )
+                        .into_response();
+                }
+            }
+        }
+        if let Err(response) = authorize(&state, &grant, &steps).await {
+            if response.status() == StatusCode::FORBIDDEN {
+                audit::record_batch(&state, caller.as_ref(), &batch.actions, true);
+            }
+            return response;
+        }
+    }
+    audit::record_batch(&state, caller.as_ref(), &batch.actions, false);
+
+    let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+    let msg = BridgeMessage {
+        command: RemoteCommand::Batch(batch),
+        reply: Some(reply_tx),
+    };
+
+    if state.bridge_tx.send(msg).await.is_err() {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "bridge unavailable"})),
+        )
+            .into_response();
+    }
+
+    match reply_rx.await {
+        Ok(CommandResult::Ok(Some(body))) => {
+            let status = if body.get("failed_step").is_some() {
+                StatusCode::BAD_REQUEST
+            } else {
+                StatusCode::OK
+            };
+            (status, Json(body)).into_response()
+        }
+        Ok(CommandResult::Err(e)) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": e})),
+        )
+            .into_response(),
+        Ok(_) | Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "command processing failed"})),
+        )
+            .into_response(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
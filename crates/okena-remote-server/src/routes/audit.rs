@@ -0,0 +1,232 @@
+//! Audit logging for token-authenticated requests, and `GET /v1/audit`.
+//!
+//! Unix socket traffic comes from the local desktop app and the CLI on the
+//! same account, so it carries no [`Caller`] and isn't logged.
+
+use crate::auth::AuthenticatedToken;
+use crate::routes::AppState;
+use crate::types::ActionRequest;
+use axum::Json;
+use axum::extract::{Extension, Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use okena_core::audit::{self, AuditEntry, AuditLog, AuditQuery};
+use okena_core::batch::references_as_text;
+use okena_core::scope::{Scope, TokenGrant};
+use std::net::IpAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// The token and client behind a request, inserted by the auth middleware
+/// as `Option<Caller>` (`None` on the Unix socket).
+#[derive(Clone, Debug)]
+pub struct Caller {
+    pub token_id: String,
+    pub token_name: Option<String>,
+    pub client: IpAddr,
+}
+
+impl Caller {
+    pub fn new(token: &AuthenticatedToken, client: IpAddr) -> Self {
+        Self {
+            token_id: token.id.clone(),
+            token_name: token.name.clone(),
+            client,
+        }
+    }
+}
+
+/// Hands entries to a writer task, so recording never blocks a request or
+/// the WebSocket input loop on file IO. Entries are written in the order
+/// they were recorded.
+#[derive(Clone)]
+pub struct AuditWriter {
+    log: Arc<AuditLog>,
+    tx: mpsc::UnboundedSender<AuditEntry>,
+}
+
+impl AuditWriter {
+    /// Start the writer task. Must be called inside a Tokio runtime.
+    pub fn spawn(log: AuditLog) -> Self {
+        let log = Arc::new(log);
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(write_entries(log.clone(), rx));
+        Self { log, tx }
+    }
+
+    pub fn log(&self) -> &Arc<AuditLog> {
+        &self.log
+    }
+}
+
+async fn write_entries(audit_log: Arc<AuditLog>, mut rx: mpsc::UnboundedReceiver<AuditEntry>) {
+    while let Some(entry) = rx.recv().await {
+        let writer = audit_log.clone();
+        let result = tokio::task::spawn_blocking(move || writer.append(&entry)).await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => log::warn!("audit: failed to write {}: {e}", audit_log.path().display()),
+            Err(e) => log::warn!("audit: writer task failed: {e}"),
+        }
+    }
+}
+
+/// Queue one entry. A failed write is logged, never surfaced to the caller.
+pub(crate) fn record(
+    state: &AppState,
+    caller: &Caller,
+    kind: impl Into<String>,
+    summary: String,
+    denied: bool,
+) {
+    let entry = AuditEntry {
+        at: audit::now_secs(),
+        token_id: caller.token_id.clone(),
+        token_name: caller.token_name.clone(),
+        client: caller.client.to_string(),
+        kind: kind.into(),
+        summary,
+        denied,
+    };
+    // Only fails once the writer task is gone, at shutdown.
+    let _ = state.audit.tx.send(entry);
+}
+
+/// Record `action` if it mutates anything or was refused.
+pub(crate) fn record_action(
+    state: &AppState,
+    caller: Option<&Caller>,
+    action: &ActionRequest,
+    denied: bool,
+) {
+    let Some(caller) = caller else {
+        return;
+    };
+    if denied || audit::is_audited(action) {
+        let (kind, summary) = audit::describe_action(action);
+        record(state, caller, kind, summary, denied);
+    }
+}
+
+/// Record a batch as one entry listing its steps, if any step mutates
+/// anything or the batch was refused. A step's `$ref`s are listed as their
+/// text.
+pub(crate) fn record_batch(
+    state: &AppState,
+    caller: Option<&Caller>,
+    steps: &[serde_json::Value],
+    denied: bool,
+) {
+    let Some(caller) = caller else {
+        return;
+    };
+    let actions: Vec<Option<ActionRequest>> = steps
+        .iter()
+        .map(|step| serde_json::from_value(references_as_text(step)).ok())
+        .collect();
+    let mutating = actions
+        .iter()
+        .any(|action| action.as_ref().is_none_or(audit::is_audited));
+    if !denied && !mutating {
+        return;
+    }
+    let described: Vec<String> = actions
+        .iter()
+        .map(|action| match action {
+            Some(action) => {
+                let (kind, summary) = audit::describe_action(action);
+                format!("{kind}({summary})")
+            }
+            None => "<unparsed step>".to_string(),
+        })
+        .collect();
+    let summary = format!("{} steps: {}", steps.len(), described.join("; "));
+    record(state, caller, "batch", summary, denied);
+}
+
+/// `GET /v1/audit?token=&kind=&since=&limit=` — matching entries, oldest
+/// first. Admin only, and not for project-restricted admins.
+pub async fn get_audit(
+    State(state): State<AppState>,
+    Extension(grant): Extension<TokenGrant>,
+    Query(query): Query<AuditQuery>,
+) -> impl IntoResponse {
+    if !grant.allows(Scope::Admin) || grant.is_project_restricted() {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({ "error": "token lacks the 'admin' scope" })),
+        )
+            .into_response();
+    }
+    let log = state.audit.log().clone();
+    match tokio::task::spawn_blocking(move || log.query(&query)).await {
+        Ok(entries) => Json(serde_json::json!({ "entries": entries })).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn entry(summary: &str) -> AuditEntry {
+        AuditEntry {
+            at: 0,
+            token_id: "t".to_string(),
+            token_name: None,
+            client: "127.0.0.1".to_string(),
+            kind: "send_text".to_string(),
+            summary: summary.to_string(),
+            denied: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn writer_appends_entries_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let writer = AuditWriter::spawn(AuditLog::new(dir.path().join("audit.jsonl")));
+        for summary in ["first", "second", "third"] {
+            writer.tx.send(entry(summary)).unwrap();
+        }
+
+        let mut summaries = Vec::new();
+        for _ in 0..100 {
+            summaries = writer
+                .log()
+                .query(&AuditQuery::default())
+                .into_iter()
+                .map(|entry| entry.summary)
+                .collect();
+            if summaries.len() == 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(summaries, ["first", "second", "third"]);
+    }
+
+    #[tokio::test]
+    async fn restricted_admins_cannot_read_the_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = crate::routes::test_state(dir.path());
+        let query = || Query(AuditQuery::default());
+        let restricted = TokenGrant {
+            folders: vec!["f1".into()],
+            ..TokenGrant::default()
+        };
+
+        let response = get_audit(State(state.clone()), Extension(restricted), query())
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = get_audit(State(state), Extension(TokenGrant::default()), query())
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -1,19 +1,17 @@
 use crate::bridge::{BridgeMessage, CommandResult, RemoteCommand};
-use crate::routes::AppState;
+use crate::routes::{AppState, actions};
 use axum::Json;
 use axum::body::Body;
-use axum::extract::State;
+use axum::extract::{Extension, State};
 use axum::http::header::{CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_TYPE};
 use axum::http::{HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
 use okena_core::api::{ActionRequest, FileDownloadRequest, ResolvedPath, ResolvedPathKind};
+use okena_core::scope::TokenGrant;
 use tokio_util::io::ReaderStream;
 
-async fn resolve_file(
-    state: &AppState,
-    request: FileDownloadRequest,
-) -> Result<ResolvedPath, Response> {
-    let action = match request {
+fn download_action(request: FileDownloadRequest) -> ActionRequest {
+    match request {
         FileDownloadRequest::Project {
             project_id,
             relative_path,
@@ -31,7 +29,17 @@ async fn resolve_file(
             root,
             relative_path,
         },
-    };
+    }
+}
+
+/// Authorize and run one of the `Resolve*Path` actions. Shared with uploads,
+/// which resolve the target directory the same way.
+pub(crate) async fn resolve_path(
+    state: &AppState,
+    grant: &TokenGrant,
+    action: ActionRequest,
+) -> Result<ResolvedPath, Response> {
+    actions::authorize(state, grant, std::slice::from_ref(&action)).await?;
     let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
     state
         .bridge_tx
@@ -76,9 +84,10 @@ fn safe_download_name(name: &str) -> String {
 
 pub async fn post_download(
     State(state): State<AppState>,
+    Extension(grant): Extension<TokenGrant>,
     Json(request): Json<FileDownloadRequest>,
 ) -> Response {
-    let file = match resolve_file(&state, request).await {
+    let file = match resolve_path(&state, &grant, download_action(request)).await {
         Ok(file) => file,
         Err(response) => return response,
     };
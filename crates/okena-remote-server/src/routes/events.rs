@@ -0,0 +1,215 @@
+//! `GET /v1/events` — semantic events as newline-delimited JSON.
+//!
+//! Each connection takes a baseline state snapshot, then turns every
+//! `state_version` bump into `state_changed` plus the semantic events from
+//! [`diff_states`] against the previous snapshot. Git status changes, toasts and
+//! the PTY-level events the daemon pushes on `events_tx` (command finished,
+//! bell, notification) are merged into the same stream. Filtering happens here,
+//! so `?type=bell,command_finished&project=<id>` only ships matching lines.
+//!
+//! An empty line is written every [`HEARTBEAT`] so a vanished client is noticed
+//! (the write fails) and the pump task exits.
+
+use crate::bridge::{BridgeMessage, BridgeSender, CommandResult, RemoteCommand};
+use crate::routes::AppState;
+use axum::Json;
+use axum::body::Body;
+use axum::extract::{Extension, Query, State};
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use okena_core::api::StateResponse;
+use okena_core::events::{ApiEvent, EventFilter, diff_git_statuses, diff_states};
+use okena_core::scope::{ProjectAccess, TokenGrant};
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+
+const HEARTBEAT: Duration = Duration::from_secs(15);
+
+#[derive(Deserialize)]
+pub struct EventsQuery {
+    /// Comma-separated event types; omit for all.
+    #[serde(rename = "type")]
+    types: Option<String>,
+    /// Comma-separated project ids; omit for all.
+    project: Option<String>,
+}
+
+pub async fn get_events(
+    State(state): State<AppState>,
+    Extension(grant): Extension<TokenGrant>,
+    Query(query): Query<EventsQuery>,
+) -> Response {
+    let mut filter = match EventFilter::parse(query.types.as_deref(), query.project.as_deref()) {
+        Ok(filter) => filter,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({"error": e})),
+            )
+                .into_response();
+        }
+    };
+
+    // Subscribe before taking the baseline so nothing in between is lost.
+    // A fresh watch receiver has already seen the current value, so only
+    // later bumps wake the pump.
+    let version_rx = state.state_version.subscribe();
+    let git_rx = state.git_status.subscribe();
+    let git_baseline = git_rx.borrow().clone();
+    let toast_rx = state.toast_tx.subscribe();
+    let events_rx = state.events_tx.subscribe();
+
+    let baseline = match fetch_state(&state.bridge_tx).await {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": e})),
+            )
+                .into_response();
+        }
+    };
+
+    // A project-restricted token only hears about its projects, which also
+    // drops the project-less events (state changes, toasts).
+    if grant.is_project_restricted() {
+        let access = ProjectAccess::new(&grant, &baseline);
+        if filter.project_ids.is_empty() {
+            filter.project_ids = access.project_ids().cloned().collect();
+        } else {
+            filter.project_ids.retain(|id| access.allows_project(id));
+        }
+        if filter.project_ids.is_empty() {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({"error": "token is not allowed to access this project"})),
+            )
+                .into_response();
+        }
+    }
+
+    let (line_tx, line_rx) = mpsc::channel::<String>(256);
+    tokio::spawn(pump_events(
+        EventSources {
+            bridge_tx: state.bridge_tx.clone(),
+            version_rx,
+            git_rx,
+            toast_rx,
+            events_rx,
+        },
+        baseline,
+        git_baseline,
+        filter,
+        line_tx,
+    ));
+
+    let stream = futures::stream::unfold(line_rx, |mut rx| async move {
+        rx.recv().await.map(|line| (Ok::<_, Infallible>(line), rx))
+    });
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/x-ndjson")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(Body::from_stream(stream))
+        .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+struct EventSources {
+    bridge_tx: BridgeSender,
+    version_rx: tokio::sync::watch::Receiver<u64>,
+    git_rx: tokio::sync::watch::Receiver<
+        std::collections::HashMap<String, okena_core::api::ApiGitStatus>,
+    >,
+    toast_rx: broadcast::Receiver<okena_core::api::ApiToast>,
+    events_rx: broadcast::Receiver<ApiEvent>,
+}
+
+/// Forward events to one subscriber until it disconnects or the server stops.
+async fn pump_events(
+    mut sources: EventSources,
+    mut last_state: StateResponse,
+    mut last_git: std::collections::HashMap<String, okena_core::api::ApiGitStatus>,
+    filter: EventFilter,
+    line_tx: mpsc::Sender<String>,
+) {
+    let mut heartbeat = tokio::time::interval(HEARTBEAT);
+    heartbeat.tick().await;
+    loop {
+        let events = tokio::select! {
+            changed = sources.version_rx.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+                let state_version = *sources.version_rx.borrow_and_update();
+                let mut events = vec![ApiEvent::StateChanged { state_version }];
+                match fetch_state(&sources.bridge_tx).await {
+                    Ok(next) => {
+                        events.extend(diff_states(&last_state, &next));
+                        last_state = next;
+                    }
+                    Err(e) => log::warn!("events: state refresh failed: {e}"),
+                }
+                events
+            }
+            changed = sources.git_rx.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+                let next = sources.git_rx.borrow_and_update().clone();
+                let events = diff_git_statuses(&last_git, &next);
+                last_git = next;
+                events
+            }
+            toast = sources.toast_rx.recv() => match toast {
+                Ok(toast) => vec![ApiEvent::Toast { toast }],
+                Err(broadcast::error::RecvError::Lagged(_)) => Vec::new(),
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            event = sources.events_rx.recv() => match event {
+                Ok(event) => vec![event],
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    log::warn!("events: subscriber lagged, dropped {n} events");
+                    Vec::new()
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            _ = heartbeat.tick() => {
+                if line_tx.send("\n".to_string()).await.is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+
+        for event in events.iter().filter(|e| filter.matches(e)) {
+            let Ok(mut line) = serde_json::to_string(event) else {
+                continue;
+            };
+            line.push('\n');
+            if line_tx.send(line).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+pub(crate) async fn fetch_state(bridge_tx: &BridgeSender) -> Result<StateResponse, String> {
+    let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+    let msg = BridgeMessage {
+        command: RemoteCommand::GetState,
+        reply: Some(reply_tx),
+    };
+    bridge_tx
+        .send(msg)
+        .await
+        .map_err(|_| "bridge unavailable".to_string())?;
+    match reply_rx.await {
+        Ok(CommandResult::Ok(Some(value))) => {
+            serde_json::from_value(value).map_err(|e| format!("invalid state: {e}"))
+        }
+        Ok(CommandResult::Err(e)) => Err(e),
+        _ => Err("unexpected response".to_string()),
+    }
+}
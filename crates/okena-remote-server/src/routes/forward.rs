@@ -0,0 +1,207 @@
+//! `GET /v1/forward`: relay one TCP connection to a service's detected port.
+//!
+//! The protocol is described in [`okena_core::forward`]. The socket
+//! authenticates itself like `/v1/stream`, needs the `services` scope on the
+//! project, and may only reach a port the daemon detected for that service.
+
+// `ForwardReply` is an internal DTO whose Serialize impl cannot fail.
+#![allow(clippy::expect_used)]
+
+use crate::routes::audit::{self, Caller};
+use crate::routes::{AppState, PeerInfo, events};
+use crate::types::WsInbound;
+use axum::extract::ws::{Message, WebSocket};
+use axum::extract::{Extension, Query, State, WebSocketUpgrade};
+use axum::response::IntoResponse;
+use futures::{SinkExt, StreamExt};
+use okena_core::forward::{ForwardReply, PortForwardQuery, resolve_target};
+use okena_core::scope::{ProjectAccess, Scope, TokenGrant};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// How long a client has to send its `auth` message.
+const AUTH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Largest binary frame sent towards the client.
+const RELAY_CHUNK: usize = 64 * 1024;
+
+pub async fn ws_forward(
+    State(state): State<AppState>,
+    Query(query): Query<PortForwardQuery>,
+    Extension(peer): Extension<PeerInfo>,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_forward(socket, state, query, peer))
+}
+
+async fn handle_forward(
+    mut socket: WebSocket,
+    state: AppState,
+    query: PortForwardQuery,
+    peer: PeerInfo,
+) {
+    let Some((grant, caller)) = authenticate(&mut socket, &state, &peer).await else {
+        reply_error(socket, "authentication required").await;
+        return;
+    };
+
+    let target = authorize(&state, &grant, &query).await;
+    if let Some(caller) = &caller {
+        let port = match &target {
+            Ok(port) => port.to_string(),
+            Err(_) => query
+                .port
+                .map_or_else(|| "-".to_string(), |p| p.to_string()),
+        };
+        let summary = format!(
+            "project_id={} service={} port={port}",
+            query.project_id, query.service
+        );
+        let denied = matches!(target, Err(Denial::Forbidden(_)));
+        audit::record(&state, caller, "forward", summary, denied);
+    }
+    let port = match target {
+        Ok(port) => port,
+        Err(Denial::Forbidden(error) | Denial::Invalid(error)) => {
+            reply_error(socket, error).await;
+            return;
+        }
+    };
+
+    // Services commonly bind only one of 127.0.0.1 and ::1; `localhost`
+    // tries both.
+    let tcp = match TcpStream::connect(("localhost", port)).await {
+        Ok(tcp) => tcp,
+        Err(e) => {
+            reply_error(socket, format!("cannot connect to port {port}: {e}")).await;
+            return;
+        }
+    };
+    let _ = tcp.set_nodelay(true);
+    if send_reply(&mut socket, &ForwardReply::Ready { port })
+        .await
+        .is_err()
+    {
+        return;
+    }
+    relay(socket, tcp).await;
+}
+
+/// First-message auth, as on `/v1/stream`. Local clients skip it.
+async fn authenticate(
+    socket: &mut WebSocket,
+    state: &AppState,
+    peer: &PeerInfo,
+) -> Option<(TokenGrant, Option<Caller>)> {
+    if matches!(peer, PeerInfo::Local) {
+        return Some((TokenGrant::default(), None));
+    }
+    let Ok(Some(Ok(Message::Text(text)))) = tokio::time::timeout(AUTH_TIMEOUT, socket.recv()).await
+    else {
+        return None;
+    };
+    let Ok(WsInbound::Auth { token, .. }) = serde_json::from_str::<WsInbound>(&text) else {
+        return None;
+    };
+    let token = state.auth_store.authenticate(&token)?;
+    let caller = match peer {
+        PeerInfo::Tcp(addr) => Some(Caller::new(&token, addr.ip())),
+        PeerInfo::Local => None,
+    };
+    Some((token.grant, caller))
+}
+
+enum Denial {
+    /// The grant doesn't cover the project or the `services` scope.
+    Forbidden(String),
+    /// Unknown project or service, or a port the service doesn't listen on.
+    Invalid(String),
+}
+
+async fn authorize(
+    state: &AppState,
+    grant: &TokenGrant,
+    query: &PortForwardQuery,
+) -> Result<u16, Denial> {
+    let scope = Scope::Services;
+    if !grant.allows(scope) {
+        return Err(Denial::Forbidden(format!(
+            "token lacks the '{scope}' scope"
+        )));
+    }
+    let snapshot = events::fetch_state(&state.bridge_tx)
+        .await
+        .map_err(Denial::Invalid)?;
+    if grant.is_project_restricted()
+        && !ProjectAccess::new(grant, &snapshot).allows_project(&query.project_id)
+    {
+        return Err(Denial::Forbidden(
+            "token is not allowed to access this project".to_string(),
+        ));
+    }
+    resolve_target(&snapshot, query).map_err(Denial::Invalid)
+}
+
+async fn send_reply(socket: &mut WebSocket, reply: &ForwardReply) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(reply).expect("BUG: ForwardReply must serialize");
+    socket.send(Message::Text(text.into())).await
+}
+
+async fn reply_error(mut socket: WebSocket, error: impl Into<String>) {
+    let reply = ForwardReply::Error {
+        error: error.into(),
+    };
+    if send_reply(&mut socket, &reply).await.is_ok() {
+        let _ = socket.send(Message::Close(None)).await;
+    }
+}
+
+/// Copy bytes both ways until both directions reach EOF or either side
+/// fails. EOF travels as an empty binary frame (WebSocket has no half-close),
+/// so a client that half-closes still receives the whole reply.
+async fn relay(socket: WebSocket, tcp: TcpStream) {
+    let (mut ws_tx, mut ws_rx) = socket.split();
+    let (mut tcp_rx, mut tcp_tx) = tcp.into_split();
+    {
+        let upstream = async {
+            while let Some(Ok(message)) = ws_rx.next().await {
+                match message {
+                    Message::Binary(data) => {
+                        if data.is_empty() {
+                            let _ = tcp_tx.shutdown().await;
+                            return true;
+                        }
+                        if tcp_tx.write_all(&data).await.is_err() {
+                            return false;
+                        }
+                    }
+                    Message::Close(_) => return false,
+                    _ => {}
+                }
+            }
+            false
+        };
+        let downstream = async {
+            let mut buf = vec![0u8; RELAY_CHUNK];
+            loop {
+                match tcp_rx.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        let frame = Message::Binary(buf[..n].to_vec().into());
+                        if ws_tx.send(frame).await.is_err() {
+                            return false;
+                        }
+                    }
+                }
+            }
+            ws_tx.send(Message::Binary(Vec::new().into())).await.is_ok()
+        };
+        tokio::pin!(upstream, downstream);
+        tokio::select! {
+            done = &mut upstream => if done { downstream.await; },
+            done = &mut downstream => if done { upstream.await; },
+        }
+    }
+    let _ = ws_tx.close().await;
+}
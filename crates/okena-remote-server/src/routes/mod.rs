@@ -1,16 +1,23 @@
 pub mod actions;
+pub mod audit;
 pub mod auth_reload;
 pub mod download;
+pub mod events;
+pub mod forward;
 pub mod health;
 pub mod pair;
 pub mod paste_image;
 pub mod refresh;
 pub mod restart;
+pub mod schema;
+pub mod shares;
 pub mod shutdown;
 pub mod state;
 pub mod stream;
 pub mod tokens;
 pub mod update;
+pub mod upload;
+pub mod wait_for;
 
 use crate::auth::AuthStore;
 use crate::bridge::BridgeSender;
@@ -22,7 +29,10 @@ use axum::http::StatusCode;
 use axum::middleware::{self, Next};
 use axum::response::Response;
 use okena_core::api::{ApiGitStatus, ApiTerminalFocusRequest, ApiToast};
+use okena_core::audit::{AUDIT_FILE, AuditLog};
+use okena_core::events::ApiEvent;
 use okena_core::git_poll::GitPollTrigger;
+use okena_core::scope::TokenGrant;
 use rust_embed::RustEmbed;
 use std::collections::{HashMap, HashSet};
 use std::net::{IpAddr, SocketAddr};
@@ -52,6 +62,10 @@ pub struct AppState {
     /// One-shot exact-terminal focus requests produced by successful external
     /// actions and consumed by connected desktop clients.
     pub terminal_focus_tx: Arc<tokio::sync::broadcast::Sender<ApiTerminalFocusRequest>>,
+    /// Semantic events the daemon emits directly (command finished, bell,
+    /// notification) for `/v1/events` subscribers. State-derived events are
+    /// diffed per connection in [`events::get_events`].
+    pub events_tx: Arc<tokio::sync::broadcast::Sender<ApiEvent>>,
     /// Per-connection set of subscribed terminal IDs (connection_id → terminal_ids).
     /// Used by GitStatusWatcher to poll git for projects visible on remote clients.
     pub remote_subscribed_terminals: Arc<RwLock<HashMap<u64, HashSet<String>>>>,
@@ -84,6 +98,18 @@ pub struct AppState {
     /// instance lock through normal drops.
     pub process_shutdown: Arc<tokio::sync::Notify>,
     pub update_info: okena_ext_updater::UpdateInfo,
+    /// Append-only record of what paired tokens did (see [`audit`]).
+    pub audit: audit::AuditWriter,
+    /// Connections opened with a share link (connection_id → share id). Their
+    /// count is the viewer number owners see on the shared pane.
+    pub spectators: Arc<RwLock<HashMap<u64, String>>>,
+    /// Ids of revoked shares, so their open connections close at once.
+    pub share_revoked: Arc<tokio::sync::broadcast::Sender<String>>,
+    /// Origin share URLs are built on (see [`shares::share_origin`]).
+    pub share_origin: String,
+    /// State deltas computed once per version for unrestricted stream
+    /// connections.
+    pub state_deltas: Arc<stream::SharedStateDeltas>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -125,6 +151,7 @@ pub fn build_router(
     git_status: Arc<tokio::sync::watch::Sender<HashMap<String, ApiGitStatus>>>,
     toast_tx: Arc<tokio::sync::broadcast::Sender<ApiToast>>,
     terminal_focus_tx: Arc<tokio::sync::broadcast::Sender<ApiTerminalFocusRequest>>,
+    events_tx: Arc<tokio::sync::broadcast::Sender<ApiEvent>>,
     remote_subscribed_terminals: Arc<RwLock<HashMap<u64, HashSet<String>>>>,
     remote_visible_projects: Arc<RwLock<HashMap<u64, HashSet<String>>>>,
     git_poll_trigger_tx: Option<tokio::sync::mpsc::UnboundedSender<GitPollTrigger>>,
@@ -134,6 +161,7 @@ pub fn build_router(
     ui_owned: bool,
     had_client: Arc<AtomicBool>,
     update_info: okena_ext_updater::UpdateInfo,
+    share_origin: String,
 ) -> Router {
     let state = AppState {
         bridge_tx,
@@ -144,6 +172,7 @@ pub fn build_router(
         git_status,
         toast_tx,
         terminal_focus_tx,
+        events_tx,
         remote_subscribed_terminals,
         remote_visible_projects,
         git_poll_trigger_tx,
@@ -154,16 +183,30 @@ pub fn build_router(
         shutdown_when_idle: Arc::new(AtomicBool::new(false)),
         had_client,
         update_info,
+        audit: audit::AuditWriter::spawn(AuditLog::new(
+            okena_workspace::persistence::config_dir().join(AUDIT_FILE),
+        )),
+        spectators: Arc::new(RwLock::new(HashMap::new())),
+        share_revoked: Arc::new(tokio::sync::broadcast::channel(16).0),
+        share_origin,
+        state_deltas: Arc::new(stream::SharedStateDeltas::default()),
     };
 
     // Routes that require auth
     let protected = Router::new()
         .route("/v1/state", axum::routing::get(state::get_state))
         .route("/v1/actions", axum::routing::post(actions::post_actions))
+        .route(
+            "/v1/actions/batch",
+            axum::routing::post(actions::post_actions_batch),
+        )
         .route(
             "/v1/files/download",
             axum::routing::post(download::post_download),
         )
+        // Streams its raw body, which `DefaultBodyLimit` doesn't cover; the
+        // handler enforces `upload::UPLOAD_LIMIT` itself.
+        .route("/v1/upload", axum::routing::post(upload::post_upload))
         .route(
             "/v1/terminals/{terminal_id}/paste-image",
             axum::routing::post(paste_image::post_paste_image)
@@ -174,13 +217,29 @@ pub fn build_router(
             axum::routing::post(paste_image::post_paste_file)
                 .layer(DefaultBodyLimit::max(paste_image::FILE_UPLOAD_LIMIT)),
         )
+        .route(
+            "/v1/terminals/{terminal_id}/wait-for",
+            axum::routing::post(wait_for::post_wait_for),
+        )
         .route("/v1/stream", axum::routing::get(stream::ws_handler))
+        .route("/v1/forward", axum::routing::get(forward::ws_forward))
+        .route("/v1/events", axum::routing::get(events::get_events))
         .route("/v1/refresh", axum::routing::post(refresh::post_refresh))
         .route("/v1/tokens", axum::routing::get(tokens::list_tokens))
+        .route("/v1/audit", axum::routing::get(audit::get_audit))
+        .route("/v1/tokens/self", axum::routing::get(tokens::own_grant))
         .route(
             "/v1/tokens/{id}",
             axum::routing::delete(tokens::revoke_token),
         )
+        .route(
+            "/v1/shares",
+            axum::routing::get(shares::list_shares).post(shares::post_share),
+        )
+        .route(
+            "/v1/shares/{id}",
+            axum::routing::delete(shares::revoke_share),
+        )
         .route(
             "/v1/pair-code",
             axum::routing::post(pair::post_pair_code).delete(pair::delete_pair_code),
@@ -202,6 +261,7 @@ pub fn build_router(
     let public = Router::new()
         .route("/health", axum::routing::get(health::get_health))
         .route("/v1/pair", axum::routing::post(pair::post_pair))
+        .route("/v1/schema", axum::routing::get(schema::get_schema))
         .route(
             "/v1/auth/reload",
             axum::routing::post(auth_reload::post_reload),
@@ -273,15 +333,19 @@ fn serve_embedded_file(path: &str, file: rust_embed::EmbeddedFile) -> axum::resp
     ([(axum::http::header::CONTENT_TYPE, mime)], file.data).into_response()
 }
 
-/// Auth middleware: validates Bearer token on protected routes.
+/// Auth middleware: validates Bearer token on protected routes and stores its
+/// [`TokenGrant`] in the request extensions for the handlers to enforce, next
+/// to the `Option<`[`audit::Caller`]`>` the audit log records.
 /// Unix socket traffic is already same-user scoped by the local transport.
 /// Skips validation for WebSocket upgrade requests (WS has its own auth flow).
 async fn auth_middleware(
     axum::extract::State(state): axum::extract::State<AppState>,
-    req: Request,
+    mut req: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
     if request_is_unix_socket(&req) {
+        req.extensions_mut().insert(TokenGrant::default());
+        req.extensions_mut().insert(None::<audit::Caller>);
         return Ok(next.run(req).await);
     }
 
@@ -306,13 +370,51 @@ async fn auth_middleware(
         _ => return Err(StatusCode::UNAUTHORIZED),
     };
 
-    if !state.auth_store.validate_token(token) {
+    let Some(token) = state.auth_store.authenticate(token) else {
         return Err(StatusCode::UNAUTHORIZED);
-    }
+    };
+    let caller = match req.extensions().get::<PeerInfo>() {
+        Some(PeerInfo::Tcp(addr)) => Some(audit::Caller::new(&token, addr.ip())),
+        _ => None,
+    };
+    req.extensions_mut().insert(token.grant);
+    req.extensions_mut().insert(caller);
 
     Ok(next.run(req).await)
 }
 
+/// A router state with nothing attached, for calling handlers directly in
+/// tests. The audit log lives in `dir`. Must be called inside a Tokio runtime.
+#[cfg(test)]
+pub(crate) fn test_state(dir: &std::path::Path) -> AppState {
+    AppState {
+        bridge_tx: crate::bridge::bridge_channel().0,
+        auth_store: Arc::new(AuthStore::with_secret(vec![7; 32])),
+        broadcaster: Arc::new(PtyBroadcaster::new()),
+        state_version: Arc::new(tokio::sync::watch::channel(0).0),
+        start_time: Instant::now(),
+        git_status: Arc::new(tokio::sync::watch::channel(HashMap::new()).0),
+        toast_tx: Arc::new(tokio::sync::broadcast::channel(16).0),
+        terminal_focus_tx: Arc::new(tokio::sync::broadcast::channel(16).0),
+        events_tx: Arc::new(tokio::sync::broadcast::channel(16).0),
+        remote_subscribed_terminals: Arc::new(RwLock::new(HashMap::new())),
+        remote_visible_projects: Arc::new(RwLock::new(HashMap::new())),
+        git_poll_trigger_tx: None,
+        next_connection_id: Arc::new(AtomicU64::new(1)),
+        active_connections: Arc::new(AtomicU64::new(0)),
+        ui_owned: false,
+        shutdown_when_idle: Arc::new(AtomicBool::new(false)),
+        had_client: Arc::new(AtomicBool::new(false)),
+        process_shutdown: Arc::new(tokio::sync::Notify::new()),
+        update_info: okena_ext_updater::UpdateInfo::new(String::new()),
+        audit: audit::AuditWriter::spawn(AuditLog::new(dir.join(AUDIT_FILE))),
+        spectators: Arc::new(RwLock::new(HashMap::new())),
+        share_revoked: Arc::new(tokio::sync::broadcast::channel(16).0),
+        share_origin: String::new(),
+        state_deltas: Arc::new(stream::SharedStateDeltas::default()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,10 +1,13 @@
 use crate::auth::{PairError, TOKEN_TTL_SECS};
+use crate::routes::audit::{self, Caller};
 use crate::routes::{AppState, PeerInfo};
 use crate::types::{PairRequest, PairResponse};
 use axum::Json;
+use axum::body::Bytes;
 use axum::extract::{ConnectInfo, Extension, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
+use okena_core::scope::{Scope, TokenGrant};
 use std::net::SocketAddr;
 
 pub async fn post_pair(
@@ -14,6 +17,12 @@ pub async fn post_pair(
 ) -> impl IntoResponse {
     match state.auth_store.try_pair(&req.code, addr.ip()) {
         Ok(token) => {
+            // The new token's first entry: which device paired from where.
+            if let Some(paired) = state.auth_store.authenticate(&token) {
+                let summary = format!("access={}", paired.grant.summary());
+                let caller = Caller::new(&paired, addr.ip());
+                audit::record(&state, &caller, "pair", summary, false);
+            }
             #[allow(
                 clippy::unwrap_used,
                 reason = "PairResponse is an internal type — serialization is infallible"
@@ -46,15 +55,43 @@ pub async fn post_pair(
     }
 }
 
+/// Only local, unrestricted admins may mint or drop pairing codes: a code
+/// hands out a new token, so a project-restricted one could widen itself.
+fn may_manage_codes(peer: PeerInfo, caller: &TokenGrant) -> bool {
+    peer.is_local_trusted() && caller.allows(Scope::Admin) && !caller.is_project_restricted()
+}
+
+/// `POST /v1/pair-code`. The optional body is the [`TokenGrant`] the paired
+/// token gets; without one it gets full access. The grant may not exceed the
+/// caller's own.
 pub async fn post_pair_code(
     Extension(peer): Extension<PeerInfo>,
+    Extension(caller): Extension<TokenGrant>,
     State(state): State<AppState>,
+    body: Bytes,
 ) -> impl IntoResponse {
-    if !peer.is_local_trusted() {
+    if !may_manage_codes(peer, &caller) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let grant = if body.is_empty() {
+        TokenGrant::default()
+    } else {
+        match serde_json::from_slice::<TokenGrant>(&body) {
+            Ok(grant) => grant,
+            Err(e) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({"error": format!("invalid grant: {e}")})),
+                )
+                    .into_response();
+            }
+        }
+    };
+    if !caller.covers(&grant) {
         return StatusCode::FORBIDDEN.into_response();
     }
 
-    let code = state.auth_store.generate_fresh_code();
+    let code = state.auth_store.generate_fresh_code_with_grant(grant);
     Json(serde_json::json!({
         "code": code,
         "expires_in": state.auth_store.code_remaining_secs(),
@@ -64,12 +101,62 @@ pub async fn post_pair_code(
 
 pub async fn delete_pair_code(
     Extension(peer): Extension<PeerInfo>,
+    Extension(caller): Extension<TokenGrant>,
     State(state): State<AppState>,
 ) -> StatusCode {
-    if !peer.is_local_trusted() {
+    if !may_manage_codes(peer, &caller) {
         return StatusCode::FORBIDDEN;
     }
 
     state.auth_store.invalidate_code();
     StatusCode::NO_CONTENT
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::test_state;
+
+    #[tokio::test]
+    async fn restricted_admins_cannot_mint_pairing_codes() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        let restricted = TokenGrant {
+            projects: vec!["web".into()],
+            ..TokenGrant::default()
+        };
+        let mint = |caller: TokenGrant, body: &'static str| {
+            post_pair_code(
+                Extension(PeerInfo::Local),
+                Extension(caller),
+                State(state.clone()),
+                Bytes::from_static(body.as_bytes()),
+            )
+        };
+
+        let response = mint(restricted.clone(), "").await.into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = mint(
+            restricted.clone(),
+            r#"{"scopes":["read"],"projects":["web"]}"#,
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let status = delete_pair_code(
+            Extension(PeerInfo::Local),
+            Extension(restricted),
+            State(state.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let response = mint(
+            TokenGrant::default(),
+            r#"{"scopes":["read"],"projects":["web"]}"#,
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -8,12 +8,14 @@
 //! image-paste (write temp file → `send_paste(path)`), just across the wire.
 
 use crate::bridge::{BridgeMessage, CommandResult, RemoteCommand};
-use crate::routes::AppState;
+use crate::routes::audit::{self, Caller};
+use crate::routes::{AppState, actions};
 use axum::Json;
 use axum::body::Bytes;
-use axum::extract::{Path, State};
+use axum::extract::{Extension, Path, State};
 use axum::http::{HeaderMap, StatusCode, header::CONTENT_TYPE};
 use axum::response::{IntoResponse, Response};
+use okena_core::scope::{Scope, TokenGrant};
 use std::sync::atomic::{AtomicU64, Ordering};
 
 /// Max accepted image upload (20 MiB). Clipboard screenshots — especially
@@ -25,6 +27,8 @@ pub const FILE_UPLOAD_LIMIT: usize = 64 * 1024 * 1024;
 
 pub async fn post_paste_image(
     State(state): State<AppState>,
+    Extension(grant): Extension<TokenGrant>,
+    Extension(caller): Extension<Option<Caller>>,
     Path(terminal_id): Path<String>,
     headers: HeaderMap,
     body: Bytes,
@@ -35,11 +39,18 @@ pub async fn post_paste_image(
         .unwrap_or("image/png");
     let ext = image_ext_for_mime(mime);
 
-    materialize_and_paste(state, terminal_id, body, ext, false, "image").await
+    let upload = Upload {
+        grant,
+        caller,
+        kind: "image",
+    };
+    materialize_and_paste(state, upload, terminal_id, body, ext, false).await
 }
 
 pub async fn post_paste_file(
     State(state): State<AppState>,
+    Extension(grant): Extension<TokenGrant>,
+    Extension(caller): Extension<Option<Caller>>,
     Path(terminal_id): Path<String>,
     headers: HeaderMap,
     body: Bytes,
@@ -49,17 +60,45 @@ pub async fn post_paste_file(
         .and_then(|value| value.to_str().ok())
         .and_then(safe_file_extension)
         .unwrap_or("bin");
-    materialize_and_paste(state, terminal_id, body, extension, true, "file").await
+    let upload = Upload {
+        grant,
+        caller,
+        kind: "file",
+    };
+    materialize_and_paste(state, upload, terminal_id, body, extension, true).await
+}
+
+/// Who is uploading what: `kind` is `image` or `file`.
+struct Upload {
+    grant: TokenGrant,
+    caller: Option<Caller>,
+    kind: &'static str,
 }
 
 async fn materialize_and_paste(
     state: AppState,
+    upload: Upload,
     terminal_id: String,
     body: Bytes,
     extension: &str,
     append_space: bool,
-    kind: &str,
 ) -> Response {
+    let kind = upload.kind;
+    // The upload lands in the temp dir and only its path is typed, so a paste
+    // is terminal input.
+    let authorized =
+        actions::authorize_terminal(&state, &upload.grant, Scope::TerminalInput, &terminal_id)
+            .await;
+    if let Some(caller) = &upload.caller {
+        let denied = authorized
+            .as_ref()
+            .is_err_and(|response| response.status() == StatusCode::FORBIDDEN);
+        let summary = format!("terminal_id={terminal_id} <{} bytes>", body.len());
+        audit::record(&state, caller, format!("paste_{kind}"), summary, denied);
+    }
+    if let Err(response) = authorized {
+        return response;
+    }
     if body.is_empty() {
         return (
             StatusCode::BAD_REQUEST,
@@ -0,0 +1,7 @@
+use axum::Json;
+
+/// `GET /v1/schema`: the OpenAPI document for this API, generated from the
+/// wire types this server was built with.
+pub async fn get_schema() -> Json<serde_json::Value> {
+    Json(okena_core::schema::openapi())
+}
@@ -0,0 +1,248 @@
+//! View-only share links: a short-lived read grant on one project or
+//! terminal, opened in the built-in web client.
+//!
+//! Shares live in [`AuthStore`](crate::auth::AuthStore) memory only. Every
+//! create, revoke and expiry bumps `state_version`, so owners see the share
+//! (and its viewer count from [`AppState::spectators`]) in `StateResponse`.
+
+use crate::auth::{MAX_SHARE_TTL_SECS, SHARE_TTL_SECS};
+use crate::routes::audit::{self, Caller};
+use crate::routes::{AppState, events};
+use axum::Json;
+use axum::extract::{Extension, Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use okena_core::api::{ApiShare, ApiShareCreated, ApiShareRequest, StateResponse};
+use okena_core::scope::{ProjectAccess, Scope, TokenGrant};
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+use std::time::Duration;
+
+/// Shortest lifetime a share may be created with.
+const MIN_SHARE_TTL_SECS: u64 = 60;
+
+fn admin_only() -> axum::response::Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(serde_json::json!({ "error": "token lacks the 'admin' scope" })),
+    )
+        .into_response()
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> axum::response::Response {
+    (status, Json(serde_json::json!({ "error": message.into() }))).into_response()
+}
+
+/// `POST /v1/shares`: mint a view-only link to a project or terminal.
+pub async fn post_share(
+    State(state): State<AppState>,
+    Extension(grant): Extension<TokenGrant>,
+    Extension(caller): Extension<Option<Caller>>,
+    Json(req): Json<ApiShareRequest>,
+) -> impl IntoResponse {
+    let detail = match &req.terminal_id {
+        Some(terminal_id) => format!("project={} terminal={terminal_id}", req.project_id),
+        None => format!("project={}", req.project_id),
+    };
+    let denied = !grant.allows(Scope::Admin) || grant.is_project_restricted();
+    if let Some(caller) = &caller {
+        audit::record(&state, caller, "create_share", detail, denied);
+    }
+    if denied {
+        return admin_only();
+    }
+
+    let snapshot = match events::fetch_state(&state.bridge_tx).await {
+        Ok(snapshot) => snapshot,
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+    if let Err(e) = check_target(&snapshot, &req) {
+        return error(StatusCode::NOT_FOUND, e);
+    }
+
+    let ttl = req
+        .ttl_secs
+        .unwrap_or(SHARE_TTL_SECS)
+        .clamp(MIN_SHARE_TTL_SECS, MAX_SHARE_TTL_SECS);
+    let (share, token) = state.auth_store.create_share(
+        &req.project_id,
+        req.terminal_id.as_deref(),
+        Duration::from_secs(ttl),
+    );
+    state.state_version.send_modify(|v| *v += 1);
+
+    // Announce the expiry so owners stop showing the share.
+    let version = state.state_version.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(ttl)).await;
+        version.send_modify(|v| *v += 1);
+    });
+
+    let path = share_path(&token, &share);
+    let url = format!("{}{path}", state.share_origin);
+    (
+        StatusCode::CREATED,
+        Json(ApiShareCreated {
+            share,
+            token,
+            path,
+            url,
+        }),
+    )
+        .into_response()
+}
+
+/// `GET /v1/shares`: live shares with their viewer counts.
+pub async fn list_shares(
+    State(state): State<AppState>,
+    Extension(grant): Extension<TokenGrant>,
+) -> impl IntoResponse {
+    if !grant.allows(Scope::Admin) {
+        return admin_only();
+    }
+    Json(serde_json::json!({ "shares": visible(&state, &grant) })).into_response()
+}
+
+/// `DELETE /v1/shares/{id}`: end a share and disconnect its viewers.
+pub async fn revoke_share(
+    State(state): State<AppState>,
+    Extension(grant): Extension<TokenGrant>,
+    Extension(caller): Extension<Option<Caller>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let denied = !grant.allows(Scope::Admin) || grant.is_project_restricted();
+    if let Some(caller) = &caller {
+        audit::record(&state, caller, "revoke_share", format!("id={id}"), denied);
+    }
+    if denied {
+        return admin_only();
+    }
+    if !state.auth_store.revoke_share(&id) {
+        return error(StatusCode::NOT_FOUND, "share not found");
+    }
+    let _ = state.share_revoked.send(id);
+    state.state_version.send_modify(|v| *v += 1);
+    (StatusCode::OK, Json(serde_json::json!({ "revoked": true }))).into_response()
+}
+
+/// Live shares for `grant`, with viewer counts. Only unrestricted admin
+/// grants see shares; everyone else gets an empty list.
+pub(crate) fn visible(state: &AppState, grant: &TokenGrant) -> Vec<ApiShare> {
+    if !grant.allows(Scope::Admin) || grant.is_project_restricted() {
+        return Vec::new();
+    }
+    let mut shares = state.auth_store.list_shares();
+    if let Ok(spectators) = state.spectators.read() {
+        for share in &mut shares {
+            share.viewers = spectators.values().filter(|id| **id == share.id).count() as u32;
+        }
+    }
+    shares
+}
+
+/// Fill `snapshot.shares` for grants allowed to see them.
+pub(crate) fn attach(state: &AppState, grant: &TokenGrant, snapshot: &mut StateResponse) {
+    snapshot.shares = visible(state, grant);
+}
+
+fn check_target(snapshot: &StateResponse, req: &ApiShareRequest) -> Result<(), String> {
+    if !snapshot.projects.iter().any(|p| p.id == req.project_id) {
+        return Err(format!("project '{}' not found", req.project_id));
+    }
+    if let Some(terminal_id) = &req.terminal_id {
+        let grant = TokenGrant {
+            projects: vec![req.project_id.clone()],
+            ..TokenGrant::read_only()
+        };
+        if !ProjectAccess::new(&grant, snapshot).allows_terminal(terminal_id) {
+            return Err(format!(
+                "terminal '{terminal_id}' not found in project '{}'",
+                req.project_id
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Web client route for a share. The token rides in the fragment so it never
+/// reaches server or proxy logs.
+fn share_path(token: &str, share: &ApiShare) -> String {
+    let mut path = format!("/#share={token}&project={}", share.project_id);
+    if let Some(terminal_id) = &share.terminal_id {
+        path.push_str("&terminal=");
+        path.push_str(terminal_id);
+    }
+    path
+}
+
+/// Base URL viewers should open, e.g. `https://192.168.1.20:19100`.
+///
+/// Prefers a non-loopback bind address; a wildcard bind resolves to the
+/// host's LAN address. Loopback-only servers produce a same-host URL.
+pub(crate) fn share_origin(bind_addrs: &[IpAddr], port: u16, tls_enabled: bool) -> String {
+    let host = bind_addrs
+        .iter()
+        .find(|addr| !addr.is_loopback() && !addr.is_unspecified())
+        .copied()
+        .or_else(|| {
+            bind_addrs
+                .iter()
+                .any(|addr| addr.is_unspecified())
+                .then(lan_ip)
+                .flatten()
+        });
+    // Loopback TLS listeners also accept plain http (see `server.rs`).
+    let (scheme, host) = match host {
+        Some(host) if tls_enabled => ("https", host),
+        Some(host) => ("http", host),
+        None => ("http", IpAddr::V4(Ipv4Addr::LOCALHOST)),
+    };
+    match host {
+        IpAddr::V4(v4) => format!("{scheme}://{v4}:{port}"),
+        IpAddr::V6(v6) => format!("{scheme}://[{v6}]:{port}"),
+    }
+}
+
+/// The address the OS would route outbound traffic from. Connecting a UDP
+/// socket sends nothing; it only picks a source address.
+fn lan_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((Ipv4Addr::new(192, 0, 2, 1), 9)).ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_loopback() && !ip.is_unspecified()).then_some(ip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn share_origin_prefers_routable_bind_address() {
+        let addrs = [
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5)),
+        ];
+        assert_eq!(share_origin(&addrs, 19100, true), "https://10.0.0.5:19100");
+        assert_eq!(share_origin(&addrs, 19100, false), "http://10.0.0.5:19100");
+    }
+
+    #[test]
+    fn loopback_share_origin_uses_plain_http() {
+        let addrs = [IpAddr::V4(Ipv4Addr::LOCALHOST)];
+        assert_eq!(share_origin(&addrs, 19100, true), "http://127.0.0.1:19100");
+    }
+
+    #[test]
+    fn share_path_carries_token_in_fragment() {
+        let share = ApiShare {
+            id: "s1".into(),
+            project_id: "p1".into(),
+            terminal_id: Some("t1".into()),
+            expires_at: 0,
+            viewers: 0,
+        };
+        assert_eq!(
+            share_path("tok", &share),
+            "/#share=tok&project=p1&terminal=t1"
+        );
+    }
+}
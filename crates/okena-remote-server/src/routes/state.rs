@@ -1,11 +1,18 @@
 use crate::bridge::{BridgeMessage, CommandResult, RemoteCommand};
-use crate::routes::AppState;
+use crate::routes::{AppState, shares};
 use axum::Json;
-use axum::extract::State;
+use axum::extract::{Extension, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
+use okena_core::api::StateResponse;
+use okena_core::scope::{ProjectAccess, TokenGrant};
 
-pub async fn get_state(State(state): State<AppState>) -> impl IntoResponse {
+/// `GET /v1/state`. A project-restricted token only sees its projects; only
+/// unrestricted admin tokens see the live share links.
+pub async fn get_state(
+    State(state): State<AppState>,
+    Extension(grant): Extension<TokenGrant>,
+) -> impl IntoResponse {
     let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
 
     let msg = BridgeMessage {
@@ -22,7 +29,28 @@ pub async fn get_state(State(state): State<AppState>) -> impl IntoResponse {
     }
 
     match reply_rx.await {
-        Ok(CommandResult::Ok(Some(value))) => (StatusCode::OK, Json(value)).into_response(),
+        Ok(CommandResult::Ok(Some(value))) if grant.is_project_restricted() => {
+            match serde_json::from_value::<StateResponse>(value) {
+                Ok(mut snapshot) => {
+                    ProjectAccess::new(&grant, &snapshot).filter_state(&mut snapshot);
+                    (StatusCode::OK, Json(snapshot)).into_response()
+                }
+                Err(e) => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({"error": format!("invalid state: {e}")})),
+                )
+                    .into_response(),
+            }
+        }
+        Ok(CommandResult::Ok(Some(mut value))) => {
+            if let Some(object) = value.as_object_mut() {
+                object.insert(
+                    "shares".to_string(),
+                    serde_json::json!(shares::visible(&state, &grant)),
+                );
+            }
+            (StatusCode::OK, Json(value)).into_response()
+        }
         Ok(CommandResult::Err(e)) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(serde_json::json!({"error": e})),
@@ -3,19 +3,24 @@
 #![allow(clippy::expect_used)]
 
 use crate::bridge::{BridgeMessage, CommandResult, RemoteCommand};
-use crate::routes::{AppState, PeerInfo};
+use crate::routes::audit::{self, Caller};
+use crate::routes::{AppState, PeerInfo, events, shares};
 use crate::types::{
-    ActionRequest, ApiSystemStats, FRAME_TYPE_INPUT, FRAME_TYPE_SNAPSHOT, WsInbound, WsOutbound,
-    build_binary_frame, build_pty_frame, parse_binary_frame,
+    ActionRequest, ApiSystemStats, FRAME_TYPE_INPUT, FRAME_TYPE_SNAPSHOT, FrameDeflater,
+    WsCompression, WsInbound, WsOutbound, build_binary_frame, build_pty_frame, parse_binary_frame,
 };
-use axum::extract::ws::{Message, WebSocket};
+use axum::extract::ws::{Message, Utf8Bytes, WebSocket};
 use axum::extract::{Extension, Query, State, WebSocketUpgrade};
 use axum::response::IntoResponse;
 use futures::{SinkExt, StreamExt};
+use okena_core::api::StateResponse;
+use okena_core::audit::{INPUT_BURST_GAP, InputBursts};
 use okena_core::git_poll::GitPollTrigger;
-use std::collections::HashMap;
+use okena_core::scope::{ProjectAccess, Scope, TokenGrant};
+use okena_core::state_delta;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::Ordering;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use sysinfo::System;
 use tokio::sync::{broadcast, mpsc};
 
@@ -69,9 +74,445 @@ impl SystemStatsCache {
     }
 }
 
+/// The connection's token grant. A project-restricted grant keeps the
+/// project map of the state version it was built from and rebuilds it after
+/// the state moves on.
+struct ConnectionGrant {
+    grant: TokenGrant,
+    access: Option<(u64, ProjectAccess)>,
+}
+
+impl ConnectionGrant {
+    fn new(grant: TokenGrant) -> Self {
+        Self {
+            grant,
+            access: None,
+        }
+    }
+
+    async fn access(&mut self, state: &AppState) -> Option<&ProjectAccess> {
+        let version = *state.state_version.borrow();
+        if self
+            .access
+            .as_ref()
+            .is_none_or(|(seen, _)| *seen != version)
+        {
+            let snapshot = events::fetch_state(&state.bridge_tx).await.ok()?;
+            self.access = Some((version, ProjectAccess::new(&self.grant, &snapshot)));
+        }
+        self.access.as_ref().map(|(_, access)| access)
+    }
+
+    async fn allows_terminal(&mut self, state: &AppState, scope: Scope, terminal_id: &str) -> bool {
+        if !self.grant.allows(scope) {
+            return false;
+        }
+        if !self.grant.is_project_restricted() {
+            return true;
+        }
+        self.access(state)
+            .await
+            .is_some_and(|access| access.allows_terminal(terminal_id))
+    }
+
+    /// Drop the git statuses of projects the grant can't see.
+    async fn filter_git_statuses<T>(
+        &mut self,
+        state: &AppState,
+        statuses: &mut HashMap<String, T>,
+    ) {
+        if !self.grant.is_project_restricted() {
+            return;
+        }
+        match self.access(state).await {
+            Some(access) => statuses.retain(|project_id, _| access.allows_project(project_id)),
+            None => statuses.clear(),
+        }
+    }
+
+    /// Drop the project ids the grant can't see.
+    async fn filter_project_ids(&mut self, state: &AppState, project_ids: &mut Vec<String>) {
+        if !self.grant.is_project_restricted() {
+            return;
+        }
+        match self.access(state).await {
+            Some(access) => project_ids.retain(|project_id| access.allows_project(project_id)),
+            None => project_ids.clear(),
+        }
+    }
+}
+
+/// Logs the connection's input as one audit entry per burst. Connections
+/// without a [`Caller`] (the Unix socket) aren't logged.
+struct InputAudit {
+    caller: Option<Caller>,
+    bursts: InputBursts,
+}
+
+impl InputAudit {
+    fn input(&mut self, state: &AppState, terminal_id: &str, bytes: usize) {
+        let Some(caller) = &self.caller else {
+            return;
+        };
+        if let Some(burst) = self.bursts.push(terminal_id, bytes, Instant::now()) {
+            audit::record(state, caller, "ws_input", burst.summary(), false);
+        }
+    }
+
+    /// Log the current burst once it has gone quiet, or unconditionally when
+    /// the connection closes.
+    fn flush(&mut self, state: &AppState, closing: bool) {
+        let Some(caller) = &self.caller else {
+            return;
+        };
+        let burst = if closing {
+            self.bursts.finish()
+        } else {
+            self.bursts.take_idle(Instant::now())
+        };
+        if let Some(burst) = burst {
+            audit::record(state, caller, "ws_input", burst.summary(), false);
+        }
+    }
+}
+
+/// State-change announcements for one connection. With deltas negotiated
+/// (`?state_deltas=1`) it tracks the state the client was last brought to and
+/// sends what changed since; otherwise, or when the state can't be fetched,
+/// a bare `state_changed` that makes the client refetch.
+struct StateAnnouncer {
+    deltas: bool,
+    /// Version the client was last brought to, for grants that share deltas.
+    version: Option<u64>,
+    /// Project-restricted grants see a filtered state of their own, so they
+    /// keep their own baseline; the rest share [`SharedStateDeltas`].
+    baseline: Option<StateResponse>,
+}
+
+impl StateAnnouncer {
+    async fn new(state: &AppState, grant: &TokenGrant, deltas: bool) -> Self {
+        let mut announcer = Self {
+            deltas,
+            version: None,
+            baseline: None,
+        };
+        if !deltas {
+            return announcer;
+        }
+        if grant.is_project_restricted() {
+            announcer.baseline = visible_state(state, grant).await;
+        } else {
+            let current = *state.state_version.borrow();
+            announcer.version = state
+                .state_deltas
+                .advance(state, grant, current)
+                .await
+                .map(|(version, _)| version);
+        }
+        announcer
+    }
+
+    /// The message for a change to `current`, or `None` when the client
+    /// already holds the latest state.
+    async fn announce(
+        &mut self,
+        state: &AppState,
+        grant: &TokenGrant,
+        current: u64,
+    ) -> Option<Utf8Bytes> {
+        if !self.deltas {
+            return Some(state_changed(current));
+        }
+        if grant.is_project_restricted() {
+            return self.announce_own(state, grant, current).await;
+        }
+        let Some((version, last)) = state.state_deltas.advance(state, grant, current).await else {
+            return Some(state_changed(current));
+        };
+        match self.version.replace(version) {
+            Some(previous) if previous == version => None,
+            // A client that skipped a version can't apply the shared delta.
+            Some(previous) => match last {
+                Some((base_version, message)) if base_version == previous => Some(message),
+                _ => Some(state_changed(version)),
+            },
+            None => Some(state_changed(version)),
+        }
+    }
+
+    async fn announce_own(
+        &mut self,
+        state: &AppState,
+        grant: &TokenGrant,
+        current: u64,
+    ) -> Option<Utf8Bytes> {
+        let Some(next) = visible_state(state, grant).await else {
+            return Some(state_changed(current));
+        };
+        let previous = self.baseline.replace(next);
+        let next = self.baseline.as_ref()?;
+        match previous {
+            Some(previous) if previous.state_version == next.state_version => None,
+            Some(previous) => Some(encode(&WsOutbound::StateDelta {
+                base_version: previous.state_version,
+                state_version: next.state_version,
+                delta: state_delta::diff(&previous, next),
+            })),
+            None => Some(state_changed(next.state_version)),
+        }
+    }
+}
+
+/// The latest state of the two views unrestricted grants get (with shares
+/// for admins, without for the rest) and the delta message that reached it,
+/// so a version bump fetches, diffs and serializes the state once rather
+/// than once per connection.
+#[derive(Default)]
+pub struct SharedStateDeltas {
+    views: tokio::sync::Mutex<HashMap<bool, SharedView>>,
+}
+
+struct SharedView {
+    state: StateResponse,
+    /// Base version and serialized `state_delta` message that brought the
+    /// view to `state`.
+    last: Option<(u64, Utf8Bytes)>,
+}
+
+impl SharedStateDeltas {
+    /// Bring `grant`'s view up to `current` if it is behind, and return its
+    /// version with the last delta. `None` when the state can't be fetched.
+    async fn advance(
+        &self,
+        state: &AppState,
+        grant: &TokenGrant,
+        current: u64,
+    ) -> Option<(u64, Option<(u64, Utf8Bytes)>)> {
+        let key = grant.allows(Scope::Admin);
+        // Held across the fetch so concurrent connections wait for it
+        // instead of fetching the same version again.
+        let mut views = self.views.lock().await;
+        match views.get_mut(&key) {
+            Some(view) if view.state.state_version >= current => {}
+            Some(view) => {
+                let next = visible_state(state, grant).await?;
+                if next.state_version != view.state.state_version {
+                    let message = encode(&WsOutbound::StateDelta {
+                        base_version: view.state.state_version,
+                        state_version: next.state_version,
+                        delta: state_delta::diff(&view.state, &next),
+                    });
+                    view.last = Some((view.state.state_version, message));
+                    view.state = next;
+                }
+            }
+            None => {
+                let next = visible_state(state, grant).await?;
+                views.insert(
+                    key,
+                    SharedView {
+                        state: next,
+                        last: None,
+                    },
+                );
+            }
+        }
+        let view = views.get(&key)?;
+        Some((view.state.state_version, view.last.clone()))
+    }
+}
+
+fn state_changed(state_version: u64) -> Utf8Bytes {
+    encode(&WsOutbound::StateChanged { state_version })
+}
+
+fn encode(message: &WsOutbound) -> Utf8Bytes {
+    serde_json::to_string(message)
+        .expect("BUG: WsOutbound must serialize")
+        .into()
+}
+
+/// The workspace state as `grant` sees it, the same as `GET /v1/state`.
+async fn visible_state(state: &AppState, grant: &TokenGrant) -> Option<StateResponse> {
+    let mut snapshot = events::fetch_state(&state.bridge_tx).await.ok()?;
+    if grant.is_project_restricted() {
+        ProjectAccess::new(grant, &snapshot).filter_state(&mut snapshot);
+    }
+    shares::attach(state, grant, &mut snapshot);
+    Some(snapshot)
+}
+
+/// Slowest pace a client may ask for, so a background terminal never looks
+/// frozen for long once it is brought back.
+const MAX_OUTPUT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Held output past this size goes out early instead of growing behind a
+/// slow pace.
+const MAX_HELD_OUTPUT: usize = 256 * 1024;
+
+/// Output held back for the terminals a client asked to receive coalesced
+/// ([`WsInbound::SetOutputRate`]), keyed by stream id.
+#[derive(Default)]
+struct OutputPacer {
+    interval: Duration,
+    paced: HashSet<String>,
+    held: HashMap<u32, HeldOutput>,
+}
+
+struct HeldOutput {
+    data: Vec<u8>,
+    due: tokio::time::Instant,
+}
+
+impl OutputPacer {
+    /// Replace the paced set, returning the held output of terminals that
+    /// left it.
+    fn set_rate(
+        &mut self,
+        terminal_ids: Vec<String>,
+        interval_ms: u32,
+        subscribed_ids: &HashMap<String, u32>,
+    ) -> Vec<(u32, Vec<u8>)> {
+        self.interval = Duration::from_millis(interval_ms.into()).min(MAX_OUTPUT_INTERVAL);
+        self.paced = if self.interval.is_zero() {
+            HashSet::new()
+        } else {
+            terminal_ids.into_iter().collect()
+        };
+        let still_paced: HashSet<u32> = self
+            .paced
+            .iter()
+            .filter_map(|id| subscribed_ids.get(id).copied())
+            .collect();
+        let released: Vec<u32> = self
+            .held
+            .keys()
+            .filter(|stream_id| !still_paced.contains(stream_id))
+            .copied()
+            .collect();
+        released
+            .into_iter()
+            .filter_map(|stream_id| self.take(stream_id).map(|data| (stream_id, data)))
+            .collect()
+    }
+
+    /// Move the output of paced terminals out of `batch` and into the hold.
+    fn hold(
+        &mut self,
+        batch: &mut HashMap<u32, Vec<u8>>,
+        reverse_stream_map: &HashMap<u32, String>,
+    ) {
+        if self.paced.is_empty() {
+            return;
+        }
+        let now = tokio::time::Instant::now();
+        batch.retain(|stream_id, data| {
+            let paced = reverse_stream_map
+                .get(stream_id)
+                .is_some_and(|terminal_id| self.paced.contains(terminal_id));
+            if !paced {
+                return true;
+            }
+            let held = self.held.entry(*stream_id).or_insert_with(|| HeldOutput {
+                data: Vec::new(),
+                due: now + self.interval,
+            });
+            held.data.append(data);
+            if held.data.len() >= MAX_HELD_OUTPUT {
+                held.due = now;
+            }
+            false
+        });
+    }
+
+    fn is_holding(&self) -> bool {
+        !self.held.is_empty()
+    }
+
+    fn next_due(&self) -> Option<tokio::time::Instant> {
+        self.held.values().map(|held| held.due).min()
+    }
+
+    /// Held output whose interval is up.
+    fn take_due(&mut self) -> Vec<(u32, Vec<u8>)> {
+        let now = tokio::time::Instant::now();
+        let due: Vec<u32> = self
+            .held
+            .iter()
+            .filter(|(_, held)| held.due <= now)
+            .map(|(stream_id, _)| *stream_id)
+            .collect();
+        due.into_iter()
+            .filter_map(|stream_id| self.take(stream_id).map(|data| (stream_id, data)))
+            .collect()
+    }
+
+    fn take(&mut self, stream_id: u32) -> Option<Vec<u8>> {
+        self.held.remove(&stream_id).map(|held| held.data)
+    }
+
+    /// Forget held output a fresh snapshot already shows.
+    fn discard(&mut self, stream_ids: impl IntoIterator<Item = u32>) {
+        for stream_id in stream_ids {
+            self.held.remove(&stream_id);
+        }
+    }
+}
+
+async fn send_pty_frames(
+    out_tx: &mpsc::Sender<Message>,
+    frames: Vec<(u32, Vec<u8>)>,
+) -> Result<(), ()> {
+    for (stream_id, data) in frames {
+        let frame = build_pty_frame(stream_id, &data);
+        if out_tx.send(Message::Binary(frame.into())).await.is_err() {
+            return Err(());
+        }
+    }
+    Ok(())
+}
+
+/// A connection opened with a share link.
+struct Spectator {
+    share_id: String,
+    deadline: tokio::time::Instant,
+}
+
+impl Spectator {
+    fn new(share_id: String, expires_at: std::time::SystemTime) -> Self {
+        let remaining = expires_at
+            .duration_since(std::time::SystemTime::now())
+            .unwrap_or_default();
+        Self {
+            share_id,
+            deadline: tokio::time::Instant::now() + remaining,
+        }
+    }
+}
+
+/// Sent to a share viewer right before its connection closes.
+fn share_ended_message(reason: &str) -> Message {
+    let resp = serde_json::to_string(&WsOutbound::AuthFailed {
+        error: reason.into(),
+    })
+    .expect("BUG: WsOutbound must serialize");
+    Message::Text(resp.into())
+}
+
+/// Reply for input the connection's grant doesn't cover.
+fn denied_message() -> Message {
+    let resp = serde_json::to_string(&WsOutbound::Error {
+        error: "token is not allowed to send input to this terminal".into(),
+    })
+    .expect("BUG: WsOutbound must serialize");
+    Message::Text(resp.into())
+}
+
 #[derive(serde::Deserialize)]
 pub struct WsQuery {
     pub token: Option<String>,
+    /// `1` asks for `state_delta` messages; see [`okena_core::state_delta`].
+    pub state_deltas: Option<u8>,
 }
 
 pub async fn ws_handler(
@@ -80,46 +521,77 @@ pub async fn ws_handler(
     Extension(peer): Extension<PeerInfo>,
     ws: WebSocketUpgrade,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_ws(socket, state, query.token, peer))
+    let state_deltas = query.state_deltas == Some(1);
+    ws.on_upgrade(move |socket| handle_ws(socket, state, query.token, state_deltas, peer))
 }
 
 async fn handle_ws(
     mut socket: WebSocket,
     state: AppState,
     query_token: Option<String>,
+    state_deltas: bool,
     peer: PeerInfo,
 ) {
     // ── Auth phase ──────────────────────────────────────────────────────
     // Unix socket clients are same-user local clients; bearer auth is for TCP.
+    let identify = |token: &str| {
+        state.auth_store.authenticate(token).map(|token| {
+            let caller = match peer {
+                PeerInfo::Tcp(addr) => Some(Caller::new(&token, addr.ip())),
+                PeerInfo::Local => None,
+            };
+            let spectator = token
+                .share_expires_at
+                .map(|expires_at| Spectator::new(token.id.clone(), expires_at));
+            (token.grant, caller, spectator)
+        })
+    };
+    // Compression is only negotiated in the auth message: local clients
+    // gain nothing from it, and query-token clients never send one.
+    let mut compression = None;
     let authenticated = if matches!(peer, PeerInfo::Local) {
-        true
+        Some((TokenGrant::default(), None, None))
     } else if let Some(token) = query_token {
-        state.auth_store.validate_token(&token)
+        identify(&token)
     } else {
         // Wait for first-message auth (2 second timeout)
         match tokio::time::timeout(std::time::Duration::from_secs(2), socket.recv()).await {
             Ok(Some(Ok(Message::Text(text)))) => {
-                if let Ok(WsInbound::Auth { token }) = serde_json::from_str::<WsInbound>(&text) {
-                    state.auth_store.validate_token(&token)
+                if let Ok(WsInbound::Auth {
+                    token,
+                    compression: offered,
+                }) = serde_json::from_str::<WsInbound>(&text)
+                {
+                    compression = WsCompression::negotiate(&offered);
+                    identify(&token)
                 } else {
-                    false
+                    None
                 }
             }
-            _ => false,
+            _ => None,
         }
     };
 
-    if !authenticated {
+    let Some((grant, caller, spectator)) = authenticated else {
         let msg = serde_json::to_string(&WsOutbound::AuthFailed {
             error: "authentication required".into(),
         })
         .expect("BUG: WsOutbound must serialize");
         let _ = socket.send(Message::Text(msg.into())).await;
         return;
-    }
+    };
+    let mut grant = ConnectionGrant::new(grant);
+    let mut input_audit = InputAudit {
+        caller,
+        bursts: InputBursts::default(),
+    };
 
     // Send auth success
-    let msg = serde_json::to_string(&WsOutbound::AuthOk).expect("BUG: WsOutbound must serialize");
+    let msg = serde_json::to_string(&WsOutbound::AuthOk {
+        compression,
+        output_rate: true,
+    })
+    .expect("BUG: WsOutbound must serialize");
     if socket.send(Message::Text(msg.into())).await.is_err() {
         return;
     }
@@ -130,7 +602,7 @@ async fn handle_ws(
 
     // Writer task: pumps messages from out_rx to the WebSocket sink.
     // Exits when out_rx is closed (reader dropped out_tx) or on write error.
-    let writer_handle = tokio::spawn(ws_writer(ws_write, out_rx));
+    let writer_handle = tokio::spawn(ws_writer(ws_write, out_rx, compression));
 
     // ── Main loop state ─────────────────────────────────────────────────
     let mut pty_rx = state.broadcaster.subscribe();
@@ -138,6 +610,7 @@ async fn handle_ws(
     let mut output_watermarks: HashMap<String, u64> = HashMap::new();
     let mut reverse_stream_map: HashMap<u32, String> = HashMap::new();
     let mut next_stream_id: u32 = 1;
+    let mut pacer = OutputPacer::default();
     let connection_id = state.next_connection_id.fetch_add(1, Ordering::Relaxed);
     let connection_owner_id = connection_id.to_string();
     // Register this live connection (deregistered in the cleanup block below).
@@ -146,9 +619,23 @@ async fn handle_ws(
     state.active_connections.fetch_add(1, Ordering::SeqCst);
     // Record that this daemon has served a client, arming the idle-exit monitor.
     state.had_client.store(true, Ordering::SeqCst);
+    // Share viewers show up as a count on the owner's pane.
+    if let Some(spectator) = &spectator {
+        if let Ok(mut map) = state.spectators.write() {
+            map.insert(connection_id, spectator.share_id.clone());
+        }
+        state.state_version.send_modify(|v| *v += 1);
+    }
+    let mut share_revoked_rx = state.share_revoked.subscribe();
+    let mut share_revoked_open = spectator.is_some();
+    let share_deadline = spectator
+        .as_ref()
+        .map_or_else(tokio::time::Instant::now, |spectator| spectator.deadline);
 
-    // Subscribe to state_version and git status changes
+    // Subscribe to state_version and git status changes. The delta baseline is
+    // taken after subscribing so no change can fall between the two.
     let mut state_rx = state.state_version.subscribe();
+    let mut announcer = StateAnnouncer::new(&state, &grant.grant, state_deltas).await;
     let mut git_rx = state.git_status.subscribe();
     // Subscribe to daemon-originated toasts (fire-and-forget broadcast).
     let mut toast_rx = state.toast_tx.subscribe();
@@ -160,6 +647,8 @@ async fn handle_ws(
     let mut system_stats = SystemStatsCache::new();
     let mut system_stats_interval = tokio::time::interval(SYSTEM_STATS_REFRESH_INTERVAL);
     system_stats_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut audit_flush_interval = tokio::time::interval(INPUT_BURST_GAP);
+    audit_flush_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
     // Pin the writer handle for use in select!
     tokio::pin!(writer_handle);
@@ -173,7 +662,16 @@ async fn handle_ws(
                     Some(Ok(Message::Text(text))) => {
                         let parsed = serde_json::from_str::<WsInbound>(&text);
                         match parsed {
-                            Ok(WsInbound::Subscribe { terminal_ids }) => {
+                            Ok(WsInbound::Subscribe { mut terminal_ids }) => {
+                                if grant.grant.is_project_restricted() {
+                                    let mut allowed = Vec::with_capacity(terminal_ids.len());
+                                    for id in terminal_ids {
+                                        if grant.allows_terminal(&state, Scope::Read, &id).await {
+                                            allowed.push(id);
+                                        }
+                                    }
+                                    terminal_ids = allowed;
+                                }
                                 for id in &terminal_ids {
                                     if !subscribed_ids.contains_key(id) {
                                         let sid = next_stream_id;
@@ -228,6 +726,7 @@ async fn handle_ws(
                                     break;
                                 }
 
+                                pacer.discard(terminal_ids.iter().filter_map(|id| subscribed_ids.get(id).copied()));
                                 let watermarks = match send_snapshots_and_reconcile(
                                     &out_tx,
                                     &state,
@@ -247,6 +746,7 @@ async fn handle_ws(
                                 for id in &terminal_ids {
                                     if let Some(sid) = subscribed_ids.remove(id) {
                                         reverse_stream_map.remove(&sid);
+                                        pacer.discard([sid]);
                                     }
                                     output_watermarks.remove(id);
                                 }
@@ -259,7 +759,8 @@ async fn handle_ws(
                                     }
                                 }
                             }
-                            Ok(WsInbound::SetVisibleProjects { project_ids }) => {
+                            Ok(WsInbound::SetVisibleProjects { mut project_ids }) => {
+                                grant.filter_project_ids(&state, &mut project_ids).await;
                                 // Full replacement set, so a project leaving the
                                 // client's viewport drops out of the `gh` scope.
                                 if let Ok(mut map) = state.remote_visible_projects.write() {
@@ -274,6 +775,13 @@ async fn handle_ws(
                                 }
                             }
                             Ok(WsInbound::SendText { terminal_id, text }) => {
+                                if !grant.allows_terminal(&state, Scope::TerminalInput, &terminal_id).await {
+                                    if out_tx.send(denied_message()).await.is_err() {
+                                        break;
+                                    }
+                                    continue;
+                                }
+                                input_audit.input(&state, &terminal_id, text.len());
                                 let _ = state.bridge_tx.send(BridgeMessage {
                                     command: RemoteCommand::ActionFromConnection {
                                         action: ActionRequest::SendText { terminal_id, text },
@@ -283,6 +791,13 @@ async fn handle_ws(
                                 }).await;
                             }
                             Ok(WsInbound::SendBytes { terminal_id, data }) => {
+                                if !grant.allows_terminal(&state, Scope::TerminalInput, &terminal_id).await {
+                                    if out_tx.send(denied_message()).await.is_err() {
+                                        break;
+                                    }
+                                    continue;
+                                }
+                                input_audit.input(&state, &terminal_id, data.len());
                                 okena_core::latency_probe::daemon_input_received(
                                     &terminal_id,
                                     &data,
@@ -296,6 +811,13 @@ async fn handle_ws(
                                 }).await;
                             }
                             Ok(WsInbound::SendSpecialKey { terminal_id, key }) => {
+                                if !grant.allows_terminal(&state, Scope::TerminalInput, &terminal_id).await {
+                                    if out_tx.send(denied_message()).await.is_err() {
+                                        break;
+                                    }
+                                    continue;
+                                }
+                                input_audit.input(&state, &terminal_id, 0);
                                 let _ = state.bridge_tx.send(BridgeMessage {
                                     command: RemoteCommand::ActionFromConnection {
                                         action: ActionRequest::SendSpecialKey { terminal_id, key },
@@ -305,6 +827,12 @@ async fn handle_ws(
                                 }).await;
                             }
                             Ok(WsInbound::Resize { terminal_id, cols, rows }) => {
+                                if !grant.allows_terminal(&state, Scope::TerminalInput, &terminal_id).await {
+                                    if out_tx.send(denied_message()).await.is_err() {
+                                        break;
+                                    }
+                                    continue;
+                                }
                                 // Ask for a reply: a denied resize carries the
                                 // authoritative size, which we bounce back to
                                 // THIS client as a server-owned resize so it
@@ -341,6 +869,12 @@ async fn handle_ws(
                                     }
                                 }
                             }
+                            Ok(WsInbound::SetOutputRate { terminal_ids, interval_ms }) => {
+                                let released = pacer.set_rate(terminal_ids, interval_ms, &subscribed_ids);
+                                if send_pty_frames(&out_tx, released).await.is_err() {
+                                    break;
+                                }
+                            }
                             Ok(WsInbound::Ping) => {
                                 let resp = serde_json::to_string(&WsOutbound::Pong).expect("BUG: WsOutbound must serialize");
                                 if out_tx.send(Message::Text(resp.into())).await.is_err() {
@@ -361,7 +895,9 @@ async fn handle_ws(
                     Some(Ok(Message::Binary(data))) => {
                         // Binary input frame from client — fire-and-forget
                         if let Some((FRAME_TYPE_INPUT, stream_id, payload)) = parse_binary_frame(&data)
-                            && let Some(terminal_id) = reverse_stream_map.get(&stream_id) {
+                            && let Some(terminal_id) = reverse_stream_map.get(&stream_id)
+                            && grant.allows_terminal(&state, Scope::TerminalInput, terminal_id).await {
+                                input_audit.input(&state, terminal_id, payload.len());
                                 okena_core::latency_probe::daemon_input_received(
                                     terminal_id,
                                     payload,
@@ -459,6 +995,7 @@ async fn handle_ws(
                                     // Batch is stale — clear it and send snapshots instead
                                     batch.clear();
                                     resize_msgs.clear();
+                                    pacer.discard(subscribed_ids.values().copied());
                                     let resp = serde_json::to_string(&WsOutbound::Dropped { count: n })
                                         .expect("BUG: WsOutbound must serialize");
                                     if out_tx.send(Message::Text(resp.into())).await.is_err() {
@@ -494,6 +1031,21 @@ async fn handle_ws(
                             break;
                         }
 
+                        // Held output predates any resize, so it goes out first.
+                        let mut released = Vec::new();
+                        for msg in &resize_msgs {
+                            if let WsOutbound::TerminalResized { terminal_id, .. } = msg
+                                && let Some(&sid) = subscribed_ids.get(terminal_id)
+                                && let Some(data) = pacer.take(sid)
+                            {
+                                released.push((sid, data));
+                            }
+                        }
+                        if send_pty_frames(&out_tx, released).await.is_err() {
+                            break;
+                        }
+                        pacer.hold(&mut batch, &reverse_stream_map);
+
                         // Send resize notifications first (so client updates grid before PTY data)
                         for msg in resize_msgs {
                             let resp = serde_json::to_string(&msg).expect("BUG: WsOutbound must serialize");
@@ -519,6 +1071,7 @@ async fn handle_ws(
                         }
                     }
                     Err(broadcast::error::RecvError::Lagged(n)) => {
+                        pacer.discard(subscribed_ids.values().copied());
                         let resp = serde_json::to_string(&WsOutbound::Dropped { count: n }).expect("BUG: WsOutbound must serialize");
                         if out_tx.send(Message::Text(resp.into())).await.is_err() {
                             break;
@@ -544,14 +1097,21 @@ async fn handle_ws(
                 }
             }
 
+            // Paced output whose interval is up.
+            _ = tokio::time::sleep_until(pacer.next_due().unwrap_or_else(tokio::time::Instant::now)), if pacer.is_holding() => {
+                if send_pty_frames(&out_tx, pacer.take_due()).await.is_err() {
+                    break;
+                }
+            }
+
             // Immediate state version push
             result = state_rx.changed() => {
                 if result.is_ok() {
                     let current = *state_rx.borrow_and_update();
-                    let resp = serde_json::to_string(&WsOutbound::StateChanged {
-                        state_version: current,
-                    }).expect("BUG: WsOutbound must serialize");
-                    if out_tx.send(Message::Text(resp.into())).await.is_err() {
+                    let Some(msg) = announcer.announce(&state, &grant.grant, current).await else {
+                        continue;
+                    };
+                    if out_tx.send(Message::Text(msg)).await.is_err() {
                         break;
                     }
                 } else {
@@ -563,7 +1123,8 @@ async fn handle_ws(
             // Git status changes push
             result = git_rx.changed() => {
                 if result.is_ok() {
-                    let statuses = git_rx.borrow_and_update().clone();
+                    let mut statuses = git_rx.borrow_and_update().clone();
+                    grant.filter_git_statuses(&state, &mut statuses).await;
                     let resp = serde_json::to_string(&WsOutbound::GitStatusChanged {
                         projects: statuses,
                     }).expect("BUG: WsOutbound must serialize");
@@ -585,6 +1146,11 @@ async fn handle_ws(
                 }
             }
 
+            // Log input bursts that have gone quiet.
+            _ = audit_flush_interval.tick() => {
+                input_audit.flush(&state, false);
+            }
+
             // One-shot exact-terminal focus request for connected desktop clients.
             result = terminal_focus_rx.recv(), if terminal_focus_open => {
                 match result {
@@ -627,6 +1193,33 @@ async fn handle_ws(
                 }
             }
 
+            // A share link ends its viewers' connections when it expires...
+            _ = tokio::time::sleep_until(share_deadline), if spectator.is_some() => {
+                let _ = out_tx.send(share_ended_message("share link expired")).await;
+                break;
+            }
+
+            // ...or is revoked.
+            result = share_revoked_rx.recv(), if share_revoked_open => {
+                let Some(spectator) = &spectator else {
+                    continue;
+                };
+                let revoked = match result {
+                    Ok(id) => id == spectator.share_id,
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        !state.auth_store.share_is_live(&spectator.share_id)
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {
+                        share_revoked_open = false;
+                        false
+                    }
+                };
+                if revoked {
+                    let _ = out_tx.send(share_ended_message("share link revoked")).await;
+                    break;
+                }
+            }
+
             // Writer task died (socket write error) — stop the reader too
             _ = &mut writer_handle => {
                 writer_finished = true;
@@ -635,6 +1228,8 @@ async fn handle_ws(
         }
     }
 
+    input_audit.flush(&state, true);
+
     // Cleanup: remove this connection's subscribed terminals from shared state
     if let Ok(mut map) = state.remote_subscribed_terminals.write() {
         map.remove(&connection_id);
@@ -643,6 +1238,12 @@ async fn handle_ws(
     if let Ok(mut map) = state.remote_visible_projects.write() {
         map.remove(&connection_id);
     }
+    if spectator.is_some() {
+        if let Ok(mut map) = state.spectators.write() {
+            map.remove(&connection_id);
+        }
+        state.state_version.send_modify(|v| *v += 1);
+    }
 
     // Deregister the live connection (paired with the fetch_add above).
     let remaining = state
@@ -666,11 +1267,23 @@ async fn handle_ws(
 }
 
 /// Writer task: pumps messages from the mpsc channel to the WebSocket sink.
+/// With compression negotiated it compresses terminal frames here, the one
+/// place that sees them in the order the client will.
 async fn ws_writer(
     mut ws_write: futures::stream::SplitSink<WebSocket, Message>,
     mut out_rx: mpsc::Receiver<Message>,
+    compression: Option<WsCompression>,
 ) {
-    while let Some(msg) = out_rx.recv().await {
+    let mut deflater = match compression {
+        Some(WsCompression::Deflate) => Some(FrameDeflater::new()),
+        Some(WsCompression::Unknown) | None => None,
+    };
+    while let Some(mut msg) = out_rx.recv().await {
+        if let (Some(deflater), Message::Binary(frame)) = (deflater.as_mut(), &msg)
+            && let Some(compressed) = deflater.compress_frame(frame)
+        {
+            msg = Message::Binary(compressed.into());
+        }
         if ws_write.send(msg).await.is_err() {
             break;
         }
@@ -934,6 +1547,45 @@ mod tests {
         assert!(output_is_newer(&watermarks, "terminal", 43));
     }
 
+    #[tokio::test]
+    async fn paced_output_is_held_until_released() {
+        let subscribed = HashMap::from([("bg".to_string(), 1), ("fg".to_string(), 2)]);
+        let reverse: HashMap<u32, String> = subscribed
+            .iter()
+            .map(|(id, sid)| (*sid, id.clone()))
+            .collect();
+        let mut pacer = OutputPacer::default();
+        assert!(
+            pacer
+                .set_rate(vec!["bg".to_string()], 1000, &subscribed)
+                .is_empty()
+        );
+
+        let mut batch = HashMap::from([(1, b"build ".to_vec()), (2, b"prompt".to_vec())]);
+        pacer.hold(&mut batch, &reverse);
+        let mut more = HashMap::from([(1, b"log".to_vec())]);
+        pacer.hold(&mut more, &reverse);
+        assert_eq!(batch.keys().collect::<Vec<_>>(), [&2]);
+        assert!(more.is_empty());
+        assert!(pacer.take_due().is_empty());
+
+        // Leaving the paced set releases what was held, in one frame.
+        let released = pacer.set_rate(Vec::new(), 0, &subscribed);
+        assert_eq!(released, [(1, b"build log".to_vec())]);
+        assert!(!pacer.is_holding());
+    }
+
+    #[tokio::test]
+    async fn oversized_held_output_is_due_at_once() {
+        let subscribed = HashMap::from([("bg".to_string(), 1)]);
+        let reverse = HashMap::from([(1, "bg".to_string())]);
+        let mut pacer = OutputPacer::default();
+        pacer.set_rate(vec!["bg".to_string()], u32::MAX, &subscribed);
+        let mut batch = HashMap::from([(1, vec![b'x'; MAX_HELD_OUTPUT])]);
+        pacer.hold(&mut batch, &reverse);
+        assert_eq!(pacer.take_due().len(), 1);
+    }
+
     #[tokio::test]
     async fn post_snapshot_drain_forwards_only_output_after_watermark() {
         let broadcaster = crate::pty_broadcaster::PtyBroadcaster::new();
@@ -959,4 +1611,55 @@ mod tests {
         assert_eq!(stream_id, 7);
         assert_eq!(payload, b"new");
     }
+
+    #[tokio::test]
+    async fn unrestricted_connections_share_one_state_fetch_per_version() {
+        use std::sync::Arc;
+        use std::sync::atomic::AtomicU64;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = crate::routes::test_state(dir.path());
+        let (bridge_tx, bridge_rx) = crate::bridge::bridge_channel();
+        state.bridge_tx = bridge_tx;
+        let fetches = Arc::new(AtomicU64::new(0));
+        let version = state.state_version.clone();
+        tokio::spawn({
+            let fetches = fetches.clone();
+            async move {
+                while let Ok(msg) = bridge_rx.recv().await {
+                    fetches.fetch_add(1, Ordering::SeqCst);
+                    let snapshot = serde_json::json!({
+                        "state_version": *version.borrow(),
+                        "projects": [],
+                        "focused_project_id": null,
+                        "fullscreen_terminal": null,
+                    });
+                    if let Some(reply) = msg.reply {
+                        let _ = reply.send(CommandResult::Ok(Some(snapshot)));
+                    }
+                }
+            }
+        });
+
+        let grant = TokenGrant::default();
+        let mut first = StateAnnouncer::new(&state, &grant, true).await;
+        let mut second = StateAnnouncer::new(&state, &grant, true).await;
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        state.state_version.send_modify(|v| *v += 1);
+        let a = first.announce(&state, &grant, 1).await.unwrap();
+        let b = second.announce(&state, &grant, 1).await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        assert_eq!(a, b);
+        assert!(a.as_str().contains("\"type\":\"state_delta\""));
+        assert!(first.announce(&state, &grant, 1).await.is_none());
+
+        // A client that missed a version can't use the shared delta.
+        state.state_version.send_modify(|v| *v += 1);
+        first.announce(&state, &grant, 2).await.unwrap();
+        state.state_version.send_modify(|v| *v += 1);
+        first.announce(&state, &grant, 3).await.unwrap();
+        let skipped = second.announce(&state, &grant, 3).await.unwrap();
+        assert!(skipped.as_str().contains("\"type\":\"state_changed\""));
+    }
 }
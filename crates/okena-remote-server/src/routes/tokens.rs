@@ -1,18 +1,49 @@
 use crate::routes::AppState;
+use crate::routes::audit::{self, Caller};
 use axum::Json;
-use axum::extract::{Path, State};
+use axum::extract::{Extension, Path, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
+use okena_core::scope::{Scope, TokenGrant};
 
-pub async fn list_tokens(State(state): State<AppState>) -> impl IntoResponse {
+fn admin_only() -> axum::response::Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(serde_json::json!({ "error": "token lacks the 'admin' scope" })),
+    )
+        .into_response()
+}
+
+pub async fn list_tokens(
+    State(state): State<AppState>,
+    Extension(grant): Extension<TokenGrant>,
+) -> impl IntoResponse {
+    if !grant.allows(Scope::Admin) || grant.is_project_restricted() {
+        return admin_only();
+    }
     let tokens = state.auth_store.list_tokens();
-    Json(serde_json::json!({ "tokens": tokens }))
+    Json(serde_json::json!({ "tokens": tokens })).into_response()
+}
+
+/// `GET /v1/tokens/self`: the caller's own grant, so a client can hide what
+/// its token may not do. Any scope may ask.
+pub async fn own_grant(Extension(grant): Extension<TokenGrant>) -> impl IntoResponse {
+    Json(serde_json::json!({ "grant": grant }))
 }
 
 pub async fn revoke_token(
     State(state): State<AppState>,
+    Extension(grant): Extension<TokenGrant>,
+    Extension(caller): Extension<Option<Caller>>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
+    let denied = !grant.allows(Scope::Admin) || grant.is_project_restricted();
+    if let Some(caller) = &caller {
+        audit::record(&state, caller, "revoke_token", format!("id={id}"), denied);
+    }
+    if denied {
+        return admin_only();
+    }
     if state.auth_store.revoke_token(&id) {
         (StatusCode::OK, Json(serde_json::json!({ "revoked": true }))).into_response()
     } else {
@@ -23,3 +54,52 @@ pub async fn revoke_token(
             .into_response()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::test_state;
+    use std::collections::BTreeSet;
+
+    #[tokio::test]
+    async fn restricted_admins_cannot_manage_tokens() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        let code = state.auth_store.generate_fresh_code();
+        state
+            .auth_store
+            .try_pair(&code, [127, 0, 0, 1].into())
+            .unwrap();
+        let id = state.auth_store.list_tokens()[0].id.clone();
+        let restricted = TokenGrant {
+            scopes: BTreeSet::from([Scope::Admin]),
+            projects: vec!["web".into()],
+            ..TokenGrant::default()
+        };
+
+        let list = list_tokens(State(state.clone()), Extension(restricted.clone()))
+            .await
+            .into_response();
+        assert_eq!(list.status(), StatusCode::FORBIDDEN);
+        let revoke = revoke_token(
+            State(state.clone()),
+            Extension(restricted),
+            Extension(None),
+            Path(id.clone()),
+        )
+        .await
+        .into_response();
+        assert_eq!(revoke.status(), StatusCode::FORBIDDEN);
+        assert_eq!(state.auth_store.list_tokens().len(), 1);
+
+        let revoke = revoke_token(
+            State(state.clone()),
+            Extension(TokenGrant::default()),
+            Extension(None),
+            Path(id),
+        )
+        .await
+        .into_response();
+        assert_eq!(revoke.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,414 @@
+//! Stream a file from a remote client into a project or scoped directory.
+//!
+//! The target directory is resolved with the same `ResolveProjectPath` /
+//! `ResolvePathInScope` actions downloads use, so the token's `files` scope
+//! and the daemon's path checks apply unchanged. Only the final name is
+//! joined here, after it has been checked to be a single path component.
+
+use crate::routes::audit::{self, Caller};
+use crate::routes::{AppState, download};
+use axum::Json;
+use axum::body::Body;
+use axum::extract::{Extension, Query, State};
+use axum::http::header::CONTENT_LENGTH;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use futures::{Stream, StreamExt};
+use okena_core::api::{
+    ActionRequest, FileUploadRequest, FileUploadResponse, ResolvedPathKind, UploadConflict,
+};
+use okena_core::scope::TokenGrant;
+use std::fmt::Display;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::io::AsyncWriteExt;
+
+/// Max accepted upload (256 MiB). The body is streamed to disk, so this bounds
+/// disk use rather than memory.
+pub const UPLOAD_LIMIT: u64 = 256 * 1024 * 1024;
+
+/// Candidates `UploadConflict::Rename` tries before giving up.
+const MAX_RENAME_ATTEMPTS: u32 = 1000;
+
+pub async fn post_upload(
+    State(state): State<AppState>,
+    Extension(grant): Extension<TokenGrant>,
+    Extension(caller): Extension<Option<Caller>>,
+    Query(request): Query<FileUploadRequest>,
+    headers: HeaderMap,
+    body: Body,
+) -> Response {
+    let FileUploadRequest {
+        project_id,
+        root,
+        relative_path,
+        conflict,
+    } = request;
+    let (dir, name) = relative_path
+        .rsplit_once('/')
+        .unwrap_or(("", relative_path.as_str()));
+    if !is_file_name(name) {
+        return error(
+            StatusCode::BAD_REQUEST,
+            "relative_path must end in a file name",
+        );
+    }
+    let (target, action) = match (project_id, root) {
+        (Some(project_id), None) => (
+            format!("project_id={project_id}"),
+            ActionRequest::ResolveProjectPath {
+                project_id,
+                relative_path: dir.to_string(),
+            },
+        ),
+        (None, Some(root)) => (
+            format!("root={root}"),
+            ActionRequest::ResolvePathInScope {
+                root,
+                relative_path: dir.to_string(),
+            },
+        ),
+        _ => {
+            return error(
+                StatusCode::BAD_REQUEST,
+                "exactly one of project_id and root is required",
+            );
+        }
+    };
+
+    let resolved = download::resolve_path(&state, &grant, action).await;
+    if let Some(caller) = &caller {
+        let denied = resolved
+            .as_ref()
+            .is_err_and(|response| response.status() == StatusCode::FORBIDDEN);
+        let summary = format!("{target} relative_path={relative_path} conflict={conflict:?}");
+        audit::record(&state, caller, "upload", summary, denied);
+    }
+    let dir = match resolved {
+        Ok(dir) => dir,
+        Err(response) => return response,
+    };
+    if dir.kind != ResolvedPathKind::Directory {
+        return error(StatusCode::BAD_REQUEST, "parent path is not a directory");
+    }
+    let declared = headers
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    if declared.is_some_and(|length| length > UPLOAD_LIMIT) {
+        return error(StatusCode::PAYLOAD_TOO_LARGE, too_large());
+    }
+
+    let chunks = body.into_data_stream();
+    match write_upload(
+        Path::new(&dir.canonical_path),
+        name,
+        conflict,
+        chunks,
+        UPLOAD_LIMIT,
+    )
+    .await
+    {
+        Ok((path, size)) => {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            Json(FileUploadResponse {
+                path: path.to_string_lossy().into_owned(),
+                name,
+                size,
+            })
+            .into_response()
+        }
+        Err(UploadError::Exists) => error(
+            StatusCode::CONFLICT,
+            format!("{name} already exists; retry with conflict=rename or conflict=overwrite"),
+        ),
+        Err(UploadError::TooLarge) => error(StatusCode::PAYLOAD_TOO_LARGE, too_large()),
+        Err(UploadError::Body(message)) => error(
+            StatusCode::BAD_REQUEST,
+            format!("upload interrupted: {message}"),
+        ),
+        Err(UploadError::Io(e)) => {
+            log::error!("upload into {} failed: {e}", dir.canonical_path);
+            error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("failed to write file: {e}"),
+            )
+        }
+    }
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(serde_json::json!({"error": message.into()}))).into_response()
+}
+
+fn too_large() -> String {
+    format!(
+        "upload exceeds the {} MiB limit",
+        UPLOAD_LIMIT / (1024 * 1024)
+    )
+}
+
+/// A single, ordinary path component: nothing that could climb out of or
+/// alias the resolved directory. Names Windows would open as a device, as an
+/// alternate data stream or under a trimmed name are refused on every host.
+fn is_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.contains(['/', '\\', '\0', ':'])
+        && !name.ends_with(['.', ' '])
+        && !is_reserved_windows_name(name)
+}
+
+/// `CON`, `NUL`, `COM1`, `lpt9.txt` and the like, with or without an
+/// extension.
+fn is_reserved_windows_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).to_ascii_uppercase();
+    match stem.as_str() {
+        "CON" | "PRN" | "AUX" | "NUL" => true,
+        _ => stem
+            .strip_prefix("COM")
+            .or_else(|| stem.strip_prefix("LPT"))
+            .is_some_and(|n| n.len() == 1 && matches!(n.as_bytes()[0], b'1'..=b'9')),
+    }
+}
+
+#[derive(Debug)]
+enum UploadError {
+    /// The target exists and the conflict policy is `Fail`.
+    Exists,
+    TooLarge,
+    /// The client's body stream failed.
+    Body(String),
+    Io(io::Error),
+}
+
+impl From<io::Error> for UploadError {
+    fn from(e: io::Error) -> Self {
+        UploadError::Io(e)
+    }
+}
+
+/// Write `chunks` to `dir/name`, returning the final path and size.
+///
+/// The file is always created with `create_new`, so an existing entry
+/// (symlinks included) is never written through. `Overwrite` streams into a
+/// hidden sibling and renames it over the target once complete; the other
+/// policies claim the target up front so a conflict is reported before any
+/// of the body is read. Partial files are removed on failure.
+async fn write_upload<S, B, E>(
+    dir: &Path,
+    name: &str,
+    conflict: UploadConflict,
+    mut chunks: S,
+    limit: u64,
+) -> Result<(PathBuf, u64), UploadError>
+where
+    S: Stream<Item = Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+    E: Display,
+{
+    let (mut file, written_to) = match conflict {
+        UploadConflict::Fail => {
+            let path = dir.join(name);
+            match create_new(&path).await {
+                Ok(file) => (file, path),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    return Err(UploadError::Exists);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        UploadConflict::Rename => claim_free_name(dir, name).await?,
+        UploadConflict::Overwrite => {
+            let path = dir.join(format!(".{name}.okena-upload-{}", next_token()));
+            (create_new(&path).await?, path)
+        }
+    };
+
+    let mut size = 0u64;
+    let copied: Result<(), UploadError> = async {
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk.map_err(|e| UploadError::Body(e.to_string()))?;
+            let chunk = chunk.as_ref();
+            size += chunk.len() as u64;
+            if size > limit {
+                return Err(UploadError::TooLarge);
+            }
+            file.write_all(chunk).await?;
+        }
+        file.flush().await?;
+        Ok(())
+    }
+    .await;
+    drop(file);
+    if let Err(e) = copied {
+        let _ = tokio::fs::remove_file(&written_to).await;
+        return Err(e);
+    }
+
+    if conflict != UploadConflict::Overwrite {
+        return Ok((written_to, size));
+    }
+    let target = dir.join(name);
+    if let Err(e) = tokio::fs::rename(&written_to, &target).await {
+        let _ = tokio::fs::remove_file(&written_to).await;
+        return Err(e.into());
+    }
+    Ok((target, size))
+}
+
+async fn create_new(path: &Path) -> io::Result<tokio::fs::File> {
+    tokio::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .await
+}
+
+/// Create the first of `name`, `name (1)`, `name (2)`, ... that is free.
+async fn claim_free_name(
+    dir: &Path,
+    name: &str,
+) -> Result<(tokio::fs::File, PathBuf), UploadError> {
+    for attempt in 0..MAX_RENAME_ATTEMPTS {
+        let path = dir.join(numbered_name(name, attempt));
+        match create_new(&path).await {
+            Ok(file) => return Ok((file, path)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(UploadError::Exists)
+}
+
+/// `report.txt` -> `report (2).txt`. Dotfiles and extensionless names get the
+/// suffix at the end: `.env (2)`, `Makefile (2)`.
+fn numbered_name(name: &str, n: u32) -> String {
+    if n == 0 {
+        return name.to_string();
+    }
+    match name.rfind('.') {
+        Some(dot) if dot > 0 => format!("{} ({n}){}", &name[..dot], &name[dot..]),
+        _ => format!("{name} ({n})"),
+    }
+}
+
+/// Unique suffix for `Overwrite` staging files, so two uploads of the same
+/// name never share one.
+fn next_token() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{}-{n:x}", std::process::id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(chunks: &[&'static [u8]]) -> impl Stream<Item = Result<&'static [u8], String>> + Unpin {
+        futures::stream::iter(chunks.iter().map(|chunk| Ok(*chunk)).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn file_names_are_single_components() {
+        assert!(is_file_name("notes.md"));
+        assert!(is_file_name(".env"));
+        assert!(!is_file_name(""));
+        assert!(!is_file_name(".."));
+        assert!(!is_file_name("a\\b"));
+        assert!(!is_file_name("a\0b"));
+    }
+
+    #[test]
+    fn windows_aliasing_names_are_refused() {
+        for name in [
+            "a:b",
+            "notes.md:stream",
+            "CON",
+            "con.txt",
+            "Nul",
+            "aux.tar.gz",
+            "PRN",
+            "COM1",
+            "lpt9.log",
+            "notes.",
+            "notes ",
+        ] {
+            assert!(!is_file_name(name), "{name}");
+        }
+        for name in ["console.log", "COM0", "COM10", "lpt", "nul_device", "a.b"] {
+            assert!(is_file_name(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn numbered_names_keep_the_extension() {
+        assert_eq!(numbered_name("log.txt", 0), "log.txt");
+        assert_eq!(numbered_name("log.txt", 2), "log (2).txt");
+        assert_eq!(numbered_name(".env", 1), ".env (1)");
+        assert_eq!(numbered_name("Makefile", 1), "Makefile (1)");
+    }
+
+    #[tokio::test]
+    async fn conflict_policies() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.log"), b"old").unwrap();
+
+        let failed = write_upload(
+            dir.path(),
+            "a.log",
+            UploadConflict::Fail,
+            body(&[b"new"]),
+            10,
+        )
+        .await;
+        assert!(matches!(failed, Err(UploadError::Exists)));
+
+        let (renamed, size) = write_upload(
+            dir.path(),
+            "a.log",
+            UploadConflict::Rename,
+            body(&[b"ne", b"w"]),
+            10,
+        )
+        .await
+        .unwrap();
+        assert_eq!((renamed, size), (dir.path().join("a (1).log"), 3));
+
+        let (replaced, _) = write_upload(
+            dir.path(),
+            "a.log",
+            UploadConflict::Overwrite,
+            body(&[b"newer"]),
+            10,
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read(&replaced).unwrap(), b"newer");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn oversized_and_failed_uploads_leave_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let too_large = write_upload(
+            dir.path(),
+            "big.bin",
+            UploadConflict::Overwrite,
+            body(&[b"12345", b"678"]),
+            6,
+        )
+        .await;
+        assert!(matches!(too_large, Err(UploadError::TooLarge)));
+
+        let chunks = futures::stream::iter(vec![Ok(&b"partial"[..]), Err("reset".to_string())]);
+        let broken = write_upload(dir.path(), "cut.bin", UploadConflict::Fail, chunks, 100).await;
+        assert!(matches!(broken, Err(UploadError::Body(_))));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,173 @@
+//! `POST /v1/terminals/{terminal_id}/wait-for` — block until a terminal's
+//! output matches a regex.
+//!
+//! The handler subscribes to the [`PtyBroadcaster`](crate::pty_broadcaster::PtyBroadcaster)
+//! before reading the visible screen, so output produced while the screen read
+//! is in flight is not lost. Matching is done by
+//! [`OutputMatcher`](okena_terminal::output_matcher::OutputMatcher) over the
+//! live byte stream, which catches lines that scroll off before a client could
+//! poll `read_content`. If the subscriber lags and events are dropped, the
+//! terminal's output since the first read is read back and matching resumes
+//! from there.
+
+use crate::bridge::{BridgeMessage, CommandResult, RemoteCommand};
+use crate::pty_broadcaster::PtyBroadcastEvent;
+use crate::routes::{AppState, actions};
+use crate::types::ActionRequest;
+use axum::Json;
+use axum::extract::{Extension, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use okena_core::api::{ApiReadContent, ApiWaitForRequest};
+use okena_core::scope::{Scope, TokenGrant};
+use okena_terminal::output_matcher::OutputMatcher;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(serde_json::json!({"error": message.into()}))).into_response()
+}
+
+/// A failed terminal read: 404 when the terminal is gone, like the daemon's
+/// `terminal not found: <id>`, otherwise 400.
+fn read_error(message: String) -> Response {
+    let status = if message.starts_with("terminal not found") {
+        StatusCode::NOT_FOUND
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+    error(status, message)
+}
+
+pub async fn post_wait_for(
+    State(state): State<AppState>,
+    Extension(grant): Extension<TokenGrant>,
+    Path(terminal_id): Path<String>,
+    Json(request): Json<ApiWaitForRequest>,
+) -> Response {
+    if let Err(response) =
+        actions::authorize_terminal(&state, &grant, Scope::Read, &terminal_id).await
+    {
+        return response;
+    }
+    let mut matcher = match OutputMatcher::new(&request.regex) {
+        Ok(matcher) => matcher,
+        Err(e) => return error(StatusCode::BAD_REQUEST, e),
+    };
+    let timeout = request
+        .timeout_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_TIMEOUT);
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    let mut output_rx = state.broadcaster.subscribe();
+
+    // The screen read doubles as the "terminal exists" check, so it runs even
+    // with `since_now`; only the seeding is skipped.
+    let (screen, sequence) = match read_content(&state, &terminal_id, None).await {
+        Ok(read) => read,
+        Err(e) => return read_error(e),
+    };
+    let screen = if request.since_now {
+        String::new()
+    } else {
+        screen
+    };
+    matcher.seed(&screen);
+    if let Some(found) = matcher.find() {
+        return (StatusCode::OK, Json(found)).into_response();
+    }
+
+    loop {
+        let event = match tokio::time::timeout_at(deadline, output_rx.recv()).await {
+            Ok(event) => event,
+            Err(_) => {
+                return error(
+                    StatusCode::REQUEST_TIMEOUT,
+                    format!("no match within {} ms", timeout.as_millis()),
+                );
+            }
+        };
+        match event {
+            Ok(PtyBroadcastEvent::Output {
+                terminal_id: id,
+                data,
+                ..
+            }) if id == terminal_id => {
+                matcher.feed(&data);
+                if let Some(found) = matcher.find() {
+                    return (StatusCode::OK, Json(found)).into_response();
+                }
+            }
+            Ok(_) => {}
+            Err(RecvError::Lagged(n)) => {
+                log::warn!("wait-for {terminal_id}: lagged, {n} output events skipped");
+                // The match may be in the skipped output; the terminal still
+                // has it.
+                match read_content(&state, &terminal_id, Some(sequence)).await {
+                    Ok((missed, _)) => matcher.resync(&format!("{screen}\n{missed}")),
+                    Err(e) => return read_error(e),
+                }
+                if let Some(found) = matcher.find() {
+                    return (StatusCode::OK, Json(found)).into_response();
+                }
+            }
+            Err(RecvError::Closed) => {
+                return error(StatusCode::SERVICE_UNAVAILABLE, "output stream closed");
+            }
+        }
+    }
+}
+
+/// The visible screen, or with `since` the output after that read, plus the
+/// read's output position.
+async fn read_content(
+    state: &AppState,
+    terminal_id: &str,
+    since: Option<u64>,
+) -> Result<(String, u64), String> {
+    let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+    let msg = BridgeMessage {
+        command: RemoteCommand::Action(ActionRequest::ReadContent {
+            terminal_id: terminal_id.to_string(),
+            lines: None,
+            all: false,
+            ansi: false,
+            since,
+            strip_prompt: false,
+        }),
+        reply: Some(reply_tx),
+    };
+    state
+        .bridge_tx
+        .send(msg)
+        .await
+        .map_err(|_| "bridge unavailable".to_string())?;
+    match reply_rx.await {
+        Ok(CommandResult::Ok(value)) => {
+            let read: ApiReadContent = value
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| format!("unexpected response: {e}"))?
+                .ok_or_else(|| "unexpected response".to_string())?;
+            Ok((read.content, read.sequence))
+        }
+        Ok(CommandResult::Err(e)) => Err(e),
+        _ => Err("unexpected response".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_terminals_are_not_found() {
+        let missing = read_error("terminal not found: t1".to_string());
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        let other = read_error("bridge unavailable".to_string());
+        assert_eq!(other.status(), StatusCode::BAD_REQUEST);
+    }
+}
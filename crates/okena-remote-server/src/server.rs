@@ -3,6 +3,7 @@ use crate::bridge::BridgeSender;
 use crate::pty_broadcaster::PtyBroadcaster;
 use crate::routes;
 use okena_core::api::{ApiGitStatus, ApiTerminalFocusRequest, ApiToast};
+use okena_core::events::ApiEvent;
 use okena_core::git_poll::GitPollTrigger;
 use okena_transport::client::LocalEndpoint;
 use std::collections::{HashMap, HashSet};
@@ -41,6 +42,7 @@ impl RemoteServer {
         git_status: Arc<watch::Sender<HashMap<String, ApiGitStatus>>>,
         toast_tx: Arc<tokio::sync::broadcast::Sender<ApiToast>>,
         terminal_focus_tx: Arc<tokio::sync::broadcast::Sender<ApiTerminalFocusRequest>>,
+        events_tx: Arc<tokio::sync::broadcast::Sender<ApiEvent>>,
         remote_subscribed_terminals: Arc<RwLock<HashMap<u64, HashSet<String>>>>,
         remote_visible_projects: Arc<RwLock<HashMap<u64, HashSet<String>>>>,
         git_poll_trigger_tx: Option<tokio::sync::mpsc::UnboundedSender<GitPollTrigger>>,
@@ -174,6 +176,8 @@ impl RemoteServer {
         // freshly-spawned daemon isn't reaped before its GUI first connects.
         let had_client = Arc::new(std::sync::atomic::AtomicBool::new(false));
 
+        let share_origin = routes::shares::share_origin(&bind_addrs, port, tls_enabled);
+
         // Spawn the server task
         let mut shutdown_rx_clone = shutdown_rx.clone();
         runtime.spawn(async move {
@@ -202,6 +206,7 @@ impl RemoteServer {
                 git_status,
                 toast_tx,
                 terminal_focus_tx,
+                events_tx,
                 remote_subscribed_terminals,
                 remote_visible_projects,
                 git_poll_trigger_tx,
@@ -211,6 +216,7 @@ impl RemoteServer {
                 ui_owned,
                 had_client,
                 update_info,
+                share_origin,
             );
             #[cfg(unix)]
             let local_server = if let Some((path, listener)) = local_listener {
@@ -335,6 +335,7 @@ mod handshake_tests {
             tls: true,
             pinned_cert_sha256: Some(material.fingerprint.clone()),
             local_endpoint: None,
+            proxy_command: None,
         };
 
         let async_result = okena_transport::remote_action::post_action_async(
@@ -360,4 +361,53 @@ mod handshake_tests {
 
         assert_eq!(result, Some(serde_json::json!({ "actions": [] })));
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn proxy_commands_run_pinned_tls_through_the_relay() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        let dir = tempfile::tempdir().unwrap();
+        let material = load_or_generate(dir.path()).unwrap();
+        let addr = spawn_tls_only(&material).await;
+        // Stands in for `ssh -W %h:%p`: splice stdin/stdout onto a TCP socket.
+        let splice = "bash -c 'exec 3<>/dev/tcp/%h/%p; cat <&3 & exec cat >&3'";
+        let mut config = okena_transport::RemoteConnectionConfig {
+            id: "proxy-tls-test".to_string(),
+            name: "Proxy TLS test".to_string(),
+            host: addr.ip().to_string(),
+            port: addr.port(),
+            saved_token: Some("test-token".to_string()),
+            token_obtained_at: None,
+            tls: true,
+            pinned_cert_sha256: Some(material.fingerprint.clone()),
+            local_endpoint: None,
+            proxy_command: Some(splice.to_string()),
+        };
+
+        let (client, url) =
+            okena_transport::remote_http::async_client_and_url(&config, "/health").unwrap();
+        assert_eq!(url, format!("https://{addr}/health"));
+        assert!(
+            get_with_retry(&client, &url)
+                .await
+                .map(|response| response.status().is_success())
+                .unwrap_or(false),
+            "TLS must run through the proxy command"
+        );
+        let result = okena_transport::remote_action::post_action_async(
+            &config,
+            "test-token",
+            okena_core::api::ActionRequest::ListActions,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, Some(serde_json::json!({ "actions": [] })));
+
+        // The pin still holds behind the relay.
+        config.pinned_cert_sha256 = Some("00".repeat(32));
+        let (client, url) =
+            okena_transport::remote_http::async_client_and_url(&config, "/health").unwrap();
+        assert!(client.get(url).send().await.is_err());
+    }
 }
@@ -37,6 +37,15 @@ pub struct ServiceDefinition {
     pub restart_on_crash: bool,
     #[serde(default = "default_restart_delay")]
     pub restart_delay_ms: u64,
+    /// Globs (relative to `cwd`) whose changes restart the running service.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub watch: Vec<String>,
+    /// Globs (relative to `cwd`) excluded from `watch`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    /// Quiet period after the last matching change before restarting.
+    #[serde(default = "default_watch_debounce")]
+    pub watch_debounce_ms: u64,
 }
 
 /// Filesystem result prepared away from the service-manager reactor.
@@ -81,6 +90,10 @@ fn default_restart_delay() -> u64 {
     1000
 }
 
+fn default_watch_debounce() -> u64 {
+    500
+}
+
 /// Load project config from `{project_path}/okena.yaml`.
 ///
 /// Returns `Ok(None)` if the file doesn't exist, `Err` on parse failure.
@@ -218,5 +231,25 @@ services:
         assert!(!svc.auto_start);
         assert!(!svc.restart_on_crash);
         assert_eq!(svc.restart_delay_ms, 1000);
+        assert!(svc.watch.is_empty());
+        assert!(svc.ignore.is_empty());
+        assert_eq!(svc.watch_debounce_ms, 500);
+    }
+
+    #[test]
+    fn parse_watch_config() {
+        let yaml = r#"
+services:
+  - name: "api"
+    command: "go run ./cmd/api"
+    watch: ["**/*.go", "go.mod"]
+    ignore: ["**/*_test.go"]
+    watch_debounce_ms: 1500
+"#;
+        let config: OkenaProjectConfig = serde_yaml_ng::from_str(yaml).unwrap();
+        let svc = &config.services[0];
+        assert_eq!(svc.watch, vec!["**/*.go", "go.mod"]);
+        assert_eq!(svc.ignore, vec!["**/*_test.go"]);
+        assert_eq!(svc.watch_debounce_ms, 1500);
     }
 }
@@ -2,7 +2,7 @@
 
 use super::{
     DockerMutation, DockerMutationKind, MAX_RESTART_COUNT, OkenaLaunchToken, ServiceAsyncCx,
-    ServiceCx, ServiceHandle, ServiceKind, ServiceManager, ServiceStatus,
+    ServiceCx, ServiceEvent, ServiceHandle, ServiceKind, ServiceManager, ServiceStatus,
 };
 use crate::port_detect;
 use okena_core::process::is_process_alive;
@@ -121,6 +121,15 @@ impl ServiceManager {
                             {
                                 instance.status = ServiceStatus::Stopped;
                             }
+                            // A log viewer the rebuild closed followed the old
+                            // container; attach it to the new one.
+                            if mutation.kind == DockerMutationKind::Rebuild
+                                && !this.docker_mutations.has_service_mutation(&key.0, &key.1)
+                                && this.rebuild_log_viewers.remove(&key)
+                                && !failed
+                            {
+                                this.open_docker_logs(&key.0, &key.1, cx);
+                            }
                             cx.notify();
                         }
                         next
@@ -167,6 +176,10 @@ impl ServiceManager {
             if let Some(instance) = self.instances.get_mut(key) {
                 instance.status = ServiceStatus::Running;
             }
+            self.push_service_event(ServiceEvent::Ready {
+                project_id: key.0.clone(),
+                service_name: key.1.clone(),
+            });
             self.start_port_detection(&key.0, &key.1, cx);
         }
         self.finish_okena_launch(key, launch_token);
@@ -244,6 +257,10 @@ impl ServiceManager {
         if instance.status == ServiceStatus::Running || instance.status == ServiceStatus::Starting {
             return;
         }
+        // A restart re-enters here while Restarting; keep its trigger.
+        if instance.status != ServiceStatus::Restarting {
+            instance.restart_trigger = None;
+        }
 
         match &instance.kind {
             ServiceKind::DockerCompose { compose_file } => {
@@ -390,6 +407,14 @@ impl ServiceManager {
                                     OkenaLaunchFailure::Reconnect { .. } => ServiceStatus::Stopped,
                                 };
                             }
+                            if matches!(failure, OkenaLaunchFailure::Crashed) {
+                                this.push_service_event(ServiceEvent::Crashed {
+                                    project_id: key.0.clone(),
+                                    service_name: key.1.clone(),
+                                    exit_code: None,
+                                    restarting: false,
+                                });
+                            }
                             this.finish_okena_launch(&key, &launch_token);
                             cx.notify();
                             if matches!(failure, OkenaLaunchFailure::Reconnect { auto_start: true })
@@ -432,6 +457,8 @@ impl ServiceManager {
             None => return,
         };
 
+        instance.restart_trigger = None;
+
         // Kill log viewer PTY if any (for both kinds)
         if let Some(terminal_id) = instance.terminal_id.take() {
             self.backend.kill(&terminal_id);
@@ -485,6 +512,7 @@ impl ServiceManager {
             Some(i) => i,
             None => return,
         };
+        instance.restart_trigger = None;
 
         match &instance.kind {
             ServiceKind::DockerCompose { compose_file } => {
@@ -592,6 +620,107 @@ impl ServiceManager {
         }
     }
 
+    /// Rebuild a Docker Compose service's image and recreate its container.
+    pub fn rebuild_service(
+        &mut self,
+        project_id: &str,
+        service_name: &str,
+        project_path: &str,
+        cx: &mut impl ServiceCx,
+    ) -> Result<(), String> {
+        self.schedule_compose_only_mutation(
+            project_id,
+            service_name,
+            project_path,
+            DockerMutationKind::Rebuild,
+            cx,
+        )
+    }
+
+    /// Pull a Docker Compose service's image. The running container keeps the
+    /// old image until the next rebuild or restart.
+    pub fn pull_service(
+        &mut self,
+        project_id: &str,
+        service_name: &str,
+        project_path: &str,
+        cx: &mut impl ServiceCx,
+    ) -> Result<(), String> {
+        self.schedule_compose_only_mutation(
+            project_id,
+            service_name,
+            project_path,
+            DockerMutationKind::Pull,
+            cx,
+        )
+    }
+
+    fn schedule_compose_only_mutation(
+        &mut self,
+        project_id: &str,
+        service_name: &str,
+        project_path: &str,
+        kind: DockerMutationKind,
+        cx: &mut impl ServiceCx,
+    ) -> Result<(), String> {
+        let key = (project_id.to_string(), service_name.to_string());
+        let Some(instance) = self.instances.get_mut(&key) else {
+            return Err(format!("service not found: {service_name}"));
+        };
+        let ServiceKind::DockerCompose { compose_file } = &instance.kind else {
+            return Err(format!("'{service_name}' is not a Docker Compose service"));
+        };
+        let compose_file = compose_file.clone();
+
+        if kind == DockerMutationKind::Rebuild {
+            // The container is recreated, so the log viewer would follow a dead
+            // one. It is reopened once `up -d --build` finishes.
+            if let Some(terminal_id) = instance.terminal_id.take() {
+                self.backend.kill(&terminal_id);
+                self.terminals.lock().remove(&terminal_id);
+                self.terminal_to_service.remove(&terminal_id);
+                self.rebuild_log_viewers.insert(key.clone());
+            }
+            instance.status = ServiceStatus::Restarting;
+            instance.detected_ports.clear();
+            cx.notify();
+        }
+
+        self.schedule_docker_mutation(key, project_path.to_string(), compose_file, kind, cx);
+        Ok(())
+    }
+
+    /// Shell marker for an interactive terminal inside a Docker Compose
+    /// service's container. The compose file is made absolute so the terminal
+    /// can be spawned (and restored) from any working directory.
+    pub fn service_shell(
+        &self,
+        project_id: &str,
+        service_name: &str,
+        shell: Option<String>,
+    ) -> Result<ShellType, String> {
+        let key = (project_id.to_string(), service_name.to_string());
+        let instance = self
+            .instances
+            .get(&key)
+            .ok_or_else(|| format!("service not found: {service_name}"))?;
+        let ServiceKind::DockerCompose { compose_file } = &instance.kind else {
+            return Err(format!("'{service_name}' is not a Docker Compose service"));
+        };
+        let project_path = self
+            .project_paths
+            .get(project_id)
+            .ok_or_else(|| format!("project not found: {project_id}"))?;
+        Ok(ShellType::DockerExec {
+            compose_file: Path::new(project_path)
+                .join(compose_file)
+                .to_string_lossy()
+                .into_owned(),
+            service: service_name.to_string(),
+            shell,
+        })
+    }
+
     /// Start all services for a project.
     pub fn start_all(&mut self, project_id: &str, project_path: &str, cx: &mut impl ServiceCx) {
         let names: Vec<String> = self
@@ -687,6 +816,12 @@ impl ServiceManager {
             // so the user can see the crash output until they manually restart.
             instance.status = ServiceStatus::Crashed { exit_code };
         }
+        self.push_service_event(ServiceEvent::Crashed {
+            project_id,
+            service_name,
+            exit_code,
+            restarting: should_restart,
+        });
 
         cx.notify();
         true
@@ -707,7 +842,18 @@ fn run_docker_mutation(mutation: &DockerMutation) -> crate::ServiceResult<()> {
                 &mutation.service_name,
             ]);
         }
-        DockerMutationKind::Stop | DockerMutationKind::Restart => {
+        DockerMutationKind::Rebuild => {
+            command.args([
+                "compose",
+                "-f",
+                &mutation.compose_file,
+                "up",
+                "-d",
+                "--build",
+                &mutation.service_name,
+            ]);
+        }
+        DockerMutationKind::Stop | DockerMutationKind::Restart | DockerMutationKind::Pull => {
             command.args([
                 "compose",
                 "-f",
@@ -1,8 +1,8 @@
 //! Docker Compose service discovery, log-viewer PTYs, and status polling.
 
 use super::{
-    ServiceAsyncCx, ServiceCx, ServiceHandle, ServiceInstance, ServiceKind, ServiceManager,
-    ServiceStatus,
+    ServiceAsyncCx, ServiceCx, ServiceEvent, ServiceHandle, ServiceInstance, ServiceKind,
+    ServiceManager, ServiceStatus,
 };
 use crate::config::ServiceDefinition;
 use crate::docker_compose;
@@ -74,6 +74,55 @@ fn reconcile_docker_statuses(
     (has_definitions, changed)
 }
 
+fn docker_statuses(
+    instances: &HashMap<(String, String), ServiceInstance>,
+    project_id: &str,
+) -> HashMap<String, ServiceStatus> {
+    instances
+        .iter()
+        .filter(|((pid, _), instance)| {
+            pid == project_id && matches!(instance.kind, ServiceKind::DockerCompose { .. })
+        })
+        .map(|((_, name), instance)| (name.clone(), instance.status.clone()))
+        .collect()
+}
+
+/// Crash / ready edges between two polls. Only transitions out of an active
+/// state count, so the first poll after loading (everything `Stopped`) does
+/// not report containers that were already up or already failed.
+fn docker_status_events(
+    project_id: &str,
+    before: &HashMap<String, ServiceStatus>,
+    after: &HashMap<String, ServiceStatus>,
+) -> Vec<ServiceEvent> {
+    let mut events = Vec::new();
+    for (name, status) in after {
+        let Some(previous) = before.get(name) else {
+            continue;
+        };
+        match (previous, status) {
+            (
+                ServiceStatus::Starting | ServiceStatus::Restarting | ServiceStatus::Crashed { .. },
+                ServiceStatus::Running,
+            ) => events.push(ServiceEvent::Ready {
+                project_id: project_id.to_string(),
+                service_name: name.clone(),
+            }),
+            (
+                ServiceStatus::Running | ServiceStatus::Starting | ServiceStatus::Restarting,
+                ServiceStatus::Crashed { exit_code },
+            ) => events.push(ServiceEvent::Crashed {
+                project_id: project_id.to_string(),
+                service_name: name.clone(),
+                exit_code: *exit_code,
+                restarting: false,
+            }),
+            _ => {}
+        }
+    }
+    events
+}
+
 fn reconcile_docker_poll_result(
     instances: &mut HashMap<(String, String), ServiceInstance>,
     mutations: &super::DockerMutationQueue,
@@ -200,6 +249,9 @@ impl ServiceManager {
                                 auto_start: false,
                                 restart_on_crash: false,
                                 restart_delay_ms: 0,
+                                watch: Vec::new(),
+                                ignore: Vec::new(),
+                                watch_debounce_ms: 0,
                             },
                             kind: ServiceKind::DockerCompose {
                                 compose_file: compose_file.clone(),
@@ -209,6 +261,7 @@ impl ServiceManager {
                             restart_count: 0,
                             detected_ports: Vec::new(),
                             is_extra,
+                            restart_trigger: None,
                         });
                 }
 
@@ -470,6 +523,7 @@ impl ServiceManager {
                                 if !this.is_project_incarnation_current(&pid, &incarnation) {
                                     return true;
                                 }
+                                let before = docker_statuses(&this.instances, &pid);
                                 let Some((has_definitions, changed)) = reconcile_docker_poll_result(
                                     &mut this.instances,
                                     &this.docker_mutations,
@@ -482,6 +536,10 @@ impl ServiceManager {
                                     return false;
                                 };
                                 if changed {
+                                    let after = docker_statuses(&this.instances, &pid);
+                                    for event in docker_status_events(&pid, &before, &after) {
+                                        this.push_service_event(event);
+                                    }
                                     cx.notify();
                                 }
                                 !has_definitions
@@ -525,6 +583,9 @@ mod tests {
                 auto_start: false,
                 restart_on_crash: false,
                 restart_delay_ms: 0,
+                watch: Vec::new(),
+                ignore: Vec::new(),
+                watch_debounce_ms: 0,
             },
             kind: ServiceKind::DockerCompose {
                 compose_file: "compose.yml".to_string(),
@@ -534,9 +595,51 @@ mod tests {
             restart_count: 0,
             detected_ports: ports,
             is_extra: false,
+            restart_trigger: None,
         }
     }
 
+    #[test]
+    fn status_events_only_follow_transitions_out_of_active_states() {
+        let before = HashMap::from([
+            ("web".to_string(), ServiceStatus::Starting),
+            ("db".to_string(), ServiceStatus::Running),
+            ("cache".to_string(), ServiceStatus::Stopped),
+            ("queue".to_string(), ServiceStatus::Stopped),
+        ]);
+        let after = HashMap::from([
+            ("web".to_string(), ServiceStatus::Running),
+            (
+                "db".to_string(),
+                ServiceStatus::Crashed { exit_code: Some(1) },
+            ),
+            // First poll after load: already up / already failed.
+            ("cache".to_string(), ServiceStatus::Running),
+            (
+                "queue".to_string(),
+                ServiceStatus::Crashed { exit_code: Some(2) },
+            ),
+        ]);
+
+        let mut events = docker_status_events("project", &before, &after);
+        events.sort_by_key(|event| format!("{event:?}"));
+        assert_eq!(
+            events,
+            vec![
+                ServiceEvent::Crashed {
+                    project_id: "project".into(),
+                    service_name: "db".into(),
+                    exit_code: Some(1),
+                    restarting: false,
+                },
+                ServiceEvent::Ready {
+                    project_id: "project".into(),
+                    service_name: "web".into(),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn empty_snapshot_stops_known_service_without_stopping_poller() {
         let key = ("project".to_string(), "web".to_string());
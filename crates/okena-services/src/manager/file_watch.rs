@@ -0,0 +1,364 @@
+//! File-watch triggered restarts: services with `watch` globs are restarted,
+//! debounced, when a matching file under their cwd changes.
+//!
+//! Only directories that can hold a watched file get an OS watch: anything
+//! named in [`DEFAULT_IGNORED_DIRS`] or ignored by `.gitignore` is skipped,
+//! so dependency and build trees neither trigger restarts nor use up the
+//! inotify watch limit.
+
+use super::{
+    ProjectIncarnation, ServiceAsyncCx, ServiceCx, ServiceHandle, ServiceKind, ServiceManager,
+    ServiceStatus,
+};
+use crate::config::ServiceDefinition;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+/// Directory names never watched, whatever the globs say.
+pub(super) const DEFAULT_IGNORED_DIRS: &[&str] = &[".git", "node_modules", "target"];
+
+/// Live filesystem watch for one service. Dropping it stops the OS watcher,
+/// which closes the event channel and ends the debounce task.
+pub(super) struct ServiceFileWatch {
+    _watcher: Arc<Mutex<notify::RecommendedWatcher>>,
+}
+
+/// What the OS watcher reports to the debounce task.
+enum WatchEvent {
+    /// A watched file changed, relative to the service cwd.
+    Changed(String),
+    /// A directory appeared that needs its own watch.
+    NewDir(PathBuf),
+}
+
+/// The files changed in one debounced burst.
+pub(super) struct ChangeBurst {
+    latest: String,
+    paths: HashSet<String>,
+}
+
+impl ChangeBurst {
+    pub(super) fn new(first: String) -> Self {
+        Self {
+            paths: HashSet::from([first.clone()]),
+            latest: first,
+        }
+    }
+
+    pub(super) fn push(&mut self, changed: String) {
+        self.paths.insert(changed.clone());
+        self.latest = changed;
+    }
+
+    /// The last file changed, plus how many others changed with it:
+    /// `src/a.rs` or `src/a.rs and 12 more`.
+    pub(super) fn describe(self) -> String {
+        match self.paths.len() - 1 {
+            0 => self.latest,
+            others => format!("{} and {others} more", self.latest),
+        }
+    }
+}
+
+/// Compiled `watch` / `ignore` globs for one service, matched against paths
+/// relative to the service cwd, plus the cwd's `.gitignore`.
+pub(super) struct WatchFilter {
+    roots: Vec<PathBuf>,
+    watch: GlobSet,
+    ignore: GlobSet,
+    gitignore: Gitignore,
+}
+
+impl WatchFilter {
+    pub(super) fn new(root: &Path, watch: &[String], ignore: &[String]) -> Result<Self, String> {
+        // Event paths may arrive canonicalized (macOS reports /private/var for
+        // /var), so match against both spellings of the root.
+        let mut roots = vec![root.to_path_buf()];
+        if let Ok(canonical) = std::fs::canonicalize(root)
+            && canonical != root
+        {
+            roots.push(canonical);
+        }
+        Ok(Self {
+            roots,
+            watch: build_glob_set(watch)?,
+            ignore: build_glob_set(ignore)?,
+            gitignore: load_gitignore(root),
+        })
+    }
+
+    /// The path relative to the service cwd, if it is watched and not ignored.
+    pub(super) fn matches(&self, path: &Path) -> Option<String> {
+        let relative = self.unignored(path, false)?;
+        (self.watch.is_match(relative) && !self.ignore.is_match(relative))
+            .then(|| relative.to_string_lossy().replace('\\', "/"))
+    }
+
+    /// Whether a directory under the service cwd should be watched.
+    pub(super) fn watches_dir(&self, path: &Path) -> bool {
+        self.unignored(path, true).is_some()
+    }
+
+    /// `path` relative to the service cwd, unless it lies outside it or
+    /// under a default-ignored or gitignored directory.
+    fn unignored<'a>(&self, path: &'a Path, is_dir: bool) -> Option<&'a Path> {
+        let relative = self
+            .roots
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())?;
+        if relative
+            .components()
+            .any(|c| is_default_ignored(c.as_os_str()))
+        {
+            return None;
+        }
+        if relative.as_os_str().is_empty() {
+            return Some(relative);
+        }
+        let is_dir = is_dir || path.is_dir();
+        (!self
+            .gitignore
+            .matched_path_or_any_parents(relative, is_dir)
+            .is_ignore())
+        .then_some(relative)
+    }
+}
+
+fn is_default_ignored(name: &OsStr) -> bool {
+    DEFAULT_IGNORED_DIRS.iter().any(|ignored| name == *ignored)
+}
+
+/// The cwd's `.gitignore`, or an empty matcher when there is none.
+fn load_gitignore(root: &Path) -> Gitignore {
+    let path = root.join(".gitignore");
+    if !path.is_file() {
+        return Gitignore::empty();
+    }
+    let mut builder = GitignoreBuilder::new(root);
+    if let Some(e) = builder.add(&path) {
+        log::warn!("[services] Cannot read {}: {}", path.display(), e);
+    }
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+/// `dir` and every directory below it that is not ignored, by
+/// [`DEFAULT_IGNORED_DIRS`] or by any `.gitignore` on the way.
+fn watched_dirs(dir: &Path) -> Vec<PathBuf> {
+    ignore::WalkBuilder::new(dir)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| !is_default_ignored(entry.file_name()))
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_dir()))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// Watch `top` and the directories below it, one level at a time. Only a
+/// failure on `top` itself is an error; a subdirectory that vanished or
+/// can't be read is skipped.
+fn watch_tree(watcher: &Mutex<notify::RecommendedWatcher>, top: &Path) -> Result<(), String> {
+    let mut watcher = watcher.lock().unwrap_or_else(|e| e.into_inner());
+    watcher
+        .watch(top, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("{}: {e}", top.display()))?;
+    for dir in watched_dirs(top).iter().filter(|dir| *dir != top) {
+        if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+            log::warn!("[services] Cannot watch {}: {}", dir.display(), e);
+        }
+    }
+    Ok(())
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| format!("invalid glob '{pattern}': {e}"))?;
+        builder.add(glob);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// The changed path of a [`WatchEvent::Changed`]. A new directory is
+/// watched instead, unless the service's watch is already gone.
+fn handle_event(
+    event: WatchEvent,
+    watcher: &Weak<Mutex<notify::RecommendedWatcher>>,
+) -> Option<String> {
+    match event {
+        WatchEvent::Changed(changed) => Some(changed),
+        WatchEvent::NewDir(dir) => {
+            if let Some(watcher) = watcher.upgrade()
+                && let Err(e) = watch_tree(&watcher, &dir)
+            {
+                log::warn!("[services] Cannot watch new directory {}", e);
+            }
+            None
+        }
+    }
+}
+
+impl ServiceManager {
+    /// Re-arm file watches for a project's Okena services from their current
+    /// definitions. Called after load / reload; existing watches are dropped.
+    pub(super) fn sync_file_watches(&mut self, project_id: &str, cx: &mut impl ServiceCx) {
+        self.file_watches.retain(|(pid, _), _| pid != project_id);
+        let Some(project_path) = self.project_paths.get(project_id).cloned() else {
+            return;
+        };
+        let Some(project_incarnation) = self.project_incarnation(project_id, &project_path) else {
+            return;
+        };
+
+        let watched: Vec<((String, String), ServiceDefinition)> = self
+            .instances
+            .iter()
+            .filter(|((pid, _), instance)| {
+                pid == project_id
+                    && instance.kind == ServiceKind::Okena
+                    && !instance.definition.watch.is_empty()
+            })
+            .map(|(key, instance)| (key.clone(), instance.definition.clone()))
+            .collect();
+
+        for (key, definition) in watched {
+            match self.spawn_file_watch(
+                &key,
+                &definition,
+                &project_path,
+                project_incarnation.clone(),
+                cx,
+            ) {
+                Ok(watch) => {
+                    self.file_watches.insert(key, watch);
+                }
+                Err(e) => log::warn!("[services] Cannot watch files for '{}': {}", key.1, e),
+            }
+        }
+    }
+
+    fn spawn_file_watch(
+        &self,
+        key: &(String, String),
+        definition: &ServiceDefinition,
+        project_path: &str,
+        project_incarnation: ProjectIncarnation,
+        cx: &mut impl ServiceCx,
+    ) -> Result<ServiceFileWatch, String> {
+        let root = Path::new(project_path).join(&definition.cwd);
+        let filter = WatchFilter::new(&root, &definition.watch, &definition.ignore)?;
+        let (events_tx, events_rx) = async_channel::unbounded::<WatchEvent>();
+
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if !matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) {
+                return;
+            }
+            for path in &event.paths {
+                // The watch can't be added from the watcher's own thread.
+                if matches!(event.kind, EventKind::Create(_))
+                    && path.is_dir()
+                    && filter.watches_dir(path)
+                {
+                    let _ = events_tx.try_send(WatchEvent::NewDir(path.clone()));
+                }
+                if let Some(relative) = filter.matches(path) {
+                    let _ = events_tx.try_send(WatchEvent::Changed(relative));
+                }
+            }
+        })
+        .map_err(|e| e.to_string())?;
+        let watcher = Arc::new(Mutex::new(watcher));
+        watch_tree(&watcher, &root)?;
+
+        let key = key.clone();
+        let debounce = Duration::from_millis(definition.watch_debounce_ms);
+        let weak_watcher = Arc::downgrade(&watcher);
+        cx.spawn_main(async move |this, cx| {
+            while let Ok(event) = events_rx.recv().await {
+                let Some(first) = handle_event(event, &weak_watcher) else {
+                    continue;
+                };
+                // Trailing debounce: restart once the burst (save-all, git
+                // checkout, build output) has been quiet for `debounce`.
+                let mut burst = ChangeBurst::new(first);
+                loop {
+                    cx.timer(debounce).await;
+                    if events_rx.is_empty() {
+                        break;
+                    }
+                    while let Ok(event) = events_rx.try_recv() {
+                        if let Some(changed) = handle_event(event, &weak_watcher) {
+                            burst.push(changed);
+                        }
+                    }
+                }
+                let changed = burst.describe();
+                let keep_watching = this
+                    .update(cx, |this, cx| {
+                        this.restart_for_file_change(&key, &project_incarnation, changed, cx)
+                    })
+                    .unwrap_or(false);
+                if !keep_watching {
+                    return;
+                }
+            }
+        });
+
+        Ok(ServiceFileWatch { _watcher: watcher })
+    }
+
+    /// Restart a running (or crashed) service after a watched file changed.
+    /// Stopped services stay stopped. Returns `false` once the watch belongs
+    /// to a stale project incarnation so its task can exit.
+    pub(super) fn restart_for_file_change(
+        &mut self,
+        key: &(String, String),
+        project_incarnation: &ProjectIncarnation,
+        changed: String,
+        cx: &mut impl ServiceCx,
+    ) -> bool {
+        if !self.is_project_incarnation_current(&key.0, project_incarnation) {
+            return false;
+        }
+        let Some(instance) = self.instances.get(key) else {
+            return false;
+        };
+        if !matches!(
+            instance.status,
+            ServiceStatus::Running | ServiceStatus::Crashed { .. }
+        ) {
+            return true;
+        }
+        let Some(project_path) = self.project_paths.get(&key.0).cloned() else {
+            return false;
+        };
+
+        log::info!(
+            "[services] Restarting '{}' after change in {}",
+            key.1,
+            changed
+        );
+        self.restart_service(&key.0, &key.1, &project_path, cx);
+        if let Some(instance) = self.instances.get_mut(key) {
+            instance.restart_trigger = Some(changed);
+        }
+        cx.notify();
+        true
+    }
+}
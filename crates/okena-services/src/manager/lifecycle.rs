@@ -199,6 +199,7 @@ impl ServiceManager {
                     restart_count: 0,
                     detected_ports: Vec::new(),
                     is_extra: false,
+                    restart_trigger: None,
                 },
             );
         }
@@ -222,6 +223,8 @@ impl ServiceManager {
             }
         }
 
+        self.sync_file_watches(project_id, cx);
+
         // Load Docker Compose services
         self.load_docker_compose_services_prepared(
             project_id,
@@ -317,6 +320,7 @@ impl ServiceManager {
         if let Some(cancel) = self.docker_pollers.remove(project_id) {
             cancel.store(true, Ordering::Relaxed);
         }
+        self.file_watches.retain(|(pid, _), _| pid != project_id);
 
         let keys: Vec<(String, String)> = self
             .instances
@@ -468,6 +472,7 @@ impl ServiceManager {
                         restart_count: 0,
                         detected_ports: Vec::new(),
                         is_extra: false,
+                        restart_trigger: None,
                     },
                 );
             }
@@ -507,6 +512,8 @@ impl ServiceManager {
             }
         }
 
+        self.sync_file_watches(project_id, cx);
+
         // Reload Docker Compose services
         self.reload_docker_compose_services_prepared(
             project_id,
@@ -5,10 +5,12 @@
 //! - [`commands`]       — start / stop / restart individual services
 //! - [`docker`]         — Docker Compose discovery, log viewers, status polling
 //! - [`port_detection`] — centralized listening-port discovery poller
+//! - [`file_watch`]     — restart services when their `watch` globs change
 
 mod commands;
 mod context;
 mod docker;
+mod file_watch;
 mod lifecycle;
 mod port_detection;
 
@@ -47,10 +49,37 @@ pub struct ServiceManager {
     pub(super) docker_pollers: HashMap<String, Arc<AtomicBool>>,
     docker_mutations: DockerMutationQueue,
     docker_mutation_runner: Arc<dyn commands::DockerMutationRunner>,
+    /// Services whose log viewer a queued rebuild closed, reopened once it succeeds.
+    rebuild_log_viewers: HashSet<(String, String)>,
     /// Services currently undergoing port detection.
     pub(super) port_detection_active: HashMap<(String, String), PortDetectionState>,
     /// Whether the centralized port detection poller task is running.
     pub(super) port_detection_running: bool,
+    /// Filesystem watches for services with `watch` globs.
+    file_watches: HashMap<(String, String), file_watch::ServiceFileWatch>,
+    /// Crash / ready edges not yet drained by [`Self::take_service_events`].
+    pending_events: VecDeque<ServiceEvent>,
+}
+
+/// Cap on undrained [`ServiceEvent`]s; the oldest are dropped beyond it.
+const MAX_PENDING_SERVICE_EVENTS: usize = 256;
+
+/// A service lifecycle edge, queued for the daemon's `service.*` hooks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ServiceEvent {
+    /// The service exited with a failure (or its container entered a failed
+    /// state). `restarting` is set when `restart_on_crash` scheduled a retry.
+    Crashed {
+        project_id: String,
+        service_name: String,
+        exit_code: Option<u32>,
+        restarting: bool,
+    },
+    /// The service reached `running`: its PTY launched, or its container came up.
+    Ready {
+        project_id: String,
+        service_name: String,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -77,14 +106,19 @@ pub enum DockerMutationKind {
     Start,
     Stop,
     Restart,
+    /// Rebuild the image and recreate the container (`up -d --build`).
+    Rebuild,
+    /// Pull the service image without touching the running container.
+    Pull,
 }
 
 impl DockerMutationKind {
     fn compose_argument(self) -> &'static str {
         match self {
-            Self::Start => "up",
+            Self::Start | Self::Rebuild => "up",
             Self::Stop => "stop",
             Self::Restart => "restart",
+            Self::Pull => "pull",
         }
     }
 }
@@ -331,6 +365,8 @@ pub struct ServiceInstance {
     pub detected_ports: Vec<u16>,
     /// Docker service not listed in okena.yaml filter — shown in "Other" section.
     pub is_extra: bool,
+    /// Watched path whose change caused the last automatic restart.
+    pub restart_trigger: Option<String>,
 }
 
 impl ServiceInstance {
@@ -362,6 +398,7 @@ impl ServiceInstance {
             exit_code,
             kind: kind.to_string(),
             is_extra: self.is_extra,
+            restart_trigger: self.restart_trigger.clone(),
         }
     }
 }
@@ -465,6 +502,36 @@ impl ServiceManager {
         okena_core::api::CommandResult::Ok(None)
     }
 
+    pub fn rebuild_service_action(
+        &mut self,
+        project_id: &str,
+        service_name: &str,
+        cx: &mut impl ServiceCx,
+    ) -> okena_core::api::CommandResult {
+        match self.project_path(project_id).cloned() {
+            Some(path) => match self.rebuild_service(project_id, service_name, &path, cx) {
+                Ok(()) => okena_core::api::CommandResult::Ok(None),
+                Err(e) => okena_core::api::CommandResult::Err(e),
+            },
+            None => okena_core::api::CommandResult::Err(format!("project not found: {project_id}")),
+        }
+    }
+
+    pub fn pull_service_action(
+        &mut self,
+        project_id: &str,
+        service_name: &str,
+        cx: &mut impl ServiceCx,
+    ) -> okena_core::api::CommandResult {
+        match self.project_path(project_id).cloned() {
+            Some(path) => match self.pull_service(project_id, service_name, &path, cx) {
+                Ok(()) => okena_core::api::CommandResult::Ok(None),
+                Err(e) => okena_core::api::CommandResult::Err(e),
+            },
+            None => okena_core::api::CommandResult::Err(format!("project not found: {project_id}")),
+        }
+    }
+
     pub fn reload_services_action(
         &mut self,
         project_id: &str,
@@ -498,9 +565,24 @@ impl ServiceManager {
             docker_pollers: HashMap::new(),
             docker_mutations: DockerMutationQueue::default(),
             docker_mutation_runner: Arc::new(commands::CommandDockerMutationRunner),
+            rebuild_log_viewers: HashSet::new(),
             port_detection_active: HashMap::new(),
             port_detection_running: false,
+            file_watches: HashMap::new(),
+            pending_events: VecDeque::new(),
+        }
+    }
+
+    /// Drain the crash / ready edges recorded since the previous call.
+    pub fn take_service_events(&mut self) -> Vec<ServiceEvent> {
+        self.pending_events.drain(..).collect()
+    }
+
+    pub(super) fn push_service_event(&mut self, event: ServiceEvent) {
+        if self.pending_events.len() == MAX_PENDING_SERVICE_EVENTS {
+            self.pending_events.pop_front();
         }
+        self.pending_events.push_back(event);
     }
 
     /// Get the current mapping of service_name -> terminal_id for a project.
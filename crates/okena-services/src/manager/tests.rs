@@ -9,7 +9,7 @@ use okena_terminal::terminal::TerminalTransport;
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::future::{Future, ready};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::{Rc, Weak};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
@@ -410,6 +410,9 @@ fn make_instance(
         auto_start: false,
         restart_on_crash,
         restart_delay_ms: 1000,
+        watch: Vec::new(),
+        ignore: Vec::new(),
+        watch_debounce_ms: 0,
     };
     (
         (project_id.to_string(), name.to_string()),
@@ -421,6 +424,7 @@ fn make_instance(
             restart_count,
             detected_ports: Vec::new(),
             is_extra: false,
+            restart_trigger: None,
         },
     )
 }
@@ -498,6 +502,7 @@ fn clean_okena_exit_stops_without_scheduling_restart() {
 
     assert!(manager.handle_service_exit(&terminal_id, Some(0), &mut cx));
 
+    assert!(manager.take_service_events().is_empty());
     assert_eq!(manager.instances[&key].status, ServiceStatus::Stopped);
     assert_eq!(manager.instances[&key].terminal_id, None);
     assert_eq!(manager.instances[&key].restart_count, 0);
@@ -505,6 +510,46 @@ fn clean_okena_exit_stops_without_scheduling_restart() {
     assert_eq!(cx.spawned.load(Ordering::Relaxed), 0);
 }
 
+#[test]
+fn failed_okena_exit_queues_a_crash_event() {
+    let path = "/project";
+    let mut manager = manager();
+    manager.project_paths.insert("project".into(), path.into());
+    manager.begin_project_incarnation("project", path);
+    let mut terminal_ids = Vec::new();
+    for (name, restart_on_crash) in [("web", true), ("worker", false)] {
+        let (key, instance) =
+            make_instance("project", name, restart_on_crash, 0, ServiceStatus::Running);
+        let terminal_id = instance.terminal_id.clone().expect("running terminal");
+        manager.instances.insert(key.clone(), instance);
+        manager.terminal_to_service.insert(terminal_id.clone(), key);
+        terminal_ids.push(terminal_id);
+    }
+    let mut cx = RecordingCx::default();
+
+    assert!(manager.handle_service_exit(&terminal_ids[0], Some(1), &mut cx));
+    assert!(manager.handle_service_exit(&terminal_ids[1], Some(137), &mut cx));
+
+    assert_eq!(
+        manager.take_service_events(),
+        vec![
+            ServiceEvent::Crashed {
+                project_id: "project".into(),
+                service_name: "web".into(),
+                exit_code: Some(1),
+                restarting: true,
+            },
+            ServiceEvent::Crashed {
+                project_id: "project".into(),
+                service_name: "worker".into(),
+                exit_code: Some(137),
+                restarting: false,
+            },
+        ]
+    );
+    assert!(manager.take_service_events().is_empty());
+}
+
 #[test]
 fn scheduled_restart_requires_the_same_restarting_state() {
     let mut manager = manager();
@@ -630,6 +675,9 @@ fn make_docker_instance(
         auto_start: false,
         restart_on_crash: false,
         restart_delay_ms: 0,
+        watch: Vec::new(),
+        ignore: Vec::new(),
+        watch_debounce_ms: 0,
     };
     (
         (project_id.to_string(), name.to_string()),
@@ -643,6 +691,7 @@ fn make_docker_instance(
             restart_count: 0,
             detected_ports: Vec::new(),
             is_extra: false,
+            restart_trigger: None,
         },
     )
 }
@@ -740,6 +789,182 @@ fn docker_start_then_stop_dispatches_in_request_order() {
     assert!(!manager.docker_mutations.active.contains_key(&scope));
 }
 
+#[test]
+fn docker_rebuild_queues_behind_active_start_and_marks_restarting() {
+    let path = "/project";
+    let key = ("project".to_string(), "web".to_string());
+    let mut manager = manager();
+    manager.project_paths.insert(key.0.clone(), path.into());
+    manager.begin_project_incarnation(&key.0, path);
+    let (_, mut instance) = make_docker_instance(&key.0, &key.1, ServiceStatus::Stopped);
+    instance.terminal_id = None;
+    manager.instances.insert(key.clone(), instance);
+    let mut cx = RecordingCx::default();
+
+    manager.start_service(&key.0, &key.1, path, &mut cx);
+    manager
+        .rebuild_service(&key.0, &key.1, path, &mut cx)
+        .unwrap();
+    manager.pull_service(&key.0, &key.1, path, &mut cx).unwrap();
+
+    assert_eq!(cx.spawned.load(Ordering::Relaxed), 1);
+    assert_eq!(manager.instances[&key].status, ServiceStatus::Restarting);
+    let scope = manager.docker_mutations.active.keys().next().unwrap();
+    assert_eq!(
+        manager.docker_mutations.active[scope]
+            .pending
+            .iter()
+            .map(|mutation| mutation.kind)
+            .collect::<Vec<_>>(),
+        vec![DockerMutationKind::Rebuild, DockerMutationKind::Pull]
+    );
+}
+
+#[test]
+fn docker_rebuild_reopens_the_log_viewer_when_done() {
+    let path = "/project";
+    let key = ("project".to_string(), "web".to_string());
+    let (events_tx, events_rx) = async_channel::unbounded();
+    let (mut service_manager, _plans) = recording_manager();
+    service_manager.docker_mutation_runner = Arc::new(TimeoutDockerRunner { events: events_tx });
+    service_manager
+        .project_paths
+        .insert(key.0.clone(), path.into());
+    service_manager.begin_project_incarnation(&key.0, path);
+    let (_, instance) = make_docker_instance(&key.0, &key.1, ServiceStatus::Running);
+    service_manager.instances.insert(key.clone(), instance);
+
+    let executor = Rc::new(smol::LocalExecutor::new());
+    let service_manager = Rc::new(RefCell::new(service_manager));
+    let handle = ExecutingHandle {
+        manager: Rc::downgrade(&service_manager),
+        executor: executor.clone(),
+        notifications: Arc::new(AtomicUsize::new(0)),
+    };
+
+    smol::block_on(executor.run(async {
+        let mut cx = ExecutingCx { handle };
+        service_manager
+            .borrow_mut()
+            .rebuild_service(&key.0, &key.1, path, &mut cx)
+            .unwrap();
+        assert_eq!(service_manager.borrow().instances[&key].terminal_id, None);
+
+        assert_eq!(events_rx.recv().await, Ok(DockerMutationKind::Rebuild));
+        while !service_manager.borrow().docker_mutations.active.is_empty() {
+            smol::Timer::after(Duration::from_millis(1)).await;
+        }
+        let viewer = service_manager.borrow().instances[&key]
+            .terminal_id
+            .clone()
+            .expect("rebuild must reopen the log viewer");
+        assert_ne!(viewer, "term-web");
+        let spawned = || {
+            service_manager
+                .borrow()
+                .terminals
+                .lock()
+                .contains_key(&viewer)
+        };
+        for _ in 0..1000 {
+            if spawned() {
+                break;
+            }
+            smol::Timer::after(Duration::from_millis(1)).await;
+        }
+        assert!(spawned());
+        assert_eq!(
+            service_manager.borrow().terminal_to_service.get(&viewer),
+            Some(&key)
+        );
+    }));
+}
+
+#[test]
+fn docker_rebuild_leaves_a_closed_log_viewer_closed() {
+    let path = "/project";
+    let key = ("project".to_string(), "web".to_string());
+    let (events_tx, events_rx) = async_channel::unbounded();
+    let (mut service_manager, _plans) = recording_manager();
+    service_manager.docker_mutation_runner = Arc::new(TimeoutDockerRunner { events: events_tx });
+    service_manager
+        .project_paths
+        .insert(key.0.clone(), path.into());
+    service_manager.begin_project_incarnation(&key.0, path);
+    let (_, mut instance) = make_docker_instance(&key.0, &key.1, ServiceStatus::Running);
+    instance.terminal_id = None;
+    service_manager.instances.insert(key.clone(), instance);
+
+    let executor = Rc::new(smol::LocalExecutor::new());
+    let service_manager = Rc::new(RefCell::new(service_manager));
+    let handle = ExecutingHandle {
+        manager: Rc::downgrade(&service_manager),
+        executor: executor.clone(),
+        notifications: Arc::new(AtomicUsize::new(0)),
+    };
+
+    smol::block_on(executor.run(async {
+        let mut cx = ExecutingCx { handle };
+        service_manager
+            .borrow_mut()
+            .rebuild_service(&key.0, &key.1, path, &mut cx)
+            .unwrap();
+
+        assert_eq!(events_rx.recv().await, Ok(DockerMutationKind::Rebuild));
+        while !service_manager.borrow().docker_mutations.active.is_empty() {
+            smol::Timer::after(Duration::from_millis(1)).await;
+        }
+        assert_eq!(service_manager.borrow().instances[&key].terminal_id, None);
+        assert!(service_manager.borrow().rebuild_log_viewers.is_empty());
+    }));
+}
+
+#[test]
+fn compose_only_actions_reject_okena_services() {
+    let path = "/project";
+    let mut manager = manager();
+    manager.project_paths.insert("proj1".into(), path.into());
+    let (key, instance) = make_instance("proj1", "web", false, 0, ServiceStatus::Running);
+    manager.instances.insert(key, instance);
+    let mut cx = RecordingCx::default();
+
+    assert!(
+        manager
+            .rebuild_service("proj1", "web", path, &mut cx)
+            .is_err()
+    );
+    assert!(manager.pull_service("proj1", "web", path, &mut cx).is_err());
+    assert!(manager.service_shell("proj1", "web", None).is_err());
+    assert_eq!(
+        manager.instances[&("proj1".into(), "web".into())].status,
+        ServiceStatus::Running
+    );
+    assert_eq!(cx.spawned.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn service_shell_targets_the_absolute_compose_file() {
+    let mut manager = manager();
+    manager
+        .project_paths
+        .insert("proj1".into(), "/project".into());
+    let (key, instance) = make_docker_instance("proj1", "db", ServiceStatus::Running);
+    manager.instances.insert(key, instance);
+
+    let shell = manager.service_shell("proj1", "db", None).unwrap();
+    assert_eq!(
+        shell,
+        ShellType::DockerExec {
+            compose_file: Path::new("/project")
+                .join("docker-compose.yml")
+                .to_string_lossy()
+                .into_owned(),
+            service: "db".into(),
+            shell: None,
+        }
+    );
+}
+
 #[test]
 fn docker_start_then_immediate_stop_waits_for_running_compose_command() {
     let path = "/project";
@@ -1305,6 +1530,9 @@ fn prepared_reload_does_not_probe_the_project_path() {
                     auto_start: false,
                     restart_on_crash: false,
                     restart_delay_ms: 1000,
+                    watch: Vec::new(),
+                    ignore: Vec::new(),
+                    watch_debounce_ms: 0,
                 }],
                 docker_compose: None,
             }),
@@ -1349,6 +1577,9 @@ fn reload_replaces_docker_name_collision_with_okena_service() {
                     auto_start: false,
                     restart_on_crash: false,
                     restart_delay_ms: 1000,
+                    watch: Vec::new(),
+                    ignore: Vec::new(),
+                    watch_debounce_ms: 0,
                 }],
                 docker_compose: None,
             }),
@@ -1454,6 +1685,9 @@ fn initial_okena_launch_applies_service_environment() {
                         auto_start: true,
                         restart_on_crash: false,
                         restart_delay_ms: 1000,
+                        watch: Vec::new(),
+                        ignore: Vec::new(),
+                        watch_debounce_ms: 0,
                     }],
                     docker_compose: None,
                 }),
@@ -1522,6 +1756,9 @@ fn starting_launch_completes_after_same_project_reload() {
         auto_start: false,
         restart_on_crash: false,
         restart_delay_ms: 1000,
+        watch: Vec::new(),
+        ignore: Vec::new(),
+        watch_debounce_ms: 0,
     };
     let prepared = || PreparedProjectConfig::Loaded {
         config: Some(OkenaProjectConfig {
@@ -1634,6 +1871,9 @@ fn reload_drains_restart_while_pid_lookup_is_pending() {
                             auto_start: false,
                             restart_on_crash: false,
                             restart_delay_ms: 60_000,
+                            watch: Vec::new(),
+                            ignore: Vec::new(),
+                            watch_debounce_ms: 0,
                         }],
                         docker_compose: None,
                     }),
@@ -1765,3 +2005,117 @@ fn project_path_update_retries_after_parse_failure() {
     assert!(manager.update_project_path("project", &new_path, &mut cx));
     assert_eq!(manager.project_path("project"), Some(&new_path));
 }
+
+#[test]
+fn watch_filter_matches_relative_globs_and_honors_ignore() {
+    let root = Path::new("/project/api");
+    let filter = file_watch::WatchFilter::new(
+        root,
+        &["**/*.go".to_string(), "go.mod".to_string()],
+        &["**/*_test.go".to_string()],
+    )
+    .unwrap();
+
+    assert_eq!(
+        filter.matches(&root.join("cmd/server/main.go")),
+        Some("cmd/server/main.go".to_string())
+    );
+    assert_eq!(
+        filter.matches(&root.join("go.mod")),
+        Some("go.mod".to_string())
+    );
+    assert_eq!(filter.matches(&root.join("cmd/go.mod")), None);
+    assert_eq!(filter.matches(&root.join("handler_test.go")), None);
+    assert_eq!(filter.matches(&root.join(".git/hooks/x.go")), None);
+    assert_eq!(filter.matches(&root.join("node_modules/x/y.go")), None);
+    assert_eq!(filter.matches(Path::new("/elsewhere/main.go")), None);
+}
+
+#[test]
+fn watch_filter_honors_gitignore() {
+    let project = ProjectDir::with_config("services: []\n");
+    std::fs::write(project.0.join(".gitignore"), "/build/\n*.gen.go\n").unwrap();
+    std::fs::create_dir_all(project.0.join("build/out")).unwrap();
+    std::fs::create_dir_all(project.0.join("target/debug")).unwrap();
+    std::fs::create_dir_all(project.0.join("cmd")).unwrap();
+    let filter = file_watch::WatchFilter::new(&project.0, &["**/*.go".to_string()], &[]).unwrap();
+
+    assert_eq!(
+        filter.matches(&project.0.join("cmd/main.go")),
+        Some("cmd/main.go".to_string())
+    );
+    assert_eq!(filter.matches(&project.0.join("cmd/api.gen.go")), None);
+    assert_eq!(filter.matches(&project.0.join("build/out/main.go")), None);
+    assert!(filter.watches_dir(&project.0.join("cmd")));
+    assert!(!filter.watches_dir(&project.0.join("build/out")));
+    assert!(!filter.watches_dir(&project.0.join("target/debug")));
+}
+
+#[test]
+fn change_burst_names_the_latest_file_and_counts_the_rest() {
+    let mut burst = file_watch::ChangeBurst::new("src/a.rs".to_string());
+    burst.push("src/a.rs".to_string());
+    assert_eq!(burst.describe(), "src/a.rs");
+
+    let mut burst = file_watch::ChangeBurst::new("src/a.rs".to_string());
+    burst.push("src/b.rs".to_string());
+    burst.push("src/c.rs".to_string());
+    burst.push("src/b.rs".to_string());
+    assert_eq!(burst.describe(), "src/b.rs and 2 more");
+}
+
+#[test]
+fn watch_filter_rejects_invalid_globs() {
+    assert!(file_watch::WatchFilter::new(Path::new("/p"), &["src/[".to_string()], &[]).is_err());
+}
+
+#[test]
+fn file_change_restarts_running_service_and_records_trigger() {
+    let path = "/project";
+    let mut manager = manager();
+    manager.project_paths.insert("proj1".into(), path.into());
+    let incarnation = manager.begin_project_incarnation("proj1", path);
+    let (running, instance) = make_instance("proj1", "api", false, 0, ServiceStatus::Running);
+    manager.instances.insert(running.clone(), instance);
+    let (stopped, mut instance) =
+        make_instance("proj1", "worker", false, 0, ServiceStatus::Stopped);
+    instance.terminal_id = None;
+    manager.instances.insert(stopped.clone(), instance);
+    let mut cx = RecordingCx::default();
+
+    assert!(manager.restart_for_file_change(&running, &incarnation, "main.go".into(), &mut cx));
+    assert!(manager.restart_for_file_change(&stopped, &incarnation, "main.go".into(), &mut cx));
+
+    assert_eq!(
+        manager.instances[&running].status,
+        ServiceStatus::Restarting
+    );
+    assert_eq!(
+        manager.instances[&running]
+            .to_api()
+            .restart_trigger
+            .as_deref(),
+        Some("main.go")
+    );
+    assert_eq!(manager.instances[&stopped].status, ServiceStatus::Stopped);
+    assert!(manager.instances[&stopped].restart_trigger.is_none());
+
+    // A manual stop clears the trigger.
+    manager.stop_service("proj1", "api", &mut cx);
+    assert!(manager.instances[&running].restart_trigger.is_none());
+}
+
+#[test]
+fn file_change_for_stale_incarnation_ends_the_watch() {
+    let path = "/project";
+    let mut manager = manager();
+    manager.project_paths.insert("proj1".into(), path.into());
+    let stale = manager.begin_project_incarnation("proj1", path);
+    manager.begin_project_incarnation("proj1", path);
+    let (key, instance) = make_instance("proj1", "api", false, 0, ServiceStatus::Running);
+    manager.instances.insert(key.clone(), instance);
+    let mut cx = RecordingCx::default();
+
+    assert!(!manager.restart_for_file_change(&key, &stale, "main.go".into(), &mut cx));
+    assert_eq!(manager.instances[&key].status, ServiceStatus::Running);
+}
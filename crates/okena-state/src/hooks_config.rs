@@ -1,9 +1,15 @@
 //! Lifecycle hook configuration.
 //!
 //! Backward-compatible deserialization: accepts both the legacy flat key format
-//! and the new grouped (`project`/`terminal`/`worktree`) format.
+//! and the new grouped (`project`/`terminal`/`worktree`/`service`/`git`) format.
+//!
+//! Inside a group a hook is either a command string or a step list (see
+//! [`okena_core::hooks::HookSteps`]); step lists are kept in
+//! [`HooksConfig::steps`] and written back inline.
 
+pub use okena_core::hooks::{HookCondition, HookMergeMode, HookStep, HookSteps};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Project lifecycle hooks
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
@@ -25,6 +31,35 @@ pub struct TerminalHooks {
     /// Example: `devcontainer exec --workspace-folder $OKENA_PROJECT_PATH -- {shell}`
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub shell_wrapper: Option<String>,
+    /// A shell-integrated command finished (OSC 133 ;D).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_command_finished: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_bell: Option<String>,
+    /// The terminal raised a desktop notification (OSC 9/777/99).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_notification: Option<String>,
+}
+
+/// Service lifecycle hooks
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServiceHooks {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_crash: Option<String>,
+    /// The service reached `running` after starting or restarting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_ready: Option<String>,
+}
+
+/// Git and forge hooks, fired from the daemon's git poller.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GitHooks {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_branch_change: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_ci_status_change: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_pr_state_change: Option<String>,
 }
 
 /// Worktree lifecycle hooks
@@ -48,23 +83,69 @@ pub struct WorktreeHooks {
     pub on_dirty_close: Option<String>,
 }
 
-/// Grouped hook configuration (project, terminal, worktree).
+/// Grouped hook configuration (project, terminal, worktree, service, git).
 /// Backward-compatible: deserializes both the old flat format and the new grouped format.
-#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct HooksConfig {
-    #[serde(default, skip_serializing_if = "is_default")]
     pub project: ProjectHooks,
-    #[serde(default, skip_serializing_if = "is_default")]
     pub terminal: TerminalHooks,
-    #[serde(default, skip_serializing_if = "is_default")]
     pub worktree: WorktreeHooks,
-}
-
-fn is_default<T: Default + PartialEq>(val: &T) -> bool {
-    *val == T::default()
+    pub service: ServiceHooks,
+    pub git: GitHooks,
+    /// Step-list hooks keyed by `group.hook` (e.g. `worktree.on_create`).
+    /// The two forms share one key in the file, so a list only applies while
+    /// the matching command string is unset: setting the string (as the
+    /// settings editor does) replaces the list.
+    pub steps: BTreeMap<String, HookSteps>,
 }
 
 impl HooksConfig {
+    /// The command string configured for `hook` (`group.hook` key).
+    pub fn command(&self, hook: &str) -> Option<&String> {
+        match hook {
+            "project.on_open" => self.project.on_open.as_ref(),
+            "project.on_close" => self.project.on_close.as_ref(),
+            "terminal.on_create" => self.terminal.on_create.as_ref(),
+            "terminal.on_close" => self.terminal.on_close.as_ref(),
+            "terminal.shell_wrapper" => self.terminal.shell_wrapper.as_ref(),
+            "terminal.on_command_finished" => self.terminal.on_command_finished.as_ref(),
+            "terminal.on_bell" => self.terminal.on_bell.as_ref(),
+            "terminal.on_notification" => self.terminal.on_notification.as_ref(),
+            "worktree.on_create" => self.worktree.on_create.as_ref(),
+            "worktree.on_close" => self.worktree.on_close.as_ref(),
+            "worktree.pre_merge" => self.worktree.pre_merge.as_ref(),
+            "worktree.post_merge" => self.worktree.post_merge.as_ref(),
+            "worktree.before_remove" => self.worktree.before_remove.as_ref(),
+            "worktree.after_remove" => self.worktree.after_remove.as_ref(),
+            "worktree.on_rebase_conflict" => self.worktree.on_rebase_conflict.as_ref(),
+            "worktree.on_dirty_close" => self.worktree.on_dirty_close.as_ref(),
+            "service.on_crash" => self.service.on_crash.as_ref(),
+            "service.on_ready" => self.service.on_ready.as_ref(),
+            "git.on_branch_change" => self.git.on_branch_change.as_ref(),
+            "git.on_ci_status_change" => self.git.on_ci_status_change.as_ref(),
+            "git.on_pr_state_change" => self.git.on_pr_state_change.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Whether `hook` is set at this level, as a command or a step list.
+    pub fn has_hook(&self, hook: &str) -> bool {
+        self.steps.contains_key(hook) || self.command(hook).is_some()
+    }
+
+    /// This level's steps for `hook`: the command string as a single
+    /// replacing step if one is set, otherwise its step list.
+    pub fn hook_steps(&self, hook: &str) -> Option<HookSteps> {
+        if let Some(command) = self.command(hook) {
+            return Some(HookSteps {
+                merge: HookMergeMode::Replace,
+                timeout: None,
+                steps: vec![HookStep::command(command.clone())],
+            });
+        }
+        self.steps.get(hook).cloned()
+    }
+
     /// Project onto the wire mirror in `okena-core` (see [`okena_core::api::ApiHooksConfig`]).
     pub fn to_api(&self) -> okena_core::api::ApiHooksConfig {
         okena_core::api::ApiHooksConfig {
@@ -76,6 +157,9 @@ impl HooksConfig {
                 on_create: self.terminal.on_create.clone(),
                 on_close: self.terminal.on_close.clone(),
                 shell_wrapper: self.terminal.shell_wrapper.clone(),
+                on_command_finished: self.terminal.on_command_finished.clone(),
+                on_bell: self.terminal.on_bell.clone(),
+                on_notification: self.terminal.on_notification.clone(),
             },
             worktree: okena_core::api::ApiWorktreeHooks {
                 on_create: self.worktree.on_create.clone(),
@@ -87,6 +171,16 @@ impl HooksConfig {
                 on_rebase_conflict: self.worktree.on_rebase_conflict.clone(),
                 on_dirty_close: self.worktree.on_dirty_close.clone(),
             },
+            service: okena_core::api::ApiServiceHooks {
+                on_crash: self.service.on_crash.clone(),
+                on_ready: self.service.on_ready.clone(),
+            },
+            git: okena_core::api::ApiGitHooks {
+                on_branch_change: self.git.on_branch_change.clone(),
+                on_ci_status_change: self.git.on_ci_status_change.clone(),
+                on_pr_state_change: self.git.on_pr_state_change.clone(),
+            },
+            steps: self.steps.clone(),
         }
     }
 
@@ -101,6 +195,9 @@ impl HooksConfig {
                 on_create: api.terminal.on_create.clone(),
                 on_close: api.terminal.on_close.clone(),
                 shell_wrapper: api.terminal.shell_wrapper.clone(),
+                on_command_finished: api.terminal.on_command_finished.clone(),
+                on_bell: api.terminal.on_bell.clone(),
+                on_notification: api.terminal.on_notification.clone(),
             },
             worktree: WorktreeHooks {
                 on_create: api.worktree.on_create.clone(),
@@ -112,10 +209,22 @@ impl HooksConfig {
                 on_rebase_conflict: api.worktree.on_rebase_conflict.clone(),
                 on_dirty_close: api.worktree.on_dirty_close.clone(),
             },
+            service: ServiceHooks {
+                on_crash: api.service.on_crash.clone(),
+                on_ready: api.service.on_ready.clone(),
+            },
+            git: GitHooks {
+                on_branch_change: api.git.on_branch_change.clone(),
+                on_ci_status_change: api.git.on_ci_status_change.clone(),
+                on_pr_state_change: api.git.on_pr_state_change.clone(),
+            },
+            steps: api.steps.clone(),
         }
     }
 }
 
+const HOOK_GROUPS: &[&str] = &["project", "terminal", "worktree", "service", "git"];
+
 const FLAT_HOOK_KEYS: &[&str] = &[
     "on_project_open",
     "on_project_close",
@@ -137,9 +246,7 @@ impl<'de> Deserialize<'de> for HooksConfig {
             None => return Ok(HooksConfig::default()),
         };
 
-        let is_new_format = obj.contains_key("project")
-            || obj.contains_key("terminal")
-            || obj.contains_key("worktree");
+        let is_new_format = HOOK_GROUPS.iter().any(|group| obj.contains_key(*group));
         let has_flat_keys = !is_new_format && FLAT_HOOK_KEYS.iter().any(|k| obj.contains_key(*k));
 
         if has_flat_keys {
@@ -162,12 +269,31 @@ impl<'de> Deserialize<'de> for HooksConfig {
                     on_rebase_conflict: s("on_rebase_conflict"),
                     on_dirty_close: s("on_dirty_worktree_close"),
                 },
+                service: ServiceHooks::default(),
+                git: GitHooks::default(),
+                steps: BTreeMap::new(),
             })
         } else {
-            let deser = |key: &str| -> serde_json::Value {
-                obj.get(key)
-                    .cloned()
-                    .unwrap_or(serde_json::Value::Object(serde_json::Map::new()))
+            let mut steps = BTreeMap::new();
+            let mut deser = |group: &str| -> serde_json::Value {
+                let mut fields = match obj.get(group) {
+                    Some(serde_json::Value::Object(fields)) => fields.clone(),
+                    _ => serde_json::Map::new(),
+                };
+                // Lift step lists out so the string-typed groups still parse.
+                fields.retain(|hook, value| {
+                    if value.is_string() || value.is_null() {
+                        return true;
+                    }
+                    match serde_json::from_value::<HookSteps>(value.clone()) {
+                        Ok(parsed) => {
+                            steps.insert(format!("{group}.{hook}"), parsed);
+                        }
+                        Err(e) => log::warn!("Ignoring invalid hook steps for {group}.{hook}: {e}"),
+                    }
+                    false
+                });
+                serde_json::Value::Object(fields)
             };
             let project: ProjectHooks =
                 serde_json::from_value(deser("project")).unwrap_or_default();
@@ -175,15 +301,57 @@ impl<'de> Deserialize<'de> for HooksConfig {
                 serde_json::from_value(deser("terminal")).unwrap_or_default();
             let worktree: WorktreeHooks =
                 serde_json::from_value(deser("worktree")).unwrap_or_default();
+            let service: ServiceHooks =
+                serde_json::from_value(deser("service")).unwrap_or_default();
+            let git: GitHooks = serde_json::from_value(deser("git")).unwrap_or_default();
             Ok(HooksConfig {
                 project,
                 terminal,
                 worktree,
+                service,
+                git,
+                steps,
             })
         }
     }
 }
 
+impl Serialize for HooksConfig {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::Error;
+
+        let mut groups = serde_json::Map::new();
+        for (group, value) in [
+            ("project", serde_json::to_value(&self.project)),
+            ("terminal", serde_json::to_value(&self.terminal)),
+            ("worktree", serde_json::to_value(&self.worktree)),
+            ("service", serde_json::to_value(&self.service)),
+            ("git", serde_json::to_value(&self.git)),
+        ] {
+            groups.insert(group.to_string(), value.map_err(S::Error::custom)?);
+        }
+        // Step lists go back inline, next to the string hooks of their group,
+        // unless a command string took their key.
+        for (key, steps) in &self.steps {
+            let Some((group, hook)) = key.split_once('.') else {
+                continue;
+            };
+            if self.command(key).is_some() {
+                continue;
+            }
+            if let Some(serde_json::Value::Object(fields)) = groups.get_mut(group) {
+                fields.insert(
+                    hook.to_string(),
+                    serde_json::to_value(steps).map_err(S::Error::custom)?,
+                );
+            }
+        }
+        // Empty groups are omitted, matching the derived field defaults.
+        groups.retain(|_, value| value.as_object().is_some_and(|fields| !fields.is_empty()));
+        groups.serialize(serializer)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -290,8 +458,7 @@ mod tests {
                 on_open: Some("setup".into()),
                 on_close: None,
             },
-            terminal: TerminalHooks::default(),
-            worktree: WorktreeHooks::default(),
+            ..Default::default()
         };
 
         let json = serde_json::to_value(&config).unwrap();
@@ -383,6 +550,9 @@ mod tests {
                 on_create: Some("t-create".into()),
                 on_close: Some("t-close".into()),
                 shell_wrapper: Some("wrap {shell}".into()),
+                on_command_finished: Some("t-finished".into()),
+                on_bell: Some("t-bell".into()),
+                on_notification: Some("t-notify".into()),
             },
             worktree: WorktreeHooks {
                 on_create: Some("w-create".into()),
@@ -394,15 +564,137 @@ mod tests {
                 on_rebase_conflict: Some("rebase".into()),
                 on_dirty_close: Some("dirty".into()),
             },
+            service: ServiceHooks {
+                on_crash: Some("s-crash".into()),
+                on_ready: Some("s-ready".into()),
+            },
+            git: GitHooks {
+                on_branch_change: Some("g-branch".into()),
+                on_ci_status_change: Some("g-ci".into()),
+                on_pr_state_change: Some("g-pr".into()),
+            },
+            steps: BTreeMap::from([(
+                "worktree.on_create".to_string(),
+                HookSteps {
+                    merge: HookMergeMode::Append,
+                    timeout: None,
+                    steps: vec![HookStep::command("w-step")],
+                },
+            )]),
         };
         let back = HooksConfig::from_api(&original.to_api());
         assert_eq!(back, original);
     }
 
+    #[test]
+    fn parses_event_hook_groups() {
+        let json = r#"{
+            "terminal": { "on_command_finished": "notify-send done", "on_bell": "beep" },
+            "service": { "on_crash": "alert $OKENA_SERVICE_NAME" },
+            "git": { "on_branch_change": ["npm ci"] }
+        }"#;
+        let config: HooksConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            config.terminal.on_command_finished.as_deref(),
+            Some("notify-send done")
+        );
+        assert_eq!(
+            config.command("terminal.on_bell").map(String::as_str),
+            Some("beep")
+        );
+        assert_eq!(
+            config.command("service.on_crash").map(String::as_str),
+            Some("alert $OKENA_SERVICE_NAME")
+        );
+        assert!(config.has_hook("git.on_branch_change"));
+        assert!(!config.has_hook("git.on_pr_state_change"));
+
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(
+            json["git"]["on_branch_change"],
+            serde_json::json!(["npm ci"])
+        );
+        assert!(json.get("worktree").is_none());
+    }
+
     #[test]
     fn api_round_trip_default_is_empty() {
         let api = HooksConfig::default().to_api();
         assert!(api.is_empty());
         assert_eq!(HooksConfig::from_api(&api), HooksConfig::default());
     }
+
+    #[test]
+    fn step_lists_are_lifted_and_written_back_inline() {
+        let json = r#"{
+            "project": { "on_open": "echo open" },
+            "worktree": {
+                "pre_merge": "lint",
+                "on_create": [
+                    "npm ci",
+                    { "run": "cargo build", "when": { "exists": "Cargo.toml" }, "timeout": 600 }
+                ],
+                "post_merge": { "merge": "append", "steps": ["notify"] }
+            }
+        }"#;
+
+        let config: HooksConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.project.on_open.as_deref(), Some("echo open"));
+        assert_eq!(config.worktree.pre_merge.as_deref(), Some("lint"));
+        assert!(config.worktree.on_create.is_none());
+        let on_create = &config.steps["worktree.on_create"];
+        assert_eq!(on_create.merge, HookMergeMode::Replace);
+        assert_eq!(on_create.steps.len(), 2);
+        assert_eq!(on_create.steps[1].timeout, Some(600));
+        assert_eq!(
+            config.steps["worktree.post_merge"].merge,
+            HookMergeMode::Append
+        );
+
+        let value = serde_json::to_value(&config).unwrap();
+        assert_eq!(value["worktree"]["on_create"][0], "npm ci");
+        assert_eq!(value["worktree"]["post_merge"]["merge"], "append");
+        assert!(value.get("steps").is_none());
+        let reparsed: HooksConfig = serde_json::from_value(value).unwrap();
+        assert_eq!(reparsed, config);
+    }
+
+    #[test]
+    fn invalid_step_list_is_dropped_without_losing_the_group() {
+        let json = r#"{ "worktree": { "pre_merge": "lint", "on_create": [{ "cmd": "x" }] } }"#;
+        let config: HooksConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.worktree.pre_merge.as_deref(), Some("lint"));
+        assert!(config.steps.is_empty());
+    }
+
+    #[test]
+    fn hook_steps_prefers_the_command_over_the_step_list() {
+        let mut config = HooksConfig::default();
+        let steps = HookSteps {
+            merge: HookMergeMode::Append,
+            timeout: None,
+            steps: vec![HookStep::command("test")],
+        };
+        config
+            .steps
+            .insert("worktree.pre_merge".into(), steps.clone());
+        assert_eq!(config.hook_steps("worktree.pre_merge"), Some(steps));
+        assert!(config.hook_steps("worktree.post_merge").is_none());
+
+        // An edit of the string form, as the settings editor makes, wins and
+        // replaces the list on save.
+        config.worktree.pre_merge = Some("lint".into());
+        assert_eq!(
+            config.hook_steps("worktree.pre_merge"),
+            Some(HookSteps {
+                merge: HookMergeMode::Replace,
+                timeout: None,
+                steps: vec![HookStep::command("lint")],
+            })
+        );
+        let saved: HooksConfig =
+            serde_json::from_value(serde_json::to_value(&config).unwrap()).unwrap();
+        assert_eq!(saved.worktree.pre_merge.as_deref(), Some("lint"));
+        assert!(saved.steps.is_empty());
+    }
 }
@@ -15,7 +15,10 @@ mod window_state;
 mod windows;
 mod workspace_data;
 
-pub use hooks_config::{HooksConfig, ProjectHooks, TerminalHooks, WorktreeHooks};
+pub use hooks_config::{
+    GitHooks, HookCondition, HookMergeMode, HookStep, HookSteps, HooksConfig, ProjectHooks,
+    ServiceHooks, TerminalHooks, WorktreeHooks,
+};
 pub use okena_layout::{LayoutNode, SplitDirection};
 pub use toast::{Toast, ToastAction, ToastActionStyle, ToastLevel};
 pub use transient::{DropZone, FocusedTerminalState, PendingWorktreeClose};
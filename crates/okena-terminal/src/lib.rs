@@ -5,6 +5,7 @@ pub mod input;
 /// macOS process introspection via libproc (replaces `pgrep`/`lsof`/`ps`).
 #[cfg(target_os = "macos")]
 pub mod macos_proc;
+pub mod output_matcher;
 pub mod process;
 pub mod pty_manager;
 pub mod session_backend;
@@ -0,0 +1,169 @@
+//! Streaming regex matching over raw PTY output (`okena wait-for`).
+//!
+//! PTY bytes are run through a VTE parser that keeps only printable text, line
+//! feeds and tabs, so escape sequences (colors, cursor moves, OSC titles) never
+//! split or pollute a match — including sequences that straddle two chunks.
+//! The decoded text accumulates in a bounded window and the regex is re-run
+//! after every chunk, so a match that spans chunk boundaries is still found.
+
+use alacritty_terminal::vte::{Parser, Perform};
+use okena_core::api::ApiOutputMatch;
+use regex::{Regex, RegexBuilder};
+
+/// Upper bound on retained decoded text. Older output is dropped from the
+/// front; a match has to fit inside this window.
+const MAX_WINDOW_BYTES: usize = 64 * 1024;
+
+pub struct OutputMatcher {
+    regex: Regex,
+    parser: Parser,
+    text: TextCollector,
+}
+
+impl OutputMatcher {
+    /// Compile `pattern` multi-line, so `^`/`$` anchor at line breaks.
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let regex = RegexBuilder::new(pattern)
+            .multi_line(true)
+            .build()
+            .map_err(|e| format!("invalid regex: {e}"))?;
+        Ok(Self {
+            regex,
+            parser: Parser::new(),
+            text: TextCollector::default(),
+        })
+    }
+
+    /// Add already-rendered text (e.g. the visible screen) ahead of the live
+    /// stream. Terminated with a line break so it can't fuse with new output.
+    pub fn seed(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        self.text.buf.push_str(text);
+        self.text.buf.push('\n');
+        self.text.trim();
+    }
+
+    /// Replace the window with already-rendered `text`, dropping any escape
+    /// sequence in progress. For when the live stream skipped output: the
+    /// caller re-reads the terminal and matching continues from that.
+    pub fn resync(&mut self, text: &str) {
+        self.parser = Parser::new();
+        self.text.buf.clear();
+        self.seed(text);
+    }
+
+    /// Feed raw PTY bytes.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.parser.advance(&mut self.text, bytes);
+        self.text.trim();
+    }
+
+    /// The first match in the retained window, if any.
+    pub fn find(&self) -> Option<ApiOutputMatch> {
+        let caps = self.regex.captures(&self.text.buf)?;
+        let matched = caps.get(0)?.as_str().to_string();
+        let groups = caps
+            .iter()
+            .skip(1)
+            .map(|m| m.map(|m| m.as_str().to_string()))
+            .collect();
+        let named = self
+            .regex
+            .capture_names()
+            .flatten()
+            .filter_map(|name| Some((name.to_string(), caps.name(name)?.as_str().to_string())))
+            .collect();
+        Some(ApiOutputMatch {
+            matched,
+            groups,
+            named,
+        })
+    }
+}
+
+#[derive(Default)]
+struct TextCollector {
+    buf: String,
+}
+
+impl TextCollector {
+    fn trim(&mut self) {
+        if self.buf.len() <= MAX_WINDOW_BYTES {
+            return;
+        }
+        let mut cut = self.buf.len() - MAX_WINDOW_BYTES;
+        while !self.buf.is_char_boundary(cut) {
+            cut += 1;
+        }
+        self.buf.drain(..cut);
+    }
+}
+
+impl Perform for TextCollector {
+    fn print(&mut self, c: char) {
+        self.buf.push(c);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            b'\n' => self.buf.push('\n'),
+            b'\t' => self.buf.push('\t'),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_across_chunks_and_escape_sequences() {
+        let mut matcher = OutputMatcher::new(r"ready on port (\d+)").unwrap();
+        matcher.feed(b"\x1b[32mready on po");
+        assert!(matcher.find().is_none());
+        // A color reset split across two chunks must not leak into the text.
+        matcher.feed(b"rt 30\x1b[");
+        matcher.feed(b"0m81\r\n");
+        let found = matcher.find().expect("match");
+        assert_eq!(found.matched, "ready on port 3081");
+        assert_eq!(found.groups, vec![Some("3081".to_string())]);
+    }
+
+    #[test]
+    fn seeded_screen_and_named_groups() {
+        let mut matcher = OutputMatcher::new(r"^(?P<prompt>>>>)\s*$").unwrap();
+        // The screen read trims trailing blanks, so the prompt arrives bare.
+        matcher.seed("Python 3.12\n>>>");
+        let found = matcher.find().expect("prompt on screen");
+        assert_eq!(found.named.get("prompt").map(String::as_str), Some(">>>"));
+    }
+
+    #[test]
+    fn resync_replaces_the_window() {
+        let mut matcher = OutputMatcher::new(r"done \d+").unwrap();
+        matcher.feed(b"building \x1b[3");
+        matcher.resync("step 1\ndone 7");
+        assert_eq!(matcher.find().expect("match").matched, "done 7");
+        // The half-read escape before the resync must not eat new output.
+        matcher.resync("");
+        matcher.feed(b"1mdone 8");
+        assert_eq!(matcher.find().expect("match").matched, "done 8");
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(OutputMatcher::new("(unclosed").is_err());
+    }
+
+    #[test]
+    fn window_is_bounded() {
+        let mut matcher = OutputMatcher::new("never").unwrap();
+        for _ in 0..100 {
+            matcher.feed(&[b'x'; 4096]);
+        }
+        assert!(matcher.text.buf.len() <= MAX_WINDOW_BYTES);
+    }
+}
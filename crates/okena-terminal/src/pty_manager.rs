@@ -1030,6 +1030,7 @@ impl PtyManager {
         launch_environment: &[(String, Option<String>)],
     ) -> CommandBuilder {
         let session_backend = self.session_backend();
+        let docker_exec = plan.route.docker_exec_command();
         // Extract custom command from ShellType::Custom{path:<shell>, args:["-c"/"-ic", cmd]}
         // so it can be passed to the session backend
         let custom_command = plan.initial_command.as_ref().map_or_else(
@@ -1039,6 +1040,14 @@ impl PtyManager {
                 {
                     Some(SessionCommand::ShellScript(args[1].as_str()))
                 }
+                ShellType::DockerExec { .. } => {
+                    docker_exec
+                        .as_ref()
+                        .map(|(program, args)| SessionCommand::Program {
+                            program: program.as_str(),
+                            args,
+                        })
+                }
                 _ => None,
             },
             |command| {
@@ -1098,6 +1107,7 @@ impl PtyManager {
         let session_backend = self.session_backend();
         let session_backend_preference = self.session_backend_preference();
 
+        let docker_exec = plan.route.docker_exec_command();
         // Preserve the exact executable and argv for psmux. In particular,
         // keep-alive hooks require cmd.exe delayed expansion via `/V:ON`.
         let custom_command = plan.initial_command.as_ref().map_or_else(
@@ -1106,6 +1116,14 @@ impl PtyManager {
                     program: path.as_str(),
                     args,
                 }),
+                ShellType::DockerExec { .. } => {
+                    docker_exec
+                        .as_ref()
+                        .map(|(program, args)| SessionCommand::Program {
+                            program: program.as_str(),
+                            args,
+                        })
+                }
                 _ => None,
             },
             |command| {
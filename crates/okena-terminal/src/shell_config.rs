@@ -68,6 +68,15 @@ impl ShellCommandExt for ShellType {
                 cmd.cwd(cwd);
                 cmd
             }
+            ShellType::DockerExec { .. } => {
+                let (program, args) = self.docker_exec_command().unwrap_or_default();
+                let mut cmd = CommandBuilder::new(program);
+                for arg in args {
+                    cmd.arg(arg);
+                }
+                cmd.cwd(cwd);
+                cmd
+            }
         }
     }
 }
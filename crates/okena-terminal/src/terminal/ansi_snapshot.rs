@@ -1,6 +1,6 @@
-use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::grid::{Dimensions, Grid};
 use alacritty_terminal::index::{Column, Line, Point};
-use alacritty_terminal::term::cell::Flags;
+use alacritty_terminal::term::cell::{Cell, Flags};
 use alacritty_terminal::term::{Term, TermMode};
 use alacritty_terminal::vte::ansi::{Color, NamedColor};
 
@@ -39,62 +39,12 @@ pub(super) fn grid_to_ansi(term: &Term<ZedEventListener>) -> Vec<u8> {
     // before the snapshot body renders.
     buf.extend_from_slice(b"\x1b[2J\x1b[3J\x1b[H");
 
-    let default_fg = Color::Named(NamedColor::Foreground);
-    let default_bg = Color::Named(NamedColor::Background);
-
     let mut current = SgrState::default();
 
     for row in 0..screen_lines as i32 {
         // Position cursor at start of row
         write_csi_pos(&mut buf, row + 1, 1);
-
-        let mut col_idx = 0usize;
-        while col_idx < cols {
-            let cell = &grid[Point::new(Line(row), Column(col_idx))];
-
-            // Skip wide char spacer cells
-            if cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
-                col_idx += 1;
-                continue;
-            }
-
-            // Determine desired SGR state
-            let desired = SgrState {
-                bold: cell.flags.contains(Flags::BOLD),
-                dim: cell.flags.contains(Flags::DIM),
-                italic: cell.flags.contains(Flags::ITALIC),
-                underline: cell.flags.intersects(Flags::ALL_UNDERLINES),
-                inverse: cell.flags.contains(Flags::INVERSE),
-                strikeout: cell.flags.contains(Flags::STRIKEOUT),
-                fg: if cell.fg == default_fg {
-                    None
-                } else {
-                    Some(cell.fg)
-                },
-                bg: if cell.bg == default_bg {
-                    None
-                } else {
-                    Some(cell.bg)
-                },
-            };
-
-            if desired != current {
-                emit_sgr(&mut buf, &desired);
-                current = desired;
-            }
-
-            // Write the character
-            let c = cell.c;
-            if c == '\0' || c == ' ' {
-                buf.push(b' ');
-            } else {
-                let mut utf8_buf = [0u8; 4];
-                let encoded = c.encode_utf8(&mut utf8_buf);
-                buf.extend_from_slice(encoded.as_bytes());
-            }
-
-            col_idx += 1;
-        }
+        write_cells(&mut buf, grid, Line(row), 0, cols, &mut current);
     }
 
     // Reset attributes
@@ -111,6 +61,87 @@ pub(super) fn grid_to_ansi(term: &Term<ZedEventListener>) -> Vec<u8> {
     buf
 }
 
+/// Serialize one grid row (history lines included) from column `from` as
+/// SGR-styled text, without cursor positioning. Trailing blank cells with the
+/// default background are dropped and attributes are reset at the end, so
+/// rows can be joined with `\n` into a scrollable transcript.
+pub(super) fn row_to_ansi(grid: &Grid<Cell>, line: Line, from: usize) -> Vec<u8> {
+    let default_bg = Color::Named(NamedColor::Background);
+    let row = &grid[line];
+    let end = (from..grid.columns())
+        .rev()
+        .find(|&col| {
+            let cell = &row[Column(col)];
+            !matches!(cell.c, ' ' | '\0') || cell.bg != default_bg
+        })
+        .map_or(from, |col| col + 1);
+
+    let mut buf = Vec::new();
+    let mut current = SgrState::default();
+    write_cells(&mut buf, grid, line, from, end, &mut current);
+    if current != SgrState::default() {
+        buf.extend_from_slice(b"\x1b[0m");
+    }
+    buf
+}
+
+/// Append cells `from..to` of `line`, emitting SGR only when the style changes.
+fn write_cells(
+    buf: &mut Vec<u8>,
+    grid: &Grid<Cell>,
+    line: Line,
+    from: usize,
+    to: usize,
+    current: &mut SgrState,
+) {
+    let default_fg = Color::Named(NamedColor::Foreground);
+    let default_bg = Color::Named(NamedColor::Background);
+
+    for col_idx in from..to {
+        let cell = &grid[Point::new(line, Column(col_idx))];
+
+        // Skip wide char spacer cells
+        if cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+            continue;
+        }
+
+        // Determine desired SGR state
+        let desired = SgrState {
+            bold: cell.flags.contains(Flags::BOLD),
+            dim: cell.flags.contains(Flags::DIM),
+            italic: cell.flags.contains(Flags::ITALIC),
+            underline: cell.flags.intersects(Flags::ALL_UNDERLINES),
+            inverse: cell.flags.contains(Flags::INVERSE),
+            strikeout: cell.flags.contains(Flags::STRIKEOUT),
+            fg: if cell.fg == default_fg {
+                None
+            } else {
+                Some(cell.fg)
+            },
+            bg: if cell.bg == default_bg {
+                None
+            } else {
+                Some(cell.bg)
+            },
+        };
+
+        if desired != *current {
+            emit_sgr(buf, &desired);
+            *current = desired;
+        }
+
+        // Write the character
+        let c = cell.c;
+        if c == '\0' || c == ' ' {
+            buf.push(b' ');
+        } else {
+            let mut utf8_buf = [0u8; 4];
+            let encoded = c.encode_utf8(&mut utf8_buf);
+            buf.extend_from_slice(encoded.as_bytes());
+        }
+    }
+}
+
 /// Write CSI cursor position: `\x1b[{row};{col}H`
 fn write_csi_pos(buf: &mut Vec<u8>, row: i32, col: i32) {
     use std::io::Write;
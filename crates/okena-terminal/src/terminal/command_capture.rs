@@ -0,0 +1,208 @@
+use alacritty_terminal::vte::{Parser, Perform};
+
+use super::prompt_marks::parse_osc133_kind;
+use super::types::PromptMarkKind;
+
+/// Cap on captured output. Past it the oldest text is dropped, so the tail —
+/// where errors usually are — survives.
+const MAX_CAPTURE_BYTES: usize = 1024 * 1024;
+
+/// Output and exit code of one shell-integrated command, as cut out of the
+/// raw PTY stream by [`CommandCapture`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapturedCommand {
+    /// Printable output between `OSC 133 ; C` and `OSC 133 ; D`, with escape
+    /// sequences stripped.
+    pub output: String,
+    /// Exit code carried by the `D` mark, if the shell sent one.
+    pub exit_code: Option<i32>,
+    /// Older output was dropped to stay under the capture cap.
+    pub truncated: bool,
+}
+
+/// Watches a PTY byte stream for the next shell-integrated command and
+/// collects its output.
+///
+/// Feed it everything the terminal prints after the command was typed. Text
+/// between `OSC 133 ; C` (command executing) and `OSC 133 ; D` (finished) is
+/// the command's output. Shells that skip `C` still get a result: everything
+/// after the echoed command line counts as output.
+pub struct CommandCapture {
+    parser: Parser,
+    perform: CapturePerform,
+}
+
+impl Default for CommandCapture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandCapture {
+    pub fn new() -> Self {
+        Self {
+            parser: Parser::new(),
+            perform: CapturePerform {
+                pre_start: String::new(),
+                output: String::new(),
+                executing: false,
+                truncated: false,
+                finished: None,
+            },
+        }
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        if self.perform.finished.is_some() {
+            return;
+        }
+        self.parser.advance(&mut self.perform, bytes);
+    }
+
+    /// The captured command once its `D` mark arrived.
+    pub fn finished(&self) -> Option<CapturedCommand> {
+        let exit_code = self.perform.finished?;
+        let output = if self.perform.executing {
+            self.perform.output.clone()
+        } else {
+            // No `C` mark: drop the echoed command line.
+            self.perform
+                .pre_start
+                .split_once('\n')
+                .map(|(_, rest)| rest.to_string())
+                .unwrap_or_default()
+        };
+        Some(CapturedCommand {
+            output,
+            exit_code,
+            truncated: self.perform.truncated,
+        })
+    }
+}
+
+struct CapturePerform {
+    /// Text seen before `C` (echo of the typed command, prompt redraws).
+    pre_start: String,
+    output: String,
+    executing: bool,
+    truncated: bool,
+    /// `Some(exit_code)` once `D` arrived.
+    finished: Option<Option<i32>>,
+}
+
+impl CapturePerform {
+    fn push(&mut self, c: char) {
+        if self.finished.is_some() {
+            return;
+        }
+        let buf = if self.executing {
+            &mut self.output
+        } else {
+            &mut self.pre_start
+        };
+        buf.push(c);
+        if buf.len() > MAX_CAPTURE_BYTES {
+            // Trim down to 3/4 of the cap so the drain's memmove happens once
+            // per quarter-megabyte, not once per printed char.
+            let mut cut = buf.len() - MAX_CAPTURE_BYTES / 4 * 3;
+            while !buf.is_char_boundary(cut) {
+                cut += 1;
+            }
+            buf.drain(..cut);
+            self.truncated = true;
+        }
+    }
+}
+
+impl Perform for CapturePerform {
+    fn print(&mut self, c: char) {
+        self.push(c);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            b'\n' => self.push('\n'),
+            b'\t' => self.push('\t'),
+            _ => {}
+        }
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], _bell_terminated: bool) {
+        if params.first().copied() != Some(b"133".as_ref()) || self.finished.is_some() {
+            return;
+        }
+        let Some(&kind_byte) = params.get(1).and_then(|p| p.first()) else {
+            return;
+        };
+        match parse_osc133_kind(kind_byte, &params[2..]) {
+            Some(PromptMarkKind::CommandExecuted) => {
+                self.executing = true;
+                self.truncated = false;
+            }
+            Some(PromptMarkKind::CommandFinished { exit_code }) => {
+                self.finished = Some(exit_code);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_output_between_c_and_d() {
+        let mut capture = CommandCapture::new();
+        capture.feed(b"cargo test\r\n\x1b]133;C\x07running 3 tests\r\n\x1b[32mok\x1b[0m\r\n");
+        assert!(capture.finished().is_none());
+        capture.feed(b"\x1b]133;D;101\x07\x1b]133;A\x07$ ");
+        let done = capture.finished().expect("finished");
+        assert_eq!(done.output, "running 3 tests\nok\n");
+        assert_eq!(done.exit_code, Some(101));
+        assert!(!done.truncated);
+    }
+
+    #[test]
+    fn marks_split_across_chunks_still_register() {
+        let mut capture = CommandCapture::new();
+        capture.feed(b"ls\r\n\x1b]13");
+        capture.feed(b"3;C\x07a b\r\n\x1b]133;");
+        capture.feed(b"D;0\x1b\\");
+        let done = capture.finished().expect("finished");
+        assert_eq!(done.output, "a b\n");
+        assert_eq!(done.exit_code, Some(0));
+    }
+
+    #[test]
+    fn without_c_mark_skips_the_echoed_command_line() {
+        let mut capture = CommandCapture::new();
+        capture.feed(b"echo hi\r\nhi\r\n\x1b]133;D\x07");
+        let done = capture.finished().expect("finished");
+        assert_eq!(done.output, "hi\n");
+        assert_eq!(done.exit_code, None);
+    }
+
+    #[test]
+    fn output_after_d_is_ignored() {
+        let mut capture = CommandCapture::new();
+        capture.feed(b"\x1b]133;C\x07one\n\x1b]133;D;0\x07two\n\x1b]133;C\x07three\n");
+        assert_eq!(capture.finished().expect("finished").output, "one\n");
+    }
+
+    #[test]
+    fn long_output_keeps_the_tail_under_the_cap() {
+        let mut capture = CommandCapture::new();
+        capture.feed(b"\x1b]133;C\x07");
+        let line = [b'x'; 1023];
+        for _ in 0..2048 {
+            capture.feed(&line);
+            capture.feed(b"\n");
+        }
+        capture.feed(b"tail\x1b]133;D;0\x07");
+        let done = capture.finished().expect("finished");
+        assert!(done.truncated);
+        assert!(done.output.len() <= MAX_CAPTURE_BYTES);
+        assert!(done.output.ends_with("\ntail"));
+    }
+}
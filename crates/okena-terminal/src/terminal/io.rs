@@ -8,6 +8,9 @@ use super::{InputRepaintRequest, Terminal};
 
 const INPUT_REPAINT_REQUEST_TTL: Duration = Duration::from_secs(5);
 
+/// Finished commands kept for the PTY event loop; older ones are dropped.
+const MAX_PENDING_FINISHED_COMMANDS: usize = 64;
+
 impl Terminal {
     /// Process output from PTY
     pub fn process_output(&self, data: &[u8]) {
@@ -39,15 +42,15 @@ impl Terminal {
         // byte where each mark arrives. `advance_until_terminated` stops
         // the prompt sidecar at every OSC 133 so the main processor can
         // catch up before we read `grid.cursor.point`.
-        let command_finished = advance_with_prompt_marks(
+        let finished = advance_with_prompt_marks(
             &mut *term,
             &mut processor,
             &mut prompt_sidecar,
             &mut prompt_tracker,
             data,
         );
-        if command_finished {
-            self.command_finished_pending.store(true, Ordering::Relaxed);
+        if !finished.is_empty() {
+            self.queue_finished_commands(finished);
         }
 
         let history_after = term.grid().history_size();
@@ -56,6 +59,11 @@ impl Terminal {
             history_after,
             term.grid().topmost_line().0,
         );
+        self.read_marks.lock().on_history_changed(
+            history_before,
+            history_after,
+            term.grid().topmost_line().0,
+        );
 
         // New output disengages the prompt-jump walker so the next
         // Above jump starts from the newest prompt again.
@@ -132,15 +140,15 @@ impl Terminal {
 
         let history_before = term.grid().history_size();
         sidecar.advance(&data);
-        let command_finished = advance_with_prompt_marks(
+        let finished = advance_with_prompt_marks(
             &mut *term,
             &mut processor,
             &mut prompt_sidecar,
             &mut prompt_tracker,
             &data,
         );
-        if command_finished {
-            self.command_finished_pending.store(true, Ordering::Relaxed);
+        if !finished.is_empty() {
+            self.queue_finished_commands(finished);
         }
         let history_after = term.grid().history_size();
         prompt_tracker.on_history_changed(
@@ -148,11 +156,23 @@ impl Terminal {
             history_after,
             term.grid().topmost_line().0,
         );
+        self.read_marks.lock().on_history_changed(
+            history_before,
+            history_after,
+            term.grid().topmost_line().0,
+        );
         self.content_generation.fetch_add(1, Ordering::Relaxed);
         self.processed_output_epoch
             .fetch_max(output_epoch, Ordering::Release);
     }
 
+    fn queue_finished_commands(&self, finished: Vec<super::FinishedCommand>) {
+        let mut pending = self.pending_finished_commands.lock();
+        pending.extend(finished);
+        let overflow = pending.len().saturating_sub(MAX_PENDING_FINISHED_COMMANDS);
+        pending.drain(..overflow);
+    }
+
     /// Check if terminal has pending changes (and clear the flag).
     /// Used by PTY event loop for direct content pane notification.
     pub fn take_dirty(&self) -> bool {
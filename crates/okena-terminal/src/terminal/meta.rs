@@ -135,14 +135,13 @@ impl Terminal {
             .swap(false, std::sync::atomic::Ordering::Relaxed)
     }
 
-    /// Consume the one-shot "a command finished (OSC 133 ;D) since last drain"
-    /// edge. Returns true if a command completed since the previous call, then
-    /// resets it. The PTY event loop uses this to bump the owning project's
-    /// activity timestamp once per finished command (drives the activity-sorted
-    /// sidebar view). Shells without OSC 133 shell integration never raise it.
-    pub fn take_pending_command_finished(&self) -> bool {
-        self.command_finished_pending
-            .swap(false, std::sync::atomic::Ordering::Relaxed)
+    /// Drain the commands that finished (OSC 133 ;D) since the previous call.
+    /// The PTY event loop uses these to bump the owning project's activity
+    /// timestamp (drives the activity-sorted sidebar view) and to fire
+    /// `terminal.on_command_finished`. Shells without OSC 133 shell
+    /// integration never produce any.
+    pub fn take_finished_commands(&self) -> Vec<super::FinishedCommand> {
+        std::mem::take(&mut *self.pending_finished_commands.lock())
     }
 
     /// Mark that this pane raised an OSC 9/777 desktop notification. Drives the
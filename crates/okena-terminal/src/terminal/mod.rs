@@ -11,6 +11,7 @@ use std::time::Instant;
 mod ansi_snapshot;
 mod app_version;
 mod child_processes;
+mod command_capture;
 mod event_listener;
 mod idle;
 mod io;
@@ -21,6 +22,7 @@ mod mouse;
 mod osc_sidecar;
 mod prompt_jump;
 mod prompt_marks;
+mod read;
 mod render;
 mod resize;
 mod resize_authority;
@@ -36,6 +38,7 @@ mod tests;
 
 pub use app_version::set_app_version;
 pub use child_processes::{foreground_command, has_child_processes};
+pub use command_capture::{CapturedCommand, CommandCapture};
 pub use event_listener::set_process_palette;
 pub use resize_authority::{
     claim_remote_resize_if_allowed, claim_resize_authority_local, claim_resize_authority_remote,
@@ -44,15 +47,18 @@ pub use resize_authority::{
 };
 pub use transport::TerminalTransport;
 pub use types::{
-    AppCursorShape, ClipboardReadResponder, DetectedLink, PromptMark, PromptMarkKind, ResizeState,
-    SelectionState, TerminalProgress, TerminalProgressState, TerminalSize,
+    AppCursorShape, ClipboardReadResponder, DetectedLink, FinishedCommand, PromptMark,
+    PromptMarkKind, ResizeState, SelectionState, TerminalProgress, TerminalProgressState,
+    TerminalSize,
 };
 
 pub use osc_sidecar::TerminalNotification;
+pub use read::{ReadOptions, ReadOutput, ReadRange};
 
 use event_listener::{ClipboardQueues, CurrentState, ZedEventListener};
 use osc_sidecar::OscSidecar;
 use prompt_marks::{PromptSidecar, PromptTracker};
+use read::ReadMarks;
 use types::FocusReportState;
 
 #[derive(Debug, Clone, Copy)]
@@ -233,13 +239,17 @@ pub struct Terminal {
     /// GPUI thread only.
     pub(super) prompt_tracker: Mutex<PromptTracker>,
 
-    /// One-shot "a command finished (OSC 133 ;D) since last drain" edge.
-    /// Set in `process_output` when the prompt sidecar records a
-    /// `CommandFinished` mark, consumed (swapped to false) by the PTY event
-    /// loop so a finished command bumps the owning project's activity
-    /// timestamp exactly once. Mirrors `bell_pending`; not Arc-shared since it
-    /// is only ever set on the GPUI thread. GPUI thread only.
-    pub(super) command_finished_pending: AtomicBool,
+    /// Where each recent `read_text` left off, keyed by output sequence, so
+    /// the next read can return only what came after. Shifted with history
+    /// in `process_output`.
+    pub(super) read_marks: Mutex<ReadMarks>,
+
+    /// Commands that finished (OSC 133 ;D) since the last drain. Pushed in
+    /// `process_output` when the prompt sidecar records a `CommandFinished`
+    /// mark, drained by the PTY event loop so each finished command bumps the
+    /// owning project's activity timestamp and fires `on_command_finished`.
+    /// Capped so an undrained terminal cannot grow it. GPUI thread only.
+    pub(super) pending_finished_commands: Mutex<Vec<FinishedCommand>>,
 
     /// Reverse index into the current list of `PromptStart` marks (0 =
     /// newest). `Some` while the user is walking through prompts with
@@ -446,7 +456,8 @@ impl Terminal {
             osc_sidecar,
             prompt_sidecar: Mutex::new(PromptSidecar::new()),
             prompt_tracker: Mutex::new(PromptTracker::new()),
-            command_finished_pending: AtomicBool::new(false),
+            read_marks: Mutex::new(ReadMarks::new()),
+            pending_finished_commands: Mutex::new(Vec::new()),
             prompt_jump_index: Mutex::new(None),
             failed_jump_index: Mutex::new(None),
             last_output_time: Arc::new(Mutex::new(Instant::now())),
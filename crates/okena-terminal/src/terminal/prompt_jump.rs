@@ -12,6 +12,14 @@ impl Terminal {
         self.prompt_tracker.lock().snapshot()
     }
 
+    /// Whether the shell is idle at a prompt, judged by the latest OSC 133
+    /// mark: `None` when the shell never sent one (no shell integration),
+    /// `Some(false)` while a command is executing (`C` without its `D`).
+    pub fn at_shell_prompt(&self) -> Option<bool> {
+        let last = self.prompt_tracker.lock().last_kind()?;
+        Some(!matches!(last, PromptMarkKind::CommandExecuted))
+    }
+
     /// Scroll the viewport so the next older `OSC 133 ; A` prompt lands at
     /// visual row 0. The first call after any shell output lands on the
     /// most-recent prompt (even if it's already visible); each subsequent
@@ -4,8 +4,9 @@ use alacritty_terminal::term::Term;
 use alacritty_terminal::vte::Perform;
 use alacritty_terminal::vte::ansi::Processor;
 use std::collections::VecDeque;
+use std::time::Instant;
 
-use super::types::{PromptMark, PromptMarkKind};
+use super::types::{FinishedCommand, PromptMark, PromptMarkKind};
 
 /// Ring buffer of recent OSC 133 marks plus a best-effort scroll tracker.
 ///
@@ -22,6 +23,9 @@ pub(crate) struct PromptTracker {
     /// Oldest-first ring buffer cap. Shells that run thousands of commands
     /// don't need thousands of marks — the UX only looks at the last few.
     capacity: usize,
+    /// When the running command was marked executing (`OSC 133 ; C`), for
+    /// the duration reported with its `D` mark.
+    executing_since: Option<Instant>,
 }
 
 impl PromptTracker {
@@ -29,6 +33,7 @@ impl PromptTracker {
         Self {
             marks: VecDeque::with_capacity(64),
             capacity: 64,
+            executing_since: None,
         }
     }
 
@@ -63,6 +68,10 @@ impl PromptTracker {
     pub(super) fn snapshot(&self) -> Vec<PromptMark> {
         self.marks.iter().copied().collect()
     }
+
+    pub(super) fn last_kind(&self) -> Option<PromptMarkKind> {
+        self.marks.back().map(|mark| mark.kind)
+    }
 }
 
 /// Parse the body of an `OSC 133 ; <kind> [; <args...>]` sequence into a
@@ -146,17 +155,16 @@ impl Perform for PromptSidecarPerform {
 /// offset (so the cursor is at its post-OSC position, which is unchanged
 /// since OSC sequences are zero-width) and then record the mark.
 ///
-/// Returns `true` if at least one `CommandFinished` (OSC 133 ;D) mark was
-/// recorded in this chunk, so the caller can raise the per-terminal
-/// command-finished activity edge exactly once per drain.
+/// Returns the commands that finished (OSC 133 ;D) in this chunk, timed
+/// from their `C` mark, so the caller can queue them for the PTY event loop.
 pub(super) fn advance_with_prompt_marks<L: EventListener>(
     term: &mut Term<L>,
     processor: &mut Processor,
     sidecar: &mut PromptSidecar,
     tracker: &mut PromptTracker,
     data: &[u8],
-) -> bool {
-    let mut command_finished = false;
+) -> Vec<FinishedCommand> {
+    let mut finished = Vec::new();
     let mut pos = 0;
     while pos < data.len() {
         let consumed = sidecar
@@ -164,8 +172,13 @@ pub(super) fn advance_with_prompt_marks<L: EventListener>(
             .advance_until_terminated(&mut sidecar.perform, &data[pos..]);
         processor.advance(term, &data[pos..pos + consumed]);
         if let Some(kind) = sidecar.perform.pending.take() {
-            if matches!(kind, PromptMarkKind::CommandFinished { .. }) {
-                command_finished = true;
+            match kind {
+                PromptMarkKind::CommandExecuted => tracker.executing_since = Some(Instant::now()),
+                PromptMarkKind::CommandFinished { exit_code } => finished.push(FinishedCommand {
+                    exit_code,
+                    duration: tracker.executing_since.take().map(|at| at.elapsed()),
+                }),
+                PromptMarkKind::PromptStart | PromptMarkKind::CommandStart => {}
             }
             let point = term.grid().cursor.point;
             tracker.record(kind, point);
@@ -179,5 +192,5 @@ pub(super) fn advance_with_prompt_marks<L: EventListener>(
         }
         pos += consumed;
     }
-    command_finished
+    finished
 }
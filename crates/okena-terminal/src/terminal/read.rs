@@ -0,0 +1,229 @@
+//! Text reads for `read_content`: the visible screen, ranges reaching into
+//! scrollback, SGR-styled output and incremental reads.
+//!
+//! Incremental reads are keyed by the output sequence a previous read
+//! returned. Each read leaves a [`ReadMark`] at the cursor; `since` resumes
+//! from the mark recorded with that sequence. Marks shift with history like
+//! prompt marks do. Once scrollback is full, lines rotate out without the
+//! history growing, so a mark also keeps the text before it and is re-found by
+//! that prefix.
+
+use std::collections::VecDeque;
+use std::sync::atomic::Ordering;
+
+use alacritty_terminal::grid::{Dimensions, Grid};
+use alacritty_terminal::index::{Column, Line, Point};
+use alacritty_terminal::term::cell::{Cell, Flags};
+
+use super::Terminal;
+use super::ansi_snapshot::row_to_ansi;
+use super::types::PromptMarkKind;
+
+/// Marks kept per terminal; enough for a handful of concurrent pollers.
+const READ_MARK_CAPACITY: usize = 32;
+
+/// Which lines [`Terminal::read_text`] covers before `since` is applied.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReadRange {
+    /// The visible screen.
+    #[default]
+    Screen,
+    /// The last `n` lines of content, reaching into scrollback.
+    Last(usize),
+    /// All of scrollback plus the screen.
+    All,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReadOptions {
+    pub range: ReadRange,
+    /// Keep colors and attributes as SGR escapes.
+    pub ansi: bool,
+    /// Only content written after the read that returned this sequence. It
+    /// may reach into scrollback even with `Screen`; `Last(n)` still caps it.
+    pub since: Option<u64>,
+    /// Drop the shell prompt at the end: from the last `OSC 133 ; A` when the
+    /// shell reports marks, otherwise the cursor's line.
+    pub strip_prompt: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReadOutput {
+    pub content: String,
+    /// Pass back as [`ReadOptions::since`] to continue from here.
+    pub sequence: u64,
+    /// `since` could not be honored in full (its mark was evicted or scrolled
+    /// out of history, or the range cap cut it), so earlier output is missing.
+    pub truncated: bool,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct ReadMark {
+    sequence: u64,
+    point: Point,
+    /// Row text left of `point`, to re-find the row after untracked scrolling.
+    prefix: String,
+}
+
+pub(crate) struct ReadMarks {
+    marks: VecDeque<ReadMark>,
+}
+
+impl ReadMarks {
+    pub(super) fn new() -> Self {
+        Self {
+            marks: VecDeque::with_capacity(READ_MARK_CAPACITY),
+        }
+    }
+
+    fn record(&mut self, mark: ReadMark) {
+        self.marks.retain(|m| m.sequence != mark.sequence);
+        if self.marks.len() == READ_MARK_CAPACITY {
+            self.marks.pop_front();
+        }
+        self.marks.push_back(mark);
+    }
+
+    /// Shift marks up by the lines that just scrolled into history, dropping
+    /// those that fell off the top. Mirrors `PromptTracker::on_history_changed`.
+    pub(super) fn on_history_changed(&mut self, before: usize, after: usize, topmost: i32) {
+        let delta = after.saturating_sub(before);
+        if delta == 0 {
+            return;
+        }
+        let delta = delta as i32;
+        self.marks.retain_mut(|mark| {
+            mark.point.line.0 -= delta;
+            mark.point.line.0 >= topmost
+        });
+    }
+
+    /// Where the mark for `sequence` sits now: at its tracked line when the
+    /// prefix still matches, else the nearest row above it that matches.
+    fn locate(&self, sequence: u64, grid: &Grid<Cell>) -> Option<Point> {
+        let mark = self.marks.iter().find(|m| m.sequence == sequence)?;
+        let topmost = grid.topmost_line().0;
+        let expected = mark.point.line.0.min(grid.bottommost_line().0);
+        (topmost..=expected)
+            .rev()
+            .find(|&line| row_text(grid, Line(line), 0, mark.point.column.0) == mark.prefix)
+            .map(|line| Point::new(Line(line), mark.point.column))
+    }
+}
+
+impl Terminal {
+    /// Read terminal content as text (see [`ReadOptions`]) and leave a mark so
+    /// a later read can pass the returned sequence as `since`.
+    pub fn read_text(&self, options: &ReadOptions) -> ReadOutput {
+        self.drain_pending_output();
+        let term = self.term.lock();
+        let sequence = self.processed_output_sequence.load(Ordering::Acquire);
+        let grid = term.grid();
+        let topmost = grid.topmost_line().0;
+        let cursor = grid.cursor.point;
+
+        let last_content = (topmost..=grid.bottommost_line().0)
+            .rev()
+            .find(|&line| !row_text(grid, Line(line), 0, grid.columns()).is_empty())
+            .unwrap_or(topmost);
+        let mut end = last_content.max(cursor.line.0);
+        if options.strip_prompt
+            && let Some(prompt) = self.prompt_start_line(cursor.line.0)
+        {
+            end = end.min(prompt - 1);
+        }
+
+        let mut marks = self.read_marks.lock();
+        let mut truncated = false;
+        let range = match options.range {
+            ReadRange::Screen if options.since.is_some() => ReadRange::All,
+            range => range,
+        };
+        let mut start = match range {
+            ReadRange::Screen => Point::new(Line(0), Column(0)),
+            ReadRange::Last(n) => {
+                let first = end.saturating_sub(i32::try_from(n).unwrap_or(i32::MAX) - 1);
+                Point::new(Line(first.max(topmost)), Column(0))
+            }
+            ReadRange::All => Point::new(Line(topmost), Column(0)),
+        };
+        match options.since {
+            Some(since) if since == sequence => start = Point::new(Line(end + 1), Column(0)),
+            Some(since) => match marks.locate(since, grid) {
+                Some(point) if point >= start => start = point,
+                Some(_) => truncated = true,
+                // Sequence 0 is "before any output": everything is new.
+                None => {
+                    truncated = since != 0;
+                    start = Point::new(Line(topmost), Column(0));
+                }
+            },
+            None => {}
+        }
+
+        let mut lines = Vec::new();
+        for line in start.line.0..=end {
+            let from = if line == start.line.0 {
+                start.column.0
+            } else {
+                0
+            };
+            if options.ansi {
+                let bytes = row_to_ansi(grid, Line(line), from);
+                lines.push(String::from_utf8_lossy(&bytes).into_owned());
+            } else {
+                lines.push(row_text(grid, Line(line), from, grid.columns()));
+            }
+        }
+        while lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop();
+        }
+
+        marks.record(ReadMark {
+            sequence,
+            point: cursor,
+            prefix: row_text(grid, cursor.line, 0, cursor.column.0),
+        });
+
+        ReadOutput {
+            content: lines.join("\n"),
+            sequence,
+            truncated,
+        }
+    }
+
+    /// First line of the prompt the cursor sits on; `None` while the shell
+    /// reports a command running, when there is no prompt to strip.
+    fn prompt_start_line(&self, cursor_line: i32) -> Option<i32> {
+        let tracker = self.prompt_tracker.lock();
+        match tracker.last_kind() {
+            Some(PromptMarkKind::CommandExecuted) => None,
+            Some(PromptMarkKind::PromptStart | PromptMarkKind::CommandStart) => Some(
+                tracker
+                    .snapshot()
+                    .iter()
+                    .rev()
+                    .find(|mark| mark.kind == PromptMarkKind::PromptStart)
+                    .map_or(cursor_line, |mark| mark.line.min(cursor_line)),
+            ),
+            // No shell integration, or `D` arrived and the next prompt is
+            // still being drawn at the cursor.
+            Some(PromptMarkKind::CommandFinished { .. }) | None => Some(cursor_line),
+        }
+    }
+}
+
+/// Plain text of cells `from..to` of one row, trailing blanks trimmed.
+fn row_text(grid: &Grid<Cell>, line: Line, from: usize, to: usize) -> String {
+    let row = &grid[line];
+    let mut text = String::with_capacity(to.saturating_sub(from));
+    for col in from..to.min(grid.columns()) {
+        let cell = &row[Column(col)];
+        if cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+            continue;
+        }
+        text.push(if cell.c == '\0' { ' ' } else { cell.c });
+    }
+    text.truncate(text.trim_end().len());
+    text
+}
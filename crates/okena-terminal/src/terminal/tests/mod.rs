@@ -4,6 +4,7 @@ mod input_repaint;
 mod kitty;
 mod osc;
 mod prompt_jump;
+mod read;
 mod resize_authority;
 mod snapshot_watermark;
 mod unread;
@@ -243,3 +243,30 @@ fn test_jump_to_failed_ignores_zero_exit() {
 
     assert!(!terminal.jump_to_prev_failed_command());
 }
+
+#[test]
+fn test_finished_commands_carry_exit_code_and_duration() {
+    let transport = Arc::new(NullTransport);
+    let terminal = Terminal::new(
+        "t".into(),
+        TerminalSize::default(),
+        transport,
+        "/tmp".into(),
+    );
+
+    // Timed command: C then D in separate chunks.
+    terminal.process_output(b"\x1b]133;A\x1b\\$ make\r\n\x1b]133;C\x1b\\");
+    terminal.process_output(b"build ok\r\n\x1b]133;D;2\x1b\\");
+    // A D without a preceding C (some shells on an empty prompt).
+    terminal.process_output(b"\x1b]133;D\x1b\\");
+
+    let finished = terminal.take_finished_commands();
+    assert_eq!(finished.len(), 2);
+    assert_eq!(finished[0].exit_code, Some(2));
+    assert!(finished[0].duration.is_some());
+    assert_eq!(finished[1].exit_code, None);
+    assert_eq!(finished[1].duration, None);
+
+    // Drained: a second take is empty.
+    assert!(terminal.take_finished_commands().is_empty());
+}
@@ -0,0 +1,109 @@
+use super::super::Terminal;
+use super::super::read::{ReadOptions, ReadRange};
+use super::super::types::TerminalSize;
+use super::NullTransport;
+use std::sync::Arc;
+
+fn terminal(rows: u16) -> Terminal {
+    Terminal::new(
+        "read".to_string(),
+        TerminalSize {
+            rows,
+            cols: 40,
+            ..TerminalSize::default()
+        },
+        Arc::new(NullTransport),
+        "/tmp".to_string(),
+    )
+}
+
+fn read(terminal: &Terminal, options: ReadOptions) -> (String, u64, bool) {
+    let out = terminal.read_text(&options);
+    (out.content, out.sequence, out.truncated)
+}
+
+#[test]
+fn ranges_reach_into_scrollback() {
+    let terminal = terminal(4);
+    let text: String = (1..=10).map(|i| format!("line {i}\r\n")).collect();
+    terminal.process_output_with_sequence(text.as_bytes(), 1);
+
+    let (screen, _, _) = read(&terminal, ReadOptions::default());
+    assert_eq!(screen, "line 8\nline 9\nline 10");
+
+    let last = ReadOptions {
+        range: ReadRange::Last(5),
+        ..ReadOptions::default()
+    };
+    assert_eq!(read(&terminal, last).0, "line 7\nline 8\nline 9\nline 10");
+
+    let all = ReadOptions {
+        range: ReadRange::All,
+        ..ReadOptions::default()
+    };
+    assert!(read(&terminal, all).0.starts_with("line 1\nline 2\n"));
+}
+
+#[test]
+fn since_returns_only_newer_output() {
+    let terminal = terminal(4);
+    terminal.process_output_with_sequence(b"$ make\r\nbuilding\r\n$ ", 7);
+    let (_, sequence, _) = read(&terminal, ReadOptions::default());
+    assert_eq!(sequence, 7);
+
+    let since = ReadOptions {
+        since: Some(sequence),
+        ..ReadOptions::default()
+    };
+    assert_eq!(read(&terminal, since), (String::new(), 7, false));
+
+    // Enough output to scroll the mark into history.
+    let text: String =
+        "ls\r\n".to_string() + &(1..=6).map(|i| format!("f{i}\r\n")).collect::<String>();
+    terminal.process_output_with_sequence(text.as_bytes(), 9);
+    assert_eq!(
+        read(&terminal, since),
+        ("ls\nf1\nf2\nf3\nf4\nf5\nf6".to_string(), 9, false)
+    );
+}
+
+#[test]
+fn unknown_since_falls_back_to_everything_and_flags_it() {
+    let terminal = terminal(4);
+    terminal.process_output_with_sequence(b"hello", 3);
+    let unknown = ReadOptions {
+        since: Some(2),
+        ..ReadOptions::default()
+    };
+    assert_eq!(read(&terminal, unknown), ("hello".to_string(), 3, true));
+}
+
+#[test]
+fn strip_prompt_drops_the_prompt_line() {
+    let terminal = terminal(6);
+    terminal.process_output_with_sequence(
+        b"\x1b]133;A\x07$ \x1b]133;B\x07make\r\n\x1b]133;C\x07ok\r\n\x1b]133;D;0\x07\x1b]133;A\x07$ \x1b]133;B\x07",
+        1,
+    );
+    let strip = ReadOptions {
+        strip_prompt: true,
+        ..ReadOptions::default()
+    };
+    assert_eq!(read(&terminal, strip).0, "$ make\nok");
+
+    // Without shell integration the cursor's line is taken as the prompt.
+    let plain = self::terminal(6);
+    plain.process_output_with_sequence(b"out\r\nuser@host:~$ ", 1);
+    assert_eq!(read(&plain, strip).0, "out");
+}
+
+#[test]
+fn ansi_keeps_styles_per_line() {
+    let terminal = terminal(4);
+    terminal.process_output_with_sequence(b"\x1b[31mred\x1b[0m plain\r\nnext", 1);
+    let ansi = ReadOptions {
+        ansi: true,
+        ..ReadOptions::default()
+    };
+    assert_eq!(read(&terminal, ansi).0, "\x1b[0;31mred\x1b[0m plain\nnext");
+}
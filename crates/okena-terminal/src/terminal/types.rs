@@ -74,6 +74,17 @@ pub struct PromptMark {
     pub column: usize,
 }
 
+/// A command that finished (`OSC 133 ; D`), as drained by
+/// [`super::Terminal::take_finished_commands`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FinishedCommand {
+    /// Exit code reported with the `D` mark, if the shell sent one.
+    pub exit_code: Option<i32>,
+    /// Time since the preceding `OSC 133 ; C`. `None` when the shell never
+    /// marked the command as executing.
+    pub duration: Option<std::time::Duration>,
+}
+
 /// Progress state reported via the ConEmu / Windows Terminal protocol
 /// `OSC 9 ; 4 ; st ; pr` (also spoken by WezTerm, Ghostty, Kitty, …).
 ///
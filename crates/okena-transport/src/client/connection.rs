@@ -1,12 +1,15 @@
-use crate::client::config::{LOCAL_DAEMON_CONNECTION_ID, LocalEndpoint, RemoteConnectionConfig};
+use crate::client::config::{LOCAL_DAEMON_CONNECTION_ID, RemoteConnectionConfig};
 use crate::client::id::make_prefixed_id;
 use crate::client::state::{
-    collect_all_terminal_ids, collect_state_terminal_ids, collect_terminal_sizes, diff_states,
+    apply_state_delta, collect_all_terminal_ids, collect_background_terminal_ids,
+    collect_state_terminal_ids, collect_terminal_sizes, diff_states, may_change_settings,
 };
+use crate::client::throughput::{StreamMeter, StreamThroughput};
 use crate::client::types::{
     ConnectionEvent, ConnectionStatus, SessionError, TOKEN_REFRESH_AGE_SECS, WsClientMessage,
 };
 use okena_core::api::{ActionRequest, ApiSystemStats, StateResponse};
+use okena_core::ws::{FRAME_FLAG_COMPRESSED, FrameInflater, STATE_DELTAS_QUERY, WsCompression};
 
 use futures::{Sink, Stream};
 use std::collections::HashMap;
@@ -18,9 +21,16 @@ use tokio_tungstenite::tungstenite;
 type TcpWsStream =
     tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
 #[cfg(unix)]
-type UnixWsStream = tokio_tungstenite::WebSocketStream<tokio::net::UnixStream>;
+type UnixWsStream =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::UnixStream>>;
 
-enum AnyWsStream {
+/// How often the server flushes output of terminals in projects the client
+/// isn't showing. Long enough to collapse a build log's bursts, short enough
+/// that switching to the project finds it current.
+const BACKGROUND_OUTPUT_INTERVAL_MS: u32 = 500;
+
+/// A WebSocket over TCP (plain or TLS) or a local Unix socket.
+pub(crate) enum AnyWsStream {
     Tcp(Box<TcpWsStream>),
     #[cfg(unix)]
     Unix(Box<UnixWsStream>),
@@ -74,32 +84,35 @@ impl Sink<tungstenite::Message> for AnyWsStream {
     }
 }
 
+/// HTTP client over `path`, speaking TLS when the connection's proxy command
+/// reaches a TLS listener.
 #[cfg(unix)]
-fn unix_http_client(path: &str) -> Result<reqwest::Client, String> {
-    reqwest::Client::builder()
-        .unix_socket(path)
-        .build()
-        .map_err(|error| format!("Cannot initialise Unix socket HTTP client: {error}"))
+fn unix_http_client(
+    config: &RemoteConnectionConfig,
+    path: &str,
+    observed: crate::client::tls::ObservedFingerprint,
+) -> Result<reqwest::Client, String> {
+    crate::client::tls::build_unix_reqwest_client(
+        path,
+        config.proxy_uses_tls(),
+        config.pinned_cert_sha256.clone(),
+        observed,
+    )
 }
 
 #[cfg(not(unix))]
-fn unix_http_client(_path: &str) -> Result<reqwest::Client, String> {
+fn unix_http_client(
+    _config: &RemoteConnectionConfig,
+    _path: &str,
+    _observed: crate::client::tls::ObservedFingerprint,
+) -> Result<reqwest::Client, String> {
     Err("Unix socket HTTP transport is not supported on this platform".to_string())
 }
 
-fn local_unix_path(config: &RemoteConnectionConfig) -> Option<&str> {
-    #[cfg(unix)]
-    {
-        match &config.local_endpoint {
-            Some(LocalEndpoint::UnixSocket { path }) => Some(path.as_str()),
-            _ => None,
-        }
-    }
-    #[cfg(not(unix))]
-    {
-        let _ = config;
-        None
-    }
+/// Unix socket standing in for TCP: the local daemon's endpoint or a proxy
+/// command relay.
+pub(crate) fn local_unix_path(config: &RemoteConnectionConfig) -> Result<Option<String>, String> {
+    config.unix_transport_path()
 }
 
 fn initial_connect_attempts(config: &RemoteConnectionConfig) -> u32 {
@@ -107,7 +120,7 @@ fn initial_connect_attempts(config: &RemoteConnectionConfig) -> u32 {
         // Fail fast: ensure_local_daemon() verified reachability right before
         // this dial, and the app-layer self-heal re-runs it once Error fires.
         5
-    } else if config.local_endpoint.is_some() {
+    } else if config.local_endpoint.is_some() || config.proxy_command.is_some() {
         30
     } else {
         1
@@ -233,6 +246,12 @@ pub struct RemoteClient<H: ConnectionHandler> {
     /// server drops a connection's entry on close, and a client that never
     /// re-sends would silently fall out of the `gh` PR/CI scope.
     visible_projects: Arc<std::sync::RwLock<Vec<String>>>,
+    /// Terminals of the projects outside `visible_projects`, which the server
+    /// is asked to coalesce. `None` until a viewport is declared, so clients
+    /// that never declare one receive every terminal at full rate.
+    background_terminals: Arc<std::sync::RwLock<Option<Vec<String>>>>,
+    /// Output frame counters of the current session, polled by the UI.
+    throughput: Arc<StreamMeter>,
 }
 
 impl<H: ConnectionHandler> RemoteClient<H> {
@@ -257,6 +276,8 @@ impl<H: ConnectionHandler> RemoteClient<H> {
             ws_abort_handle: None,
             shared_token,
             visible_projects: Arc::new(std::sync::RwLock::new(Vec::new())),
+            background_terminals: Arc::new(std::sync::RwLock::new(None)),
+            throughput: Arc::new(StreamMeter::default()),
         }
     }
 
@@ -265,17 +286,45 @@ impl<H: ConnectionHandler> RemoteClient<H> {
     /// Full replacement set of unprefixed ids. Stored for reconnects and pushed
     /// to the server immediately when a session is up.
     pub fn set_visible_projects(&self, project_ids: Vec<String>) {
-        if let Ok(mut stored) = self.visible_projects.write() {
-            if *stored == project_ids {
+        let changed = match self.visible_projects.write() {
+            Ok(mut stored) if *stored != project_ids => {
+                stored.clone_from(&project_ids);
+                true
+            }
+            _ => false,
+        };
+        if changed && let Some(tx) = self.ws_tx.as_ref() {
+            let _ = tx.try_send(WsClientMessage::SetVisibleProjects { project_ids });
+        }
+        self.refresh_background_terminals();
+    }
+
+    /// Recompute which terminals belong to hidden projects and, when that
+    /// changed, ask the server to pace them.
+    fn refresh_background_terminals(&self) {
+        let background = match (&self.remote_state, self.visible_projects.read()) {
+            (Some(state), Ok(visible)) => collect_background_terminal_ids(state, &visible),
+            _ => Vec::new(),
+        };
+        if let Ok(mut stored) = self.background_terminals.write() {
+            if stored.as_ref() == Some(&background) {
                 return;
             }
-            stored.clone_from(&project_ids);
+            *stored = Some(background.clone());
         }
         if let Some(tx) = self.ws_tx.as_ref() {
-            let _ = tx.try_send(WsClientMessage::SetVisibleProjects { project_ids });
+            let _ = tx.try_send(WsClientMessage::SetOutputRate {
+                terminal_ids: background,
+                interval_ms: BACKGROUND_OUTPUT_INTERVAL_MS,
+            });
         }
     }
 
+    /// Terminal output throughput of the current session.
+    pub fn throughput(&self) -> StreamThroughput {
+        self.throughput.snapshot()
+    }
+
     pub fn config(&self) -> &RemoteConnectionConfig {
         &self.config
     }
@@ -306,6 +355,14 @@ impl<H: ConnectionHandler> RemoteClient<H> {
 
     pub fn set_remote_state(&mut self, state: Option<StateResponse>) {
         self.remote_state = state;
+        // New terminals in hidden projects start paced too.
+        let declared = self
+            .background_terminals
+            .read()
+            .is_ok_and(|ids| ids.is_some());
+        if declared {
+            self.refresh_background_terminals();
+        }
     }
 
     pub fn system_stats(&self) -> Option<&ApiSystemStats> {
@@ -362,6 +419,8 @@ impl<H: ConnectionHandler> RemoteClient<H> {
         let (ws_tx, ws_rx) = ws_message_channel();
         self.ws_tx = Some(ws_tx.clone());
         let visible_projects = self.visible_projects.clone();
+        let background_terminals = self.background_terminals.clone();
+        let throughput = self.throughput.clone();
 
         let task = self.runtime.spawn(async move {
             let mut config = config;
@@ -371,9 +430,25 @@ impl<H: ConnectionHandler> RemoteClient<H> {
             // tries TLS (never downgrade). A legacy plain connection prefers TLS
             // (auto-upgrade) but falls back to plain http so it keeps working
             // against a server that hasn't enabled TLS.
-            let local_unix = local_unix_path(&config).map(str::to_string);
+            let local_unix = match local_unix_path(&config) {
+                Ok(path) => path,
+                Err(msg) => {
+                    log::warn!("{}", msg);
+                    let _ = event_tx
+                        .send(ConnectionEvent::StatusChanged {
+                            connection_id: config.id.clone(),
+                            status: ConnectionStatus::Error(msg),
+                        })
+                        .await;
+                    return;
+                }
+            };
             let schemes: &[bool] = if local_unix.is_some() {
-                &[false]
+                if config.proxy_uses_tls() {
+                    &[true]
+                } else {
+                    &[false]
+                }
             } else if config.tls {
                 &[true]
             } else {
@@ -402,7 +477,8 @@ impl<H: ConnectionHandler> RemoteClient<H> {
 
                 for &tls in schemes {
                     let client_and_url = if let Some(path) = local_unix.as_deref() {
-                        unix_http_client(path).map(|client| (client, config.http_origin()))
+                        unix_http_client(&config, path, observed.clone())
+                            .map(|client| (client, config.http_origin()))
                     } else {
                         crate::client::tls::build_reqwest_client(
                             tls,
@@ -494,8 +570,8 @@ impl<H: ConnectionHandler> RemoteClient<H> {
 
             // Step 2: Validate saved token, or trust same-user Unix socket transport.
             if let Some(token) = config.effective_auth_token() {
-                let trusted_local_transport =
-                    config.saved_token.is_none() && config.is_trusted_local_transport();
+                let trusted_local_transport = config.saved_token.is_none()
+                    && (config.is_trusted_local_transport() || config.proxy_command.is_some());
                 match client
                     .get(format!("{}/v1/state", base_url))
                     .header("Authorization", format!("Bearer {}", token))
@@ -522,6 +598,8 @@ impl<H: ConnectionHandler> RemoteClient<H> {
                             handler,
                             shared_token,
                             visible_projects,
+                            background_terminals,
+                            throughput,
                         )
                         .await;
                         return;
@@ -595,21 +673,22 @@ impl<H: ConnectionHandler> RemoteClient<H> {
         let (ws_tx, ws_rx) = ws_message_channel();
         self.ws_tx = Some(ws_tx.clone());
         let visible_projects = self.visible_projects.clone();
+        let background_terminals = self.background_terminals.clone();
+        let throughput = self.throughput.clone();
 
         self.status = ConnectionStatus::Connecting;
 
         let task = self.runtime.spawn(async move {
-            let local_unix = local_unix_path(&config).map(str::to_string);
             let base_url = config.http_origin();
             let observed = crate::client::tls::new_observed();
-            let client = if let Some(path) = local_unix.as_deref() {
-                unix_http_client(path)
-            } else {
-                crate::client::tls::build_reqwest_client(
+            let client = match local_unix_path(&config) {
+                Ok(Some(path)) => unix_http_client(&config, &path, observed.clone()),
+                Ok(None) => crate::client::tls::build_reqwest_client(
                     config.tls,
                     config.pinned_cert_sha256.clone(),
                     observed.clone(),
-                )
+                ),
+                Err(error) => Err(error),
             };
             let client = match client {
                 Ok(client) => client,
@@ -673,6 +752,8 @@ impl<H: ConnectionHandler> RemoteClient<H> {
                                 handler,
                                 shared_token,
                                 visible_projects,
+                                background_terminals,
+                                throughput,
                             )
                             .await;
                         }
@@ -762,6 +843,8 @@ impl<H: ConnectionHandler> RemoteClient<H> {
         handler: Arc<H>,
         shared_token: Arc<std::sync::RwLock<Option<String>>>,
         visible_projects: Arc<std::sync::RwLock<Vec<String>>>,
+        background_terminals: Arc<std::sync::RwLock<Option<Vec<String>>>>,
+        throughput: Arc<StreamMeter>,
     ) {
         let mut reconnect_attempt: u32 = 0;
         let max_reconnect_attempts = ws_reconnect_max_attempts(&config);
@@ -776,6 +859,8 @@ impl<H: ConnectionHandler> RemoteClient<H> {
                 &ws_rx,
                 &handler,
                 &visible_projects,
+                &background_terminals,
+                &throughput,
             )
             .await
             {
@@ -856,6 +941,7 @@ impl<H: ConnectionHandler> RemoteClient<H> {
     }
 
     /// A single WebSocket session. Returns Ok(()) on clean disconnect, Err on failure.
+    #[allow(clippy::too_many_arguments)]
     async fn ws_session(
         config: &RemoteConnectionConfig,
         token: &str,
@@ -864,114 +950,54 @@ impl<H: ConnectionHandler> RemoteClient<H> {
         ws_rx: &async_channel::Receiver<WsClientMessage>,
         handler: &Arc<H>,
         visible_projects: &Arc<std::sync::RwLock<Vec<String>>>,
+        background_terminals: &Arc<std::sync::RwLock<Option<Vec<String>>>>,
+        throughput: &Arc<StreamMeter>,
     ) -> Result<(), SessionError> {
         // Shared stream maps: terminal_id -> stream_id (for writer) and reverse (for reader)
         let stream_map: Arc<std::sync::RwLock<HashMap<String, u32>>> =
             Arc::new(std::sync::RwLock::new(HashMap::new()));
         let mut reverse_stream_map: HashMap<u32, String> = HashMap::new();
-        let ws_url = config.ws_url();
+        let local_unix = local_unix_path(config).map_err(SessionError::Transient)?;
         let observed = crate::client::tls::new_observed();
 
-        // Connect WebSocket. With TLS we go through connect_async_tls_with_config
-        // using the pinned rustls connector; otherwise the plain ws:// path.
-        let (ws_stream, _response) = if let Some(path) = local_unix_path(config) {
-            #[cfg(unix)]
-            {
-                let stream = tokio::net::UnixStream::connect(path).await.map_err(|e| {
-                    SessionError::Transient(format!("Unix socket connect failed: {}", e))
-                })?;
-                let (ws, response) =
-                    tokio_tungstenite::client_async("ws://okena.local/v1/stream", stream)
-                        .await
-                        .map_err(|e| {
-                            SessionError::Transient(format!("WebSocket connect failed: {}", e))
-                        })?;
-                (AnyWsStream::Unix(Box::new(ws)), response)
-            }
-            #[cfg(not(unix))]
-            {
-                let _ = path;
-                return Err(SessionError::Transient(
-                    "Unix socket transport is not supported on this platform".to_string(),
-                ));
-            }
-        } else if config.tls {
-            let connector = crate::client::tls::ws_connector(
-                true,
-                config.pinned_cert_sha256.clone(),
-                observed.clone(),
-            );
-            let (ws, response) =
-                tokio_tungstenite::connect_async_tls_with_config(&ws_url, None, false, connector)
-                    .await
-                    .map_err(|e| {
-                        SessionError::Transient(format!("WebSocket connect failed: {}", e))
-                    })?;
-            (AnyWsStream::Tcp(Box::new(ws)), response)
+        // Steps 1-2: connect, send Auth, wait for AuthOk. Compression only
+        // pays off over the network, so Unix socket links never offer it.
+        let mut offer = if local_unix.is_none() {
+            vec![WsCompression::Deflate]
         } else {
-            let (ws, response) = tokio_tungstenite::connect_async(&ws_url)
-                .await
-                .map_err(|e| SessionError::Transient(format!("WebSocket connect failed: {}", e)))?;
-            (AnyWsStream::Tcp(Box::new(ws)), response)
+            Vec::new()
         };
-
-        let (mut ws_write, mut ws_read) = futures::StreamExt::split(ws_stream);
-
-        // Step 1: Send Auth
-        let auth_msg = serde_json::json!({
-            "type": "auth",
-            "token": token,
-        });
-        futures::SinkExt::send(
-            &mut ws_write,
-            tungstenite::Message::Text(auth_msg.to_string()),
-        )
-        .await
-        .map_err(|e| SessionError::Transient(format!("Failed to send auth: {}", e)))?;
-
-        // Step 2: Wait for AuthOk
-        let auth_response = tokio::time::timeout(
-            std::time::Duration::from_secs(10),
-            futures::StreamExt::next(&mut ws_read),
-        )
-        .await
-        .map_err(|_| SessionError::Transient("Auth response timeout".to_string()))?
-        .ok_or_else(|| {
-            SessionError::Transient("WebSocket closed before auth response".to_string())
-        })?
-        .map_err(|e| SessionError::Transient(format!("WebSocket read error: {}", e)))?;
-
-        match &auth_response {
-            tungstenite::Message::Text(text) => {
-                let parsed: serde_json::Value = serde_json::from_str(text)
-                    .map_err(|e| SessionError::Transient(format!("Invalid JSON: {}", e)))?;
-                let msg_type = parsed.get("type").and_then(|v| v.as_str()).unwrap_or("");
-                if msg_type == "auth_ok" {
-                    log::info!("Authenticated with {}:{}", config.host, config.port);
-                } else if msg_type == "auth_failed" {
-                    let error = parsed
-                        .get("error")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("unknown");
-                    return Err(SessionError::Auth(format!("Auth failed: {}", error)));
-                } else {
-                    return Err(SessionError::Transient(format!(
-                        "Unexpected auth response type: {}",
-                        msg_type
-                    )));
+        let (ws_stream, accepted) = loop {
+            let mut ws_stream = connect_ws(config, local_unix.as_deref(), &observed).await?;
+            match authenticate(&mut ws_stream, token, &offer).await {
+                // Servers that predate compression reject the unknown field
+                // like a bad token; a second try without it tells them apart.
+                Err(SessionError::Auth(msg)) if !offer.is_empty() => {
+                    log::info!(
+                        "{}:{} refused auth with compression ({}), retrying without",
+                        config.host,
+                        config.port,
+                        msg
+                    );
+                    offer.clear();
                 }
+                result => break (ws_stream, result?),
             }
-            _ => {
-                return Err(SessionError::Transient(
-                    "Expected text message for auth response".to_string(),
-                ));
-            }
-        }
+        };
+        log::info!(
+            "Authenticated with {}:{} (compression: {:?})",
+            config.host,
+            config.port,
+            accepted.compression
+        );
+        throughput.reset(accepted.compression);
+
+        let (mut ws_write, mut ws_read) = futures::StreamExt::split(ws_stream);
 
         // Step 3: Fetch state via HTTP
         let base_url = config.http_origin();
-        let client = if let Some(path) = local_unix_path(config) {
-            unix_http_client(path)
+        let client = if let Some(path) = local_unix.as_deref() {
+            unix_http_client(config, path, observed.clone())
         } else {
             crate::client::tls::build_reqwest_client(
                 config.tls,
@@ -1076,6 +1102,25 @@ impl<H: ConnectionHandler> RemoteClient<H> {
                 SessionError::Transient(format!("Failed to send visible projects: {}", e))
             })?;
         }
+        // Pacing is per-connection too.
+        let background = background_terminals
+            .read()
+            .ok()
+            .and_then(|ids| ids.clone())
+            .unwrap_or_default();
+        if accepted.output_rate && !background.is_empty() {
+            let rate_msg = serde_json::json!({
+                "type": "set_output_rate",
+                "terminal_ids": background,
+                "interval_ms": BACKGROUND_OUTPUT_INTERVAL_MS,
+            });
+            futures::SinkExt::send(
+                &mut ws_write,
+                tungstenite::Message::Text(rate_msg.to_string()),
+            )
+            .await
+            .map_err(|e| SessionError::Transient(format!("Failed to send output rate: {}", e)))?;
+        }
 
         // Notify connected
         let _ = event_tx
@@ -1096,8 +1141,13 @@ impl<H: ConnectionHandler> RemoteClient<H> {
         // Spawn writer task
         let ws_rx_clone = ws_rx.clone();
         let stream_map_for_writer = stream_map.clone();
+        let output_rate = accepted.output_rate;
         let writer_handle = tokio::spawn(async move {
             while let Ok(msg) = ws_rx_clone.recv().await {
+                // Older servers would answer with an "invalid message" error.
+                if matches!(msg, WsClientMessage::SetOutputRate { .. }) && !output_rate {
+                    continue;
+                }
                 // Prefer compact binary input when the subscription mapping is known.
                 if let WsClientMessage::SendInput { terminal_id, data } = &msg {
                     let stream_id = stream_map_for_writer
@@ -1167,6 +1217,16 @@ impl<H: ConnectionHandler> RemoteClient<H> {
                             "project_ids": project_ids,
                         })
                     }
+                    WsClientMessage::SetOutputRate {
+                        terminal_ids,
+                        interval_ms,
+                    } => {
+                        serde_json::json!({
+                            "type": "set_output_rate",
+                            "terminal_ids": terminal_ids,
+                            "interval_ms": interval_ms,
+                        })
+                    }
                 };
                 if let Err(e) = futures::SinkExt::send(
                     &mut ws_write,
@@ -1182,6 +1242,7 @@ impl<H: ConnectionHandler> RemoteClient<H> {
 
         // Reader loop
         let mut cached_state = state;
+        let mut inflater = accepted.compression.map(|_| FrameInflater::new());
         loop {
             match futures::StreamExt::next(&mut ws_read).await {
                 Some(Ok(tungstenite::Message::Binary(data))) => {
@@ -1189,9 +1250,32 @@ impl<H: ConnectionHandler> RemoteClient<H> {
                     if let Some((frame_type, stream_id, payload)) =
                         okena_core::ws::parse_binary_frame(&data)
                     {
-                        match frame_type {
+                        // A compressed payload continues the connection's one
+                        // DEFLATE stream, so a frame that fails to inflate
+                        // leaves every later one unreadable: start over.
+                        let inflated = if frame_type & FRAME_FLAG_COMPRESSED != 0 {
+                            let result = match inflater.as_mut() {
+                                Some(inflater) => inflater.inflate(payload),
+                                None => Err("compression was not negotiated".to_string()),
+                            };
+                            match result {
+                                Ok(inflated) => Some(inflated),
+                                Err(e) => {
+                                    writer_handle.abort();
+                                    return Err(SessionError::Transient(format!(
+                                        "Failed to decompress output: {}",
+                                        e
+                                    )));
+                                }
+                            }
+                        } else {
+                            None
+                        };
+                        let payload = inflated.as_deref().unwrap_or(payload);
+                        match frame_type & !FRAME_FLAG_COMPRESSED {
                             okena_core::ws::FRAME_TYPE_PTY
                             | okena_core::ws::FRAME_TYPE_SNAPSHOT => {
+                                throughput.record(data.len(), payload.len());
                                 // Route PTY output or snapshot to the correct terminal
                                 if let Some(remote_tid) = reverse_stream_map.get(&stream_id) {
                                     let prefixed = make_prefixed_id(&config_id, remote_tid);
@@ -1260,122 +1344,45 @@ impl<H: ConnectionHandler> RemoteClient<H> {
                                             .await;
                                     }
                                 }
-                                "state_changed" => {
+                                "state_changed" | "state_delta" => {
                                     log::info!("State changed on remote server");
-                                    // Reuse the session HTTP client (built once at
-                                    // Step 3) so connection pooling / keep-alive is
-                                    // preserved across state_changed events instead
-                                    // of rebuilding a client per event.
-                                    match client
-                                        .get(format!("{}/v1/state", base_url))
-                                        .header("Authorization", format!("Bearer {}", token))
-                                        .timeout(std::time::Duration::from_secs(10))
-                                        .send()
-                                        .await
-                                    {
-                                        Ok(resp) if resp.status().is_success() => {
-                                            if let Ok(new_state) =
-                                                resp.json::<StateResponse>().await
-                                            {
-                                                let diff = diff_states(&cached_state, &new_state);
-                                                let new_size_map =
-                                                    collect_terminal_sizes(&new_state);
-
-                                                // Add new terminals via handler
-                                                for tid in &diff.added_terminals {
-                                                    let prefixed =
-                                                        make_prefixed_id(&config_id, tid);
-                                                    let (cols, rows) = new_size_map
-                                                        .get(tid)
-                                                        .copied()
-                                                        .unwrap_or((0, 0));
-                                                    handler_clone.create_terminal(
-                                                        &config_id,
-                                                        tid,
-                                                        &prefixed,
-                                                        ws_tx_clone.clone(),
-                                                        cols,
-                                                        rows,
-                                                    );
-                                                }
-
-                                                // Remove old terminals via handler
-                                                for tid in &diff.removed_terminals {
-                                                    let prefixed =
-                                                        make_prefixed_id(&config_id, tid);
-                                                    handler_clone.remove_terminal(&prefixed);
-                                                }
-
-                                                // Subscribe to new terminals. Use a blocking
-                                                // send (not try_send): dropping this on a full
-                                                // channel would leave the new terminals silently
-                                                // never streaming output.
-                                                if !diff.added_terminals.is_empty()
-                                                    && let Err(e) = ws_tx_clone
-                                                        .send(WsClientMessage::Subscribe {
-                                                            terminal_ids: diff
-                                                                .added_terminals
-                                                                .clone(),
-                                                        })
-                                                        .await
-                                                {
-                                                    log::warn!(
-                                                        "failed to send Subscribe for {} terminals: {}",
-                                                        diff.added_terminals.len(),
-                                                        e
-                                                    );
-                                                }
-
-                                                // Unsubscribe from removed terminals. Likewise
-                                                // blocking — a dropped Unsubscribe leaks a stream
-                                                // for an already-gone terminal.
-                                                if !diff.removed_terminals.is_empty()
-                                                    && let Err(e) = ws_tx_clone
-                                                        .send(WsClientMessage::Unsubscribe {
-                                                            terminal_ids: diff
-                                                                .removed_terminals
-                                                                .clone(),
-                                                        })
-                                                        .await
-                                                {
-                                                    log::warn!(
-                                                        "failed to send Unsubscribe for {} terminals: {}",
-                                                        diff.removed_terminals.len(),
-                                                        e
-                                                    );
-                                                }
-
-                                                cached_state = new_state.clone();
-
+                                    // A delta that starts from the cached state
+                                    // saves the refetch; anything else (older
+                                    // server, version gap) refetches.
+                                    let patched = if msg_type == "state_delta" {
+                                        apply_state_delta(&cached_state, &value)
+                                    } else {
+                                        None
+                                    };
+                                    let new_state = match patched {
+                                        Some(state) => Some(state),
+                                        None => refetch_state(&client, &base_url, token).await,
+                                    };
+                                    if let Some(new_state) = new_state {
+                                        adopt_state(
+                                            &mut cached_state,
+                                            new_state,
+                                            &config_id,
+                                            &handler_clone,
+                                            &ws_tx_clone,
+                                            &event_tx_clone,
+                                        )
+                                        .await;
+                                    }
+                                    if may_change_settings(&value) {
+                                        match fetch_remote_settings(&client, &base_url, token).await
+                                        {
+                                            Ok(settings) => {
                                                 let _ = event_tx_clone
-                                                    .send(ConnectionEvent::StateReceived {
+                                                    .send(ConnectionEvent::SettingsChanged {
                                                         connection_id: config_id.clone(),
-                                                        state: new_state,
+                                                        settings,
                                                     })
                                                     .await;
                                             }
-                                        }
-                                        Ok(resp) => {
-                                            log::warn!(
-                                                "State re-fetch failed: HTTP {}",
-                                                resp.status()
-                                            );
-                                        }
-                                        Err(e) => {
-                                            log::warn!("State re-fetch failed: {}", e);
+                                            Err(error) => log::warn!("{error}"),
                                         }
                                     }
-                                    match fetch_remote_settings(&client, &base_url, token).await {
-                                        Ok(settings) => {
-                                            let _ = event_tx_clone
-                                                .send(ConnectionEvent::SettingsChanged {
-                                                    connection_id: config_id.clone(),
-                                                    settings,
-                                                })
-                                                .await;
-                                        }
-                                        Err(error) => log::warn!("{error}"),
-                                    }
                                 }
                                 "pong" => {
                                     // Keep-alive response, ignore
@@ -1383,6 +1390,7 @@ impl<H: ConnectionHandler> RemoteClient<H> {
                                 "dropped" => {
                                     let count =
                                         value.get("count").and_then(|v| v.as_u64()).unwrap_or(0);
+                                    throughput.record_dropped(count);
                                     log::warn!(
                                         "Server dropped {} messages for {}:{}",
                                         count,
@@ -1553,6 +1561,246 @@ impl<H: ConnectionHandler> Drop for RemoteClient<H> {
     }
 }
 
+/// Fetch `/v1/state` over the session's HTTP client, logging failures.
+/// Reuses the client built once per session so connection pooling and
+/// keep-alive survive across state changes.
+async fn refetch_state(
+    client: &reqwest::Client,
+    base_url: &str,
+    token: &str,
+) -> Option<StateResponse> {
+    match client
+        .get(format!("{}/v1/state", base_url))
+        .header("Authorization", format!("Bearer {}", token))
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await
+    {
+        Ok(resp) if resp.status().is_success() => resp.json::<StateResponse>().await.ok(),
+        Ok(resp) => {
+            log::warn!("State re-fetch failed: HTTP {}", resp.status());
+            None
+        }
+        Err(e) => {
+            log::warn!("State re-fetch failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Open the session's WebSocket. With TLS we go through
+/// connect_async_tls_with_config using the pinned rustls connector; otherwise
+/// the plain ws:// path, or the Unix socket standing in for TCP.
+async fn connect_ws(
+    config: &RemoteConnectionConfig,
+    local_unix: Option<&str>,
+    observed: &crate::client::tls::ObservedFingerprint,
+) -> Result<AnyWsStream, SessionError> {
+    // Ask for state deltas; servers that predate them ignore the query.
+    let path = format!("/v1/stream?{STATE_DELTAS_QUERY}");
+    dial_ws(config, local_unix, observed, &path)
+        .await
+        .map_err(SessionError::Transient)
+}
+
+/// Open a WebSocket to `path` (which may carry a query) over whichever
+/// transport `config` uses: `local_unix`, TLS with the pinned certificate,
+/// or plain TCP. Authentication is left to the caller.
+pub(crate) async fn dial_ws(
+    config: &RemoteConnectionConfig,
+    local_unix: Option<&str>,
+    observed: &crate::client::tls::ObservedFingerprint,
+    path: &str,
+) -> Result<AnyWsStream, String> {
+    if let Some(socket_path) = local_unix {
+        #[cfg(unix)]
+        {
+            let stream = tokio::net::UnixStream::connect(socket_path)
+                .await
+                .map_err(|e| format!("Unix socket connect failed: {}", e))?;
+            // A proxy command may reach a TLS listener; the handshake then
+            // runs over the relay with the pinned certificate.
+            let (url, connector) = if config.proxy_uses_tls() {
+                let connector = crate::client::tls::ws_connector(
+                    true,
+                    config.pinned_cert_sha256.clone(),
+                    observed.clone(),
+                );
+                (config.ws_url_for(path), connector)
+            } else {
+                (format!("ws://okena.local{path}"), None)
+            };
+            let (ws, _response) =
+                tokio_tungstenite::client_async_tls_with_config(url, stream, None, connector)
+                    .await
+                    .map_err(|e| format!("WebSocket connect failed: {}", e))?;
+            Ok(AnyWsStream::Unix(Box::new(ws)))
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = (socket_path, observed);
+            Err("Unix socket transport is not supported on this platform".to_string())
+        }
+    } else if config.tls {
+        let connector = crate::client::tls::ws_connector(
+            true,
+            config.pinned_cert_sha256.clone(),
+            observed.clone(),
+        );
+        let (ws, _response) = tokio_tungstenite::connect_async_tls_with_config(
+            config.ws_url_for(path),
+            None,
+            false,
+            connector,
+        )
+        .await
+        .map_err(|e| format!("WebSocket connect failed: {}", e))?;
+        Ok(AnyWsStream::Tcp(Box::new(ws)))
+    } else {
+        let (ws, _response) = tokio_tungstenite::connect_async(config.ws_url_for(path))
+            .await
+            .map_err(|e| format!("WebSocket connect failed: {}", e))?;
+        Ok(AnyWsStream::Tcp(Box::new(ws)))
+    }
+}
+
+/// What the server agreed to in `auth_ok`.
+struct AuthAccepted {
+    compression: Option<WsCompression>,
+    /// The server honors `set_output_rate`.
+    output_rate: bool,
+}
+
+/// Send Auth (offering `compression`, if any) and wait for the verdict.
+async fn authenticate(
+    ws: &mut AnyWsStream,
+    token: &str,
+    compression: &[WsCompression],
+) -> Result<AuthAccepted, SessionError> {
+    let mut auth_msg = serde_json::json!({
+        "type": "auth",
+        "token": token,
+    });
+    // Left out when empty: older servers reject fields they don't know.
+    if !compression.is_empty() {
+        auth_msg["compression"] = serde_json::json!(compression);
+    }
+    futures::SinkExt::send(ws, tungstenite::Message::Text(auth_msg.to_string()))
+        .await
+        .map_err(|e| SessionError::Transient(format!("Failed to send auth: {}", e)))?;
+
+    let auth_response = tokio::time::timeout(
+        std::time::Duration::from_secs(10),
+        futures::StreamExt::next(ws),
+    )
+    .await
+    .map_err(|_| SessionError::Transient("Auth response timeout".to_string()))?
+    .ok_or_else(|| SessionError::Transient("WebSocket closed before auth response".to_string()))?
+    .map_err(|e| SessionError::Transient(format!("WebSocket read error: {}", e)))?;
+
+    let tungstenite::Message::Text(text) = auth_response else {
+        return Err(SessionError::Transient(
+            "Expected text message for auth response".to_string(),
+        ));
+    };
+    let parsed: serde_json::Value = serde_json::from_str(&text)
+        .map_err(|e| SessionError::Transient(format!("Invalid JSON: {}", e)))?;
+    let msg_type = parsed.get("type").and_then(|v| v.as_str()).unwrap_or("");
+    match msg_type {
+        "auth_ok" => Ok(AuthAccepted {
+            // Servers that predate either feature leave the field out.
+            compression: parsed
+                .get("compression")
+                .and_then(|v| serde_json::from_value(v.clone()).ok()),
+            output_rate: parsed
+                .get("output_rate")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        }),
+        "auth_failed" => {
+            let error = parsed
+                .get("error")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown");
+            Err(SessionError::Auth(format!("Auth failed: {}", error)))
+        }
+        _ => Err(SessionError::Transient(format!(
+            "Unexpected auth response type: {}",
+            msg_type
+        ))),
+    }
+}
+
+/// Make `new_state` the session's state: create and subscribe terminals that
+/// appeared, remove and unsubscribe those that went away, and emit
+/// `StateReceived`.
+async fn adopt_state<H: ConnectionHandler>(
+    cached_state: &mut StateResponse,
+    new_state: StateResponse,
+    config_id: &str,
+    handler: &Arc<H>,
+    ws_tx: &async_channel::Sender<WsClientMessage>,
+    event_tx: &async_channel::Sender<ConnectionEvent>,
+) {
+    let diff = diff_states(cached_state, &new_state);
+    let new_size_map = collect_terminal_sizes(&new_state);
+
+    // Add new terminals via handler
+    for tid in &diff.added_terminals {
+        let prefixed = make_prefixed_id(config_id, tid);
+        let (cols, rows) = new_size_map.get(tid).copied().unwrap_or((0, 0));
+        handler.create_terminal(config_id, tid, &prefixed, ws_tx.clone(), cols, rows);
+    }
+
+    // Remove old terminals via handler
+    for tid in &diff.removed_terminals {
+        let prefixed = make_prefixed_id(config_id, tid);
+        handler.remove_terminal(&prefixed);
+    }
+
+    // Subscribe to new terminals. Use a blocking send (not try_send): dropping
+    // this on a full channel would leave the new terminals silently never
+    // streaming output.
+    if !diff.added_terminals.is_empty()
+        && let Err(e) = ws_tx
+            .send(WsClientMessage::Subscribe {
+                terminal_ids: diff.added_terminals.clone(),
+            })
+            .await
+    {
+        log::warn!(
+            "failed to send Subscribe for {} terminals: {}",
+            diff.added_terminals.len(),
+            e
+        );
+    }
+
+    // Unsubscribe from removed terminals. Likewise blocking — a dropped
+    // Unsubscribe leaks a stream for an already-gone terminal.
+    if !diff.removed_terminals.is_empty()
+        && let Err(e) = ws_tx
+            .send(WsClientMessage::Unsubscribe {
+                terminal_ids: diff.removed_terminals.clone(),
+            })
+            .await
+    {
+        log::warn!(
+            "failed to send Unsubscribe for {} terminals: {}",
+            diff.removed_terminals.len(),
+            e
+        );
+    }
+
+    *cached_state = new_state.clone();
+
+    let _ = event_tx
+        .send(ConnectionEvent::StateReceived {
+            connection_id: config_id.to_string(),
+            state: new_state,
+        })
+        .await;
+}
+
 /// Attempt to refresh a token if it's older than 20 hours.
 /// On success, sends a `TokenRefreshed` event. On failure, logs a warning.
 pub async fn try_refresh_token(
@@ -1576,16 +1824,15 @@ pub async fn try_refresh_token(
     }
     // If token_obtained_at is None, attempt refresh (legacy token without timestamp)
 
-    let local_unix = local_unix_path(config);
     let base_url = config.http_origin();
-    let client = if let Some(path) = local_unix {
-        unix_http_client(path)
-    } else {
-        crate::client::tls::build_reqwest_client(
+    let client = match local_unix_path(config) {
+        Ok(Some(path)) => unix_http_client(config, &path, crate::client::tls::new_observed()),
+        Ok(None) => crate::client::tls::build_reqwest_client(
             config.tls,
             config.pinned_cert_sha256.clone(),
             crate::client::tls::new_observed(),
-        )
+        ),
+        Err(error) => Err(error),
     };
     let client = match client {
         Ok(client) => client,
@@ -1651,6 +1898,7 @@ pub async fn try_refresh_token(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::client::config::LocalEndpoint;
     use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[derive(Default)]
@@ -1699,6 +1947,7 @@ mod tests {
             tls: false,
             pinned_cert_sha256: None,
             local_endpoint,
+            proxy_command: None,
         }
     }
 
@@ -1795,6 +2044,7 @@ mod tests {
             folders: Vec::new(),
             windows: Vec::new(),
             hooks: Vec::new(),
+            shares: Vec::new(),
         }));
 
         client.reconnect();
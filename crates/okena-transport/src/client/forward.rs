@@ -0,0 +1,320 @@
+//! Local port forwards to a remote daemon's services.
+//!
+//! [`PortForward`] listens on a loopback port and relays every accepted
+//! connection over its own `/v1/forward` WebSocket (see
+//! [`okena_core::forward`]), so a forward reuses the connection's transport,
+//! certificate pin and token instead of an SSH tunnel.
+
+use crate::client::config::RemoteConnectionConfig;
+use crate::client::connection::{dial_ws, local_unix_path};
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use okena_core::forward::{FORWARD_PATH, ForwardReply, PortForwardQuery};
+use okena_core::ws::WsInbound;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinSet;
+use tokio_tungstenite::tungstenite::{self, Message};
+
+/// How long the daemon has to answer `auth` with a [`ForwardReply`].
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest binary frame sent towards the daemon.
+const RELAY_CHUNK: usize = 64 * 1024;
+
+/// A running forward. Dropping it stops listening and cuts the connections
+/// it relays.
+pub struct PortForward {
+    target: PortForwardQuery,
+    local_addr: SocketAddr,
+    accept_task: tokio::task::JoinHandle<()>,
+}
+
+impl PortForward {
+    /// Check `target` with the daemon, then listen on `127.0.0.1`.
+    ///
+    /// `local_port` `None` reuses the remote port when it is free locally and
+    /// takes any free port otherwise. Must be called within a Tokio runtime.
+    pub async fn start(
+        config: RemoteConnectionConfig,
+        token: String,
+        target: PortForwardQuery,
+        local_port: Option<u16>,
+    ) -> Result<Self, String> {
+        let local_unix = local_unix_path(&config)?;
+        let dialer = Arc::new(Dialer {
+            config,
+            token,
+            local_unix,
+        });
+        // A first connection surfaces scope and target errors before
+        // anything listens, and pins the port the daemon picked.
+        let (mut probe, port) = dialer.open(&target).await?;
+        let _ = probe.close().await;
+        let target = PortForwardQuery {
+            port: Some(port),
+            ..target
+        };
+
+        let listener = bind_local(local_port, port).await?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| format!("Cannot read the local address: {e}"))?;
+        let accept_task = tokio::spawn(accept_loop(listener, dialer, target.clone()));
+        Ok(Self {
+            target,
+            local_addr,
+            accept_task,
+        })
+    }
+
+    /// The forwarded service, with the remote port resolved.
+    pub fn target(&self) -> &PortForwardQuery {
+        &self.target
+    }
+
+    pub fn remote_port(&self) -> u16 {
+        self.target.port.unwrap_or_default()
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// `http://localhost:<local port>`, for opening in a browser.
+    pub fn url(&self) -> String {
+        format!("http://localhost:{}", self.local_addr.port())
+    }
+}
+
+impl Drop for PortForward {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+    }
+}
+
+async fn bind_local(local_port: Option<u16>, remote_port: u16) -> Result<TcpListener, String> {
+    let bind = |port: u16| TcpListener::bind((Ipv4Addr::LOCALHOST, port));
+    match local_port {
+        Some(port) => bind(port)
+            .await
+            .map_err(|e| format!("Cannot listen on local port {port}: {e}")),
+        None => match bind(remote_port).await {
+            Ok(listener) => Ok(listener),
+            Err(_) => bind(0)
+                .await
+                .map_err(|e| format!("Cannot listen on a local port: {e}")),
+        },
+    }
+}
+
+/// Accept until aborted. The relays live in a [`JoinSet`] owned by this
+/// task, so aborting it drops them too.
+async fn accept_loop(listener: TcpListener, dialer: Arc<Dialer>, target: PortForwardQuery) {
+    let mut relays = JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let tcp = match accepted {
+                    Ok((tcp, _)) => tcp,
+                    Err(e) => {
+                        log::warn!("port forward to {}: accept failed: {e}", target.service);
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        continue;
+                    }
+                };
+                let _ = tcp.set_nodelay(true);
+                let dialer = dialer.clone();
+                let target = target.clone();
+                relays.spawn(async move {
+                    match dialer.open(&target).await {
+                        Ok((ws, _)) => relay(ws, tcp).await,
+                        Err(e) => log::warn!("port forward to {}: {e}", target.service),
+                    }
+                });
+            }
+            Some(_) = relays.join_next(), if !relays.is_empty() => {}
+        }
+    }
+}
+
+struct Dialer {
+    config: RemoteConnectionConfig,
+    token: String,
+    local_unix: Option<String>,
+}
+
+impl Dialer {
+    /// Open and authenticate one forward socket, returning it with the
+    /// remote port once the daemon has connected to the service.
+    async fn open(
+        &self,
+        target: &PortForwardQuery,
+    ) -> Result<(crate::client::connection::AnyWsStream, u16), String> {
+        let path = format!("{FORWARD_PATH}?{}", target.to_query_string());
+        let observed = crate::client::tls::new_observed();
+        let mut ws = dial_ws(&self.config, self.local_unix.as_deref(), &observed, &path).await?;
+        let auth = WsInbound::Auth {
+            token: self.token.clone(),
+            compression: Vec::new(),
+        };
+        let auth = serde_json::to_string(&auth).map_err(|e| e.to_string())?;
+        ws.send(Message::Text(auth))
+            .await
+            .map_err(|e| format!("Failed to send auth: {e}"))?;
+        let reply = tokio::time::timeout(REPLY_TIMEOUT, read_reply(&mut ws))
+            .await
+            .map_err(|_| "Timed out waiting for the daemon".to_string())??;
+        match reply {
+            ForwardReply::Ready { port } => Ok((ws, port)),
+            ForwardReply::Error { error } => Err(error),
+        }
+    }
+}
+
+async fn read_reply<S>(ws: &mut S) -> Result<ForwardReply, String>
+where
+    S: Stream<Item = Result<Message, tungstenite::Error>> + Unpin,
+{
+    while let Some(message) = ws.next().await {
+        match message.map_err(|e| format!("Forward connection failed: {e}"))? {
+            Message::Text(text) => {
+                return serde_json::from_str(&text)
+                    .map_err(|e| format!("Unexpected reply from the daemon: {e}"));
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+    Err("The daemon closed the forward before it was ready".to_string())
+}
+
+/// Copy bytes both ways until both directions reach EOF or either side
+/// fails. EOF travels as an empty binary frame, so a local client that
+/// half-closes still receives the whole reply.
+async fn relay<S>(ws: S, tcp: TcpStream)
+where
+    S: Stream<Item = Result<Message, tungstenite::Error>>
+        + Sink<Message, Error = tungstenite::Error>
+        + Unpin,
+{
+    let (mut ws_tx, mut ws_rx) = ws.split();
+    let (mut tcp_rx, mut tcp_tx) = tcp.into_split();
+    {
+        let upstream = async {
+            let mut buf = vec![0u8; RELAY_CHUNK];
+            loop {
+                match tcp_rx.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        if ws_tx
+                            .send(Message::Binary(buf[..n].to_vec()))
+                            .await
+                            .is_err()
+                        {
+                            return false;
+                        }
+                    }
+                }
+            }
+            ws_tx.send(Message::Binary(Vec::new())).await.is_ok()
+        };
+        let downstream = async {
+            while let Some(Ok(message)) = ws_rx.next().await {
+                match message {
+                    Message::Binary(data) => {
+                        if data.is_empty() {
+                            let _ = tcp_tx.shutdown().await;
+                            return true;
+                        }
+                        if tcp_tx.write_all(&data).await.is_err() {
+                            return false;
+                        }
+                    }
+                    Message::Close(_) => return false,
+                    _ => {}
+                }
+            }
+            false
+        };
+        tokio::pin!(upstream, downstream);
+        tokio::select! {
+            done = &mut upstream => if done { downstream.await; },
+            done = &mut downstream => if done { upstream.await; },
+        }
+    }
+    let _ = ws_tx.close().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_tungstenite::WebSocketStream;
+    use tokio_tungstenite::tungstenite::protocol::Role;
+
+    #[tokio::test]
+    async fn relay_carries_half_closes_both_ways() {
+        let (client_io, daemon_io) = tokio::io::duplex(1024);
+        let ws = WebSocketStream::from_raw_socket(client_io, Role::Client, None).await;
+        let mut daemon = WebSocketStream::from_raw_socket(daemon_io, Role::Server, None).await;
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let mut browser = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (accepted, _) = listener.accept().await.unwrap();
+        let relay = tokio::spawn(relay(ws, accepted));
+
+        browser.write_all(b"GET / HTTP/1.0\r\n\r\n").await.unwrap();
+        browser.shutdown().await.unwrap();
+        let mut request = Vec::new();
+        loop {
+            match daemon.next().await.unwrap().unwrap() {
+                Message::Binary(data) if data.is_empty() => break,
+                Message::Binary(data) => request.extend(data),
+                other => panic!("unexpected {other:?}"),
+            }
+        }
+        assert_eq!(request, b"GET / HTTP/1.0\r\n\r\n");
+
+        // The browser's half-close must not stop the reply.
+        daemon
+            .send(Message::Binary(b"HTTP/1.0 200 OK\r\n\r\nhi".to_vec()))
+            .await
+            .unwrap();
+        daemon.send(Message::Binary(Vec::new())).await.unwrap();
+        let mut response = Vec::new();
+        browser.read_to_end(&mut response).await.unwrap();
+        assert_eq!(response, b"HTTP/1.0 200 OK\r\n\r\nhi");
+
+        relay.await.unwrap();
+        assert!(matches!(daemon.next().await, Some(Ok(Message::Close(_)))));
+    }
+
+    #[tokio::test]
+    async fn busy_remote_port_falls_back_to_any_port() {
+        let taken = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let port = taken.local_addr().unwrap().port();
+
+        assert!(bind_local(Some(port), port).await.is_err());
+        let listener = bind_local(None, port).await.unwrap();
+        assert_ne!(listener.local_addr().unwrap().port(), port);
+    }
+
+    #[tokio::test]
+    async fn error_reply_is_surfaced() {
+        let (client_io, daemon_io) = tokio::io::duplex(1024);
+        let mut ws = WebSocketStream::from_raw_socket(client_io, Role::Client, None).await;
+        let mut daemon = WebSocketStream::from_raw_socket(daemon_io, Role::Server, None).await;
+        let error = ForwardReply::Error {
+            error: "token lacks the 'services' scope".to_string(),
+        };
+        daemon
+            .send(Message::Text(serde_json::to_string(&error).unwrap()))
+            .await
+            .unwrap();
+        assert_eq!(read_reply(&mut ws).await, Ok(error));
+    }
+}
@@ -1,4 +1,6 @@
 use okena_core::api::{ApiLayoutNode, StateResponse};
+use okena_core::ws::WsOutbound;
+use serde::Deserialize;
 use std::collections::HashSet;
 
 /// Represents the differences between two remote state snapshots.
@@ -85,6 +87,29 @@ pub fn collect_state_terminal_ids(state: &StateResponse) -> Vec<String> {
     ids
 }
 
+/// Terminals of every project outside `visible_projects`, sorted. These are
+/// the subscriptions a client can ask the server to coalesce, since nothing
+/// on screen is waiting on their output.
+pub fn collect_background_terminal_ids(
+    state: &StateResponse,
+    visible_projects: &[String],
+) -> Vec<String> {
+    let mut ids = Vec::new();
+    for project in &state.projects {
+        if visible_projects.contains(&project.id) {
+            continue;
+        }
+        if let Some(ref layout) = project.layout {
+            collect_layout_terminal_ids_into(layout, &mut ids);
+        }
+        for hook in &project.hook_terminals {
+            ids.push(hook.terminal_id.clone());
+        }
+    }
+    ids.sort();
+    ids
+}
+
 /// Collect terminal IDs from a single layout tree in render order.
 pub fn collect_layout_terminal_ids(node: &ApiLayoutNode) -> Vec<String> {
     let mut ids = Vec::new();
@@ -146,6 +171,50 @@ fn collect_layout_terminal_sizes(
     }
 }
 
+/// `cached` advanced by a `state_delta` WebSocket message, or `None` when the
+/// delta doesn't start from `cached`'s version or doesn't apply, in which
+/// case the client refetches `/v1/state`.
+pub fn apply_state_delta(
+    cached: &StateResponse,
+    message: &serde_json::Value,
+) -> Option<StateResponse> {
+    let Ok(WsOutbound::StateDelta {
+        base_version,
+        state_version,
+        delta,
+    }) = WsOutbound::deserialize(message)
+    else {
+        log::warn!("Malformed state delta, refetching state");
+        return None;
+    };
+    if base_version != cached.state_version {
+        log::debug!(
+            "State delta from version {base_version} but holding {}, refetching",
+            cached.state_version
+        );
+        return None;
+    }
+    let mut next = cached.clone();
+    match okena_core::state_delta::apply(&mut next, delta, state_version) {
+        Ok(()) => Some(next),
+        Err(error) => {
+            log::warn!("{error}, refetching state");
+            None
+        }
+    }
+}
+
+/// Whether a `state_changed` / `state_delta` message may follow a settings
+/// change. Settings aren't part of the state, so a change to them (or to the
+/// theme) bumps the version with nothing to diff: only a bare
+/// `state_changed` or an empty delta can carry one.
+pub fn may_change_settings(message: &serde_json::Value) -> bool {
+    match WsOutbound::deserialize(message) {
+        Ok(WsOutbound::StateDelta { delta, .. }) => delta.is_empty(),
+        _ => true,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,6 +232,7 @@ mod tests {
             folders: vec![],
             windows: vec![],
             hooks: Vec::new(),
+            shares: Vec::new(),
         }
     }
 
@@ -272,6 +342,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn background_terminals_are_those_of_hidden_projects() {
+        let state = make_state(vec![
+            make_project("p1", vec!["t1"]),
+            make_project("p2", vec!["t3", "t2"]),
+            make_project("p3", vec![]),
+        ]);
+        assert_eq!(
+            collect_background_terminal_ids(&state, &["p1".to_string()]),
+            vec!["t2", "t3"]
+        );
+        assert_eq!(
+            collect_background_terminal_ids(&state, &[]),
+            vec!["t1", "t2", "t3"]
+        );
+    }
+
     #[test]
     fn diff_states_detects_changed_projects() {
         let old = make_state(vec![make_project("p1", vec!["t1"])]);
@@ -387,4 +474,45 @@ mod tests {
         assert_eq!(sizes.get("t1"), Some(&(120, 40)));
         assert_eq!(sizes.get("t2"), None);
     }
+
+    fn delta_message(old: &StateResponse, new: &StateResponse) -> serde_json::Value {
+        serde_json::to_value(WsOutbound::StateDelta {
+            base_version: old.state_version,
+            state_version: new.state_version,
+            delta: okena_core::state_delta::diff(old, new),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn apply_state_delta_advances_matching_version() {
+        let old = make_state(vec![make_project("p1", vec!["t1"])]);
+        let mut new = make_state(vec![make_project("p1", vec!["t1", "t2"])]);
+        new.state_version = 4;
+        let applied = apply_state_delta(&old, &delta_message(&old, &new)).unwrap();
+        assert_eq!(applied.state_version, 4);
+        assert_eq!(diff_states(&old, &applied).added_terminals, vec!["t2"]);
+    }
+
+    #[test]
+    fn only_bare_changes_and_empty_deltas_refetch_settings() {
+        let old = make_state(vec![make_project("p1", vec!["t1"])]);
+        let mut new = old.clone();
+        new.state_version = 2;
+        assert!(may_change_settings(&delta_message(&old, &new)));
+        new.projects.push(make_project("p2", vec!["t2"]));
+        assert!(!may_change_settings(&delta_message(&old, &new)));
+        let changed = serde_json::to_value(WsOutbound::StateChanged { state_version: 2 }).unwrap();
+        assert!(may_change_settings(&changed));
+    }
+
+    #[test]
+    fn apply_state_delta_refuses_a_version_gap() {
+        let old = make_state(vec![make_project("p1", vec!["t1"])]);
+        let mut new = old.clone();
+        new.state_version = 4;
+        let mut message = delta_message(&old, &new);
+        message["base_version"] = 3.into();
+        assert!(apply_state_delta(&old, &message).is_none());
+    }
 }
@@ -0,0 +1,183 @@
+use okena_core::ws::WsCompression;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Window the receive rate is averaged over.
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Terminal output counters for one connection's WebSocket stream.
+///
+/// Shared between the session reader (which records every output frame) and
+/// the UI (which polls [`StreamMeter::snapshot`]). Totals restart with each
+/// session so the compression ratio reflects the codec negotiated last.
+#[derive(Default)]
+pub struct StreamMeter {
+    inner: Mutex<MeterState>,
+}
+
+#[derive(Default)]
+struct MeterState {
+    compression: Option<WsCompression>,
+    wire_bytes: u64,
+    output_bytes: u64,
+    dropped: u64,
+    window_start: Option<Instant>,
+    window_bytes: u64,
+    bytes_per_sec: u64,
+}
+
+/// Point-in-time view of a [`StreamMeter`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StreamThroughput {
+    /// Codec the server agreed to, `None` for raw frames.
+    pub compression: Option<WsCompression>,
+    /// Output frame bytes as received on the socket.
+    pub wire_bytes: u64,
+    /// The same frames after decompression.
+    pub output_bytes: u64,
+    /// Wire receive rate over the last second.
+    pub bytes_per_sec: u64,
+    /// Messages the server reported dropping because this client lagged.
+    pub dropped: u64,
+}
+
+impl StreamThroughput {
+    /// Decompressed bytes per wire byte; 1.0 until anything arrives.
+    pub fn compression_ratio(&self) -> f64 {
+        if self.wire_bytes == 0 {
+            return 1.0;
+        }
+        self.output_bytes as f64 / self.wire_bytes as f64
+    }
+}
+
+impl StreamMeter {
+    /// Start counting a new session.
+    pub(crate) fn reset(&self, compression: Option<WsCompression>) {
+        if let Ok(mut state) = self.inner.lock() {
+            *state = MeterState {
+                compression,
+                ..MeterState::default()
+            };
+        }
+    }
+
+    /// Count one output frame: `wire` bytes received, `output` after decoding.
+    pub(crate) fn record(&self, wire: usize, output: usize) {
+        self.record_at(Instant::now(), wire, output);
+    }
+
+    pub(crate) fn record_dropped(&self, count: u64) {
+        if let Ok(mut state) = self.inner.lock() {
+            state.dropped += count;
+        }
+    }
+
+    pub fn snapshot(&self) -> StreamThroughput {
+        self.snapshot_at(Instant::now())
+    }
+
+    fn record_at(&self, now: Instant, wire: usize, output: usize) {
+        if let Ok(mut state) = self.inner.lock() {
+            state.roll(now);
+            state.wire_bytes += wire as u64;
+            state.output_bytes += output as u64;
+            state.window_bytes += wire as u64;
+        }
+    }
+
+    fn snapshot_at(&self, now: Instant) -> StreamThroughput {
+        let Ok(mut state) = self.inner.lock() else {
+            return StreamThroughput::default();
+        };
+        state.roll(now);
+        StreamThroughput {
+            compression: state.compression,
+            wire_bytes: state.wire_bytes,
+            output_bytes: state.output_bytes,
+            bytes_per_sec: state.bytes_per_sec,
+            dropped: state.dropped,
+        }
+    }
+}
+
+impl MeterState {
+    /// Close the rate window once it has run its length. A window that ran
+    /// long (nothing arrived to close it) averages over its whole span, so an
+    /// idle stream decays to zero instead of showing its last burst.
+    fn roll(&mut self, now: Instant) {
+        let Some(start) = self.window_start else {
+            self.window_start = Some(now);
+            return;
+        };
+        let elapsed = now.saturating_duration_since(start);
+        if elapsed < RATE_WINDOW {
+            return;
+        }
+        self.bytes_per_sec = (self.window_bytes as f64 / elapsed.as_secs_f64()) as u64;
+        self.window_bytes = 0;
+        self.window_start = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_covers_the_last_full_window() {
+        let meter = StreamMeter::default();
+        let start = Instant::now();
+        meter.record_at(start, 0, 0);
+        meter.record_at(start + Duration::from_millis(200), 4_000, 10_000);
+        meter.record_at(start + Duration::from_millis(600), 4_000, 10_000);
+        assert_eq!(
+            meter
+                .snapshot_at(start + Duration::from_millis(900))
+                .bytes_per_sec,
+            0
+        );
+
+        let stats = meter.snapshot_at(start + Duration::from_secs(1));
+        assert_eq!(stats.bytes_per_sec, 8_000);
+        assert_eq!(stats.wire_bytes, 8_000);
+        assert_eq!(stats.output_bytes, 20_000);
+        assert_eq!(stats.compression_ratio(), 2.5);
+    }
+
+    #[test]
+    fn idle_stream_decays_to_zero() {
+        let meter = StreamMeter::default();
+        let start = Instant::now();
+        meter.record_at(start, 0, 0);
+        meter.record_at(start + Duration::from_millis(500), 10_000, 10_000);
+        assert_eq!(
+            meter
+                .snapshot_at(start + Duration::from_secs(10))
+                .bytes_per_sec,
+            1_000
+        );
+        assert_eq!(
+            meter
+                .snapshot_at(start + Duration::from_secs(12))
+                .bytes_per_sec,
+            0
+        );
+    }
+
+    #[test]
+    fn reset_starts_a_new_session() {
+        let meter = StreamMeter::default();
+        meter.record(100, 300);
+        meter.record_dropped(2);
+        meter.reset(Some(WsCompression::Deflate));
+
+        let stats = meter.snapshot();
+        assert_eq!(stats.compression, Some(WsCompression::Deflate));
+        assert_eq!(
+            (stats.wire_bytes, stats.output_bytes, stats.dropped),
+            (0, 0, 0)
+        );
+        assert_eq!(stats.compression_ratio(), 1.0);
+    }
+}
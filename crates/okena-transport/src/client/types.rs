@@ -39,6 +39,13 @@ pub enum WsClientMessage {
     /// Declare which projects this client renders, as a full replacement set.
     /// Server-side ids (unprefixed) — scopes the server's `gh` PR/CI fan-out.
     SetVisibleProjects { project_ids: Vec<String> },
+    /// Ask the server to coalesce these terminals' output into one frame per
+    /// `interval_ms`, as a full replacement set. Dropped unless the server
+    /// advertised support in `auth_ok`.
+    SetOutputRate {
+        terminal_ids: Vec<String>,
+        interval_ms: u32,
+    },
 }
 
 /// Error type distinguishing auth failures from transient network errors.
@@ -46,6 +46,13 @@ pub struct RemoteConnectionConfig {
     /// Remote user-managed connections leave this empty and use TCP host/port.
     #[serde(default)]
     pub local_endpoint: Option<LocalEndpoint>,
+    /// Command whose stdin/stdout carry the connection instead of TCP, e.g.
+    /// `ssh -W %h:%p jump` or `ssh host okena-daemon --stdio`. `%h` and `%p`
+    /// expand to `host` and `port`. With `tls` the pinned handshake runs
+    /// through the command, except for `okena-daemon --stdio`, which lands
+    /// on the daemon's plain Unix socket.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_command: Option<String>,
 }
 
 impl RemoteConnectionConfig {
@@ -57,11 +64,34 @@ impl RemoteConnectionConfig {
 
     /// `ws://host:port/v1/stream` or `wss://…` depending on `tls`.
     pub fn ws_url(&self) -> String {
+        self.ws_url_for("/v1/stream")
+    }
+
+    /// [`Self::ws_url`] for another WebSocket route; `path` may carry a query.
+    pub fn ws_url_for(&self, path: &str) -> String {
         let scheme = if self.tls { "wss" } else { "ws" };
-        format!("{}://{}:{}/v1/stream", scheme, self.host, self.port)
+        format!("{}://{}:{}{}", scheme, self.host, self.port, path)
+    }
+
+    /// Whether TLS runs through the proxy command. `ssh -W` ends at the
+    /// server's TCP listener, which may refuse plaintext; `okena-daemon
+    /// --stdio` ends at its Unix socket, which never speaks TLS.
+    pub fn proxy_uses_tls(&self) -> bool {
+        self.tls
+            && self
+                .proxy_command
+                .as_deref()
+                .is_some_and(|command| !command.contains("--stdio"))
     }
 
     pub fn http_origin(&self) -> String {
+        if self.proxy_command.is_some() {
+            return if self.proxy_uses_tls() {
+                self.base_url()
+            } else {
+                "http://okena.local".to_string()
+            };
+        }
         match &self.local_endpoint {
             Some(LocalEndpoint::UnixSocket { .. }) => "http://okena.local".to_string(),
             _ => self.base_url(),
@@ -73,6 +103,9 @@ impl RemoteConnectionConfig {
     }
 
     pub fn display_endpoint(&self) -> String {
+        if let Some(command) = &self.proxy_command {
+            return format!("{}:{} via `{}`", self.host, self.port, command);
+        }
         match &self.local_endpoint {
             Some(LocalEndpoint::UnixSocket { path }) => format!("unix:{path}"),
             Some(LocalEndpoint::NamedPipe { name }) => format!("pipe:{name}"),
@@ -86,10 +119,13 @@ impl RemoteConnectionConfig {
 
     /// Bearer token to send on requests. Same-user Unix socket connections are
     /// trusted by transport and use an empty token only to satisfy client flows
-    /// that carry a token string alongside remote actions.
+    /// that carry a token string alongside remote actions. A proxy command
+    /// tries the same: through `okena-daemon --stdio` it lands on the remote
+    /// daemon's Unix socket; otherwise the server answers 401 and pairing
+    /// starts as usual.
     pub fn effective_auth_token(&self) -> Option<String> {
-        self.saved_token
-            .clone()
-            .or_else(|| self.is_trusted_local_transport().then(String::new))
+        self.saved_token.clone().or_else(|| {
+            (self.is_trusted_local_transport() || self.proxy_command.is_some()).then(String::new)
+        })
     }
 }
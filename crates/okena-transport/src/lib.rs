@@ -17,6 +17,8 @@ pub mod client;
 #[cfg(feature = "blocking-http")]
 pub mod http;
 #[cfg(any(feature = "client", feature = "blocking-http"))]
+pub mod proxy;
+#[cfg(any(feature = "client", feature = "blocking-http"))]
 pub mod remote_action;
 #[cfg(any(feature = "client", feature = "blocking-http"))]
 pub mod remote_http;
@@ -0,0 +1,219 @@
+//! Proxy-command transport: reach a server through a command's stdin/stdout,
+//! OpenSSH `ProxyCommand` style (`ssh -W %h:%p host`,
+//! `ssh host okena-daemon --stdio`).
+//!
+//! Each distinct command gets a relay: a private Unix socket whose every
+//! accepted connection spawns the command and splices the two together. The
+//! HTTP and WebSocket clients then dial that socket exactly like a local
+//! daemon's, so the protocol code needs no second transport.
+
+use crate::RemoteConnectionConfig;
+
+/// Substitute `%h` (host), `%p` (port) and `%%` in a proxy command template.
+/// Unknown `%` sequences are kept verbatim.
+pub fn expand_proxy_command(template: &str, host: &str, port: u16) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('h') => out.push_str(host),
+            Some('p') => out.push_str(&port.to_string()),
+            Some('%') => out.push('%'),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+    out
+}
+
+impl RemoteConnectionConfig {
+    /// Unix socket to dial instead of TCP: the local daemon's endpoint, or the
+    /// relay fronting this connection's proxy command (started on first use).
+    pub fn unix_transport_path(&self) -> Result<Option<String>, String> {
+        if let Some(template) = &self.proxy_command {
+            let command = expand_proxy_command(template, &self.host, self.port);
+            return relay_socket(&command).map(Some);
+        }
+        #[cfg(unix)]
+        if let Some(crate::LocalEndpoint::UnixSocket { path }) = &self.local_endpoint {
+            return Ok(Some(path.clone()));
+        }
+        Ok(None)
+    }
+}
+
+/// Socket path of the relay for `command`, starting it if needed. Relays live
+/// for the rest of the process.
+#[cfg(unix)]
+pub fn relay_socket(command: &str) -> Result<String, String> {
+    use std::collections::HashMap;
+    use std::sync::{Mutex, OnceLock};
+
+    static RELAYS: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+    let mut relays = RELAYS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(path) = relays.get(command) {
+        return Ok(path.clone());
+    }
+    let path = unix::start_relay(command, relays.len())
+        .map_err(|e| format!("Cannot start proxy command relay: {e}"))?;
+    relays.insert(command.to_string(), path.clone());
+    Ok(path)
+}
+
+#[cfg(not(unix))]
+pub fn relay_socket(_command: &str) -> Result<String, String> {
+    Err("Proxy command transport is not supported on this platform".to_string())
+}
+
+/// Copy until EOF, flushing every chunk: WebSocket frames must not wait in a
+/// buffer (stdout is line-buffered) for more data. Shared with the daemon's
+/// `--stdio` end of the pipe.
+pub fn pump(from: &mut impl std::io::Read, to: &mut impl std::io::Write) -> std::io::Result<()> {
+    let mut buf = [0u8; 16 * 1024];
+    loop {
+        let n = match from.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        to.write_all(&buf[..n])?;
+        to.flush()?;
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::pump;
+    use std::io::{self, BufRead};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::PathBuf;
+    use std::process::{Command, Stdio};
+    use std::sync::OnceLock;
+
+    /// Private per-process directory holding the relay sockets. Randomly
+    /// named and created 0700, so no other account can pre-create it on a
+    /// shared /tmp or connect through someone else's ssh session. Kept for
+    /// the life of the process, like the relays themselves.
+    fn relay_dir() -> io::Result<PathBuf> {
+        static DIR: OnceLock<PathBuf> = OnceLock::new();
+        if let Some(dir) = DIR.get() {
+            return Ok(dir.clone());
+        }
+        let dir = tempfile::Builder::new()
+            .prefix("okena-proxy-")
+            .permissions(std::fs::Permissions::from_mode(0o700))
+            .tempdir()?
+            .keep();
+        Ok(DIR.get_or_init(|| dir).clone())
+    }
+
+    pub(super) fn start_relay(command: &str, index: usize) -> io::Result<String> {
+        let path = relay_dir()?.join(format!("{index}.sock"));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path)?;
+        let command = command.to_string();
+        std::thread::Builder::new()
+            .name("okena-proxy-relay".to_string())
+            .spawn(move || {
+                for conn in listener.incoming() {
+                    match conn {
+                        Ok(conn) => {
+                            let command = command.clone();
+                            std::thread::spawn(move || {
+                                if let Err(e) = splice(conn, &command) {
+                                    log::warn!("Proxy command `{command}` failed: {e}");
+                                }
+                            });
+                        }
+                        Err(e) => log::warn!("Proxy relay accept failed: {e}"),
+                    }
+                }
+            })?;
+        Ok(path.to_string_lossy().into_owned())
+    }
+
+    /// Run `command` through the shell (so quoting works as in an ssh_config
+    /// `ProxyCommand`) and carry `conn` over its stdin/stdout until either
+    /// side closes.
+    fn splice(conn: UnixStream, command: &str) -> io::Result<()> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let (Some(mut stdin), Some(mut stdout), Some(stderr)) =
+            (child.stdin.take(), child.stdout.take(), child.stderr.take())
+        else {
+            return Err(io::Error::other("child stdio was not piped"));
+        };
+
+        let name = command.to_string();
+        std::thread::spawn(move || {
+            for line in io::BufReader::new(stderr).lines().map_while(Result::ok) {
+                log::warn!("[{name}] {line}");
+            }
+        });
+        let mut upstream_conn = conn.try_clone()?;
+        let upstream = std::thread::spawn(move || {
+            // Dropping stdin afterwards is the command's EOF.
+            let _ = pump(&mut upstream_conn, &mut stdin);
+        });
+
+        let mut downstream_conn = conn.try_clone()?;
+        let result = pump(&mut stdout, &mut downstream_conn);
+        let _ = conn.shutdown(std::net::Shutdown::Both);
+        let _ = upstream.join();
+        let _ = child.kill();
+        let _ = child.wait();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_host_port_and_literal_percent() {
+        assert_eq!(
+            expand_proxy_command("ssh -W %h:%p jump", "box.lan", 19100),
+            "ssh -W box.lan:19100 jump"
+        );
+        assert_eq!(
+            expand_proxy_command("echo 100%% %x%", "h", 1),
+            "echo 100% %x%"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn relay_carries_bytes_through_the_command() {
+        use std::io::{Read, Write};
+        use std::os::unix::net::UnixStream;
+
+        // `cat` stands in for ssh: whatever goes in comes straight back.
+        let path = relay_socket("cat").unwrap();
+        assert_eq!(relay_socket("cat").unwrap(), path);
+
+        let mut conn = UnixStream::connect(&path).unwrap();
+        conn.write_all(b"GET /health HTTP/1.1\r\n\r\n").unwrap();
+        conn.shutdown(std::net::Shutdown::Write).unwrap();
+        let mut echoed = String::new();
+        conn.read_to_string(&mut echoed).unwrap();
+        assert_eq!(echoed, "GET /health HTTP/1.1\r\n\r\n");
+    }
+}
@@ -1,7 +1,7 @@
 //! Shared async and blocking clients for actions sent to an Okena daemon.
 
 use crate::RemoteConnectionConfig;
-use okena_core::api::{ActionRequest, FileDownloadRequest};
+use okena_core::api::{ActionRequest, FileDownloadRequest, FileUploadRequest, FileUploadResponse};
 #[cfg(feature = "blocking-http")]
 use std::sync::{Arc, OnceLock};
 
@@ -27,6 +27,10 @@ const LONG_MUTATION_TIMEOUT_SECS: u64 = 11 * 60;
 
 const ACTIONS_PATH: &str = "/v1/actions";
 const DOWNLOAD_PATH: &str = "/v1/files/download";
+const UPLOAD_PATH: &str = "/v1/upload";
+
+/// Total request timeout for file transfers, which stream whole files.
+const TRANSFER_TIMEOUT_SECS: u64 = 30 * 60;
 
 /// Hard ceiling on response body size accepted by the remote bridge. Cuts
 /// off arbitrarily large or runaway responses before they're buffered into
@@ -54,6 +58,8 @@ fn client_kind_for(action: &ActionRequest) -> ActionClientKind {
         ActionRequest::RemoveWorktreeProject { .. }
         | ActionRequest::ForceRemoveWorktreeProject { .. }
         | ActionRequest::RenameProjectDirectory { .. } => ActionClientKind::LongMutation,
+        // Replies only once the command finishes (default cap: five minutes).
+        ActionRequest::ExecInTerminal { .. } => ActionClientKind::LongMutation,
         _ => ActionClientKind::Fast,
     }
 }
@@ -86,6 +92,7 @@ struct RemoteActionClientInner {
     search: OnceLock<Result<ClientAndUrl, String>>,
     long_mutation: OnceLock<Result<ClientAndUrl, String>>,
     download: OnceLock<Result<ClientAndUrl, String>>,
+    upload: OnceLock<Result<ClientAndUrl, String>>,
     #[cfg(feature = "cancellable-http")]
     async_search: OnceLock<Result<AsyncClientAndUrl, String>>,
 }
@@ -111,6 +118,7 @@ impl RemoteActionClient {
                 search: OnceLock::new(),
                 long_mutation: OnceLock::new(),
                 download: OnceLock::new(),
+                upload: OnceLock::new(),
                 #[cfg(feature = "cancellable-http")]
                 async_search: OnceLock::new(),
             }),
@@ -158,7 +166,7 @@ impl RemoteActionClient {
             crate::remote_http::blocking_client_and_url(
                 &self.inner.config,
                 DOWNLOAD_PATH,
-                std::time::Duration::from_secs(30 * 60),
+                std::time::Duration::from_secs(TRANSFER_TIMEOUT_SECS),
             )
         });
         let (client, url) = match client_and_url {
@@ -183,6 +191,38 @@ impl RemoteActionClient {
             .map_err(|error| format!("Cannot save downloaded file: {error}"))
     }
 
+    /// Stream `length` bytes from `reader` into a daemon-side file.
+    pub fn upload_file(
+        &self,
+        request: &FileUploadRequest,
+        reader: impl std::io::Read + Send + 'static,
+        length: u64,
+    ) -> Result<FileUploadResponse, String> {
+        let client_and_url = self.inner.upload.get_or_init(|| {
+            crate::remote_http::blocking_client_and_url(
+                &self.inner.config,
+                UPLOAD_PATH,
+                std::time::Duration::from_secs(TRANSFER_TIMEOUT_SECS),
+            )
+        });
+        let (client, url) = match client_and_url {
+            Ok(client_and_url) => client_and_url,
+            Err(error) => return Err(error.clone()),
+        };
+        let response = client
+            .post(url)
+            .bearer_auth(&self.inner.token)
+            .query(request)
+            .body(reqwest::blocking::Body::sized(reader, length))
+            .send()
+            .map_err(|error| format!("Upload request failed: {error}"))?;
+        let status = response.status();
+        let body = response
+            .bytes()
+            .map_err(|error| format!("Failed to read response: {error}"))?;
+        parse_upload_response(status, &body)
+    }
+
     /// Post a content search while observing the request-local cancellation flag.
     ///
     /// The HTTP future runs on a shared async runtime, while this blocking API
@@ -319,6 +359,41 @@ pub async fn post_action_async(
     post_action_async_with_client(&client, &base_url, token, action).await
 }
 
+/// Stream a local file into a daemon-side file, for async callers.
+#[cfg(feature = "client")]
+pub async fn upload_file_async(
+    config: &RemoteConnectionConfig,
+    token: &str,
+    request: &FileUploadRequest,
+    path: &std::path::Path,
+) -> Result<FileUploadResponse, String> {
+    let file = tokio::fs::File::open(path)
+        .await
+        .map_err(|error| format!("Cannot open {}: {error}", path.display()))?;
+    let length = file
+        .metadata()
+        .await
+        .map_err(|error| format!("Cannot read {}: {error}", path.display()))?
+        .len();
+    let (client, url) = crate::remote_http::async_client_and_url(config, UPLOAD_PATH)?;
+    let response = client
+        .post(url)
+        .bearer_auth(token)
+        .query(request)
+        .header(reqwest::header::CONTENT_LENGTH, length)
+        .body(reqwest::Body::from(file))
+        .timeout(std::time::Duration::from_secs(TRANSFER_TIMEOUT_SECS))
+        .send()
+        .await
+        .map_err(|error| format!("Upload request failed: {error}"))?;
+    let status = response.status();
+    let body = response
+        .bytes()
+        .await
+        .map_err(|error| format!("Failed to read response: {error}"))?;
+    parse_upload_response(status, &body)
+}
+
 /// Post through an already-selected async client. Connection setup uses this
 /// while it is still negotiating the final config, but keeps action response
 /// parsing and size limits on the same path as every other caller.
@@ -427,6 +502,21 @@ fn parse_action_response(
     Ok(Some(body))
 }
 
+/// Upload errors carry `{"error": ...}`; surface just the message.
+fn parse_upload_response(
+    status: reqwest::StatusCode,
+    body: &[u8],
+) -> Result<FileUploadResponse, String> {
+    if !status.is_success() {
+        let message = serde_json::from_slice::<serde_json::Value>(body)
+            .ok()
+            .and_then(|body| body.get("error")?.as_str().map(str::to_string))
+            .unwrap_or_else(|| String::from_utf8_lossy(body).into_owned());
+        return Err(format!("Upload failed ({status}): {message}"));
+    }
+    serde_json::from_slice(body).map_err(|e| format!("Failed to parse response: {e}"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -480,6 +570,7 @@ mod tests {
             tls: false,
             pinned_cert_sha256: None,
             local_endpoint: None,
+            proxy_command: None,
         }
     }
 
@@ -506,6 +597,25 @@ mod tests {
         assert_eq!(FAST_TIMEOUT_SECS, 10);
     }
 
+    #[test]
+    fn upload_errors_surface_the_server_message() {
+        let conflict = parse_upload_response(
+            reqwest::StatusCode::CONFLICT,
+            br#"{"error":"crash.txt already exists"}"#,
+        );
+        assert_eq!(
+            conflict,
+            Err("Upload failed (409 Conflict): crash.txt already exists".to_string())
+        );
+
+        let written = parse_upload_response(
+            reqwest::StatusCode::OK,
+            br#"{"path":"/srv/app/crash (1).txt","name":"crash (1).txt","size":3}"#,
+        )
+        .unwrap();
+        assert_eq!(written.name, "crash (1).txt");
+    }
+
     #[test]
     fn action_posts_use_the_canonical_route() {
         assert_eq!(ACTIONS_PATH, "/v1/actions");
@@ -9,20 +9,33 @@ pub fn async_client_and_url(
     config: &RemoteConnectionConfig,
     path: &str,
 ) -> Result<(reqwest::Client, String), String> {
+    async_client_and_url_observed(config, path, crate::tls::new_observed())
+}
+
+/// [`async_client_and_url`] that records the server's certificate
+/// fingerprint in `observed`, for pinning a new connection.
+#[cfg(any(feature = "client", feature = "cancellable-http"))]
+pub fn async_client_and_url_observed(
+    config: &RemoteConnectionConfig,
+    path: &str,
+    observed: crate::tls::ObservedFingerprint,
+) -> Result<(reqwest::Client, String), String> {
+    // Off Unix this only surfaces the proxy command's "unsupported" error.
+    #[cfg(not(unix))]
+    config.unix_transport_path()?;
     #[cfg(unix)]
-    if let Some(crate::LocalEndpoint::UnixSocket { path: socket_path }) = &config.local_endpoint {
-        let client = reqwest::Client::builder()
-            .unix_socket(socket_path.as_str())
-            .build()
-            .map_err(|error| format!("Cannot initialise Unix socket HTTP client: {error}"))?;
+    if let Some(socket_path) = config.unix_transport_path()? {
+        let client = crate::tls::build_unix_reqwest_client(
+            &socket_path,
+            config.proxy_uses_tls(),
+            config.pinned_cert_sha256.clone(),
+            observed,
+        )?;
         return Ok((client, config.http_url(path)));
     }
 
-    let client = crate::tls::build_reqwest_client(
-        config.tls,
-        config.pinned_cert_sha256.clone(),
-        crate::tls::new_observed(),
-    )?;
+    let client =
+        crate::tls::build_reqwest_client(config.tls, config.pinned_cert_sha256.clone(), observed)?;
     Ok((client, config.http_url(path)))
 }
 
@@ -34,14 +47,18 @@ pub fn blocking_client_and_url(
     path: &str,
     timeout: std::time::Duration,
 ) -> Result<(reqwest::blocking::Client, String), String> {
+    // Off Unix this only surfaces the proxy command's "unsupported" error.
+    #[cfg(not(unix))]
+    config.unix_transport_path()?;
     #[cfg(unix)]
-    if let Some(crate::LocalEndpoint::UnixSocket { path: socket_path }) = &config.local_endpoint {
-        let client = reqwest::blocking::Client::builder()
-            .unix_socket(socket_path.as_str())
-            .timeout(timeout)
-            .connect_timeout(std::time::Duration::from_secs(5))
-            .build()
-            .map_err(|e| format!("Cannot initialise Unix socket HTTP client: {e}"))?;
+    if let Some(socket_path) = config.unix_transport_path()? {
+        let client = crate::tls::build_blocking_unix_reqwest_client(
+            &socket_path,
+            config.proxy_uses_tls(),
+            config.pinned_cert_sha256.clone(),
+            crate::tls::new_observed(),
+            timeout,
+        )?;
         return Ok((client, config.http_url(path)));
     }
 
@@ -70,6 +87,7 @@ mod tests {
             tls,
             pinned_cert_sha256: Some("00".repeat(32)),
             local_endpoint,
+            proxy_command: None,
         }
     }
 
@@ -108,4 +126,21 @@ mod tests {
         .unwrap();
         assert_eq!(url, "http://okena.local/v1/actions");
     }
+
+    #[cfg(all(unix, feature = "blocking-http"))]
+    #[test]
+    fn blocking_proxy_command_keeps_tls_unless_it_reaches_a_unix_socket() {
+        let mut config = config(true, None);
+        config.proxy_command = Some("cat".to_string());
+        let (_, url) =
+            blocking_client_and_url(&config, "/v1/actions", std::time::Duration::from_secs(10))
+                .unwrap();
+        assert_eq!(url, "https://remote.example:19100/v1/actions");
+
+        config.proxy_command = Some("cat --stdio".to_string());
+        let (_, url) =
+            blocking_client_and_url(&config, "/v1/actions", std::time::Duration::from_secs(10))
+                .unwrap();
+        assert_eq!(url, "http://okena.local/v1/actions");
+    }
 }
@@ -194,6 +194,24 @@ pub fn build_reqwest_client(
         .map_err(|error| format!("Cannot initialise pinned TLS client: {error}"))
 }
 
+/// [`build_reqwest_client`] dialing a Unix socket: the local daemon's, or the
+/// relay of a proxy command. With `tls` the pinned handshake runs over it.
+#[cfg(all(unix, any(feature = "client", feature = "cancellable-http")))]
+pub fn build_unix_reqwest_client(
+    socket_path: &str,
+    tls: bool,
+    pinned: Option<String>,
+    observed: ObservedFingerprint,
+) -> Result<reqwest::Client, String> {
+    let mut builder = reqwest::Client::builder().unix_socket(socket_path);
+    if tls {
+        builder = builder.use_preconfigured_tls(pinned_client_config(pinned, observed));
+    }
+    builder
+        .build()
+        .map_err(|error| format!("Cannot initialise Unix socket HTTP client: {error}"))
+}
+
 /// Build a blocking reqwest client for a connection. This applies the same
 /// certificate pinning policy as the async client and lets callers choose the
 /// timeout appropriate for their payload size.
@@ -215,6 +233,28 @@ pub fn build_blocking_reqwest_client(
         .map_err(|e| format!("Cannot initialise HTTP client: {e}"))
 }
 
+/// [`build_blocking_reqwest_client`] dialing a Unix socket, like
+/// [`build_unix_reqwest_client`].
+#[cfg(all(unix, feature = "blocking-http"))]
+pub fn build_blocking_unix_reqwest_client(
+    socket_path: &str,
+    tls: bool,
+    pinned: Option<String>,
+    observed: ObservedFingerprint,
+    timeout: std::time::Duration,
+) -> Result<reqwest::blocking::Client, String> {
+    let mut builder = reqwest::blocking::Client::builder()
+        .unix_socket(socket_path)
+        .timeout(timeout)
+        .connect_timeout(std::time::Duration::from_secs(5));
+    if tls {
+        builder = builder.use_preconfigured_tls(pinned_client_config(pinned, observed));
+    }
+    builder
+        .build()
+        .map_err(|e| format!("Cannot initialise Unix socket HTTP client: {e}"))
+}
+
 /// Build a tokio-tungstenite connector for the WebSocket. Returns `None` when
 /// `tls` is false (the caller uses the plain `connect_async` path).
 #[cfg(feature = "client")]
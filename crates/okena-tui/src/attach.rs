@@ -0,0 +1,259 @@
+//! `okena attach`: hand the current tty to one remote terminal, tmux-style.
+//!
+//! The pane is rendered full-screen with no chrome. Keys and pastes go to the
+//! PTY; the detach key returns to the shell and leaves the terminal running.
+//! Our tty size is claimed with [`Terminal::claim_resize_local`], so it wins
+//! the resize authority the same way a focused GUI pane does.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    queue,
+    terminal::{self, ClearType},
+};
+use okena_terminal::terminal::{Terminal, TerminalSize};
+use okena_transport::client::{
+    ConnectionEvent, ConnectionStatus, RemoteClient, RemoteConnectionConfig, make_prefixed_id,
+};
+use parking_lot::RwLock;
+
+use crate::keys::key_bytes;
+use crate::remote::{
+    RemoteView, TerminalMap, TuiConnectionHandler, handle_connection_event, wait_for_initial_state,
+};
+use crate::screen::TerminalGuard;
+
+/// How long to wait for the terminal's stream after the initial state arrives.
+const STREAM_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A `ctrl-<char>` chord that detaches instead of being sent to the PTY.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DetachKey(char);
+
+impl Default for DetachKey {
+    fn default() -> Self {
+        Self(']')
+    }
+}
+
+impl DetachKey {
+    fn matches(&self, key: &KeyEvent) -> bool {
+        if !key.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+        let KeyCode::Char(c) = key.code else {
+            return false;
+        };
+        c.eq_ignore_ascii_case(&self.0) || self.legacy_char() == Some(c)
+    }
+
+    /// Without keyboard enhancement, crossterm decodes the control bytes
+    /// 0x1C..=0x1F as ctrl-'4'..='7' rather than ctrl-'\\'..='_'.
+    fn legacy_char(&self) -> Option<char> {
+        matches!(self.0, '['..='_').then(|| ((self.0 as u8 - b'[') + b'3') as char)
+    }
+}
+
+impl FromStr for DetachKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+        let rest = lower
+            .strip_prefix("ctrl-")
+            .or_else(|| lower.strip_prefix("c-"))
+            .ok_or_else(|| format!("detach key must look like ctrl-<char>, got '{s}'"))?;
+        let mut chars = rest.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii_graphic() => Ok(Self(c)),
+            _ => Err(format!(
+                "detach key must be ctrl- followed by one character, got '{s}'"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for DetachKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ctrl-{}", self.0)
+    }
+}
+
+/// Why [`attach`] returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachEnd {
+    Detached,
+    TerminalClosed,
+}
+
+/// Connect with `config`, take over the tty and mirror `terminal_id` until
+/// the user presses `detach` or the terminal goes away.
+pub fn attach(
+    config: RemoteConnectionConfig,
+    terminal_id: &str,
+    detach: DetachKey,
+) -> Result<AttachEnd> {
+    let runtime = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .thread_name("okena-attach")
+            .build()
+            .context("creating tokio runtime")?,
+    );
+    runtime.block_on(run(config, terminal_id, detach, runtime.clone()))
+}
+
+async fn run(
+    config: RemoteConnectionConfig,
+    terminal_id: &str,
+    detach: DetachKey,
+    runtime: Arc<tokio::runtime::Runtime>,
+) -> Result<AttachEnd> {
+    let prefixed = make_prefixed_id(&config.id, terminal_id);
+    let terminals: TerminalMap = Arc::new(RwLock::new(HashMap::new()));
+    let (dirty_tx, dirty_rx) = async_channel::bounded::<()>(1);
+    let handler = Arc::new(TuiConnectionHandler::new(terminals.clone(), dirty_tx));
+    let (event_tx, event_rx) = async_channel::bounded::<ConnectionEvent>(256);
+
+    let mut client = RemoteClient::new(config, runtime, handler, event_tx);
+    let mut view = RemoteView::default();
+    client.connect();
+
+    let result = async {
+        wait_for_initial_state(&mut client, &mut view, &event_rx, None).await?;
+        let deadline = Instant::now() + STREAM_TIMEOUT;
+        while !terminals.read().contains_key(&prefixed) {
+            while let Ok(event) = event_rx.try_recv() {
+                handle_connection_event(&mut client, &mut view, event);
+            }
+            if Instant::now() >= deadline {
+                bail!("terminal {terminal_id} is not streamed by the server");
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let _guard = TerminalGuard::enter()?;
+        event_loop(
+            &mut client,
+            &mut view,
+            &event_rx,
+            &dirty_rx,
+            &terminals,
+            &prefixed,
+            detach,
+        )
+    }
+    .await;
+
+    client.disconnect();
+    result
+}
+
+fn event_loop(
+    client: &mut RemoteClient<TuiConnectionHandler>,
+    view: &mut RemoteView,
+    event_rx: &async_channel::Receiver<ConnectionEvent>,
+    dirty_rx: &async_channel::Receiver<()>,
+    terminals: &TerminalMap,
+    prefixed: &str,
+    detach: DetachKey,
+) -> Result<AttachEnd> {
+    let mut last_size = None;
+    let mut needs_render = true;
+    loop {
+        while let Ok(event) = event_rx.try_recv() {
+            handle_connection_event(client, view, event);
+        }
+        if let ConnectionStatus::Error(message) = &view.status {
+            bail!("{message}");
+        }
+        while dirty_rx.try_recv().is_ok() {
+            needs_render = true;
+        }
+
+        let Some(terminal) = terminals.read().get(prefixed).cloned() else {
+            // Only a live connection can tell us the terminal is gone; while
+            // reconnecting the map is rebuilt from the next state.
+            if matches!(view.status, ConnectionStatus::Connected) {
+                return Ok(AttachEnd::TerminalClosed);
+            }
+            std::thread::sleep(Duration::from_millis(16));
+            continue;
+        };
+
+        if event::poll(Duration::from_millis(16))? {
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    if detach.matches(&key) {
+                        return Ok(AttachEnd::Detached);
+                    }
+                    if let Some(bytes) = key_bytes(&terminal, key) {
+                        terminal.send_bytes(&bytes);
+                    }
+                }
+                Event::Paste(text) => terminal.send_paste(&text),
+                Event::Resize(..) => needs_render = true,
+                _ => {}
+            }
+        }
+
+        if needs_render {
+            render(&terminal, &mut last_size)?;
+            needs_render = false;
+        }
+    }
+}
+
+fn render(terminal: &Terminal, last_size: &mut Option<(u16, u16)>) -> Result<()> {
+    let (cols, rows) = terminal::size()?;
+    if *last_size != Some((cols, rows)) {
+        terminal.claim_resize_local();
+        terminal.resize(TerminalSize {
+            cols,
+            rows,
+            ..TerminalSize::default()
+        });
+        *last_size = Some((cols, rows));
+    }
+
+    let mut stdout = io::stdout();
+    queue!(stdout, cursor::Hide, terminal::Clear(ClearType::All))?;
+    stdout.write_all(&terminal.render_snapshot())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detach_key_parses_ctrl_chords() {
+        assert_eq!("ctrl-]".parse(), Ok(DetachKey(']')));
+        assert_eq!("C-A".parse(), Ok(DetachKey('a')));
+        assert!("]".parse::<DetachKey>().is_err());
+        assert!("ctrl-ab".parse::<DetachKey>().is_err());
+        assert_eq!(DetachKey::default().to_string(), "ctrl-]");
+    }
+
+    #[test]
+    fn default_detach_key_matches_legacy_control_byte() {
+        // crossterm on Unix reports the 0x1D byte of ctrl-] like this.
+        let key = KeyEvent::new(KeyCode::Char('5'), KeyModifiers::CONTROL);
+        assert!(DetachKey::default().matches(&key));
+        let key = KeyEvent::new(KeyCode::Char(']'), KeyModifiers::CONTROL);
+        assert!(DetachKey::default().matches(&key));
+        let key = KeyEvent::new(KeyCode::Char('5'), KeyModifiers::NONE);
+        assert!(!DetachKey::default().matches(&key));
+        let key = KeyEvent::new(KeyCode::Char('5'), KeyModifiers::CONTROL);
+        assert!(!DetachKey('a').matches(&key));
+    }
+}
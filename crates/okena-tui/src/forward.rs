@@ -0,0 +1,222 @@
+//! Service port forwards for `okena-tui --forward SERVICE[:PORT]`, Ctrl-O and
+//! `okena forward`. The forwarding itself is [`PortForward`].
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, anyhow};
+use okena_core::api::{ApiProject, StateResponse};
+use okena_core::forward::PortForwardQuery;
+use okena_transport::client::{PortForward, RemoteConnectionConfig};
+
+/// `SERVICE` or `SERVICE:PORT`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForwardSpec {
+    pub service: String,
+    pub port: Option<u16>,
+}
+
+impl FromStr for ForwardSpec {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (service, port) = match value.rsplit_once(':') {
+            Some((service, port)) => {
+                let port = port
+                    .parse()
+                    .map_err(|_| format!("invalid port in '{value}'"))?;
+                (service, Some(port))
+            }
+            None => (value, None),
+        };
+        if service.is_empty() {
+            return Err("expected SERVICE or SERVICE:PORT".to_string());
+        }
+        Ok(Self {
+            service: service.to_string(),
+            port,
+        })
+    }
+}
+
+impl fmt::Display for ForwardSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.port {
+            Some(port) => write!(f, "{}:{port}", self.service),
+            None => f.write_str(&self.service),
+        }
+    }
+}
+
+/// Resolve what to forward.
+///
+/// With a `spec`, the named service in the active terminal's project, else in
+/// the first project that has it. Without one, the service whose log is the
+/// active terminal, else the first service in the active terminal's project
+/// with a detected port that isn't in `forwarded` yet.
+pub fn pick_target(
+    state: &StateResponse,
+    spec: Option<&ForwardSpec>,
+    active_terminal: Option<&str>,
+    forwarded: &[PortForwardQuery],
+) -> Result<PortForwardQuery, String> {
+    let active_project = active_terminal.and_then(|terminal_id| {
+        state
+            .projects
+            .iter()
+            .find(|project| project_shows_terminal(project, terminal_id))
+    });
+
+    if let Some(spec) = spec {
+        let project = active_project
+            .filter(|project| has_service(project, &spec.service))
+            .or_else(|| {
+                state
+                    .projects
+                    .iter()
+                    .find(|project| has_service(project, &spec.service))
+            })
+            .ok_or_else(|| format!("no project has a service named '{}'", spec.service))?;
+        return Ok(PortForwardQuery {
+            project_id: project.id.clone(),
+            service: spec.service.clone(),
+            port: spec.port,
+        });
+    }
+
+    let project = active_project.ok_or("no active terminal")?;
+    let is_forwarded = |service: &str, port: u16| {
+        forwarded.iter().any(|query| {
+            query.project_id == project.id && query.service == service && query.port == Some(port)
+        })
+    };
+    let with_ports = || {
+        project
+            .services
+            .iter()
+            .filter(|service| !service.ports.is_empty())
+    };
+    let service = with_ports()
+        .find(|service| service.terminal_id.as_deref() == active_terminal)
+        .or_else(|| with_ports().find(|service| !is_forwarded(&service.name, service.ports[0])))
+        .ok_or_else(|| format!("no service in {} has a port to forward", project.name))?;
+    Ok(PortForwardQuery {
+        project_id: project.id.clone(),
+        service: service.name.clone(),
+        port: service.ports.first().copied(),
+    })
+}
+
+/// `okena forward`: hold one forward in the foreground until the process is
+/// interrupted. `on_ready` runs once the local port listens.
+pub fn run_forward(
+    config: RemoteConnectionConfig,
+    target: PortForwardQuery,
+    local_port: Option<u16>,
+    on_ready: impl FnOnce(&PortForward),
+) -> Result<()> {
+    let token = config
+        .effective_auth_token()
+        .ok_or_else(|| anyhow!("no auth token for {}", config.name))?;
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .thread_name("okena-forward")
+        .build()
+        .context("creating tokio runtime")?;
+    let forward = runtime
+        .block_on(PortForward::start(config, token, target, local_port))
+        .map_err(|e| anyhow!(e))?;
+    on_ready(&forward);
+    runtime.block_on(std::future::pending::<()>());
+    Ok(())
+}
+
+fn has_service(project: &ApiProject, name: &str) -> bool {
+    project.services.iter().any(|service| service.name == name)
+}
+
+fn project_shows_terminal(project: &ApiProject, terminal_id: &str) -> bool {
+    project.layout.as_ref().is_some_and(|layout| {
+        layout
+            .collect_terminal_ids()
+            .iter()
+            .any(|id| id == terminal_id)
+    }) || project
+        .services
+        .iter()
+        .any(|service| service.terminal_id.as_deref() == Some(terminal_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> StateResponse {
+        let project = |id: &str, terminal: &str, services: serde_json::Value| {
+            serde_json::json!({
+                "id": id,
+                "name": id,
+                "path": format!("/src/{id}"),
+                "show_in_overview": true,
+                "layout": { "type": "terminal", "terminal_id": terminal, "minimized": false, "detached": false },
+                "terminal_names": {},
+                "services": services,
+            })
+        };
+        serde_json::from_value(serde_json::json!({
+            "state_version": 1,
+            "focused_project_id": null,
+            "fullscreen_terminal": null,
+            "projects": [
+                project("api", "t-api", serde_json::json!([
+                    { "name": "web", "status": "running", "terminal_id": "t-api-web", "ports": [8080] },
+                ])),
+                project("site", "t-site", serde_json::json!([
+                    { "name": "db", "status": "running", "terminal_id": "t-db", "ports": [] },
+                    { "name": "web", "status": "running", "terminal_id": "t-web", "ports": [5173] },
+                    { "name": "docs", "status": "running", "terminal_id": "t-docs", "ports": [3000] },
+                ])),
+            ],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn spec_parses_service_and_port() {
+        assert_eq!(
+            "web:5173".parse(),
+            Ok(ForwardSpec {
+                service: "web".to_string(),
+                port: Some(5173),
+            })
+        );
+        assert_eq!("web".parse::<ForwardSpec>().unwrap().port, None);
+        assert!("web:http".parse::<ForwardSpec>().is_err());
+        assert!(":80".parse::<ForwardSpec>().is_err());
+    }
+
+    #[test]
+    fn spec_prefers_the_active_project() {
+        let spec: ForwardSpec = "web".parse().unwrap();
+        let state = state();
+        let target = pick_target(&state, Some(&spec), Some("t-site"), &[]).unwrap();
+        assert_eq!(target.project_id, "site");
+        let target = pick_target(&state, Some(&spec), None, &[]).unwrap();
+        assert_eq!(target.project_id, "api");
+        let missing: ForwardSpec = "worker".parse().unwrap();
+        assert!(pick_target(&state, Some(&missing), None, &[]).is_err());
+    }
+
+    #[test]
+    fn without_spec_picks_the_next_unforwarded_port() {
+        let state = state();
+        let first = pick_target(&state, None, Some("t-site"), &[]).unwrap();
+        assert_eq!((first.service.as_str(), first.port), ("web", Some(5173)));
+        let second = pick_target(&state, None, Some("t-site"), &[first]).unwrap();
+        assert_eq!((second.service.as_str(), second.port), ("docs", Some(3000)));
+
+        let own_log = pick_target(&state, None, Some("t-docs"), &[]).unwrap();
+        assert_eq!(own_log.service, "docs");
+    }
+}
@@ -0,0 +1,75 @@
+//! crossterm key events to the bytes a terminal expects.
+
+use crossterm::event::{
+    KeyCode, KeyEvent as CrosstermKeyEvent, KeyModifiers as CrosstermKeyModifiers,
+};
+use okena_terminal::input::{KeyEvent, KeyModifiers, key_to_bytes};
+use okena_terminal::terminal::Terminal;
+
+/// Encode `key` for `terminal`, honouring its cursor-key and kitty keyboard
+/// modes. `None` for keys with no terminal encoding.
+pub fn key_bytes(terminal: &Terminal, key: CrosstermKeyEvent) -> Option<Vec<u8>> {
+    let modifiers = key_modifiers(key.modifiers);
+
+    if let KeyCode::Char(ch) = key.code
+        && !modifiers.control
+        && !modifiers.alt
+        && !modifiers.platform
+    {
+        return Some(ch.to_string().into_bytes());
+    }
+
+    let key_name = match key.code {
+        KeyCode::Backspace => "backspace".to_string(),
+        KeyCode::Enter => "enter".to_string(),
+        KeyCode::Left => "left".to_string(),
+        KeyCode::Right => "right".to_string(),
+        KeyCode::Up => "up".to_string(),
+        KeyCode::Down => "down".to_string(),
+        KeyCode::Home => "home".to_string(),
+        KeyCode::End => "end".to_string(),
+        KeyCode::PageUp => "pageup".to_string(),
+        KeyCode::PageDown => "pagedown".to_string(),
+        KeyCode::Tab => "tab".to_string(),
+        KeyCode::BackTab => "tab".to_string(),
+        KeyCode::Delete => "delete".to_string(),
+        KeyCode::Insert => "insert".to_string(),
+        KeyCode::Esc => "escape".to_string(),
+        KeyCode::F(n) => format!("f{n}"),
+        KeyCode::Char(ch) => ch.to_string(),
+        KeyCode::Null
+        | KeyCode::CapsLock
+        | KeyCode::ScrollLock
+        | KeyCode::NumLock
+        | KeyCode::PrintScreen
+        | KeyCode::Pause
+        | KeyCode::Menu
+        | KeyCode::KeypadBegin
+        | KeyCode::Media(_)
+        | KeyCode::Modifier(_) => return None,
+    };
+
+    let mut event = KeyEvent {
+        key: key_name,
+        key_char: None,
+        modifiers,
+    };
+    if matches!(key.code, KeyCode::BackTab) {
+        event.modifiers.shift = true;
+    }
+
+    key_to_bytes(
+        &event,
+        terminal.is_app_cursor_mode(),
+        terminal.kitty_keyboard_flags(),
+    )
+}
+
+fn key_modifiers(modifiers: CrosstermKeyModifiers) -> KeyModifiers {
+    KeyModifiers {
+        control: modifiers.contains(CrosstermKeyModifiers::CONTROL),
+        shift: modifiers.contains(CrosstermKeyModifiers::SHIFT),
+        alt: modifiers.contains(CrosstermKeyModifiers::ALT),
+        platform: false,
+    }
+}
@@ -0,0 +1,8 @@
+//! Terminal front ends for a running Okena daemon: the `okena-tui` client and
+//! the single-pane `okena attach` view share the connection and input glue.
+
+pub mod attach;
+pub mod forward;
+pub mod keys;
+pub mod remote;
+pub mod screen;
@@ -4,30 +4,33 @@ use std::collections::HashMap;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
-use anyhow::{Context, Result, anyhow, bail};
+use anyhow::{Context, Result, anyhow};
 use clap::Parser;
 use crossterm::{
     cursor,
     event::{
-        self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode,
-        KeyEvent as CrosstermKeyEvent, KeyEventKind, KeyModifiers as CrosstermKeyModifiers,
+        self, Event, KeyCode, KeyEvent as CrosstermKeyEvent, KeyEventKind,
+        KeyModifiers as CrosstermKeyModifiers,
     },
-    execute, queue,
+    queue,
     style::{Attribute, Print, SetAttribute},
-    terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{self, ClearType},
 };
 use okena_core::api::{ApiLayoutNode, ApiProject, StateResponse};
-use okena_terminal::input::{KeyEvent, KeyModifiers, key_to_bytes};
-use okena_terminal::terminal::{Terminal, TerminalSize, TerminalTransport};
+use okena_terminal::terminal::TerminalSize;
 use okena_transport::client::{
-    ConnectionEvent, ConnectionHandler, ConnectionStatus, LocalEndpoint,
-    REMOTE_TERMINAL_ANSWERS_QUERIES, REMOTE_TERMINAL_RESIZE_DEBOUNCE_MS,
-    REMOTE_TERMINAL_USES_MOUSE_BACKEND, RemoteClient, RemoteConnectionConfig, WsClientMessage,
-    is_remote_terminal, make_prefixed_id, resize_remote_terminal, send_remote_terminal_input,
-    strip_prefix,
+    ConnectionEvent, LocalEndpoint, PortForward, RemoteClient, RemoteConnectionConfig,
+    make_prefixed_id,
 };
+use okena_tui::forward::{ForwardSpec, pick_target};
+use okena_tui::keys::key_bytes;
+use okena_tui::remote::{
+    RemoteView, TerminalMap, TuiConnectionHandler, handle_connection_event, status_label,
+    wait_for_initial_state,
+};
+use okena_tui::screen::TerminalGuard;
 use parking_lot::RwLock;
 
 #[derive(Parser, Debug)]
@@ -67,146 +70,10 @@ struct Args {
     /// Start focused on this terminal id.
     #[arg(long)]
     terminal: Option<String>,
-}
-
-struct TuiRemoteTransport {
-    ws_tx: async_channel::Sender<WsClientMessage>,
-    connection_id: String,
-}
-
-impl TerminalTransport for TuiRemoteTransport {
-    fn send_input(&self, terminal_id: &str, data: &[u8]) {
-        send_remote_terminal_input(&self.ws_tx, &self.connection_id, terminal_id, data);
-    }
-
-    fn send_response(&self, _terminal_id: &str, _data: &[u8]) {}
-
-    fn resize(&self, terminal_id: &str, cols: u16, rows: u16) {
-        resize_remote_terminal(&self.ws_tx, &self.connection_id, terminal_id, cols, rows);
-    }
 
-    fn uses_mouse_backend(&self) -> bool {
-        REMOTE_TERMINAL_USES_MOUSE_BACKEND
-    }
-
-    fn resize_debounce_ms(&self) -> u64 {
-        REMOTE_TERMINAL_RESIZE_DEBOUNCE_MS
-    }
-
-    fn answers_terminal_queries(&self) -> bool {
-        REMOTE_TERMINAL_ANSWERS_QUERIES
-    }
-}
-
-struct TuiConnectionHandler {
-    terminals: Arc<RwLock<HashMap<String, Arc<Terminal>>>>,
-    dirty_tx: async_channel::Sender<()>,
-}
-
-impl TuiConnectionHandler {
-    fn new(
-        terminals: Arc<RwLock<HashMap<String, Arc<Terminal>>>>,
-        dirty_tx: async_channel::Sender<()>,
-    ) -> Self {
-        Self {
-            terminals,
-            dirty_tx,
-        }
-    }
-}
-
-impl ConnectionHandler for TuiConnectionHandler {
-    fn create_terminal(
-        &self,
-        connection_id: &str,
-        _terminal_id: &str,
-        prefixed_id: &str,
-        ws_sender: async_channel::Sender<WsClientMessage>,
-        cols: u16,
-        rows: u16,
-    ) {
-        if self.terminals.read().contains_key(prefixed_id) {
-            return;
-        }
-
-        let size = if cols > 0 && rows > 0 {
-            TerminalSize {
-                cols,
-                rows,
-                ..TerminalSize::default()
-            }
-        } else {
-            TerminalSize::default()
-        };
-        let transport = Arc::new(TuiRemoteTransport {
-            ws_tx: ws_sender,
-            connection_id: connection_id.to_string(),
-        });
-        let terminal = Arc::new(Terminal::new(
-            prefixed_id.to_string(),
-            size,
-            transport,
-            String::new(),
-        ));
-        self.terminals
-            .write()
-            .insert(prefixed_id.to_string(), terminal);
-    }
-
-    fn on_terminal_output(&self, prefixed_id: &str, data: &[u8]) {
-        if let Some(terminal) = self.terminals.read().get(prefixed_id) {
-            terminal.enqueue_output(data);
-            let _ = self.dirty_tx.try_send(());
-        }
-    }
-
-    fn resize_terminal(&self, prefixed_id: &str, cols: u16, rows: u16, server_owns: bool) {
-        if let Some(terminal) = self.terminals.read().get(prefixed_id) {
-            if server_owns {
-                terminal.claim_resize_remote();
-            }
-            terminal.resize_grid_only(cols, rows);
-            let _ = self.dirty_tx.try_send(());
-        }
-    }
-
-    fn remove_terminal(&self, prefixed_id: &str) {
-        self.terminals.write().remove(prefixed_id);
-        let _ = self.dirty_tx.try_send(());
-    }
-
-    fn remove_all_terminals(&self, connection_id: &str) {
-        let mut terminals = self.terminals.write();
-        let to_remove: Vec<String> = terminals
-            .keys()
-            .filter(|key| is_remote_terminal(key, connection_id))
-            .cloned()
-            .collect();
-        for key in to_remove {
-            terminals.remove(&key);
-        }
-        let _ = self.dirty_tx.try_send(());
-    }
-
-    fn remove_terminals_except(
-        &self,
-        connection_id: &str,
-        keep_ids: &std::collections::HashSet<String>,
-    ) {
-        let mut terminals = self.terminals.write();
-        let to_remove: Vec<String> = terminals
-            .keys()
-            .filter(|key| {
-                is_remote_terminal(key, connection_id)
-                    && !keep_ids.contains(&strip_prefix(key, connection_id))
-            })
-            .cloned()
-            .collect();
-        for key in to_remove {
-            terminals.remove(&key);
-        }
-        let _ = self.dirty_tx.try_send(());
-    }
+    /// Forward a service port to localhost once connected. Repeatable.
+    #[arg(long, value_name = "SERVICE[:PORT]")]
+    forward: Vec<ForwardSpec>,
 }
 
 #[derive(Clone)]
@@ -216,28 +83,28 @@ struct TerminalEntry {
 }
 
 struct TuiState {
-    status: ConnectionStatus,
-    state: Option<StateResponse>,
+    view: RemoteView,
     active_terminal: Option<String>,
     terminal_request: Option<String>,
-    message: Option<String>,
     last_resize: Option<(String, u16, u16)>,
+    /// Running forwards; dropped, and so stopped, on exit.
+    forwards: Vec<PortForward>,
 }
 
 impl TuiState {
     fn new(terminal_request: Option<String>) -> Self {
         Self {
-            status: ConnectionStatus::Disconnected,
-            state: None,
+            view: RemoteView::default(),
             active_terminal: terminal_request.clone(),
             terminal_request,
-            message: None,
             last_resize: None,
+            forwards: Vec::new(),
         }
     }
 
     fn entries(&self) -> Vec<TerminalEntry> {
-        self.state
+        self.view
+            .state
             .as_ref()
             .map(collect_terminal_entries)
             .unwrap_or_default()
@@ -287,35 +154,6 @@ impl TuiState {
     }
 }
 
-struct TerminalGuard;
-
-impl TerminalGuard {
-    fn enter() -> Result<Self> {
-        terminal::enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(
-            stdout,
-            EnterAlternateScreen,
-            EnableBracketedPaste,
-            cursor::Hide
-        )?;
-        Ok(Self)
-    }
-}
-
-impl Drop for TerminalGuard {
-    fn drop(&mut self) {
-        let _ = terminal::disable_raw_mode();
-        let mut stdout = io::stdout();
-        let _ = execute!(
-            stdout,
-            cursor::Show,
-            DisableBracketedPaste,
-            LeaveAlternateScreen
-        );
-    }
-}
-
 fn main() -> Result<()> {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
 
@@ -343,12 +181,23 @@ async fn run(
     let (dirty_tx, dirty_rx) = async_channel::bounded::<()>(1);
     let handler = Arc::new(TuiConnectionHandler::new(terminals.clone(), dirty_tx));
     let (event_tx, event_rx) = async_channel::bounded::<ConnectionEvent>(256);
+    let (forward_tx, forward_rx) = async_channel::unbounded::<Result<PortForward, String>>();
 
     let mut client = RemoteClient::new(config, runtime, handler, event_tx);
     let mut state = TuiState::new(args.terminal.clone());
     client.connect();
 
-    wait_for_initial_state(&mut client, &mut state, &event_rx, args.pair.as_deref()).await?;
+    wait_for_initial_state(
+        &mut client,
+        &mut state.view,
+        &event_rx,
+        args.pair.as_deref(),
+    )
+    .await?;
+    state.ensure_active_terminal();
+    for spec in &args.forward {
+        request_forward(&client, &mut state, Some(spec), &forward_tx);
+    }
 
     let _guard = TerminalGuard::enter()?;
     render(&connection_id, &terminals, &mut state)?;
@@ -356,7 +205,7 @@ async fn run(
     let mut needs_render = false;
     loop {
         while let Ok(event) = event_rx.try_recv() {
-            handle_connection_event(&mut client, &mut state, event);
+            handle_connection_event(&mut client, &mut state.view, event);
             state.ensure_active_terminal();
             needs_render = true;
         }
@@ -365,11 +214,32 @@ async fn run(
             needs_render = true;
         }
 
+        while let Ok(started) = forward_rx.try_recv() {
+            state.view.message = Some(match started {
+                Ok(forward) => {
+                    let message = format!(
+                        "forwarding {} :{} -> {}",
+                        forward.target().service,
+                        forward.remote_port(),
+                        forward.url()
+                    );
+                    state.forwards.push(forward);
+                    message
+                }
+                Err(e) => format!("forward failed: {e}"),
+            });
+            needs_render = true;
+        }
+
         if event::poll(Duration::from_millis(16))?
             && let Event::Key(key) = event::read()?
         {
             match handle_key(&connection_id, &terminals, &mut state, key)? {
                 LoopControl::Continue => needs_render = true,
+                LoopControl::Forward => {
+                    request_forward(&client, &mut state, None, &forward_tx);
+                    needs_render = true;
+                }
                 LoopControl::Quit => break,
             }
         }
@@ -384,123 +254,51 @@ async fn run(
     Ok(())
 }
 
-async fn wait_for_initial_state(
-    client: &mut RemoteClient<TuiConnectionHandler>,
-    state: &mut TuiState,
-    event_rx: &async_channel::Receiver<ConnectionEvent>,
-    pair_code: Option<&str>,
-) -> Result<()> {
-    let deadline = Instant::now() + Duration::from_secs(30);
-    let mut pair_sent = false;
-
-    loop {
-        while let Ok(event) = event_rx.try_recv() {
-            handle_connection_event(client, state, event);
-        }
-
-        match &state.status {
-            ConnectionStatus::Connected if state.state.is_some() => {
-                state.ensure_active_terminal();
-                return Ok(());
-            }
-            ConnectionStatus::Pairing => {
-                let Some(code) = pair_code else {
-                    bail!(
-                        "pairing required. Pass --pair <code>, --token <token>, or connect over discovered local Unix socket"
-                    );
-                };
-                if !pair_sent {
-                    client.pair(code);
-                    pair_sent = true;
-                }
-            }
-            ConnectionStatus::Error(message) => {
-                bail!("{message}");
-            }
-            _ => {}
-        }
-
-        if Instant::now() >= deadline {
-            bail!("timed out waiting for remote state");
-        }
-        tokio::time::sleep(Duration::from_millis(50)).await;
-    }
+enum LoopControl {
+    Continue,
+    Forward,
+    Quit,
 }
 
-fn handle_connection_event(
-    client: &mut RemoteClient<TuiConnectionHandler>,
+/// Resolve a forward target and start it in the background; the result
+/// arrives on `forward_tx`.
+fn request_forward(
+    client: &RemoteClient<TuiConnectionHandler>,
     state: &mut TuiState,
-    event: ConnectionEvent,
+    spec: Option<&ForwardSpec>,
+    forward_tx: &async_channel::Sender<Result<PortForward, String>>,
 ) {
-    match event {
-        ConnectionEvent::StatusChanged { status, .. } => {
-            state.status = status;
-        }
-        ConnectionEvent::TokenObtained {
-            token,
-            cert_fingerprint,
-            ..
-        } => {
-            client.update_shared_token(&token);
-            client.config_mut().saved_token = Some(token);
-            client.config_mut().token_obtained_at = Some(unix_now());
-            client.config_mut().pinned_cert_sha256 = cert_fingerprint;
-        }
-        ConnectionEvent::TlsUpgraded {
-            cert_fingerprint, ..
-        } => {
-            client.config_mut().tls = true;
-            client.config_mut().pinned_cert_sha256 = cert_fingerprint;
-        }
-        ConnectionEvent::StateReceived {
-            state: new_state, ..
-        } => {
-            client.set_remote_state(Some(new_state.clone()));
-            state.state = Some(new_state);
-        }
-        ConnectionEvent::SettingsChanged { .. } => {}
-        ConnectionEvent::SubscriptionMappings { mappings, .. } => {
-            client.update_stream_mappings(mappings);
-        }
-        ConnectionEvent::ServerWarning { message, .. } => {
-            state.message = Some(message);
-        }
-        ConnectionEvent::GitStatusChanged { statuses, .. } => {
-            if let Some(remote_state) = state.state.as_mut() {
-                for project in &mut remote_state.projects {
-                    project.git_status = statuses.get(&project.id).cloned();
-                }
-            }
-        }
-        ConnectionEvent::SystemStatsChanged { .. }
-        | ConnectionEvent::TerminalFocusRequested { .. } => {}
-        ConnectionEvent::Toast { toast, .. } => {
-            state.message = Some(format!("{}: {}", toast.level, toast.message));
-        }
-        ConnectionEvent::TokenRefreshed { token, .. } => {
-            client.update_shared_token(&token);
-            client.config_mut().saved_token = Some(token);
-            client.config_mut().token_obtained_at = Some(unix_now());
+    let Some(snapshot) = state.view.state.as_ref() else {
+        return;
+    };
+    let forwarded: Vec<_> = state
+        .forwards
+        .iter()
+        .map(|forward| forward.target().clone())
+        .collect();
+    let target = match pick_target(snapshot, spec, state.active_terminal.as_deref(), &forwarded) {
+        Ok(target) => target,
+        Err(e) => {
+            state.view.message = Some(format!("forward failed: {e}"));
+            return;
         }
-    }
-}
-
-fn unix_now() -> i64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .ok()
-        .and_then(|duration| i64::try_from(duration.as_secs()).ok())
-        .unwrap_or_default()
-}
-
-enum LoopControl {
-    Continue,
-    Quit,
+    };
+    let config = client.config().clone();
+    let Some(token) = config.effective_auth_token() else {
+        state.view.message = Some("forward failed: no auth token".to_string());
+        return;
+    };
+    state.view.message = Some(format!("forwarding {}...", target.service));
+    let forward_tx = forward_tx.clone();
+    tokio::spawn(async move {
+        let started = PortForward::start(config, token, target, None).await;
+        let _ = forward_tx.send(started).await;
+    });
 }
 
 fn handle_key(
     connection_id: &str,
-    terminals: &Arc<RwLock<HashMap<String, Arc<Terminal>>>>,
+    terminals: &TerminalMap,
     state: &mut TuiState,
     key: CrosstermKeyEvent,
 ) -> Result<LoopControl> {
@@ -521,6 +319,12 @@ fn handle_key(
         return Ok(LoopControl::Continue);
     }
 
+    if key.modifiers.contains(CrosstermKeyModifiers::CONTROL)
+        && matches!(key.code, KeyCode::Char('o'))
+    {
+        return Ok(LoopControl::Forward);
+    }
+
     let Some(active) = state.active_terminal.as_deref() else {
         return Ok(LoopControl::Continue);
     };
@@ -537,77 +341,7 @@ fn handle_key(
     Ok(LoopControl::Continue)
 }
 
-fn key_bytes(terminal: &Terminal, key: CrosstermKeyEvent) -> Option<Vec<u8>> {
-    let modifiers = key_modifiers(key.modifiers);
-
-    if let KeyCode::Char(ch) = key.code
-        && !modifiers.control
-        && !modifiers.alt
-        && !modifiers.platform
-    {
-        return Some(ch.to_string().into_bytes());
-    }
-
-    let key_name = match key.code {
-        KeyCode::Backspace => "backspace".to_string(),
-        KeyCode::Enter => "enter".to_string(),
-        KeyCode::Left => "left".to_string(),
-        KeyCode::Right => "right".to_string(),
-        KeyCode::Up => "up".to_string(),
-        KeyCode::Down => "down".to_string(),
-        KeyCode::Home => "home".to_string(),
-        KeyCode::End => "end".to_string(),
-        KeyCode::PageUp => "pageup".to_string(),
-        KeyCode::PageDown => "pagedown".to_string(),
-        KeyCode::Tab => "tab".to_string(),
-        KeyCode::BackTab => "tab".to_string(),
-        KeyCode::Delete => "delete".to_string(),
-        KeyCode::Insert => "insert".to_string(),
-        KeyCode::Esc => "escape".to_string(),
-        KeyCode::F(n) => format!("f{n}"),
-        KeyCode::Char(ch) => ch.to_string(),
-        KeyCode::Null
-        | KeyCode::CapsLock
-        | KeyCode::ScrollLock
-        | KeyCode::NumLock
-        | KeyCode::PrintScreen
-        | KeyCode::Pause
-        | KeyCode::Menu
-        | KeyCode::KeypadBegin
-        | KeyCode::Media(_)
-        | KeyCode::Modifier(_) => return None,
-    };
-
-    let mut event = KeyEvent {
-        key: key_name,
-        key_char: None,
-        modifiers,
-    };
-    if matches!(key.code, KeyCode::BackTab) {
-        event.modifiers.shift = true;
-    }
-
-    key_to_bytes(
-        &event,
-        terminal.is_app_cursor_mode(),
-        terminal.kitty_keyboard_flags(),
-    )
-}
-
-fn key_modifiers(modifiers: CrosstermKeyModifiers) -> KeyModifiers {
-    KeyModifiers {
-        control: modifiers.contains(CrosstermKeyModifiers::CONTROL),
-        shift: modifiers.contains(CrosstermKeyModifiers::SHIFT),
-        alt: modifiers.contains(CrosstermKeyModifiers::ALT),
-        platform: false,
-    }
-}
-
-fn render(
-    connection_id: &str,
-    terminals: &Arc<RwLock<HashMap<String, Arc<Terminal>>>>,
-    state: &mut TuiState,
-) -> Result<()> {
+fn render(connection_id: &str, terminals: &TerminalMap, state: &mut TuiState) -> Result<()> {
     let (cols, rows) = terminal::size()?;
     let terminal_rows = rows.saturating_sub(1).max(1);
     resize_active_terminal(connection_id, terminals, state, cols, terminal_rows);
@@ -642,7 +376,7 @@ fn render(
 
 fn resize_active_terminal(
     connection_id: &str,
-    terminals: &Arc<RwLock<HashMap<String, Arc<Terminal>>>>,
+    terminals: &TerminalMap,
     state: &mut TuiState,
     cols: u16,
     rows: u16,
@@ -681,10 +415,10 @@ fn draw_status(stdout: &mut io::Stdout, state: &TuiState, cols: u16, rows: u16)
         .and_then(|active| entries.iter().find(|entry| entry.id == active))
         .map(|entry| entry.label.as_str())
         .unwrap_or("none");
-    let message = state.message.as_deref().unwrap_or("");
+    let message = state.view.message.as_deref().unwrap_or("");
     let status = format!(
-        " Okena TUI | {} | {}/{} {} | Ctrl-] quit | Ctrl-T next {}{}",
-        status_label(&state.status),
+        " Okena TUI | {} | {}/{} {} | Ctrl-] quit | Ctrl-T next | Ctrl-O forward {}{}",
+        status_label(&state.view.status),
         active_index,
         entries.len(),
         active_label,
@@ -703,17 +437,6 @@ fn draw_status(stdout: &mut io::Stdout, state: &TuiState, cols: u16, rows: u16)
     Ok(())
 }
 
-fn status_label(status: &ConnectionStatus) -> String {
-    match status {
-        ConnectionStatus::Disconnected => "disconnected".to_string(),
-        ConnectionStatus::Connecting => "connecting".to_string(),
-        ConnectionStatus::Pairing => "pairing".to_string(),
-        ConnectionStatus::Connected => "connected".to_string(),
-        ConnectionStatus::Reconnecting { attempt } => format!("reconnecting:{attempt}"),
-        ConnectionStatus::Error(message) => format!("error:{message}"),
-    }
-}
-
 fn fit_line(line: &str, cols: u16) -> String {
     line.chars().take(usize::from(cols)).collect()
 }
@@ -797,6 +520,7 @@ fn connection_config(args: &Args) -> Result<RemoteConnectionConfig> {
         tls,
         pinned_cert_sha256: None,
         local_endpoint,
+        proxy_command: None,
     })
 }
 
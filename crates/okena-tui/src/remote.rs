@@ -0,0 +1,294 @@
+//! Remote-client glue shared by the TUI and `okena attach`: a
+//! [`TerminalTransport`] that writes over the WebSocket, a
+//! [`ConnectionHandler`] that keeps one local [`Terminal`] per remote pane, and
+//! the connection-event bookkeeping both front ends need.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+use okena_core::api::StateResponse;
+use okena_terminal::terminal::{Terminal, TerminalSize, TerminalTransport};
+use okena_transport::client::{
+    ConnectionEvent, ConnectionHandler, ConnectionStatus, REMOTE_TERMINAL_ANSWERS_QUERIES,
+    REMOTE_TERMINAL_RESIZE_DEBOUNCE_MS, REMOTE_TERMINAL_USES_MOUSE_BACKEND, RemoteClient,
+    WsClientMessage, is_remote_terminal, resize_remote_terminal, send_remote_terminal_input,
+    strip_prefix,
+};
+use parking_lot::RwLock;
+
+/// Local terminals keyed by prefixed id (see [`make_prefixed_id`](okena_transport::client::make_prefixed_id)).
+pub type TerminalMap = Arc<RwLock<HashMap<String, Arc<Terminal>>>>;
+
+/// What the connection has told us so far.
+pub struct RemoteView {
+    pub status: ConnectionStatus,
+    pub state: Option<StateResponse>,
+    /// Last server warning or toast, for a status line.
+    pub message: Option<String>,
+}
+
+impl Default for RemoteView {
+    fn default() -> Self {
+        Self {
+            status: ConnectionStatus::Disconnected,
+            state: None,
+            message: None,
+        }
+    }
+}
+
+pub(crate) struct TuiRemoteTransport {
+    ws_tx: async_channel::Sender<WsClientMessage>,
+    connection_id: String,
+}
+
+impl TerminalTransport for TuiRemoteTransport {
+    fn send_input(&self, terminal_id: &str, data: &[u8]) {
+        send_remote_terminal_input(&self.ws_tx, &self.connection_id, terminal_id, data);
+    }
+
+    fn send_response(&self, _terminal_id: &str, _data: &[u8]) {}
+
+    fn resize(&self, terminal_id: &str, cols: u16, rows: u16) {
+        resize_remote_terminal(&self.ws_tx, &self.connection_id, terminal_id, cols, rows);
+    }
+
+    fn uses_mouse_backend(&self) -> bool {
+        REMOTE_TERMINAL_USES_MOUSE_BACKEND
+    }
+
+    fn resize_debounce_ms(&self) -> u64 {
+        REMOTE_TERMINAL_RESIZE_DEBOUNCE_MS
+    }
+
+    fn answers_terminal_queries(&self) -> bool {
+        REMOTE_TERMINAL_ANSWERS_QUERIES
+    }
+}
+
+pub struct TuiConnectionHandler {
+    terminals: TerminalMap,
+    dirty_tx: async_channel::Sender<()>,
+}
+
+impl TuiConnectionHandler {
+    pub fn new(terminals: TerminalMap, dirty_tx: async_channel::Sender<()>) -> Self {
+        Self {
+            terminals,
+            dirty_tx,
+        }
+    }
+}
+
+impl ConnectionHandler for TuiConnectionHandler {
+    fn create_terminal(
+        &self,
+        connection_id: &str,
+        _terminal_id: &str,
+        prefixed_id: &str,
+        ws_sender: async_channel::Sender<WsClientMessage>,
+        cols: u16,
+        rows: u16,
+    ) {
+        if self.terminals.read().contains_key(prefixed_id) {
+            return;
+        }
+
+        let size = if cols > 0 && rows > 0 {
+            TerminalSize {
+                cols,
+                rows,
+                ..TerminalSize::default()
+            }
+        } else {
+            TerminalSize::default()
+        };
+        let transport = Arc::new(TuiRemoteTransport {
+            ws_tx: ws_sender,
+            connection_id: connection_id.to_string(),
+        });
+        let terminal = Arc::new(Terminal::new(
+            prefixed_id.to_string(),
+            size,
+            transport,
+            String::new(),
+        ));
+        self.terminals
+            .write()
+            .insert(prefixed_id.to_string(), terminal);
+    }
+
+    fn on_terminal_output(&self, prefixed_id: &str, data: &[u8]) {
+        if let Some(terminal) = self.terminals.read().get(prefixed_id) {
+            terminal.enqueue_output(data);
+            let _ = self.dirty_tx.try_send(());
+        }
+    }
+
+    fn resize_terminal(&self, prefixed_id: &str, cols: u16, rows: u16, server_owns: bool) {
+        if let Some(terminal) = self.terminals.read().get(prefixed_id) {
+            if server_owns {
+                terminal.claim_resize_remote();
+            }
+            terminal.resize_grid_only(cols, rows);
+            let _ = self.dirty_tx.try_send(());
+        }
+    }
+
+    fn remove_terminal(&self, prefixed_id: &str) {
+        self.terminals.write().remove(prefixed_id);
+        let _ = self.dirty_tx.try_send(());
+    }
+
+    fn remove_all_terminals(&self, connection_id: &str) {
+        let mut terminals = self.terminals.write();
+        let to_remove: Vec<String> = terminals
+            .keys()
+            .filter(|key| is_remote_terminal(key, connection_id))
+            .cloned()
+            .collect();
+        for key in to_remove {
+            terminals.remove(&key);
+        }
+        let _ = self.dirty_tx.try_send(());
+    }
+
+    fn remove_terminals_except(
+        &self,
+        connection_id: &str,
+        keep_ids: &std::collections::HashSet<String>,
+    ) {
+        let mut terminals = self.terminals.write();
+        let to_remove: Vec<String> = terminals
+            .keys()
+            .filter(|key| {
+                is_remote_terminal(key, connection_id)
+                    && !keep_ids.contains(&strip_prefix(key, connection_id))
+            })
+            .cloned()
+            .collect();
+        for key in to_remove {
+            terminals.remove(&key);
+        }
+        let _ = self.dirty_tx.try_send(());
+    }
+}
+
+pub async fn wait_for_initial_state(
+    client: &mut RemoteClient<TuiConnectionHandler>,
+    view: &mut RemoteView,
+    event_rx: &async_channel::Receiver<ConnectionEvent>,
+    pair_code: Option<&str>,
+) -> Result<()> {
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let mut pair_sent = false;
+
+    loop {
+        while let Ok(event) = event_rx.try_recv() {
+            handle_connection_event(client, view, event);
+        }
+
+        match &view.status {
+            ConnectionStatus::Connected if view.state.is_some() => return Ok(()),
+            ConnectionStatus::Pairing => {
+                let Some(code) = pair_code else {
+                    bail!(
+                        "pairing required. Pass --pair <code>, --token <token>, or connect over discovered local Unix socket"
+                    );
+                };
+                if !pair_sent {
+                    client.pair(code);
+                    pair_sent = true;
+                }
+            }
+            ConnectionStatus::Error(message) => {
+                bail!("{message}");
+            }
+            _ => {}
+        }
+
+        if Instant::now() >= deadline {
+            bail!("timed out waiting for remote state");
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+pub fn handle_connection_event(
+    client: &mut RemoteClient<TuiConnectionHandler>,
+    view: &mut RemoteView,
+    event: ConnectionEvent,
+) {
+    match event {
+        ConnectionEvent::StatusChanged { status, .. } => {
+            view.status = status;
+        }
+        ConnectionEvent::TokenObtained {
+            token,
+            cert_fingerprint,
+            ..
+        } => {
+            client.update_shared_token(&token);
+            client.config_mut().saved_token = Some(token);
+            client.config_mut().token_obtained_at = Some(unix_now());
+            client.config_mut().pinned_cert_sha256 = cert_fingerprint;
+        }
+        ConnectionEvent::TlsUpgraded {
+            cert_fingerprint, ..
+        } => {
+            client.config_mut().tls = true;
+            client.config_mut().pinned_cert_sha256 = cert_fingerprint;
+        }
+        ConnectionEvent::StateReceived {
+            state: new_state, ..
+        } => {
+            client.set_remote_state(Some(new_state.clone()));
+            view.state = Some(new_state);
+        }
+        ConnectionEvent::SettingsChanged { .. } => {}
+        ConnectionEvent::SubscriptionMappings { mappings, .. } => {
+            client.update_stream_mappings(mappings);
+        }
+        ConnectionEvent::ServerWarning { message, .. } => {
+            view.message = Some(message);
+        }
+        ConnectionEvent::GitStatusChanged { statuses, .. } => {
+            if let Some(remote_state) = view.state.as_mut() {
+                for project in &mut remote_state.projects {
+                    project.git_status = statuses.get(&project.id).cloned();
+                }
+            }
+        }
+        ConnectionEvent::SystemStatsChanged { .. }
+        | ConnectionEvent::TerminalFocusRequested { .. } => {}
+        ConnectionEvent::Toast { toast, .. } => {
+            view.message = Some(format!("{}: {}", toast.level, toast.message));
+        }
+        ConnectionEvent::TokenRefreshed { token, .. } => {
+            client.update_shared_token(&token);
+            client.config_mut().saved_token = Some(token);
+            client.config_mut().token_obtained_at = Some(unix_now());
+        }
+    }
+}
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .and_then(|duration| i64::try_from(duration.as_secs()).ok())
+        .unwrap_or_default()
+}
+
+pub fn status_label(status: &ConnectionStatus) -> String {
+    match status {
+        ConnectionStatus::Disconnected => "disconnected".to_string(),
+        ConnectionStatus::Connecting => "connecting".to_string(),
+        ConnectionStatus::Pairing => "pairing".to_string(),
+        ConnectionStatus::Connected => "connected".to_string(),
+        ConnectionStatus::Reconnecting { attempt } => format!("reconnecting:{attempt}"),
+        ConnectionStatus::Error(message) => format!("error:{message}"),
+    }
+}
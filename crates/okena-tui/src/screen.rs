@@ -0,0 +1,42 @@
+//! Raw-mode / alternate-screen guard for full-screen front ends.
+
+use std::io;
+
+use anyhow::Result;
+use crossterm::{
+    cursor,
+    event::{DisableBracketedPaste, EnableBracketedPaste},
+    execute,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+/// Puts the tty in raw mode on the alternate screen; restores it on drop, so
+/// an early `?` return or a panic never leaves the user's shell unusable.
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn enter() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(
+            stdout,
+            EnterAlternateScreen,
+            EnableBracketedPaste,
+            cursor::Hide
+        )?;
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+        let mut stdout = io::stdout();
+        let _ = execute!(
+            stdout,
+            cursor::Show,
+            DisableBracketedPaste,
+            LeaveAlternateScreen
+        );
+    }
+}
@@ -20,6 +20,7 @@ pub struct RemoteConnectDialog {
     name_input: Entity<SimpleInputState>,
     host_input: Entity<SimpleInputState>,
     port_input: Entity<SimpleInputState>,
+    proxy_input: Entity<SimpleInputState>,
     code_input: Entity<SimpleInputState>,
     status: ConnectionDialogStatus,
     initial_focus_done: bool,
@@ -87,12 +88,7 @@ async fn detect_scheme(
         if let Ok((base_url, Ok(resp))) = probe
             && resp.status().is_success()
         {
-            let version = resp
-                .text()
-                .await
-                .ok()
-                .and_then(|b| serde_json::from_str::<serde_json::Value>(&b).ok())
-                .and_then(|v| v.get("version").and_then(|v| v.as_str()).map(String::from));
+            let version = health_version(resp).await;
             return Ok(Detected {
                 tls,
                 base_url,
@@ -103,6 +99,66 @@ async fn detect_scheme(
     Err("Cannot reach server over TLS or plain http".to_string())
 }
 
+/// Probe `/health` through the relay for `config`'s proxy command: over TLS
+/// first, then plain http, like [`detect_scheme`]. A command ending at
+/// `okena-daemon --stdio` reaches a Unix socket, so only plain http is tried.
+async fn detect_via_proxy(
+    runtime: &Arc<tokio::runtime::Runtime>,
+    config: RemoteConnectionConfig,
+    observed: okena_transport::client::tls::ObservedFingerprint,
+) -> Result<Detected, String> {
+    let mut last_error = None;
+    for tls in [true, false] {
+        let mut config = config.clone();
+        config.tls = tls;
+        if tls && !config.proxy_uses_tls() {
+            continue;
+        }
+        let observed = observed.clone();
+        let probe = runtime
+            .spawn(async move {
+                let (client, url) = okena_transport::remote_http::async_client_and_url_observed(
+                    &config, "/health", observed,
+                )?;
+                let resp = client
+                    .get(url)
+                    .timeout(std::time::Duration::from_secs(10))
+                    .send()
+                    .await
+                    .map_err(|error| error.to_string())?;
+                if !resp.status().is_success() {
+                    return Err(format!("Health check returned HTTP {}", resp.status()));
+                }
+                Ok(Detected {
+                    tls,
+                    base_url: config.http_origin(),
+                    version: health_version(resp).await,
+                })
+            })
+            .await
+            .map_err(|error| error.to_string())?;
+        match probe {
+            Ok(detected) => return Ok(detected),
+            Err(error) => last_error = Some(error),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| "Cannot reach server".to_string()))
+}
+
+async fn health_version(resp: reqwest::Response) -> Option<String> {
+    resp.text()
+        .await
+        .ok()
+        .and_then(|b| serde_json::from_str::<serde_json::Value>(&b).ok())
+        .and_then(|v| v.get("version").and_then(|v| v.as_str()).map(String::from))
+}
+
+/// Trimmed contents of the proxy command field, `None` when blank.
+fn proxy_command_value(input: &Entity<SimpleInputState>, cx: &App) -> Option<String> {
+    let value = input.read(cx).value().trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
 pub enum RemoteConnectDialogEvent {
     Close,
     Connected { config: RemoteConnectionConfig },
@@ -125,6 +181,9 @@ impl RemoteConnectDialog {
             s.set_value("19100", cx);
             s.placeholder("19100")
         });
+        let proxy_input = cx.new(|cx| {
+            SimpleInputState::new(cx).placeholder("Optional, e.g. ssh -W %h:%p jump-host")
+        });
         let code_input =
             cx.new(|cx| SimpleInputState::new(cx).placeholder("Pairing code from remote..."));
 
@@ -134,6 +193,7 @@ impl RemoteConnectDialog {
             name_input,
             host_input,
             port_input,
+            proxy_input,
             code_input,
             status: ConnectionDialogStatus::Idle,
             initial_focus_done: false,
@@ -162,6 +222,7 @@ impl RemoteConnectDialog {
     fn test_connection(&mut self, cx: &mut Context<Self>) {
         let host = self.host_input.read(cx).value().to_string();
         let port = self.port_input.read(cx).value().to_string();
+        let proxy_command = proxy_command_value(&self.proxy_input, cx);
 
         if host.is_empty() {
             self.status = ConnectionDialogStatus::TestFailed("Host is required".to_string());
@@ -176,11 +237,37 @@ impl RemoteConnectDialog {
         let runtime = self.runtime(cx);
 
         cx.spawn(async move |this: WeakEntity<Self>, cx| {
-            let observed = okena_transport::client::tls::new_observed();
-            let status = match detect_scheme(&runtime, host, port_num, observed).await {
+            let proxied = proxy_command.is_some();
+            let detected = match proxy_command {
+                Some(command) => {
+                    let config = RemoteConnectionConfig {
+                        id: String::new(),
+                        name: String::new(),
+                        host,
+                        port: port_num,
+                        saved_token: None,
+                        token_obtained_at: None,
+                        tls: false,
+                        pinned_cert_sha256: None,
+                        local_endpoint: None,
+                        proxy_command: Some(command),
+                    };
+                    let observed = okena_transport::client::tls::new_observed();
+                    detect_via_proxy(&runtime, config, observed).await
+                }
+                None => {
+                    let observed = okena_transport::client::tls::new_observed();
+                    detect_scheme(&runtime, host, port_num, observed).await
+                }
+            };
+            let status = match detected {
                 Ok(d) => {
                     let version = d.version.unwrap_or_else(|| "unknown".to_string());
-                    let label = if d.tls {
+                    let label = if proxied && d.tls {
+                        format!("{version}, TLS via proxy command")
+                    } else if proxied {
+                        format!("{version}, via proxy command")
+                    } else if d.tls {
                         format!("{version}, TLS")
                     } else {
                         format!("{version}, plaintext")
@@ -203,8 +290,12 @@ impl RemoteConnectDialog {
         let host = self.host_input.read(cx).value().to_string();
         let port_str = self.port_input.read(cx).value().to_string();
         let code = self.code_input.read(cx).value().to_string();
+        let proxy_command = proxy_command_value(&self.proxy_input, cx);
 
-        if host.is_empty() || code.is_empty() {
+        // A proxy command to `okena-daemon --stdio` lands on a trusted socket,
+        // so the code is optional there; over `ssh -W` the server asks for
+        // pairing on first connect instead.
+        if host.is_empty() || (code.is_empty() && proxy_command.is_none()) {
             self.status = ConnectionDialogStatus::ConnectFailed(
                 "Host and pairing code are required".to_string(),
             );
@@ -232,6 +323,7 @@ impl RemoteConnectDialog {
             tls: false, // set by auto-detection below
             pinned_cert_sha256: None,
             local_endpoint: None,
+            proxy_command,
         };
 
         let runtime = self.runtime(cx);
@@ -243,9 +335,14 @@ impl RemoteConnectDialog {
         cx.spawn(async move |this: WeakEntity<Self>, cx| {
             let mut config = config;
 
-            // Auto-detect the scheme (TLS first, plain-http fallback) and adopt it.
-            let detected = match detect_scheme(&runtime, host.clone(), port, observed.clone()).await
-            {
+            // Auto-detect the scheme (TLS first, plain-http fallback) and adopt
+            // it, through the proxy command's relay when there is one.
+            let detected = if config.proxy_command.is_some() {
+                detect_via_proxy(&runtime, config.clone(), observed.clone()).await
+            } else {
+                detect_scheme(&runtime, host.clone(), port, observed.clone()).await
+            };
+            let detected = match detected {
                 Ok(d) => d,
                 Err(e) => {
                     let _ = this.update(cx, |this, cx| {
@@ -259,15 +356,28 @@ impl RemoteConnectDialog {
             let base_url = detected.base_url;
             config.tls = use_tls;
 
+            if code.is_empty() {
+                let _ = this.update(cx, |_, cx| {
+                    cx.emit(RemoteConnectDialogEvent::Connected { config });
+                });
+                return;
+            }
+
             let pair_result = runtime
                 .spawn({
                     let base_url = base_url.clone();
                     let code = code.clone();
                     let observed = observed.clone();
+                    let proxied = config.proxy_command.is_some().then(|| config.clone());
                     async move {
-                        let client = okena_transport::client::tls::build_reqwest_client(
-                            use_tls, None, observed,
-                        )?;
+                        let client = match proxied {
+                            Some(config) => {
+                                okena_transport::remote_http::async_client_and_url(&config, "")?.0
+                            }
+                            None => okena_transport::client::tls::build_reqwest_client(
+                                use_tls, None, observed,
+                            )?,
+                        };
                         let pair_body = serde_json::json!({ "code": code });
                         client
                             .post(format!("{}/v1/pair", base_url))
@@ -500,6 +610,22 @@ impl Render for RemoteConnectDialog {
                                     ),
                                 ),
                             )
+                            .child(
+                                labeled_input("Proxy Command:", &t).child(
+                                    input_container(&t, None).child(
+                                        SimpleInput::new(&self.proxy_input)
+                                            .text_size(ui_text_md(cx)),
+                                    ),
+                                ),
+                            )
+                            .child(
+                                div()
+                                    .text_size(ui_text_sm(cx))
+                                    .text_color(rgb(t.text_muted))
+                                    .child(
+                                        "Reach the server through a command's stdin/stdout instead of TCP, e.g. ssh host okena-daemon --stdio (no pairing code needed)",
+                                    ),
+                            )
                             .child(
                                 div()
                                     .flex()
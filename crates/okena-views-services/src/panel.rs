@@ -12,8 +12,20 @@ use std::sync::Arc;
 
 /// Callback taking a service name (e.g. start/stop/restart a named service).
 type ServiceActionCallback = Arc<dyn Fn(String, &mut Window, &mut App) + 'static>;
-/// Callback taking a port number (e.g. open a detected port in the browser).
-type PortClickCallback = Arc<dyn Fn(u16) + 'static>;
+/// Callback taking a service name and one of its detected ports (open it in
+/// the browser, forwarding it first when the daemon is remote).
+type PortClickCallback = Arc<dyn Fn(String, u16, &mut App) + 'static>;
+
+/// Tooltip for a detected port badge. `remote_host` is the daemon's host when
+/// it isn't this machine; clicking such a port forwards it to localhost.
+pub(crate) fn port_tooltip(remote_host: Option<&str>, port: u16) -> String {
+    match remote_host {
+        Some(host) if host != "localhost" => {
+            format!("{host}:{port} (forward to localhost and open)")
+        }
+        _ => format!("http://localhost:{port}"),
+    }
+}
 
 /// Render the tab header row for the service panel.
 ///
@@ -34,10 +46,17 @@ pub fn render_service_panel_header(
     on_start: impl Fn(&mut Window, &mut App) + 'static,
     on_stop: impl Fn(&mut Window, &mut App) + 'static,
     on_restart: impl Fn(&mut Window, &mut App) + 'static,
+    on_shell: impl Fn(&mut Window, &mut App) + 'static,
+    on_rebuild: impl Fn(&mut Window, &mut App) + 'static,
+    on_pull: impl Fn(&mut Window, &mut App) + 'static,
     on_close: impl Fn(&mut Window, &mut App) + 'static,
     active_status: Option<&ServiceStatus>,
 ) -> Stateful<Div> {
     let is_overview = active_service_name.is_none();
+    let active_service =
+        active_service_name.and_then(|name| services.iter().find(|s| s.name == name));
+    let active_is_docker = active_service.is_some_and(|s| s.is_docker);
+    let active_restart_trigger = active_service.and_then(|s| s.restart_trigger.clone());
 
     let active_is_running = matches!(active_status, Some(ServiceStatus::Running));
     let active_is_starting = matches!(
@@ -217,6 +236,20 @@ pub fn render_service_panel_header(
                                     .child(label),
                             )
                         })
+                        // Watch-triggered restart note
+                        .when_some(active_restart_trigger, |d, changed| {
+                            d.child(
+                                div()
+                                    .px(px(5.0))
+                                    .max_w(px(260.0))
+                                    .overflow_hidden()
+                                    .whitespace_nowrap()
+                                    .text_ellipsis()
+                                    .text_size(ui_text_ms(cx))
+                                    .text_color(rgb(t.text_muted))
+                                    .child(format!("restarted due to change in {changed}")),
+                            )
+                        })
                         // Start button (when stopped/crashed)
                         .when(active_is_stopped, |d| {
                             d.child(
@@ -262,6 +295,61 @@ pub fn render_service_panel_header(
                                     .tooltip(|_window, cx| Tooltip::new("Stop").build(_window, cx)),
                             )
                         })
+                        // Docker-only: shell into the running container
+                        .when(active_is_docker && active_is_running, |d| {
+                            d.child(
+                                icon_action_button(
+                                    "svc-panel-shell",
+                                    ">_",
+                                    t.text_secondary,
+                                    t,
+                                    cx,
+                                )
+                                .on_click(move |_, window, cx| {
+                                    cx.stop_propagation();
+                                    on_shell(window, cx);
+                                })
+                                .tooltip(|_window, cx| {
+                                    Tooltip::new("Open Shell in Container").build(_window, cx)
+                                }),
+                            )
+                        })
+                        // Docker-only: rebuild image + recreate container
+                        .when(active_is_docker && !active_is_starting, |d| {
+                            d.child(
+                                icon_action_button(
+                                    "svc-panel-rebuild",
+                                    "\u{2692}",
+                                    t.text_secondary,
+                                    t,
+                                    cx,
+                                )
+                                .on_click(move |_, window, cx| {
+                                    cx.stop_propagation();
+                                    on_rebuild(window, cx);
+                                })
+                                .tooltip(|_window, cx| Tooltip::new("Rebuild").build(_window, cx)),
+                            )
+                        })
+                        // Docker-only: pull latest image
+                        .when(active_is_docker, |d| {
+                            d.child(
+                                icon_action_button(
+                                    "svc-panel-pull",
+                                    "\u{2913}",
+                                    t.text_secondary,
+                                    t,
+                                    cx,
+                                )
+                                .on_click(move |_, window, cx| {
+                                    cx.stop_propagation();
+                                    on_pull(window, cx);
+                                })
+                                .tooltip(|_window, cx| {
+                                    Tooltip::new("Pull Image").build(_window, cx)
+                                }),
+                            )
+                        })
                 }),
         )
         .child(
@@ -309,7 +397,7 @@ pub fn render_service_overview(
     on_start: impl Fn(String, &mut Window, &mut App) + 'static,
     on_stop: impl Fn(String, &mut Window, &mut App) + 'static,
     on_restart: impl Fn(String, &mut Window, &mut App) + 'static,
-    on_port_click: impl Fn(u16) + 'static,
+    on_port_click: impl Fn(String, u16, &mut App) + 'static,
 ) -> Stateful<Div> {
     let has_docker = services.iter().any(|s| s.is_docker);
     let has_ports = services.iter().any(|s| !s.ports.is_empty());
@@ -535,10 +623,9 @@ fn render_overview_row(
                         let on_port_click = on_port_click.clone();
                         move |port| {
                             let port = *port;
-                            let host = remote_host.as_deref().unwrap_or("localhost");
-                            let url = format!("http://{}:{}", host, port);
-                            let tooltip_url = url.clone();
+                            let tooltip = port_tooltip(remote_host.as_deref(), port);
                             let on_port_click = on_port_click.clone();
+                            let name = name.clone();
                             div()
                                 .id(ElementId::Name(
                                     format!("svc-overview-port-{}-{}-{}", project_id, name, port)
@@ -557,11 +644,11 @@ fn render_overview_row(
                                 .text_color(rgb(t.text_muted))
                                 .child(format!(":{}", port))
                                 .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
-                                .on_click(move |_, _, _cx| {
-                                    on_port_click(port);
+                                .on_click(move |_, _, cx| {
+                                    on_port_click(name.clone(), port, cx);
                                 })
                                 .tooltip(move |_window, cx| {
-                                    Tooltip::new(tooltip_url.clone()).build(_window, cx)
+                                    Tooltip::new(tooltip.clone()).build(_window, cx)
                                 })
                         }
                     })),
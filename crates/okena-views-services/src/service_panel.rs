@@ -322,6 +322,7 @@ impl<D: ActionDispatch + Send + Sync> ServicePanel<D> {
                         ports: inst.detected_ports.clone(),
                         is_docker: matches!(inst.kind, ServiceKind::DockerCompose { .. }),
                         is_extra: inst.is_extra,
+                        restart_trigger: inst.restart_trigger.clone(),
                     })
                     .collect();
             }
@@ -338,6 +339,7 @@ impl<D: ActionDispatch + Send + Sync> ServicePanel<D> {
                         ports: api_svc.ports.clone(),
                         is_docker: api_svc.kind == "docker_compose",
                         is_extra: api_svc.is_extra,
+                        restart_trigger: api_svc.restart_trigger.clone(),
                     })
                     .collect()
             })
@@ -543,6 +545,64 @@ impl<D: ActionDispatch + Send + Sync> ServicePanel<D> {
                         }
                     }
                 },
+                // on_shell (active service)
+                {
+                    let entity = entity.clone();
+                    move |_window, cx| {
+                        if let Some(e) = entity.upgrade() {
+                            e.update(cx, |this, cx| {
+                                if let Some(name) = this.active_service_name.clone() {
+                                    this.dispatch_service_action(
+                                        ActionRequest::ExecServiceShell {
+                                            project_id: this.project_id.clone(),
+                                            service_name: name,
+                                            shell: None,
+                                        },
+                                        cx,
+                                    );
+                                }
+                            });
+                        }
+                    }
+                },
+                // on_rebuild (active service)
+                {
+                    let entity = entity.clone();
+                    move |_window, cx| {
+                        if let Some(e) = entity.upgrade() {
+                            e.update(cx, |this, cx| {
+                                if let Some(name) = this.active_service_name.clone() {
+                                    this.dispatch_service_action(
+                                        ActionRequest::RebuildService {
+                                            project_id: this.project_id.clone(),
+                                            service_name: name,
+                                        },
+                                        cx,
+                                    );
+                                }
+                            });
+                        }
+                    }
+                },
+                // on_pull (active service)
+                {
+                    let entity = entity.clone();
+                    move |_window, cx| {
+                        if let Some(e) = entity.upgrade() {
+                            e.update(cx, |this, cx| {
+                                if let Some(name) = this.active_service_name.clone() {
+                                    this.dispatch_service_action(
+                                        ActionRequest::PullService {
+                                            project_id: this.project_id.clone(),
+                                            service_name: name,
+                                        },
+                                        cx,
+                                    );
+                                }
+                            });
+                        }
+                    }
+                },
                 // on_close
                 {
                     let entity = entity.clone();
@@ -602,9 +662,9 @@ impl<D: ActionDispatch + Send + Sync> ServicePanel<D> {
             .read(cx)
             .remote_snapshot(&self.project_id)
             .and_then(|snap| snap.host.clone());
-        // Port links must target the daemon's host, not the client's localhost
-        // (the service runs on the daemon machine). Falls back to localhost for
-        // a loopback/local daemon that reports no host.
+        // A remote daemon's ports are forwarded to localhost by the dispatcher.
+        // Without one they're opened on the daemon's host, or localhost for a
+        // loopback/local daemon that reports no host.
         let port_host = remote_host
             .clone()
             .unwrap_or_else(|| "localhost".to_string());
@@ -679,9 +739,17 @@ impl<D: ActionDispatch + Send + Sync> ServicePanel<D> {
                 }
             },
             // on_port_click
-            move |port: u16| {
-                let url = format!("http://{}:{}", port_host, port);
-                open_url(&url);
+            {
+                let project_id = project_id.clone();
+                let dispatcher = self.action_dispatcher.clone();
+                move |service: String, port: u16, cx: &mut App| {
+                    let forwarded = dispatcher
+                        .as_ref()
+                        .is_some_and(|d| d.forward_service_port(&project_id, &service, port, cx));
+                    if !forwarded {
+                        open_url(&format!("http://{}:{}", port_host, port));
+                    }
+                }
             },
         )
     }
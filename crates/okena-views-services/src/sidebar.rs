@@ -97,7 +97,7 @@ pub fn render_service_item(
     on_stop: impl Fn(&mut Window, &mut App) + 'static,
     on_restart: impl Fn(&mut Window, &mut App) + 'static,
     on_click: impl Fn(&mut Window, &mut App) + 'static,
-    on_port_click: impl Fn(u16) + 'static,
+    on_port_click: impl Fn(String, u16, &mut App) + 'static,
 ) -> Stateful<Div> {
     let pid = project_id.to_string();
     let service_name = service.name.clone();
@@ -140,14 +140,21 @@ pub fn render_service_item(
                 .h(px(6.0))
                 .rounded(px(1.5))
                 .bg(rgb(sc));
-            if let ServiceStatus::Crashed { exit_code } = &status {
-                let tip = match exit_code {
+            let tip = match &status {
+                ServiceStatus::Crashed { exit_code } => Some(match exit_code {
                     Some(code) => format!("Exited with code {}", code),
                     None => "Crashed".to_string(),
-                };
-                dot.tooltip(move |_window, cx| Tooltip::new(tip.clone()).build(_window, cx))
-            } else {
-                dot
+                }),
+                _ => service
+                    .restart_trigger
+                    .as_ref()
+                    .map(|changed| format!("Restarted due to change in {changed}")),
+            };
+            match tip {
+                Some(tip) => {
+                    dot.tooltip(move |_window, cx| Tooltip::new(tip.clone()).build(_window, cx))
+                }
+                None => dot,
             }
         })
         .when(is_docker, |d| {
@@ -185,9 +192,9 @@ pub fn render_service_item(
                 let on_port_click = std::sync::Arc::new(on_port_click);
                 move |port| {
                     let port = *port;
-                    let url = format!("http://{}:{}", port_host, port);
-                    let tooltip_url = url.clone();
+                    let tooltip = crate::panel::port_tooltip(Some(port_host.as_str()), port);
                     let on_port_click = on_port_click.clone();
+                    let service_name = service_name.clone();
                     div()
                         .id(ElementId::Name(
                             format!("svc-port-{}-{}-{}", pid, service_name, port).into(),
@@ -205,11 +212,11 @@ pub fn render_service_item(
                         .text_color(rgb(t.text_muted))
                         .child(format!(":{}", port))
                         .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
-                        .on_click(move |_, _, _cx| {
-                            on_port_click(port);
+                        .on_click(move |_, _, cx| {
+                            on_port_click(service_name.clone(), port, cx);
                         })
                         .tooltip(move |_window, cx| {
-                            Tooltip::new(tooltip_url.clone()).build(_window, cx)
+                            Tooltip::new(tooltip.clone()).build(_window, cx)
                         })
                 }
             }),
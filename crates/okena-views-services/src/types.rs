@@ -11,6 +11,8 @@ pub struct ServiceSnapshot {
     pub is_docker: bool,
     /// Docker service not listed in okena.yaml — shown in "Other" section.
     pub is_extra: bool,
+    /// Watched path whose change caused the last automatic restart.
+    pub restart_trigger: Option<String>,
 }
 
 /// Compute the status dot color for a given ServiceStatus.
@@ -1,17 +1,36 @@
-//! Hook log overlay — shows recent hook execution history.
+//! Hook log overlay — shows recent hook execution history, lets running hooks
+//! be cancelled, and browses the runs the daemon persisted to disk.
 
 use crate::Cancel;
 use gpui::prelude::*;
 use gpui::*;
-use gpui_component::v_flex;
+use gpui_component::{h_flex, v_flex};
+use okena_core::api::{ActionRequest, ApiHookRun, ApiHookRunDiff, ApiHookStatus};
 use okena_core::theme::ThemeColors;
+use okena_transport::remote_action::RemoteActionClient;
 use okena_ui::badge::keyboard_hints_footer;
+use okena_ui::button::button;
 use okena_ui::modal::{modal_backdrop, modal_content, modal_header};
 use okena_ui::theme::theme;
 use okena_ui::tokens::{ui_text, ui_text_md, ui_text_ms};
 use okena_workspace::hook_monitor::{HookExecution, HookMonitor, HookStatus};
 use std::time::Duration;
 
+/// How many persisted runs the "Saved runs" tab lists.
+const SAVED_RUN_LIMIT: usize = 100;
+
+/// What the overlay body currently shows.
+enum HookLogView {
+    /// In-memory execution history from the `HookMonitor`.
+    Recent,
+    /// Runs persisted by the daemon (survive restarts).
+    Saved,
+    /// One persisted run with its captured output.
+    Run(Box<ApiHookRun>),
+    /// Output diff between two persisted runs.
+    Diff(Box<ApiHookRunDiff>),
+}
+
 /// Hook log overlay — shows recent hook execution history.
 pub struct HookLog {
     focus_handle: FocusHandle,
@@ -19,10 +38,18 @@ pub struct HookLog {
     /// Last seen HookMonitor version — used to skip expensive history cloning
     /// when nothing has changed.
     last_version: u64,
+    /// Daemon action client; `None` disables cancel and the saved-run browser.
+    client: Option<RemoteActionClient>,
+    view: HookLogView,
+    saved_runs: Vec<ApiHookRun>,
+    /// Run picked as the base of the next diff.
+    base_run: Option<u64>,
+    loading: bool,
+    error_message: Option<String>,
 }
 
 impl HookLog {
-    pub fn new(cx: &mut Context<Self>) -> Self {
+    pub fn new(client: Option<RemoteActionClient>, cx: &mut Context<Self>) -> Self {
         let (history, last_version) = cx
             .try_global::<HookMonitor>()
             .map(|m| (m.history(), m.version()))
@@ -56,12 +83,123 @@ impl HookLog {
             focus_handle,
             history,
             last_version,
+            client,
+            view: HookLogView::Recent,
+            saved_runs: Vec::new(),
+            base_run: None,
+            loading: false,
+            error_message: None,
         }
     }
 
     fn close(&self, cx: &mut Context<Self>) {
         cx.emit(HookLogEvent::Close);
     }
+
+    /// Post `action` to the daemon off the UI thread and hand the response
+    /// to `on_done`. Errors land in `error_message`.
+    fn request(
+        &mut self,
+        action: ActionRequest,
+        cx: &mut Context<Self>,
+        on_done: impl FnOnce(&mut Self, Option<serde_json::Value>, &mut Context<Self>) + 'static,
+    ) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        self.loading = true;
+        self.error_message = None;
+        cx.notify();
+        cx.spawn(async move |this, cx| {
+            let result = smol::unblock(move || client.post_action(action)).await;
+            let _ = this.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(value) => on_done(this, value, cx),
+                    Err(error) => this.error_message = Some(error),
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn cancel_execution(&mut self, execution_id: u64, cx: &mut Context<Self>) {
+        // The mirrored monitor picks up the Cancelled status on the next poll.
+        self.request(ActionRequest::CancelHook { execution_id }, cx, |_, _, _| {});
+    }
+
+    fn show_recent(&mut self, cx: &mut Context<Self>) {
+        self.view = HookLogView::Recent;
+        self.error_message = None;
+        cx.notify();
+    }
+
+    fn show_saved_runs(&mut self, cx: &mut Context<Self>) {
+        self.view = HookLogView::Saved;
+        self.request(
+            ActionRequest::ListHookRuns {
+                hook_type: None,
+                limit: Some(SAVED_RUN_LIMIT),
+            },
+            cx,
+            |this, value, _| match serde_json::from_value::<Vec<ApiHookRun>>(
+                value.unwrap_or_default(),
+            )
+            .map_err(invalid_response)
+            {
+                Ok(runs) => this.saved_runs = runs,
+                Err(error) => this.error_message = Some(error),
+            },
+        );
+    }
+
+    fn open_run(&mut self, run_id: u64, cx: &mut Context<Self>) {
+        self.request(
+            ActionRequest::GetHookRun { run_id },
+            cx,
+            |this, value, _| match serde_json::from_value::<ApiHookRun>(value.unwrap_or_default())
+                .map_err(invalid_response)
+            {
+                Ok(run) => this.view = HookLogView::Run(Box::new(run)),
+                Err(error) => this.error_message = Some(error),
+            },
+        );
+    }
+
+    fn toggle_base_run(&mut self, run_id: u64, cx: &mut Context<Self>) {
+        self.base_run = if self.base_run == Some(run_id) {
+            None
+        } else {
+            Some(run_id)
+        };
+        cx.notify();
+    }
+
+    fn diff_against_base(&mut self, run_id: u64, cx: &mut Context<Self>) {
+        let Some(base_run_id) = self.base_run else {
+            return;
+        };
+        self.request(
+            ActionRequest::DiffHookRuns {
+                base_run_id,
+                run_id,
+            },
+            cx,
+            |this, value, _| match serde_json::from_value::<ApiHookRunDiff>(
+                value.unwrap_or_default(),
+            )
+            .map_err(invalid_response)
+            {
+                Ok(diff) => this.view = HookLogView::Diff(Box::new(diff)),
+                Err(error) => this.error_message = Some(error),
+            },
+        );
+    }
+}
+
+fn invalid_response(error: serde_json::Error) -> String {
+    format!("Invalid hook run response: {error}")
 }
 
 pub enum HookLogEvent {
@@ -89,12 +227,118 @@ fn format_elapsed(execution: &HookExecution) -> String {
     let elapsed = execution.started_at.elapsed();
     match &execution.status {
         HookStatus::Running => format!("{}... (running)", format_duration(elapsed)),
-        HookStatus::Succeeded { duration } => format_duration(*duration),
-        HookStatus::Failed { duration, .. } => format_duration(*duration),
+        HookStatus::Succeeded { duration }
+        | HookStatus::Failed { duration, .. }
+        | HookStatus::Cancelled { duration }
+        | HookStatus::TimedOut { duration } => format_duration(*duration),
         HookStatus::SpawnError { .. } => "—".to_string(),
     }
 }
 
+fn format_run_duration(status: &ApiHookStatus) -> String {
+    match status {
+        ApiHookStatus::Running => "running".to_string(),
+        ApiHookStatus::Succeeded { duration_ms }
+        | ApiHookStatus::Failed { duration_ms, .. }
+        | ApiHookStatus::Cancelled { duration_ms }
+        | ApiHookStatus::TimedOut { duration_ms } => {
+            format_duration(Duration::from_millis(*duration_ms))
+        }
+        ApiHookStatus::SpawnError { .. } => "—".to_string(),
+    }
+}
+
+fn run_status_icon(status: &ApiHookStatus, t: &ThemeColors) -> (&'static str, u32) {
+    match status {
+        ApiHookStatus::Running => ("◦", t.term_yellow),
+        ApiHookStatus::Succeeded { .. } => ("✓", t.success),
+        ApiHookStatus::Cancelled { .. } => ("■", t.text_muted),
+        ApiHookStatus::Failed { .. }
+        | ApiHookStatus::SpawnError { .. }
+        | ApiHookStatus::TimedOut { .. } => ("✗", t.error),
+    }
+}
+
+impl HookLog {
+    fn render_tabs(&self, t: &ThemeColors, cx: &mut Context<Self>) -> impl IntoElement {
+        let on_recent = matches!(self.view, HookLogView::Recent);
+        let tab = |id: &'static str, label: &'static str, active: bool| {
+            button(id, label, t)
+                .text_size(ui_text_ms(cx))
+                .when(active, |el| {
+                    el.bg(rgb(t.bg_hover)).text_color(rgb(t.text_primary))
+                })
+        };
+        h_flex()
+            .px(px(16.0))
+            .py(px(6.0))
+            .gap(px(6.0))
+            .border_b_1()
+            .border_color(rgb(t.border))
+            .child(
+                tab("hook-log-tab-recent", "Recent", on_recent)
+                    .on_click(cx.listener(|this, _, _window, cx| this.show_recent(cx))),
+            )
+            .child(
+                tab("hook-log-tab-saved", "Saved runs", !on_recent)
+                    .on_click(cx.listener(|this, _, _window, cx| this.show_saved_runs(cx))),
+            )
+            .child(div().flex_1())
+            .when(self.loading, |el| {
+                el.child(
+                    div()
+                        .text_size(ui_text_ms(cx))
+                        .text_color(rgb(t.text_muted))
+                        .child("Loading…"),
+                )
+            })
+    }
+
+    fn render_body(&self, t: &ThemeColors, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        match &self.view {
+            HookLogView::Recent if self.history.is_empty() => {
+                vec![empty_message("No hooks have been executed yet.", t, cx)]
+            }
+            HookLogView::Recent => {
+                let can_cancel = self.client.is_some();
+                self.history
+                    .iter()
+                    .enumerate()
+                    .map(|(i, exec)| render_hook_row(i, exec, can_cancel, t, cx).into_any_element())
+                    .collect()
+            }
+            HookLogView::Saved if self.saved_runs.is_empty() && !self.loading => {
+                vec![empty_message("No saved hook runs.", t, cx)]
+            }
+            HookLogView::Saved => {
+                let base = self
+                    .base_run
+                    .and_then(|id| self.saved_runs.iter().find(|run| run.id == id));
+                self.saved_runs
+                    .iter()
+                    .map(|run| render_saved_run_row(run, base, t, cx).into_any_element())
+                    .collect()
+            }
+            HookLogView::Run(run) => vec![
+                render_back_bar(&format_run_title(run), t, cx),
+                render_output_block("stdout", &run.stdout, t, cx),
+                render_output_block("stderr", &run.stderr, t, cx),
+            ],
+            HookLogView::Diff(diff) => {
+                let title = format!(
+                    "{}: run #{} → #{}",
+                    diff.run.hook_type, diff.base.id, diff.run.id
+                );
+                vec![
+                    render_back_bar(&title, t, cx),
+                    render_diff_block("stdout", &diff.stdout_diff, t, cx),
+                    render_diff_block("stderr", &diff.stderr_diff, t, cx),
+                ]
+            }
+        }
+    }
+}
+
 impl Render for HookLog {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let t = theme(cx);
@@ -104,6 +348,9 @@ impl Render for HookLog {
             window.focus(&focus_handle, cx);
         }
 
+        let body = self.render_body(&t, cx);
+        let error_message = self.error_message.clone();
+
         modal_backdrop("hook-log-backdrop", &t)
             .track_focus(&focus_handle)
             .key_context("HookLog")
@@ -128,59 +375,73 @@ impl Render for HookLog {
                         cx,
                         cx.listener(|this, _, _window, cx| this.close(cx)),
                     ))
+                    .when(self.client.is_some(), |el| {
+                        el.child(self.render_tabs(&t, cx))
+                    })
+                    .when_some(error_message, |el, message| {
+                        el.child(
+                            div()
+                                .px(px(16.0))
+                                .py(px(6.0))
+                                .text_size(ui_text_ms(cx))
+                                .text_color(rgb(t.error))
+                                .child(message),
+                        )
+                    })
                     .child(
                         v_flex()
                             .id("hook-log-list")
                             .flex_1()
                             .overflow_y_scroll()
-                            .children(if self.history.is_empty() {
-                                vec![
-                                    div()
-                                        .px(px(16.0))
-                                        .py(px(24.0))
-                                        .text_size(ui_text(13.0, cx))
-                                        .text_color(rgb(t.text_muted))
-                                        .child("No hooks have been executed yet.")
-                                        .into_any_element(),
-                                ]
-                            } else {
-                                self.history
-                                    .iter()
-                                    .enumerate()
-                                    .map(|(i, exec)| {
-                                        render_hook_row(i, exec, &t, cx).into_any_element()
-                                    })
-                                    .collect()
-                            }),
+                            .children(body),
                     )
                     .child(keyboard_hints_footer(&[("Esc", "to close")], &t)),
             )
     }
 }
 
+fn empty_message(message: &'static str, t: &ThemeColors, cx: &App) -> AnyElement {
+    div()
+        .px(px(16.0))
+        .py(px(24.0))
+        .text_size(ui_text(13.0, cx))
+        .text_color(rgb(t.text_muted))
+        .child(message)
+        .into_any_element()
+}
+
 fn render_hook_row(
     index: usize,
     exec: &HookExecution,
+    can_cancel: bool,
     t: &ThemeColors,
-    cx: &App,
+    cx: &mut Context<HookLog>,
 ) -> impl IntoElement {
     let (status_icon, status_color) = match &exec.status {
         HookStatus::Running => ("◦", t.term_yellow),
         HookStatus::Succeeded { .. } => ("✓", t.success),
         HookStatus::Failed { .. } => ("✗", t.error),
         HookStatus::SpawnError { .. } => ("✗", t.error),
+        HookStatus::Cancelled { .. } => ("■", t.text_muted),
+        HookStatus::TimedOut { .. } => ("✗", t.error),
     };
 
     let duration_str = format_elapsed(exec);
     let hook_type = exec.hook_type.to_string();
     let project_name = exec.project_name.clone();
     let command = exec.command.clone();
+    let execution_id = exec.id;
+    let cancellable = can_cancel && matches!(exec.status, HookStatus::Running);
 
     let error_detail = match &exec.status {
         HookStatus::Failed {
             stderr, exit_code, ..
         } => Some(format!("Exit {}: {}", exit_code, stderr)),
         HookStatus::SpawnError { message } => Some(message.clone()),
+        HookStatus::Cancelled { .. } => Some("Cancelled".to_string()),
+        HookStatus::TimedOut { duration } => {
+            Some(format!("Timed out after {}", format_duration(*duration)))
+        }
         _ => None,
     };
 
@@ -225,7 +486,22 @@ fn render_hook_row(
                         .font_family("monospace")
                         .text_color(rgb(t.text_secondary))
                         .child(duration_str),
-                ),
+                )
+                .when(cancellable, |el| {
+                    el.child(
+                        button(
+                            ElementId::Name(format!("hook-cancel-{}", execution_id).into()),
+                            "Cancel",
+                            t,
+                        )
+                        .text_size(ui_text_ms(cx))
+                        .on_click(cx.listener(
+                            move |this, _, _window, cx| {
+                                this.cancel_execution(execution_id, cx);
+                            },
+                        )),
+                    )
+                }),
         )
         .child(
             div()
@@ -251,4 +527,168 @@ fn render_hook_row(
         })
 }
 
+fn format_run_title(run: &ApiHookRun) -> String {
+    format!("{} #{} — {}", run.hook_type, run.id, run.project_name)
+}
+
+/// A saved run row: click opens it; the trailing button picks the diff base,
+/// or diffs against the picked base when both are runs of the same hook.
+fn render_saved_run_row(
+    run: &ApiHookRun,
+    base: Option<&ApiHookRun>,
+    t: &ThemeColors,
+    cx: &mut Context<HookLog>,
+) -> impl IntoElement {
+    let (status_icon, status_color) = run_status_icon(&run.status, t);
+    let run_id = run.id;
+    let is_base = base.is_some_and(|b| b.id == run_id);
+    let can_diff = base.is_some_and(|b| b.id != run_id && b.hook_type == run.hook_type);
+    let action_label = if is_base {
+        "Clear base"
+    } else if can_diff {
+        "Diff vs base"
+    } else {
+        "Set as base"
+    };
+
+    h_flex()
+        .id(ElementId::Name(format!("hook-run-{}", run_id).into()))
+        .px(px(16.0))
+        .py(px(8.0))
+        .gap(px(8.0))
+        .border_b_1()
+        .border_color(rgb(t.border))
+        .cursor_pointer()
+        .hover(|s| s.bg(rgb(t.bg_hover)))
+        .when(is_base, |el| el.bg(rgb(t.bg_secondary)))
+        .on_click(cx.listener(move |this, _, _window, cx| this.open_run(run_id, cx)))
+        .child(
+            div()
+                .text_color(rgb(status_color))
+                .text_size(ui_text(13.0, cx))
+                .flex_shrink_0()
+                .child(status_icon),
+        )
+        .child(
+            div()
+                .text_size(ui_text_md(cx))
+                .font_weight(FontWeight::SEMIBOLD)
+                .text_color(rgb(t.text_primary))
+                .child(format_run_title(run)),
+        )
+        .child(div().flex_1())
+        .child(
+            div()
+                .text_size(ui_text_ms(cx))
+                .font_family("monospace")
+                .text_color(rgb(t.text_secondary))
+                .child(format_run_duration(&run.status)),
+        )
+        .child(
+            button(
+                ElementId::Name(format!("hook-run-base-{}", run_id).into()),
+                action_label,
+                t,
+            )
+            .text_size(ui_text_ms(cx))
+            .on_click(cx.listener(move |this, _, _window, cx| {
+                cx.stop_propagation();
+                if can_diff {
+                    this.diff_against_base(run_id, cx);
+                } else {
+                    this.toggle_base_run(run_id, cx);
+                }
+            })),
+        )
+}
+
+fn render_back_bar(title: &str, t: &ThemeColors, cx: &mut Context<HookLog>) -> AnyElement {
+    h_flex()
+        .px(px(16.0))
+        .py(px(8.0))
+        .gap(px(8.0))
+        .border_b_1()
+        .border_color(rgb(t.border))
+        .child(
+            button("hook-log-back", "← Back", t)
+                .text_size(ui_text_ms(cx))
+                .on_click(cx.listener(|this, _, _window, cx| this.show_saved_runs(cx))),
+        )
+        .child(
+            div()
+                .text_size(ui_text_md(cx))
+                .font_weight(FontWeight::SEMIBOLD)
+                .text_color(rgb(t.text_primary))
+                .child(title.to_string()),
+        )
+        .into_any_element()
+}
+
+fn output_section(label: &'static str, t: &ThemeColors, cx: &App) -> Div {
+    v_flex().px(px(16.0)).py(px(8.0)).gap(px(2.0)).child(
+        div()
+            .text_size(ui_text_ms(cx))
+            .text_color(rgb(t.text_muted))
+            .child(label),
+    )
+}
+
+fn render_output_block(label: &'static str, output: &str, t: &ThemeColors, cx: &App) -> AnyElement {
+    let section = output_section(label, t, cx);
+    if output.is_empty() {
+        return section
+            .child(
+                div()
+                    .text_size(ui_text_ms(cx))
+                    .text_color(rgb(t.text_muted))
+                    .child("(empty)"),
+            )
+            .into_any_element();
+    }
+    section
+        .children(output.lines().map(|line| {
+            div()
+                .text_size(ui_text_ms(cx))
+                .font_family("monospace")
+                .text_color(rgb(t.text_secondary))
+                .whitespace_nowrap()
+                .child(line.to_string())
+        }))
+        .into_any_element()
+}
+
+fn render_diff_block(label: &'static str, diff: &str, t: &ThemeColors, cx: &App) -> AnyElement {
+    let section = output_section(label, t, cx);
+    if diff.is_empty() {
+        return section
+            .child(
+                div()
+                    .text_size(ui_text_ms(cx))
+                    .text_color(rgb(t.text_muted))
+                    .child("(identical)"),
+            )
+            .into_any_element();
+    }
+    section
+        .children(diff.lines().map(|line| {
+            let color =
+                if line.starts_with("+++") || line.starts_with("---") || line.starts_with("@@") {
+                    t.text_muted
+                } else if line.starts_with('+') {
+                    t.success
+                } else if line.starts_with('-') {
+                    t.error
+                } else {
+                    t.text_secondary
+                };
+            div()
+                .text_size(ui_text_ms(cx))
+                .font_family("monospace")
+                .text_color(rgb(color))
+                .whitespace_nowrap()
+                .child(line.to_string())
+        }))
+        .into_any_element()
+}
+
 okena_ui::impl_focusable!(HookLog);
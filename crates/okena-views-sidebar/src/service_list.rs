@@ -124,6 +124,7 @@ impl Sidebar {
             ports: service.ports.clone(),
             is_docker: service.is_docker,
             is_extra: false,
+            restart_trigger: service.restart_trigger.clone(),
         };
 
         okena_views_services::sidebar::render_service_item(
@@ -228,10 +229,19 @@ impl Sidebar {
             },
             // on_port_click
             {
+                let entity = entity.clone();
+                let project_id = project_id.clone();
                 let port_host = port_host.clone();
-                move |port: u16| {
-                    let url = format!("http://{}:{}", port_host, port);
-                    okena_core::process::open_url(&url);
+                move |service: String, port: u16, cx: &mut App| {
+                    let forwarded = entity.upgrade().is_some_and(|entity| {
+                        entity.update(cx, |this, cx| {
+                            this.forward_service_port(&project_id, &service, port, cx)
+                        })
+                    });
+                    if !forwarded {
+                        let url = format!("http://{}:{}", port_host, port);
+                        okena_core::process::open_url(&url);
+                    }
                 }
             },
         )
@@ -65,6 +65,11 @@ pub type SendRemoteActionFn = Box<dyn Fn(&str, ActionRequest, &mut App)>;
 /// Arguments: (conn_id, action, cx)
 pub type DispatchForConnectionFn = Box<dyn Fn(&str, ActionRequest, &mut App)>;
 
+/// Callback to forward a remote service port to localhost and open it.
+/// Arguments: (project_id, service_name, port, cx). Returns `false` when the
+/// port is already local and should be opened directly.
+pub type ForwardServicePortFn = Box<dyn Fn(&str, &str, u16, &mut App) -> bool>;
+
 /// Callback to get the server folder ID for a remote folder reorder operation.
 /// Arguments: (conn_id, prefixed_project_id, cx) -> Option<folder_id>
 pub type GetRemoteFolderFn = Box<dyn Fn(&str, &str, &App) -> Option<String>>;
@@ -190,6 +195,8 @@ pub struct Sidebar {
     pub(crate) dispatch_for_connection: Option<DispatchForConnectionFn>,
     /// Callback to get remote folder ID for reordering
     pub(crate) get_remote_folder: Option<GetRemoteFolderFn>,
+    /// Callback to forward a remote service port. See [`ForwardServicePortFn`].
+    pub(crate) forward_service_port: Option<ForwardServicePortFn>,
     /// Last computed activity-view ordering `(tier, project_id)`. Reused while
     /// the pointer is inside the sidebar (hover-freeze) so rows don't reshuffle
     /// out from under the cursor; recomputed once the pointer leaves.
@@ -281,6 +288,7 @@ impl Sidebar {
             send_remote_action: None,
             dispatch_for_connection: None,
             get_remote_folder: None,
+            forward_service_port: None,
             activity_order_cache: Vec::new(),
             activity_pointer_inside: false,
             activity_cursor_items: Vec::new(),
@@ -324,6 +332,11 @@ impl Sidebar {
         self.dispatch_for_connection = Some(f);
     }
 
+    /// Set the service port forwarding callback.
+    pub fn set_forward_service_port(&mut self, f: ForwardServicePortFn) {
+        self.forward_service_port = Some(f);
+    }
+
     /// Set the get remote folder callback.
     pub fn set_get_remote_folder(&mut self, f: GetRemoteFolderFn) {
         self.get_remote_folder = Some(f);
@@ -341,6 +354,20 @@ impl Sidebar {
         }
     }
 
+    /// Forward a service port through the forwarding callback. `false` means
+    /// the caller should open the port directly.
+    pub(crate) fn forward_service_port(
+        &self,
+        project_id: &str,
+        service_name: &str,
+        port: u16,
+        cx: &mut App,
+    ) -> bool {
+        self.forward_service_port
+            .as_ref()
+            .is_some_and(|forward| forward(project_id, service_name, port, cx))
+    }
+
     /// Dispatch a folder-scoped action to the connection that owns the folder.
     /// Folder ids are `remote:<conn>:<id>`; the dispatcher strips the prefix
     /// against the resolved connection. Falls back to the local daemon for an
@@ -702,6 +729,8 @@ pub struct SidebarServiceInfo {
     pub port_host: String,
     /// Whether this service is a Docker Compose service
     pub is_docker: bool,
+    /// File change that caused the last watch-triggered restart.
+    pub restart_trigger: Option<String>,
 }
 
 /// Hook terminal info for sidebar rendering.
@@ -46,6 +46,7 @@ impl Sidebar {
                                     inst.kind,
                                     okena_services::manager::ServiceKind::DockerCompose { .. }
                                 ),
+                                restart_trigger: inst.restart_trigger.clone(),
                             })
                             .collect();
                         (p.id.clone(), services)
@@ -78,6 +79,7 @@ impl Sidebar {
                         ports: api_svc.ports.clone(),
                         port_host: port_host.clone(),
                         is_docker: api_svc.kind == "docker_compose",
+                        restart_trigger: api_svc.restart_trigger.clone(),
                     })
                     .collect();
                 project_services.insert(project.id.clone(), services);
@@ -6,6 +6,7 @@ mod navigation;
 mod render;
 mod scrollbar;
 mod search_bar;
+mod spectators;
 pub mod url_detector;
 mod zoom;
 
@@ -125,6 +125,7 @@ impl<D: ActionDispatch + Send + Sync> Render for TerminalPane<D> {
         } else {
             None
         };
+        let spectator_badge = self.render_spectator_badge(cx);
 
         div()
             .id(format!("terminal-pane-main-{}", id_suffix))
@@ -340,7 +341,8 @@ impl<D: ActionDispatch + Send + Sync> Render for TerminalPane<D> {
                                     .border_1()
                                     .border_color(border_color),
                             )
-                        }),
+                        })
+                        .children(spectator_badge),
                 )
             })
             .when(search_active, |el: Stateful<Div>| {
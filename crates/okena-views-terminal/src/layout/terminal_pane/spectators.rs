@@ -0,0 +1,99 @@
+//! Presence badge for panes watched through view-only share links.
+
+use crate::ActionDispatch;
+use gpui::*;
+use gpui_component::h_flex;
+use gpui_component::tooltip::Tooltip;
+use okena_files::theme::theme;
+use okena_ui::tokens::ui_text_ms;
+use okena_workspace::requests::{OverlayRequest, ProjectOverlay, ProjectOverlayKind};
+
+use super::TerminalPane;
+
+impl<D: ActionDispatch + Send + Sync> TerminalPane<D> {
+    /// Shares covering this pane (its own or its project's) and how many
+    /// viewers they have between them.
+    fn watching_shares(&self, cx: &Context<Self>) -> (Vec<String>, u32) {
+        let Some(terminal_id) = self.terminal_id.as_deref() else {
+            return (Vec::new(), 0);
+        };
+        let ws = self.workspace.read(cx);
+        let Some(snapshot) = ws.remote_snapshot(&self.project_id) else {
+            return (Vec::new(), 0);
+        };
+        let shares: Vec<_> = snapshot
+            .shares
+            .iter()
+            .filter(|share| {
+                share
+                    .terminal_id
+                    .as_deref()
+                    .is_none_or(|id| id == terminal_id)
+            })
+            .collect();
+        let viewers = shares.iter().map(|share| share.viewers).sum();
+        let ids = shares.iter().map(|share| share.id.clone()).collect();
+        (ids, viewers)
+    }
+
+    /// "N watching" pill in the pane's top-right corner while spectators are
+    /// connected. Clicking it revokes the shares.
+    pub(super) fn render_spectator_badge(&self, cx: &Context<Self>) -> Option<AnyElement> {
+        let (share_ids, viewers) = self.watching_shares(cx);
+        if viewers == 0 {
+            return None;
+        }
+        let t = theme(cx);
+        let request_broker = self.request_broker.clone();
+        let project_id = self.project_id.clone();
+
+        Some(
+            div()
+                .id(format!("spectator-badge-{}", self.id_suffix()))
+                .absolute()
+                .top(px(6.0))
+                .right(px(12.0))
+                .child(
+                    h_flex()
+                        .gap(px(4.0))
+                        .px(px(6.0))
+                        .py(px(2.0))
+                        .rounded(px(4.0))
+                        .bg(rgb(t.bg_secondary))
+                        .border_1()
+                        .border_color(rgb(t.border))
+                        .text_size(ui_text_ms(cx))
+                        .text_color(rgb(t.text_secondary))
+                        .child(
+                            svg()
+                                .path("icons/eye.svg")
+                                .size(px(12.0))
+                                .text_color(rgb(t.text_secondary)),
+                        )
+                        .child(format!("{viewers} watching"))
+                        .child(
+                            svg()
+                                .path("icons/close.svg")
+                                .size(px(10.0))
+                                .text_color(rgb(t.text_muted)),
+                        ),
+                )
+                .cursor_pointer()
+                .tooltip(|window, cx| Tooltip::new("Stop sharing").build(window, cx))
+                .on_click(move |_, _window, cx| {
+                    request_broker.update(cx, |broker, cx| {
+                        broker.push_overlay_request(
+                            OverlayRequest::Project(ProjectOverlay {
+                                project_id: project_id.clone(),
+                                kind: ProjectOverlayKind::RevokeShares {
+                                    share_ids: share_ids.clone(),
+                                },
+                            }),
+                            cx,
+                        );
+                    });
+                })
+                .into_any_element(),
+        )
+    }
+}
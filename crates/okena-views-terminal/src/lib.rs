@@ -74,6 +74,21 @@ pub trait ActionDispatch: Clone + 'static {
         let _ = (terminal_id, files, cx);
     }
 
+    /// Forward a service port of a remote daemon to localhost and open it.
+    ///
+    /// Returns `false` when the daemon runs on this machine, so the caller
+    /// opens the port directly. Default: `false`.
+    fn forward_service_port(
+        &self,
+        project_id: &str,
+        service_name: &str,
+        port: u16,
+        cx: &mut gpui::App,
+    ) -> bool {
+        let _ = (project_id, service_name, port, cx);
+        false
+    }
+
     /// Export a terminal's scrollback buffer to a client-side temp file and
     /// return its path (the caller copies it to the clipboard). The capture
     /// runs on the server; remote dispatchers fetch the content over HTTP and
@@ -42,6 +42,12 @@ pub enum TerminalContextMenuEvent {
         project_id: String,
         terminal_id: String,
     },
+    /// Mint a view-only share link to this terminal, or to its whole
+    /// project when `terminal_id` is `None`.
+    ShareView {
+        project_id: String,
+        terminal_id: Option<String>,
+    },
     OpenLink {
         url: String,
     },
@@ -249,6 +255,41 @@ impl Render for TerminalContextMenu {
                             )),
                         )
                         .child(menu_separator(&t))
+                        // Share View-Only Link (this terminal)
+                        .child(
+                            menu_item(
+                                "ctx-share-terminal",
+                                "icons/eye.svg",
+                                "Share View-Only Link",
+                                &t,
+                            )
+                            .on_click(cx.listener(
+                                |this, _, _window, cx| {
+                                    cx.emit(TerminalContextMenuEvent::ShareView {
+                                        project_id: this.project_id.clone(),
+                                        terminal_id: Some(this.terminal_id.clone()),
+                                    });
+                                },
+                            )),
+                        )
+                        // Share Project View-Only Link
+                        .child(
+                            menu_item(
+                                "ctx-share-project",
+                                "icons/eye.svg",
+                                "Share Project View-Only Link",
+                                &t,
+                            )
+                            .on_click(cx.listener(
+                                |this, _, _window, cx| {
+                                    cx.emit(TerminalContextMenuEvent::ShareView {
+                                        project_id: this.project_id.clone(),
+                                        terminal_id: None,
+                                    });
+                                },
+                            )),
+                        )
+                        .child(menu_separator(&t))
                         // Split Horizontal
                         .child(
                             menu_item(
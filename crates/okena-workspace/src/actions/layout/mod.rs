@@ -12,9 +12,97 @@ mod move_ops;
 mod split;
 mod tabs;
 
-use crate::state::Workspace;
+use crate::context::WorkspaceCx;
+use crate::state::{LayoutNode, Workspace};
+use std::collections::{HashMap, HashSet};
+
+/// One project's layout and per-terminal metadata, taken by
+/// [`Workspace::layout_snapshot`] to undo a run of layout changes.
+#[derive(Clone, Debug)]
+pub struct LayoutSnapshot {
+    project_id: String,
+    layout: Option<LayoutNode>,
+    terminal_names: HashMap<String, String>,
+    hidden_terminals: HashMap<String, bool>,
+}
+
+/// What [`Workspace::restore_layouts`] changed.
+#[derive(Debug, Default)]
+pub struct LayoutRestore {
+    /// Terminals the restored layouts dropped (created since the snapshot),
+    /// whose PTYs the caller should kill.
+    pub dropped: Vec<String>,
+    /// Projects whose layout was put back.
+    pub projects: Vec<String>,
+}
 
 impl Workspace {
+    /// Snapshot every project's layout for [`Workspace::restore_layouts`].
+    pub fn layout_snapshot(&self) -> Vec<LayoutSnapshot> {
+        self.data()
+            .projects
+            .iter()
+            .map(|project| LayoutSnapshot {
+                project_id: project.id.clone(),
+                layout: project.layout.clone(),
+                terminal_names: project.terminal_names.clone(),
+                hidden_terminals: project.hidden_terminals.clone(),
+            })
+            .collect()
+    }
+
+    /// Put back the layouts in `snapshot`. Projects added since keep theirs,
+    /// and projects whose layout still matches the snapshot are left alone.
+    ///
+    /// In the restored projects, slots whose terminal `is_alive` no longer
+    /// reports are reset to uninitialized so the caller can respawn them.
+    pub fn restore_layouts(
+        &mut self,
+        snapshot: Vec<LayoutSnapshot>,
+        is_alive: impl Fn(&str) -> bool,
+        cx: &mut impl WorkspaceCx,
+    ) -> LayoutRestore {
+        let mut restore = LayoutRestore::default();
+        for saved in snapshot {
+            let Some(project) = self.project_mut(&saved.project_id) else {
+                continue;
+            };
+            if project.layout == saved.layout
+                && project.terminal_names == saved.terminal_names
+                && project.hidden_terminals == saved.hidden_terminals
+            {
+                continue;
+            }
+            let saved_ids: HashSet<String> = saved
+                .layout
+                .as_ref()
+                .map(|l| l.collect_terminal_ids().into_iter().collect())
+                .unwrap_or_default();
+            if let Some(layout) = &project.layout {
+                restore.dropped.extend(
+                    layout
+                        .collect_terminal_ids()
+                        .into_iter()
+                        .filter(|id| !saved_ids.contains(id)),
+                );
+            }
+            let alive: HashSet<&str> = saved_ids
+                .iter()
+                .map(String::as_str)
+                .filter(|id| is_alive(id))
+                .collect();
+            project.layout = saved.layout;
+            if let Some(layout) = &mut project.layout {
+                layout.clear_terminal_ids_except(&alive);
+            }
+            project.terminal_names = saved.terminal_names;
+            project.hidden_terminals = saved.hidden_terminals;
+            restore.projects.push(saved.project_id);
+        }
+        self.notify_data(cx);
+        restore
+    }
+
     /// Remove terminal_names/hidden_terminals entries that are no longer in the layout.
     /// Returns the orphaned terminal IDs (for PTY cleanup by callers).
     pub(super) fn cleanup_orphaned_metadata(&mut self, project_id: &str) -> Vec<String> {
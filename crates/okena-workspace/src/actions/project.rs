@@ -8,6 +8,7 @@ use crate::hooks;
 use crate::persistence::HooksConfig;
 use crate::state::{LayoutNode, ProjectData, WindowId, Workspace};
 use okena_core::theme::FolderColor;
+use okena_terminal::shell_config::ShellType;
 use std::collections::{HashMap, HashSet};
 
 /// A fresh, unparented project row — the one place the full `ProjectData`
@@ -548,18 +549,34 @@ impl Workspace {
         focus_manager: &mut FocusManager,
         project_id: &str,
         cx: &mut impl WorkspaceCx,
+    ) {
+        self.add_terminal_with_shell(focus_manager, project_id, ShellType::Default, cx);
+    }
+
+    /// Add a new terminal using a specific shell (e.g. a container shell) to a
+    /// project and focus it.
+    pub fn add_terminal_with_shell(
+        &mut self,
+        focus_manager: &mut FocusManager,
+        project_id: &str,
+        shell: ShellType,
+        cx: &mut impl WorkspaceCx,
     ) {
         if let Some(project) = self.project_mut(project_id) {
+            let mut new_node = LayoutNode::new_terminal();
+            if let LayoutNode::Terminal { shell_type, .. } = &mut new_node {
+                *shell_type = shell;
+            }
             if let Some(ref old_layout) = project.layout {
                 let old_layout = old_layout.clone();
                 project.layout = Some(LayoutNode::Split {
                     direction: crate::state::SplitDirection::Vertical,
                     sizes: vec![50.0, 50.0],
-                    children: vec![old_layout, LayoutNode::new_terminal()],
+                    children: vec![old_layout, new_node],
                 });
             } else {
                 // Project has no layout - create one with a terminal
-                project.layout = Some(LayoutNode::new_terminal());
+                project.layout = Some(new_node);
             }
             self.notify_data(cx);
         }
@@ -140,6 +140,21 @@ pub fn apply_remote_snapshot(
                     .collect();
                 let remote_host = Some(snap.config.host.clone());
                 let remote_git_status = api_project.git_status.clone();
+                // View-only share links on this project, prefixed like the
+                // terminals they point at.
+                let remote_shares: Vec<okena_core::api::ApiShare> = state
+                    .shares
+                    .iter()
+                    .filter(|share| share.project_id == api_project.id)
+                    .map(|share| {
+                        let mut share = share.clone();
+                        share.project_id = prefixed_id.clone();
+                        share.terminal_id = share
+                            .terminal_id
+                            .map(|tid| format!("remote:{}:{}", conn_id, tid));
+                        share
+                    })
+                    .collect();
 
                 // Daemon-owned per-project data surfaced for rendering: pin
                 // marker, activity-sort timestamp, shell-picker selection, and
@@ -268,6 +283,7 @@ pub fn apply_remote_snapshot(
                 snapshot.services = remote_services;
                 snapshot.host = remote_host;
                 snapshot.git_status = remote_git_status;
+                snapshot.shares = remote_shares;
             }
 
             // Sync remote folders and project_order into workspace
@@ -533,6 +549,7 @@ mod tests {
             tls: false,
             pinned_cert_sha256: None,
             local_endpoint: None,
+            proxy_command: None,
         }
     }
 
@@ -585,6 +602,7 @@ mod tests {
             folders,
             windows: vec![],
             hooks: Vec::new(),
+            shares: Vec::new(),
         }
     }
 
@@ -2,7 +2,7 @@
 
 use std::collections::{HashMap, HashSet};
 
-use okena_core::api::{ApiGitStatus, ApiServiceInfo};
+use okena_core::api::{ApiGitStatus, ApiServiceInfo, ApiShare};
 use okena_layout::LayoutNode;
 use okena_state::WindowId;
 
@@ -18,6 +18,8 @@ pub struct RemoteProjectSnapshot {
     pub host: Option<String>,
     /// Last-known git status.
     pub git_status: Option<ApiGitStatus>,
+    /// Live view-only share links on this project (daemon-owned projects only).
+    pub shares: Vec<ApiShare>,
 }
 
 /// Transient remote-sync state that lives alongside persistent workspace data.
@@ -90,6 +90,10 @@ pub enum ProjectOverlayKind {
     WorktreeList {
         position: gpui::Point<gpui::Pixels>,
     },
+    /// End these view-only share links on the project.
+    RevokeShares {
+        share_ids: Vec<String>,
+    },
 }
 
 /// Folder-scoped overlay request. Carries a `folder_id` once;
@@ -840,6 +840,7 @@ mod tests {
                 on_create: Some("echo create".into()),
                 on_close: Some("echo exit".into()),
                 shell_wrapper: Some("devcontainer exec -- {shell}".into()),
+                ..Default::default()
             },
             worktree: WorktreeHooks {
                 on_create: Some("npm install".into()),
@@ -851,6 +852,7 @@ mod tests {
                 on_rebase_conflict: Some("terminal: claude -p \"fix\"".into()),
                 on_dirty_close: Some("echo dirty".into()),
             },
+            ..Default::default()
         };
         let json = serde_json::to_string(&config).unwrap();
         let deserialized: HooksConfig = serde_json::from_str(&json).unwrap();
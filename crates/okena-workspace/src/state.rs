@@ -2382,6 +2382,73 @@ mod workspace_tests {
         }
     }
 
+    #[test]
+    fn restore_layouts_undoes_splits_and_reports_new_terminals() {
+        let mut workspace = Workspace::new(make_workspace_data(
+            vec![make_project("p1"), make_project("p2")],
+            vec!["p1", "p2"],
+        ));
+        let snapshot = workspace.layout_snapshot();
+
+        let project = workspace.project_mut("p1").unwrap();
+        project.layout = Some(LayoutNode::Split {
+            direction: SplitDirection::Vertical,
+            sizes: vec![50.0, 50.0],
+            children: vec![
+                project.layout.take().unwrap(),
+                LayoutNode::Terminal {
+                    terminal_id: Some("new".into()),
+                    minimized: false,
+                    detached: false,
+                    shell_type: ShellType::Default,
+                    zoom_level: 1.0,
+                },
+            ],
+        });
+        project.terminal_names.insert("new".into(), "dev".into());
+
+        // A step closed p2's only terminal, so term_p2 lost its PTY.
+        workspace.project_mut("p2").unwrap().layout = None;
+        let mut cx = RecordingCx::default();
+        let restore = workspace.restore_layouts(snapshot, |id| id != "term_p2", &mut cx);
+        assert_eq!(restore.dropped, vec!["new".to_string()]);
+        assert_eq!(restore.projects, vec!["p1".to_string(), "p2".to_string()]);
+        let p1 = workspace.project("p1").unwrap();
+        assert!(matches!(
+            &p1.layout,
+            Some(LayoutNode::Terminal { terminal_id: Some(id), .. }) if id == "term_p1"
+        ));
+        assert!(p1.terminal_names.is_empty());
+        assert!(matches!(
+            &workspace.project("p2").unwrap().layout,
+            Some(LayoutNode::Terminal {
+                terminal_id: None,
+                ..
+            })
+        ));
+        assert_eq!(cx.notifications, 1);
+    }
+
+    #[test]
+    fn restore_layouts_leaves_untouched_projects_alone() {
+        let mut workspace = Workspace::new(make_workspace_data(
+            vec![make_project("p1"), make_project("p2")],
+            vec!["p1", "p2"],
+        ));
+        let snapshot = workspace.layout_snapshot();
+        workspace.project_mut("p1").unwrap().layout = None;
+
+        // term_p2 died on its own; the batch never touched p2.
+        let mut cx = RecordingCx::default();
+        let restore = workspace.restore_layouts(snapshot, |id| id != "term_p2", &mut cx);
+        assert!(restore.dropped.is_empty());
+        assert_eq!(restore.projects, vec!["p1".to_string()]);
+        assert!(matches!(
+            &workspace.project("p2").unwrap().layout,
+            Some(LayoutNode::Terminal { terminal_id: Some(id), .. }) if id == "term_p2"
+        ));
+    }
+
     #[test]
     fn orphaned_worktree_close_aborts_atomically_and_idempotently() {
         let mut project = make_project("wt1");
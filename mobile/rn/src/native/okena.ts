@@ -90,6 +90,9 @@ export type SpecialKey =
 /** Diff mode for the git diff / file-contents calls. */
 export type DiffMode = 'working_tree' | 'staged';
 
+/** What `uploadFile` does when the destination exists. */
+export type UploadConflict = 'fail' | 'rename' | 'overwrite';
+
 /** Split direction for `splitTerminal`. */
 export type SplitDirection = 'horizontal' | 'vertical';
 
@@ -392,6 +395,21 @@ export interface OkenaNative {
     newIndex: number,
   ): Promise<void>;
 
+  // ── files (async — returns a JSON string) ────────────────────────────────
+
+  /**
+   * Stream a local file into a project. `relativePath` is the destination
+   * under the project root. Resolves to the JSON `{path, name, size}` of the
+   * written file.
+   */
+  uploadFile(
+    connId: ConnId,
+    projectId: ProjectId,
+    localPath: string,
+    relativePath: string,
+    conflict: UploadConflict,
+  ): Promise<string>;
+
   // ── layout actions (state.rs, async) ─────────────────────────────────────
 
   updateSplitSizes(
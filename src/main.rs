@@ -342,6 +342,11 @@ fn run_headless(listen_addr: Option<IpAddr>) -> anyhow::Result<()> {
 }
 
 fn main() {
+    // A timed hook step calls back into this binary; run it and leave.
+    if let Some(code) = okena_daemon_core::hook_step::run_from_args() {
+        std::process::exit(code);
+    }
+
     if let Err(error) = okena_remote_server::local::remember_current_executable() {
         eprintln!("Warning: failed to remember executable path: {error}");
     }
@@ -3,52 +3,62 @@ import { appReducer, initialState, AppContext, TerminalRegistry } from "./state/
 import { WsManager, type WsStatus } from "./api/websocket";
 import type { WsOutbound } from "./api/types";
 import { getState, refresh, AuthError } from "./api/client";
-import { loadToken, clearToken, tokenTtlSecs } from "./auth/token";
+import { loadToken, clearToken, tokenTtlSecs, loadShareLink } from "./auth/token";
 import { PairingScreen } from "./components/PairingScreen";
+import { SpectatorView } from "./components/SpectatorView";
 import { WorkspaceLayout } from "./components/WorkspaceLayout";
 
 export function App() {
   const [state, dispatch] = useReducer(appReducer, initialState);
   const [authed, setAuthed] = useState<boolean | null>(null); // null = checking
+  // Set when this tab was opened from a view-only share link.
+  const [share] = useState(loadShareLink);
   const wsRef = useRef<WsManager>(null!);
   const registryRef = useRef<TerminalRegistry>(null!);
 
   if (!wsRef.current) wsRef.current = new WsManager();
   if (!registryRef.current) registryRef.current = new TerminalRegistry();
 
+  // A spectator's credential can't be re-paired or refreshed; once it stops
+  // working the tab just says so.
+  const signOut = useCallback(() => {
+    if (!share) clearToken();
+    setAuthed(false);
+  }, [share]);
+
   const fetchState = useCallback(async () => {
     try {
       const ws = await getState();
       dispatch({ type: "set_workspace", workspace: ws });
       // Auto-select focused project or first project
       if (!state.selectedProjectId) {
-        const projectId = ws.focused_project_id ?? ws.projects[0]?.id ?? null;
+        const projectId = share?.projectId ?? ws.focused_project_id ?? ws.projects[0]?.id ?? null;
         if (projectId) dispatch({ type: "select_project", projectId });
       }
     } catch (e) {
-      if (e instanceof AuthError) {
-        clearToken();
-        setAuthed(false);
-      }
+      if (e instanceof AuthError) signOut();
     }
-  }, [state.selectedProjectId]);
+  }, [state.selectedProjectId, share, signOut]);
 
   const handleWsMessage = useCallback(
     (msg: WsOutbound) => {
       switch (msg.type) {
         case "subscribed":
           dispatch({ type: "set_stream_mappings", mappings: msg.mappings });
+          if (msg.sizes) dispatch({ type: "set_terminal_sizes", sizes: msg.sizes });
+          break;
+        case "terminal_resized":
+          dispatch({ type: "set_terminal_sizes", sizes: { [msg.terminal_id]: [msg.cols, msg.rows] } });
           break;
         case "state_changed":
           fetchState();
           break;
         case "auth_failed":
-          clearToken();
-          setAuthed(false);
+          signOut();
           break;
       }
     },
-    [fetchState],
+    [fetchState, share, signOut],
   );
 
   // Keep a ref to the latest handleWsMessage so the WS effect doesn't
@@ -67,17 +77,17 @@ export function App() {
     getState()
       .then((ws) => {
         dispatch({ type: "set_workspace", workspace: ws });
-        const projectId = ws.focused_project_id ?? ws.projects[0]?.id ?? null;
+        const projectId = share?.projectId ?? ws.focused_project_id ?? ws.projects[0]?.id ?? null;
         if (projectId) dispatch({ type: "select_project", projectId });
         setAuthed(true);
       })
       .catch((e) => {
-        if (e instanceof AuthError) {
+        if (e instanceof AuthError && !share) {
           clearToken();
         }
         setAuthed(false);
       });
-  }, []);
+  }, [share]);
 
   // Connect WS when authed — use ref for handler to avoid re-running on handler changes
   useEffect(() => {
@@ -96,7 +106,7 @@ export function App() {
   // Token refresh scheduler
   const [refreshCounter, setRefreshCounter] = useState(0);
   useEffect(() => {
-    if (!authed) return;
+    if (!authed || share) return;
     const ttl = tokenTtlSecs();
     if (ttl <= 0) return;
 
@@ -111,7 +121,7 @@ export function App() {
       setRefreshCounter((c) => c + 1);
     }, refreshIn);
     return () => clearTimeout(timer);
-  }, [authed, refreshCounter]);
+  }, [authed, share, refreshCounter]);
 
   const handlePaired = useCallback(() => {
     setAuthed(true);
@@ -126,6 +136,14 @@ export function App() {
     );
   }
 
+  if (!authed && share) {
+    return (
+      <div className="flex items-center justify-center h-screen">
+        <div className="text-zinc-500">This share link has expired or was revoked.</div>
+      </div>
+    );
+  }
+
   if (!authed) {
     return <PairingScreen onPaired={handlePaired} />;
   }
@@ -138,9 +156,10 @@ export function App() {
         ws: wsRef.current,
         registry: registryRef.current,
         handleWsMessage,
+        viewOnly: share != null,
       }}
     >
-      <WorkspaceLayout />
+      {share ? <SpectatorView share={share} /> : <WorkspaceLayout />}
     </AppContext.Provider>
   );
 }
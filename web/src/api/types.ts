@@ -16,6 +16,17 @@ export interface StateResponse {
   project_order?: string[];
   folders?: ApiFolder[];
   windows?: ApiWindow[];
+  shares?: ApiShare[];
+}
+
+/** A live view-only share link (only admin tokens see these). */
+export interface ApiShare {
+  id: string;
+  project_id: string;
+  terminal_id?: string;
+  /** Unix seconds */
+  expires_at: number;
+  viewers: number;
 }
 
 export interface ApiProject {
@@ -154,6 +165,7 @@ export interface ApiServiceInfo {
   exit_code?: number | null;
   kind?: string;
   is_extra?: boolean;
+  restart_trigger?: string | null;
 }
 
 export interface ApiWorktreeMetadata {
@@ -184,6 +196,20 @@ export interface ApiTerminalHooks {
   on_create?: string | null;
   on_close?: string | null;
   shell_wrapper?: string | null;
+  on_command_finished?: string | null;
+  on_bell?: string | null;
+  on_notification?: string | null;
+}
+
+export interface ApiServiceHooks {
+  on_crash?: string | null;
+  on_ready?: string | null;
+}
+
+export interface ApiGitHooks {
+  on_branch_change?: string | null;
+  on_ci_status_change?: string | null;
+  on_pr_state_change?: string | null;
 }
 
 export interface ApiWorktreeHooks {
@@ -197,10 +223,36 @@ export interface ApiWorktreeHooks {
   on_dirty_close?: string | null;
 }
 
+export interface ApiHookStep {
+  run: string;
+  when?: {
+    branch?: string | string[];
+    os?: string | string[];
+    exists?: string;
+  };
+  continue_on_error?: boolean;
+  timeout?: number;
+  terminal?: boolean;
+}
+
+/** A step list replaces inherited steps; the object form can append to them. */
+export type ApiHookSteps =
+  | (string | ApiHookStep)[]
+  | {
+      merge: "append" | "replace";
+      /** Seconds before the whole hook is killed. */
+      timeout?: number;
+      steps: (string | ApiHookStep)[];
+    };
+
 export interface ApiHooksConfig {
   project?: ApiProjectHooks;
   terminal?: ApiTerminalHooks;
   worktree?: ApiWorktreeHooks;
+  service?: ApiServiceHooks;
+  git?: ApiGitHooks;
+  /** Step-list hooks keyed by `group.hook`, e.g. `worktree.on_create`. */
+  steps?: Record<string, ApiHookSteps>;
 }
 
 export interface ApiToastAction {
@@ -247,6 +299,12 @@ export interface ApiFullscreen {
   terminal_id: string;
 }
 
+// serde(tag = "kind", rename_all = "snake_case")
+export type TerminalPlacement =
+  | { kind: "split"; terminal_id: string; direction: SplitDirection }
+  | { kind: "tab"; terminal_id: string }
+  | { kind: "detached" };
+
 // serde(tag = "action", rename_all = "snake_case")
 export type ActionRequest =
   | { action: "send_text"; terminal_id: string; text: string }
@@ -259,10 +317,29 @@ export type ActionRequest =
   | { action: "close_terminal_now"; terminal_id: string }
   | { action: "focus_terminal"; project_id: string; terminal_id: string; window?: string | null }
   | { action: "record_project_activity"; project_id: string }
-  | { action: "read_content"; terminal_id: string }
+  | {
+      action: "read_content";
+      terminal_id: string;
+      lines?: number;
+      all?: boolean;
+      ansi?: boolean;
+      since?: number;
+      strip_prompt?: boolean;
+    }
   | { action: "export_buffer"; terminal_id: string }
+  | { action: "exec_in_terminal"; terminal_id: string; command: string; mirror?: boolean; timeout_ms?: number }
   | { action: "resize"; terminal_id: string; cols: number; rows: number }
-  | { action: "create_terminal"; project_id: string }
+  | {
+      action: "create_terminal";
+      project_id: string;
+      shell?: ShellType;
+      cwd?: string;
+      command?: string;
+      env?: Record<string, string>;
+      name?: string;
+      minimized?: boolean;
+      placement?: TerminalPlacement;
+    }
   | { action: "update_split_sizes"; project_id: string; path: number[]; sizes: number[] }
   | { action: "toggle_minimized"; project_id: string; terminal_id: string }
   | { action: "set_fullscreen"; project_id: string; terminal_id: string | null; window?: string | null }
@@ -314,6 +391,9 @@ export type ActionRequest =
   | { action: "start_service"; project_id: string; service_name: string }
   | { action: "stop_service"; project_id: string; service_name: string }
   | { action: "restart_service"; project_id: string; service_name: string }
+  | { action: "rebuild_service"; project_id: string; service_name: string }
+  | { action: "pull_service"; project_id: string; service_name: string }
+  | { action: "exec_service_shell"; project_id: string; service_name: string; shell?: string | null }
   | { action: "start_all_services"; project_id: string }
   | { action: "stop_all_services"; project_id: string }
   | { action: "reload_services"; project_id: string }
@@ -321,6 +401,10 @@ export type ActionRequest =
   | { action: "add_discovered_worktree"; parent_project_id: string; worktree_path: string; branch: string }
   | { action: "rerun_hook"; project_id: string; terminal_id: string }
   | { action: "dismiss_hook"; project_id: string; terminal_id: string }
+  | { action: "cancel_hook"; execution_id: number }
+  | { action: "list_hook_runs"; hook_type?: string; limit?: number }
+  | { action: "get_hook_run"; run_id: number }
+  | { action: "diff_hook_runs"; base_run_id: number; run_id: number }
   | { action: "list_files"; project_id: string; show_ignored?: boolean }
   | { action: "list_directory"; project_id: string; relative_path?: string; show_ignored?: boolean }
   | { action: "read_file"; project_id: string; relative_path: string }
@@ -383,6 +467,19 @@ export type ActionRequest =
   | { action: "list_actions" }
   | { action: "invoke_action"; action_name: string; window?: string | null };
 
+/** Any step field may be `{ "$ref": "N.path" }` to use a value from step N's result. */
+export interface ActionBatchRequest {
+  actions: JsonValue[];
+  rollback?: boolean;
+}
+
+export interface ApiBatchResult {
+  results: JsonValue[];
+  failed_step?: number;
+  error?: string;
+  rolled_back?: boolean;
+}
+
 export interface PairRequest {
   code: string;
 }
@@ -400,17 +497,21 @@ export interface ErrorResponse {
 
 // serde(tag = "type", rename_all = "snake_case")
 export type WsInbound =
-  | { type: "auth"; token: string }
+  | { type: "auth"; token: string; compression?: WsCompression[] }
   | { type: "subscribe"; terminal_ids: string[] }
   | { type: "unsubscribe"; terminal_ids: string[] }
   | { type: "send_text"; terminal_id: string; text: string }
   | { type: "send_special_key"; terminal_id: string; key: SpecialKey }
   | { type: "resize"; terminal_id: string; cols: number; rows: number }
+  | { type: "set_output_rate"; terminal_ids: string[]; interval_ms: number }
   | { type: "ping" };
 
+// Binary frame compression offered in `auth`; the browser client sends none.
+export type WsCompression = "deflate";
+
 // serde(tag = "type", rename_all = "snake_case")
 export type WsOutbound =
-  | { type: "auth_ok" }
+  | { type: "auth_ok"; compression?: WsCompression | null; output_rate?: boolean }
   | { type: "auth_failed"; error: string }
   | { type: "subscribed"; mappings: Record<string, number>; sizes?: Record<string, [number, number]> }
   | { type: "state_changed"; state_version: number }
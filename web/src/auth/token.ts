@@ -1,5 +1,32 @@
 const TOKEN_KEY = "okena_token";
 const EXPIRY_KEY = "okena_token_expiry";
+const SHARE_KEY = "okena_share";
+
+/** A view-only share link opened in this tab (`/#share=<token>&project=..`). */
+export interface ShareLink {
+  token: string;
+  projectId: string;
+  terminalId: string | null;
+}
+
+/**
+ * The share link this tab was opened with, if any. The first call moves it
+ * from the URL fragment into sessionStorage so the token doesn't linger in
+ * the address bar or history; later calls (and reloads) read it back.
+ */
+export function loadShareLink(): ShareLink | null {
+  const params = new URLSearchParams(window.location.hash.slice(1));
+  const token = params.get("share");
+  const projectId = params.get("project");
+  if (token && projectId) {
+    const link: ShareLink = { token, projectId, terminalId: params.get("terminal") };
+    sessionStorage.setItem(SHARE_KEY, JSON.stringify(link));
+    history.replaceState(null, "", window.location.pathname + window.location.search);
+    return link;
+  }
+  const stored = sessionStorage.getItem(SHARE_KEY);
+  return stored ? (JSON.parse(stored) as ShareLink) : null;
+}
 
 export function saveToken(token: string, expiresIn: number): void {
   const expiryMs = Date.now() + expiresIn * 1000;
@@ -8,6 +35,9 @@ export function saveToken(token: string, expiresIn: number): void {
 }
 
 export function loadToken(): string | null {
+  const share = sessionStorage.getItem(SHARE_KEY);
+  if (share) return (JSON.parse(share) as ShareLink).token;
+
   const token = localStorage.getItem(TOKEN_KEY);
   const expiry = localStorage.getItem(EXPIRY_KEY);
   if (!token || !expiry) return null;
@@ -243,7 +243,13 @@ function ServiceRow({
         className="min-w-0 flex-1 truncate text-left hover:text-[var(--ok-text-secondary)]"
         onClick={() => service.terminal_id && onOpenTerminal(service.terminal_id)}
         disabled={!service.terminal_id}
-        title={service.terminal_id ? "Open service terminal" : undefined}
+        title={
+          service.restart_trigger
+            ? `Restarted due to change in ${service.restart_trigger}`
+            : service.terminal_id
+              ? "Open service terminal"
+              : undefined
+        }
       >
         <span className="text-[var(--ok-text-secondary)]">{service.name}</span>
         <span className="ml-1 text-[var(--ok-text-muted)]">{status}{ports}{crash}</span>
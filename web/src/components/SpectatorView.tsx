@@ -0,0 +1,51 @@
+import type { ShareLink } from "../auth/token";
+import { useApp } from "../state/store";
+import { TerminalArea } from "./TerminalArea";
+import { TerminalPane } from "./TerminalPane";
+
+/** What a share link opens: one terminal, or a whole project, view only. */
+export function SpectatorView({ share }: { share: ShareLink }) {
+  const { state } = useApp();
+  const project = state.workspace?.projects.find((p) => p.id === share.projectId);
+
+  let body;
+  if (!state.workspace) {
+    body = <Placeholder label="Loading..." />;
+  } else if (!project) {
+    body = <Placeholder label="The shared project is no longer available" />;
+  } else if (share.terminalId) {
+    body = (
+      <TerminalPane
+        terminalId={share.terminalId}
+        name={project.terminal_names[share.terminalId]}
+        projectId={project.id}
+        path={[]}
+        hideSplitActions
+      />
+    );
+  } else if (project.layout) {
+    body = <TerminalArea layout={project.layout} project={project} />;
+  } else {
+    body = <Placeholder label="No terminals in this project" />;
+  }
+
+  return (
+    <div className="app-shell flex h-screen flex-col">
+      <header className="project-header flex min-h-[36px] flex-shrink-0 items-center gap-2 border-b px-3">
+        <h2 className="truncate text-[13px] font-bold text-[var(--ok-text)]">
+          {project?.name ?? "Shared view"}
+        </h2>
+        <span className="rounded-[3px] border border-[var(--ok-border)] px-1 text-[10px] text-[var(--ok-text-muted)]">
+          view only
+        </span>
+      </header>
+      <main className="min-h-0 flex-1">{body}</main>
+    </div>
+  );
+}
+
+function Placeholder({ label }: { label: string }) {
+  return (
+    <div className="flex items-center justify-center h-full text-zinc-600 text-sm">{label}</div>
+  );
+}
@@ -1,6 +1,7 @@
 import { useCallback, useRef, useState } from "react";
 import type { ApiLayoutNode, ApiProject, SplitDirection } from "../api/types";
 import { postAction } from "../api/client";
+import { useApp } from "../state/store";
 import { LayoutRenderer } from "./TerminalArea";
 
 const MIN_PERCENT = 5;
@@ -18,6 +19,7 @@ export function SplitLayout({
   project: ApiProject;
   path: number[];
 }) {
+  const { viewOnly } = useApp();
   const isHorizontalSplit = direction === "horizontal";
   const containerRef = useRef<HTMLDivElement>(null);
   const [localSizes, setLocalSizes] = useState<number[] | null>(null);
@@ -130,7 +132,7 @@ export function SplitLayout({
               data-active={activeDivider === i - 1}
               role="separator"
               aria-orientation={isHorizontalSplit ? "horizontal" : "vertical"}
-              onPointerDown={(event) => handlePointerDown(event, i - 1)}
+              onPointerDown={viewOnly ? undefined : (event) => handlePointerDown(event, i - 1)}
             />
           )}
           <div
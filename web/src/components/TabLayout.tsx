@@ -1,6 +1,7 @@
 import { useCallback, useEffect, useState } from "react";
 import type { ApiLayoutNode, ApiProject } from "../api/types";
 import { postAction } from "../api/client";
+import { useApp } from "../state/store";
 import { LayoutRenderer } from "./TerminalArea";
 
 function firstTerminalId(node: ApiLayoutNode): string | null {
@@ -23,6 +24,7 @@ export function TabLayout({
   project: ApiProject;
   path: number[];
 }) {
+  const { viewOnly } = useApp();
   const initialIdx = Math.min(initialActive, Math.max(children.length - 1, 0));
   const [activeTerminalId, setActiveTerminalId] = useState(() =>
     children[initialIdx] ? firstTerminalId(children[initialIdx]) : null,
@@ -75,14 +77,16 @@ export function TabLayout({
             </button>
           );
         })}
-        <button
-          onClick={addTab}
-          className="icon-button ml-auto h-[30px] w-[30px]"
-          title="New tab"
-          aria-label="New tab"
-        >
-          +
-        </button>
+        {!viewOnly && (
+          <button
+            onClick={addTab}
+            className="icon-button ml-auto h-[30px] w-[30px]"
+            title="New tab"
+            aria-label="New tab"
+          >
+            +
+          </button>
+        )}
       </div>
 
       <div className="flex-1 min-h-0">
@@ -36,7 +36,7 @@ export function TerminalPane({
   const containerRef = useRef<HTMLDivElement>(null);
   const termRef = useRef<Terminal | null>(null);
   const fitRef = useRef<FitAddon | null>(null);
-  const { ws, registry, state } = useApp();
+  const { ws, registry, state, viewOnly } = useApp();
   const resizeTimer = useRef<ReturnType<typeof setTimeout>>(null);
   // Incremented when a new xterm instance is created, so the registration
   // effect re-runs even if streamId hasn't changed (e.g. after split remount).
@@ -64,20 +64,27 @@ export function TerminalPane({
     return () => registry.unregister(streamId);
   }, [streamId, registry, termReady]);
 
+  // Spectators follow the owner's grid instead of fitting their own.
+  const serverSize = terminalId ? state.terminalSizes[terminalId] : undefined;
+  useEffect(() => {
+    if (!viewOnly || !serverSize || !termRef.current) return;
+    termRef.current.resize(serverSize[0], serverSize[1]);
+  }, [viewOnly, serverSize, termReady]);
+
   // Send resize when terminal dimensions change
   const sendResize = useCallback(() => {
-    if (!terminalId || !termRef.current) return;
+    if (viewOnly || !terminalId || !termRef.current) return;
     const { cols, rows } = termRef.current;
     if (cols > 1 && rows > 1) {
       ws.resize(terminalId, cols, rows);
     }
-  }, [terminalId, ws]);
+  }, [terminalId, ws, viewOnly]);
 
   // Actions
   const handleFocus = useCallback(() => {
-    if (!terminalId) return;
+    if (viewOnly || !terminalId) return;
     postAction({ action: "record_project_activity", project_id: projectId }).catch(() => {});
-  }, [terminalId, projectId]);
+  }, [viewOnly, terminalId, projectId]);
 
   const handleSplit = useCallback((direction: "horizontal" | "vertical") => {
     postAction({ action: "split_terminal", project_id: projectId, path, direction }).catch(() => {});
@@ -131,7 +138,8 @@ export function TerminalPane({
       },
       allowProposedApi: true,
       scrollback: 5000,
-      cursorBlink: true,
+      cursorBlink: !viewOnly,
+      disableStdin: viewOnly,
     });
 
     const fit = new FitAddon();
@@ -151,15 +159,17 @@ export function TerminalPane({
     }
 
     const container = containerRef.current;
-    safeFit(fit, container);
-    sendResize();
+    if (!viewOnly) {
+      safeFit(fit, container);
+      sendResize();
+    }
 
     termRef.current = term;
     fitRef.current = fit;
     setTermReady((r) => r + 1);
 
     // Forward user input to server (prefer binary frames when streamId is available)
-    if (terminalId) {
+    if (terminalId && !viewOnly) {
       term.onData((data) => {
         const sid = streamMappingsRef.current[terminalId];
         if (sid != null) {
@@ -172,6 +182,7 @@ export function TerminalPane({
 
     // ResizeObserver for fit
     const observer = new ResizeObserver(() => {
+      if (viewOnly) return;
       if (resizeTimer.current) clearTimeout(resizeTimer.current);
       resizeTimer.current = setTimeout(() => {
         if (safeFit(fit, container)) {
@@ -188,7 +199,7 @@ export function TerminalPane({
       termRef.current = null;
       fitRef.current = null;
     };
-  }, [terminalId, ws, sendResize]);
+  }, [terminalId, ws, sendResize, viewOnly]);
 
   if (!terminalId) {
     return (
@@ -204,46 +215,51 @@ export function TerminalPane({
         <span className="min-w-0 flex-1 truncate text-[11px] text-[var(--ok-text-secondary)]">
           {name ?? "Terminal"}
         </span>
-        <div className="flex items-center gap-0.5 ml-2">
-          {!hideSplitActions && (
-            <>
-              <button
-                onClick={(e) => { e.stopPropagation(); handleSplit("horizontal"); }}
-                className="icon-button"
-                title="Split horizontal"
-                aria-label="Split horizontal"
-              >
-                &#x2500;
-              </button>
-              <button
-                onClick={(e) => { e.stopPropagation(); handleSplit("vertical"); }}
-                className="icon-button"
-                title="Split vertical"
-                aria-label="Split vertical"
-              >
-                &#x2502;
-              </button>
-            </>
-          )}
-          <button
-            onClick={(e) => { e.stopPropagation(); handleRename(); }}
-            className="icon-button"
-            title="Rename terminal"
-            aria-label="Rename terminal"
-          >
-            R
-          </button>
-          <button
-            onClick={(e) => { e.stopPropagation(); handleClose(); }}
-            className="icon-button icon-button-danger"
-            title="Close terminal"
-            aria-label="Close terminal"
-          >
-            &#x2715;
-          </button>
-        </div>
+        {!viewOnly && (
+          <div className="flex items-center gap-0.5 ml-2">
+            {!hideSplitActions && (
+              <>
+                <button
+                  onClick={(e) => { e.stopPropagation(); handleSplit("horizontal"); }}
+                  className="icon-button"
+                  title="Split horizontal"
+                  aria-label="Split horizontal"
+                >
+                  &#x2500;
+                </button>
+                <button
+                  onClick={(e) => { e.stopPropagation(); handleSplit("vertical"); }}
+                  className="icon-button"
+                  title="Split vertical"
+                  aria-label="Split vertical"
+                >
+                  &#x2502;
+                </button>
+              </>
+            )}
+            <button
+              onClick={(e) => { e.stopPropagation(); handleRename(); }}
+              className="icon-button"
+              title="Rename terminal"
+              aria-label="Rename terminal"
+            >
+              R
+            </button>
+            <button
+              onClick={(e) => { e.stopPropagation(); handleClose(); }}
+              className="icon-button icon-button-danger"
+              title="Close terminal"
+              aria-label="Close terminal"
+            >
+              &#x2715;
+            </button>
+          </div>
+        )}
       </div>
-      <div ref={containerRef} className="flex-1 min-h-0 overflow-hidden" />
+      <div
+        ref={containerRef}
+        className={`flex-1 min-h-0 ${viewOnly ? "overflow-auto" : "overflow-hidden"}`}
+      />
     </div>
   );
 }
@@ -55,6 +55,8 @@ export interface AppState {
   wsStatus: WsStatus;
   /** terminalId → streamId mapping from WS subscribe */
   streamMappings: Record<string, number>;
+  /** terminalId → [cols, rows] as the server last reported it */
+  terminalSizes: Record<string, [number, number]>;
 }
 
 export type AppAction =
@@ -64,7 +66,8 @@ export type AppAction =
   | { type: "set_sidebar_open"; open: boolean }
   | { type: "set_ws_status"; status: WsStatus }
   | { type: "set_stream_mappings"; mappings: Record<string, number> }
-  | { type: "clear_stream_mappings" };
+  | { type: "clear_stream_mappings" }
+  | { type: "set_terminal_sizes"; sizes: Record<string, [number, number]> };
 
 export function appReducer(state: AppState, action: AppAction): AppState {
   switch (action.type) {
@@ -82,6 +85,8 @@ export function appReducer(state: AppState, action: AppAction): AppState {
       return { ...state, streamMappings: { ...state.streamMappings, ...action.mappings } };
     case "clear_stream_mappings":
       return { ...state, streamMappings: {} };
+    case "set_terminal_sizes":
+      return { ...state, terminalSizes: { ...state.terminalSizes, ...action.sizes } };
   }
 }
 
@@ -92,6 +97,7 @@ export const initialState: AppState = {
   sidebarOpen: false,
   wsStatus: "disconnected",
   streamMappings: {},
+  terminalSizes: {},
 };
 
 // ── Context ─────────────────────────────────────────────────────────────────
@@ -103,6 +109,8 @@ export interface AppContextValue {
   registry: TerminalRegistry;
   /** Handle a WS JSON message (called from App after dispatch) */
   handleWsMessage: (msg: WsOutbound) => void;
+  /** Opened through a share link: no input, resizing or layout edits. */
+  viewOnly: boolean;
 }
 
 export const AppContext = createContext<AppContextValue>(null!);